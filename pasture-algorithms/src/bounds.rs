@@ -5,8 +5,9 @@ use pasture_core::{
     nalgebra::{Point3, Vector3},
 };
 
-/// Calculate the bounding box of the points in the given `buffer`. Returns `None` if the buffer contains zero
-/// points, or if the `PointLayout` of the buffer does not contain the `POSITION_3D` attribute
+/// Calculate the bounding box of the points in the given `buffer`. Positions that contain NaN or infinite coordinates
+/// are ignored. Returns `None` if the buffer contains no points with valid positions, or if the `PointLayout` of the
+/// buffer does not contain the `POSITION_3D` attribute
 pub fn calculate_bounds<T: PointBuffer>(buffer: &T) -> Option<AABB<f64>> {
     if buffer.len() == 0 {
         return None;
@@ -20,16 +21,21 @@ pub fn calculate_bounds<T: PointBuffer>(buffer: &T) -> Option<AABB<f64>> {
     };
 
     if position_attribute.datatype() == POSITION_3D.datatype() {
-        Some(calculate_bounds_from_default_positions(buffer))
+        calculate_bounds_from_default_positions(buffer)
     } else {
-        Some(calculate_bounds_from_custom_positions(buffer))
+        calculate_bounds_from_custom_positions(buffer)
     }
 }
 
-fn calculate_bounds_from_default_positions<T: PointBuffer>(buffer: &T) -> AABB<f64> {
+fn calculate_bounds_from_default_positions<T: PointBuffer>(buffer: &T) -> Option<AABB<f64>> {
     let mut pos_min = Point3::new(f64::MAX, f64::MAX, f64::MAX);
     let mut pos_max = Point3::new(f64::MIN, f64::MIN, f64::MIN);
+    let mut has_valid_position = false;
     for pos in buffer.iter_attribute::<Vector3<f64>>(&POSITION_3D) {
+        if !is_valid_position(&pos) {
+            continue;
+        }
+        has_valid_position = true;
         if pos.x < pos_min.x {
             pos_min.x = pos.x;
         }
@@ -49,13 +55,22 @@ fn calculate_bounds_from_default_positions<T: PointBuffer>(buffer: &T) -> AABB<f
             pos_max.z = pos.z;
         }
     }
-    AABB::from_min_max(pos_min, pos_max)
+    if has_valid_position {
+        Some(AABB::from_min_max(pos_min, pos_max))
+    } else {
+        None
+    }
 }
 
-fn calculate_bounds_from_custom_positions<T: PointBuffer>(buffer: &T) -> AABB<f64> {
+fn calculate_bounds_from_custom_positions<T: PointBuffer>(buffer: &T) -> Option<AABB<f64>> {
     let mut pos_min = Point3::new(f64::MAX, f64::MAX, f64::MAX);
     let mut pos_max = Point3::new(f64::MIN, f64::MIN, f64::MIN);
+    let mut has_valid_position = false;
     for pos in buffer.iter_attribute_as::<Vector3<f64>>(&POSITION_3D) {
+        if !is_valid_position(&pos) {
+            continue;
+        }
+        has_valid_position = true;
         if pos.x < pos_min.x {
             pos_min.x = pos.x;
         }
@@ -75,5 +90,48 @@ fn calculate_bounds_from_custom_positions<T: PointBuffer>(buffer: &T) -> AABB<f6
             pos_max.z = pos.z;
         }
     }
-    AABB::from_min_max(pos_min, pos_max)
+    if has_valid_position {
+        Some(AABB::from_min_max(pos_min, pos_max))
+    } else {
+        None
+    }
+}
+
+fn is_valid_position(position: &Vector3<f64>) -> bool {
+    position.iter().all(|component| component.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::{PerAttributeVecPointStorage, PointBufferWriteable},
+        layout::{attributes, PointLayout},
+    };
+
+    fn buffer_with_positions(positions: &[Vector3<f64>]) -> PerAttributeVecPointStorage {
+        let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+        ]));
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&attributes::POSITION_3D, positions);
+        pusher.done();
+        buffer
+    }
+
+    #[test]
+    fn test_calculate_bounds_ignores_invalid_positions() {
+        let buffer = buffer_with_positions(&[
+            Vector3::new(0.0, 1.0, 2.0),
+            Vector3::new(f64::NAN, 10.0, 10.0),
+            Vector3::new(-1.0, f64::INFINITY, 0.0),
+            Vector3::new(3.0, 2.0, 1.0),
+        ]);
+        let bounds = calculate_bounds(&buffer).expect("Bounds must exist");
+        assert_eq!(Point3::new(0.0, 1.0, 1.0), *bounds.min());
+        assert_eq!(Point3::new(3.0, 2.0, 2.0), *bounds.max());
+
+        let only_invalid = buffer_with_positions(&[Vector3::new(f64::NAN, 0.0, 0.0)]);
+        assert_eq!(None, calculate_bounds(&only_invalid));
+    }
 }
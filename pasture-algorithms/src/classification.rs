@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use pasture_core::{
+    containers::{PointBuffer, PointBufferWriteable, PointBufferWriteableExt},
+    layout::attributes::CLASSIFICATION,
+};
+
+use crate::returns::attribute_values;
+
+/// Rewrites the `CLASSIFICATION` of all points in `buffer` in place using the given `map`, which maps the classification
+/// codes of `buffer` to new classification codes. This is useful to translate vendor-specific classification schemes into
+/// the ASPRS standard classes. Classifications that are not contained in `map` are left unchanged, use
+/// [remap_classification_strict] if every classification has to be mapped.
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::classification::remap_classification;
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::CLASSIFICATION]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::CLASSIFICATION, &[20_u8, 2, 20]);
+/// pusher.done();
+///
+/// // The vendor uses class 20 for buildings, which is class 6 in the ASPRS standard
+/// let map = vec![(20, 6)].into_iter().collect::<HashMap<_, _>>();
+/// remap_classification(&mut buffer, &map);
+/// assert_eq!(vec![6, 2, 6], buffer.iter_attribute::<u8>(&attributes::CLASSIFICATION).collect::<Vec<_>>());
+/// ```
+///
+/// # Panics
+///
+/// If `buffer` does not contain a `CLASSIFICATION` attribute
+pub fn remap_classification<T: PointBuffer + PointBufferWriteable>(
+    buffer: &mut T,
+    map: &HashMap<u8, u8>,
+) {
+    assert!(
+        buffer
+            .point_layout()
+            .has_attribute_with_name(CLASSIFICATION.name()),
+        "remap_classification requires the CLASSIFICATION attribute"
+    );
+    buffer.transform_attribute(CLASSIFICATION.name(), |_, classification: &mut u8| {
+        if let Some(new_classification) = map.get(classification) {
+            *classification = *new_classification;
+        }
+    });
+}
+
+/// Like [remap_classification], but fails if `buffer` contains a classification that is not part of `map`
+///
+/// # Errors
+///
+/// If any point in `buffer` has a classification that is not contained in `map`. In this case, `buffer` is not modified
+///
+/// # Panics
+///
+/// If `buffer` does not contain a `CLASSIFICATION` attribute
+pub fn remap_classification_strict<T: PointBuffer + PointBufferWriteable>(
+    buffer: &mut T,
+    map: &HashMap<u8, u8>,
+) -> Result<()> {
+    let classifications = attribute_values::<u8, T>(buffer, &CLASSIFICATION);
+    if let Some((index, classification)) = classifications
+        .iter()
+        .enumerate()
+        .find(|(_, classification)| !map.contains_key(classification))
+    {
+        bail!(
+            "Classification {} of point {} is not contained in the classification map",
+            classification,
+            index
+        );
+    }
+
+    remap_classification(buffer, map);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::{PerAttributeVecPointStorage, PointBufferExt},
+        layout::{PointAttributeDataType, PointLayout},
+    };
+
+    fn make_buffer(classifications: &[u8]) -> PerAttributeVecPointStorage {
+        let mut buffer =
+            PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[CLASSIFICATION]));
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&CLASSIFICATION, classifications);
+        pusher.done();
+        buffer
+    }
+
+    fn classifications(buffer: &PerAttributeVecPointStorage) -> Vec<u8> {
+        buffer.iter_attribute::<u8>(&CLASSIFICATION).collect()
+    }
+
+    #[test]
+    fn test_remap_classification_partial() {
+        let mut buffer = make_buffer(&[20, 2, 21, 7, 20, 1]);
+        let map = vec![(20, 6), (21, 9), (2, 2)]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        remap_classification(&mut buffer, &map);
+        assert_eq!(vec![6, 2, 9, 7, 6, 1], classifications(&buffer));
+    }
+
+    #[test]
+    fn test_remap_classification_strict() {
+        let map = vec![(20, 6), (2, 2)].into_iter().collect::<HashMap<_, _>>();
+
+        let mut buffer = make_buffer(&[20, 2, 20]);
+        remap_classification_strict(&mut buffer, &map).unwrap();
+        assert_eq!(vec![6, 2, 6], classifications(&buffer));
+
+        let mut buffer = make_buffer(&[20, 2, 7]);
+        assert!(remap_classification_strict(&mut buffer, &map).is_err());
+        assert_eq!(
+            vec![20, 2, 7],
+            classifications(&buffer),
+            "A failed strict remap must not modify the buffer"
+        );
+    }
+
+    #[test]
+    fn test_remap_classification_with_custom_datatype() {
+        let classification_u16 = CLASSIFICATION.with_custom_datatype(PointAttributeDataType::U16);
+        let mut buffer =
+            PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[classification_u16]));
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&classification_u16, &[20_u16, 3]);
+        pusher.done();
+
+        let map = vec![(20, 6)].into_iter().collect::<HashMap<_, _>>();
+        remap_classification(&mut buffer, &map);
+        assert_eq!(
+            vec![6, 3],
+            buffer
+                .iter_attribute::<u16>(&classification_u16)
+                .collect::<Vec<_>>()
+        );
+    }
+}
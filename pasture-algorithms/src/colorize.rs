@@ -0,0 +1,304 @@
+use pasture_core::{
+    containers::{PointBuffer, PointBufferExt, PointBufferWriteable, PointBufferWriteableExt},
+    layout::{
+        attributes::{COLOR_RGB, POSITION_3D},
+        PointAttributeDataType, PointAttributeDefinition, PrimitiveType,
+    },
+    nalgebra::Vector3,
+};
+
+/// Control points of the viridis color ramp (as used by matplotlib), evenly spaced in `[0; 1]`
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+/// Palette of visually distinct colors for categorical data (the 'Tableau 10' palette)
+const CATEGORICAL: [[u8; 3]; 10] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+    [227, 119, 194],
+    [127, 127, 127],
+    [188, 189, 34],
+    [23, 190, 207],
+];
+
+/// Color ramps that can be used with [colorize_by_attribute]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorRamp {
+    /// Linear ramp from black to white
+    Grayscale,
+    /// The perceptually uniform viridis ramp, going from dark purple to yellow
+    Viridis,
+    /// A palette of distinct colors for categorical attributes such as `CLASSIFICATION`. Values are not normalized but
+    /// rounded to the nearest integer, which selects one of the colors in the palette. If there are more categories than
+    /// colors in the palette, the colors repeat
+    Categorical,
+}
+
+impl ColorRamp {
+    /// Returns the 8-bit RGB color of this ramp for the given value. For `Grayscale` and `Viridis`, `value` is clamped
+    /// to `[0; 1]`, for `Categorical`, `value` is the category
+    pub fn color_at(&self, value: f64) -> Vector3<u8> {
+        match self {
+            ColorRamp::Grayscale => {
+                let gray = (value.max(0.0).min(1.0) * 255.0).round() as u8;
+                Vector3::new(gray, gray, gray)
+            }
+            ColorRamp::Viridis => {
+                let position = value.max(0.0).min(1.0) * (VIRIDIS.len() - 1) as f64;
+                let lower = (position.floor() as usize).min(VIRIDIS.len() - 2);
+                let fraction = position - lower as f64;
+                let interpolate = |component: usize| {
+                    let start = VIRIDIS[lower][component] as f64;
+                    let end = VIRIDIS[lower + 1][component] as f64;
+                    (start + fraction * (end - start)).round() as u8
+                };
+                Vector3::new(interpolate(0), interpolate(1), interpolate(2))
+            }
+            ColorRamp::Categorical => {
+                let index = (value.round() as i64).rem_euclid(CATEGORICAL.len() as i64) as usize;
+                let [r, g, b] = CATEGORICAL[index];
+                Vector3::new(r, g, b)
+            }
+        }
+    }
+}
+
+/// Maps the values of the given scalar `attribute` to colors using the given `ramp` and writes them into the `COLOR_RGB`
+/// attribute of `buffer`, overwriting any existing colors. For the continuous ramps, the attribute values are normalized
+/// to `[0; 1]` using the minimum and maximum value within `buffer`, so the minimum value maps to the start of the ramp and
+/// the maximum value to the end of the ramp. If all values are equal, all points get the color at the start of the ramp.
+/// `COLOR_RGB` can be stored either with its default datatype `Vec3u16` or as `Vec3u8`.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_core::nalgebra::Vector3;
+/// # use pasture_algorithms::colorize::*;
+/// let layout = PointLayout::from_attributes(&[attributes::INTENSITY, attributes::COLOR_RGB]);
+/// let mut buffer = PerAttributeVecPointStorage::new(layout);
+/// buffer.resize(3);
+/// buffer.set_attribute(&attributes::INTENSITY, 1, 50_u16);
+/// buffer.set_attribute(&attributes::INTENSITY, 2, 100_u16);
+///
+/// colorize_by_attribute::<u16, _>(&mut buffer, &attributes::INTENSITY, ColorRamp::Grayscale);
+/// let colors = buffer.iter_attribute::<Vector3<u16>>(&attributes::COLOR_RGB).collect::<Vec<_>>();
+/// assert_eq!(Vector3::new(0, 0, 0), colors[0]);
+/// assert_eq!(Vector3::new(65535, 65535, 65535), colors[2]);
+/// ```
+///
+/// # Panics
+///
+/// If the `PointLayout` of `buffer` does not contain `attribute`, or if it does not contain a `COLOR_RGB` attribute with
+/// datatype `Vec3u16` or `Vec3u8`. To colorize points that have no colors, copy them into a buffer whose `PointLayout`
+/// includes `COLOR_RGB` first.
+pub fn colorize_by_attribute<
+    T: PrimitiveType + Into<f64>,
+    B: PointBuffer + PointBufferWriteable,
+>(
+    buffer: &mut B,
+    attribute: &PointAttributeDefinition,
+    ramp: ColorRamp,
+) {
+    let values: Vec<f64> = if buffer.point_layout().has_attribute(attribute) {
+        buffer
+            .iter_attribute::<T>(attribute)
+            .map(|value| value.into())
+            .collect()
+    } else {
+        buffer
+            .iter_attribute_as::<T>(attribute)
+            .map(|value| value.into())
+            .collect()
+    };
+    write_colors(buffer, &values, ramp);
+}
+
+/// Like [colorize_by_attribute], but uses the elevation (i.e. the z-coordinate of the `POSITION_3D` attribute) of each
+/// point as the value that is mapped to a color
+///
+/// # Panics
+///
+/// If the `PointLayout` of `buffer` does not contain a `POSITION_3D` attribute, or if it does not contain a `COLOR_RGB`
+/// attribute with datatype `Vec3u16` or `Vec3u8`
+pub fn colorize_by_elevation<B: PointBuffer + PointBufferWriteable>(
+    buffer: &mut B,
+    ramp: ColorRamp,
+) {
+    let elevations: Vec<f64> = buffer
+        .iter_attribute_as::<Vector3<f64>>(&POSITION_3D)
+        .map(|position| position.z)
+        .collect();
+    write_colors(buffer, &elevations, ramp);
+}
+
+fn write_colors<B: PointBuffer + PointBufferWriteable>(
+    buffer: &mut B,
+    values: &[f64],
+    ramp: ColorRamp,
+) {
+    let color_datatype = buffer
+        .point_layout()
+        .get_attribute_by_name(COLOR_RGB.name())
+        .map(|attribute| attribute.datatype())
+        .unwrap_or_else(|| {
+            panic!(
+                "Attribute {} not contained in PointLayout of buffer ({})",
+                COLOR_RGB,
+                buffer.point_layout()
+            )
+        });
+
+    let (min, max) = values
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), value| {
+            (min.min(*value), max.max(*value))
+        });
+    let normalize = |value: f64| match ramp {
+        ColorRamp::Categorical => value,
+        _ if max > min => (value - min) / (max - min),
+        _ => 0.0,
+    };
+
+    for (index, value) in values.iter().enumerate() {
+        let color = ramp.color_at(normalize(*value));
+        match color_datatype {
+            PointAttributeDataType::Vec3u16 => {
+                // Scale by 257 so that 255 maps to 65535
+                let color: Vector3<u16> = color.map(|component| component as u16 * 257);
+                buffer.set_attribute(&COLOR_RGB, index, color);
+            }
+            PointAttributeDataType::Vec3u8 => {
+                buffer.set_attribute(
+                    &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+                    index,
+                    color,
+                );
+            }
+            other => panic!(
+                "Unsupported datatype {} of attribute {}, expected Vec3<u16> or Vec3<u8>",
+                other, COLOR_RGB
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::{InterleavedVecPointStorage, PerAttributeVecPointStorage},
+        layout::{attributes, PointLayout},
+    };
+
+    fn buffer_with_values(values: &[f32]) -> PerAttributeVecPointStorage {
+        let attribute = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::F32);
+        let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            attribute.clone(),
+            attributes::COLOR_RGB,
+        ]));
+        buffer.resize(values.len());
+        for (index, value) in values.iter().enumerate() {
+            buffer.set_attribute(&attribute, index, *value);
+        }
+        buffer
+    }
+
+    fn colors_of<B: PointBuffer>(buffer: &B) -> Vec<Vector3<u16>> {
+        buffer
+            .iter_attribute::<Vector3<u16>>(&attributes::COLOR_RGB)
+            .collect()
+    }
+
+    fn to_u16(color: Vector3<u8>) -> Vector3<u16> {
+        color.map(|component| component as u16 * 257)
+    }
+
+    #[test]
+    fn test_colorize_min_and_max_map_to_ramp_ends() {
+        let attribute = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::F32);
+        for ramp in [ColorRamp::Grayscale, ColorRamp::Viridis].iter() {
+            let mut buffer = buffer_with_values(&[-2.0, 10.0, 4.0]);
+            colorize_by_attribute::<f32, _>(&mut buffer, &attribute, *ramp);
+
+            let colors = colors_of(&buffer);
+            assert_eq!(to_u16(ramp.color_at(0.0)), colors[0]);
+            assert_eq!(to_u16(ramp.color_at(1.0)), colors[1]);
+            assert_eq!(to_u16(ramp.color_at(0.5)), colors[2]);
+        }
+
+        assert_eq!(Vector3::new(68, 1, 84), ColorRamp::Viridis.color_at(0.0));
+        assert_eq!(Vector3::new(253, 231, 37), ColorRamp::Viridis.color_at(1.0));
+        assert_eq!(Vector3::new(0, 0, 0), ColorRamp::Grayscale.color_at(0.0));
+        assert_eq!(
+            Vector3::new(255, 255, 255),
+            ColorRamp::Grayscale.color_at(1.0)
+        );
+    }
+
+    #[test]
+    fn test_colorize_constant_values() {
+        let attribute = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::F32);
+        let mut buffer = buffer_with_values(&[3.0, 3.0]);
+        colorize_by_attribute::<f32, _>(&mut buffer, &attribute, ColorRamp::Viridis);
+        let start_color = to_u16(ColorRamp::Viridis.color_at(0.0));
+        assert_eq!(vec![start_color, start_color], colors_of(&buffer));
+    }
+
+    #[test]
+    fn test_colorize_classification_categorical() {
+        let layout = PointLayout::from_attributes(&[
+            attributes::CLASSIFICATION,
+            attributes::COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+        ]);
+        let mut buffer = InterleavedVecPointStorage::new(layout);
+        buffer.resize(3);
+        buffer.set_attribute(&attributes::CLASSIFICATION, 0, 2_u8);
+        buffer.set_attribute(&attributes::CLASSIFICATION, 1, 6_u8);
+        buffer.set_attribute(&attributes::CLASSIFICATION, 2, 2_u8);
+
+        colorize_by_attribute::<u8, _>(
+            &mut buffer,
+            &attributes::CLASSIFICATION,
+            ColorRamp::Categorical,
+        );
+
+        let colors = buffer
+            .iter_attribute::<Vector3<u8>>(
+                &attributes::COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(ColorRamp::Categorical.color_at(2.0), colors[0]);
+        assert_eq!(ColorRamp::Categorical.color_at(6.0), colors[1]);
+        assert_eq!(colors[0], colors[2]);
+        assert_ne!(colors[0], colors[1]);
+    }
+
+    #[test]
+    fn test_colorize_by_elevation() {
+        let layout =
+            PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::COLOR_RGB]);
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        buffer.resize(2);
+        buffer.set_attribute(&attributes::POSITION_3D, 0, Vector3::new(5.0, 0.0, -1.0));
+        buffer.set_attribute(&attributes::POSITION_3D, 1, Vector3::new(0.0, 5.0, 1.0));
+
+        colorize_by_elevation(&mut buffer, ColorRamp::Grayscale);
+        assert_eq!(
+            vec![Vector3::new(0, 0, 0), Vector3::new(65535, 65535, 65535)],
+            colors_of(&buffer)
+        );
+    }
+}
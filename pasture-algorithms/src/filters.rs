@@ -0,0 +1,474 @@
+use anyhow::{bail, Result};
+use pasture_core::{
+    containers::{PerAttributeVecPointStorage, PointBuffer, PointBufferWriteable},
+    layout::{
+        attributes::{CLASSIFICATION_FLAGS, NUMBER_OF_RETURNS, POSITION_3D, RETURN_NUMBER},
+        classification_flags::CLASSIFICATION_FLAG_OVERLAP,
+        PointAttributeDefinition,
+    },
+    math::AABB,
+    nalgebra::{Point3, Vector3},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::returns::attribute_values;
+
+/// How `sample` selects the points that it keeps
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SampleStrategy {
+    /// Keeps evenly spaced points, i.e. every k-th point for a stride of `k = len / target_count`. This is
+    /// deterministic, but might produce patterns if the order of the points correlates with their position
+    Systematic,
+    /// Keeps uniformly distributed random points, selected through reservoir sampling. The same seed always selects
+    /// the same points
+    Random(u64),
+}
+
+/// Returns a new buffer with the same `PointLayout` as `buffer` that contains only the first returns of each laser pulse,
+/// i.e. all points whose `RETURN_NUMBER` is 1. First returns usually hit the top of the canopy or buildings.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::filters::keep_first_returns;
+/// let layout = PointLayout::from_attributes(&[attributes::RETURN_NUMBER, attributes::NUMBER_OF_RETURNS]);
+/// let mut buffer = PerAttributeVecPointStorage::new(layout);
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::RETURN_NUMBER, &[1_u8, 2, 1]);
+/// pusher.push_attribute_range(&attributes::NUMBER_OF_RETURNS, &[2_u8, 2, 1]);
+/// pusher.done();
+///
+/// let first_returns = keep_first_returns(&buffer).unwrap();
+/// assert_eq!(2, first_returns.len());
+/// ```
+///
+/// # Errors
+///
+/// If `buffer` does not contain the `RETURN_NUMBER` attribute
+pub fn keep_first_returns<T: PointBuffer>(buffer: &T) -> Result<PerAttributeVecPointStorage> {
+    ensure_has_attribute(buffer, &RETURN_NUMBER, "keep_first_returns")?;
+    let return_numbers = attribute_values::<u8, T>(buffer, &RETURN_NUMBER);
+    Ok(filter_points(buffer, |index| return_numbers[index] == 1))
+}
+
+/// Returns a new buffer with the same `PointLayout` as `buffer` that contains only the last returns of each laser pulse,
+/// i.e. all points whose `RETURN_NUMBER` equals their `NUMBER_OF_RETURNS`. Last returns usually hit the ground. Points of
+/// pulses with a single return are both first and last returns.
+///
+/// # Errors
+///
+/// If `buffer` does not contain the `RETURN_NUMBER` or `NUMBER_OF_RETURNS` attributes
+pub fn keep_last_returns<T: PointBuffer>(buffer: &T) -> Result<PerAttributeVecPointStorage> {
+    ensure_has_attribute(buffer, &RETURN_NUMBER, "keep_last_returns")?;
+    ensure_has_attribute(buffer, &NUMBER_OF_RETURNS, "keep_last_returns")?;
+    let return_numbers = attribute_values::<u8, T>(buffer, &RETURN_NUMBER);
+    let numbers_of_returns = attribute_values::<u8, T>(buffer, &NUMBER_OF_RETURNS);
+    Ok(filter_points(buffer, |index| {
+        return_numbers[index] == numbers_of_returns[index]
+    }))
+}
+
+/// Returns a new buffer with the same `PointLayout` as `buffer` that contains only the points that are not within the
+/// overlap region of two or more swaths, i.e. all points whose `CLASSIFICATION_FLAGS` don't have the overlap bit set. The
+/// extended LAS point formats (6 to 10) store overlap as such a flag, independent of the `CLASSIFICATION` of the point,
+/// which is kept unaltered. Removing the overlap points avoids duplicate points at the boundaries of flight strips or
+/// tiles.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::filters::exclude_overlap;
+/// let layout = PointLayout::from_attributes(&[attributes::CLASSIFICATION, attributes::CLASSIFICATION_FLAGS]);
+/// let mut buffer = PerAttributeVecPointStorage::new(layout);
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::CLASSIFICATION, &[2_u8, 2, 6]);
+/// pusher.push_attribute_range(&attributes::CLASSIFICATION_FLAGS, &[0_u8, 0b1000, 0]);
+/// pusher.done();
+///
+/// let without_overlap = exclude_overlap(&buffer).unwrap();
+/// assert_eq!(2, without_overlap.len());
+/// ```
+///
+/// # Errors
+///
+/// If `buffer` does not contain the `CLASSIFICATION_FLAGS` attribute
+pub fn exclude_overlap<T: PointBuffer>(buffer: &T) -> Result<PerAttributeVecPointStorage> {
+    ensure_has_attribute(buffer, &CLASSIFICATION_FLAGS, "exclude_overlap")?;
+    let classification_flags = attribute_values::<u8, T>(buffer, &CLASSIFICATION_FLAGS);
+    Ok(filter_points(buffer, |index| {
+        classification_flags[index] & CLASSIFICATION_FLAG_OVERLAP == 0
+    }))
+}
+
+/// Returns a new buffer with the same `PointLayout` as `buffer` that contains only the points whose `POSITION_3D` lies
+/// within `aabb`. Points right on the boundary of `aabb` are considered to be inside of it. If `invert` is `true`, the
+/// points within `aabb` are removed instead, i.e. only the points outside of `aabb` are kept. Positions are compared as
+/// `f64` values, regardless of the datatype that they are stored with in `buffer`.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_core::math::AABB;
+/// # use pasture_core::nalgebra::{Point3, Vector3};
+/// # use pasture_algorithms::filters::crop;
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::POSITION_3D]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(
+///     &attributes::POSITION_3D,
+///     &[Vector3::new(0.5, 0.5, 0.5), Vector3::new(2.0, 0.5, 0.5)],
+/// );
+/// pusher.done();
+///
+/// let aabb = AABB::from_min_max(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+/// assert_eq!(1, crop(&buffer, aabb, false).unwrap().len());
+/// ```
+///
+/// # Errors
+///
+/// If `buffer` does not contain the `POSITION_3D` attribute
+pub fn crop<T: PointBuffer>(
+    buffer: &T,
+    aabb: AABB<f64>,
+    invert: bool,
+) -> Result<PerAttributeVecPointStorage> {
+    ensure_has_attribute(buffer, &POSITION_3D, "crop")?;
+    let positions = attribute_values::<Vector3<f64>, T>(buffer, &POSITION_3D);
+    Ok(filter_points(buffer, |index| {
+        aabb.contains(&Point3::from(positions[index])) != invert
+    }))
+}
+
+/// Returns a new buffer with the same `PointLayout` as `buffer` that contains `target_count` of the points in `buffer`,
+/// selected using the given `strategy`. This is a quick way to reduce a point cloud to a fixed number of points, e.g. for
+/// previews, regardless of the spatial distribution of the points. The points keep their relative order. If
+/// `target_count` is at least the number of points in `buffer`, all points are kept.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::filters::*;
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::INTENSITY, &(0..10_u16).collect::<Vec<_>>());
+/// pusher.done();
+///
+/// let sampled = sample(&buffer, 5, SampleStrategy::Systematic);
+/// let intensities = sampled.iter_attribute::<u16>(&attributes::INTENSITY).collect::<Vec<_>>();
+/// assert_eq!(vec![0, 2, 4, 6, 8], intensities);
+/// ```
+pub fn sample<T: PointBuffer>(
+    buffer: &T,
+    target_count: usize,
+    strategy: SampleStrategy,
+) -> PerAttributeVecPointStorage {
+    let len = buffer.len();
+    if target_count >= len {
+        return copy_points(buffer, 0..len);
+    }
+
+    match strategy {
+        SampleStrategy::Systematic => {
+            // Use a fractional stride so that exactly `target_count` points are selected
+            let stride = len as f64 / target_count as f64;
+            copy_points(
+                buffer,
+                (0..target_count).map(|index| (index as f64 * stride) as usize),
+            )
+        }
+        SampleStrategy::Random(seed) => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut reservoir = (0..target_count).collect::<Vec<_>>();
+            for index in target_count..len {
+                let slot = rng.gen_range(0..=index);
+                if slot < target_count {
+                    reservoir[slot] = index;
+                }
+            }
+            reservoir.sort_unstable();
+            copy_points(buffer, reservoir.into_iter())
+        }
+    }
+}
+
+/// Copies all points from `buffer` for which `predicate` returns `true` into a new buffer with the same `PointLayout`
+fn filter_points<T: PointBuffer, F: Fn(usize) -> bool>(
+    buffer: &T,
+    predicate: F,
+) -> PerAttributeVecPointStorage {
+    copy_points(buffer, (0..buffer.len()).filter(|index| predicate(*index)))
+}
+
+/// Copies the points at the given `indices` from `buffer` into a new buffer with the same `PointLayout`
+fn copy_points<T: PointBuffer, I: Iterator<Item = usize>>(
+    buffer: &T,
+    indices: I,
+) -> PerAttributeVecPointStorage {
+    let kept_indices = indices.collect::<Vec<_>>();
+
+    let mut filtered_points = PerAttributeVecPointStorage::with_capacity(
+        kept_indices.len(),
+        buffer.point_layout().clone(),
+    );
+    filtered_points.resize(kept_indices.len());
+
+    let mut point_data = vec![0; buffer.point_layout().size_of_point_entry() as usize];
+    for (target_index, source_index) in kept_indices.into_iter().enumerate() {
+        buffer.get_raw_point(source_index, &mut point_data);
+        filtered_points.set_raw_point(target_index, &point_data);
+    }
+
+    filtered_points
+}
+
+fn ensure_has_attribute<T: PointBuffer>(
+    buffer: &T,
+    attribute: &PointAttributeDefinition,
+    filter_name: &str,
+) -> Result<()> {
+    if !buffer
+        .point_layout()
+        .has_attribute_with_name(attribute.name())
+    {
+        bail!(
+            "{} requires the attribute {}, but it is not contained in the PointLayout of the buffer ({})",
+            filter_name,
+            attribute,
+            buffer.point_layout()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use pasture_core::{
+        containers::{InterleavedVecPointStorage, PointBufferExt, PointBufferWriteableExt},
+        layout::{attributes, PointLayout},
+    };
+    use pasture_io::{base::PointReader, las::LASReader};
+
+    /// Creates a buffer with mixed single and multiple returns. The `INTENSITY` of each point is its index
+    fn mixed_returns() -> InterleavedVecPointStorage {
+        let returns: [(u8, u8); 7] = [(1, 1), (1, 3), (2, 3), (3, 3), (1, 2), (2, 2), (1, 1)];
+        let layout = PointLayout::from_attributes(&[
+            attributes::INTENSITY,
+            attributes::RETURN_NUMBER,
+            attributes::NUMBER_OF_RETURNS,
+        ]);
+        let mut buffer = InterleavedVecPointStorage::new(layout);
+        buffer.resize(returns.len());
+        for (index, (return_number, number_of_returns)) in returns.iter().enumerate() {
+            buffer.set_raw_attribute(index, &attributes::INTENSITY, &(index as u16).to_ne_bytes());
+            buffer.set_raw_attribute(index, &attributes::RETURN_NUMBER, &[*return_number]);
+            buffer.set_raw_attribute(index, &attributes::NUMBER_OF_RETURNS, &[*number_of_returns]);
+        }
+        buffer
+    }
+
+    fn intensities<T: PointBuffer>(buffer: &T) -> Vec<u16> {
+        buffer
+            .iter_attribute::<u16>(&attributes::INTENSITY)
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_first_returns() -> Result<()> {
+        let first_returns = keep_first_returns(&mixed_returns())?;
+        assert_eq!(4, first_returns.len());
+        assert_eq!(vec![0, 1, 4, 6], intensities(&first_returns));
+        assert!(first_returns
+            .iter_attribute::<u8>(&attributes::RETURN_NUMBER)
+            .all(|return_number| return_number == 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_last_returns() -> Result<()> {
+        let last_returns = keep_last_returns(&mixed_returns())?;
+        assert_eq!(4, last_returns.len());
+        assert_eq!(vec![0, 3, 5, 6], intensities(&last_returns));
+        Ok(())
+    }
+
+    #[test]
+    fn test_return_filters_without_return_attributes() {
+        let buffer =
+            InterleavedVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+        assert!(keep_first_returns(&buffer).is_err());
+        assert!(keep_last_returns(&buffer).is_err());
+
+        let layout = PointLayout::from_attributes(&[attributes::RETURN_NUMBER]);
+        let buffer = InterleavedVecPointStorage::new(layout);
+        assert!(keep_first_returns(&buffer).is_ok());
+        assert!(keep_last_returns(&buffer).is_err());
+    }
+
+    #[test]
+    fn test_exclude_overlap() -> Result<()> {
+        // Mix of overlap points and other flags, the `INTENSITY` of each point is its index
+        let points: [(u8, u8); 6] = [
+            (2, 0),
+            (2, CLASSIFICATION_FLAG_OVERLAP),
+            (6, 0b0001),
+            (6, 0b0001 | CLASSIFICATION_FLAG_OVERLAP),
+            (9, 0b0100),
+            (40, CLASSIFICATION_FLAG_OVERLAP),
+        ];
+        let layout = PointLayout::from_attributes(&[
+            attributes::INTENSITY,
+            attributes::CLASSIFICATION,
+            attributes::CLASSIFICATION_FLAGS,
+        ]);
+        let mut buffer = InterleavedVecPointStorage::new(layout);
+        buffer.resize(points.len());
+        for (index, (classification, flags)) in points.iter().enumerate() {
+            buffer.set_raw_attribute(index, &attributes::INTENSITY, &(index as u16).to_ne_bytes());
+            buffer.set_raw_attribute(index, &attributes::CLASSIFICATION, &[*classification]);
+            buffer.set_raw_attribute(index, &attributes::CLASSIFICATION_FLAGS, &[*flags]);
+        }
+
+        let without_overlap = exclude_overlap(&buffer)?;
+        assert_eq!(buffer.point_layout(), without_overlap.point_layout());
+        assert_eq!(vec![0, 2, 4], intensities(&without_overlap));
+        assert_eq!(
+            vec![2, 6, 9],
+            without_overlap
+                .iter_attribute::<u8>(&attributes::CLASSIFICATION)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 0b0001, 0b0100],
+            without_overlap
+                .iter_attribute::<u8>(&attributes::CLASSIFICATION_FLAGS)
+                .collect::<Vec<_>>()
+        );
+
+        let no_flags =
+            InterleavedVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+        assert!(exclude_overlap(&no_flags).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclude_overlap_from_las_file() -> Result<()> {
+        // The classification flags of the points in the test file are 0 to 9, so the last two points are overlap points
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../pasture-io/resources/test/10_points_format_6.las");
+        let mut reader = LASReader::from_path(path)?;
+        let mut points =
+            PerAttributeVecPointStorage::new(reader.get_default_point_layout().clone());
+        reader.read_into(&mut points, 10)?;
+        let num_overlap_points = points
+            .iter_attribute::<u8>(&attributes::CLASSIFICATION_FLAGS)
+            .filter(|flags| flags & CLASSIFICATION_FLAG_OVERLAP != 0)
+            .count();
+        assert_eq!(2, num_overlap_points);
+
+        let without_overlap = exclude_overlap(&points)?;
+        assert_eq!(
+            (0..8).collect::<Vec<u8>>(),
+            without_overlap
+                .iter_attribute::<u8>(&attributes::CLASSIFICATION_FLAGS)
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    /// Creates a buffer with `count` points, where the `INTENSITY` of each point is its index
+    fn indexed_points(count: usize) -> InterleavedVecPointStorage {
+        let mut buffer =
+            InterleavedVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+        buffer.resize(count);
+        for index in 0..count {
+            buffer.set_raw_attribute(index, &attributes::INTENSITY, &(index as u16).to_ne_bytes());
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_sample_systematic() {
+        let buffer = indexed_points(1000);
+        for target_count in [1, 3, 10, 333, 999].iter() {
+            let sampled = sample(&buffer, *target_count, SampleStrategy::Systematic);
+            assert_eq!(*target_count, sampled.len());
+            let sampled_intensities = intensities(&sampled);
+            assert_eq!(0, sampled_intensities[0]);
+            assert!(sampled_intensities.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        let every_fourth = sample(&buffer, 250, SampleStrategy::Systematic);
+        assert!(intensities(&every_fourth)
+            .iter()
+            .enumerate()
+            .all(|(index, intensity)| *intensity as usize == index * 4));
+    }
+
+    #[test]
+    fn test_sample_random() {
+        let buffer = indexed_points(1000);
+        let target_count = 100;
+        let sampled = sample(&buffer, target_count, SampleStrategy::Random(42));
+        assert_eq!(target_count, sampled.len());
+        let sampled_intensities = intensities(&sampled);
+        assert!(sampled_intensities.windows(2).all(|w| w[0] < w[1]));
+        // The samples should cover the whole buffer and not just its start
+        let mean = sampled_intensities.iter().map(|i| *i as f64).sum::<f64>() / target_count as f64;
+        assert!((mean - 500.0).abs() < 100.0, "Mean intensity was {}", mean);
+
+        let same_seed = sample(&buffer, target_count, SampleStrategy::Random(42));
+        assert_eq!(sampled_intensities, intensities(&same_seed));
+    }
+
+    #[test]
+    fn test_sample_more_points_than_buffer() {
+        let buffer = indexed_points(10);
+        for strategy in [SampleStrategy::Systematic, SampleStrategy::Random(7)].iter() {
+            assert_eq!(10, sample(&buffer, 20, *strategy).len());
+            assert_eq!(0, sample(&buffer, 0, *strategy).len());
+        }
+    }
+
+    #[test]
+    fn test_crop() -> Result<()> {
+        // Positions are stored as f32 to test the conversion to f64
+        let position_f32 = attributes::POSITION_3D
+            .with_custom_datatype(pasture_core::layout::PointAttributeDataType::Vec3f32);
+        let positions = [
+            Vector3::new(0.5_f32, 0.5, 0.5),
+            // Exactly on the boundary of the box
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            Vector3::new(1.0, 0.5, 0.0),
+            // Just outside of the box
+            Vector3::new(1.0001, 0.5, 0.5),
+            Vector3::new(0.5, -0.0001, 0.5),
+            Vector3::new(5.0, 5.0, 5.0),
+        ];
+        let mut buffer = InterleavedVecPointStorage::new(PointLayout::from_attributes(&[
+            position_f32.clone(),
+            attributes::INTENSITY,
+        ]));
+        buffer.resize(positions.len());
+        for (index, position) in positions.iter().enumerate() {
+            buffer.set_attribute(&position_f32, index, *position);
+            buffer.set_raw_attribute(index, &attributes::INTENSITY, &(index as u16).to_ne_bytes());
+        }
+
+        let aabb = AABB::from_min_max(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let inside = crop(&buffer, aabb, false)?;
+        assert_eq!(buffer.point_layout(), inside.point_layout());
+        assert_eq!(vec![0, 1, 2, 3], intensities(&inside));
+
+        let outside = crop(&buffer, aabb, true)?;
+        assert_eq!(vec![4, 5, 6], intensities(&outside));
+
+        let no_positions =
+            InterleavedVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+        assert!(crop(&no_positions, aabb, false).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+use pasture_core::{
+    containers::{PointBuffer, PointBufferWriteable, PointBufferWriteableExt},
+    layout::attributes::{CLASSIFICATION, POSITION_3D},
+    nalgebra::Vector3,
+};
+
+use crate::returns::attribute_values;
+
+/// ASPRS classification value for ground points
+const CLASSIFICATION_GROUND: u8 = 2;
+/// ASPRS classification value for unclassified points
+const CLASSIFICATION_UNCLASSIFIED: u8 = 1;
+
+/// Options for the progressive morphological filter in [classify_ground_pmf]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PmfOptions {
+    /// Size of the cells of the minimum elevation grid, in the units of the point positions
+    pub cell_size: f64,
+    /// Size of the window in the first iteration, in cells. Should be an odd number
+    pub initial_window_size: usize,
+    /// Maximum size of the window, in cells. Objects that are larger than the maximum window size are not removed by the
+    /// filter, so this should be larger than the largest building
+    pub max_window_size: usize,
+    /// Slope of the terrain, used to derive the elevation threshold of each iteration
+    pub slope: f64,
+    /// Elevation threshold in the first iteration. Points that are further above the filtered surface than the current
+    /// elevation threshold are classified as non-ground
+    pub initial_distance: f64,
+    /// Upper limit for the elevation threshold
+    pub max_distance: f64,
+}
+
+impl Default for PmfOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 1.0,
+            initial_window_size: 3,
+            max_window_size: 33,
+            slope: 1.0,
+            initial_distance: 0.5,
+            max_distance: 2.5,
+        }
+    }
+}
+
+/// A regular grid in the XY plane that stores the minimum elevation of the points within each cell
+struct MinElevationGrid {
+    min_x: f64,
+    min_y: f64,
+    cell_size: f64,
+    width: usize,
+    height: usize,
+    /// Minimum elevation of each cell in row-major order, or `None` if the cell contains no points
+    cells: Vec<Option<f64>>,
+}
+
+impl MinElevationGrid {
+    fn new(positions: &[Vector3<f64>], cell_size: f64) -> Self {
+        let (min_x, min_y, max_x, max_y) = positions.iter().fold(
+            (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+            |(min_x, min_y, max_x, max_y), position| {
+                (
+                    min_x.min(position.x),
+                    min_y.min(position.y),
+                    max_x.max(position.x),
+                    max_y.max(position.y),
+                )
+            },
+        );
+        let width = ((max_x - min_x) / cell_size).floor() as usize + 1;
+        let height = ((max_y - min_y) / cell_size).floor() as usize + 1;
+        Self {
+            min_x,
+            min_y,
+            cell_size,
+            width,
+            height,
+            cells: vec![None; width * height],
+        }
+    }
+
+    fn cell_index(&self, position: &Vector3<f64>) -> usize {
+        let column = ((position.x - self.min_x) / self.cell_size).floor() as usize;
+        let row = ((position.y - self.min_y) / self.cell_size).floor() as usize;
+        row.min(self.height - 1) * self.width + column.min(self.width - 1)
+    }
+
+    /// Fills the cells with the minimum elevation of the points at the given `indices`
+    fn fill(&mut self, positions: &[Vector3<f64>], indices: &[usize]) {
+        self.cells.iter_mut().for_each(|cell| *cell = None);
+        for index in indices {
+            let position = &positions[*index];
+            let cell = self.cell_index(position);
+            self.cells[cell] = Some(match self.cells[cell] {
+                Some(elevation) => elevation.min(position.z),
+                None => position.z,
+            });
+        }
+    }
+
+    /// Applies a morphological opening (an erosion followed by a dilation) with a square window of the given `window_size`
+    /// to the grid and returns the resulting surface
+    fn opening(&self, window_size: usize) -> Vec<Option<f64>> {
+        let radius = window_size / 2;
+        let eroded = self.filter(&self.cells, radius, f64::min);
+        self.filter(&eroded, radius, f64::max)
+    }
+
+    /// Applies `op` to all non-empty cells in a square window with the given `radius` around each cell. Since the window
+    /// is square, this is done separately for rows and columns
+    fn filter(
+        &self,
+        cells: &[Option<f64>],
+        radius: usize,
+        op: fn(f64, f64) -> f64,
+    ) -> Vec<Option<f64>> {
+        let reduce = |values: &mut dyn Iterator<Item = Option<f64>>| {
+            values.fold(None, |acc: Option<f64>, value| match (acc, value) {
+                (Some(acc), Some(value)) => Some(op(acc, value)),
+                (None, value) => value,
+                (acc, None) => acc,
+            })
+        };
+
+        let mut rows_filtered = vec![None; cells.len()];
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let first = column.saturating_sub(radius);
+                let last = (column + radius).min(self.width - 1);
+                rows_filtered[row * self.width + column] =
+                    reduce(&mut (first..=last).map(|c| cells[row * self.width + c]));
+            }
+        }
+
+        let mut filtered = vec![None; cells.len()];
+        for row in 0..self.height {
+            let first = row.saturating_sub(radius);
+            let last = (row + radius).min(self.height - 1);
+            for column in 0..self.width {
+                filtered[row * self.width + column] =
+                    reduce(&mut (first..=last).map(|r| rows_filtered[r * self.width + column]));
+            }
+        }
+        filtered
+    }
+}
+
+/// Classifies the points in `buffer` into ground and non-ground points using the progressive morphological filter by
+/// Zhang et al. ("A progressive morphological filter for removing nonground measurements from airborne LIDAR data", 2003).
+/// The `CLASSIFICATION` of all ground points is set to 2 (ground), the `CLASSIFICATION` of all other points is set to 1
+/// (unclassified).
+///
+/// The filter rasterizes the points into a grid that stores the minimum elevation per cell. It then repeatedly applies a
+/// morphological opening with increasing window sizes to this grid, starting at [PmfOptions::initial_window_size] and
+/// doubling the window size in each iteration (`w_{k+1} = 2 * w_k + 1`) up to [PmfOptions::max_window_size]. Objects
+/// that are smaller than the window are removed by the opening, so points that are above the opened surface by more
+/// than the elevation threshold of the current iteration are classified as non-ground. The elevation threshold is
+/// `initial_distance` in the first iteration and `slope * (w_k - w_{k-1}) * cell_size + initial_distance` afterwards,
+/// limited to `max_distance`.
+///
+/// # Panics
+///
+/// If `buffer` does not contain the `POSITION_3D` attribute, or if it does not contain a `CLASSIFICATION` attribute with
+/// datatype `U8`. Also panics if `options.cell_size` is not positive
+pub fn classify_ground_pmf<T: PointBuffer + PointBufferWriteable>(
+    buffer: &mut T,
+    options: &PmfOptions,
+) {
+    if options.cell_size <= 0.0 {
+        panic!("Cell size must be positive, but was {}", options.cell_size);
+    }
+    if buffer.is_empty() {
+        return;
+    }
+
+    let positions = attribute_values::<Vector3<f64>, T>(buffer, &POSITION_3D);
+    let mut grid = MinElevationGrid::new(&positions, options.cell_size);
+
+    let mut ground_indices = (0..positions.len()).collect::<Vec<_>>();
+    let mut previous_window_size = None;
+    let mut window_size = options.initial_window_size.max(1);
+    while window_size <= options.max_window_size {
+        let elevation_threshold = match previous_window_size {
+            None => options.initial_distance,
+            Some(previous_window_size) => {
+                options.slope * (window_size - previous_window_size) as f64 * options.cell_size
+                    + options.initial_distance
+            }
+        }
+        .min(options.max_distance);
+
+        grid.fill(&positions, &ground_indices);
+        let surface = grid.opening(window_size);
+        ground_indices.retain(|index| {
+            let position = &positions[*index];
+            match surface[grid.cell_index(position)] {
+                Some(elevation) => position.z - elevation <= elevation_threshold,
+                None => true,
+            }
+        });
+
+        previous_window_size = Some(window_size);
+        window_size = 2 * window_size + 1;
+    }
+
+    for index in 0..buffer.len() {
+        buffer.set_attribute(&CLASSIFICATION, index, CLASSIFICATION_UNCLASSIFIED);
+    }
+    for index in ground_indices {
+        buffer.set_attribute(&CLASSIFICATION, index, CLASSIFICATION_GROUND);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::{PerAttributeVecPointStorage, PointBufferExt},
+        layout::{attributes, PointLayout},
+    };
+
+    /// Is the given XY position on the roof of one of the two buildings?
+    fn is_on_roof(x: f64, y: f64) -> bool {
+        let in_first_building = (10.0..18.0).contains(&x) && (10.0..18.0).contains(&y);
+        let in_second_building = (30.0..36.0).contains(&x) && (25.0..40.0).contains(&y);
+        in_first_building || in_second_building
+    }
+
+    /// Creates a ramp that rises along the x-axis with two flat-roofed buildings on it. Returns the points and for each
+    /// point whether it is on a roof
+    fn ramp_with_buildings() -> (PerAttributeVecPointStorage, Vec<bool>) {
+        let mut positions = vec![];
+        let mut on_roof = vec![];
+        for ix in 0..100 {
+            for iy in 0..100 {
+                let x = ix as f64 * 0.5;
+                let y = iy as f64 * 0.5;
+                let ground_elevation = 0.1 * x;
+                if is_on_roof(x, y) {
+                    positions.push(Vector3::new(x, y, ground_elevation + 10.0));
+                    on_roof.push(true);
+                } else {
+                    positions.push(Vector3::new(x, y, ground_elevation));
+                    on_roof.push(false);
+                }
+            }
+        }
+
+        let layout =
+            PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::CLASSIFICATION]);
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&attributes::POSITION_3D, &positions);
+        pusher.push_attribute_range(&attributes::CLASSIFICATION, &vec![0_u8; positions.len()]);
+        pusher.done();
+        (buffer, on_roof)
+    }
+
+    #[test]
+    fn test_classify_ground_pmf_ramp_with_buildings() {
+        let (mut buffer, on_roof) = ramp_with_buildings();
+        let options = PmfOptions {
+            cell_size: 1.0,
+            initial_window_size: 3,
+            max_window_size: 20,
+            slope: 0.3,
+            initial_distance: 0.15,
+            max_distance: 3.0,
+        };
+        classify_ground_pmf(&mut buffer, &options);
+
+        let classifications = buffer
+            .iter_attribute::<u8>(&attributes::CLASSIFICATION)
+            .collect::<Vec<_>>();
+        let num_roof_points = on_roof.iter().filter(|on_roof| **on_roof).count();
+        assert!(num_roof_points > 0);
+
+        for (classification, on_roof) in classifications.iter().zip(on_roof.iter()) {
+            if *on_roof {
+                assert_eq!(CLASSIFICATION_UNCLASSIFIED, *classification);
+            }
+        }
+
+        let num_terrain_points = on_roof.len() - num_roof_points;
+        let num_terrain_points_classified_as_ground = classifications
+            .iter()
+            .zip(on_roof.iter())
+            .filter(|(classification, on_roof)| {
+                !**on_roof && **classification == CLASSIFICATION_GROUND
+            })
+            .count();
+        assert!(
+            num_terrain_points_classified_as_ground as f64 >= 0.95 * num_terrain_points as f64,
+            "Only {} of {} terrain points were classified as ground",
+            num_terrain_points_classified_as_ground,
+            num_terrain_points
+        );
+    }
+
+    #[test]
+    fn test_classify_ground_pmf_empty_buffer() {
+        let layout =
+            PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::CLASSIFICATION]);
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        classify_ground_pmf(&mut buffer, &Default::default());
+        assert!(buffer.is_empty());
+    }
+}
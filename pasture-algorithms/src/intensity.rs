@@ -0,0 +1,136 @@
+use pasture_core::{
+    containers::{PointBuffer, PointBufferExt},
+    layout::attributes::INTENSITY,
+};
+
+use crate::minmax::minmax_attribute;
+
+/// Normalizes the `INTENSITY` values of all points in `buffer` into the range `[0; 1]`, assuming that the sensor populated
+/// the lowest `bits` bits of the intensity values. Each value is divided by `2^bits - 1` and clamped to `[0; 1]`, so values
+/// that exceed the given number of bits are mapped to 1.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::intensity::normalize_intensity;
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::INTENSITY, &[0_u16, 51, 255, 1000]);
+/// pusher.done();
+///
+/// assert_eq!(vec![0.0, 0.2, 1.0, 1.0], normalize_intensity(&buffer, 8));
+/// ```
+///
+/// # Panics
+///
+/// If `bits` is not in the range `[1; 16]`, or if `buffer` does not contain the `INTENSITY` attribute
+pub fn normalize_intensity<T: PointBuffer>(buffer: &T, bits: u8) -> Vec<f32> {
+    if bits == 0 || bits > 16 {
+        panic!(
+            "Number of intensity bits must be in the range [1;16], but was {}",
+            bits
+        );
+    }
+    assert_has_intensity(buffer);
+
+    let divisor = ((1_u32 << bits) - 1) as f32;
+    let normalize = |intensity: u16| (intensity as f32 / divisor).min(1.0);
+    if buffer.point_layout().has_attribute(&INTENSITY) {
+        buffer
+            .iter_attribute::<u16>(&INTENSITY)
+            .map(normalize)
+            .collect()
+    } else {
+        buffer
+            .iter_attribute_as::<u16>(&INTENSITY)
+            .map(normalize)
+            .collect()
+    }
+}
+
+/// Like [normalize_intensity], but determines the number of intensity bits from the data in `buffer`. If the maximum
+/// intensity fits into 8 bits, the intensities are normalized as 8-bit values, otherwise as 16-bit values. Note that this
+/// is a heuristic: A 16-bit sensor that only recorded low intensities will be treated as an 8-bit sensor.
+///
+/// # Panics
+///
+/// If `buffer` does not contain the `INTENSITY` attribute
+pub fn normalize_intensity_auto<T: PointBuffer>(buffer: &T) -> Vec<f32> {
+    assert_has_intensity(buffer);
+    let max_intensity = minmax_attribute::<u16, T>(buffer, &INTENSITY)
+        .map(|(_, max)| max)
+        .unwrap_or(0);
+    let bits = if max_intensity <= u8::MAX as u16 {
+        8
+    } else {
+        16
+    };
+    normalize_intensity(buffer, bits)
+}
+
+fn assert_has_intensity<T: PointBuffer>(buffer: &T) {
+    if !buffer
+        .point_layout()
+        .has_attribute_with_name(INTENSITY.name())
+    {
+        panic!(
+            "Attribute {} not contained in PointLayout of buffer ({})",
+            INTENSITY,
+            buffer.point_layout()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::PerAttributeVecPointStorage,
+        layout::{attributes, PointLayout},
+    };
+
+    fn buffer_with_intensities(intensities: &[u16]) -> PerAttributeVecPointStorage {
+        let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            attributes::INTENSITY,
+        ]));
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&attributes::INTENSITY, intensities);
+        pusher.done();
+        buffer
+    }
+
+    #[test]
+    fn test_normalize_intensity_explicit_bits() {
+        let buffer = buffer_with_intensities(&[0, 255, 65535]);
+        assert_eq!(
+            vec![0.0, 255.0 / 65535.0, 1.0],
+            normalize_intensity(&buffer, 16)
+        );
+        assert_eq!(vec![0.0, 1.0, 1.0], normalize_intensity(&buffer, 8));
+    }
+
+    #[test]
+    fn test_normalize_intensity_auto_bits() {
+        let eight_bit_buffer = buffer_with_intensities(&[0, 51, 255]);
+        assert_eq!(
+            vec![0.0, 0.2, 1.0],
+            normalize_intensity_auto(&eight_bit_buffer)
+        );
+
+        let sixteen_bit_buffer = buffer_with_intensities(&[0, 255, 65535]);
+        assert_eq!(
+            vec![0.0, 255.0 / 65535.0, 1.0],
+            normalize_intensity_auto(&sixteen_bit_buffer)
+        );
+
+        let empty_buffer = buffer_with_intensities(&[]);
+        assert!(normalize_intensity_auto(&empty_buffer).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_normalize_intensity_invalid_bits() {
+        let buffer = buffer_with_intensities(&[0]);
+        normalize_intensity(&buffer, 17);
+    }
+}
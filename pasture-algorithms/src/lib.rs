@@ -20,3 +20,19 @@ pub mod voxel_grid;
 // Contains a normal estimation algorithm that can be used to determine the orientation of the surface
 // over a point and its k nearest neighbors. The algorithm also determine the curvature of the surface
 pub mod normal_estimation;
+// Contains helpers to normalize intensity values into the range [0;1]
+pub mod intensity;
+// Contains helpers to colorize points by mapping an attribute to a color ramp
+pub mod colorize;
+// Contains an algorithm to group the returns of a laser pulse together
+pub mod returns;
+// Contains filters that select a subset of the points of a point cloud
+pub mod filters;
+// Contains a progressive morphological filter to classify ground points
+pub mod ground;
+// Contains an algorithm to rasterize a point cloud into a 2D grid, e.g. to create elevation models
+pub mod raster;
+// Contains helpers to remap the classifications of a point cloud, e.g. from a vendor-specific scheme to the ASPRS classes
+pub mod classification;
+// Contains a validation pass that reports points whose attributes violate range or NaN constraints
+pub mod validate;
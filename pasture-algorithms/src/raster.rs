@@ -0,0 +1,187 @@
+use pasture_core::{
+    containers::PointBuffer,
+    layout::attributes::POSITION_3D,
+    math::{Raster, RASTER_NODATA},
+    nalgebra::{Vector2, Vector3},
+};
+
+use crate::returns::attribute_values;
+
+/// How the elevations of all points within a raster cell are combined into the value of the cell
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aggregation {
+    /// The minimum elevation, e.g. to create a digital terrain model from ground points
+    Min,
+    /// The maximum elevation, e.g. to create a digital surface model
+    Max,
+    /// The mean elevation
+    Mean,
+    /// The number of points within the cell. Empty cells have a count of zero instead of the nodata value
+    Count,
+}
+
+/// Rasterizes the points in `buffer` into a 2D grid with square cells of size `cell_size`. Each point is assigned to the
+/// cell that contains its XY position, and the elevations (i.e. z-coordinates) of all points within a cell are combined
+/// using `aggregation`. Cells that contain no points are set to [RASTER_NODATA] (except for [Aggregation::Count]).
+///
+/// The upper left corner of the raster is at the minimum x and maximum y coordinate of the points, and the raster is
+/// just large enough to contain all points. If `buffer` is empty, the resulting raster has no cells.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_core::nalgebra::Vector3;
+/// # use pasture_algorithms::raster::*;
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::POSITION_3D]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(
+///     &attributes::POSITION_3D,
+///     &[Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.5, 0.5, 3.0), Vector3::new(1.5, 0.5, 2.0)],
+/// );
+/// pusher.done();
+/// let raster = rasterize(&buffer, 1.0, Aggregation::Mean);
+/// assert_eq!((2, 1), (raster.width, raster.height));
+/// assert_eq!(vec![2.0, 2.0], raster.values);
+/// ```
+///
+/// # Panics
+///
+/// If `buffer` does not contain the `POSITION_3D` attribute, or if `cell_size` is not positive
+pub fn rasterize<T: PointBuffer>(
+    buffer: &T,
+    cell_size: f64,
+    aggregation: Aggregation,
+) -> Raster<f32> {
+    if cell_size <= 0.0 {
+        panic!("Cell size must be positive, but was {}", cell_size);
+    }
+    let positions = attribute_values::<Vector3<f64>, T>(buffer, &POSITION_3D);
+    if positions.is_empty() {
+        return Raster::new(Vector2::new(0.0, 0.0), cell_size, 0, 0, RASTER_NODATA);
+    }
+
+    let (min_x, min_y, max_x, max_y) = positions.iter().fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(min_x, min_y, max_x, max_y), position| {
+            (
+                min_x.min(position.x),
+                min_y.min(position.y),
+                max_x.max(position.x),
+                max_y.max(position.y),
+            )
+        },
+    );
+    let width = ((max_x - min_x) / cell_size).floor() as usize + 1;
+    let height = ((max_y - min_y) / cell_size).floor() as usize + 1;
+
+    // Accumulate in f64 so that the mean of many points does not lose precision
+    let mut accumulated: Vec<Option<f64>> = vec![None; width * height];
+    let mut counts = vec![0_usize; width * height];
+    for position in positions.iter() {
+        let column = (((position.x - min_x) / cell_size).floor() as usize).min(width - 1);
+        let row = (((max_y - position.y) / cell_size).floor() as usize).min(height - 1);
+        let cell = row * width + column;
+        counts[cell] += 1;
+        accumulated[cell] = Some(match (accumulated[cell], aggregation) {
+            (None, _) => position.z,
+            (Some(value), Aggregation::Min) => value.min(position.z),
+            (Some(value), Aggregation::Max) => value.max(position.z),
+            (Some(value), Aggregation::Mean) | (Some(value), Aggregation::Count) => {
+                value + position.z
+            }
+        });
+    }
+
+    let mut raster = Raster::new(
+        Vector2::new(min_x, max_y),
+        cell_size,
+        width,
+        height,
+        RASTER_NODATA,
+    );
+    for (cell, value) in raster.values.iter_mut().enumerate() {
+        *value = match (accumulated[cell], aggregation) {
+            (_, Aggregation::Count) => counts[cell] as f32,
+            (None, _) => RASTER_NODATA,
+            (Some(sum), Aggregation::Mean) => (sum / counts[cell] as f64) as f32,
+            (Some(value), _) => value as f32,
+        };
+    }
+    raster
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::{InterleavedVecPointStorage, PointBufferWriteable},
+        layout::{attributes, PointLayout},
+    };
+
+    /// Creates a buffer for a 3x2 grid with cell size 1 and origin (0, 2). The upper middle cell is empty
+    fn tiny_grid() -> InterleavedVecPointStorage {
+        let positions = [
+            // Upper left cell
+            Vector3::new(0.0, 2.0, 1.0),
+            Vector3::new(0.7, 1.1, 3.0),
+            // Upper right cell
+            Vector3::new(2.5, 1.5, 7.0),
+            // Lower left cell
+            Vector3::new(0.3, 0.1, -2.0),
+            // Lower middle cell
+            Vector3::new(1.1, 0.2, 4.0),
+            Vector3::new(1.9, 0.9, 5.0),
+            Vector3::new(1.5, 0.5, 9.0),
+            // Lower right cell, on the upper border of the cell
+            Vector3::new(2.9, 1.0, 6.0),
+        ];
+        let mut buffer = InterleavedVecPointStorage::new(PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+        ]));
+        buffer.resize(positions.len());
+        for (index, position) in positions.iter().enumerate() {
+            let bytes = position
+                .iter()
+                .flat_map(|v| v.to_ne_bytes().to_vec())
+                .collect::<Vec<_>>();
+            buffer.set_raw_attribute(index, &attributes::POSITION_3D, &bytes);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_rasterize_cell_assignment() {
+        let raster = rasterize(&tiny_grid(), 1.0, Aggregation::Count);
+        assert_eq!(Vector2::new(0.0, 2.0), raster.origin);
+        assert_eq!(3, raster.width);
+        assert_eq!(2, raster.height);
+        assert_eq!(vec![2.0, 0.0, 1.0, 1.0, 3.0, 1.0], raster.values);
+    }
+
+    #[test]
+    fn test_rasterize_aggregations() {
+        let buffer = tiny_grid();
+        let nd = RASTER_NODATA;
+
+        let min = rasterize(&buffer, 1.0, Aggregation::Min);
+        assert_eq!(vec![1.0, nd, 7.0, -2.0, 4.0, 6.0], min.values);
+
+        let max = rasterize(&buffer, 1.0, Aggregation::Max);
+        assert_eq!(vec![3.0, nd, 7.0, -2.0, 9.0, 6.0], max.values);
+
+        let mean = rasterize(&buffer, 1.0, Aggregation::Mean);
+        assert_eq!(vec![2.0, nd, 7.0, -2.0, 6.0, 6.0], mean.values);
+        assert_eq!(nd, mean.nodata);
+    }
+
+    #[test]
+    fn test_rasterize_empty_buffer() {
+        let buffer = InterleavedVecPointStorage::new(PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+        ]));
+        let raster = rasterize(&buffer, 1.0, Aggregation::Max);
+        assert_eq!(0, raster.width);
+        assert_eq!(0, raster.height);
+        assert!(raster.values.is_empty());
+    }
+}
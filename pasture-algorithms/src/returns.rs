@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use pasture_core::{
+    containers::{PointBuffer, PointBufferExt},
+    layout::{
+        attributes::{GPS_TIME, NUMBER_OF_RETURNS, RETURN_NUMBER},
+        PointAttributeDefinition, PrimitiveType,
+    },
+};
+
+/// The result of [group_returns]: The indices of the points that belong to each laser pulse
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReturnGroups {
+    /// The point indices of each pulse whose returns are consistent, i.e. all points of the pulse have the same
+    /// `NUMBER_OF_RETURNS` value `n` and there is exactly one point for each `RETURN_NUMBER` in `[1; n]`. The indices
+    /// of each pulse are sorted by return number
+    pub pulses: Vec<Vec<usize>>,
+    /// The point indices of all pulses whose returns are inconsistent, e.g. because a return is missing, a return number
+    /// occurs twice, or the points of the pulse disagree on the number of returns. This can happen if the point cloud
+    /// was cropped or filtered. The indices of each pulse are in the order in which they appear in the buffer
+    pub inconsistent_pulses: Vec<Vec<usize>>,
+}
+
+/// Groups the points in `buffer` by the laser pulse that they belong to. All returns of a single pulse share the same
+/// `GPS_TIME`, so the points are grouped by their GPS time. Each group is then checked for consistency using the
+/// `RETURN_NUMBER` and `NUMBER_OF_RETURNS` attributes. Groups that are not consistent are reported separately in
+/// [ReturnGroups::inconsistent_pulses] instead of being grouped silently. Pulses are ordered by the index of their first
+/// point within `buffer`.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::returns::group_returns;
+/// let layout = PointLayout::from_attributes(&[
+///     attributes::RETURN_NUMBER,
+///     attributes::NUMBER_OF_RETURNS,
+///     attributes::GPS_TIME,
+/// ]);
+/// let mut buffer = PerAttributeVecPointStorage::new(layout);
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::RETURN_NUMBER, &[2_u8, 1, 1]);
+/// pusher.push_attribute_range(&attributes::NUMBER_OF_RETURNS, &[2_u8, 2, 1]);
+/// pusher.push_attribute_range(&attributes::GPS_TIME, &[1.0, 1.0, 2.0]);
+/// pusher.done();
+///
+/// let groups = group_returns(&buffer);
+/// assert_eq!(vec![vec![1, 0], vec![2]], groups.pulses);
+/// assert!(groups.inconsistent_pulses.is_empty());
+/// ```
+///
+/// # Panics
+///
+/// If `buffer` does not contain the `RETURN_NUMBER`, `NUMBER_OF_RETURNS` or `GPS_TIME` attributes
+pub fn group_returns<T: PointBuffer>(buffer: &T) -> ReturnGroups {
+    let return_numbers = attribute_values::<u8, T>(buffer, &RETURN_NUMBER);
+    let numbers_of_returns = attribute_values::<u8, T>(buffer, &NUMBER_OF_RETURNS);
+    let gps_times = attribute_values::<f64, T>(buffer, &GPS_TIME);
+
+    let mut return_groups: ReturnGroups = Default::default();
+    for mut group in group_by_gps_time(&gps_times) {
+        let number_of_returns = numbers_of_returns[group[0]];
+        let has_consistent_returns = group.len() == number_of_returns as usize
+            && group
+                .iter()
+                .all(|index| numbers_of_returns[*index] == number_of_returns);
+        if !has_consistent_returns {
+            return_groups.inconsistent_pulses.push(group);
+            continue;
+        }
+
+        group.sort_by_key(|index| return_numbers[*index]);
+        let has_all_return_numbers =
+            group
+                .iter()
+                .zip(1..=number_of_returns)
+                .all(|(index, expected_return_number)| {
+                    return_numbers[*index] == expected_return_number
+                });
+        if has_all_return_numbers {
+            return_groups.pulses.push(group);
+        } else {
+            group.sort_unstable();
+            return_groups.inconsistent_pulses.push(group);
+        }
+    }
+
+    return_groups
+}
+
+/// Index that maps between per-point and per-pulse indexing of the points in a buffer. Each point is identified by the
+/// index of the laser pulse that it belongs to (its `pulse_id`) and its position within the returns of that pulse (its
+/// `return_index`). Like [group_returns], all points with the same `GPS_TIME` belong to the same pulse, even if they
+/// have different return numbers. Pulses are numbered in the order of their first point within the buffer, and the
+/// returns of each pulse are sorted by their `RETURN_NUMBER`, so `return_index` 0 is the first return of a pulse.
+///
+/// In contrast to [group_returns], `ReturnIndex` does not check the returns of a pulse for consistency. Every point of
+/// the buffer belongs to exactly one pulse, so the mapping can be inverted for all points.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::returns::ReturnIndex;
+/// let layout = PointLayout::from_attributes(&[
+///     attributes::RETURN_NUMBER,
+///     attributes::NUMBER_OF_RETURNS,
+///     attributes::GPS_TIME,
+/// ]);
+/// let mut buffer = PerAttributeVecPointStorage::new(layout);
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::RETURN_NUMBER, &[2_u8, 1, 1]);
+/// pusher.push_attribute_range(&attributes::NUMBER_OF_RETURNS, &[2_u8, 2, 1]);
+/// pusher.push_attribute_range(&attributes::GPS_TIME, &[1.0, 1.0, 2.0]);
+/// pusher.done();
+///
+/// let index = ReturnIndex::new(&buffer);
+/// assert_eq!(2, index.pulse_count());
+/// assert_eq!(Some((0, 1)), index.return_of_point(0));
+/// assert_eq!(Some(1), index.point_of_return(0, 0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReturnIndex {
+    /// The `(pulse_id, return_index)` pair of each point
+    returns_of_points: Vec<(usize, usize)>,
+    /// The point indices of each pulse, sorted by return number
+    points_of_pulses: Vec<Vec<usize>>,
+}
+
+impl ReturnIndex {
+    /// Builds the `ReturnIndex` for all points in `buffer`
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` does not contain the `RETURN_NUMBER` or `GPS_TIME` attributes
+    pub fn new<T: PointBuffer>(buffer: &T) -> Self {
+        let return_numbers = attribute_values::<u8, T>(buffer, &RETURN_NUMBER);
+        let gps_times = attribute_values::<f64, T>(buffer, &GPS_TIME);
+
+        let mut points_of_pulses = group_by_gps_time(&gps_times);
+        let mut returns_of_points = vec![(0, 0); gps_times.len()];
+        for (pulse_id, points) in points_of_pulses.iter_mut().enumerate() {
+            // Stable sort, so that returns with the same return number keep their order within the buffer
+            points.sort_by_key(|index| return_numbers[*index]);
+            for (return_index, point_index) in points.iter().enumerate() {
+                returns_of_points[*point_index] = (pulse_id, return_index);
+            }
+        }
+
+        Self {
+            returns_of_points,
+            points_of_pulses,
+        }
+    }
+
+    /// Returns the number of points in this `ReturnIndex`
+    pub fn point_count(&self) -> usize {
+        self.returns_of_points.len()
+    }
+
+    /// Returns the number of laser pulses in this `ReturnIndex`
+    pub fn pulse_count(&self) -> usize {
+        self.points_of_pulses.len()
+    }
+
+    /// Returns the `(pulse_id, return_index)` pair of the point at `point_index`, or `None` if `point_index` is out of
+    /// bounds
+    pub fn return_of_point(&self, point_index: usize) -> Option<(usize, usize)> {
+        self.returns_of_points.get(point_index).copied()
+    }
+
+    /// Returns the index of the point that is the return with `return_index` of the pulse `pulse_id`, or `None` if there
+    /// is no such pulse or the pulse has fewer returns
+    pub fn point_of_return(&self, pulse_id: usize, return_index: usize) -> Option<usize> {
+        self.points_of_pulses
+            .get(pulse_id)
+            .and_then(|points| points.get(return_index))
+            .copied()
+    }
+
+    /// Returns the indices of all points of the pulse `pulse_id`, sorted by return number, or `None` if there is no such
+    /// pulse
+    pub fn points_of_pulse(&self, pulse_id: usize) -> Option<&[usize]> {
+        self.points_of_pulses
+            .get(pulse_id)
+            .map(|points| &points[..])
+    }
+}
+
+/// Groups the point indices by their GPS time, in the order of the first point of each group. GPS times are compared
+/// bitwise, since all returns of a pulse have exactly the same GPS time
+fn group_by_gps_time(gps_times: &[f64]) -> Vec<Vec<usize>> {
+    let mut group_by_gps_time: HashMap<u64, usize> = HashMap::new();
+    let mut groups: Vec<Vec<usize>> = vec![];
+    for (point_index, gps_time) in gps_times.iter().enumerate() {
+        let group_index = *group_by_gps_time
+            .entry(gps_time.to_bits())
+            .or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+        groups[group_index].push(point_index);
+    }
+    groups
+}
+
+/// Returns the values of `attribute` for all points in `buffer`, converted to `T` if the attribute is stored with a
+/// different datatype
+pub(crate) fn attribute_values<T: PrimitiveType, B: PointBuffer>(
+    buffer: &B,
+    attribute: &PointAttributeDefinition,
+) -> Vec<T> {
+    if buffer.point_layout().has_attribute(attribute) {
+        buffer.iter_attribute::<T>(attribute).collect()
+    } else {
+        buffer.iter_attribute_as::<T>(attribute).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::PerAttributeVecPointStorage,
+        layout::{attributes, PointLayout},
+    };
+
+    /// Creates a buffer with the given return numbers, numbers of returns and GPS times per point
+    fn make_buffer(returns: &[(u8, u8, f64)]) -> PerAttributeVecPointStorage {
+        let layout = PointLayout::from_attributes(&[
+            attributes::RETURN_NUMBER,
+            attributes::NUMBER_OF_RETURNS,
+            attributes::GPS_TIME,
+        ]);
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(
+            &attributes::RETURN_NUMBER,
+            &returns.iter().map(|r| r.0).collect::<Vec<_>>(),
+        );
+        pusher.push_attribute_range(
+            &attributes::NUMBER_OF_RETURNS,
+            &returns.iter().map(|r| r.1).collect::<Vec<_>>(),
+        );
+        pusher.push_attribute_range(
+            &attributes::GPS_TIME,
+            &returns.iter().map(|r| r.2).collect::<Vec<_>>(),
+        );
+        pusher.done();
+        buffer
+    }
+
+    #[test]
+    fn test_group_returns_three_return_pulses() {
+        // Three pulses with three returns each, with the returns of the pulses interleaved
+        let buffer = make_buffer(&[
+            (1, 3, 10.0),
+            (1, 3, 11.0),
+            (3, 3, 10.0),
+            (2, 3, 11.0),
+            (2, 3, 10.0),
+            (3, 3, 11.0),
+            (3, 3, 12.0),
+            (2, 3, 12.0),
+            (1, 3, 12.0),
+        ]);
+
+        let groups = group_returns(&buffer);
+        assert_eq!(
+            vec![vec![0, 4, 2], vec![1, 3, 5], vec![8, 7, 6]],
+            groups.pulses
+        );
+        assert!(groups.inconsistent_pulses.is_empty());
+    }
+
+    #[test]
+    fn test_group_returns_reports_inconsistent_pulses() {
+        let buffer = make_buffer(&[
+            // Missing the second return
+            (1, 3, 1.0),
+            (3, 3, 1.0),
+            // Disagreement on the number of returns
+            (1, 2, 2.0),
+            (2, 3, 2.0),
+            // Duplicate return number
+            (1, 2, 3.0),
+            (1, 2, 3.0),
+            // Consistent single return
+            (1, 1, 4.0),
+        ]);
+
+        let groups = group_returns(&buffer);
+        assert_eq!(vec![vec![6]], groups.pulses);
+        assert_eq!(
+            vec![vec![0, 1], vec![2, 3], vec![4, 5]],
+            groups.inconsistent_pulses
+        );
+    }
+
+    #[test]
+    fn test_return_index_maps_points_and_returns() {
+        let buffer = make_buffer(&[
+            (2, 3, 10.0),
+            (1, 2, 11.0),
+            (1, 3, 10.0),
+            (1, 1, 12.0),
+            (3, 3, 10.0),
+            (2, 2, 11.0),
+        ]);
+
+        let index = ReturnIndex::new(&buffer);
+        assert_eq!(6, index.point_count());
+        assert_eq!(3, index.pulse_count());
+
+        let expected_returns = [(0, 1), (1, 0), (0, 0), (2, 0), (0, 2), (1, 1)];
+        for (point_index, expected_return) in expected_returns.iter().enumerate() {
+            assert_eq!(Some(*expected_return), index.return_of_point(point_index));
+            assert_eq!(
+                Some(point_index),
+                index.point_of_return(expected_return.0, expected_return.1)
+            );
+        }
+
+        assert_eq!(Some(&[2, 0, 4][..]), index.points_of_pulse(0));
+        assert_eq!(None, index.return_of_point(6));
+        assert_eq!(None, index.point_of_return(2, 1));
+        assert_eq!(None, index.point_of_return(3, 0));
+        assert_eq!(None, index.points_of_pulse(3));
+    }
+
+    #[test]
+    fn test_return_index_groups_identical_gps_times() {
+        // Inconsistent returns with the same GPS time still belong to the same pulse
+        let buffer = make_buffer(&[(3, 3, 5.0), (1, 2, 5.0), (1, 2, 5.0), (2, 1, 6.0)]);
+
+        let index = ReturnIndex::new(&buffer);
+        assert_eq!(2, index.pulse_count());
+        assert_eq!(Some(&[1, 2, 0][..]), index.points_of_pulse(0));
+        assert_eq!(Some((0, 2)), index.return_of_point(0));
+        assert_eq!(Some((0, 0)), index.return_of_point(1));
+        assert_eq!(Some((0, 1)), index.return_of_point(2));
+        assert_eq!(Some((1, 0)), index.return_of_point(3));
+    }
+}
@@ -4,6 +4,7 @@ use pasture_core::{
     containers::{PointBuffer, PointBufferExt},
     layout::attributes::POSITION_3D,
     nalgebra::Vector3,
+    util::run_parallel,
 };
 use rand::Rng;
 use rayon::prelude::*;
@@ -187,15 +188,17 @@ pub fn ransac_plane_par<T: PointBuffer + Sync>(
         panic!("buffer needs to include at least 3 points to generate a plane.");
     }
     // iterate in parallel over num_of_iterations
-    (0..num_of_iterations)
-        .into_par_iter()
-        .map(|_x| {
-            // generate one model for the current iteration
-            generate_plane_model(buffer, distance_threshold)
-        })
-        // get the best plane-model from all iterations (highest ranking)
-        .max_by(|(x, _y), (a, _b)| x.ranking.cmp(&a.ranking))
-        .unwrap()
+    run_parallel(|| {
+        (0..num_of_iterations)
+            .into_par_iter()
+            .map(|_x| {
+                // generate one model for the current iteration
+                generate_plane_model(buffer, distance_threshold)
+            })
+            // get the best plane-model from all iterations (highest ranking)
+            .max_by(|(x, _y), (a, _b)| x.ranking.cmp(&a.ranking))
+            .unwrap()
+    })
 }
 
 /// Ransac Plane Segmentation in serial (for maximum speed use ransac_plane_par).
@@ -307,14 +310,16 @@ pub fn ransac_line_par<T: PointBuffer + Sync>(
         panic!("buffer needs to include at least 2 points to generate a line.");
     }
     // iterate num_of_iterations in parallel
-    (0..num_of_iterations)
-        .into_par_iter()
-        .map(|_x| 
-            // generate one model for the current iteration
-            generate_line_model(buffer, distance_threshold))
-        // get the best line-model from all iterations (highest ranking)
-        .max_by(|(x, _y), (a, _b)| x.ranking.cmp(&a.ranking))
-        .unwrap()
+    run_parallel(|| {
+        (0..num_of_iterations)
+            .into_par_iter()
+            .map(|_x| 
+                // generate one model for the current iteration
+                generate_line_model(buffer, distance_threshold))
+            // get the best line-model from all iterations (highest ranking)
+            .max_by(|(x, _y), (a, _b)| x.ranking.cmp(&a.ranking))
+            .unwrap()
+    })
 }
 
 /// Ransac Line Segmentation in serial (for maximum speed use ransac_line_par).
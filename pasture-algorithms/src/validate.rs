@@ -0,0 +1,290 @@
+use std::fmt::Display;
+
+use pasture_core::{
+    containers::PointBuffer,
+    layout::{conversion::scaled_converter, PointAttributeDataType, PointAttributeDefinition},
+};
+
+/// A rule that constrains the values of a single point attribute, used by `check_buffer`. For vector and array
+/// attributes, the constraints apply to each component of the values. Rules are created with `AttributeRule::new` and
+/// the constraints are added in builder style:
+///
+/// ```
+/// # use pasture_core::layout::attributes::{CLASSIFICATION, POSITION_3D};
+/// # use pasture_algorithms::validate::AttributeRule;
+/// let rules = [
+///     // Legacy LAS point formats only support classifications up to 31
+///     AttributeRule::new(CLASSIFICATION).max(31.0),
+///     AttributeRule::new(POSITION_3D).no_nan(),
+/// ];
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeRule {
+    attribute: PointAttributeDefinition,
+    min: Option<f64>,
+    max: Option<f64>,
+    no_nan: bool,
+}
+
+impl AttributeRule {
+    /// Creates a new `AttributeRule` for the given `attribute` without any constraints
+    pub fn new(attribute: PointAttributeDefinition) -> Self {
+        Self {
+            attribute,
+            min: None,
+            max: None,
+            no_nan: false,
+        }
+    }
+
+    /// Requires all values of the attribute to be greater than or equal to `min`
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Requires all values of the attribute to be less than or equal to `max`
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Requires all values of the attribute to be within the inclusive range `[min, max]`
+    pub fn range(self, min: f64, max: f64) -> Self {
+        self.min(min).max(max)
+    }
+
+    /// Requires all values of the attribute to not be NaN. NaN values never violate the `min` and `max` constraints of
+    /// a rule, so they are only reported if this constraint is set
+    pub fn no_nan(mut self) -> Self {
+        self.no_nan = true;
+        self
+    }
+
+    /// Returns the attribute that this rule applies to
+    pub fn attribute(&self) -> &PointAttributeDefinition {
+        &self.attribute
+    }
+
+    /// Returns the reason why the given `value` violates this rule, or `None` if `value` satisfies this rule
+    fn check(&self, value: f64) -> Option<ViolationReason> {
+        if value.is_nan() {
+            return if self.no_nan {
+                Some(ViolationReason::NaN)
+            } else {
+                None
+            };
+        }
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => Some(ViolationReason::BelowMin { value, min }),
+            (_, Some(max)) if value > max => Some(ViolationReason::AboveMax { value, max }),
+            _ => None,
+        }
+    }
+}
+
+/// The reason why a value violates an `AttributeRule`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ViolationReason {
+    /// The value is NaN
+    NaN,
+    /// The value is less than the minimum value of the rule
+    BelowMin { value: f64, min: f64 },
+    /// The value is greater than the maximum value of the rule
+    AboveMax { value: f64, max: f64 },
+}
+
+/// A point whose value of an attribute violates an `AttributeRule`, as returned by `check_buffer`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Index of the point within the checked buffer
+    pub point_index: usize,
+    /// The attribute whose value violates the rule
+    pub attribute: PointAttributeDefinition,
+    /// Why the value violates the rule. For vector and array attributes, this describes the first component that
+    /// violates the rule
+    pub reason: ViolationReason,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Point {}: {} ", self.point_index, self.attribute.name())?;
+        match self.reason {
+            ViolationReason::NaN => write!(f, "is NaN"),
+            ViolationReason::BelowMin { value, min } => {
+                write!(f, "is {}, which is less than the minimum {}", value, min)
+            }
+            ViolationReason::AboveMax { value, max } => {
+                write!(f, "is {}, which is greater than the maximum {}", value, max)
+            }
+        }
+    }
+}
+
+/// Checks the points in `buffer` against the given `rules` and returns all violations, sorted by rule and then by point
+/// index. Each point violates each rule at most once. This is the basis for data-quality reports, e.g. to find points
+/// with NaN positions or classifications that the target file format doesn't support.
+///
+/// Values are compared in the datatype that the attribute has in `buffer`, converted to `f64`, so the datatype of the
+/// attribute in a rule doesn't have to match the datatype in `buffer`. Rules for attributes that `buffer` doesn't
+/// contain are ignored, so that the same rules can be applied to buffers with different `PointLayout`s.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_algorithms::validate::{check_buffer, AttributeRule};
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::CLASSIFICATION]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(&attributes::CLASSIFICATION, &[2_u8, 40, 6]);
+/// pusher.done();
+///
+/// let violations = check_buffer(&buffer, &[AttributeRule::new(attributes::CLASSIFICATION).max(31.0)]);
+/// assert_eq!(1, violations.len());
+/// assert_eq!(1, violations[0].point_index);
+/// ```
+pub fn check_buffer<T: PointBuffer>(buffer: &T, rules: &[AttributeRule]) -> Vec<Violation> {
+    let mut violations = vec![];
+    for rule in rules {
+        let buffer_attribute = match buffer
+            .point_layout()
+            .get_attribute_by_name(rule.attribute.name())
+        {
+            Some(attribute) => PointAttributeDefinition::from(attribute),
+            None => continue,
+        };
+
+        let datatype = buffer_attribute.datatype();
+        // Booleans are stored as a single byte, so they can be read just like `U8` values
+        let component_type = match datatype.component_type() {
+            PointAttributeDataType::Bool => PointAttributeDataType::U8,
+            other => other,
+        };
+        let converter = scaled_converter(component_type, 1.0, 0.0)
+            .expect("All component types are scalar types");
+        let component_size = component_type.size() as usize;
+
+        let mut attribute_data = vec![0; datatype.size() as usize];
+        let mut value_data = [0; 8];
+        for point_index in 0..buffer.len() {
+            buffer.get_raw_attribute(point_index, &buffer_attribute, &mut attribute_data);
+            let reason = attribute_data
+                .chunks_exact(component_size)
+                .find_map(|component| {
+                    let value = unsafe {
+                        converter.convert(component, &mut value_data);
+                        f64::from_ne_bytes(value_data)
+                    };
+                    rule.check(value)
+                });
+            if let Some(reason) = reason {
+                violations.push(Violation {
+                    point_index,
+                    attribute: buffer_attribute.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pasture_core::{
+        containers::{PerAttributeVecPointStorage, PointBufferWriteable},
+        layout::{
+            attributes::{CLASSIFICATION, INTENSITY, POSITION_3D},
+            PointLayout,
+        },
+        nalgebra::Vector3,
+    };
+
+    fn make_buffer(
+        positions: &[Vector3<f64>],
+        classifications: &[u8],
+    ) -> PerAttributeVecPointStorage {
+        let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            POSITION_3D,
+            CLASSIFICATION,
+        ]));
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&POSITION_3D, positions);
+        pusher.push_attribute_range(&CLASSIFICATION, classifications);
+        pusher.done();
+        buffer
+    }
+
+    #[test]
+    fn test_check_buffer_reports_out_of_range_classification() {
+        let buffer = make_buffer(&[Vector3::new(0.0, 0.0, 0.0); 4], &[2, 6, 40, 31]);
+        let violations = check_buffer(&buffer, &[AttributeRule::new(CLASSIFICATION).max(31.0)]);
+        assert_eq!(
+            vec![Violation {
+                point_index: 2,
+                attribute: CLASSIFICATION,
+                reason: ViolationReason::AboveMax {
+                    value: 40.0,
+                    max: 31.0
+                },
+            }],
+            violations
+        );
+        assert_eq!(
+            "Point 2: Classification is 40, which is greater than the maximum 31",
+            violations[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_check_buffer_reports_nan_and_vector_components() {
+        let buffer = make_buffer(
+            &[
+                Vector3::new(1.0, 2.0, 3.0),
+                Vector3::new(1.0, f64::NAN, f64::NAN),
+                Vector3::new(1.0, -5.0, 3.0),
+            ],
+            &[2, 2, 2],
+        );
+
+        // NaN values only violate rules that reject them
+        let range_rule = AttributeRule::new(POSITION_3D).range(0.0, 10.0);
+        let violations = check_buffer(&buffer, &[range_rule.clone()]);
+        assert_eq!(1, violations.len());
+        assert_eq!(2, violations[0].point_index);
+        assert_eq!(
+            ViolationReason::BelowMin {
+                value: -5.0,
+                min: 0.0
+            },
+            violations[0].reason
+        );
+
+        let violations = check_buffer(&buffer, &[range_rule.no_nan()]);
+        let reasons = violations
+            .iter()
+            .map(|violation| (violation.point_index, violation.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (1, ViolationReason::NaN),
+                (
+                    2,
+                    ViolationReason::BelowMin {
+                        value: -5.0,
+                        min: 0.0
+                    }
+                )
+            ],
+            reasons
+        );
+    }
+
+    #[test]
+    fn test_check_buffer_ignores_missing_attributes() {
+        let mut buffer = make_buffer(&[], &[]);
+        buffer.resize(3);
+        let violations = check_buffer(&buffer, &[AttributeRule::new(INTENSITY).min(1.0)]);
+        assert!(violations.is_empty());
+    }
+}
@@ -8,7 +8,7 @@ use pasture_core::{
         attributes::{self, POSITION_3D},
         PointAttributeDataType, PointAttributeDefinition,
     },
-    nalgebra::Vector3,
+    nalgebra::{Vector3, Vector4},
     util::view_raw_bytes,
 };
 
@@ -204,11 +204,18 @@ fn centroid_max_pool<T: PointBuffer>(
                 value = buffer.get_attribute::<f64>(attribute_definition, *p) as f64;
             }
             PointAttributeDataType::Bool => panic!("Max pooling not possible with booleans."),
-            PointAttributeDataType::Vec3u8 => unimplemented!(),
-            PointAttributeDataType::Vec3u16 => unimplemented!(),
-            PointAttributeDataType::Vec3f32 => unimplemented!(),
-            PointAttributeDataType::Vec3f64 => unimplemented!(),
-            PointAttributeDataType::Vec4u8 => unimplemented!(),
+            PointAttributeDataType::Vec3u8
+            | PointAttributeDataType::Vec3u16
+            | PointAttributeDataType::Vec3f32
+            | PointAttributeDataType::Vec3f64
+            | PointAttributeDataType::Vec4u8
+            | PointAttributeDataType::Vec4u16
+            | PointAttributeDataType::Vec4f32
+            | PointAttributeDataType::Vec4f64 => panic!("Max pooling not possible with vectors."),
+            PointAttributeDataType::Array4f32
+            | PointAttributeDataType::Array8f32
+            | PointAttributeDataType::Array4i32
+            | PointAttributeDataType::Array8i32 => panic!("Max pooling not possible with arrays."),
         }
         if value > curr_max {
             curr_max = value;
@@ -316,11 +323,22 @@ fn centroid_most_common<T: PointBuffer>(
                 )
                 .or_insert(0) += 1
             }
-            PointAttributeDataType::Vec3u8 => unimplemented!(),
-            PointAttributeDataType::Vec3u16 => unimplemented!(),
-            PointAttributeDataType::Vec3f32 => unimplemented!(),
-            PointAttributeDataType::Vec3f64 => unimplemented!(),
-            PointAttributeDataType::Vec4u8 => unimplemented!(),
+            PointAttributeDataType::Vec3u8
+            | PointAttributeDataType::Vec3u16
+            | PointAttributeDataType::Vec3f32
+            | PointAttributeDataType::Vec3f64
+            | PointAttributeDataType::Vec4u8
+            | PointAttributeDataType::Vec4u16
+            | PointAttributeDataType::Vec4f32
+            | PointAttributeDataType::Vec4f64 => {
+                panic!("Most common value not supported for vectors.")
+            }
+            PointAttributeDataType::Array4f32
+            | PointAttributeDataType::Array8f32
+            | PointAttributeDataType::Array4i32
+            | PointAttributeDataType::Array8i32 => {
+                panic!("Most common value not supported for arrays.")
+            }
         }
     }
     let mut highest_count = 0;
@@ -382,7 +400,6 @@ fn centroid_average_vec<T: PointBuffer>(
                 y_sum += vec.y;
                 z_sum += vec.z;
             }
-            PointAttributeDataType::Vec4u8 => unimplemented!(),
             _ => panic!("Invalid data type for centroid_average_vec"),
         }
     }
@@ -441,13 +458,108 @@ fn centroid_average_num<PB: PointBuffer>(
             PointAttributeDataType::Vec3u16 => panic!("For vector types use centroid_average_vec."),
             PointAttributeDataType::Vec3f32 => panic!("For vector types use centroid_average_vec."),
             PointAttributeDataType::Vec3f64 => panic!("For vector types use centroid_average_vec."),
-            PointAttributeDataType::Vec4u8 => panic!("For vector types use centroid_average_vec."),
+            PointAttributeDataType::Vec4u8
+            | PointAttributeDataType::Vec4u16
+            | PointAttributeDataType::Vec4f32
+            | PointAttributeDataType::Vec4f64 => {
+                panic!("For Vec4 types use centroid_average_components.")
+            }
+            PointAttributeDataType::Array4f32
+            | PointAttributeDataType::Array8f32
+            | PointAttributeDataType::Array4i32
+            | PointAttributeDataType::Array8i32 => {
+                panic!("For array types use centroid_average_components.")
+            }
         }
     }
     let average = sum / v.points.len() as f64;
     average
 }
 
+/// returns the component-wise average value in the voxel for attribute_definition
+/// Vec4 and array types only
+fn centroid_average_components<PB: PointBuffer>(
+    v: &Voxel,
+    buffer: &PB,
+    attribute_definition: &PointAttributeDefinition,
+    point_type: PointAttributeDataType,
+) -> Vec<f64> {
+    let mut sums = vec![0.0; point_type.component_count()];
+    let mut add_components = |components: &[f64]| {
+        for (sum, component) in sums.iter_mut().zip(components) {
+            *sum += *component;
+        }
+    };
+    for p in &v.points {
+        match point_type {
+            PointAttributeDataType::Vec4u8 => {
+                let vec = buffer.get_attribute::<Vector4<u8>>(attribute_definition, *p);
+                add_components(vec.map(|c| c as f64).as_slice());
+            }
+            PointAttributeDataType::Vec4u16 => {
+                let vec = buffer.get_attribute::<Vector4<u16>>(attribute_definition, *p);
+                add_components(vec.map(|c| c as f64).as_slice());
+            }
+            PointAttributeDataType::Vec4f32 => {
+                let vec = buffer.get_attribute::<Vector4<f32>>(attribute_definition, *p);
+                add_components(vec.map(|c| c as f64).as_slice());
+            }
+            PointAttributeDataType::Vec4f64 => {
+                let vec = buffer.get_attribute::<Vector4<f64>>(attribute_definition, *p);
+                add_components(vec.as_slice());
+            }
+            PointAttributeDataType::Array4f32 => {
+                let array = buffer.get_attribute::<[f32; 4]>(attribute_definition, *p);
+                add_components(&array.iter().map(|c| *c as f64).collect::<Vec<_>>());
+            }
+            PointAttributeDataType::Array8f32 => {
+                let array = buffer.get_attribute::<[f32; 8]>(attribute_definition, *p);
+                add_components(&array.iter().map(|c| *c as f64).collect::<Vec<_>>());
+            }
+            PointAttributeDataType::Array4i32 => {
+                let array = buffer.get_attribute::<[i32; 4]>(attribute_definition, *p);
+                add_components(&array.iter().map(|c| *c as f64).collect::<Vec<_>>());
+            }
+            PointAttributeDataType::Array8i32 => {
+                let array = buffer.get_attribute::<[i32; 8]>(attribute_definition, *p);
+                add_components(&array.iter().map(|c| *c as f64).collect::<Vec<_>>());
+            }
+            _ => panic!("Invalid data type for centroid_average_components"),
+        }
+    }
+
+    let num_of_points = v.points.len() as f64;
+    sums.iter().map(|sum| sum / num_of_points).collect()
+}
+
+/// converts the component-wise `average` into the binary representation of `point_type`. Integer components are
+/// rounded to the nearest integer
+fn components_to_bytes(average: &[f64], point_type: PointAttributeDataType) -> Vec<u8> {
+    match point_type.component_type() {
+        PointAttributeDataType::U8 => average
+            .iter()
+            .map(|component| component.round() as u8)
+            .collect(),
+        PointAttributeDataType::U16 => average
+            .iter()
+            .flat_map(|component| (component.round() as u16).to_ne_bytes().to_vec())
+            .collect(),
+        PointAttributeDataType::F64 => average
+            .iter()
+            .flat_map(|component| component.to_ne_bytes().to_vec())
+            .collect(),
+        PointAttributeDataType::F32 => average
+            .iter()
+            .flat_map(|component| (*component as f32).to_ne_bytes().to_vec())
+            .collect(),
+        PointAttributeDataType::I32 => average
+            .iter()
+            .flat_map(|component| (component.round() as i32).to_ne_bytes().to_vec())
+            .collect(),
+        _ => panic!("Invalid data type for components_to_bytes"),
+    }
+}
+
 /// sets all attributes of the point-buffer for the centroid
 /// currently, only standard builtin types work.
 fn set_all_attributes<PB: PointBuffer, PBW: PointBufferWriteable>(
@@ -653,9 +765,25 @@ fn set_all_attributes<PB: PointBuffer, PBW: PointBufferWriteable>(
             let normal_f32 = Vector3::new(normal.x as f32, normal.y as f32, normal.z as f32);
             let nor_slice = unsafe { view_raw_bytes(&normal_f32) };
             &centroid.set_raw_attribute(&attributes::NORMAL, nor_slice);
+        } else if matches!(
+            a.datatype(),
+            PointAttributeDataType::Vec4u8
+                | PointAttributeDataType::Vec4u16
+                | PointAttributeDataType::Vec4f32
+                | PointAttributeDataType::Vec4f64
+                | PointAttributeDataType::Array4f32
+                | PointAttributeDataType::Array8f32
+                | PointAttributeDataType::Array4i32
+                | PointAttributeDataType::Array8i32
+        ) {
+            // Vec4s (e.g. RGBA colors) and arrays (e.g. feature vectors) are averaged component-wise
+            let attribute = PointAttributeDefinition::from(a);
+            let average = centroid_average_components(v, buffer, &attribute, a.datatype());
+            let avg_bytes = components_to_bytes(&average, a.datatype());
+            &centroid.set_raw_attribute(&attribute, &avg_bytes);
         }
         // we have a non-standard attribute -> use max-pooling for numbers and average for vec
-        // currently, only f64 and Vec3f64 is supported
+        // currently, only Vec4s and arrays are supported (see above)
         else {
             if a.datatype() == PointAttributeDataType::Vec3u8
                 || a.datatype() == PointAttributeDataType::Vec3u16
@@ -680,8 +808,8 @@ mod tests {
     use crate::voxel_grid::voxelgrid_filter;
     use pasture_core::{
         containers::{PerAttributeVecPointStorage, PointBuffer, PointBufferExt},
-        layout::{attributes, PointType},
-        nalgebra::Vector3,
+        layout::{attributes, PointAttributeDataType, PointAttributeDefinition, PointType},
+        nalgebra::{Vector3, Vector4},
     };
     use pasture_derive::PointType;
     use rand::{prelude::ThreadRng, Rng};
@@ -914,4 +1042,47 @@ mod tests {
         // most_common bool
         assert!(!filtered.get_attribute::<bool>(&attributes::SCAN_DIRECTION_FLAG, 1));
     }
+
+    #[repr(C)]
+    #[derive(PointType, Debug)]
+    pub struct FeaturePoint {
+        #[pasture(BUILTIN_POSITION_3D)]
+        pub position: Vector3<f64>,
+        #[pasture(attribute = "Features")]
+        pub features: [f32; 4],
+        #[pasture(BUILTIN_COLOR_RGBA)]
+        pub color_rgba: Vector4<u8>,
+    }
+
+    #[test]
+    fn test_voxel_grid_filter_averages_components() {
+        let points = [
+            FeaturePoint {
+                position: Vector3::new(0.0, 0.0, 0.0),
+                features: [1.0, 2.0, 3.0, 4.0],
+                color_rgba: Vector4::new(10, 20, 30, 255),
+            },
+            FeaturePoint {
+                position: Vector3::new(0.2, 0.2, 0.2),
+                features: [3.0, 4.0, 5.0, -6.0],
+                color_rgba: Vector4::new(20, 30, 41, 255),
+            },
+        ];
+        let mut buffer = PerAttributeVecPointStorage::new(FeaturePoint::layout());
+        buffer.push_points(&points);
+        let mut filtered = PerAttributeVecPointStorage::new(buffer.point_layout().clone());
+        voxelgrid_filter(&buffer, 1.0, 1.0, 1.0, &mut filtered);
+        assert_eq!(1, filtered.len());
+
+        let features =
+            PointAttributeDefinition::custom("Features", PointAttributeDataType::Array4f32);
+        assert_eq!(
+            [2.0, 3.0, 4.0, -1.0],
+            filtered.get_attribute::<[f32; 4]>(&features, 0)
+        );
+        assert_eq!(
+            Vector4::new(15, 25, 36, 255),
+            filtered.get_attribute::<Vector4<u8>>(&attributes::COLOR_RGBA, 0)
+        );
+    }
 }
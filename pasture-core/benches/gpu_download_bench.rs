@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pasture_core::{
+    containers::PointBuffer,
+    gpu::{BufferInfoPerAttribute, Device, GpuPointBufferPerAttribute},
+    layout::{attributes, PointAttributeDefinition, PointLayout},
+    testutil::random_point_buffer,
+};
+
+const NUM_POINTS: usize = 1_000_000;
+
+/// Twelve attributes, similar to a LAS point format 8 point with normals
+fn many_attributes() -> Vec<PointAttributeDefinition> {
+    vec![
+        attributes::POSITION_3D,
+        attributes::INTENSITY,
+        attributes::RETURN_NUMBER,
+        attributes::NUMBER_OF_RETURNS,
+        attributes::CLASSIFICATION,
+        attributes::SCAN_ANGLE_RANK,
+        attributes::USER_DATA,
+        attributes::POINT_SOURCE_ID,
+        attributes::COLOR_RGB,
+        attributes::GPS_TIME,
+        attributes::NIR,
+        attributes::NORMAL,
+    ]
+}
+
+fn bench(c: &mut Criterion) {
+    // Skip this benchmark on systems without a suitable GPU
+    let device = match futures::executor::block_on(Device::new(Default::default())) {
+        Ok(device) => device,
+        Err(_) => return,
+    };
+
+    let attributes = many_attributes();
+    let mut points = random_point_buffer(PointLayout::from_attributes(&attributes), NUM_POINTS, 0);
+    let buffer_infos = attributes
+        .iter()
+        .enumerate()
+        .map(|(binding, attribute)| BufferInfoPerAttribute {
+            attribute,
+            binding: binding as u32,
+            extra_usage: wgpu::BufferUsages::empty(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut gpu_point_buffer = GpuPointBufferPerAttribute::new();
+    gpu_point_buffer.malloc(NUM_POINTS as u64, &buffer_infos, &device.wgpu_device);
+    gpu_point_buffer
+        .upload(
+            &points,
+            0..points.len(),
+            &buffer_infos,
+            &device.wgpu_device,
+            &device.wgpu_queue,
+        )
+        .expect("Upload failed");
+
+    let mut group = c.benchmark_group("gpu_download_12_attributes");
+    group.sample_size(20);
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            futures::executor::block_on(gpu_point_buffer.download_into_per_attribute_serial(
+                &mut points,
+                0..NUM_POINTS,
+                &buffer_infos,
+                &device.wgpu_device,
+            ))
+        })
+    });
+    group.bench_function("batched", |b| {
+        b.iter(|| {
+            futures::executor::block_on(gpu_point_buffer.download_into_per_attribute(
+                &mut points,
+                0..NUM_POINTS,
+                &buffer_infos,
+                &device.wgpu_device,
+            ))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
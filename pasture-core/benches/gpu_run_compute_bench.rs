@@ -0,0 +1,69 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pasture_core::{
+    gpu::{BufferInfoPerAttribute, Device},
+    layout::{attributes, PointLayout},
+    testutil::random_point_buffer,
+};
+
+const NUM_POINTS: usize = 1_000;
+
+const SHADER_SRC: &str = "
+    #version 450
+    layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+    layout(std430, set = 0, binding = 0) buffer Intensities {
+        uint intensities[];
+    };
+    void main() {
+        intensities[gl_GlobalInvocationID.x] += 1;
+    }";
+
+fn bench(c: &mut Criterion) {
+    // Skip this benchmark on systems without a suitable GPU
+    let mut device = match futures::executor::block_on(Device::new(Default::default())) {
+        Ok(device) => device,
+        Err(_) => return,
+    };
+
+    let mut points = random_point_buffer(
+        PointLayout::from_attributes(&[attributes::INTENSITY]),
+        NUM_POINTS,
+        0,
+    );
+    let buffer_infos = [BufferInfoPerAttribute {
+        attribute: &attributes::INTENSITY,
+        binding: 0,
+        extra_usage: wgpu::BufferUsages::empty(),
+    }];
+
+    // Many small jobs, as e.g. on a server. Clearing the pool after every job allocates new GPU buffers for each job,
+    // like `run_compute` did before it used pooled buffers
+    let mut group = c.benchmark_group("gpu_run_compute_small_jobs");
+    group.sample_size(20);
+    group.bench_function("pooled", |b| {
+        b.iter(|| {
+            futures::executor::block_on(device.run_compute(
+                &mut points,
+                &buffer_infos,
+                SHADER_SRC,
+                (NUM_POINTS as u32, 1, 1),
+            ))
+            .expect("run_compute failed")
+        })
+    });
+    group.bench_function("unpooled", |b| {
+        b.iter(|| {
+            futures::executor::block_on(device.run_compute(
+                &mut points,
+                &buffer_infos,
+                SHADER_SRC,
+                (NUM_POINTS as u32, 1, 1),
+            ))
+            .expect("run_compute failed");
+            device.clear_pool();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pasture_core::{
+    containers::{InterleavedVecPointStorage, PerAttributeVecPointStorage, PointBuffer},
+    layout::PointType,
+    nalgebra::Vector3,
+};
+use pasture_derive::PointType;
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+#[derive(PointType, Default, Clone, Copy)]
+#[repr(C)]
+struct CustomPointTypeSmall {
+    #[pasture(BUILTIN_POSITION_3D)]
+    pub position: Vector3<f64>,
+    #[pasture(BUILTIN_CLASSIFICATION)]
+    pub classification: u8,
+}
+
+const NUM_BATCHES: usize = 1_000;
+const BATCH_SIZE: usize = 100;
+
+fn get_dummy_batch() -> Vec<CustomPointTypeSmall> {
+    let mut rng = thread_rng();
+    (0..BATCH_SIZE)
+        .map(|_| CustomPointTypeSmall {
+            position: Vector3::new(
+                rng.sample(Uniform::new(-100.0, 100.0)),
+                rng.sample(Uniform::new(-100.0, 100.0)),
+                rng.sample(Uniform::new(-100.0, 100.0)),
+            ),
+            classification: rng.sample(Uniform::new(0u8, 8)),
+        })
+        .collect()
+}
+
+fn push_batches_interleaved(batch: &[CustomPointTypeSmall], reserve: bool) -> usize {
+    let mut buffer = InterleavedVecPointStorage::new(CustomPointTypeSmall::layout());
+    if reserve {
+        buffer.reserve(NUM_BATCHES * batch.len());
+    }
+    for _ in 0..NUM_BATCHES {
+        buffer.push_points(batch);
+    }
+    buffer.len()
+}
+
+fn push_batches_per_attribute(batch: &[CustomPointTypeSmall], reserve: bool) -> usize {
+    let mut buffer = PerAttributeVecPointStorage::new(CustomPointTypeSmall::layout());
+    if reserve {
+        buffer.reserve(NUM_BATCHES * batch.len());
+    }
+    for _ in 0..NUM_BATCHES {
+        buffer.push_points(batch);
+    }
+    buffer.len()
+}
+
+fn bench(c: &mut Criterion) {
+    let batch = get_dummy_batch();
+
+    c.bench_function("push_batches_interleaved", |b| {
+        b.iter(|| push_batches_interleaved(&batch, false))
+    });
+    c.bench_function("push_batches_interleaved_reserved", |b| {
+        b.iter(|| push_batches_interleaved(&batch, true))
+    });
+    c.bench_function("push_batches_per_attribute", |b| {
+        b.iter(|| push_batches_per_attribute(&batch, false))
+    });
+    c.bench_function("push_batches_per_attribute_reserved", |b| {
+        b.iter(|| push_batches_per_attribute(&batch, true))
+    });
+}
+
+criterion_group! {
+    name = point_buffer_push;
+    config = Criterion::default().sample_size(40);
+    targets = bench
+}
+criterion_main!(point_buffer_push);
@@ -122,6 +122,7 @@ mod ex {
             device_backend: gpu::DeviceBackend::Vulkan,
             use_adapter_features: true,
             use_adapter_limits: true,
+            ..Default::default()
         })
         .await;
 
@@ -156,17 +157,20 @@ mod ex {
         let buffer_info_interleaved = gpu::BufferInfoInterleaved {
             attributes: attribs,
             binding: 0,
+            extra_usage: wgpu::BufferUsages::empty(),
         };
 
         let mut gpu_point_buffer = GpuPointBufferInterleaved::new();
-        gpu_point_buffer.malloc(3, &buffer_info_interleaved, &mut device.wgpu_device);
-        gpu_point_buffer.upload(
-            &point_buffer,
-            0..point_buffer.len(),
-            &buffer_info_interleaved,
-            &mut device.wgpu_device,
-            &device.wgpu_queue,
-        );
+        gpu_point_buffer.malloc(3, &buffer_info_interleaved, &device.wgpu_device);
+        gpu_point_buffer
+            .upload(
+                &point_buffer,
+                0..point_buffer.len(),
+                &buffer_info_interleaved,
+                &device.wgpu_device,
+                &device.wgpu_queue,
+            )
+            .expect("Failed to upload points to the GPU");
 
         device.set_bind_group(
             0,
@@ -191,7 +195,8 @@ mod ex {
                 &buffer_info_interleaved,
                 &device.wgpu_device,
             )
-            .await;
+            .await
+            .expect("Failed to download points from the GPU");
 
         println!("After:");
         for point in point_buffer.iter_point::<MyPointType>() {
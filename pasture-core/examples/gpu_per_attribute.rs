@@ -3,7 +3,6 @@ mod ex {
 
     use pasture_core::containers::{PerAttributeVecPointStorage, PointBufferExt};
     use pasture_core::gpu;
-    use pasture_core::gpu::GpuPointBufferPerAttribute;
     use pasture_core::layout::PointType;
     use pasture_core::layout::{attributes, PointAttributeDataType, PointAttributeDefinition};
     use pasture_core::nalgebra::Vector3;
@@ -122,6 +121,7 @@ mod ex {
             device_backend: gpu::DeviceBackend::Vulkan,
             use_adapter_features: true,
             use_adapter_limits: true,
+            ..Default::default()
         })
         .await;
 
@@ -143,70 +143,65 @@ mod ex {
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::POSITION_3D,
                 binding: 0,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::COLOR_RGB,
                 binding: 1,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &custom_color_attrib,
                 binding: 2,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &custom_byte_vec_attrib,
                 binding: 3,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::CLASSIFICATION,
                 binding: 4,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::INTENSITY,
                 binding: 5,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::SCAN_ANGLE,
                 binding: 6,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::SCAN_DIRECTION_FLAG,
                 binding: 7,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &custom_int_attrib,
                 binding: 8,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::WAVEFORM_PACKET_SIZE,
                 binding: 9,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::RETURN_POINT_WAVEFORM_LOCATION,
                 binding: 10,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::GPS_TIME,
                 binding: 11,
+                extra_usage: wgpu::BufferUsages::empty(),
             },
         ];
 
-        let mut gpu_point_buffer = GpuPointBufferPerAttribute::new();
-        gpu_point_buffer.malloc(3, &buffer_infos, &mut device.wgpu_device);
-        gpu_point_buffer.upload(
-            &mut point_buffer,
-            0..3,
-            &buffer_infos,
-            &mut device.wgpu_device,
-            &device.wgpu_queue,
-        );
-
-        device.set_bind_group(
-            0,
-            gpu_point_buffer.bind_group_layout.as_ref().unwrap(),
-            gpu_point_buffer.bind_group.as_ref().unwrap(),
-        );
-        device.set_compute_shader_glsl(include_str!("shaders/per_attribute.comp"));
-        device.compute(1, 1, 1);
         println!("\n===== COMPUTE =====\n");
 
         println!("Before:");
@@ -215,14 +210,16 @@ mod ex {
         }
         println!();
 
-        gpu_point_buffer
-            .download_into_per_attribute(
+        // Uploads the points, runs the shader and writes the results back into the point buffer
+        device
+            .run_compute(
                 &mut point_buffer,
-                0..3,
                 &buffer_infos,
-                &device.wgpu_device,
+                include_str!("shaders/per_attribute.comp"),
+                (1, 1, 1),
             )
-            .await;
+            .await
+            .expect("Failed to run compute shader");
 
         println!("After:");
         for point in point_buffer.iter_point::<MyPointType>() {
@@ -107,7 +107,8 @@ pub mod attr1 {
         buffer: &'a B,
         source_attribute: PointAttributeDefinition,
         current_index: usize,
-        converter: AttributeConversionFn,
+        /// `None` if the attribute already has the requested datatype
+        converter: Option<AttributeConversionFn>,
         source_attribute_buffer: Vec<u8>,
         _unused: PhantomData<T>,
     }
@@ -128,10 +129,10 @@ pub mod attr1 {
                 ),
             };
 
-            let converter = match get_converter_for_attributes(&source_attribute.into(), target_attribute) {
-                Some(c) => c,
-                None => panic!("Can't convert from attribute {} to attribute {} because no valid conversion exists", source_attribute, target_attribute),
-            };
+            let converter = get_converter_for_attributes(&source_attribute.into(), target_attribute);
+            if converter.is_none() && source_attribute.datatype() != target_attribute.datatype() {
+                panic!("Can't convert from attribute {} to attribute {} because no valid conversion exists", source_attribute, target_attribute);
+            }
 
             Self {
                 buffer,
@@ -160,16 +161,24 @@ pub mod attr1 {
                     target_attribute.as_mut_ptr() as *mut u8,
                     std::mem::size_of::<T>(),
                 );
-                self.buffer.get_raw_attribute(
-                    self.current_index,
-                    &self.source_attribute,
-                    self.source_attribute_buffer.as_mut_slice(),
-                );
-
-                (self.converter)(
-                    self.source_attribute_buffer.as_slice(),
-                    target_attribute_byte_slice,
-                );
+                match self.converter {
+                    Some(converter) => {
+                        self.buffer.get_raw_attribute(
+                            self.current_index,
+                            &self.source_attribute,
+                            self.source_attribute_buffer.as_mut_slice(),
+                        );
+                        converter(
+                            self.source_attribute_buffer.as_slice(),
+                            target_attribute_byte_slice,
+                        );
+                    }
+                    None => self.buffer.get_raw_attribute(
+                        self.current_index,
+                        &self.source_attribute,
+                        target_attribute_byte_slice,
+                    ),
+                }
             }
 
             self.current_index += 1;
@@ -0,0 +1,169 @@
+use std::convert::TryInto;
+
+use crate::layout::{PointAttributeDataType, PointAttributeDefinition};
+
+use super::PointBuffer;
+
+/// Returns `true` if the two buffers `a` and `b` store the same point data. This is the case if both buffers have the same
+/// number of points, their `PointLayout`s contain the same attributes with the same datatypes, and the bytes of all attributes
+/// of all points are equal. The memory layout of the buffers (e.g. Interleaved or PerAttribute) and the offsets of the
+/// attributes within the `PointLayout`s are irrelevant for the comparison, so any two buffers with the same point data are
+/// considered equal:
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// let layout = PointLayout::from_attributes(&[attributes::INTENSITY]);
+/// let mut interleaved = InterleavedVecPointStorage::new(layout.clone());
+/// interleaved.resize(2);
+/// let mut per_attribute = PerAttributeVecPointStorage::new(layout);
+/// per_attribute.resize(2);
+/// assert!(buffers_equal(&interleaved, &per_attribute));
+///
+/// per_attribute.set_attribute(&attributes::INTENSITY, 1, 42_u16);
+/// assert!(!buffers_equal(&interleaved, &per_attribute));
+/// ```
+pub fn buffers_equal(a: &dyn PointBuffer, b: &dyn PointBuffer) -> bool {
+    compare_buffers(a, b, |_, a_bytes, b_bytes| a_bytes == b_bytes)
+}
+
+/// Like [buffers_equal], but compares all floating-point attributes with the given `epsilon` tolerance. Two values of a
+/// floating-point attribute are considered equal if each of their components differs by at most `epsilon`. All other
+/// attributes are compared exactly. This is useful for comparing the results of lossy file formats, e.g. point positions
+/// that were quantized to integer coordinates
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_core::nalgebra::Vector3;
+/// let layout = PointLayout::from_attributes(&[attributes::POSITION_3D]);
+/// let mut a = InterleavedVecPointStorage::new(layout.clone());
+/// a.resize(1);
+/// let mut b = InterleavedVecPointStorage::new(layout);
+/// b.resize(1);
+/// b.set_attribute(&attributes::POSITION_3D, 0, Vector3::new(0.001, 0.0, 0.0));
+///
+/// assert!(buffers_approx_equal(&a, &b, 0.01));
+/// assert!(!buffers_approx_equal(&a, &b, 0.0001));
+/// ```
+pub fn buffers_approx_equal(a: &dyn PointBuffer, b: &dyn PointBuffer, epsilon: f64) -> bool {
+    compare_buffers(a, b, |attribute, a_bytes, b_bytes| {
+        let component_type = attribute.datatype().component_type();
+        match component_type {
+            PointAttributeDataType::F32 | PointAttributeDataType::F64 => {
+                let component_size = component_type.size() as usize;
+                a_bytes
+                    .chunks_exact(component_size)
+                    .zip(b_bytes.chunks_exact(component_size))
+                    .all(|(a_component, b_component)| {
+                        let a_value = float_from_bytes(a_component);
+                        let b_value = float_from_bytes(b_component);
+                        (a_value - b_value).abs() <= epsilon
+                    })
+            }
+            _ => a_bytes == b_bytes,
+        }
+    })
+}
+
+fn float_from_bytes(bytes: &[u8]) -> f64 {
+    match bytes.len() {
+        4 => f32::from_ne_bytes(bytes.try_into().unwrap()) as f64,
+        8 => f64::from_ne_bytes(bytes.try_into().unwrap()),
+        _ => panic!("Invalid size {} of floating-point value", bytes.len()),
+    }
+}
+
+fn compare_buffers<F: Fn(&PointAttributeDefinition, &[u8], &[u8]) -> bool>(
+    a: &dyn PointBuffer,
+    b: &dyn PointBuffer,
+    attributes_equal: F,
+) -> bool {
+    if a.len() != b.len() || !a.point_layout().compare_without_offsets(b.point_layout()) {
+        return false;
+    }
+
+    a.point_layout().attributes().all(|member| {
+        let attribute: PointAttributeDefinition = member.into();
+        let attribute_size = attribute.size() as usize;
+        let mut a_bytes = vec![0; a.len() * attribute_size];
+        let mut b_bytes = vec![0; b.len() * attribute_size];
+        a.get_raw_attribute_range(0..a.len(), &attribute, &mut a_bytes);
+        b.get_raw_attribute_range(0..b.len(), &attribute, &mut b_bytes);
+
+        a_bytes
+            .chunks_exact(attribute_size)
+            .zip(b_bytes.chunks_exact(attribute_size))
+            .all(|(a_value, b_value)| attributes_equal(&attribute, a_value, b_value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        containers::{InterleavedVecPointStorage, PerAttributeVecPointStorage},
+        layout::{attributes, PointLayout, PointType},
+    };
+    use nalgebra::Vector3;
+    use pasture_derive::PointType;
+
+    use crate as pasture_core;
+
+    #[derive(PointType, Debug, Clone, Copy, PartialEq)]
+    #[repr(C)]
+    struct TestPoint {
+        #[pasture(BUILTIN_POSITION_3D)]
+        position: Vector3<f64>,
+        #[pasture(BUILTIN_INTENSITY)]
+        intensity: u16,
+    }
+
+    fn test_points() -> Vec<TestPoint> {
+        (0..10)
+            .map(|idx| TestPoint {
+                position: Vector3::new(idx as f64, 2.0 * idx as f64, 0.5),
+                intensity: idx as u16,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_buffers_equal() {
+        let interleaved: InterleavedVecPointStorage = test_points().into();
+        let per_attribute: PerAttributeVecPointStorage = test_points().into();
+        assert!(buffers_equal(&interleaved, &per_attribute));
+
+        let mut different_points = test_points();
+        different_points[7].intensity = 42;
+        let different: PerAttributeVecPointStorage = different_points.into();
+        assert!(!buffers_equal(&interleaved, &different));
+
+        let shorter: InterleavedVecPointStorage = test_points()[..9].to_vec().into();
+        assert!(!buffers_equal(&interleaved, &shorter));
+
+        let other_layout = InterleavedVecPointStorage::new(PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+        ]));
+        let empty = InterleavedVecPointStorage::new(TestPoint::layout());
+        assert!(!buffers_equal(&other_layout, &empty));
+    }
+
+    #[test]
+    fn test_buffers_approx_equal() {
+        let interleaved: InterleavedVecPointStorage = test_points().into();
+
+        let mut close_points = test_points();
+        close_points[3].position.x += 1e-6;
+        let close: InterleavedVecPointStorage = close_points.into();
+        assert!(!buffers_equal(&interleaved, &close));
+        assert!(buffers_approx_equal(&interleaved, &close, 1e-5));
+        assert!(!buffers_approx_equal(&interleaved, &close, 1e-7));
+
+        // Non-float attributes are still compared exactly
+        let mut different_points = test_points();
+        different_points[3].intensity += 1;
+        let different: InterleavedVecPointStorage = different_points.into();
+        assert!(!buffers_approx_equal(&interleaved, &different, 10.0));
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    layout::{
+        conversion::get_converter_for_attributes, PointAttributeDefinition, PointLayout,
+        PrimitiveType,
+    },
+    util::view_raw_bytes,
+};
+
+use super::{
+    PerAttributePointBufferMut, PerAttributeVecPointStorage, PointBuffer, PointBufferWriteable,
+};
+
+/// A typed default value for a single point attribute. Default values are used whenever point data has to be created for
+/// an attribute that the source data does not provide, for example when concatenating buffers with different `PointLayout`s.
+/// Without an explicit default, missing attributes are zero-initialized, which is not always meaningful. As an example, a
+/// `CLASSIFICATION` of zero means 'never classified' in the LAS specification, so it is often better to use 1 ('unclassified'):
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// let default_classification = AttributeDefault::new(&attributes::CLASSIFICATION, 1_u8);
+/// assert_eq!(&[1], default_classification.value_bytes());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeDefault {
+    attribute: PointAttributeDefinition,
+    value_bytes: Vec<u8>,
+}
+
+impl AttributeDefault {
+    /// Creates a new `AttributeDefault` for the given `attribute` with the given default `value`
+    ///
+    /// # Panics
+    ///
+    /// If the datatype of `T` does not match the datatype of `attribute`
+    pub fn new<T: PrimitiveType>(attribute: &PointAttributeDefinition, value: T) -> Self {
+        if T::data_type() != attribute.datatype() {
+            panic!(
+                "Type T ({}) does not match datatype of attribute {}",
+                T::data_type(),
+                attribute
+            );
+        }
+        let value_bytes = unsafe { view_raw_bytes(&value) }.to_vec();
+        Self {
+            attribute: attribute.clone(),
+            value_bytes,
+        }
+    }
+
+    /// Returns the attribute that the associated `AttributeDefault` refers to
+    pub fn attribute(&self) -> &PointAttributeDefinition {
+        &self.attribute
+    }
+
+    /// Returns the default value of the associated `AttributeDefault` as raw bytes
+    pub fn value_bytes(&self) -> &[u8] {
+        &self.value_bytes
+    }
+}
+
+/// Concatenates all `buffers` into a single `PerAttributeVecPointStorage` with the given `target_layout`. The buffers
+/// may have different `PointLayout`s. Attributes with the same name but a different datatype are converted into the
+/// datatype of the `target_layout`. If a buffer does not contain an attribute of `target_layout`, the values for this
+/// attribute are taken from the matching entry in `defaults` (matched by attribute name), or are zero-initialized if
+/// there is no matching default.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// let mut positions =
+///     InterleavedVecPointStorage::new(PointLayout::from_attributes(&[attributes::POSITION_3D]));
+/// positions.resize(1);
+/// let target_layout =
+///     PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::CLASSIFICATION]);
+/// let concatenated = concatenate_buffers(
+///     &[&positions, &positions],
+///     &target_layout,
+///     &[AttributeDefault::new(&attributes::CLASSIFICATION, 1_u8)],
+/// );
+/// assert_eq!(2, concatenated.len());
+/// assert_eq!(1_u8, concatenated.get_attribute(&attributes::CLASSIFICATION, 1));
+/// ```
+///
+/// # Panics
+///
+/// If any attribute in `target_layout` can't be converted from the attribute with the same name in one of the `buffers`,
+/// or if the datatype of an entry in `defaults` does not match the datatype of the attribute in `target_layout`
+pub fn concatenate_buffers(
+    buffers: &[&dyn PointBuffer],
+    target_layout: &PointLayout,
+    defaults: &[AttributeDefault],
+) -> PerAttributeVecPointStorage {
+    let total_points = buffers.iter().map(|buffer| buffer.len()).sum();
+    let mut target =
+        PerAttributeVecPointStorage::with_capacity(total_points, target_layout.clone());
+    target.resize(total_points);
+
+    for target_member in target_layout.attributes() {
+        let target_attribute: PointAttributeDefinition = target_member.into();
+        let default = defaults
+            .iter()
+            .find(|default| default.attribute().name() == target_attribute.name());
+        if let Some(default) = default {
+            if default.attribute().datatype() != target_attribute.datatype() {
+                panic!(
+                    "Datatype of default value for attribute {} does not match the datatype in the target layout",
+                    target_attribute
+                );
+            }
+        }
+
+        let mut point_offset = 0;
+        for buffer in buffers {
+            let range = point_offset..(point_offset + buffer.len());
+            point_offset += buffer.len();
+            let target_data = target.get_raw_attribute_range_mut(range, &target_attribute);
+
+            match buffer
+                .point_layout()
+                .get_attribute_by_name(target_attribute.name())
+            {
+                Some(source_member) => {
+                    let source_attribute: PointAttributeDefinition = source_member.into();
+                    match get_converter_for_attributes(&source_attribute, &target_attribute) {
+                        None => {
+                            buffer.get_raw_attribute_range(
+                                0..buffer.len(),
+                                &target_attribute,
+                                target_data,
+                            );
+                        }
+                        Some(converter) => {
+                            let mut source_data = vec![0; source_attribute.size() as usize];
+                            for (point_index, target_value) in target_data
+                                .chunks_exact_mut(target_attribute.size() as usize)
+                                .enumerate()
+                            {
+                                buffer.get_raw_attribute(
+                                    point_index,
+                                    &source_attribute,
+                                    &mut source_data,
+                                );
+                                unsafe {
+                                    converter(&source_data, target_value);
+                                }
+                            }
+                        }
+                    }
+                }
+                None => {
+                    if let Some(default) = default {
+                        for target_value in
+                            target_data.chunks_exact_mut(target_attribute.size() as usize)
+                        {
+                            target_value.copy_from_slice(default.value_bytes());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        containers::PointBufferExt,
+        layout::{attributes, PointType},
+    };
+    use nalgebra::{Vector3, Vector4};
+    use pasture_derive::PointType;
+
+    use crate as pasture_core;
+
+    #[derive(PointType, Debug, Clone, Copy, PartialEq)]
+    #[repr(C)]
+    struct PositionOnly {
+        #[pasture(BUILTIN_POSITION_3D)]
+        position: Vector3<f64>,
+    }
+
+    #[derive(PointType, Debug, Clone, Copy, PartialEq)]
+    #[repr(C)]
+    struct PositionClassColor {
+        #[pasture(BUILTIN_POSITION_3D)]
+        position: Vector3<f64>,
+        #[pasture(BUILTIN_CLASSIFICATION)]
+        classification: u8,
+        #[pasture(BUILTIN_COLOR_RGBA)]
+        color: Vector4<u8>,
+    }
+
+    #[test]
+    fn test_concatenate_buffers_uses_defaults_for_missing_attributes() {
+        let first: PerAttributeVecPointStorage = vec![
+            PositionOnly {
+                position: Vector3::new(1.0, 2.0, 3.0),
+            },
+            PositionOnly {
+                position: Vector3::new(4.0, 5.0, 6.0),
+            },
+        ]
+        .into_iter()
+        .collect();
+        let second_points = vec![PositionClassColor {
+            position: Vector3::new(7.0, 8.0, 9.0),
+            classification: 2,
+            color: Vector4::new(10, 20, 30, 40),
+        }];
+        let mut second = PerAttributeVecPointStorage::new(PositionClassColor::layout());
+        second.push_points(&second_points);
+
+        let defaults = [
+            AttributeDefault::new(&attributes::CLASSIFICATION, 1_u8),
+            AttributeDefault::new(&attributes::COLOR_RGBA, Vector4::new(0_u8, 0, 0, 255)),
+        ];
+        let concatenated =
+            concatenate_buffers(&[&first, &second], &PositionClassColor::layout(), &defaults);
+
+        let expected = vec![
+            PositionClassColor {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                classification: 1,
+                color: Vector4::new(0, 0, 0, 255),
+            },
+            PositionClassColor {
+                position: Vector3::new(4.0, 5.0, 6.0),
+                classification: 1,
+                color: Vector4::new(0, 0, 0, 255),
+            },
+            second_points[0],
+        ];
+        let actual = concatenated
+            .iter_point::<PositionClassColor>()
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_concatenate_buffers_zero_initializes_without_defaults() {
+        let first: PerAttributeVecPointStorage = vec![PositionOnly {
+            position: Vector3::new(1.0, 2.0, 3.0),
+        }]
+        .into_iter()
+        .collect();
+
+        let layout =
+            PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::CLASSIFICATION]);
+        let concatenated = concatenate_buffers(&[&first], &layout, &[]);
+        assert_eq!(1, concatenated.len());
+        assert_eq!(
+            0_u8,
+            concatenated.get_attribute::<u8>(&attributes::CLASSIFICATION, 0)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_attribute_default_with_wrong_type() {
+        AttributeDefault::new(&attributes::CLASSIFICATION, 1_u16);
+    }
+}
@@ -35,3 +35,12 @@ pub use self::slice_buffers::*;
 
 mod untyped_point;
 pub use self::untyped_point::*;
+
+mod position_downcast;
+pub use self::position_downcast::*;
+
+mod concatenation;
+pub use self::concatenation::*;
+
+mod comparison;
+pub use self::comparison::*;
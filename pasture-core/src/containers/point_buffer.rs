@@ -1,11 +1,13 @@
-use std::{mem::MaybeUninit, ops::Range};
+use std::{any::TypeId, cell::RefCell, collections::HashMap, mem::MaybeUninit, ops::Range};
+
+use anyhow::{anyhow, bail, Result};
 
 use crate::{
     layout::{
-        conversion::get_converter_for_attributes, PointAttributeDefinition, PointLayout, PointType,
-        PrimitiveType,
+        conversion::get_converter_for_attributes, PointAttributeDataType, PointAttributeDefinition,
+        PointLayout, PointType, PrimitiveType,
     },
-    util::view_raw_bytes,
+    util::{view_raw_bytes, view_raw_bytes_mut},
 };
 
 use super::{
@@ -16,7 +18,8 @@ use super::{
     iterators::PointIteratorByMut,
     iterators::PointIteratorByRef,
     iterators::PointIteratorByValue,
-    PerAttributePointBufferSlice, PerAttributePointBufferSliceMut,
+    InterleavedPointBufferSlice, InterleavedVecPointStorage, PerAttributePointBufferSlice,
+    PerAttributePointBufferSliceMut,
 };
 
 // TODO Can we maybe impl<T: PointBufferWriteable> &T and provide some push<U> methods?
@@ -64,13 +67,29 @@ pub trait PointBuffer {
     }
     /// Returns a reference to the underlying PointLayout of this PointBuffer
     fn point_layout(&self) -> &PointLayout;
+    /// Returns the number of bytes that the points in this PointBuffer occupy, i.e. the number of points multiplied by
+    /// the size of a single point in the PointLayout. For buffers that own their memory, this does not include memory
+    /// that is reserved but not used, see `capacity_bytes` for that
+    fn memory_size_bytes(&self) -> usize {
+        self.len() * self.point_layout().size_of_point_entry() as usize
+    }
+    /// Returns the number of bytes that this PointBuffer has allocated for storing point data, including reserved memory
+    /// that is not used yet. This is always at least `memory_size_bytes`. Buffers that don't own their memory (such as
+    /// slices and views) return the same value as `memory_size_bytes`
+    fn capacity_bytes(&self) -> usize {
+        self.memory_size_bytes()
+    }
 
-    /// Try to downcast the associated `PointBuffer` into an `InterleavedPointBuffer`
+    /// Try to downcast the associated `PointBuffer` into an `InterleavedPointBuffer`. Generic code can use this to
+    /// access the point memory without copying it if the buffer supports it. Returns `None` for all buffers that
+    /// don't store their points interleaved
     fn as_interleaved(&self) -> Option<&dyn InterleavedPointBuffer> {
         None
     }
 
-    /// Try to downcast the associated `PointBuffer` into an `PerAttributePointBuffer`
+    /// Try to downcast the associated `PointBuffer` into a `PerAttributePointBuffer`. Generic code can use this to
+    /// access the memory of an attribute without copying it if the buffer supports it. Returns `None` for all buffers
+    /// that don't store their points per attribute
     fn as_per_attribute(&self) -> Option<&dyn PerAttributePointBuffer> {
         None
     }
@@ -249,6 +268,16 @@ pub trait PerAttributePointBufferMut<'b>: PerAttributePointBuffer {
 pub trait PointBufferExt<B: PointBuffer + ?Sized> {
     /// Returns the point at `index` from the associated `PointBuffer`, strongly typed to the `PointType` `T`
     fn get_point<T: PointType>(&self, index: usize) -> T;
+    /// Copies the point at `index` from the associated `PointBuffer` into `out`, strongly typed to the `PointType` `T`. In
+    /// contrast to [get_point](PointBufferExt::get_point), the point is not returned by value, so a single `T` can be
+    /// reused when reading many points in a loop. The `PointLayout` of `T` is only computed once per thread, so this
+    /// method doesn't allocate when it is called repeatedly
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds, or if the `PointLayout` of `T` does not match the `PointLayout` of the associated
+    /// `PointBuffer`
+    fn get_point_into<T: PointType + 'static>(&self, index: usize, out: &mut T);
     /// Returns the given `attribute` for the point at `index` from the associated `PointBuffer`, strongly typed to the `PrimitiveType` `T`
     fn get_attribute<T: PrimitiveType>(
         &self,
@@ -256,7 +285,41 @@ pub trait PointBufferExt<B: PointBuffer + ?Sized> {
         index: usize,
     ) -> T;
 
-    /// Returns an iterator over all points in the associated `PointBuffer`, strongly typed to the `PointType` `T`
+    /// Returns the given `attribute` for the point at `index` from the associated `PointBuffer` as an `f64`, no matter
+    /// which scalar numeric type the attribute is stored as inside the buffer. The datatype of `attribute` is ignored,
+    /// only its name is used. Use this for algorithms that process arbitrary numeric attributes (e.g. statistics or
+    /// colorization) without having to dispatch on the datatype themselves.
+    ///
+    /// ```
+    /// # use pasture_core::containers::*;
+    /// # use pasture_core::layout::*;
+    /// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+    /// let mut pusher = buffer.begin_push_attributes();
+    /// pusher.push_attribute_range(&attributes::INTENSITY, &[42_u16]);
+    /// pusher.done();
+    /// assert_eq!(42.0, buffer.get_attribute_as_f64(&attributes::INTENSITY, 0).unwrap());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If `attribute` is not part of the `PointLayout` of the buffer, or if it is stored as a non-numeric or vector type
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds
+    fn get_attribute_as_f64(
+        &self,
+        attribute: &PointAttributeDefinition,
+        index: usize,
+    ) -> Result<f64>;
+    /// Returns an iterator over all points in the associated `PointBuffer`, strongly typed to the `PointType` `T`. The
+    /// `PointLayout` of `T` is validated once when creating the iterator, after which each point is copied into a `T`.
+    /// For interleaved buffers this is a single copy per point, for per-attribute buffers the attributes are gathered
+    /// into the point.
+    ///
+    /// # Panics
+    ///
+    /// If the `PointLayout` of `T` does not match the `PointLayout` of the associated `PointBuffer`
     fn iter_point<T: PointType>(&self) -> PointIteratorByValue<'_, T, B>;
     /// Returns an iterator over the given `attribute` of all points in the associated `PointBuffer`, strongly typed to the `PrimitiveType` `T`.
     ///
@@ -272,7 +335,8 @@ pub trait PointBufferExt<B: PointBuffer + ?Sized> {
     ) -> AttributeIteratorByValue<'a, T, B>;
     /// Returns an iterator over the given `attribute` of all points in the associated `PointBuffer`, converted to the `PrimitiveType` `T`. This iterator
     /// supports conversion of types, so it works even if the `attribute` inside the buffer is stored as some other type `U`, as long as there is a valid
-    /// conversion from `U` to `T`. Regarding conversions, see the [conversions module](crate::layout::conversion).
+    /// conversion from `U` to `T`. Regarding conversions, see the [conversions module](crate::layout::conversion). If the
+    /// attribute is already stored as `T`, the values are returned without any conversion.
     ///
     /// For iterating over multiple attributes at once, use the [attributes!] macro.
     ///
@@ -284,6 +348,26 @@ pub trait PointBufferExt<B: PointBuffer + ?Sized> {
         &'a self,
         attribute: &'a PointAttributeDefinition,
     ) -> AttributeIteratorByValueWithConversion<'a, T, B>;
+
+    /// Splits the associated `PointBuffer` into `n` chunks of roughly equal size, e.g. to process the chunks in parallel.
+    /// All chunks have the same `PointLayout` as the associated `PointBuffer`. The last chunk also contains the remaining
+    /// points if the number of points is not divisible by `n`. If the associated `PointBuffer` is an `InterleavedPointBuffer`
+    /// or a `PerAttributePointBuffer`, the chunks are non-owning slices into the buffer, otherwise the point data is copied
+    /// into the chunks.
+    ///
+    /// ```
+    /// # use pasture_core::containers::*;
+    /// # use pasture_core::layout::*;
+    /// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+    /// buffer.resize(10);
+    /// let chunk_sizes = buffer.split_chunks(3).iter().map(|chunk| chunk.len()).collect::<Vec<_>>();
+    /// assert_eq!(vec![3, 3, 4], chunk_sizes);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero
+    fn split_chunks(&self, n: usize) -> Vec<Box<dyn PointBuffer + '_>>;
 }
 
 impl<B: PointBuffer + ?Sized> PointBufferExt<B> for B {
@@ -301,6 +385,13 @@ impl<B: PointBuffer + ?Sized> PointBufferExt<B> for B {
         }
     }
 
+    fn get_point_into<T: PointType + 'static>(&self, index: usize, out: &mut T) {
+        assert_point_type_matches_layout::<T>(self.point_layout());
+        unsafe {
+            self.get_raw_point(index, view_raw_bytes_mut(out));
+        }
+    }
+
     fn get_attribute<T: PrimitiveType>(
         &self,
         attribute: &PointAttributeDefinition,
@@ -320,6 +411,42 @@ impl<B: PointBuffer + ?Sized> PointBufferExt<B> for B {
         }
     }
 
+    fn get_attribute_as_f64(
+        &self,
+        attribute: &PointAttributeDefinition,
+        index: usize,
+    ) -> Result<f64> {
+        let datatype = self
+            .point_layout()
+            .get_attribute_by_name(attribute.name())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Attribute {} is not part of the PointLayout of the buffer",
+                    attribute.name()
+                )
+            })?
+            .datatype();
+        let attribute = attribute.with_custom_datatype(datatype);
+        let value = match datatype {
+            PointAttributeDataType::U8 => self.get_attribute::<u8>(&attribute, index) as f64,
+            PointAttributeDataType::I8 => self.get_attribute::<i8>(&attribute, index) as f64,
+            PointAttributeDataType::U16 => self.get_attribute::<u16>(&attribute, index) as f64,
+            PointAttributeDataType::I16 => self.get_attribute::<i16>(&attribute, index) as f64,
+            PointAttributeDataType::U32 => self.get_attribute::<u32>(&attribute, index) as f64,
+            PointAttributeDataType::I32 => self.get_attribute::<i32>(&attribute, index) as f64,
+            PointAttributeDataType::U64 => self.get_attribute::<u64>(&attribute, index) as f64,
+            PointAttributeDataType::I64 => self.get_attribute::<i64>(&attribute, index) as f64,
+            PointAttributeDataType::F32 => self.get_attribute::<f32>(&attribute, index) as f64,
+            PointAttributeDataType::F64 => self.get_attribute::<f64>(&attribute, index),
+            other => bail!(
+                "Attribute {} has datatype {}, which is not a scalar numeric type",
+                attribute.name(),
+                other
+            ),
+        };
+        Ok(value)
+    }
+
     fn iter_point<T: PointType>(&self) -> PointIteratorByValue<'_, T, B> {
         PointIteratorByValue::new(self)
     }
@@ -337,6 +464,40 @@ impl<B: PointBuffer + ?Sized> PointBufferExt<B> for B {
     ) -> AttributeIteratorByValueWithConversion<'a, T, B> {
         AttributeIteratorByValueWithConversion::new(self, attribute)
     }
+
+    fn split_chunks(&self, n: usize) -> Vec<Box<dyn PointBuffer + '_>> {
+        if n == 0 {
+            panic!("PointBufferExt::split_chunks: Number of chunks must be greater than zero");
+        }
+        let chunk_size = self.len() / n;
+        let mut chunks: Vec<Box<dyn PointBuffer + '_>> = Vec::with_capacity(n);
+        for chunk_index in 0..n {
+            let start = chunk_index * chunk_size;
+            let end = if chunk_index == n - 1 {
+                self.len()
+            } else {
+                start + chunk_size
+            };
+
+            if let Some(interleaved) = self.as_interleaved() {
+                chunks.push(Box::new(InterleavedPointBufferSlice::new(
+                    interleaved,
+                    start..end,
+                )));
+            } else if let Some(per_attribute) = self.as_per_attribute() {
+                chunks.push(Box::new(per_attribute.slice(start..end)));
+            } else {
+                let mut chunk = InterleavedVecPointStorage::with_capacity(
+                    end - start,
+                    self.point_layout().clone(),
+                );
+                chunk.resize(end - start);
+                self.get_raw_points(start..end, chunk.get_raw_points_mut(0..(end - start)));
+                chunks.push(Box::new(chunk));
+            }
+        }
+        chunks
+    }
 }
 
 /// Extension trait that provides generic methods for manipulating point and attribute data in a `PointBufferWriteable`
@@ -488,20 +649,52 @@ impl<B: PointBufferWriteable + ?Sized> PointBufferWriteableExt<B> for B {
     }
 }
 
+thread_local! {
+    /// `PointLayout`s of the `PointType`s that were used for typed access to point buffers on this thread
+    static POINT_TYPE_LAYOUTS: RefCell<HashMap<TypeId, PointLayout>> = RefCell::new(HashMap::new());
+}
+
+/// Returns `true` if the `PointLayout` of `T` equals `buffer_layout`. Since `T::layout()` allocates, the layout of
+/// each `PointType` is only computed once per thread, so that typed accessors for single points stay cheap
+fn point_type_matches_layout<T: PointType + 'static>(buffer_layout: &PointLayout) -> bool {
+    POINT_TYPE_LAYOUTS.with(|layouts| {
+        *layouts
+            .borrow_mut()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(T::layout)
+            == *buffer_layout
+    })
+}
+
+/// Panics if the `PointLayout` of `T` does not match `buffer_layout`. Reinterpreting the memory of a buffer as `T` is only
+/// valid if both layouts are equal
+fn assert_point_type_matches_layout<T: PointType + 'static>(buffer_layout: &PointLayout) {
+    if !point_type_matches_layout::<T>(buffer_layout) {
+        panic!(
+            "PointLayout of type T does not match PointLayout of buffer (buffer layout: {}, T layout: {})",
+            buffer_layout,
+            T::layout()
+        );
+    }
+}
+
 /// Extension trait that provides generic methods for accessing point data in an `InterleavedPointBuffer`
 pub trait InterleavedPointBufferExt {
-    /// Returns a reference to the point at `point_index` from the associated `InterleavedPointBuffer`, strongly typed to the `PointType` `T`
+    /// Returns a reference to the point at `point_index` from the associated `InterleavedPointBuffer`, strongly typed to the `PointType` `T`.
+    /// In contrast to [get_point](PointBufferExt::get_point), the point is not copied, which makes this the fastest way of reading
+    /// typed points from an `InterleavedPointBuffer`.
     ///
     /// # Panics
     ///
-    /// Panics if `point_index` is >= `self.len()`
-    fn get_point_ref<T: PointType>(&self, point_index: usize) -> &T;
+    /// Panics if `point_index` is >= `self.len()`, or if the associated `InterleavedPointBuffer` does not store points with type `T`
+    fn get_point_ref<T: PointType + 'static>(&self, point_index: usize) -> &T;
     /// Returns a reference to the given `range` of points from the associated `InterleavedPointBuffer`, strongly typed to the `PointType` `T`
     ///
     /// # Panics
     ///
-    /// Panics if the start of `range` is greater than the end of `range`, or if the end of `range` is greater than `self.len()`
-    fn get_points_ref<T: PointType>(&self, range: Range<usize>) -> &[T];
+    /// Panics if the start of `range` is greater than the end of `range`, if the end of `range` is greater than `self.len()`, or if
+    /// the associated `InterleavedPointBuffer` does not store points with type `T`
+    fn get_points_ref<T: PointType + 'static>(&self, range: Range<usize>) -> &[T];
     /// Returns an iterator over references to all points within the associated `InterleavedPointBuffer`, strongly typed to the `PointType` `T`
     ///
     /// # Panics
@@ -511,7 +704,8 @@ pub trait InterleavedPointBufferExt {
 }
 
 impl<B: InterleavedPointBuffer + ?Sized> InterleavedPointBufferExt for B {
-    fn get_point_ref<T: PointType>(&self, point_index: usize) -> &T {
+    fn get_point_ref<T: PointType + 'static>(&self, point_index: usize) -> &T {
+        assert_point_type_matches_layout::<T>(self.point_layout());
         let raw_point = self.get_raw_point_ref(point_index);
         unsafe {
             let ptr = raw_point.as_ptr() as *const T;
@@ -519,7 +713,8 @@ impl<B: InterleavedPointBuffer + ?Sized> InterleavedPointBufferExt for B {
         }
     }
 
-    fn get_points_ref<T: PointType>(&self, range: Range<usize>) -> &[T] {
+    fn get_points_ref<T: PointType + 'static>(&self, range: Range<usize>) -> &[T] {
+        assert_point_type_matches_layout::<T>(self.point_layout());
         let num_points = range.len();
         let raw_points = self.get_raw_points_ref(range);
         unsafe { std::slice::from_raw_parts(raw_points.as_ptr() as *const T, num_points) }
@@ -536,14 +731,15 @@ pub trait InterleavedPointBufferMutExt {
     ///
     /// # Panics
     ///
-    /// Panics if `point_index` is >= `self.len()`
-    fn get_point_mut<T: PointType>(&mut self, point_index: usize) -> &mut T;
+    /// Panics if `point_index` is >= `self.len()`, or if the associated `InterleavedPointBuffer` does not store points with type `T`
+    fn get_point_mut<T: PointType + 'static>(&mut self, point_index: usize) -> &mut T;
     /// Returns a mutable reference to the given `range` of points from the associated `InterleavedPointBuffer`, strongly typed to the `PointType` `T`
     ///
     /// # Panics
     ///
-    /// Panics if the start of `range` is greater than the end of `range`, or if the end of `range` is greater than `self.len()`
-    fn get_points_mut<T: PointType>(&mut self, range: Range<usize>) -> &mut [T];
+    /// Panics if the start of `range` is greater than the end of `range`, if the end of `range` is greater than `self.len()`, or if
+    /// the associated `InterleavedPointBuffer` does not store points with type `T`
+    fn get_points_mut<T: PointType + 'static>(&mut self, range: Range<usize>) -> &mut [T];
     /// Returns an iterator over mutable references to all points within the associated `InterleavedPointBuffer`, strongly typed to the `PointType` `T`
     ///
     /// # Panics
@@ -553,7 +749,8 @@ pub trait InterleavedPointBufferMutExt {
 }
 
 impl<B: InterleavedPointBufferMut + ?Sized> InterleavedPointBufferMutExt for B {
-    fn get_point_mut<T: PointType>(&mut self, point_index: usize) -> &mut T {
+    fn get_point_mut<T: PointType + 'static>(&mut self, point_index: usize) -> &mut T {
+        assert_point_type_matches_layout::<T>(self.point_layout());
         let raw_point = self.get_raw_point_mut(point_index);
         unsafe {
             let ptr = raw_point.as_ptr() as *mut T;
@@ -561,7 +758,8 @@ impl<B: InterleavedPointBufferMut + ?Sized> InterleavedPointBufferMutExt for B {
         }
     }
 
-    fn get_points_mut<T: PointType>(&mut self, range: Range<usize>) -> &mut [T] {
+    fn get_points_mut<T: PointType + 'static>(&mut self, range: Range<usize>) -> &mut [T] {
+        assert_point_type_matches_layout::<T>(self.point_layout());
         let num_points = range.len();
         let raw_points = self.get_raw_points_mut(range);
         unsafe { std::slice::from_raw_parts_mut(raw_points.as_ptr() as *mut T, num_points) }
@@ -55,6 +55,16 @@ pub mod iterators {
 
             unsafe { Some(point.assume_init()) }
         }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let remaining = self.buffer.len() - self.current_index;
+            (remaining, Some(remaining))
+        }
+    }
+
+    impl<'a, T: PointType, B: PointBuffer + ?Sized> ExactSizeIterator
+        for PointIteratorByValue<'a, T, B>
+    {
     }
 
     /// Iterator over an interleaved `PointBuffer` that yields strongly typed points by reference
@@ -228,5 +238,10 @@ mod tests {
         let collected_points = storage.iter_point::<TestPointType>().collect::<Vec<_>>();
 
         assert_eq!(reference_points, collected_points);
+
+        let mut points = storage.iter_point::<TestPointType>();
+        assert_eq!(2, points.len());
+        points.next();
+        assert_eq!(1, points.len());
     }
 }
@@ -0,0 +1,112 @@
+use nalgebra::Vector3;
+
+use crate::layout::attributes::POSITION_3D;
+
+use super::{PointBuffer, PointBufferExt};
+
+/// Converts the `POSITION_3D` attribute of all points in `buffer` into single-precision positions, e.g. for uploading
+/// them to the GPU. Returns the converted positions together with the origin that they are relative to. To get the
+/// original positions back, add the origin to each converted position (in double precision).
+///
+/// The origin is the center of the bounding box of all positions. Positions are shifted by the origin in double precision
+/// *before* they are converted, so the precision of the converted positions only depends on the extent of the point
+/// cloud and not on its absolute location. As an example, a point cloud in geocentric coordinates (several million meters
+/// from the coordinate origin) can't be represented with better than decimeter precision by `f32`, but after the shift,
+/// a 1km wide tile still has sub-millimeter precision. The converted positions are only meaningful together with the
+/// returned origin, so if multiple buffers have to share one coordinate system, shift them to a common origin yourself.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::{attributes, PointLayout};
+/// # use pasture_core::nalgebra::Vector3;
+/// let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::POSITION_3D]));
+/// let mut pusher = buffer.begin_push_attributes();
+/// pusher.push_attribute_range(
+///     &attributes::POSITION_3D,
+///     &[Vector3::new(1_000_000.0, 0.0, 0.0), Vector3::new(1_000_002.0, 2.0, 2.0)],
+/// );
+/// pusher.done();
+///
+/// let (positions, origin) = downcast_positions_f32(&buffer);
+/// assert_eq!(Vector3::new(1_000_001.0, 1.0, 1.0), origin);
+/// assert_eq!(vec![Vector3::new(-1.0_f32, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0)], positions);
+/// ```
+///
+/// # Panics
+///
+/// If `buffer` has no `POSITION_3D` attribute, or if its `POSITION_3D` attribute can't be converted to `Vector3<f64>`
+pub fn downcast_positions_f32(buffer: &dyn PointBuffer) -> (Vec<Vector3<f32>>, Vector3<f64>) {
+    if buffer.len() == 0 {
+        return (vec![], Vector3::zeros());
+    }
+
+    let mut min = Vector3::repeat(f64::MAX);
+    let mut max = Vector3::repeat(f64::MIN);
+    for position in buffer.iter_attribute_as::<Vector3<f64>>(&POSITION_3D) {
+        min = min.inf(&position);
+        max = max.sup(&position);
+    }
+    let origin = (min + max) / 2.0;
+
+    let positions = buffer
+        .iter_attribute_as::<Vector3<f64>>(&POSITION_3D)
+        .map(|position| {
+            let relative_position = position - origin;
+            Vector3::new(
+                relative_position.x as f32,
+                relative_position.y as f32,
+                relative_position.z as f32,
+            )
+        })
+        .collect();
+
+    (positions, origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::PerAttributeVecPointStorage;
+    use crate::layout::PointLayout;
+    use rand::{distributions::Uniform, thread_rng, Rng};
+
+    #[test]
+    fn test_downcast_positions_f32_keeps_relative_precision() {
+        // Positions roughly on the surface of the earth in geocentric coordinates, within a 1km cube
+        let base = Vector3::new(4_000_000.0, 3_000_000.0, 4_500_000.0);
+        let mut rng = thread_rng();
+        let original_positions = (0..1000)
+            .map(|_| {
+                base + Vector3::new(
+                    rng.sample(Uniform::new(0.0, 1000.0)),
+                    rng.sample(Uniform::new(0.0, 1000.0)),
+                    rng.sample(Uniform::new(0.0, 1000.0)),
+                )
+            })
+            .collect::<Vec<Vector3<f64>>>();
+
+        let mut buffer =
+            PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[POSITION_3D]));
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&POSITION_3D, original_positions.as_slice());
+        pusher.done();
+
+        let (positions, origin) = downcast_positions_f32(&buffer);
+        assert_eq!(original_positions.len(), positions.len());
+
+        for (original, downcast) in original_positions.iter().zip(positions.iter()) {
+            let restored =
+                Vector3::new(downcast.x as f64, downcast.y as f64, downcast.z as f64) + origin;
+            // f32 has a 24 bit mantissa, so positions within +-500m are accurate to well below a millimeter
+            assert!((restored - original).norm() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_downcast_positions_f32_empty_buffer() {
+        let buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[POSITION_3D]));
+        let (positions, origin) = downcast_positions_f32(&buffer);
+        assert!(positions.is_empty());
+        assert_eq!(Vector3::zeros(), origin);
+    }
+}
@@ -1,8 +1,17 @@
 use std::{collections::HashMap, iter::FromIterator, ops::Range};
 
+use anyhow::{bail, Result};
+
 use crate::{
-    layout::{PointAttributeDefinition, PointLayout, PointType, PrimitiveType},
-    util::{sort_untyped_slice_by_permutation, view_raw_bytes},
+    layout::{
+        attributes::COLOR_RGB,
+        conversion::{scaled_converter, try_get_converter_for_attributes},
+        PointAttributeDataType, PointAttributeDefinition, PointLayout, PointType, PrimitiveType,
+    },
+    util::{
+        run_parallel, sort_untyped_slice_by_permutation, view_raw_bytes, view_raw_bytes_mut,
+        AlignedByteVec,
+    },
 };
 
 use super::{
@@ -212,11 +221,21 @@ impl InterleavedVecPointStorage {
     }
 
     /// Reserve capacity for at least `additional_points` new points to be inserted into this `PointBuffer`
-    fn reserve(&mut self, additional_points: usize) {
+    pub fn reserve(&mut self, additional_points: usize) {
         let additional_bytes = additional_points * self.size_of_point_entry as usize;
         self.points.reserve(additional_bytes);
     }
 
+    /// Renames the attribute `old_name` of the associated `InterleavedVecPointStorage` to `new_name`. The point data is not
+    /// changed. See [PointLayout::rename_attribute] for details
+    ///
+    /// # Errors
+    ///
+    /// If `old_name` is not part of the `PointLayout` of the associated `InterleavedVecPointStorage`, or if `new_name` already is
+    pub fn rename_attribute(&mut self, old_name: &str, new_name: &'static str) -> Result<()> {
+        self.layout.rename_attribute(old_name, new_name)
+    }
+
     fn push_interleaved(&mut self, points: &dyn InterleavedPointBuffer) {
         if points.point_layout() != self.point_layout() {
             panic!("InterleavedVecPointStorage::push_interleaved: Layout of points in new buffer does not match this PointLayout");
@@ -410,6 +429,14 @@ impl PointBuffer for InterleavedVecPointStorage {
         &self.layout
     }
 
+    fn memory_size_bytes(&self) -> usize {
+        self.points.len()
+    }
+
+    fn capacity_bytes(&self) -> usize {
+        self.points.capacity()
+    }
+
     fn as_interleaved(&self) -> Option<&dyn InterleavedPointBuffer> {
         Some(self)
     }
@@ -582,12 +609,20 @@ impl<T: PointType> From<Vec<T>> for InterleavedVecPointStorage {
 }
 
 /// `PointBuffer` type that uses PerAttribute memory layout and `Vec`-based owning storage for point data
+///
+/// The memory of each attribute starts at a multiple of the alignment of the buffer, which is
+/// [DEFAULT_ALIGNMENT](PerAttributeVecPointStorage::DEFAULT_ALIGNMENT) unless the buffer is created with
+/// [with_alignment](PerAttributeVecPointStorage::with_alignment)
 pub struct PerAttributeVecPointStorage {
     layout: PointLayout,
-    attributes: HashMap<&'static str, Vec<u8>>,
+    attributes: HashMap<&'static str, AlignedByteVec>,
+    alignment: usize,
 }
 
 impl PerAttributeVecPointStorage {
+    /// The default alignment of the memory of each attribute, which is sufficient for all primitive attribute types
+    pub const DEFAULT_ALIGNMENT: usize = 8;
+
     /// Creates a new empty `PerAttributeVecPointStorage` with the given `PointLayout`
     ///
     /// # Examples
@@ -600,11 +635,41 @@ impl PerAttributeVecPointStorage {
     /// # assert_eq!(0, storage.len());
     /// ```
     pub fn new(layout: PointLayout) -> Self {
+        Self::with_alignment(layout, Self::DEFAULT_ALIGNMENT)
+    }
+
+    /// Creates a new empty `PerAttributeVecPointStorage` with the given `PointLayout` whose attribute memory is aligned
+    /// to `alignment` bytes. This guarantees that the memory of each attribute, for example as returned by
+    /// [get_raw_attribute_range_ref](PerAttributePointBuffer::get_raw_attribute_range_ref) with a range starting at
+    /// point 0, starts at a multiple of `alignment`, which is useful for SIMD processing. The alignment is kept when the
+    /// buffer grows
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pasture_core::containers::*;
+    /// # use pasture_core::layout::*;
+    /// let layout = PointLayout::from_attributes(&[attributes::INTENSITY]);
+    /// let mut storage = PerAttributeVecPointStorage::with_alignment(layout, 32);
+    /// storage.resize(4);
+    /// let intensities = storage.get_raw_attribute_range_ref(0..4, &attributes::INTENSITY);
+    /// assert_eq!(0, intensities.as_ptr() as usize % 32);
+    /// # assert_eq!(32, storage.alignment());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `alignment` is not a power of two
+    pub fn with_alignment(layout: PointLayout, alignment: usize) -> Self {
         let attributes = layout
             .attributes()
-            .map(|attribute| (attribute.name(), vec![]))
+            .map(|attribute| (attribute.name(), AlignedByteVec::new(alignment)))
             .collect::<HashMap<_, _>>();
-        Self { layout, attributes }
+        Self {
+            layout,
+            attributes,
+            alignment,
+        }
     }
 
     /// Creates a new `PerAttributeVecPointStorage` with enough capacity to store `capacity` points using
@@ -621,14 +686,27 @@ impl PerAttributeVecPointStorage {
     /// # assert_eq!(0, storage.len());
     /// ```
     pub fn with_capacity(capacity: usize, layout: PointLayout) -> Self {
+        let alignment = Self::DEFAULT_ALIGNMENT;
         let attributes = layout
             .attributes()
             .map(|attribute| {
                 let attribute_bytes = capacity * attribute.size() as usize;
-                (attribute.name(), Vec::with_capacity(attribute_bytes))
+                (
+                    attribute.name(),
+                    AlignedByteVec::with_capacity(attribute_bytes, alignment),
+                )
             })
             .collect::<HashMap<_, _>>();
-        Self { layout, attributes }
+        Self {
+            layout,
+            attributes,
+            alignment,
+        }
+    }
+
+    /// Returns the alignment of the memory of each attribute in the associated `PerAttributeVecPointStorage`, in bytes
+    pub fn alignment(&self) -> usize {
+        self.alignment
     }
 
     /// Pushes a single point into the associated `PerAttributeVecPointStorage`.
@@ -753,14 +831,15 @@ impl PerAttributeVecPointStorage {
             .for_each(|(idx, untyped_attribute)| {
                 let attribute_size = attribute_sizes[idx];
                 sort_untyped_slice_by_permutation(
-                    untyped_attribute.as_mut_slice(),
+                    untyped_attribute,
                     indices.as_slice(),
                     attribute_size as usize,
                 );
             });
     }
 
-    /// Like `sort_by_attribute`, but sorts each attribute in parallel. Uses the [`rayon`]() crate for parallelization
+    /// Like `sort_by_attribute`, but sorts each attribute in parallel. Uses the [`rayon`]() crate for parallelization, running
+    /// on the thread pool configured through [`set_thread_pool`](crate::util::set_thread_pool)
     pub fn par_sort_by_attribute<T: PrimitiveType + Ord>(
         &mut self,
         attribute: &PointAttributeDefinition,
@@ -789,16 +868,19 @@ impl PerAttributeVecPointStorage {
             })
             .collect::<HashMap<_, _>>();
 
-        self.attributes
-            .par_iter_mut()
-            .for_each(|(&key, untyped_attribute)| {
-                let size = *attribute_sizes.get(key).unwrap();
-                sort_untyped_slice_by_permutation(
-                    untyped_attribute.as_mut_slice(),
-                    indices.as_slice(),
-                    size as usize,
-                );
-            });
+        let attributes = &mut self.attributes;
+        run_parallel(|| {
+            attributes
+                .par_iter_mut()
+                .for_each(|(&key, untyped_attribute)| {
+                    let size = *attribute_sizes.get(key).unwrap();
+                    sort_untyped_slice_by_permutation(
+                        untyped_attribute,
+                        indices.as_slice(),
+                        size as usize,
+                    );
+                });
+        });
     }
 
     /// Reserves space for at least `additional_points` additional points in the associated `PerAttributeVecPointStorage`
@@ -810,6 +892,214 @@ impl PerAttributeVecPointStorage {
         }
     }
 
+    /// Renames the attribute `old_name` of the associated `PerAttributeVecPointStorage` to `new_name`. The point data is not
+    /// changed. See [PointLayout::rename_attribute] for details
+    ///
+    /// # Errors
+    ///
+    /// If `old_name` is not part of the `PointLayout` of the associated `PerAttributeVecPointStorage`, or if `new_name` already is
+    pub fn rename_attribute(&mut self, old_name: &str, new_name: &'static str) -> Result<()> {
+        self.layout.rename_attribute(old_name, new_name)?;
+        let attribute_data = self
+            .attributes
+            .remove(old_name)
+            .expect("Attribute data not found");
+        self.attributes.insert(new_name, attribute_data);
+        Ok(())
+    }
+
+    /// Changes the datatype of `attribute` in the associated `PerAttributeVecPointStorage` to `new_datatype`, converting
+    /// the values of all points with the converter from [try_get_converter_for_attributes]. The number of points is not
+    /// changed. This is useful to shrink attributes that are stored wider than necessary, e.g. an intensity that is stored
+    /// as `u32` but only uses values that fit into a `u16`. The offsets of all attributes in the `PointLayout` are
+    /// recomputed with [FieldAlignment::Default](crate::layout::FieldAlignment::Default)
+    ///
+    /// ```
+    /// # use pasture_core::containers::*;
+    /// # use pasture_core::layout::*;
+    /// let intensity_u32 = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::U32);
+    /// let mut storage = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[intensity_u32.clone()]));
+    /// let mut pusher = storage.begin_push_attributes();
+    /// pusher.push_attribute_range(&intensity_u32, &[42_u32, 65535]);
+    /// pusher.done();
+    ///
+    /// storage.change_attribute_datatype(&intensity_u32, PointAttributeDataType::U16).unwrap();
+    /// assert!(storage.point_layout().has_attribute(&attributes::INTENSITY));
+    /// assert_eq!(65535_u16, storage.get_attribute(&attributes::INTENSITY, 1));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If `attribute` is not part of the `PointLayout` of the associated `PerAttributeVecPointStorage`, if there is no
+    /// conversion to `new_datatype`, or if the value of any point does not fit into `new_datatype`. In case of an error,
+    /// the associated `PerAttributeVecPointStorage` is not changed. Use
+    /// [change_attribute_datatype_clamped](Self::change_attribute_datatype_clamped) to clamp values that don't fit instead
+    pub fn change_attribute_datatype(
+        &mut self,
+        attribute: &PointAttributeDefinition,
+        new_datatype: PointAttributeDataType,
+    ) -> Result<()> {
+        self.change_attribute_datatype_impl(attribute, new_datatype, false)
+    }
+
+    /// Like [change_attribute_datatype](Self::change_attribute_datatype), but clamps values that don't fit into
+    /// `new_datatype` to the smallest or largest value of `new_datatype` instead of returning an error
+    ///
+    /// # Errors
+    ///
+    /// If `attribute` is not part of the `PointLayout` of the associated `PerAttributeVecPointStorage`, or if there is
+    /// no conversion to `new_datatype`
+    pub fn change_attribute_datatype_clamped(
+        &mut self,
+        attribute: &PointAttributeDefinition,
+        new_datatype: PointAttributeDataType,
+    ) -> Result<()> {
+        self.change_attribute_datatype_impl(attribute, new_datatype, true)
+    }
+
+    fn change_attribute_datatype_impl(
+        &mut self,
+        attribute: &PointAttributeDefinition,
+        new_datatype: PointAttributeDataType,
+        clamp: bool,
+    ) -> Result<()> {
+        let old_attribute: PointAttributeDefinition =
+            match self.layout.get_attribute_by_name(attribute.name()) {
+                Some(member) => member.into(),
+                None => bail!(
+                    "Attribute {} is not part of this PointLayout",
+                    attribute.name()
+                ),
+            };
+        let new_attribute = old_attribute.with_custom_datatype(new_datatype);
+        let converter = match try_get_converter_for_attributes(&old_attribute, &new_attribute)? {
+            Some(converter) => converter,
+            None => return Ok(()),
+        };
+
+        // Only plain casts between integers can overflow. Normalized attributes and colors are rescaled by their
+        // converters, and floating-point targets can hold all values of the types that they can be converted from
+        let target_range = integer_range(new_datatype.component_type())
+            .filter(|_| !old_attribute.is_normalized() && old_attribute.name() != COLOR_RGB.name());
+        let read_component = scaled_converter(old_attribute.datatype().component_type(), 1.0, 0.0);
+
+        let old_size = old_attribute.size() as usize;
+        let new_size = new_attribute.size() as usize;
+        let old_component_size = old_attribute.datatype().component_type().size() as usize;
+        let new_component_size = new_datatype.component_type().size() as usize;
+        let old_data = &self.attributes[old_attribute.name()];
+        let mut new_data = AlignedByteVec::with_capacity(self.len() * new_size, self.alignment);
+        new_data.resize(self.len() * new_size, 0);
+
+        for (point_index, (old_value, new_value)) in old_data
+            .chunks_exact(old_size)
+            .zip(new_data.chunks_exact_mut(new_size))
+            .enumerate()
+        {
+            if let (Some((min, max)), Some(read_component)) = (target_range, read_component) {
+                let components = old_value
+                    .chunks_exact(old_component_size)
+                    .map(|component| {
+                        let mut value = 0.0_f64;
+                        unsafe {
+                            read_component.convert(component, view_raw_bytes_mut(&mut value));
+                        }
+                        value
+                    })
+                    .collect::<Vec<_>>();
+                if components.iter().any(|value| *value < min || *value > max) {
+                    if !clamp {
+                        bail!(
+                            "Value of attribute {} of point {} does not fit into datatype {}",
+                            old_attribute.name(),
+                            point_index,
+                            new_datatype
+                        );
+                    }
+                    for (component, value) in new_value
+                        .chunks_exact_mut(new_component_size)
+                        .zip(components)
+                    {
+                        write_integer_component(
+                            new_datatype.component_type(),
+                            value.max(min).min(max),
+                            component,
+                        );
+                    }
+                    continue;
+                }
+            }
+            unsafe {
+                converter(old_value, new_value);
+            }
+        }
+
+        let attributes = self
+            .layout
+            .attributes()
+            .map(|member| {
+                if member.name() == old_attribute.name() {
+                    new_attribute.clone()
+                } else {
+                    member.into()
+                }
+            })
+            .collect::<Vec<_>>();
+        self.layout = PointLayout::from_attributes(&attributes);
+        self.attributes.insert(old_attribute.name(), new_data);
+        Ok(())
+    }
+
+    /// Retains only the points for which `predicate` returns `true`, removing all other points in place. `predicate`
+    /// is called exactly once for the index of each point, in ascending order. In contrast to filtering into a new
+    /// buffer, this does not require a second copy of the point data. The capacity of the buffer is not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pasture_core::containers::*;
+    /// # use pasture_core::layout::*;
+    /// # use pasture_derive::PointType;
+    ///
+    /// #[repr(C)]
+    /// #[derive(PointType)]
+    /// struct MyPointType(#[pasture(BUILTIN_INTENSITY)] u16);
+    ///
+    /// let mut storage = PerAttributeVecPointStorage::new(MyPointType::layout());
+    /// storage.push_points(&[MyPointType(42), MyPointType(43), MyPointType(44)]);
+    /// storage.retain(|point_index| point_index != 1);
+    /// assert_eq!(2, storage.len());
+    /// ```
+    pub fn retain<F: Fn(usize) -> bool>(&mut self, predicate: F) {
+        // Evaluate the predicate only once per point so that all attributes are compacted using the exact same set
+        // of surviving points
+        let keep = (0..self.len()).map(&predicate).collect::<Vec<_>>();
+        let num_retained_points = keep.iter().filter(|&&keep_point| keep_point).count();
+        if num_retained_points == self.len() {
+            return;
+        }
+
+        for attribute in self.layout.attributes() {
+            let attribute_size = attribute.size() as usize;
+            let attribute_buffer = self.attributes.get_mut(attribute.name()).unwrap();
+            let retained_indices = keep
+                .iter()
+                .enumerate()
+                .filter(|(_, &keep_point)| keep_point)
+                .map(|(read_index, _)| read_index);
+            for (write_index, read_index) in retained_indices.enumerate() {
+                if write_index != read_index {
+                    let read_start = read_index * attribute_size;
+                    attribute_buffer.copy_within(
+                        read_start..read_start + attribute_size,
+                        write_index * attribute_size,
+                    );
+                }
+            }
+            attribute_buffer.truncate(num_retained_points * attribute_size);
+        }
+    }
+
     fn push_interleaved(&mut self, points: &dyn InterleavedPointBuffer) {
         if !points
             .point_layout()
@@ -916,6 +1206,38 @@ impl PerAttributeVecPointStorage {
     }
 }
 
+/// Returns the smallest and the largest value of the integer datatype `datatype`, or `None` if `datatype` is no integer
+/// datatype
+fn integer_range(datatype: PointAttributeDataType) -> Option<(f64, f64)> {
+    match datatype {
+        PointAttributeDataType::U8 => Some((u8::MIN as f64, u8::MAX as f64)),
+        PointAttributeDataType::I8 => Some((i8::MIN as f64, i8::MAX as f64)),
+        PointAttributeDataType::U16 => Some((u16::MIN as f64, u16::MAX as f64)),
+        PointAttributeDataType::I16 => Some((i16::MIN as f64, i16::MAX as f64)),
+        PointAttributeDataType::U32 => Some((u32::MIN as f64, u32::MAX as f64)),
+        PointAttributeDataType::I32 => Some((i32::MIN as f64, i32::MAX as f64)),
+        PointAttributeDataType::U64 => Some((u64::MIN as f64, u64::MAX as f64)),
+        PointAttributeDataType::I64 => Some((i64::MIN as f64, i64::MAX as f64)),
+        _ => None,
+    }
+}
+
+/// Writes `value` as a single value of the integer datatype `datatype` into `bytes`. `value` must be in the range of
+/// `datatype`, see [integer_range]
+fn write_integer_component(datatype: PointAttributeDataType, value: f64, bytes: &mut [u8]) {
+    match datatype {
+        PointAttributeDataType::U8 => bytes.copy_from_slice(&(value as u8).to_ne_bytes()),
+        PointAttributeDataType::I8 => bytes.copy_from_slice(&(value as i8).to_ne_bytes()),
+        PointAttributeDataType::U16 => bytes.copy_from_slice(&(value as u16).to_ne_bytes()),
+        PointAttributeDataType::I16 => bytes.copy_from_slice(&(value as i16).to_ne_bytes()),
+        PointAttributeDataType::U32 => bytes.copy_from_slice(&(value as u32).to_ne_bytes()),
+        PointAttributeDataType::I32 => bytes.copy_from_slice(&(value as i32).to_ne_bytes()),
+        PointAttributeDataType::U64 => bytes.copy_from_slice(&(value as u64).to_ne_bytes()),
+        PointAttributeDataType::I64 => bytes.copy_from_slice(&(value as i64).to_ne_bytes()),
+        other => panic!("Datatype {} is no integer datatype", other),
+    }
+}
+
 impl PointBuffer for PerAttributeVecPointStorage {
     fn get_raw_point(&self, point_index: usize, buf: &mut [u8]) {
         if point_index >= self.len() {
@@ -999,6 +1321,20 @@ impl PointBuffer for PerAttributeVecPointStorage {
         &self.layout
     }
 
+    fn memory_size_bytes(&self) -> usize {
+        self.attributes
+            .values()
+            .map(|attribute| attribute.len())
+            .sum()
+    }
+
+    fn capacity_bytes(&self) -> usize {
+        self.attributes
+            .values()
+            .map(|attribute| attribute.capacity())
+            .sum()
+    }
+
     fn as_per_attribute(&self) -> Option<&dyn PerAttributePointBuffer> {
         Some(self)
     }
@@ -1381,9 +1717,9 @@ impl<'a> PerAttributeVecPointStoragePusher<'a> {
             }
         }
 
-        for (k, mut v) in self.new_attribute_data.into_iter() {
+        for (k, v) in self.new_attribute_data.into_iter() {
             let attribute_data = self.buffer.attributes.get_mut(k).unwrap();
-            attribute_data.append(&mut v);
+            attribute_data.extend_from_slice(&v);
         }
     }
 }
@@ -1391,17 +1727,17 @@ impl<'a> PerAttributeVecPointStoragePusher<'a> {
 #[cfg(test)]
 mod tests {
 
-    use nalgebra::Vector3;
+    use nalgebra::{Vector3, Vector4};
 
     use super::*;
     use crate::containers::{
-        InterleavedPointView, PerAttributePointBufferExt, PerAttributePointView, PointBufferExt,
-        PointBufferWriteableExt,
+        InterleavedPointBufferExt, InterleavedPointBufferMutExt, InterleavedPointView,
+        PerAttributePointBufferExt, PerAttributePointView, PointBufferExt, PointBufferWriteableExt,
     };
     use crate::layout::attributes::{CLASSIFICATION, COLOR_RGB, GPS_TIME, INTENSITY, POSITION_3D};
     use crate::util::view_raw_bytes;
     use crate::{
-        layout::{attributes, PointLayout},
+        layout::{attributes, PointAttributeDataType, PointLayout},
         util::view_raw_bytes_mut,
     };
     use pasture_derive::PointType;
@@ -1424,6 +1760,25 @@ mod tests {
         #[pasture(BUILTIN_RETURN_NUMBER)] u8,
     );
 
+    /// Same size as `TestPointType`, but with the attributes in a different order
+    #[repr(packed)]
+    #[derive(Debug, Copy, Clone, PartialEq, PointType)]
+    struct SwappedTestPointType(
+        #[pasture(BUILTIN_GPS_TIME)] f64,
+        #[pasture(BUILTIN_INTENSITY)] u16,
+    );
+
+    #[repr(C)]
+    #[derive(Debug, Copy, Clone, PartialEq, PointType)]
+    struct FeaturePointType {
+        #[pasture(BUILTIN_POSITION_3D)]
+        position: Vector3<f64>,
+        #[pasture(attribute = "Features")]
+        features: [f32; 4],
+        #[pasture(attribute = "Labels")]
+        labels: [i32; 8],
+    }
+
     trait OpqaueInterleavedBuffer: InterleavedPointBufferMut + PointBufferWriteable {}
     impl OpqaueInterleavedBuffer for InterleavedVecPointStorage {}
 
@@ -2537,6 +2892,26 @@ mod tests {
         builder.push_attribute_range(&attributes::POINT_SOURCE_ID, &[42_u16, 43_u16]);
     }
 
+    #[test]
+    fn test_per_attribute_vec_storage_retain() {
+        let points = (0..10)
+            .map(|idx| TestPointType(idx as u16, idx as f64 * 0.5))
+            .collect::<Vec<_>>();
+        let mut storage = PerAttributeVecPointStorage::new(TestPointType::layout());
+        storage.push_points(points.as_slice());
+
+        storage.retain(|point_index| point_index % 2 == 0);
+
+        let expected_points = points.iter().copied().step_by(2).collect::<Vec<_>>();
+        assert_eq!(expected_points.len(), storage.len());
+        let actual_points = storage.iter_point::<TestPointType>().collect::<Vec<_>>();
+        assert_eq!(expected_points, actual_points);
+
+        storage.retain(|_| false);
+        assert_eq!(0, storage.len());
+        assert!(storage.is_empty());
+    }
+
     #[test]
     fn test_interleaved_point_buffer_extend_from_per_attribute() {
         let mut interleaved_buffer = InterleavedVecPointStorage::new(TestPointType::layout());
@@ -3426,4 +3801,433 @@ mod tests {
 
         buffer.transform_attribute(INTENSITY.name(), |_, _value: &mut Vector3<u16>| {});
     }
+
+    #[test]
+    fn test_interleaved_get_point_ref_is_zero_copy() {
+        let mut storage = InterleavedVecPointStorage::new(TestPointType::layout());
+        storage.push_points(&[TestPointType(1, 2.0), TestPointType(3, 4.0)]);
+
+        {
+            let point_ref = storage.get_point_ref::<TestPointType>(1);
+            assert_eq!(TestPointType(3, 4.0), *point_ref);
+            assert_eq!(
+                storage.get_raw_point_ref(1).as_ptr(),
+                point_ref as *const TestPointType as *const u8
+            );
+        }
+
+        *storage.get_point_mut::<TestPointType>(1) = TestPointType(5, 6.0);
+        assert_eq!(
+            TestPointType(5, 6.0),
+            *storage.get_point_ref::<TestPointType>(1)
+        );
+        assert_eq!(
+            &[TestPointType(1, 2.0), TestPointType(5, 6.0)],
+            storage.get_points_ref::<TestPointType>(0..2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "PointLayout of type T does not match PointLayout of buffer")]
+    fn test_interleaved_get_point_ref_with_wrong_type() {
+        let mut storage = InterleavedVecPointStorage::new(TestPointType::layout());
+        storage.push_points(&[TestPointType(1, 2.0)]);
+        storage.get_point_ref::<OtherPointType>(0);
+    }
+
+    #[test]
+    fn test_rename_attribute_in_buffers() {
+        let scalar_classification =
+            PointAttributeDefinition::custom("scalar_Classification", CLASSIFICATION.datatype());
+        let layout = PointLayout::from_attributes(&[POSITION_3D, scalar_classification.clone()]);
+
+        let mut interleaved = InterleavedVecPointStorage::new(layout.clone());
+        interleaved.resize(2);
+        interleaved.set_attribute(&scalar_classification, 1, 7_u8);
+        interleaved
+            .rename_attribute(scalar_classification.name(), CLASSIFICATION.name())
+            .expect("Renaming failed");
+        assert!(interleaved.point_layout().has_attribute(&CLASSIFICATION));
+        assert_eq!(7_u8, interleaved.get_attribute(&CLASSIFICATION, 1));
+
+        let mut per_attribute = PerAttributeVecPointStorage::new(layout);
+        per_attribute.resize(2);
+        per_attribute.set_attribute(&scalar_classification, 1, 7_u8);
+        per_attribute
+            .rename_attribute(scalar_classification.name(), CLASSIFICATION.name())
+            .expect("Renaming failed");
+        assert!(per_attribute.point_layout().has_attribute(&CLASSIFICATION));
+        assert_eq!(7_u8, per_attribute.get_attribute(&CLASSIFICATION, 1));
+        assert!(per_attribute
+            .rename_attribute(CLASSIFICATION.name(), POSITION_3D.name())
+            .is_err());
+    }
+
+    #[test]
+    fn test_split_chunks_reassembles_to_original_data() {
+        let points = (0..10)
+            .map(|idx| TestPointType(idx as u16, idx as f64))
+            .collect::<Vec<_>>();
+
+        let interleaved: InterleavedVecPointStorage = points.iter().copied().collect();
+        let per_attribute: PerAttributeVecPointStorage = points.iter().copied().collect();
+        let buffers: [&dyn PointBuffer; 2] = [&interleaved, &per_attribute];
+
+        for buffer in buffers.iter() {
+            let chunks = buffer.split_chunks(3);
+            assert_eq!(
+                vec![3, 3, 4],
+                chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>()
+            );
+
+            let reassembled = chunks
+                .iter()
+                .flat_map(|chunk| {
+                    assert_eq!(buffer.point_layout(), chunk.point_layout());
+                    chunk.iter_point::<TestPointType>().collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(points, reassembled);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_chunks_with_zero_chunks() {
+        let buffer = InterleavedVecPointStorage::new(TestPointType::layout());
+        buffer.split_chunks(0);
+    }
+
+    #[test]
+    fn test_memory_size_bytes() {
+        // TestPointType is packed, so each point occupies 10 bytes
+        let points = (0..10)
+            .map(|idx| TestPointType(idx, idx as f64))
+            .collect::<Vec<_>>();
+
+        let mut interleaved =
+            InterleavedVecPointStorage::with_capacity(100, TestPointType::layout());
+        assert_eq!(0, interleaved.memory_size_bytes());
+        assert!(interleaved.capacity_bytes() >= 1000);
+        interleaved.push_points(&points);
+        assert_eq!(100, interleaved.memory_size_bytes());
+        assert!(interleaved.capacity_bytes() >= 1000);
+        assert_eq!(40, interleaved.slice(0..4).memory_size_bytes());
+        assert_eq!(40, interleaved.slice(0..4).capacity_bytes());
+
+        let mut per_attribute =
+            PerAttributeVecPointStorage::with_capacity(100, TestPointType::layout());
+        assert_eq!(0, per_attribute.memory_size_bytes());
+        assert!(per_attribute.capacity_bytes() >= 1000);
+        per_attribute.push_points(&points);
+        assert_eq!(100, per_attribute.memory_size_bytes());
+        assert!(per_attribute.capacity_bytes() >= 1000);
+    }
+
+    #[test]
+    fn test_get_attribute_as_f64() {
+        let scan_angle = attributes::SCAN_ANGLE;
+        let layout = PointLayout::from_attributes(&[
+            CLASSIFICATION,
+            scan_angle.clone(),
+            GPS_TIME,
+            COLOR_RGB,
+        ]);
+        let gps_time_f32 =
+            GPS_TIME.with_custom_datatype(crate::layout::PointAttributeDataType::F32);
+        let layout_with_f32 = PointLayout::from_attributes(&[gps_time_f32.clone()]);
+
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&CLASSIFICATION, &[2_u8, 255]);
+        pusher.push_attribute_range(&scan_angle, &[-90_i16, 1200]);
+        pusher.push_attribute_range(&GPS_TIME, &[0.5_f64, 1e9]);
+        pusher.push_attribute_range(&COLOR_RGB, &[Vector3::new(1_u16, 2, 3); 2]);
+        pusher.done();
+
+        assert_eq!(
+            255.0,
+            buffer.get_attribute_as_f64(&CLASSIFICATION, 1).unwrap()
+        );
+        assert_eq!(-90.0, buffer.get_attribute_as_f64(&scan_angle, 0).unwrap());
+        assert_eq!(1e9, buffer.get_attribute_as_f64(&GPS_TIME, 1).unwrap());
+        // Only the name of the attribute matters, not its datatype
+        assert_eq!(0.5, buffer.get_attribute_as_f64(&gps_time_f32, 0).unwrap());
+        assert!(buffer.get_attribute_as_f64(&COLOR_RGB, 0).is_err());
+        assert!(buffer.get_attribute_as_f64(&INTENSITY, 0).is_err());
+
+        let mut f32_buffer = InterleavedVecPointStorage::new(layout_with_f32);
+        f32_buffer.resize(1);
+        f32_buffer.set_attribute(&gps_time_f32, 0, 0.25_f32);
+        assert_eq!(0.25, f32_buffer.get_attribute_as_f64(&GPS_TIME, 0).unwrap());
+    }
+
+    #[test]
+    fn test_downcast_point_buffers() {
+        let points = vec![TestPointType(42, 0.123), TestPointType(43, 0.456)];
+        let interleaved = InterleavedVecPointStorage::from(points.as_slice());
+        let mut per_attribute = PerAttributeVecPointStorage::new(TestPointType::layout());
+        per_attribute.push_points(points.as_slice());
+
+        let interleaved_dyn: &dyn PointBuffer = &interleaved;
+        let per_attribute_dyn: &dyn PointBuffer = &per_attribute;
+
+        let as_interleaved = interleaved_dyn
+            .as_interleaved()
+            .expect("Downcast to InterleavedPointBuffer failed");
+        assert_eq!(
+            interleaved.get_raw_points_ref(0..2),
+            as_interleaved.get_raw_points_ref(0..2)
+        );
+        assert!(interleaved_dyn.as_per_attribute().is_none());
+
+        let as_per_attribute = per_attribute_dyn
+            .as_per_attribute()
+            .expect("Downcast to PerAttributePointBuffer failed");
+        assert_eq!(
+            per_attribute.get_raw_attribute_range_ref(0..2, &INTENSITY),
+            as_per_attribute.get_raw_attribute_range_ref(0..2, &INTENSITY)
+        );
+        assert!(per_attribute_dyn.as_interleaved().is_none());
+
+        let interleaved_slice = interleaved.slice(0..1);
+        assert!(interleaved_slice.as_interleaved().is_some());
+        assert!(interleaved_slice.as_per_attribute().is_none());
+        let per_attribute_slice = per_attribute.slice(0..1);
+        assert!(per_attribute_slice.as_per_attribute().is_some());
+        assert!(per_attribute_slice.as_interleaved().is_none());
+    }
+
+    #[test]
+    fn test_array_attributes_roundtrip() {
+        let features_attribute =
+            PointAttributeDefinition::custom("Features", PointAttributeDataType::Array4f32);
+        let labels_attribute =
+            PointAttributeDefinition::custom("Labels", PointAttributeDataType::Array8i32);
+        let layout = FeaturePointType::layout();
+        assert_eq!(
+            Some(24),
+            layout.offset_of(&features_attribute),
+            "Arrays must be laid out contiguously after the position"
+        );
+        assert_eq!(Some(40), layout.offset_of(&labels_attribute));
+        assert_eq!(
+            std::mem::size_of::<FeaturePointType>() as u64,
+            layout.size_of_point_entry()
+        );
+
+        let points = vec![
+            FeaturePointType {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                features: [0.5, -1.25, 3.0, 1e-3],
+                labels: [1, 2, 3, 4, 5, 6, 7, i32::MIN],
+            },
+            FeaturePointType {
+                position: Vector3::new(-1.0, -2.0, -3.0),
+                features: [f32::MAX, 0.0, -0.0, 42.0],
+                labels: [-1, 0, 0, 0, 0, 0, 0, i32::MAX],
+            },
+        ];
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        buffer.push_points(points.as_slice());
+
+        let features = buffer
+            .iter_attribute::<[f32; 4]>(&features_attribute)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            points.iter().map(|p| p.features).collect::<Vec<_>>(),
+            features
+        );
+        assert_eq!(
+            points[1].labels,
+            buffer.get_attribute::<[i32; 8]>(&labels_attribute, 1)
+        );
+
+        let read_points = buffer.iter_point::<FeaturePointType>().collect::<Vec<_>>();
+        assert_eq!(points, read_points);
+    }
+
+    #[test]
+    fn test_change_attribute_datatype() {
+        let intensity_u32 = INTENSITY.with_custom_datatype(PointAttributeDataType::U32);
+        let layout = PointLayout::from_attributes(&[intensity_u32.clone(), GPS_TIME]);
+        let mut storage = PerAttributeVecPointStorage::new(layout);
+        let mut pusher = storage.begin_push_attributes();
+        pusher.push_attribute_range(&intensity_u32, &[0_u32, 42, 65535]);
+        pusher.push_attribute_range(&GPS_TIME, &[1.0, 2.0, 3.0]);
+        pusher.done();
+
+        storage
+            .change_attribute_datatype(&intensity_u32, PointAttributeDataType::U16)
+            .unwrap();
+        assert_eq!(3, storage.len());
+        assert!(storage.point_layout().has_attribute(&INTENSITY));
+        assert!(storage.point_layout().has_attribute(&GPS_TIME));
+        assert_eq!(
+            vec![0_u16, 42, 65535],
+            storage
+                .iter_attribute::<u16>(&INTENSITY)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1.0, 2.0, 3.0],
+            storage.iter_attribute::<f64>(&GPS_TIME).collect::<Vec<_>>()
+        );
+
+        assert!(storage
+            .change_attribute_datatype(&INTENSITY, PointAttributeDataType::Bool)
+            .is_err());
+        assert!(storage
+            .change_attribute_datatype(&CLASSIFICATION, PointAttributeDataType::U16)
+            .is_err());
+    }
+
+    #[test]
+    fn test_change_attribute_datatype_with_overflow() {
+        let intensity_u32 = INTENSITY.with_custom_datatype(PointAttributeDataType::U32);
+        let layout = PointLayout::from_attributes(&[intensity_u32.clone()]);
+        let mut storage = PerAttributeVecPointStorage::new(layout);
+        let mut pusher = storage.begin_push_attributes();
+        pusher.push_attribute_range(&intensity_u32, &[42_u32, 70000]);
+        pusher.done();
+
+        let error = storage
+            .change_attribute_datatype(&intensity_u32, PointAttributeDataType::U16)
+            .expect_err("Overflow must yield an error");
+        assert!(error.to_string().contains("point 1"));
+        // The storage is unchanged after an error
+        assert!(storage.point_layout().has_attribute(&intensity_u32));
+        assert_eq!(70000_u32, storage.get_attribute(&intensity_u32, 1));
+
+        storage
+            .change_attribute_datatype_clamped(&intensity_u32, PointAttributeDataType::U16)
+            .unwrap();
+        assert_eq!(
+            vec![42_u16, u16::MAX],
+            storage
+                .iter_attribute::<u16>(&INTENSITY)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_vec4_attributes_roundtrip() {
+        let orientation_attribute =
+            PointAttributeDefinition::custom("Orientation", PointAttributeDataType::Vec4f32);
+        let layout = PointLayout::from_attributes(&[POSITION_3D, orientation_attribute.clone()]);
+        assert_eq!(Some(24), layout.offset_of(&orientation_attribute));
+
+        let positions = vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 5.0, 6.0)];
+        let orientations = vec![
+            Vector4::new(0.0_f32, 0.0, 0.0, 1.0),
+            Vector4::new(0.5_f32, -0.5, 0.5, -0.5),
+        ];
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        let mut pusher = buffer.begin_push_attributes();
+        pusher.push_attribute_range(&POSITION_3D, positions.as_slice());
+        pusher.push_attribute_range(&orientation_attribute, orientations.as_slice());
+        pusher.done();
+
+        assert_eq!(
+            orientations,
+            buffer
+                .iter_attribute::<Vector4<f32>>(&orientation_attribute)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            orientations[1],
+            buffer.get_attribute::<Vector4<f32>>(&orientation_attribute, 1)
+        );
+        assert_eq!(
+            orientations[..],
+            buffer.get_attribute_range_ref::<Vector4<f32>>(0..2, &orientation_attribute)[..]
+        );
+    }
+
+    #[test]
+    fn test_get_point_into() {
+        let points = vec![
+            FeaturePointType {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                features: [0.5, -1.25, 3.0, 1e-3],
+                labels: [1, 2, 3, 4, 5, 6, 7, i32::MIN],
+            },
+            FeaturePointType {
+                position: Vector3::new(-1.0, -2.0, -3.0),
+                features: [f32::MAX, 0.0, -0.0, 42.0],
+                labels: [-1, 0, 0, 0, 0, 0, 0, i32::MAX],
+            },
+        ];
+        let interleaved_buffer = get_interleaved_point_buffer_from_points(points.as_slice());
+        let per_attribute_buffer = get_per_attribute_point_buffer_from_points(points.as_slice());
+
+        let mut point = FeaturePointType {
+            position: Vector3::zeros(),
+            features: [0.0; 4],
+            labels: [0; 8],
+        };
+        for index in 0..points.len() {
+            interleaved_buffer.get_point_into(index, &mut point);
+            assert_eq!(
+                interleaved_buffer.get_point::<FeaturePointType>(index),
+                point
+            );
+            assert_eq!(points[index], point);
+
+            per_attribute_buffer.get_point_into(index, &mut point);
+            assert_eq!(
+                per_attribute_buffer.get_point::<FeaturePointType>(index),
+                point
+            );
+            assert_eq!(points[index], point);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_point_into_with_wrong_type() {
+        let buffer = get_interleaved_point_buffer_from_points(&[TestPointType(42, 0.123)]);
+        let mut point = OtherPointType(Vector3::zeros(), 0);
+        buffer.get_point_into(0, &mut point);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_point_into_with_same_size_but_different_layout() {
+        let buffer = get_interleaved_point_buffer_from_points(&[TestPointType(42, 0.123)]);
+        let mut point = SwappedTestPointType(0.0, 0);
+        buffer.get_point_into(0, &mut point);
+    }
+
+    #[test]
+    fn test_per_attribute_with_alignment() {
+        let layout = PointLayout::from_attributes(&[POSITION_3D, INTENSITY, CLASSIFICATION]);
+        for &alignment in [16, 32].iter() {
+            let mut buffer = PerAttributeVecPointStorage::with_alignment(layout.clone(), alignment);
+            assert_eq!(alignment, buffer.alignment());
+
+            // Push in several steps so that the attribute memory gets reallocated
+            for num_points in 1..=100 {
+                let mut pusher = buffer.begin_push_attributes();
+                pusher.push_attribute(&POSITION_3D, Vector3::new(num_points as f64, 0.0, 0.0));
+                pusher.push_attribute(&INTENSITY, num_points as u16);
+                pusher.push_attribute(&CLASSIFICATION, num_points as u8);
+                pusher.done();
+
+                for attribute in layout.attributes() {
+                    let attribute_slice =
+                        buffer.get_raw_attribute_range_ref(0..num_points, &attribute.into());
+                    assert_eq!(0, attribute_slice.as_ptr() as usize % alignment);
+                }
+            }
+
+            let intensities = buffer.iter_attribute::<u16>(&INTENSITY).collect::<Vec<_>>();
+            assert_eq!((1..=100).collect::<Vec<u16>>(), intensities);
+        }
+
+        assert_eq!(
+            PerAttributeVecPointStorage::DEFAULT_ALIGNMENT,
+            PerAttributeVecPointStorage::new(layout).alignment()
+        );
+    }
 }
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+/// A buffer in a `BufferPool`, together with the information whether it was handed out since the last
+/// [trim()](BufferPool::trim)
+struct PooledBuffer {
+    buffer: wgpu::Buffer,
+    used_since_trim: bool,
+}
+
+/// Pool of `wgpu` buffers that are reused between computations instead of allocating and freeing GPU memory for every
+/// computation. A pooled buffer is only handed out again for a request with exactly the same size and usage. Buffers
+/// that are not used between two calls to [trim()](BufferPool::trim) are freed by the second call, all buffers are
+/// freed by [clear()](BufferPool::clear).
+#[derive(Default)]
+pub(crate) struct BufferPool {
+    buffers: HashMap<(wgpu::BufferAddress, wgpu::BufferUsages), Vec<PooledBuffer>>,
+}
+
+impl BufferPool {
+    /// Returns a buffer with the given `size` and `usage` from the pool, or creates a new buffer if the pool contains
+    /// no matching buffer. The contents of a pooled buffer are undefined.
+    pub fn acquire(
+        &mut self,
+        wgpu_device: &wgpu::Device,
+        label: &str,
+        size: wgpu::BufferAddress,
+        usage: wgpu::BufferUsages,
+    ) -> wgpu::Buffer {
+        if let Some(pooled_buffer) = self
+            .buffers
+            .get_mut(&(size, usage))
+            .and_then(|buffers| buffers.pop())
+        {
+            return pooled_buffer.buffer;
+        }
+
+        wgpu_device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Puts `buffer` into the pool, so that it can be handed out again by [acquire()](BufferPool::acquire). `size`
+    /// and `usage` have to be the ones that `buffer` was created with, and `buffer` must not be mapped.
+    pub fn release(
+        &mut self,
+        buffer: wgpu::Buffer,
+        size: wgpu::BufferAddress,
+        usage: wgpu::BufferUsages,
+    ) {
+        self.buffers
+            .entry((size, usage))
+            .or_default()
+            .push(PooledBuffer {
+                buffer,
+                used_since_trim: true,
+            });
+    }
+
+    /// Frees the memory of all buffers in the pool that were not used since the last call to `trim`. The remaining
+    /// buffers are freed by the next call to `trim`, unless they are used in the meantime
+    pub fn trim(&mut self) {
+        for buffers in self.buffers.values_mut() {
+            buffers.retain(|pooled_buffer| pooled_buffer.used_since_trim);
+            for pooled_buffer in buffers.iter_mut() {
+                pooled_buffer.used_since_trim = false;
+            }
+        }
+        self.buffers.retain(|_, buffers| !buffers.is_empty());
+    }
+
+    /// Frees the memory of all buffers in the pool
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+    }
+
+    /// Returns the number of buffers in the pool
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.buffers.values().map(|buffers| buffers.len()).sum()
+    }
+}
@@ -1,6 +1,10 @@
+use crate::containers::PerAttributePointBufferMut;
+use crate::gpu::{validate_storage_buffer_bindings, BufferPool, GpuPointBufferPerAttribute, SharedDevice};
 use crate::layout;
+use anyhow::{anyhow, bail, Result};
 use wgpu::util::DeviceExt;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 use std::ops::BitOr;
 
 /// The base structure used to get access to the GPU. In addition it handles things like
@@ -16,7 +20,10 @@ pub struct Device<'a> {
     adapter: wgpu::Adapter,
     cs_module: Option<wgpu::ShaderModule>,
     bind_group_data: BTreeMap<u32, BindGroupPair<'a>>,
+    indirect_buffers: BTreeMap<u32, &'a wgpu::Buffer>,
     compute_pipeline: Option<wgpu::ComputePipeline>,
+    compute_pipelines: Vec<wgpu::ComputePipeline>,
+    buffer_pool: BufferPool,
 }
 
 impl<'a> Device<'a> {
@@ -25,7 +32,7 @@ impl<'a> Device<'a> {
     /// - `Vulkan` as backend
     /// - `wgpu`'s [MAPPABLE_PRIMARY_BUFFERS](wgpu::Features::MAPPABLE_PRIMARY_BUFFERS) feature enabled
     /// - Minimal limits
-    pub async fn default() -> Result<Device<'a>, wgpu::RequestDeviceError> {
+    pub async fn default() -> Result<Device<'a>> {
         Device::new(DeviceOptions::default()).await
     }
 
@@ -40,7 +47,9 @@ impl<'a> Device<'a> {
     ///
     /// Note that the device must have support for `wgpu`'s
     /// [MAPPABLE_PRIMARY_BUFFERS](wgpu::Features::MAPPABLE_PRIMARY_BUFFERS) feature, otherwise
-    /// an error is returned.
+    /// an error is returned. The same holds for any of the `required_features` and `required_limits`
+    /// in `device_options` that the adapter does not support. The error names the unsupported
+    /// features and limits.
     ///
     /// # Examples
     ///
@@ -49,12 +58,12 @@ impl<'a> Device<'a> {
     ///
     /// futures::executor::block_on(async {
     ///     let device = gpu::Device::new(
-    ///         gpu::DeviceOptions {
-    ///             device_power: gpu::DevicePower::High,
-    ///             device_backend: gpu::DeviceBackend::Vulkan,
-    ///             use_adapter_features: true,
-    ///             use_adapter_limits: true,
-    ///         }
+    ///         gpu::DeviceOptions::builder()
+    ///             .power(gpu::DevicePower::High)
+    ///             .backend(gpu::DeviceBackend::Vulkan)
+    ///             .use_adapter_features(true)
+    ///             .use_adapter_limits(true)
+    ///             .build()
     ///     ).await;
     ///
     ///     let mut device = match device {
@@ -66,7 +75,7 @@ impl<'a> Device<'a> {
     ///     };
     /// });
     /// ```
-    pub async fn new(device_options: DeviceOptions) -> Result<Device<'a>, wgpu::RequestDeviceError> {
+    pub async fn new(device_options: DeviceOptions) -> Result<Device<'a>> {
         // == Create an instance from the desired backend =========================================
 
         let backend_bits = match device_options.device_backend {
@@ -78,8 +87,22 @@ impl<'a> Device<'a> {
             // DeviceBackend::Dx11 => { wgpu::Backends::DX11 }
             // DeviceBackend::OpenGL => { wgpu::Backends::GL }
             // DeviceBackend::Browser => { wgpu::Backends::BROWSER_WEBGPU }
+            DeviceBackend::Auto => {
+                return probe_backends(AUTO_BACKEND_PRIORITY, |backend_bits| {
+                    Device::new_with_backends(&device_options, backend_bits)
+                })
+                .await;
+            }
         };
 
+        Device::new_with_backends(&device_options, backend_bits).await
+    }
+
+    /// Create a device on one of the given `backend_bits`, respecting the desired [DeviceOptions]
+    async fn new_with_backends(
+        device_options: &DeviceOptions,
+        backend_bits: wgpu::Backends,
+    ) -> Result<Device<'a>> {
         let instance = wgpu::Instance::new(backend_bits);
 
         // == Create an adapter with the desired power preference =================================
@@ -102,24 +125,42 @@ impl<'a> Device<'a> {
 
         let adapter = match adapter {
             Some(a) => a,
-            None => return Result::Err(wgpu::RequestDeviceError),
+            None => bail!("No adapter found for backends {:?}", backend_bits),
         };
 
         // == Create a device and a queue from the given adapter ==================================
 
-        if !adapter.features().contains(wgpu::Features::MAPPABLE_PRIMARY_BUFFERS) {
-            return Result::Err(wgpu::RequestDeviceError);
+        let adapter_name = adapter.get_info().name;
+        let missing_features = (device_options.required_features | wgpu::Features::MAPPABLE_PRIMARY_BUFFERS)
+            - adapter.features();
+        if !missing_features.is_empty() {
+            bail!(
+                "Adapter {} does not support the required features {:?}",
+                adapter_name,
+                missing_features
+            );
+        }
+
+        let missing_limits = unsupported_limits(&device_options.required_limits, &adapter.limits());
+        if !missing_limits.is_empty() {
+            bail!(
+                "Adapter {} does not support the required limits {}",
+                adapter_name,
+                missing_limits.join(", ")
+            );
         }
 
         let features = match device_options.use_adapter_features {
             true => adapter.features().bitor(wgpu::Features::MAPPABLE_PRIMARY_BUFFERS),
             false => wgpu::Features::MAPPABLE_PRIMARY_BUFFERS,
         };
+        let features = features.bitor(device_options.required_features);
 
         let limits = match device_options.use_adapter_limits {
             true => adapter.limits(),
             false => wgpu::Limits::default(),
         };
+        let limits = merge_limits(limits, &device_options.required_limits);
 
         let (wgpu_device, wgpu_queue) = adapter.request_device(
             &wgpu::DeviceDescriptor {
@@ -134,8 +175,11 @@ impl<'a> Device<'a> {
 
         let cs_module = Option::None;
         let compute_pipeline = Option::None;
+        let compute_pipelines = Vec::new();
 
         let bind_group_data = BTreeMap::new();
+        let indirect_buffers = BTreeMap::new();
+        let buffer_pool = BufferPool::default();
 
         Ok(Device {
             adapter,
@@ -143,7 +187,10 @@ impl<'a> Device<'a> {
             wgpu_queue,
             cs_module,
             bind_group_data,
+            indirect_buffers,
             compute_pipeline,
+            compute_pipelines,
+            buffer_pool,
         })
     }
 
@@ -184,6 +231,21 @@ impl<'a> Device<'a> {
         println!("Currently active limits: {:?}", self.wgpu_device.limits());
     }
 
+    /// Returns `true` if all of the given `feature`s are active on this device. Only active features may be used,
+    /// a feature that the adapter supports but that was not requested through [DeviceOptions] when creating this
+    /// device is reported as unsupported. Use this to check for optional capabilities (e.g. timestamp queries or push
+    /// constants) before taking a code path that requires them.
+    pub fn supports(&self, feature: wgpu::Features) -> bool {
+        self.wgpu_device.features().contains(feature)
+    }
+
+    /// Returns `true` if shaders running on this device may use 64-bit floating point values, i.e. if the
+    /// [SHADER_FLOAT64](wgpu::Features::SHADER_FLOAT64) feature is active. Without it, point attributes with `f64`
+    /// components (like the default positions) have to be converted to `f32` before they are used in a shader.
+    pub fn supports_shader_f64(&self) -> bool {
+        self.supports(wgpu::Features::SHADER_FLOAT64)
+    }
+
     /// Creates a UBO from `uniform_as_bytes` and returns a bind group together with a layout
     /// for it at the given `binding`.
     ///
@@ -238,15 +300,50 @@ impl<'a> Device<'a> {
     /// Associate a bind group and its layout with a given set on the shader side.
     /// Eg. if on the shader we have a buffer with `layout(std430, set=2, binding=0)`,
     /// then the passed in `index` should equal 2.
+    ///
+    /// The bindings of a `wgpu` bind group can't be inspected, so they are not validated here. Use
+    /// [set_bind_group_with_storage_bindings()](Device::set_bind_group_with_storage_bindings) to detect
+    /// duplicate bindings and bindings that don't match the shader.
     pub fn set_bind_group(&mut self, index: u32, bind_group_layout: &'a wgpu::BindGroupLayout, bind_group: &'a wgpu::BindGroup) {
         let bind_group_pair = BindGroupPair {
             bind_group_layout,
             bind_group,
+            storage_bindings: None,
         };
 
         self.bind_group_data.insert(index, bind_group_pair);
     }
 
+    /// Like [set_bind_group()](Device::set_bind_group), but additionally records the bindings of all storage buffers
+    /// in the bind group, e.g. the bindings of the `BufferInfoPerAttribute`s that were uploaded through a
+    /// [GpuPointBufferPerAttribute](crate::gpu::GpuPointBufferPerAttribute). When a GLSL compute shader is set afterwards,
+    /// these bindings are validated against the storage buffers that the shader declares in set `index`, see
+    /// [try_set_compute_shader_glsl()](Device::try_set_compute_shader_glsl).
+    ///
+    /// # Errors
+    /// If a binding occurs more than once in `storage_bindings`, an error containing the binding number is returned and
+    /// the bind group is not set. Such a bind group would otherwise only fail with a cryptic `wgpu` panic.
+    pub fn set_bind_group_with_storage_bindings(&mut self, index: u32, bind_group_layout: &'a wgpu::BindGroupLayout, bind_group: &'a wgpu::BindGroup, storage_bindings: &[u32]) -> Result<()> {
+        check_unique_storage_bindings(index, storage_bindings)?;
+        let bind_group_pair = BindGroupPair {
+            bind_group_layout,
+            bind_group,
+            storage_bindings: Some(storage_bindings.to_vec()),
+        };
+
+        self.bind_group_data.insert(index, bind_group_pair);
+        Ok(())
+    }
+
+    /// Registers `buffer` as the indirect buffer for [compute_indirect()](Device::compute_indirect) at the given
+    /// `binding`, which usually is the binding of the buffer in the pass that computes the work group counts (see
+    /// [GpuPointBufferPerAttribute::buffer_at_binding](crate::gpu::GpuPointBufferPerAttribute::buffer_at_binding)).
+    /// The buffer must have been created with the `INDIRECT` usage, e.g. through the `extra_usage` of its
+    /// [BufferInfoPerAttribute].
+    pub fn set_indirect_buffer(&mut self, binding: u32, buffer: &'a wgpu::Buffer) {
+        self.indirect_buffers.insert(binding, buffer);
+    }
+
     /// Sets up a compute pipeline with the passed in WGSL shader source code.
     pub fn set_compute_shader_wgsl(&mut self, wgsl_compute_shader_src: &str) {
         self.cs_module = Some(self.wgpu_device.create_shader_module(
@@ -262,38 +359,65 @@ impl<'a> Device<'a> {
     }
 
     /// Compiles the passed in GLSL shader source code into Spir-V and sets up a compute pipeline.
+    ///
+    /// # Panics
+    /// Will panic if the shader fails to compile or if its storage buffer bindings don't match the bind groups, see
+    /// [try_set_compute_shader_glsl()](Device::try_set_compute_shader_glsl) for a version that returns an error instead.
     pub fn set_compute_shader_glsl(&mut self, compute_shader_src: &str) {
-        self.cs_module = self.compile_glsl_and_create_compute_module(compute_shader_src);
+        self.try_set_compute_shader_glsl(compute_shader_src).unwrap();
+    }
+
+    /// Compiles the passed in GLSL shader source code into Spir-V and sets up a compute pipeline. For all bind groups
+    /// that were set via [set_bind_group_with_storage_bindings()](Device::set_bind_group_with_storage_bindings), the
+    /// storage buffers that the shader declares in the respective set are extracted from the Spir-V code and compared
+    /// against the recorded bindings.
+    ///
+    /// # Errors
+    /// If the shader fails to compile, or if a storage buffer binding is declared in the shader but missing in the bind
+    /// group (or vice versa), an error containing the missing and extra binding numbers is returned. The previous shader
+    /// and pipeline are kept in this case.
+    pub fn try_set_compute_shader_glsl(&mut self, compute_shader_src: &str) -> Result<()> {
+        let cs_spirv = compile_glsl_compute_spirv(compute_shader_src)?;
+        for (set, bind_group_pair) in self.bind_group_data.iter() {
+            if let Some(storage_bindings) = &bind_group_pair.storage_bindings {
+                validate_storage_buffer_bindings(cs_spirv.as_binary(), *set, storage_bindings)?;
+            }
+        }
+
+        self.cs_module = Some(create_compute_module_from_spirv(&self.wgpu_device, &cs_spirv));
 
         let pipeline = self.create_compute_pipeline(self.cs_module.as_ref().unwrap());
 
         self.compute_pipeline = Some(pipeline);
+        Ok(())
     }
 
-    fn compile_glsl_and_create_compute_module(&self, compute_shader_src: &str) -> Option<wgpu::ShaderModule> {
-        // WebGPU wants its shaders pre-compiled in binary SPIR-V format.
-        // So we'll take the source code of our compute shader and compile it
-        // with the help of the shaderc crate.
-        let mut compiler = shaderc::Compiler::new().unwrap();
-        let cs_spirv = compiler
-            .compile_into_spirv(
-                compute_shader_src,
-                shaderc::ShaderKind::Compute,
-                "Compute shader",
-                "main",
-                None,
-            )
-            .unwrap();
-        let cs_data = wgpu::util::make_spirv(cs_spirv.as_binary_u8());
-
-        // Now with the binary data we can create and return our ShaderModule,
-        // which will be executed on the GPU within our compute pipeline.
-        Some(
-            self.wgpu_device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-                label: Some("glsl_compute_shader_module"),
-                source: cs_data,
+    /// Compiles each of the passed in GLSL shader sources into Spir-V and sets up one compute pipeline per shader,
+    /// which can then be run in sequence through [compute_sequence()](Device::compute_sequence). This is useful for
+    /// algorithms with multiple passes over the same buffers, where a later pass reads the results of an earlier one
+    /// (e.g. computing the minimum of some values and then normalizing the values with it). All pipelines use the bind
+    /// groups that were set via [set_bind_group()](Device::set_bind_group) before this call.
+    ///
+    /// This replaces the shaders of a previous call to `set_compute_shaders`, but is independent of the single shader
+    /// set via [set_compute_shader_glsl()](Device::set_compute_shader_glsl) and [compute()](Device::compute).
+    ///
+    /// # Errors
+    /// If any of the shaders fails to compile, an error is returned and the previous pipelines are kept.
+    pub fn set_compute_shaders(&mut self, sources: &[&str]) -> Result<()> {
+        let pipelines = sources
+            .iter()
+            .map(|src| {
+                let cs_module = self.compile_glsl_and_create_compute_module(src)?;
+                Ok(self.create_compute_pipeline(&cs_module))
             })
-        )
+            .collect::<Result<Vec<_>>>()?;
+
+        self.compute_pipelines = pipelines;
+        Ok(())
+    }
+
+    fn compile_glsl_and_create_compute_module(&self, compute_shader_src: &str) -> Result<wgpu::ShaderModule> {
+        compile_glsl_compute_module(&self.wgpu_device, compute_shader_src)
     }
 
     fn create_compute_pipeline(&self, cs_module: &wgpu::ShaderModule) -> wgpu::ComputePipeline {
@@ -302,24 +426,33 @@ impl<'a> Device<'a> {
             .map(|pair| pair.bind_group_layout)
             .collect::<Vec<&'a wgpu::BindGroupLayout>>();
 
-        let compute_pipeline_layout = self.wgpu_device.create_pipeline_layout(
-            &wgpu::PipelineLayoutDescriptor {
-                label: Some("compute_pipeline_layout"),
-                bind_group_layouts: layouts.as_slice(),
-                push_constant_ranges: &[],
-            }
-        );
+        create_compute_pipeline_with_layouts(&self.wgpu_device, cs_module, layouts.as_slice())
+    }
 
-        let compute_pipeline = self.wgpu_device.create_compute_pipeline(
-            &wgpu::ComputePipelineDescriptor {
-                label: Some("compute_pipeline"),
-                layout: Some(&compute_pipeline_layout),
-                module: &cs_module,
-                entry_point: "main",
-            }
-        );
+    /// Resets the device to the state it was in right after creation, i.e. removes the compute
+    /// shader, the compute pipeline, all bind groups that were set via
+    /// [set_bind_group()](Device::set_bind_group) and all indirect buffers that were set via
+    /// [set_indirect_buffer()](Device::set_indirect_buffer). The underlying `wgpu` device and queue are kept,
+    /// so the same `Device` can be reused for an unrelated computation without having to request
+    /// a new adapter and device, which is expensive.
+    ///
+    /// Buffers that were uploaded through a `GpuPointBuffer` are not owned by the `Device` and thus
+    /// are not affected by this call. The pooled buffers of [run_compute()](Device::run_compute) that
+    /// were used since the last reset are kept, so that the next computation can reuse them. All other
+    /// pooled buffers are freed, see also [clear_pool()](Device::clear_pool).
+    pub fn reset(&mut self) {
+        self.buffer_pool.trim();
+        self.cs_module = None;
+        self.compute_pipeline = None;
+        self.compute_pipelines.clear();
+        self.bind_group_data.clear();
+        self.indirect_buffers.clear();
+    }
 
-        compute_pipeline
+    /// Frees the GPU memory of all buffers that [run_compute()](Device::run_compute) keeps for reuse.
+    /// The memory is allocated again by the next call to `run_compute`.
+    pub fn clear_pool(&mut self) {
+        self.buffer_pool.clear();
     }
 
     /// Launches compute work groups; `x`, `y`, `z` many in their respective dimensions.
@@ -329,43 +462,415 @@ impl<'a> Device<'a> {
     /// # Panics
     /// Will panic if no shader is set.
     pub fn compute(&mut self, x: u32, y: u32, z: u32) {
-        // Use a CommandEncoder to batch all commands that you wish to send to the GPU to execute.
-        // The resulting CommandBuffer can then be submitted to the GPU via a Queue.
-        // Signal the end of the batch with CommandEncoder#finish().
+        let bind_groups = self.bind_group_data
+            .values()
+            .map(|pair| pair.bind_group)
+            .collect::<Vec<&'a wgpu::BindGroup>>();
+
+        dispatch_compute(&self.wgpu_device, &self.wgpu_queue, self.compute_pipeline.as_ref().unwrap(), bind_groups.as_slice(), Dispatch::WorkGroups(x, y, z));
+    }
+
+    /// Runs the pipelines that were set up via [set_compute_shaders()](Device::set_compute_shaders) in order, launching
+    /// the work groups given by the respective entry of `dispatches` for each pipeline, i.e. `dispatches[i]` are the
+    /// `(x, y, z)` work groups of the `i`-th shader. All passes are recorded into a single command encoder and submitted
+    /// at once. Each shader runs in its own compute pass, and `wgpu` inserts the necessary barriers between the passes,
+    /// so every pass sees all writes of the previous passes to the shared buffers.
+    ///
+    /// # Panics
+    /// Will panic if the number of `dispatches` does not match the number of shaders that were set via
+    /// [set_compute_shaders()](Device::set_compute_shaders).
+    pub fn compute_sequence(&mut self, dispatches: &[(u32, u32, u32)]) {
+        if dispatches.len() != self.compute_pipelines.len() {
+            panic!(
+                "compute_sequence got {} dispatches, but {} compute shaders are set",
+                dispatches.len(),
+                self.compute_pipelines.len()
+            );
+        }
+
         let mut encoder =
-            self.wgpu_device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("command_encoder") });
+            self.wgpu_device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("command_encoder_sequence") });
 
-        {
-            // The compute pass will start ("dispatch") our compute shader.
+        for (pipeline, (x, y, z)) in self.compute_pipelines.iter().zip(dispatches.iter()) {
+            // A separate compute pass per shader makes sure that wgpu synchronizes the buffer accesses between them
             let mut compute_pass = encoder.begin_compute_pass(
                 &wgpu::ComputePassDescriptor {
-                    label: Some("compute_pass")
+                    label: Some("compute_pass_sequence")
                 }
             );
-            compute_pass.set_pipeline(self.compute_pipeline.as_ref().unwrap());
+            compute_pass.set_pipeline(pipeline);
 
             for (i, bind_group_pair) in self.bind_group_data.values().enumerate() {
                 compute_pass.set_bind_group(i as u32, bind_group_pair.bind_group, &[]);
             }
 
-            compute_pass.insert_debug_marker("Pasture Compute Debug");
-            compute_pass.dispatch(x, y, z);
+            compute_pass.insert_debug_marker("Pasture Compute Sequence Debug");
+            compute_pass.dispatch(*x, *y, *z);
         }
 
-        // Submit to queue
         self.wgpu_queue.submit(Some(encoder.finish()));
     }
+
+    /// Runs a complete compute pass on the points in `buffer` with a single call: Uploads the
+    /// attributes described by `buffer_infos` onto the GPU, compiles the GLSL compute shader in
+    /// `shader_src`, launches `dispatch` many work groups, waits for the results and writes them
+    /// back into `buffer`. The attributes are bound in per-attribute format at set 0 of the shader,
+    /// using the bindings of the respective `buffer_infos`.
+    ///
+    /// This is meant for one-shot kernels. The state of this `Device` (i.e. the shader and the bind
+    /// groups set via [set_compute_shader_glsl()](Device::set_compute_shader_glsl) and
+    /// [set_bind_group()](Device::set_bind_group)) is neither used nor modified, so for more complex
+    /// setups (e.g. additional uniforms or multiple passes over the same GPU buffers) use the
+    /// granular methods instead.
+    ///
+    /// The GPU buffers for the attributes are taken from a pool and put back into it afterwards, so
+    /// repeated calls with the same number of points and attributes don't allocate new GPU memory.
+    /// Pooled buffers keep their memory until they are not used between two calls to
+    /// [reset()](Device::reset), until [clear_pool()](Device::clear_pool) is called, or until the
+    /// `Device` is dropped.
+    ///
+    /// Returns the number of values that were clamped when writing the results back into `buffer`, because the
+    /// shader produced values outside of the range of the datatype of their attribute (see
+    /// [GpuPointBufferPerAttribute::download_into_per_attribute()](crate::gpu::GpuPointBufferPerAttribute::download_into_per_attribute)).
+    ///
+    /// # Errors
+    /// If the points can't be uploaded onto the GPU (see
+    /// [GpuPointBufferPerAttribute::upload()](crate::gpu::GpuPointBufferPerAttribute::upload)),
+    /// if the shader fails to compile, or if the storage buffers that the shader declares in set 0
+    /// don't match the bindings of `buffer_infos`, an error is returned.
+    pub async fn run_compute<'b, B: PerAttributePointBufferMut<'b>>(
+        &mut self,
+        buffer: &mut B,
+        buffer_infos: &[BufferInfoPerAttribute<'_>],
+        shader_src: &str,
+        dispatch: (u32, u32, u32)) -> Result<usize>
+    {
+        run_compute_on(&self.wgpu_device, &self.wgpu_queue, Some(&mut self.buffer_pool), buffer, buffer_infos, shader_src, dispatch).await
+    }
+
+    /// Moves the `wgpu` device and queue of this `Device` into a [SharedDevice], which can be shared between threads
+    /// (see [SharedDevice] for details). The shader and bind groups that were set on this `Device` are discarded.
+    pub fn into_shared(self) -> SharedDevice {
+        SharedDevice {
+            wgpu_device: Arc::new(self.wgpu_device),
+            wgpu_queue: Arc::new(self.wgpu_queue),
+        }
+    }
+
+    /// Launches compute work groups, reading the number of work groups in each dimension from the
+    /// indirect buffer that was set for `indirect_buffer_binding` via
+    /// [set_indirect_buffer()](Device::set_indirect_buffer). The buffer must contain three consecutive
+    /// `u32` values (`x`, `y`, `z`) at the given `offset`. This allows the work group counts to be
+    /// computed on the GPU in a previous pass (e.g. the number of points remaining after a filter pass),
+    /// without reading them back to the CPU.
+    ///
+    /// The indirect buffer must not be part of the bind groups of this dispatch, as `wgpu` does not
+    /// allow a buffer to be used as a writable storage buffer and as an indirect buffer in the same pass.
+    ///
+    /// # Panics
+    /// Will panic if no shader is set or if no indirect buffer was set for `indirect_buffer_binding`.
+    pub fn compute_indirect(&mut self, indirect_buffer_binding: u32, offset: u64) {
+        let indirect_buffer = *self.indirect_buffers
+            .get(&indirect_buffer_binding)
+            .unwrap_or_else(|| panic!("No indirect buffer is set for binding {}", indirect_buffer_binding));
+        let bind_groups = self.bind_group_data
+            .values()
+            .map(|pair| pair.bind_group)
+            .collect::<Vec<&'a wgpu::BindGroup>>();
+
+        dispatch_compute(&self.wgpu_device, &self.wgpu_queue, self.compute_pipeline.as_ref().unwrap(), bind_groups.as_slice(), Dispatch::Indirect(indirect_buffer, offset));
+    }
+}
+
+fn compile_glsl_compute_module(wgpu_device: &wgpu::Device, compute_shader_src: &str) -> Result<wgpu::ShaderModule> {
+    let cs_spirv = compile_glsl_compute_spirv(compute_shader_src)?;
+    Ok(create_compute_module_from_spirv(wgpu_device, &cs_spirv))
+}
+
+fn compile_glsl_compute_spirv(compute_shader_src: &str) -> Result<shaderc::CompilationArtifact> {
+    // WebGPU wants its shaders pre-compiled in binary SPIR-V format.
+    // So we'll take the source code of our compute shader and compile it
+    // with the help of the shaderc crate.
+    let mut compiler = shaderc::Compiler::new().ok_or_else(|| anyhow!("Could not create shader compiler"))?;
+    let cs_spirv = compiler
+        .compile_into_spirv(
+            compute_shader_src,
+            shaderc::ShaderKind::Compute,
+            "Compute shader",
+            "main",
+            None,
+        )?;
+    Ok(cs_spirv)
+}
+
+fn create_compute_module_from_spirv(wgpu_device: &wgpu::Device, cs_spirv: &shaderc::CompilationArtifact) -> wgpu::ShaderModule {
+    let cs_data = wgpu::util::make_spirv(cs_spirv.as_binary_u8());
+
+    // Now with the binary data we can create and return our ShaderModule,
+    // which will be executed on the GPU within our compute pipeline.
+    wgpu_device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("glsl_compute_shader_module"),
+        source: cs_data,
+    })
+}
+
+fn create_compute_pipeline_with_layouts(wgpu_device: &wgpu::Device, cs_module: &wgpu::ShaderModule, layouts: &[&wgpu::BindGroupLayout]) -> wgpu::ComputePipeline {
+    let compute_pipeline_layout = wgpu_device.create_pipeline_layout(
+        &wgpu::PipelineLayoutDescriptor {
+            label: Some("compute_pipeline_layout"),
+            bind_group_layouts: layouts,
+            push_constant_ranges: &[],
+        }
+    );
+
+    let compute_pipeline = wgpu_device.create_compute_pipeline(
+        &wgpu::ComputePipelineDescriptor {
+            label: Some("compute_pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &cs_module,
+            entry_point: "main",
+        }
+    );
+
+    compute_pipeline
+}
+
+// The number of work groups for `dispatch_compute`, either given directly or read from an indirect buffer at an offset
+enum Dispatch<'b> {
+    WorkGroups(u32, u32, u32),
+    Indirect(&'b wgpu::Buffer, wgpu::BufferAddress),
+}
+
+fn dispatch_compute(wgpu_device: &wgpu::Device, wgpu_queue: &wgpu::Queue, pipeline: &wgpu::ComputePipeline, bind_groups: &[&wgpu::BindGroup], dispatch: Dispatch) {
+    // Use a CommandEncoder to batch all commands that you wish to send to the GPU to execute.
+    // The resulting CommandBuffer can then be submitted to the GPU via a Queue.
+    // Signal the end of the batch with CommandEncoder#finish().
+    let mut encoder =
+        wgpu_device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("command_encoder") });
+
+    {
+        // The compute pass will start ("dispatch") our compute shader.
+        let mut compute_pass = encoder.begin_compute_pass(
+            &wgpu::ComputePassDescriptor {
+                label: Some("compute_pass")
+            }
+        );
+        compute_pass.set_pipeline(pipeline);
+
+        for (i, bind_group) in bind_groups.iter().enumerate() {
+            compute_pass.set_bind_group(i as u32, bind_group, &[]);
+        }
+
+        compute_pass.insert_debug_marker("Pasture Compute Debug");
+        match dispatch {
+            Dispatch::WorkGroups(x, y, z) => compute_pass.dispatch(x, y, z),
+            Dispatch::Indirect(indirect_buffer, offset) => compute_pass.dispatch_indirect(indirect_buffer, offset),
+        }
+    }
+
+    // Submit to queue
+    wgpu_queue.submit(Some(encoder.finish()));
+}
+
+// Uploads the attributes of `buffer` described by `buffer_infos`, runs the GLSL compute shader `shader_src` on them and
+// downloads the results back into `buffer`. This only requires shared access to the `wgpu` device and queue, so it is
+// used by both `Device` and `SharedDevice`. If a `buffer_pool` is given, the GPU buffers are taken from and returned
+// into it. Returns the number of values that were clamped during the download.
+pub(crate) async fn run_compute_on<'b, B: PerAttributePointBufferMut<'b>>(
+    wgpu_device: &wgpu::Device,
+    wgpu_queue: &wgpu::Queue,
+    mut buffer_pool: Option<&mut BufferPool>,
+    buffer: &mut B,
+    buffer_infos: &[BufferInfoPerAttribute<'_>],
+    shader_src: &str,
+    dispatch: (u32, u32, u32)) -> Result<usize>
+{
+    let buffer_infos = buffer_infos
+        .iter()
+        .map(|info| BufferInfoPerAttribute {
+            attribute: info.attribute,
+            binding: info.binding,
+            extra_usage: info.extra_usage,
+        })
+        .collect::<Vec<_>>();
+    let num_points = buffer.len();
+
+    let mut gpu_point_buffer = GpuPointBufferPerAttribute::new();
+    match buffer_pool.as_deref_mut() {
+        Some(pool) => gpu_point_buffer.malloc_from_pool(num_points as u64, &buffer_infos, wgpu_device, pool),
+        None => gpu_point_buffer.malloc(num_points as u64, &buffer_infos, wgpu_device),
+    }
+
+    let result = compute_and_download(wgpu_device, wgpu_queue, &mut gpu_point_buffer, buffer, &buffer_infos, shader_src, dispatch).await;
+
+    // Return the buffers into the pool even if the computation failed
+    if let Some(pool) = buffer_pool {
+        gpu_point_buffer.release_into_pool(pool);
+    }
+    result
+}
+
+async fn compute_and_download<'b, B: PerAttributePointBufferMut<'b>>(
+    wgpu_device: &wgpu::Device,
+    wgpu_queue: &wgpu::Queue,
+    gpu_point_buffer: &mut GpuPointBufferPerAttribute<'_>,
+    buffer: &mut B,
+    buffer_infos: &Vec<BufferInfoPerAttribute<'_>>,
+    shader_src: &str,
+    dispatch: (u32, u32, u32)) -> Result<usize>
+{
+    let cs_spirv = compile_glsl_compute_spirv(shader_src)?;
+    let bindings = buffer_infos.iter().map(|info| info.binding).collect::<Vec<_>>();
+    validate_storage_buffer_bindings(cs_spirv.as_binary(), 0, &bindings)?;
+
+    let num_points = buffer.len();
+    gpu_point_buffer.upload(&*buffer, 0..num_points, buffer_infos, wgpu_device, wgpu_queue)?;
+
+    let cs_module = create_compute_module_from_spirv(wgpu_device, &cs_spirv);
+    let bind_group_layout = gpu_point_buffer.bind_group_layout.as_ref().unwrap();
+    let bind_group = gpu_point_buffer.bind_group.as_ref().unwrap();
+    let pipeline = create_compute_pipeline_with_layouts(wgpu_device, &cs_module, &[bind_group_layout]);
+
+    let (x, y, z) = dispatch;
+    dispatch_compute(wgpu_device, wgpu_queue, &pipeline, &[bind_group], Dispatch::WorkGroups(x, y, z));
+
+    let num_out_of_range = gpu_point_buffer
+        .download_into_per_attribute(buffer, 0..num_points, buffer_infos, wgpu_device)
+        .await?;
+
+    Ok(num_out_of_range)
+}
+
+// Merges the `required` limits into the `base` limits, i.e. takes the less restrictive value of both.
+fn merge_limits(base: wgpu::Limits, required: &wgpu::Limits) -> wgpu::Limits {
+    wgpu::Limits {
+        max_texture_dimension_1d: base
+            .max_texture_dimension_1d
+            .max(required.max_texture_dimension_1d),
+        max_texture_dimension_2d: base
+            .max_texture_dimension_2d
+            .max(required.max_texture_dimension_2d),
+        max_texture_dimension_3d: base
+            .max_texture_dimension_3d
+            .max(required.max_texture_dimension_3d),
+        max_texture_array_layers: base
+            .max_texture_array_layers
+            .max(required.max_texture_array_layers),
+        max_bind_groups: base.max_bind_groups.max(required.max_bind_groups),
+        max_dynamic_uniform_buffers_per_pipeline_layout: base
+            .max_dynamic_uniform_buffers_per_pipeline_layout
+            .max(required.max_dynamic_uniform_buffers_per_pipeline_layout),
+        max_dynamic_storage_buffers_per_pipeline_layout: base
+            .max_dynamic_storage_buffers_per_pipeline_layout
+            .max(required.max_dynamic_storage_buffers_per_pipeline_layout),
+        max_sampled_textures_per_shader_stage: base
+            .max_sampled_textures_per_shader_stage
+            .max(required.max_sampled_textures_per_shader_stage),
+        max_samplers_per_shader_stage: base
+            .max_samplers_per_shader_stage
+            .max(required.max_samplers_per_shader_stage),
+        max_storage_buffers_per_shader_stage: base
+            .max_storage_buffers_per_shader_stage
+            .max(required.max_storage_buffers_per_shader_stage),
+        max_storage_textures_per_shader_stage: base
+            .max_storage_textures_per_shader_stage
+            .max(required.max_storage_textures_per_shader_stage),
+        max_uniform_buffers_per_shader_stage: base
+            .max_uniform_buffers_per_shader_stage
+            .max(required.max_uniform_buffers_per_shader_stage),
+        max_uniform_buffer_binding_size: base
+            .max_uniform_buffer_binding_size
+            .max(required.max_uniform_buffer_binding_size),
+        max_storage_buffer_binding_size: base
+            .max_storage_buffer_binding_size
+            .max(required.max_storage_buffer_binding_size),
+        max_vertex_buffers: base.max_vertex_buffers.max(required.max_vertex_buffers),
+        max_vertex_attributes: base
+            .max_vertex_attributes
+            .max(required.max_vertex_attributes),
+        max_vertex_buffer_array_stride: base
+            .max_vertex_buffer_array_stride
+            .max(required.max_vertex_buffer_array_stride),
+        max_push_constant_size: base
+            .max_push_constant_size
+            .max(required.max_push_constant_size),
+        // Alignments are less restrictive the smaller they are
+        min_uniform_buffer_offset_alignment: base
+            .min_uniform_buffer_offset_alignment
+            .min(required.min_uniform_buffer_offset_alignment),
+        min_storage_buffer_offset_alignment: base
+            .min_storage_buffer_offset_alignment
+            .min(required.min_storage_buffer_offset_alignment),
+    }
+}
+
+// Returns the names of all `required` limits that exceed the `supported` limits.
+fn unsupported_limits(required: &wgpu::Limits, supported: &wgpu::Limits) -> Vec<&'static str> {
+    let mut unsupported = vec![];
+    macro_rules! check_limits {
+        ($cmp:tt: $($limit:ident),*) => {
+            $(
+                if required.$limit $cmp supported.$limit {
+                    unsupported.push(stringify!($limit));
+                }
+            )*
+        };
+    }
+    check_limits!(>:
+        max_texture_dimension_1d,
+        max_texture_dimension_2d,
+        max_texture_dimension_3d,
+        max_texture_array_layers,
+        max_bind_groups,
+        max_dynamic_uniform_buffers_per_pipeline_layout,
+        max_dynamic_storage_buffers_per_pipeline_layout,
+        max_sampled_textures_per_shader_stage,
+        max_samplers_per_shader_stage,
+        max_storage_buffers_per_shader_stage,
+        max_storage_textures_per_shader_stage,
+        max_uniform_buffers_per_shader_stage,
+        max_uniform_buffer_binding_size,
+        max_storage_buffer_binding_size,
+        max_vertex_buffers,
+        max_vertex_attributes,
+        max_vertex_buffer_array_stride,
+        max_push_constant_size
+    );
+    // Alignments are less restrictive the smaller they are
+    check_limits!(<: min_uniform_buffer_offset_alignment, min_storage_buffer_offset_alignment);
+    unsupported
 }
 
 // == Helper types ===============================================================================
 
-// TODO: it may be beneficial to be more flexible about features and limits.
 /// Defines the desired capabilities of a device that is to be retrieved.
+///
+/// Specific features and limits can be requested through `required_features` and `required_limits`.
+/// These are merged with the default features and limits (or those of the adapter, if
+/// `use_adapter_features` or `use_adapter_limits` are set). For example, to enable push constants and
+/// 12 storage buffers per shader stage without enabling all features of the adapter:
+///
+/// ```
+/// use pasture_core::gpu;
+///
+/// let options = gpu::DeviceOptions {
+///     required_features: wgpu::Features::PUSH_CONSTANTS | wgpu::Features::TIMESTAMP_QUERY,
+///     required_limits: wgpu::Limits {
+///         max_storage_buffers_per_shader_stage: 12,
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// ```
 pub struct DeviceOptions {
     pub device_power: DevicePower,
     pub device_backend: DeviceBackend,
     pub use_adapter_features: bool,
     pub use_adapter_limits: bool,
+    /// Features that the device must support in addition to the default features
+    pub required_features: wgpu::Features,
+    /// Limits that the device must support in addition to the default limits
+    pub required_limits: wgpu::Limits,
 }
 
 impl Default for DeviceOptions {
@@ -376,10 +881,79 @@ impl Default for DeviceOptions {
             device_backend: DeviceBackend::Vulkan,
             use_adapter_features: false,
             use_adapter_limits: false,
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+        }
+    }
+}
+
+impl DeviceOptions {
+    /// Returns a [DeviceOptionsBuilder] to configure `DeviceOptions` fluently. All options that are not set on the
+    /// builder keep their default values:
+    ///
+    /// ```
+    /// use pasture_core::gpu;
+    ///
+    /// let options = gpu::DeviceOptions::builder()
+    ///     .power(gpu::DevicePower::High)
+    ///     .use_adapter_limits(true)
+    ///     .build();
+    /// ```
+    pub fn builder() -> DeviceOptionsBuilder {
+        DeviceOptionsBuilder {
+            options: Default::default(),
         }
     }
 }
 
+/// Builder for [DeviceOptions]
+pub struct DeviceOptionsBuilder {
+    options: DeviceOptions,
+}
+
+impl DeviceOptionsBuilder {
+    /// Sets the kind of GPU that should be retrieved
+    pub fn power(mut self, device_power: DevicePower) -> Self {
+        self.options.device_power = device_power;
+        self
+    }
+
+    /// Sets the backend that should be used
+    pub fn backend(mut self, device_backend: DeviceBackend) -> Self {
+        self.options.device_backend = device_backend;
+        self
+    }
+
+    /// Sets whether all features of the adapter should be enabled
+    pub fn use_adapter_features(mut self, use_adapter_features: bool) -> Self {
+        self.options.use_adapter_features = use_adapter_features;
+        self
+    }
+
+    /// Sets whether the limits of the adapter should be used instead of the default limits
+    pub fn use_adapter_limits(mut self, use_adapter_limits: bool) -> Self {
+        self.options.use_adapter_limits = use_adapter_limits;
+        self
+    }
+
+    /// Sets the features that the device must support in addition to the default features
+    pub fn required_features(mut self, required_features: wgpu::Features) -> Self {
+        self.options.required_features = required_features;
+        self
+    }
+
+    /// Sets the limits that the device must support in addition to the default limits
+    pub fn required_limits(mut self, required_limits: wgpu::Limits) -> Self {
+        self.options.required_limits = required_limits;
+        self
+    }
+
+    /// Creates the `DeviceOptions`
+    pub fn build(self) -> DeviceOptions {
+        self.options
+    }
+}
+
 /// Controls which kind of GPU should be retrieved.
 pub enum DevicePower {
     /// Usually an integrated GPU
@@ -396,6 +970,7 @@ impl Default for DevicePower {
 /// Currently only `Vulkan` is supported, because it is the only backend that allows 64-bit floats
 /// on the shader side.
 /// In the future, support for other backends such as `DirectX12` and `Metal` may be added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DeviceBackend {
     // /// Primary backends for wgpu: Vulkan, Metal, Dx12, Browser
     // Primary,
@@ -407,6 +982,36 @@ pub enum DeviceBackend {
     // Dx11,
     // OpenGL,
     // Browser,
+    /// Tries all backends in the order given by [AUTO_BACKEND_PRIORITY] and uses the first one that yields a
+    /// working adapter and device. This is useful on systems where a backend is present but broken, e.g. a
+    /// faulty Vulkan driver. Note that shaders that use 64-bit floats only work if `Vulkan` is picked
+    Auto,
+}
+
+/// The order in which [DeviceBackend::Auto] tries the `wgpu` backends
+pub const AUTO_BACKEND_PRIORITY: &[wgpu::Backends] = &[
+    wgpu::Backends::VULKAN,
+    wgpu::Backends::METAL,
+    wgpu::Backends::DX12,
+    wgpu::Backends::GL,
+    wgpu::Backends::DX11,
+];
+
+/// Calls `try_backend` for each of the given `backends` in order and returns the first successful result. If no
+/// backend succeeds, an error listing the reason for each backend is returned
+async fn probe_backends<T, F, Fut>(backends: &[wgpu::Backends], mut try_backend: F) -> Result<T>
+where
+    F: FnMut(wgpu::Backends) -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut errors = vec![];
+    for &backend in backends {
+        match try_backend(backend).await {
+            Ok(result) => return Ok(result),
+            Err(e) => errors.push(format!("{:?}: {}", backend, e)),
+        }
+    }
+    bail!("No backend could provide a suitable device ({})", errors.join("; "))
 }
 
 impl Default for DeviceBackend {
@@ -440,16 +1045,21 @@ impl Default for DeviceBackend {
 ///     gpu::BufferInfoPerAttribute {
 ///         attribute: &attributes::POSITION_3D,
 ///         binding: 0,
+///         extra_usage: wgpu::BufferUsages::empty(),
 ///     },
 ///     gpu::BufferInfoPerAttribute {
 ///         attribute: &attributes::INTENSITY,
 ///         binding: 1,
+///         extra_usage: wgpu::BufferUsages::empty(),
 ///     },
 /// ];
 /// ```
 pub struct BufferInfoPerAttribute<'a> {
     pub attribute: &'a layout::PointAttributeDefinition,
     pub binding: u32,
+    /// Usages of the GPU buffer in addition to the default usages of a storage buffer, e.g. `VERTEX` to bind the
+    /// buffer as a vertex buffer in a render pipeline. Use `wgpu::BufferUsages::empty()` for the default usages
+    pub extra_usage: wgpu::BufferUsages,
 }
 
 /// Associates interleaved point buffer attributes with a struct in a shader at the given binding.
@@ -481,16 +1091,581 @@ pub struct BufferInfoPerAttribute<'a> {
 ///            attributes::POSITION_3D,
 ///            attributes::INTENSITY,
 ///        ],
-///        binding: 0
+///        binding: 0,
+///        extra_usage: wgpu::BufferUsages::empty(),
 /// };
 /// ```
 pub struct BufferInfoInterleaved<'a> {
     pub attributes: &'a [layout::PointAttributeDefinition],
     pub binding: u32,
+    /// Usages of the GPU buffer in addition to the default usages of a storage buffer, e.g. `VERTEX` to bind the
+    /// buffer as a vertex buffer in a render pipeline. Use `wgpu::BufferUsages::empty()` for the default usages
+    pub extra_usage: wgpu::BufferUsages,
+}
+
+// Returns an error if a binding occurs more than once in the `storage_bindings` of the bind group at set `index`
+fn check_unique_storage_bindings(index: u32, storage_bindings: &[u32]) -> Result<()> {
+    let mut seen_bindings = BTreeSet::new();
+    for binding in storage_bindings {
+        if !seen_bindings.insert(*binding) {
+            bail!("Binding {} is used by more than one storage buffer in set {}", binding, index);
+        }
+    }
+    Ok(())
 }
 
 // Helper struct to have a bind group tightly coupled with its layout.
 struct BindGroupPair<'a> {
     bind_group_layout: &'a wgpu::BindGroupLayout,
     bind_group: &'a wgpu::BindGroup,
+    // Bindings of the storage buffers in the bind group, if known. Used to validate the bindings against the shader.
+    storage_bindings: Option<Vec<u32>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_options_builder() {
+        let options = DeviceOptions::builder()
+            .power(DevicePower::High)
+            .use_adapter_limits(true)
+            .build();
+        assert!(matches!(options.device_power, DevicePower::High));
+        assert!(options.use_adapter_limits);
+
+        let defaults = DeviceOptions::default();
+        assert_eq!(defaults.device_backend, options.device_backend);
+        assert_eq!(defaults.use_adapter_features, options.use_adapter_features);
+        assert_eq!(defaults.required_features, options.required_features);
+        assert_eq!(defaults.required_limits, options.required_limits);
+
+        let options = DeviceOptions::builder()
+            .backend(DeviceBackend::Auto)
+            .use_adapter_features(true)
+            .required_features(wgpu::Features::PUSH_CONSTANTS)
+            .build();
+        assert!(matches!(options.device_power, DevicePower::Low));
+        assert_eq!(DeviceBackend::Auto, options.device_backend);
+        assert!(options.use_adapter_features);
+        assert!(!options.use_adapter_limits);
+        assert_eq!(wgpu::Features::PUSH_CONSTANTS, options.required_features);
+    }
+
+    #[test]
+    fn test_check_unique_storage_bindings() {
+        assert!(check_unique_storage_bindings(0, &[0, 1, 2]).is_ok());
+        let error = check_unique_storage_bindings(2, &[0, 1, 0])
+            .expect_err("Duplicate bindings must yield an error");
+        assert!(error.to_string().contains("Binding 0"));
+        assert!(error.to_string().contains("set 2"));
+    }
+
+    #[test]
+    fn test_merge_limits() {
+        let required = wgpu::Limits {
+            max_storage_buffers_per_shader_stage: 12,
+            min_storage_buffer_offset_alignment: 512,
+            ..wgpu::Limits::downlevel_defaults()
+        };
+        let merged = merge_limits(wgpu::Limits::default(), &required);
+
+        assert_eq!(12, merged.max_storage_buffers_per_shader_stage);
+        assert_eq!(wgpu::Limits::default().min_storage_buffer_offset_alignment, merged.min_storage_buffer_offset_alignment);
+        assert_eq!(wgpu::Limits::default().max_texture_dimension_2d, merged.max_texture_dimension_2d);
+        assert!(unsupported_limits(&wgpu::Limits::downlevel_defaults(), &wgpu::Limits::default()).is_empty());
+        assert_eq!(
+            vec!["max_storage_buffers_per_shader_stage"],
+            unsupported_limits(&required, &wgpu::Limits::default())
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        use crate::containers::{PerAttributeVecPointStorage, PointBufferExt};
+        use crate::layout::{attributes, PointLayout};
+
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let mut device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+
+            let buffer_infos = vec![BufferInfoPerAttribute {
+                attribute: &attributes::INTENSITY,
+                binding: 0,
+                extra_usage: wgpu::BufferUsages::empty(),
+            }];
+            let make_points = |intensities: &[u16]| {
+                let mut points = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+                let mut pusher = points.begin_push_attributes();
+                pusher.push_attribute_range(&attributes::INTENSITY, intensities);
+                pusher.done();
+                points
+            };
+            let make_shader = |operation: &str| format!("
+                #version 450
+                layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+                layout(std430, set = 0, binding = 0) buffer Intensities {{
+                    uint intensities[];
+                }};
+                void main() {{
+                    uint idx = gl_GlobalInvocationID.x;
+                    intensities[idx] = {};
+                }}", operation);
+
+            let mut first_points = make_points(&[1, 2, 3]);
+            let mut first_gpu_buffer = GpuPointBufferPerAttribute::new();
+            first_gpu_buffer.malloc(3, &buffer_infos, &device.wgpu_device);
+            first_gpu_buffer
+                .upload(&first_points, 0..3, &buffer_infos, &device.wgpu_device, &device.wgpu_queue)
+                .expect("Uploading the first points failed");
+            device.set_bind_group(0, first_gpu_buffer.bind_group_layout.as_ref().unwrap(), first_gpu_buffer.bind_group.as_ref().unwrap());
+            device.set_compute_shader_glsl(&make_shader("intensities[idx] * 2"));
+            assert!(device.cs_module.is_some());
+            assert!(device.compute_pipeline.is_some());
+            device.compute(3, 1, 1);
+            first_gpu_buffer
+                .download_into_per_attribute(&mut first_points, 0..3, &buffer_infos, &device.wgpu_device)
+                .await
+                .expect("Downloading the first points failed");
+            assert_eq!(vec![2, 4, 6], first_points.iter_attribute::<u16>(&attributes::INTENSITY).collect::<Vec<_>>());
+
+            device.reset();
+            assert!(device.cs_module.is_none());
+            assert!(device.compute_pipeline.is_none());
+            assert!(device.bind_group_data.is_empty());
+
+            // After a reset, a new upload and computation only uses the new buffers and the new shader
+            let mut second_points = make_points(&[10, 20, 30, 40]);
+            let mut second_gpu_buffer = GpuPointBufferPerAttribute::new();
+            second_gpu_buffer.malloc(4, &buffer_infos, &device.wgpu_device);
+            second_gpu_buffer
+                .upload(&second_points, 0..4, &buffer_infos, &device.wgpu_device, &device.wgpu_queue)
+                .expect("Uploading the second points failed");
+            device.set_bind_group(0, second_gpu_buffer.bind_group_layout.as_ref().unwrap(), second_gpu_buffer.bind_group.as_ref().unwrap());
+            device.set_compute_shader_glsl(&make_shader("intensities[idx] + 1"));
+            device.compute(4, 1, 1);
+            second_gpu_buffer
+                .download_into_per_attribute(&mut second_points, 0..4, &buffer_infos, &device.wgpu_device)
+                .await
+                .expect("Downloading the second points failed");
+            assert_eq!(vec![11, 21, 31, 41], second_points.iter_attribute::<u16>(&attributes::INTENSITY).collect::<Vec<_>>());
+
+            // The buffers of the first run were not touched by the second computation
+            first_gpu_buffer
+                .download_into_per_attribute(&mut first_points, 0..3, &buffer_infos, &device.wgpu_device)
+                .await
+                .expect("Downloading the first points failed");
+            assert_eq!(vec![2, 4, 6], first_points.iter_attribute::<u16>(&attributes::INTENSITY).collect::<Vec<_>>());
+        });
+    }
+
+    #[test]
+    fn test_run_compute() {
+        use crate::containers::{PerAttributeVecPointStorage, PointBufferExt};
+        use crate::layout::{attributes, PointLayout};
+
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let mut device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+
+            let mut points = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+            let mut pusher = points.begin_push_attributes();
+            pusher.push_attribute_range(&attributes::INTENSITY, &[1_u16, 2, 3]);
+            pusher.done();
+
+            let buffer_infos = [BufferInfoPerAttribute {
+                attribute: &attributes::INTENSITY,
+                binding: 0,
+                extra_usage: wgpu::BufferUsages::empty(),
+            }];
+            let shader_src = "
+                #version 450
+                layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+                layout(std430, set = 0, binding = 0) buffer Intensities {
+                    uint intensities[];
+                };
+                void main() {
+                    uint idx = gl_GlobalInvocationID.x;
+                    intensities[idx] *= 2;
+                }";
+
+            let num_out_of_range = device
+                .run_compute(&mut points, &buffer_infos, shader_src, (3, 1, 1))
+                .await
+                .expect("run_compute failed");
+
+            let intensities = points.iter_attribute::<u16>(&attributes::INTENSITY).collect::<Vec<_>>();
+            assert_eq!(vec![2, 4, 6], intensities);
+            assert_eq!(0, num_out_of_range);
+
+            let error = device
+                .run_compute(&mut points, &buffer_infos, "not a shader", (3, 1, 1))
+                .await;
+            assert!(error.is_err());
+        });
+    }
+
+    #[test]
+    fn test_run_compute_reuses_pooled_buffers() {
+        use crate::containers::{PerAttributeVecPointStorage, PointBufferExt, PointBufferWriteable};
+        use crate::layout::{attributes, PointLayout};
+
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let mut device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+
+            let buffer_infos = [BufferInfoPerAttribute {
+                attribute: &attributes::INTENSITY,
+                binding: 0,
+                extra_usage: wgpu::BufferUsages::empty(),
+            }];
+            let shader_src = "
+                #version 450
+                layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+                layout(std430, set = 0, binding = 0) buffer Intensities {
+                    uint intensities[];
+                };
+                void main() {
+                    intensities[gl_GlobalInvocationID.x] += 1;
+                }";
+            let mut points = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[attributes::INTENSITY]));
+            points.resize(16);
+
+            for _ in 0..3 {
+                device.run_compute(&mut points, &buffer_infos, shader_src, (16, 1, 1)).await.expect("run_compute failed");
+                assert_eq!(1, device.buffer_pool.len());
+            }
+            assert!(points.iter_attribute::<u16>(&attributes::INTENSITY).all(|intensity| intensity == 3));
+
+            // A different number of points requires a buffer of a different size
+            points.resize(8);
+            device.run_compute(&mut points, &buffer_infos, shader_src, (8, 1, 1)).await.expect("run_compute failed");
+            assert_eq!(2, device.buffer_pool.len());
+
+            // Buffers are returned into the pool even if the computation fails
+            assert!(device.run_compute(&mut points, &buffer_infos, "not a shader", (8, 1, 1)).await.is_err());
+            assert_eq!(2, device.buffer_pool.len());
+
+            // Both buffers were used since the pool was created, so they are kept
+            device.reset();
+            assert_eq!(2, device.buffer_pool.len());
+
+            // Only the buffer for 8 points is used until the next reset, so the other one is freed
+            device.run_compute(&mut points, &buffer_infos, shader_src, (8, 1, 1)).await.expect("run_compute failed");
+            device.reset();
+            assert_eq!(1, device.buffer_pool.len());
+
+            device.clear_pool();
+            assert_eq!(0, device.buffer_pool.len());
+        });
+    }
+
+    #[test]
+    fn test_compute_sequence() {
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let mut device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+
+            let values = [4.0_f32, 6.0, 2.0, 10.0];
+            let values_buffer = device.wgpu_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("values_buffer"),
+                contents: bytemuck::cast_slice(&values),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::MAP_READ,
+            });
+            let min_buffer = device.wgpu_device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("min_buffer"),
+                size: 4,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            });
+
+            let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            };
+            let layout = device.wgpu_device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("sequence_bind_group_layout"),
+                entries: &[storage_entry(0), storage_entry(1)],
+            });
+            let group = device.wgpu_device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("sequence_bind_group"),
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: values_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: min_buffer.as_entire_binding() },
+                ],
+            });
+            device.set_bind_group(0, &layout, &group);
+
+            let declarations = "
+                #version 450
+                layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+                layout(std430, set = 0, binding = 0) buffer Values {
+                    float values[];
+                };
+                layout(std430, set = 0, binding = 1) buffer Min {
+                    float min_value;
+                };";
+            let compute_min = format!("{}
+                void main() {{
+                    float current_min = values[0];
+                    for (uint i = 1; i < values.length(); ++i) {{
+                        current_min = min(current_min, values[i]);
+                    }}
+                    min_value = current_min;
+                }}", declarations);
+            let normalize = format!("{}
+                void main() {{
+                    uint idx = gl_GlobalInvocationID.x;
+                    values[idx] = values[idx] / min_value;
+                }}", declarations);
+
+            device
+                .set_compute_shaders(&[&compute_min, &normalize])
+                .expect("Compiling the shaders failed");
+            device.compute_sequence(&[(1, 1, 1), (values.len() as u32, 1, 1)]);
+
+            let values_slice = values_buffer.slice(..);
+            let mapped_future = values_slice.map_async(wgpu::MapMode::Read);
+            device.wgpu_device.poll(wgpu::Maintain::Wait);
+            mapped_future.await.expect("Mapping the values buffer failed");
+            let normalized: Vec<f32> = bytemuck::cast_slice(&values_slice.get_mapped_range()).to_vec();
+            assert_eq!(vec![2.0, 3.0, 1.0, 5.0], normalized);
+
+            assert!(device.set_compute_shaders(&["not a shader"]).is_err());
+            assert_eq!(2, device.compute_pipelines.len());
+            device.reset();
+            assert!(device.compute_pipelines.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_compute_indirect() {
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let mut device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+
+            let values = [3_u32, 8, 1, 12, 7, 20];
+            let values_buffer = device.wgpu_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("values_buffer"),
+                contents: bytemuck::cast_slice(&values),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+            let selected_buffer = device.wgpu_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("selected_buffer"),
+                contents: bytemuck::cast_slice(&[0_u32; 6]),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::MAP_READ,
+            });
+            let indirect_buffer = device.wgpu_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("indirect_buffer"),
+                contents: bytemuck::cast_slice(&[0_u32; 3]),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::MAP_READ,
+            });
+
+            let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            };
+            let points_layout = device.wgpu_device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("points_bind_group_layout"),
+                entries: &[storage_entry(0), storage_entry(1)],
+            });
+            let points_group = device.wgpu_device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("points_bind_group"),
+                layout: &points_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: values_buffer.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: selected_buffer.as_entire_binding() },
+                ],
+            });
+            let indirect_layout = device.wgpu_device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("indirect_bind_group_layout"),
+                entries: &[storage_entry(0)],
+            });
+            let indirect_group = device.wgpu_device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("indirect_bind_group"),
+                layout: &indirect_layout,
+                entries: &[wgpu::BindGroupEntry { binding: 0, resource: indirect_buffer.as_entire_binding() }],
+            });
+
+            let declarations = "
+                #version 450
+                layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+                layout(std430, set = 0, binding = 0) buffer Values {
+                    uint values[];
+                };
+                layout(std430, set = 0, binding = 1) buffer Selected {
+                    uint selected[];
+                };";
+            // The count pass selects all values greater than 5 and writes the number of work groups for the second pass
+            let count = format!("{}
+                layout(std430, set = 1, binding = 0) buffer IndirectArgs {{
+                    uint args[3];
+                }};
+                void main() {{
+                    uint count = 0;
+                    for (uint i = 0; i < values.length(); ++i) {{
+                        if (values[i] > 5) {{
+                            selected[count] = values[i];
+                            ++count;
+                        }}
+                    }}
+                    args[0] = count;
+                    args[1] = 1;
+                    args[2] = 1;
+                }}", declarations);
+            let process = format!("{}
+                void main() {{
+                    uint idx = gl_GlobalInvocationID.x;
+                    selected[idx] += 100;
+                }}", declarations);
+
+            device.set_bind_group(0, &points_layout, &points_group);
+            device.set_bind_group(1, &indirect_layout, &indirect_group);
+            device.set_compute_shader_glsl(&count);
+            device.compute(1, 1, 1);
+
+            let indirect_slice = indirect_buffer.slice(..);
+            let mapped_future = indirect_slice.map_async(wgpu::MapMode::Read);
+            device.wgpu_device.poll(wgpu::Maintain::Wait);
+            mapped_future.await.expect("Mapping the indirect buffer failed");
+            let args: Vec<u32> = bytemuck::cast_slice(&indirect_slice.get_mapped_range()).to_vec();
+            indirect_buffer.unmap();
+            assert_eq!(vec![4, 1, 1], args);
+
+            // The indirect buffer can't be bound as a storage buffer while it is used for the dispatch
+            device.reset();
+            device.set_bind_group(0, &points_layout, &points_group);
+            device.set_compute_shader_glsl(&process);
+            device.set_indirect_buffer(0, &indirect_buffer);
+            device.compute_indirect(0, 0);
+
+            let selected_slice = selected_buffer.slice(..);
+            let mapped_future = selected_slice.map_async(wgpu::MapMode::Read);
+            device.wgpu_device.poll(wgpu::Maintain::Wait);
+            mapped_future.await.expect("Mapping the selected buffer failed");
+            let selected: Vec<u32> = bytemuck::cast_slice(&selected_slice.get_mapped_range()).to_vec();
+            // Only the 4 selected values were processed
+            assert_eq!(vec![108, 112, 107, 120, 0, 0], selected);
+        });
+    }
+
+    #[test]
+    fn test_probe_backends_skips_failing_backends() {
+        futures::executor::block_on(async {
+            // Simulate a system where the Vulkan backend is broken
+            let mut tried_backends = vec![];
+            let result = probe_backends(AUTO_BACKEND_PRIORITY, |backend| {
+                tried_backends.push(backend);
+                async move {
+                    if backend == wgpu::Backends::VULKAN {
+                        Err(anyhow!("Vulkan is broken"))
+                    } else {
+                        Ok(backend)
+                    }
+                }
+            })
+            .await;
+
+            assert_eq!(wgpu::Backends::METAL, result.unwrap());
+            assert_eq!(vec![wgpu::Backends::VULKAN, wgpu::Backends::METAL], tried_backends);
+
+            let result = probe_backends(AUTO_BACKEND_PRIORITY, |backend| async move {
+                Result::<(), _>::Err(anyhow!("{:?} is broken", backend))
+            })
+            .await;
+            let message = result.unwrap_err().to_string();
+            assert!(message.contains("Vulkan is broken"));
+            assert!(message.contains("Dx11 is broken"));
+        });
+    }
+
+    #[test]
+    fn test_auto_backend() {
+        futures::executor::block_on(async {
+            let device = Device::new(DeviceOptions {
+                device_backend: DeviceBackend::Auto,
+                ..Default::default()
+            })
+            .await;
+            // Auto must find a device whenever one of the specific backends does
+            if Device::default().await.is_ok() {
+                assert!(device.is_ok());
+            }
+        });
+    }
+
+    #[test]
+    fn test_unsupported_feature_yields_error() {
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            if Device::default().await.is_err() {
+                return;
+            }
+            // No adapter supports all features at once (some of them are even mutually exclusive between backends)
+            let error = Device::new(DeviceOptions {
+                required_features: wgpu::Features::all(),
+                ..Default::default()
+            })
+            .await
+            .err()
+            .expect("Requesting all features must fail");
+            assert!(error.to_string().contains("does not support the required features"));
+
+            let error = Device::new(DeviceOptions {
+                required_limits: wgpu::Limits {
+                    max_bind_groups: u32::MAX,
+                    ..wgpu::Limits::default()
+                },
+                ..Default::default()
+            })
+            .await
+            .err()
+            .expect("Requesting unlimited bind groups must fail");
+            assert!(error.to_string().contains("max_bind_groups"));
+        });
+    }
+
+    #[test]
+    fn test_supports_shader_f64_without_feature() {
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+            // The default options enable neither the features of the adapter nor SHADER_FLOAT64
+            assert!(!device.supports_shader_f64());
+            assert!(!device.supports(wgpu::Features::SHADER_FLOAT64));
+            assert!(device.supports(wgpu::Features::MAPPABLE_PRIMARY_BUFFERS));
+        });
+    }
 }
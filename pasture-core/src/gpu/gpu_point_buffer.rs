@@ -1,44 +1,223 @@
-use crate::layout::{PointAttributeDataType, PointAttributeDefinition};
+use crate::layout::{Endianness, PointAttributeDataType, PointAttributeDefinition};
 use bytemuck::__core::convert::TryInto;
-use crate::containers::{PointBuffer, PerAttributePointBufferMutExt, PerAttributePointBufferMut, InterleavedPointBufferMut, InterleavedVecPointStorage};
-use crate::gpu::{BufferInfoInterleaved, BufferInfoPerAttribute};
+use crate::containers::{PointBuffer, PerAttributePointBufferMut, InterleavedPointBufferMut, InterleavedVecPointStorage};
+use crate::gpu::{BufferInfoInterleaved, BufferInfoPerAttribute, BufferPool};
 use std::collections::HashMap;
-use crate::nalgebra::{Vector3, Vector4};
+use std::convert::TryFrom;
+use anyhow::{bail, Result};
+
+/// Appends the given `slice` of native-endian values with `component_size` bytes each to `target`, encoding the values
+/// as little-endian, which is the byte order that the GPU expects
+fn extend_from_native_slice_as_le(target: &mut Vec<u8>, slice: &[u8], component_size: usize) {
+    if cfg!(target_endian = "little") {
+        target.extend_from_slice(slice);
+    } else {
+        for component in slice.chunks_exact(component_size) {
+            target.extend(component.iter().rev());
+        }
+    }
+}
+
+/// Integer types that are extended to 32 bits on the GPU and have to be narrowed again when downloading
+trait NarrowFromGpuWord: Sized {
+    /// Converts a single little-endian 32-bit word from the GPU into `Self`. Values outside of the range of `Self` are
+    /// clamped to the closest representable value, in which case `false` is returned as the second tuple element
+    fn narrow_from_gpu_word(word: [u8; 4]) -> (Self, bool);
+}
+
+impl NarrowFromGpuWord for u8 {
+    fn narrow_from_gpu_word(word: [u8; 4]) -> (Self, bool) {
+        let value = u32::from_le_bytes(word);
+        match u8::try_from(value) {
+            Ok(narrowed) => (narrowed, true),
+            Err(_) => (u8::MAX, false),
+        }
+    }
+}
+
+impl NarrowFromGpuWord for u16 {
+    fn narrow_from_gpu_word(word: [u8; 4]) -> (Self, bool) {
+        let value = u32::from_le_bytes(word);
+        match u16::try_from(value) {
+            Ok(narrowed) => (narrowed, true),
+            Err(_) => (u16::MAX, false),
+        }
+    }
+}
+
+impl NarrowFromGpuWord for i8 {
+    fn narrow_from_gpu_word(word: [u8; 4]) -> (Self, bool) {
+        let value = i32::from_le_bytes(word);
+        match i8::try_from(value) {
+            Ok(narrowed) => (narrowed, true),
+            Err(_) if value < 0 => (i8::MIN, false),
+            Err(_) => (i8::MAX, false),
+        }
+    }
+}
+
+impl NarrowFromGpuWord for i16 {
+    fn narrow_from_gpu_word(word: [u8; 4]) -> (Self, bool) {
+        let value = i32::from_le_bytes(word);
+        match i16::try_from(value) {
+            Ok(narrowed) => (narrowed, true),
+            Err(_) if value < 0 => (i16::MIN, false),
+            Err(_) => (i16::MAX, false),
+        }
+    }
+}
+
+/// Narrows the 32-bit words in `gpu_bytes` back into the original datatype `T` of an attribute, which was extended to
+/// 32 bits during upload. Values that don't fit into `T` are clamped and counted in `num_out_of_range`
+fn narrow_gpu_words<T: NarrowFromGpuWord>(gpu_bytes: &[u8], num_out_of_range: &mut usize) -> Vec<T> {
+    gpu_bytes
+        .chunks_exact(4)
+        .map(|word| {
+            let (narrowed, in_range) = T::narrow_from_gpu_word(word.try_into().unwrap());
+            if !in_range {
+                *num_out_of_range += 1;
+            }
+            narrowed
+        })
+        .collect()
+}
+
+/// Returns the number of bytes that a single value of `datatype` occupies on the GPU
+fn gpu_value_size(datatype: PointAttributeDataType) -> usize {
+    // Assuming no extensions and GLSL:
+    // - Only 32-bit integers (signed or unsigned) on shader side
+    // - 32-bit and 64-bit floating point numbers
+    // - vec3's are treated as vec4's
+    //
+    // Hence a u8 takes up 4 bytes (32 bits) and a Vec3u8 takes up 16 bytes (4x 32 bits).
+    let alignment = match datatype {
+        PointAttributeDataType::U8 => { 4 }
+        PointAttributeDataType::I8 => { 4 }
+        PointAttributeDataType::U16 => { 4 }
+        PointAttributeDataType::I16 => { 4 }
+        PointAttributeDataType::U32 => { 4 }
+        PointAttributeDataType::I32 => { 4 }
+        PointAttributeDataType::U64 => { 8 }    // Currently not supported on shader side
+        PointAttributeDataType::I64 => { 8 }    // Currently not supported on shader side
+        PointAttributeDataType::F32 => { 4 }
+        PointAttributeDataType::F64 => { 8 }
+        PointAttributeDataType::Bool => { 4 }
+        PointAttributeDataType::Vec3u8 => { 16 }
+        PointAttributeDataType::Vec3u16 => { 16 }
+        PointAttributeDataType::Vec3f32 => { 16 }
+        PointAttributeDataType::Vec3f64 => { 32 }
+        PointAttributeDataType::Vec4u8 => { 16 }
+        PointAttributeDataType::Vec4u16 => { 16 }
+        // vec4's need no padding
+        PointAttributeDataType::Vec4f32 => { 16 }
+        PointAttributeDataType::Vec4f64 => { 32 }
+        // Arrays of 32-bit values are stored contiguously, just like float[N] and int[N] in std430
+        PointAttributeDataType::Array4f32 => { 16 }
+        PointAttributeDataType::Array8f32 => { 32 }
+        PointAttributeDataType::Array4i32 => { 16 }
+        PointAttributeDataType::Array8i32 => { 32 }
+    };
+
+    alignment
+}
+
+/// Converts `gpu_bytes`, which hold values of `datatype` in the layout that `align_slice` produces for a contiguous
+/// range of values, back into the native memory representation of `datatype`. Values that are out of range of their
+/// original datatype are clamped and counted in `num_out_of_range`
+fn dealign_gpu_bytes(gpu_bytes: &[u8], datatype: PointAttributeDataType, num_out_of_range: &mut usize) -> Result<Vec<u8>> {
+    let value_size = gpu_value_size(datatype);
+    if gpu_bytes.len() % value_size != 0 {
+        bail!(
+            "Length of GPU data ({} bytes) is not a multiple of the size of datatype {} on the GPU ({} bytes)",
+            gpu_bytes.len(),
+            datatype,
+            value_size
+        );
+    }
+
+    let mut bytes = match datatype {
+        PointAttributeDataType::Bool => gpu_bytes
+            .chunks_exact(4)
+            .map(|b| (u32::from_le_bytes(b.try_into().unwrap()) != 0) as u8)
+            .collect(),
+        PointAttributeDataType::U8 | PointAttributeDataType::Vec3u8 | PointAttributeDataType::Vec4u8 => {
+            narrow_gpu_words::<u8>(gpu_bytes, num_out_of_range)
+        }
+        PointAttributeDataType::I8 => {
+            bytemuck::cast_slice(&narrow_gpu_words::<i8>(gpu_bytes, num_out_of_range)).to_vec()
+        }
+        PointAttributeDataType::U16 | PointAttributeDataType::Vec3u16 | PointAttributeDataType::Vec4u16 => {
+            bytemuck::cast_slice(&narrow_gpu_words::<u16>(gpu_bytes, num_out_of_range)).to_vec()
+        }
+        PointAttributeDataType::I16 => {
+            bytemuck::cast_slice(&narrow_gpu_words::<i16>(gpu_bytes, num_out_of_range)).to_vec()
+        }
+        PointAttributeDataType::U64 | PointAttributeDataType::I64 => {
+            bail!("Downloading 64-bit integer types from the GPU is not supported.")
+        }
+        _ => {
+            // All other datatypes consist of 32-bit and 64-bit values that are only stored in little-endian
+            let mut bytes = gpu_bytes.to_vec();
+            let component_type = datatype.component_type();
+            component_type.convert_endianness(&mut bytes, Endianness::Little, Endianness::NATIVE);
+            bytes
+        }
+    };
+
+    // Vec3's are stored as vec4's on the GPU, so the fourth coordinate has to be removed
+    if datatype.component_count() == 3 {
+        let component_size = datatype.component_type().size() as usize;
+        bytes = bytes
+            .chunks_exact(4 * component_size)
+            .flat_map(|vec4| vec4[..3 * component_size].iter().copied())
+            .collect();
+    }
+    Ok(bytes)
+}
+
+/// Converts the raw bytes of values of `datatype` that were downloaded from a GPU buffer back into the native memory
+/// representation of `datatype`, as it is stored in a point buffer. This is the inverse of the conversion that
+/// [GpuPointBufferPerAttribute] and [GpuPointBufferInterleaved] apply to each attribute during upload:
+/// - Integers with less than 32 bits and `Bool` values are narrowed from 32-bit words. Integer values that are out of
+///   range of `datatype` are clamped to the closest representable value. Booleans are `true` for all non-zero words
+/// - The fourth coordinate that was appended to `Vec3` values is removed
+/// - All values are converted from little-endian into the native byte order
+///
+/// `slice` has to contain a whole number of values in the layout of a [GpuPointBufferPerAttribute], i.e. without any
+/// padding between the values. This is the conversion that the `download_into_*` methods use, so it is only needed when
+/// reading GPU buffers by other means.
+///
+/// Returns the converted bytes together with the number of values that were clamped. For vector types, each
+/// component counts as a separate value.
+///
+/// # Errors
+///
+/// If the length of `slice` is not a multiple of the size of `datatype` on the GPU, or if `datatype` is a 64-bit
+/// integer type, since these are not supported on the GPU
+pub fn dealign_slice(slice: &[u8], datatype: PointAttributeDataType) -> Result<(Vec<u8>, usize)> {
+    let mut num_out_of_range = 0;
+    let bytes = dealign_gpu_bytes(slice, datatype, &mut num_out_of_range)?;
+    Ok((bytes, num_out_of_range))
+}
 
 trait GpuPointBuffer {
     fn alignment_per_element(&self, datatype: PointAttributeDataType) -> usize {
-        // Assuming no extensions and GLSL:
-        // - Only 32-bit integers (signed or unsigned) on shader side
-        // - 32-bit and 64-bit floating point numbers
-        // - vec3's are treated as vec4's
-        //
-        // Hence a u8 takes up 4 bytes (32 bits) and a Vec3u8 takes up 16 bytes (4x 32 bits).
-        let alignment = match datatype {
-            PointAttributeDataType::U8 => { 4 }
-            PointAttributeDataType::I8 => { 4 }
-            PointAttributeDataType::U16 => { 4 }
-            PointAttributeDataType::I16 => { 4 }
-            PointAttributeDataType::U32 => { 4 }
-            PointAttributeDataType::I32 => { 4 }
-            PointAttributeDataType::U64 => { 8 }    // Currently not supported on shader side
-            PointAttributeDataType::I64 => { 8 }    // Currently not supported on shader side
-            PointAttributeDataType::F32 => { 4 }
-            PointAttributeDataType::F64 => { 8 }
-            PointAttributeDataType::Bool => { 4 }
-            PointAttributeDataType::Vec3u8 => { 16 }
-            PointAttributeDataType::Vec3u16 => { 16 }
-            PointAttributeDataType::Vec3f32 => { 16 }
-            PointAttributeDataType::Vec3f64 => { 32 }
-            PointAttributeDataType::Vec4u8 => { 16 }
-        };
-
-        alignment
+        gpu_value_size(datatype)
     }
 
-    fn align_slice(&self, slice: &[u8], datatype: PointAttributeDataType, offset: &mut usize) -> Vec<u8> {
+    fn align_slice(&self, slice: &[u8], datatype: PointAttributeDataType, offset: &mut usize) -> Result<Vec<u8>> {
         let mut ret_bytes: Vec<u8> = Vec::new();
 
         let num_bytes = slice.len();
+        let datatype_size = datatype.size() as usize;
+        if num_bytes % datatype_size != 0 {
+            bail!(
+                "Length of slice ({} bytes) is not a multiple of the size of datatype {} ({} bytes)",
+                num_bytes,
+                datatype,
+                datatype_size
+            );
+        }
 
         // Remember, signed and unsigned have the same bit-level behavior.
         // When casting to a larger type, first the size will change (zero or sign extension),
@@ -57,7 +236,11 @@ trait GpuPointBuffer {
                         *offset += 1;
                     }
 
-                    let current = (slice[i] as u32).to_ne_bytes();
+                    let current = match datatype {
+                        // Sign extension, so that negative values are read correctly as int in the shader
+                        PointAttributeDataType::I8 => (slice[i] as i8 as i32).to_le_bytes(),
+                        _ => (slice[i] as u32).to_le_bytes(),
+                    };
                     ret_bytes.extend_from_slice(&current);
                     *offset += current.len();
                 }
@@ -78,34 +261,38 @@ trait GpuPointBuffer {
                     let end = (i * stride) + stride;
                     let current = u16::from_ne_bytes(slice[begin..end].try_into().unwrap());
 
-                    let current = (current as u32).to_ne_bytes();
+                    let current = match datatype {
+                        // Sign extension, so that negative values are read correctly as int in the shader
+                        PointAttributeDataType::I16 => (current as i16 as i32).to_le_bytes(),
+                        _ => (current as u32).to_le_bytes(),
+                    };
                     ret_bytes.extend_from_slice(&current);
                     *offset += std::mem::size_of::<u32>();
                 }
             }
-            PointAttributeDataType::U32 | PointAttributeDataType::I32 => {
+            PointAttributeDataType::U32 | PointAttributeDataType::I32 | PointAttributeDataType::Array4i32 | PointAttributeDataType::Array8i32 => {
                 // Alignment is 4 bytes
                 while *offset % 4 != 0 {
                     ret_bytes.push(0);
                     *offset += 1;
                 }
 
-                ret_bytes.extend_from_slice(&slice);
+                extend_from_native_slice_as_le(&mut ret_bytes, slice, 4);
                 *offset += num_bytes;
             }
             PointAttributeDataType::U64 | PointAttributeDataType::I64 => {
                 // Trouble: no 64-bit integer types on GPU
                 // TODO: consider extensions for GLSL that allow 64-bit integer types, eg. u64int
-                panic!("Uploading 64-bit integer types to the GPU is not supported.")
+                bail!("Uploading 64-bit integer types to the GPU is not supported.")
             }
-            PointAttributeDataType::F32 => {
+            PointAttributeDataType::F32 | PointAttributeDataType::Array4f32 | PointAttributeDataType::Array8f32 => {
                 // Alignment is 4 bytes
                 while *offset % 4 != 0 {
                     ret_bytes.push(0);
                     *offset += 1;
                 }
 
-                ret_bytes.extend_from_slice(&slice);
+                extend_from_native_slice_as_le(&mut ret_bytes, slice, 4);
                 *offset += num_bytes;
             }
             PointAttributeDataType::F64 => {
@@ -115,12 +302,12 @@ trait GpuPointBuffer {
                     *offset += 1;
                 }
 
-                ret_bytes.extend_from_slice(&slice);
+                extend_from_native_slice_as_le(&mut ret_bytes, slice, 8);
                 *offset += num_bytes;
             }
             PointAttributeDataType::Vec3u8 | PointAttributeDataType::Vec4u8 => {
                 // Treating as Vec4u32
-                let one_as_bytes = 1_u32.to_ne_bytes();
+                let one_as_bytes = 1_u32.to_le_bytes();
 
                 // Each entry is 8 bits, ie. 1 byte -> each Vec3 has 3 bytes
                 let stride = datatype.size() as usize;
@@ -141,25 +328,28 @@ trait GpuPointBuffer {
                         let end = (i * stride) + j + 1;
 
                         let current = u8::from_ne_bytes(slice[begin..end].try_into().unwrap());
-                        let current = (current as u32).to_ne_bytes();
+                        let current = (current as u32).to_le_bytes();
                         ret_bytes.extend_from_slice(&current);
                         *offset += current.len();
                     }
 
-                    // Append fourth coordinate
-                    ret_bytes.extend_from_slice(&one_as_bytes);
-                    *offset += one_as_bytes.len();
+                    // Append fourth coordinate, Vec4 already has one
+                    if num_components == 3 {
+                        ret_bytes.extend_from_slice(&one_as_bytes);
+                        *offset += one_as_bytes.len();
+                    }
                 }
             }
-            PointAttributeDataType::Vec3u16 => {
+            PointAttributeDataType::Vec3u16 | PointAttributeDataType::Vec4u16 => {
                 // Treating as Vec4u32
-                let one_as_bytes = 1_u32.to_ne_bytes();
+                let one_as_bytes = 1_u32.to_le_bytes();
 
-                // Each entry is 16 bits, ie. 2 bytes -> each Vec3 has 3*2 = 6 bytes
-                let stride = datatype.size() as usize;   // = 6
+                // Each entry is 16 bits, ie. 2 bytes -> each Vec3 has 3*2 = 6 bytes, each Vec4 has 4*2 = 8 bytes
+                let stride = datatype.size() as usize;
                 let num_elements = num_bytes / stride;
+                let num_components = datatype.component_count();
 
-                // Iteration over each Vec3
+                // Iteration over each Vec3 or Vec4
                 for i in 0..num_elements {
                     // Alignment is 16 bytes
                     while *offset % 16 != 0 {
@@ -168,24 +358,46 @@ trait GpuPointBuffer {
                     }
 
                     // Extend each entry to 32 bits
-                    for j in 0..3 {
+                    for j in 0..num_components {
                         let begin = (i * stride) + j * 2;
                         let end = (i * stride) + (j * 2) + 2;
 
                         let current = u16::from_ne_bytes(slice[begin..end].try_into().unwrap());
-                        let current = (current as u32).to_ne_bytes();
+                        let current = (current as u32).to_le_bytes();
                         ret_bytes.extend_from_slice(&current);
                         *offset += current.len();
                     }
 
-                    // Append fourth coordinate
-                    ret_bytes.extend_from_slice(&one_as_bytes);
-                    *offset += one_as_bytes.len();
+                    // Append fourth coordinate, Vec4 already has one
+                    if num_components == 3 {
+                        ret_bytes.extend_from_slice(&one_as_bytes);
+                        *offset += one_as_bytes.len();
+                    }
                 }
             }
+            PointAttributeDataType::Vec4f32 => {
+                // Same layout as vec4 in std430, so no padding between the elements is needed. Alignment is 16 bytes
+                while *offset % 16 != 0 {
+                    ret_bytes.push(0);
+                    *offset += 1;
+                }
+
+                extend_from_native_slice_as_le(&mut ret_bytes, slice, 4);
+                *offset += num_bytes;
+            }
+            PointAttributeDataType::Vec4f64 => {
+                // Same layout as dvec4 in std430, so no padding between the elements is needed. Alignment is 32 bytes
+                while *offset % 32 != 0 {
+                    ret_bytes.push(0);
+                    *offset += 1;
+                }
+
+                extend_from_native_slice_as_le(&mut ret_bytes, slice, 8);
+                *offset += num_bytes;
+            }
             PointAttributeDataType::Vec3f32 => {
                 // Make Vec4f32 by appending 1.0
-                let one_as_bytes = 1.0_f32.to_ne_bytes();
+                let one_as_bytes = 1.0_f32.to_le_bytes();
 
                 // Each entry is 64 bits and hence consists of 8 bytes -> a Vec3 has 24 bytes
                 let stride = datatype.size() as usize;   // = 24
@@ -203,7 +415,7 @@ trait GpuPointBuffer {
 
                     // Push current Vec3
                     let current = &slice[begin..end];
-                    ret_bytes.extend_from_slice(current);
+                    extend_from_native_slice_as_le(&mut ret_bytes, current, 4);
                     *offset += current.len();
 
                     // Push 1 as fourth coordinate
@@ -213,7 +425,7 @@ trait GpuPointBuffer {
             }
             PointAttributeDataType::Vec3f64 => {
                 // Make Vec4f64 by appending 1.0
-                let one_as_bytes = 1.0_f64.to_ne_bytes();
+                let one_as_bytes = 1.0_f64.to_le_bytes();
 
                 // Each entry is 64 bits and hence consists of 8 bytes -> a Vec3 has 24 bytes
                 let stride = datatype.size() as usize;   // = 24
@@ -231,7 +443,7 @@ trait GpuPointBuffer {
 
                     // Push current Vec3
                     let current = &slice[begin..end];
-                    ret_bytes.extend_from_slice(current);
+                    extend_from_native_slice_as_le(&mut ret_bytes, current, 8);
                     *offset += current.len();
 
                     // Push 1 as fourth coordinate
@@ -241,7 +453,7 @@ trait GpuPointBuffer {
             }
         }
 
-        return ret_bytes;
+        Ok(ret_bytes)
     }
 
     // TODO: see if this can be done better with less duplication (the offset parameter is also ugly)
@@ -270,7 +482,7 @@ trait GpuPointBuffer {
                     *offset += std::mem::size_of::<u32>();
                 }
             }
-            PointAttributeDataType::U32 | PointAttributeDataType::I32 => {
+            PointAttributeDataType::U32 | PointAttributeDataType::I32 | PointAttributeDataType::Array4i32 | PointAttributeDataType::Array8i32 => {
                 // Alignment is 4 bytes
                 while *offset % 4 != 0 {
                     *offset += 1;
@@ -282,7 +494,7 @@ trait GpuPointBuffer {
                 // TODO: consider extensions for GLSL that allow 64-bit integer types, eg. u64int
                 panic!("Uploading 64-bit integer types to the GPU is not supported.")
             }
-            PointAttributeDataType::F32 => {
+            PointAttributeDataType::F32 | PointAttributeDataType::Array4f32 | PointAttributeDataType::Array8f32 => {
                 // Alignment is 4 bytes
                 while *offset % 4 != 0 {
                     *offset += 1;
@@ -312,13 +524,13 @@ trait GpuPointBuffer {
                     *offset += 4 * std::mem::size_of::<u32>();
                 }
             }
-            PointAttributeDataType::Vec4u8 => {
+            PointAttributeDataType::Vec4u8 | PointAttributeDataType::Vec4u16 => {
                 // Alignment is 16 bytes
                 while *offset % 16 != 0 {
                     *offset += 1;
                 }
 
-                // Each entry is 8 bits, ie. 1 byte -> each Vec4 has 4 bytes
+                // Each entry is 8 or 16 bits -> each Vec4 has 4 or 8 bytes
                 let stride = datatype.size() as usize;
                 let num_elements = num_bytes / stride;
 
@@ -341,6 +553,20 @@ trait GpuPointBuffer {
                     *offset += 4 * std::mem::size_of::<u32>();
                 }
             }
+            PointAttributeDataType::Vec4f32 => {
+                // Alignment is 16 bytes
+                while *offset % 16 != 0 {
+                    *offset += 1;
+                }
+                *offset += num_bytes;
+            }
+            PointAttributeDataType::Vec4f64 => {
+                // Alignment is 32 bytes
+                while *offset % 32 != 0 {
+                    *offset += 1;
+                }
+                *offset += num_bytes;
+            }
             PointAttributeDataType::Vec3f32 => {
                 // Each entry is 64 bits and hence consists of 8 bytes -> a Vec3 has 24 bytes
                 let stride = datatype.size() as usize;   // = 24
@@ -408,8 +634,9 @@ impl GpuPointBufferInterleaved {
     }
 
     /// Allocates enough memory on the device to hold `num_points` many points that are structured
-    /// as described in `buffer_info`.
-    pub fn malloc(&mut self, num_points: u64, buffer_info: &BufferInfoInterleaved, wgpu_device: &mut wgpu::Device) {
+    /// as described in `buffer_info`. The `extra_usage` of `buffer_info` is added to the default
+    /// usages of the GPU buffer.
+    pub fn malloc(&mut self, num_points: u64, buffer_info: &BufferInfoInterleaved, wgpu_device: &wgpu::Device) {
         // Determine struct alignment
         let struct_alignment =  self.struct_alignment(&buffer_info);
 
@@ -445,7 +672,8 @@ impl GpuPointBufferInterleaved {
                     wgpu::BufferUsages::MAP_READ |
                     wgpu::BufferUsages::MAP_WRITE |
                     wgpu::BufferUsages::COPY_SRC |
-                    wgpu::BufferUsages::COPY_DST,
+                    wgpu::BufferUsages::COPY_DST |
+                    buffer_info.extra_usage,
                 mapped_at_creation: false
             }
         ));
@@ -470,6 +698,12 @@ impl GpuPointBufferInterleaved {
     /// * `wgpu_device`  - can be obtained from [Device](gpu::Device).
     /// * `wgpu_queue`   - can be obtained from [Device](gpu::Device).
     ///
+    /// # Errors
+    /// If the `extra_usage` of `buffer_info` includes `INDEX` or `UNIFORM`, an error is returned and
+    /// nothing is uploaded, since the structs of an interleaved buffer can't be used this way. If the
+    /// point data of an attribute can't be converted into its GPU representation, an error is
+    /// returned as well.
+    ///
     /// # Panics
     /// If no memory or not enough memory has been allocated previously via
     /// [malloc()](GpuPointBufferInterleaved::malloc), this method will panic.
@@ -478,9 +712,11 @@ impl GpuPointBufferInterleaved {
         point_buffer: &dyn PointBuffer,
         points_range: std::ops::Range<usize>,
         buffer_info: &BufferInfoInterleaved,
-        wgpu_device: &mut wgpu::Device,
-        wgpu_queue: &wgpu::Queue)
+        wgpu_device: &wgpu::Device,
+        wgpu_queue: &wgpu::Queue) -> Result<()>
     {
+        check_extra_usage_interleaved(buffer_info)?;
+
         let pt_rng = &points_range;
 
         // Determine struct alignment
@@ -503,7 +739,7 @@ impl GpuPointBufferInterleaved {
 
                 // Align each attribute
                 let bytes_for_attrib: &[u8] = &*bytes_for_attrib;
-                let mut bytes_for_attrib = self.align_slice(bytes_for_attrib, attrib.datatype(), &mut offset);
+                let mut bytes_for_attrib = self.align_slice(bytes_for_attrib, attrib.datatype(), &mut offset)?;
 
                 bytes_to_write.append(&mut bytes_for_attrib);
             }
@@ -526,16 +762,33 @@ impl GpuPointBufferInterleaved {
         wgpu_queue.write_buffer(&gpu_buffer, offset as wgpu::BufferAddress, bytes_to_write);
 
         self.create_bind_group(wgpu_device);
+
+        Ok(())
+    }
+
+    /// Returns the underlying GPU buffer, if memory has been allocated via
+    /// [malloc()](GpuPointBufferInterleaved::malloc). If the `extra_usage` of the buffer info
+    /// contains `INDIRECT`, the buffer can also be used as an indirect buffer for
+    /// [Device::compute_indirect()](crate::gpu::Device::compute_indirect).
+    pub fn buffer(&self) -> Option<&wgpu::Buffer> {
+        self.buffer.as_ref()
     }
 
     /// Writes the contents of the GPU buffer into `point_buffer`, which is in interleaved format,
     /// within the `points_range` range.
+    ///
+    /// Attributes that were extended to 32 bits during upload (e.g. `U16` intensities) are narrowed back into
+    /// their original datatype. Values that the shader wrote outside of the range of this datatype are clamped.
+    /// Returns the number of clamped values, so that callers can detect shaders that produce out-of-range results.
+    ///
+    /// # Errors
+    /// If any of the attributes in `buffer_info` has a 64-bit integer type, which is not supported on the GPU
     pub async fn download_into_interleaved(
         &self,
         point_buffer: &mut InterleavedVecPointStorage,
         points_range: std::ops::Range<usize>,
         buffer_info: &BufferInfoInterleaved<'_>,
-        wgpu_device: &wgpu::Device)
+        wgpu_device: &wgpu::Device) -> Result<usize>
     {
         let gpu_buffer = self.buffer.as_ref().unwrap();
 
@@ -543,9 +796,13 @@ impl GpuPointBufferInterleaved {
         let mapped_future = gpu_buffer_slice.map_async(wgpu::MapMode::Read);
         wgpu_device.poll(wgpu::Maintain::Wait); // TODO: "Should be called in event loop or other thread ..."
 
+        let mut num_out_of_range = 0;
         if let Ok(()) = mapped_future.await {
             let mapped_view = gpu_buffer_slice.get_mapped_range();
             let result_as_bytes = mapped_view.to_vec();
+            // The results were copied, so the buffer can be unmapped before they are converted
+            drop(mapped_view);
+            gpu_buffer.unmap();
 
             // Used to determine the offset of an attribute
             let point_layout = point_buffer.point_layout().clone();
@@ -559,214 +816,19 @@ impl GpuPointBufferInterleaved {
                     let offset = *datatype_offset_map.get(&attrib.datatype()).unwrap();
                     let size = self.alignment_per_element(attrib.datatype());
 
-                    match attrib.datatype() {
-                        PointAttributeDataType::Bool => {
-                            let result: Vec<bool> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) != 0)
-                                .collect();
-
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = result[i - attrib_offset] as u8;
-                            }
-                        },
-                        PointAttributeDataType::U8 => {
-                            let result: Vec<u8> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u8)
-                                .collect();
-
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = result[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::I8 => {
-                            let result: Vec<i8> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| i32::from_ne_bytes(b.try_into().unwrap()) as i8)
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::U16 => {
-                            let result: Vec<u16> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u16)
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::I16 => {
-                            let result: Vec<i16> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| i32::from_ne_bytes(b.try_into().unwrap()) as i16)
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::U32 => {
-                            let result: Vec<u32> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()))
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::I32 => {
-                            let result: Vec<i32> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| i32::from_ne_bytes(b.try_into().unwrap()))
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::U64 => { /* Currently not supported */ },
-                        PointAttributeDataType::I64 => { /* Currently not supported */ },
-                        PointAttributeDataType::F32 => {
-                            let result: Vec<f32> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::F64 => {
-                            let result: Vec<f64> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(8)
-                                .map(|b| f64::from_ne_bytes(b.try_into().unwrap()))
-                                .collect();
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::Vec3u8 => {
-                            let result4d: Vec<u8> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u8)
-                                .collect();
-
-                            // Throw 4th coordinate away
-                            let mut result: Vec<u8> = vec![];
-                            for i in 0..result4d.len() {
-                                if (i + 1) % 4 == 0 {
-                                    continue;
-                                }
-
-                                result.push(result4d[i]);
-                            }
-
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = result[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::Vec4u8 => {
-                            let result4d: Vec<u8> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u8)
-                                .collect();
-
-                            let mut result: Vec<u8> = vec![];
-                            for i in 0..result4d.len() {
-                                result.push(result4d[i]);
-                            }
-
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = result[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::Vec3u16 => {
-                            let result4d: Vec<u16> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u16)
-                                .collect();
-
-                            // Throw 4th coordinate away
-                            let mut result: Vec<u16> = vec![];
-                            for i in 0..result4d.len() {
-                                if (i + 1) % 4 == 0 {
-                                    continue;
-                                }
-
-                                result.push(result4d[i]);
-                            }
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::Vec3f32 => {
-                            let result4d: Vec<f32> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(4)
-                                .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
-                                .collect();
-
-                            // Throw 4th coordinate away
-                            let mut result: Vec<f32> = vec![];
-                            for i in 0..result4d.len() {
-                                if (i + 1) % 4 == 0 {
-                                    continue;
-                                }
-
-                                result.push(result4d[i]);
-                            }
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                        PointAttributeDataType::Vec3f64 => {
-                            let result4d: Vec<f64> = result_as_bytes[offset..(offset + size)]
-                                .chunks_exact(8)
-                                .map(|b| f64::from_ne_bytes(b.try_into().unwrap()))
-                                .collect();
-
-                            // Throw 4th coordinate away
-                            let mut result: Vec<f64> = vec![];
-                            for i in 0..result4d.len() {
-                                if (i + 1) % 4 == 0 {
-                                    continue;
-                                }
-
-                                result.push(result4d[i]);
-                            }
-
-                            let bytes: &[u8] = bytemuck::cast_slice(result.as_slice());
-                            for i in attrib_offset..(attrib_offset + attrib.size() as usize) {
-                                point_as_bytes[i] = bytes[i - attrib_offset];
-                            }
-                        },
-                    }
+                    let bytes = dealign_gpu_bytes(
+                        &result_as_bytes[offset..(offset + size)],
+                        attrib.datatype(),
+                        &mut num_out_of_range,
+                    )?;
+                    point_as_bytes[attrib_offset..(attrib_offset + bytes.len())].copy_from_slice(&bytes);
                 }
             }
-
-            drop(mapped_view);
-            gpu_buffer.unmap();
         }
+        Ok(num_out_of_range)
     }
 
-    fn create_bind_group(&mut self, wgpu_device: &mut wgpu::Device) {
+    fn create_bind_group(&mut self, wgpu_device: &wgpu::Device) {
         let bind_group_layout = wgpu_device.create_bind_group_layout(
             &wgpu::BindGroupLayoutDescriptor {
                 label: Some("storage_bind_group_layout"),
@@ -828,6 +890,111 @@ impl GpuPointBufferInterleaved {
     }
 }
 
+/// Returns an error if two of the given `buffer_infos` share the same binding. Such a configuration
+/// would result in an invalid bind group layout, which `wgpu` only reports with a rather cryptic panic.
+fn check_unique_bindings(buffer_infos: &[BufferInfoPerAttribute]) -> Result<()> {
+    let mut attributes_by_binding: HashMap<u32, &PointAttributeDefinition> = HashMap::new();
+    for info in buffer_infos {
+        if let Some(other_attribute) = attributes_by_binding.insert(info.binding, info.attribute) {
+            bail!(
+                "Binding {} is used by more than one attribute ({} and {})",
+                info.binding,
+                other_attribute,
+                info.attribute
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns an error if the `extra_usage` of one of the given `buffer_infos` is incompatible with the representation of
+/// its attribute on the GPU. Index buffers require 32-bit unsigned integers, which only unsigned integer attributes are
+/// extended to. Uniform buffers use the `std140` layout, which aligns array elements to 16 bytes, so only vector
+/// attributes have the same representation in a uniform buffer as in the `std430` storage buffer that is uploaded.
+fn check_extra_usages(buffer_infos: &[BufferInfoPerAttribute]) -> Result<()> {
+    for info in buffer_infos {
+        let datatype = info.attribute.datatype();
+        if info.extra_usage.contains(wgpu::BufferUsages::INDEX)
+            && !matches!(
+                datatype,
+                PointAttributeDataType::U8 | PointAttributeDataType::U16 | PointAttributeDataType::U32
+            )
+        {
+            bail!(
+                "Attribute {} can't be used as an index buffer, only unsigned integer attributes are supported",
+                info.attribute
+            );
+        }
+        if info.extra_usage.contains(wgpu::BufferUsages::UNIFORM)
+            && !matches!(
+                datatype,
+                PointAttributeDataType::Vec3u8
+                    | PointAttributeDataType::Vec4u8
+                    | PointAttributeDataType::Vec3u16
+                    | PointAttributeDataType::Vec4u16
+                    | PointAttributeDataType::Vec3f32
+                    | PointAttributeDataType::Vec4f32
+                    | PointAttributeDataType::Vec3f64
+                    | PointAttributeDataType::Vec4f64
+            )
+        {
+            bail!(
+                "Attribute {} can't be used as a uniform buffer, only vector attributes are supported",
+                info.attribute
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns an error if the `extra_usage` of `buffer_info` is incompatible with an interleaved buffer. An interleaved
+/// buffer stores one struct per point, which can neither be used as an index nor be read from a uniform buffer with
+/// the same layout.
+fn check_extra_usage_interleaved(buffer_info: &BufferInfoInterleaved) -> Result<()> {
+    let incompatible_usage =
+        buffer_info.extra_usage & (wgpu::BufferUsages::INDEX | wgpu::BufferUsages::UNIFORM);
+    if !incompatible_usage.is_empty() {
+        bail!(
+            "Interleaved buffers can't be used with buffer usage {:?}",
+            incompatible_usage
+        );
+    }
+    Ok(())
+}
+
+/// Writes the contents of the mapped `gpu_buffer` for the attribute described by `info` into `point_buffer` within
+/// `range` and unmaps `gpu_buffer` afterwards, even if the attribute can't be downloaded. Returns the number of values
+/// that were clamped to the datatype of the attribute
+fn download_mapped_attribute(
+    point_buffer: &mut dyn PerAttributePointBufferMut<'_>,
+    range: std::ops::Range<usize>,
+    info: &BufferInfoPerAttribute<'_>,
+    gpu_buffer: &wgpu::Buffer) -> Result<usize>
+{
+    let gpu_buffer_slice = gpu_buffer.slice(..);
+    let mapped_view = gpu_buffer_slice.get_mapped_range();
+
+    let datatype = info.attribute.datatype();
+    let num_gpu_bytes = range.len() * gpu_value_size(datatype);
+    let mut num_out_of_range = 0;
+    let dealigned = dealign_gpu_bytes(&mapped_view[..num_gpu_bytes], datatype, &mut num_out_of_range);
+
+    // Drop all mapped views before unmapping buffer
+    drop(mapped_view);
+    gpu_buffer.unmap();
+
+    let bytes = dealigned?;
+    point_buffer.get_raw_attribute_range_mut(range, info.attribute).copy_from_slice(&bytes);
+    Ok(num_out_of_range)
+}
+
+/// Default usages of the storage buffers of a `GpuPointBufferPerAttribute`
+const STORAGE_BUFFER_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::STORAGE
+    .union(wgpu::BufferUsages::MAP_READ)
+    .union(wgpu::BufferUsages::MAP_WRITE)
+    .union(wgpu::BufferUsages::COPY_SRC)
+    .union(wgpu::BufferUsages::COPY_DST);
+
 /// Manages point buffer data that is to be stored in per-attribute format on the GPU.
 ///
 /// Make sure to allocate enough memory before trying to upload anything.
@@ -843,6 +1010,7 @@ pub struct GpuPointBufferPerAttribute<'a> {
     // Consider (String, PointAttributeDataType)?, eg. ("POSITION_3D", Vec3f64)
     buffers: HashMap<String, wgpu::Buffer>,
     buffer_sizes: HashMap<String, wgpu::BufferAddress>,
+    buffer_usages: HashMap<String, wgpu::BufferUsages>,
     buffer_bindings: HashMap<String, u32>,
     buffer_keys: Vec<&'a PointAttributeDefinition>,   // For now need order (because download code in device_compute depends on it)
 }
@@ -856,14 +1024,50 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
             bind_group: None,
             buffers: HashMap::new(),
             buffer_sizes: HashMap::new(),
+            buffer_usages: HashMap::new(),
             buffer_bindings: HashMap::new(),
             buffer_keys: vec![]
         }
     }
 
     /// Allocates enough memory on the device to hold `num_points` many points that are structured
-    /// as described in `buffer_info`.
-    pub fn malloc(&mut self, num_points: u64, buffer_infos: &'a Vec<BufferInfoPerAttribute>, wgpu_device: &mut wgpu::Device) {
+    /// as described in `buffer_info`. The `extra_usage` of each buffer info is added to the default
+    /// usages of the GPU buffer of its attribute.
+    pub fn malloc(&mut self, num_points: u64, buffer_infos: &'a Vec<BufferInfoPerAttribute>, wgpu_device: &wgpu::Device) {
+        // TODO: warning message from wgpu
+        //  Feature MAPPABLE_PRIMARY_BUFFERS enabled on a discrete gpu.
+        //  This is a massive performance footgun and likely not what you wanted.
+        self.malloc_with(num_points, buffer_infos, |label, size, usage| wgpu_device.create_buffer(
+            &wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            }
+        ));
+    }
+
+    /// Like [malloc()](GpuPointBufferPerAttribute::malloc), but takes the buffers from `buffer_pool` if it contains
+    /// buffers of the right size. Use [release_into_pool()](GpuPointBufferPerAttribute::release_into_pool) to return
+    /// the buffers into the pool afterwards.
+    pub(crate) fn malloc_from_pool(&mut self, num_points: u64, buffer_infos: &'a Vec<BufferInfoPerAttribute>, wgpu_device: &wgpu::Device, buffer_pool: &mut BufferPool) {
+        self.malloc_with(num_points, buffer_infos, |label, size, usage| buffer_pool.acquire(wgpu_device, label, size, usage));
+    }
+
+    /// Moves all buffers of this `GpuPointBufferPerAttribute` into `buffer_pool`, so that they can be reused by a
+    /// later call to [malloc_from_pool()](GpuPointBufferPerAttribute::malloc_from_pool). None of the buffers may be
+    /// mapped.
+    pub(crate) fn release_into_pool(mut self, buffer_pool: &mut BufferPool) {
+        // The bind group references the buffers, so it has to be dropped before they are reused
+        self.bind_group = None;
+        for (key, buffer) in self.buffers.drain() {
+            let size = *self.buffer_sizes.get(&key).unwrap();
+            let usage = *self.buffer_usages.get(&key).unwrap();
+            buffer_pool.release(buffer, size, usage);
+        }
+    }
+
+    fn malloc_with<F: FnMut(&str, wgpu::BufferAddress, wgpu::BufferUsages) -> wgpu::Buffer>(&mut self, num_points: u64, buffer_infos: &'a Vec<BufferInfoPerAttribute>, mut create_buffer: F) {
         for info in buffer_infos {
             let size = (num_points as usize) * self.alignment_per_element(info.attribute.datatype());
 
@@ -873,24 +1077,13 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
             // HashMap need trait bound Hash, which PointAttributeDefinition does not have
             // So use String instead
             let key = String::from(info.attribute.name());
+            let usage = STORAGE_BUFFER_USAGES | info.extra_usage;
             self.buffer_sizes.insert(key.clone(), size as wgpu::BufferAddress);
+            self.buffer_usages.insert(key.clone(), usage);
             self.buffer_bindings.insert(key.clone(), info.binding);
 
-            // TODO: warning message from wgpu
-            //  Feature MAPPABLE_PRIMARY_BUFFERS enabled on a discrete gpu.
-            //  This is a massive performance footgun and likely not what you wanted.
-            self.buffers.insert(key.clone(), wgpu_device.create_buffer(
-                &wgpu::BufferDescriptor {
-                    label: Some(format!("storage_buffer_{}", key).as_str()),
-                    size: size as wgpu::BufferAddress,
-                    usage: wgpu::BufferUsages::STORAGE |
-                        wgpu::BufferUsages::MAP_READ |
-                        wgpu::BufferUsages::MAP_WRITE |
-                        wgpu::BufferUsages::COPY_SRC |
-                        wgpu::BufferUsages::COPY_DST,
-                    mapped_at_creation: false,
-                }
-            ));
+            let buffer = create_buffer(format!("storage_buffer_{}", key).as_str(), size as wgpu::BufferAddress, usage);
+            self.buffers.insert(key.clone(), buffer);
         }
     }
 
@@ -912,6 +1105,12 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
     /// * `wgpu_device`  - can be obtained from [Device](gpu::Device).
     /// * `wgpu_queue`   - can be obtained from [Device](gpu::Device).
     ///
+    /// # Errors
+    /// If two entries in `buffer_infos` use the same binding, or if the `extra_usage` of an entry is
+    /// incompatible with the GPU representation of its attribute (e.g. `INDEX` for a floating point
+    /// attribute), an error is returned and nothing is uploaded. If the point data of an attribute
+    /// can't be converted into its GPU representation, an error is returned as well.
+    ///
     /// # Panics
     /// If no memory or not enough memory has been allocated previously via
     /// [malloc()](GpuPointBufferPerAttribute::malloc), this method will panic.
@@ -920,9 +1119,12 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
         point_buffer: &dyn PointBuffer,
         points_range: std::ops::Range<usize>,
         buffer_infos: &Vec<BufferInfoPerAttribute>,
-        wgpu_device: &mut wgpu::Device,
-        wgpu_queue: &wgpu::Queue)
+        wgpu_device: &wgpu::Device,
+        wgpu_queue: &wgpu::Queue) -> Result<()>
     {
+        check_unique_bindings(buffer_infos)?;
+        check_extra_usages(buffer_infos)?;
+
         let len = points_range.len();
 
         for info in buffer_infos {
@@ -934,7 +1136,7 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
             // Change Vec<u8> to &[u8] and align bytes
             let mut unused_for_per_attrib: usize = 0;
             let bytes_to_write: &[u8] = &*bytes_to_write;
-            let bytes_to_write = &self.align_slice(bytes_to_write, info.attribute.datatype(), &mut unused_for_per_attrib)[..];
+            let bytes_to_write = &self.align_slice(bytes_to_write, info.attribute.datatype(), &mut unused_for_per_attrib)?[..];
 
             // Schedule write to GPU memory, starting from correct offset
             let mut offset: usize = 0;
@@ -945,207 +1147,101 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
         }
 
         self.create_bind_group(wgpu_device);
+
+        Ok(())
+    }
+
+    /// Returns the GPU buffer at the given `binding`, if memory for it has been allocated via
+    /// [malloc()](GpuPointBufferPerAttribute::malloc). If the `extra_usage` of its buffer info
+    /// contains `INDIRECT`, the buffer can also be used as an indirect buffer for
+    /// [Device::compute_indirect()](crate::gpu::Device::compute_indirect), e.g. when a previous
+    /// compute pass has written the work group counts into it.
+    pub fn buffer_at_binding(&self, binding: u32) -> Option<&wgpu::Buffer> {
+        self.buffer_bindings
+            .iter()
+            .find(|(_, buffer_binding)| **buffer_binding == binding)
+            .and_then(|(key, _)| self.buffers.get(key))
     }
 
     /// Writes the contents of the GPU buffer into `point_buffer`, which is in per-attribute format,
     /// within the `points_range` range.
+    ///
+    /// Attributes that were extended to 32 bits during upload (e.g. `U16` intensities) are narrowed back into
+    /// their original datatype. Values that the shader wrote outside of the range of this datatype are clamped.
+    /// Returns the number of clamped values over all attributes, so that callers can detect shaders that produce
+    /// out-of-range results.
+    ///
+    /// # Errors
+    /// If any of the attributes in `buffer_infos` has a 64-bit integer type, which is not supported on the GPU. The
+    /// other attributes are still downloaded in this case.
+    ///
+    /// Mapping of all GPU buffers is requested at once before waiting for the device, so the mapping latencies
+    /// of the individual attributes overlap. The attributes are then written into `point_buffer` in the order
+    /// of `buffer_infos`.
     pub async fn download_into_per_attribute(
         &self,
         point_buffer: &mut dyn PerAttributePointBufferMut<'_>,
         points_range: std::ops::Range<usize>,
         buffer_infos: &Vec<BufferInfoPerAttribute<'_>>,
-        wgpu_device: &wgpu::Device)
+        wgpu_device: &wgpu::Device) -> Result<usize>
     {
+        let gpu_buffers: Vec<&wgpu::Buffer> = buffer_infos
+            .iter()
+            .map(|info| self.buffers.get(info.attribute.name()).unwrap())
+            .collect();
+        let mapped_futures: Vec<_> = gpu_buffers
+            .iter()
+            .map(|gpu_buffer| gpu_buffer.slice(..).map_async(wgpu::MapMode::Read))
+            .collect();
+        wgpu_device.poll(wgpu::Maintain::Wait); // TODO: "Should be called in event loop or other thread ..."
+
+        let mut num_out_of_range = 0;
+        let mut first_error = None;
+        for ((info, gpu_buffer), mapped_future) in buffer_infos.iter().zip(gpu_buffers.iter()).zip(mapped_futures) {
+            if let Ok(()) = mapped_future.await {
+                // All buffers are mapped at this point, so the remaining attributes still have to be downloaded (and
+                // their buffers unmapped) after an error
+                match download_mapped_attribute(point_buffer, points_range.clone(), info, gpu_buffer) {
+                    Ok(num_clamped) => num_out_of_range += num_clamped,
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(num_out_of_range),
+        }
+    }
+
+    /// Like [download_into_per_attribute()](GpuPointBufferPerAttribute::download_into_per_attribute), but maps and
+    /// downloads the GPU buffers one after another, waiting for the device after mapping each buffer. This is slower
+    /// for many attributes and only exists for comparison in the `gpu_download_bench` benchmark, which is why it is
+    /// hidden from the documentation.
+    #[doc(hidden)]
+    pub async fn download_into_per_attribute_serial(
+        &self,
+        point_buffer: &mut dyn PerAttributePointBufferMut<'_>,
+        points_range: std::ops::Range<usize>,
+        buffer_infos: &Vec<BufferInfoPerAttribute<'_>>,
+        wgpu_device: &wgpu::Device) -> Result<usize>
+    {
+        let mut num_out_of_range = 0;
         for info in buffer_infos {
             let gpu_buffer = self.buffers.get(info.attribute.name()).unwrap();
 
-            let gpu_buffer_slice = gpu_buffer.slice(..);
-            let mapped_future = gpu_buffer_slice.map_async(wgpu::MapMode::Read);
+            let mapped_future = gpu_buffer.slice(..).map_async(wgpu::MapMode::Read);
             wgpu_device.poll(wgpu::Maintain::Wait); // TODO: "Should be called in event loop or other thread ..."
 
             if let Ok(()) = mapped_future.await {
-                let mapped_view = gpu_buffer_slice.get_mapped_range();
-                let result_as_bytes = mapped_view.to_vec();
-
-                let range = points_range.start..points_range.end;
-                match info.attribute.datatype() {
-                    PointAttributeDataType::Bool => {
-                        let result: Vec<bool> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) != 0)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<bool>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::U8 => {
-                        let result: Vec<u8> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u8)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<u8>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::I8 => {
-                        let result: Vec<i8> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| i32::from_ne_bytes(b.try_into().unwrap()) as i8)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<i8>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::U16 => {
-                        let result: Vec<u16> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u16)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<u16>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::I16 => {
-                        let result: Vec<i16> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| i32::from_ne_bytes(b.try_into().unwrap()) as i16)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<i16>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::U32 => {
-                        let result: Vec<u32> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()))
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<u32>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::I32 => {
-                        let result: Vec<i32> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| i32::from_ne_bytes(b.try_into().unwrap()))
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<i32>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::U64 => {},
-                    PointAttributeDataType::I64 => {},
-                    PointAttributeDataType::F32 => {
-                        let result: Vec<f32> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<f32>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::F64 => {
-                        let result: Vec<f64> = result_as_bytes
-                            .chunks_exact(8)
-                            .map(|b| f64::from_ne_bytes(b.try_into().unwrap()))
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<f64>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i] = result[i];
-                        }
-                    },
-                    PointAttributeDataType::Vec3u8 => {
-                        let result: Vec<u8> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u8)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<Vector3<u8>>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i].x = result[i * 4 + 0];
-                            attrib[i].y = result[i * 4 + 1];
-                            attrib[i].z = result[i * 4 + 2];
-                        }
-                    },
-                    PointAttributeDataType::Vec4u8 => {
-                        let result: Vec<u8> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u8)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<Vector4<u8>>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i].x = result[i * 4 + 0];
-                            attrib[i].y = result[i * 4 + 1];
-                            attrib[i].z = result[i * 4 + 2];
-                            attrib[i].w = result[i * 4 + 3];
-                        }
-                    },
-                    PointAttributeDataType::Vec3u16 => {
-                        let result: Vec<u16> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as u16)
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<Vector3<u16>>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i].x = result[i * 4 + 0];
-                            attrib[i].y = result[i * 4 + 1];
-                            attrib[i].z = result[i * 4 + 2];
-                        }
-                    },
-                    PointAttributeDataType::Vec3f32 => {
-                        let result: Vec<f32> = result_as_bytes
-                            .chunks_exact(4)
-                            .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<Vector3<f32>>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i].x = result[i * 4 + 0];
-                            attrib[i].y = result[i * 4 + 1];
-                            attrib[i].z = result[i * 4 + 2];
-                        }
-                    },
-                    PointAttributeDataType::Vec3f64 => {
-                        let result: Vec<f64> = result_as_bytes
-                            .chunks_exact(8)
-                            .map(|b| f64::from_ne_bytes(b.try_into().unwrap()))
-                            .collect();
-
-                        let attrib = point_buffer.get_attribute_range_mut::<Vector3<f64>>(range, info.attribute);
-                        for i in 0..attrib.len() {
-                            attrib[i].x = result[i * 4 + 0];
-                            attrib[i].y = result[i * 4 + 1];
-                            attrib[i].z = result[i * 4 + 2];
-                        }
-                    },
-                };
-
-                // Drop all mapped views before unmapping buffer
-                drop(mapped_view);
-                gpu_buffer.unmap();
+                num_out_of_range += download_mapped_attribute(point_buffer, points_range.clone(), info, gpu_buffer)?;
             }
         }
+        Ok(num_out_of_range)
     }
 
-    fn create_bind_group(&mut self, wgpu_device: &mut wgpu::Device) {
+    fn create_bind_group(&mut self, wgpu_device: &wgpu::Device) {
         let mut group_layout_entries: Vec<wgpu::BindGroupLayoutEntry> = vec![];
         let mut group_entries: Vec<wgpu::BindGroupEntry> = vec![];
 
@@ -1192,3 +1288,391 @@ impl<'a> GpuPointBufferPerAttribute<'a> {
         self.bind_group = Some(bind_group);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::attributes;
+    use crate::nalgebra::Vector4;
+
+    #[test]
+    fn test_check_unique_bindings() {
+        let unique_bindings = vec![
+            BufferInfoPerAttribute {
+                attribute: &attributes::POSITION_3D,
+                binding: 0,
+                extra_usage: wgpu::BufferUsages::empty(),
+            },
+            BufferInfoPerAttribute {
+                attribute: &attributes::INTENSITY,
+                binding: 1,
+                extra_usage: wgpu::BufferUsages::empty(),
+            },
+        ];
+        assert!(check_unique_bindings(&unique_bindings).is_ok());
+
+        let duplicate_bindings = vec![
+            BufferInfoPerAttribute {
+                attribute: &attributes::POSITION_3D,
+                binding: 1,
+                extra_usage: wgpu::BufferUsages::empty(),
+            },
+            BufferInfoPerAttribute {
+                attribute: &attributes::INTENSITY,
+                binding: 1,
+                extra_usage: wgpu::BufferUsages::empty(),
+            },
+        ];
+        let error = check_unique_bindings(&duplicate_bindings)
+            .expect_err("Duplicate bindings must yield an error");
+        assert!(error.to_string().contains("Binding 1"));
+    }
+
+    #[test]
+    fn test_check_extra_usages() {
+        let positions_f32 = attributes::POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32);
+        let compatible_usages = vec![
+            BufferInfoPerAttribute {
+                attribute: &positions_f32,
+                binding: 0,
+                extra_usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::UNIFORM,
+            },
+            BufferInfoPerAttribute {
+                attribute: &attributes::CLASSIFICATION,
+                binding: 1,
+                extra_usage: wgpu::BufferUsages::INDEX,
+            },
+        ];
+        assert!(check_extra_usages(&compatible_usages).is_ok());
+
+        let float_indices = [BufferInfoPerAttribute {
+            attribute: &attributes::GPS_TIME,
+            binding: 0,
+            extra_usage: wgpu::BufferUsages::INDEX,
+        }];
+        assert!(check_extra_usages(&float_indices).is_err());
+
+        let scalar_uniform = [BufferInfoPerAttribute {
+            attribute: &attributes::INTENSITY,
+            binding: 0,
+            extra_usage: wgpu::BufferUsages::UNIFORM,
+        }];
+        assert!(check_extra_usages(&scalar_uniform).is_err());
+
+        let attributes = [positions_f32.clone(), attributes::INTENSITY];
+        let interleaved_vertices = BufferInfoInterleaved {
+            attributes: &attributes,
+            binding: 0,
+            extra_usage: wgpu::BufferUsages::VERTEX,
+        };
+        assert!(check_extra_usage_interleaved(&interleaved_vertices).is_ok());
+        let interleaved_indices = BufferInfoInterleaved {
+            attributes: &attributes,
+            binding: 0,
+            extra_usage: wgpu::BufferUsages::INDEX,
+        };
+        assert!(check_extra_usage_interleaved(&interleaved_indices).is_err());
+    }
+
+    #[test]
+    fn test_vertex_usage_binds_as_vertex_buffer() {
+        use crate::containers::{PerAttributeVecPointStorage, PointBufferWriteable};
+        use crate::gpu::Device;
+        use crate::layout::PointLayout;
+
+        futures::executor::block_on(async {
+            // Skip this test on systems without a suitable GPU
+            let device = match Device::default().await {
+                Ok(device) => device,
+                Err(_) => return,
+            };
+
+            let positions_f32 = attributes::POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32);
+            let mut points = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[positions_f32.clone()]));
+            points.resize(3);
+            let buffer_infos = vec![BufferInfoPerAttribute {
+                attribute: &positions_f32,
+                binding: 0,
+                extra_usage: wgpu::BufferUsages::VERTEX,
+            }];
+            let mut gpu_point_buffer = GpuPointBufferPerAttribute::new();
+            gpu_point_buffer.malloc(3, &buffer_infos, &device.wgpu_device);
+            gpu_point_buffer
+                .upload(&points, 0..3, &buffer_infos, &device.wgpu_device, &device.wgpu_queue)
+                .expect("Upload failed");
+            let vertex_buffer = gpu_point_buffer.buffer_at_binding(0).unwrap();
+
+            let compile = |src: &str, kind: shaderc::ShaderKind| {
+                let mut compiler = shaderc::Compiler::new().unwrap();
+                let spirv = compiler.compile_into_spirv(src, kind, "vertex_usage_test", "main", None).unwrap();
+                device.wgpu_device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::util::make_spirv(spirv.as_binary_u8()),
+                })
+            };
+            // Vec3f32 positions are padded to a vec4 on the GPU
+            let vs_module = compile(
+                "#version 450
+                layout(location = 0) in vec4 position;
+                void main() {
+                    gl_Position = vec4(position.xyz, 1.0);
+                    gl_PointSize = 1.0;
+                }",
+                shaderc::ShaderKind::Vertex,
+            );
+            let fs_module = compile(
+                "#version 450
+                layout(location = 0) out vec4 color;
+                void main() {
+                    color = vec4(1.0);
+                }",
+                shaderc::ShaderKind::Fragment,
+            );
+
+            let target_format = wgpu::TextureFormat::Rgba8Unorm;
+            let pipeline = device.wgpu_device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("vertex_usage_pipeline"),
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &vs_module,
+                    entry_point: "main",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: 16,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x4],
+                    }],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::PointList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &fs_module,
+                    entry_point: "main",
+                    targets: &[target_format.into()],
+                }),
+            });
+            let target = device.wgpu_device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("vertex_usage_target"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: target_format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            });
+            let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+            // Without the VERTEX usage, binding the buffer as a vertex buffer is a validation error
+            device.wgpu_device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let mut encoder = device.wgpu_device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("vertex_usage_pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: &target_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+                render_pass.set_pipeline(&pipeline);
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.draw(0..3, 0..1);
+            }
+            device.wgpu_queue.submit(Some(encoder.finish()));
+            let error = device.wgpu_device.pop_error_scope().await;
+            assert!(error.is_none(), "Binding the vertex buffer failed: {:?}", error);
+        });
+    }
+
+    #[test]
+    fn test_align_slice_is_little_endian() {
+        let buffer = GpuPointBufferInterleaved::new();
+        let value: u16 = 0x1234;
+        let mut offset = 0;
+        let aligned = buffer
+            .align_slice(&value.to_ne_bytes(), PointAttributeDataType::U16, &mut offset)
+            .unwrap();
+        assert_eq!(vec![0x34, 0x12, 0x00, 0x00], aligned);
+        assert_eq!(4, offset);
+
+        let value: f32 = 1.5;
+        let mut offset = 0;
+        let aligned = buffer
+            .align_slice(&value.to_ne_bytes(), PointAttributeDataType::F32, &mut offset)
+            .unwrap();
+        assert_eq!(value.to_le_bytes().to_vec(), aligned);
+    }
+
+    #[test]
+    fn test_align_slice_vec4_needs_no_padding() {
+        let buffer = GpuPointBufferInterleaved::new();
+        let values = vec![
+            Vector4::new(1.0_f32, 2.0, 3.0, 4.0),
+            Vector4::new(-1.0_f32, 0.5, 0.0, 1.0),
+        ];
+        let value_bytes: &[u8] = unsafe {
+            std::slice::from_raw_parts(values.as_ptr() as *const u8, values.len() * 16)
+        };
+        let mut offset = 0;
+        let aligned = buffer
+            .align_slice(value_bytes, PointAttributeDataType::Vec4f32, &mut offset)
+            .unwrap();
+        let expected: Vec<u8> = values
+            .iter()
+            .flat_map(|v| v.iter().flat_map(|c| c.to_le_bytes().to_vec()).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(expected, aligned);
+        assert_eq!(32, offset);
+
+        let mut size = 0;
+        buffer.calc_size(value_bytes.len(), PointAttributeDataType::Vec4f32, &mut size);
+        assert_eq!(offset, size);
+
+        // Each component of a Vec4u8 is widened to 32 bits, but no fourth coordinate is appended
+        let colors = [10_u8, 20, 30, 40, 50, 60, 70, 80];
+        let mut offset = 0;
+        let aligned = buffer
+            .align_slice(&colors, PointAttributeDataType::Vec4u8, &mut offset)
+            .unwrap();
+        let expected: Vec<u8> = colors.iter().flat_map(|c| (*c as u32).to_le_bytes().to_vec()).collect();
+        assert_eq!(expected, aligned);
+
+        let mut size = 0;
+        buffer.calc_size(colors.len(), PointAttributeDataType::Vec4u8, &mut size);
+        assert_eq!(offset, size);
+    }
+
+    #[test]
+    fn test_align_slice_with_misaligned_slice() {
+        let buffer = GpuPointBufferInterleaved::new();
+        // Three bytes can't hold a whole number of U16 values
+        let misaligned_bytes = [0_u8; 3];
+        let mut offset = 0;
+        let error = buffer
+            .align_slice(&misaligned_bytes, PointAttributeDataType::U16, &mut offset)
+            .expect_err("Misaligned slice must yield an error");
+        assert!(error.to_string().contains("U16"));
+        assert!(error.to_string().contains("3 bytes"));
+
+        let misaligned_bytes = [0_u8; 25];
+        assert!(buffer
+            .align_slice(&misaligned_bytes, PointAttributeDataType::Vec3f64, &mut offset)
+            .is_err());
+
+        // 64-bit integers can't be uploaded at all
+        assert!(buffer
+            .align_slice(&[0; 8], PointAttributeDataType::U64, &mut offset)
+            .is_err());
+    }
+
+    #[test]
+    fn test_narrow_gpu_words_roundtrip() {
+        let buffer = GpuPointBufferInterleaved::new();
+        let values: Vec<u16> = vec![0, 1, 0x1234, u16::MAX];
+        let mut offset = 0;
+        let aligned = buffer
+            .align_slice(bytemuck::cast_slice(&values), PointAttributeDataType::U16, &mut offset)
+            .unwrap();
+
+        let mut num_out_of_range = 0;
+        let narrowed: Vec<u16> = narrow_gpu_words(&aligned, &mut num_out_of_range);
+        assert_eq!(values, narrowed);
+        assert_eq!(0, num_out_of_range);
+    }
+
+    #[test]
+    fn test_narrow_gpu_words_clamps_out_of_range_values() {
+        let words = [70000_u32.to_le_bytes(), 42_u32.to_le_bytes()].concat();
+        let mut num_out_of_range = 0;
+        let narrowed: Vec<u16> = narrow_gpu_words(&words, &mut num_out_of_range);
+        assert_eq!(vec![u16::MAX, 42], narrowed);
+        assert_eq!(1, num_out_of_range);
+
+        let words = [(-200_i32).to_le_bytes(), 200_i32.to_le_bytes(), (-5_i32).to_le_bytes()].concat();
+        let mut num_out_of_range = 0;
+        let narrowed: Vec<i8> = narrow_gpu_words(&words, &mut num_out_of_range);
+        assert_eq!(vec![i8::MIN, i8::MAX, -5], narrowed);
+        assert_eq!(2, num_out_of_range);
+    }
+
+    #[test]
+    fn test_dealign_slice_is_inverse_of_align_slice() {
+        let buffer = GpuPointBufferPerAttribute::new();
+        let datatypes = [
+            PointAttributeDataType::U8,
+            PointAttributeDataType::I8,
+            PointAttributeDataType::U16,
+            PointAttributeDataType::I16,
+            PointAttributeDataType::U32,
+            PointAttributeDataType::I32,
+            PointAttributeDataType::F32,
+            PointAttributeDataType::F64,
+            PointAttributeDataType::Bool,
+            PointAttributeDataType::Vec3u8,
+            PointAttributeDataType::Vec3u16,
+            PointAttributeDataType::Vec3f32,
+            PointAttributeDataType::Vec3f64,
+            PointAttributeDataType::Vec4u8,
+            PointAttributeDataType::Vec4u16,
+            PointAttributeDataType::Vec4f32,
+            PointAttributeDataType::Vec4f64,
+            PointAttributeDataType::Array4f32,
+            PointAttributeDataType::Array8f32,
+            PointAttributeDataType::Array4i32,
+            PointAttributeDataType::Array8i32,
+        ];
+        for datatype in datatypes.iter() {
+            let num_bytes = 3 * datatype.size() as usize;
+            let values: Vec<u8> = match datatype {
+                PointAttributeDataType::Bool => vec![0, 1, 1],
+                // Includes negative values for the signed types
+                _ => (0..num_bytes).map(|i| (i * 37 + 200) as u8).collect(),
+            };
+
+            let mut offset = 0;
+            let aligned = buffer.align_slice(&values, *datatype, &mut offset).unwrap();
+            assert_eq!(3 * buffer.alignment_per_element(*datatype), aligned.len(), "{}", datatype);
+            let (dealigned, num_out_of_range) = dealign_slice(&aligned, *datatype).unwrap();
+            assert_eq!(values, dealigned, "{}", datatype);
+            assert_eq!(0, num_out_of_range, "{}", datatype);
+        }
+    }
+
+    #[test]
+    fn test_dealign_slice_narrows_and_strips_fourth_coordinate() {
+        // Negative values are sign-extended, so that the shader reads them correctly
+        let buffer = GpuPointBufferPerAttribute::new();
+        let values: Vec<i16> = vec![-1, 300];
+        let mut offset = 0;
+        let aligned = buffer
+            .align_slice(bytemuck::cast_slice(&values), PointAttributeDataType::I16, &mut offset)
+            .unwrap();
+        assert_eq!([(-1_i32).to_le_bytes(), 300_i32.to_le_bytes()].concat(), aligned);
+
+        let words = [2_u32.to_le_bytes(), 0_u32.to_le_bytes()].concat();
+        assert_eq!((vec![1, 0], 0), dealign_slice(&words, PointAttributeDataType::Bool).unwrap());
+
+        let colors = [1_u32, 70000, 3, 1, 4, 5, 6, 1]
+            .iter()
+            .flat_map(|c| c.to_le_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let (dealigned, num_out_of_range) = dealign_slice(&colors, PointAttributeDataType::Vec3u16).unwrap();
+        let expected: Vec<u16> = vec![1, u16::MAX, 3, 4, 5, 6];
+        assert_eq!(bytemuck::cast_slice::<u16, u8>(&expected), dealigned.as_slice());
+        assert_eq!(1, num_out_of_range);
+
+        assert!(dealign_slice(&[0; 6], PointAttributeDataType::U16).is_err());
+        assert!(dealign_slice(&[0; 8], PointAttributeDataType::U64).is_err());
+    }
+}
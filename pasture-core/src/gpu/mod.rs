@@ -7,16 +7,30 @@
 //!
 //! At the core of everything lies [Device](device::Device), which is responsible for obtaining a handle to the GPU
 //! and submitting work to it. It also exposes some `wgpu` structures that could be used by the user
-//! for more fine grained control.
+//! for more fine grained control. To share a GPU between multiple threads, use [SharedDevice](shared_device::SharedDevice).
 //!
 //! [GpuPointBufferInterleaved](gpu_point_buffer::GpuPointBufferInterleaved) and
 //! [GpuPointBufferPerAttribute](gpu_point_buffer::GpuPointBufferPerAttribute) can be used to store
 //! point cloud data in either format on the GPU and retrieve it. They also take care of aligning
 //! the data so that your shaders work with correct values.
 //! It is important to note that for storage buffers only the `std430` layout is supported.
+//! [generate_shader_bindings](shader_bindings::generate_shader_bindings) generates the matching GLSL declarations
+//! of these storage buffers.
 
 mod device;
 pub use self::device::*;
 
 mod gpu_point_buffer;
 pub use self::gpu_point_buffer::*;
+
+mod shared_device;
+pub use self::shared_device::*;
+
+mod shader_bindings;
+pub use self::shader_bindings::*;
+
+mod shader_reflection;
+pub use self::shader_reflection::*;
+
+mod buffer_pool;
+pub(crate) use self::buffer_pool::*;
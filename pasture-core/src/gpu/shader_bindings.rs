@@ -0,0 +1,186 @@
+use crate::gpu::BufferInfoPerAttribute;
+use crate::layout::PointAttributeDataType;
+use anyhow::{bail, Result};
+use std::fmt::Write;
+
+/// Generates the GLSL declarations of the shader storage buffers that are described by `infos`, one
+/// `layout(std430, set=<set>, binding=N) buffer` declaration per attribute. The GLSL type of each buffer matches the
+/// `std430` representation that [GpuPointBufferPerAttribute](crate::gpu::GpuPointBufferPerAttribute) uploads for the
+/// datatype of the attribute, so the returned snippet can be pasted at the top of a compute shader to keep the Rust
+/// and the GLSL side consistent.
+///
+/// The name of each buffer block is the sanitized attribute name followed by `Buffer`, the name of the array inside
+/// the block is the sanitized attribute name starting with a lowercase letter. For [POSITION_3D](crate::layout::attributes::POSITION_3D)
+/// at binding 0 this yields:
+/// ```ignore
+/// layout(std430, set=0, binding=0) buffer Position3DBuffer {
+///     dvec4 position3D[];
+/// };
+/// ```
+///
+/// # Errors
+///
+/// If any of the attributes has a 64-bit integer datatype, since these are not supported on the shader side
+///
+/// # Examples
+///
+/// ```
+/// use pasture_core::gpu;
+/// use pasture_core::layout::attributes;
+///
+/// let buffer_infos = vec![
+///     gpu::BufferInfoPerAttribute {
+///         attribute: &attributes::POSITION_3D,
+///         binding: 0,
+///         extra_usage: wgpu::BufferUsages::empty(),
+///     },
+///     gpu::BufferInfoPerAttribute {
+///         attribute: &attributes::INTENSITY,
+///         binding: 1,
+///         extra_usage: wgpu::BufferUsages::empty(),
+///     },
+/// ];
+///
+/// let bindings = gpu::generate_shader_bindings(0, &buffer_infos).unwrap();
+/// assert!(bindings.contains("dvec4 position3D[];"));
+/// assert!(bindings.contains("uint intensity[];"));
+/// ```
+pub fn generate_shader_bindings(set: u32, infos: &[BufferInfoPerAttribute]) -> Result<String> {
+    let mut bindings = String::new();
+    for info in infos {
+        let glsl_type = glsl_type_for_datatype(info.attribute.datatype())?;
+        let identifier = glsl_identifier(info.attribute.name());
+        let mut chars = identifier.chars();
+        let array_name = match chars.next() {
+            Some(first) => first.to_lowercase().chain(chars).collect::<String>(),
+            None => identifier.clone(),
+        };
+        let (element_type, array_suffix) = match glsl_type.find('[') {
+            Some(idx) => glsl_type.split_at(idx),
+            None => (glsl_type, ""),
+        };
+
+        writeln!(
+            bindings,
+            "layout(std430, set={}, binding={}) buffer {}Buffer {{",
+            set, info.binding, identifier
+        )?;
+        writeln!(
+            bindings,
+            "    {} {}[]{};",
+            element_type, array_name, array_suffix
+        )?;
+        writeln!(bindings, "}};")?;
+    }
+    Ok(bindings)
+}
+
+/// Returns the GLSL type that corresponds to the `std430` representation of `datatype` on the GPU, following the
+/// rules that `GpuPointBufferPerAttribute` uses when aligning the data
+fn glsl_type_for_datatype(datatype: PointAttributeDataType) -> Result<&'static str> {
+    let glsl_type = match datatype {
+        PointAttributeDataType::U8 | PointAttributeDataType::U16 | PointAttributeDataType::U32 => {
+            "uint"
+        }
+        PointAttributeDataType::I8 | PointAttributeDataType::I16 | PointAttributeDataType::I32 => {
+            "int"
+        }
+        PointAttributeDataType::U64 | PointAttributeDataType::I64 => {
+            bail!("Datatype {} is not supported on the shader side", datatype)
+        }
+        PointAttributeDataType::F32 => "float",
+        PointAttributeDataType::F64 => "double",
+        PointAttributeDataType::Bool => "bool",
+        PointAttributeDataType::Vec3u8
+        | PointAttributeDataType::Vec4u8
+        | PointAttributeDataType::Vec3u16
+        | PointAttributeDataType::Vec4u16 => "uvec4",
+        PointAttributeDataType::Vec3f32 | PointAttributeDataType::Vec4f32 => "vec4",
+        PointAttributeDataType::Vec3f64 | PointAttributeDataType::Vec4f64 => "dvec4",
+        PointAttributeDataType::Array4f32 => "float[4]",
+        PointAttributeDataType::Array8f32 => "float[8]",
+        PointAttributeDataType::Array4i32 => "int[4]",
+        PointAttributeDataType::Array8i32 => "int[8]",
+    };
+    Ok(glsl_type)
+}
+
+/// Turns the attribute name `name` into a valid GLSL identifier by replacing all unsupported characters with
+/// underscores
+fn glsl_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{attributes, PointAttributeDefinition};
+
+    #[test]
+    fn test_generated_shader_bindings_compile() -> Result<()> {
+        let custom_attribute =
+            PointAttributeDefinition::custom("Custom attribute", PointAttributeDataType::Array4f32);
+        let attributes = [
+            attributes::POSITION_3D,
+            attributes::INTENSITY,
+            attributes::RETURN_NUMBER,
+            attributes::CLASSIFICATION,
+            attributes::EDGE_OF_FLIGHT_LINE,
+            attributes::SCAN_ANGLE,
+            attributes::GPS_TIME,
+            attributes::COLOR_RGB,
+            attributes::NORMAL,
+            custom_attribute,
+        ];
+        let infos = attributes
+            .iter()
+            .enumerate()
+            .map(|(binding, attribute)| BufferInfoPerAttribute {
+                attribute,
+                binding: binding as u32,
+                extra_usage: wgpu::BufferUsages::empty(),
+            })
+            .collect::<Vec<_>>();
+
+        let bindings = generate_shader_bindings(0, &infos)?;
+        assert!(bindings.contains("layout(std430, set=0, binding=0) buffer Position3DBuffer {"));
+        assert!(bindings.contains("dvec4 position3D[];"));
+        assert!(bindings.contains("bool edgeOfFlightLine[];"));
+        assert!(bindings.contains("uvec4 colorRGB[];"));
+        assert!(bindings.contains("buffer Custom_attributeBuffer {"));
+        assert!(bindings.contains("float custom_attribute[][4];"));
+
+        let shader_src = format!(
+            "#version 450\nlayout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;\n{}\nvoid main() {{}}\n",
+            bindings
+        );
+        let mut compiler = shaderc::Compiler::new().unwrap();
+        compiler.compile_into_spirv(
+            &shader_src,
+            shaderc::ShaderKind::Compute,
+            "generated_bindings.comp",
+            "main",
+            None,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shader_bindings_reject_64_bit_integers() {
+        let attribute = PointAttributeDefinition::custom("Counter", PointAttributeDataType::U64);
+        let infos = [BufferInfoPerAttribute {
+            attribute: &attribute,
+            binding: 0,
+            extra_usage: wgpu::BufferUsages::empty(),
+        }];
+        assert!(generate_shader_bindings(0, &infos).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{bail, Result};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const SPIRV_MAGIC_NUMBER: u32 = 0x0723_0203;
+const SPIRV_HEADER_WORDS: usize = 5;
+
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+
+const DECORATION_BUFFER_BLOCK: u32 = 3;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+
+const STORAGE_CLASS_UNIFORM: u32 = 2;
+const STORAGE_CLASS_STORAGE_BUFFER: u32 = 12;
+
+/// Returns the `(set, binding)` pairs of all storage buffers that are declared in the given SPIR-V binary, sorted by set
+/// and binding. In GLSL, these are all `layout(std430, set=S, binding=B) buffer` declarations. Uniform buffers and other
+/// resources are ignored.
+///
+/// # Errors
+///
+/// If `spirv` is no valid SPIR-V binary
+pub fn storage_buffer_bindings(spirv: &[u32]) -> Result<Vec<(u32, u32)>> {
+    if spirv.len() < SPIRV_HEADER_WORDS || spirv[0] != SPIRV_MAGIC_NUMBER {
+        bail!("Shader is no valid SPIR-V binary");
+    }
+
+    let mut buffer_blocks = HashSet::new();
+    let mut descriptor_sets = HashMap::new();
+    let mut bindings = HashMap::new();
+    // Maps the ID of each array type to the ID of its element type, and the ID of each pointer type to its storage class
+    // and the ID of the type that it points to
+    let mut array_element_types = HashMap::new();
+    let mut pointer_types = HashMap::new();
+    // ID of the pointer type of each variable together with its storage class
+    let mut variables = vec![];
+
+    let mut position = SPIRV_HEADER_WORDS;
+    while position < spirv.len() {
+        let word_count = (spirv[position] >> 16) as usize;
+        let opcode = spirv[position] & 0xffff;
+        if word_count == 0 || position + word_count > spirv.len() {
+            bail!("Malformed SPIR-V instruction at word {}", position);
+        }
+        let operands = &spirv[position + 1..position + word_count];
+        match opcode {
+            OP_DECORATE if operands.len() >= 2 => match operands[1] {
+                DECORATION_BUFFER_BLOCK => {
+                    buffer_blocks.insert(operands[0]);
+                }
+                DECORATION_BINDING if operands.len() >= 3 => {
+                    bindings.insert(operands[0], operands[2]);
+                }
+                DECORATION_DESCRIPTOR_SET if operands.len() >= 3 => {
+                    descriptor_sets.insert(operands[0], operands[2]);
+                }
+                _ => (),
+            },
+            OP_TYPE_ARRAY | OP_TYPE_RUNTIME_ARRAY if operands.len() >= 2 => {
+                array_element_types.insert(operands[0], operands[1]);
+            }
+            OP_TYPE_POINTER if operands.len() >= 3 => {
+                pointer_types.insert(operands[0], (operands[1], operands[2]));
+            }
+            OP_VARIABLE if operands.len() >= 3 => {
+                variables.push((operands[1], operands[0], operands[2]));
+            }
+            _ => (),
+        }
+        position += word_count;
+    }
+
+    let mut storage_buffers = BTreeSet::new();
+    for (variable, pointer_type, storage_class) in variables {
+        let is_storage_buffer = match storage_class {
+            STORAGE_CLASS_STORAGE_BUFFER => true,
+            // Older SPIR-V versions declare storage buffers as uniforms whose type is decorated with `BufferBlock`
+            STORAGE_CLASS_UNIFORM => {
+                let mut pointee_type = match pointer_types.get(&pointer_type) {
+                    Some((_, pointee_type)) => *pointee_type,
+                    None => continue,
+                };
+                while let Some(element_type) = array_element_types.get(&pointee_type) {
+                    pointee_type = *element_type;
+                }
+                buffer_blocks.contains(&pointee_type)
+            }
+            _ => false,
+        };
+        if !is_storage_buffer {
+            continue;
+        }
+        if let Some(binding) = bindings.get(&variable) {
+            let set = descriptor_sets.get(&variable).copied().unwrap_or(0);
+            storage_buffers.insert((set, *binding));
+        }
+    }
+
+    Ok(storage_buffers.into_iter().collect())
+}
+
+/// Checks that the storage buffers at the given `bindings` in the descriptor set `set` match exactly the storage buffers
+/// that the given SPIR-V binary declares in this set. A binding that is declared in the shader but not provided, or
+/// provided but not declared in the shader, usually means that the bindings on the Rust and on the shader side are out
+/// of sync, which otherwise goes unnoticed until the results of the shader are wrong.
+///
+/// # Errors
+///
+/// If `spirv` is no valid SPIR-V binary, or if the bindings don't match. The error contains all missing and all extra
+/// binding numbers
+pub fn validate_storage_buffer_bindings(spirv: &[u32], set: u32, bindings: &[u32]) -> Result<()> {
+    let declared_bindings = storage_buffer_bindings(spirv)?
+        .into_iter()
+        .filter(|(declared_set, _)| *declared_set == set)
+        .map(|(_, binding)| binding)
+        .collect::<BTreeSet<_>>();
+    let provided_bindings = bindings.iter().copied().collect::<BTreeSet<_>>();
+
+    let missing_bindings = declared_bindings
+        .difference(&provided_bindings)
+        .collect::<Vec<_>>();
+    let extra_bindings = provided_bindings
+        .difference(&declared_bindings)
+        .collect::<Vec<_>>();
+    if !missing_bindings.is_empty() || !extra_bindings.is_empty() {
+        bail!(
+            "Storage buffer bindings of set {} do not match the shader (missing bindings: {:?}, extra bindings: {:?})",
+            set,
+            missing_bindings,
+            extra_bindings
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(shader_src: &str) -> Vec<u32> {
+        let mut compiler = shaderc::Compiler::new().unwrap();
+        compiler
+            .compile_into_spirv(
+                shader_src,
+                shaderc::ShaderKind::Compute,
+                "reflection_test.comp",
+                "main",
+                None,
+            )
+            .expect("Compiling the shader failed")
+            .as_binary()
+            .to_vec()
+    }
+
+    const SHADER_WITH_THREE_BINDINGS: &str = "
+        #version 450
+        layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+        layout(std430, set = 0, binding = 0) buffer Positions {
+            dvec4 positions[];
+        };
+        layout(std430, set = 0, binding = 1) buffer Intensities {
+            uint intensities[];
+        };
+        layout(std430, set = 0, binding = 2) buffer Classifications {
+            uint classifications[];
+        };
+        layout(std140, set = 1, binding = 0) uniform Parameters {
+            uint offset;
+        };
+        void main() {
+            uint idx = gl_GlobalInvocationID.x;
+            intensities[idx] = uint(positions[idx].x) + classifications[idx] + offset;
+        }";
+
+    #[test]
+    fn test_storage_buffer_bindings() {
+        let spirv = compile(SHADER_WITH_THREE_BINDINGS);
+        assert_eq!(
+            vec![(0, 0), (0, 1), (0, 2)],
+            storage_buffer_bindings(&spirv).unwrap()
+        );
+        assert!(storage_buffer_bindings(&[0, 1, 2, 3, 4]).is_err());
+    }
+
+    #[test]
+    fn test_validate_storage_buffer_bindings() {
+        let spirv = compile(SHADER_WITH_THREE_BINDINGS);
+        assert!(validate_storage_buffer_bindings(&spirv, 0, &[0, 1, 2]).is_ok());
+        assert!(validate_storage_buffer_bindings(&spirv, 1, &[]).is_ok());
+
+        let error = validate_storage_buffer_bindings(&spirv, 0, &[0, 2])
+            .expect_err("Missing binding 1 was not detected");
+        assert!(
+            error.to_string().contains("missing bindings: [1]"),
+            "Unexpected error: {}",
+            error
+        );
+
+        let error = validate_storage_buffer_bindings(&spirv, 0, &[0, 1, 2, 3])
+            .expect_err("Extra binding 3 was not detected");
+        assert!(
+            error.to_string().contains("extra bindings: [3]"),
+            "Unexpected error: {}",
+            error
+        );
+    }
+}
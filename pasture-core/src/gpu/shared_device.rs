@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::containers::PerAttributePointBufferMut;
+
+use super::{device::run_compute_on, BufferInfoPerAttribute, Device, DeviceOptions};
+
+/// A handle to the GPU that can be shared between threads, e.g. to dispatch compute jobs from a thread pool.
+///
+/// A [Device] stores the state of a single compute job (the compute shader, the pipeline and the bound bind groups)
+/// next to the `wgpu` device and queue, so it can't be used by multiple jobs at the same time. `SharedDevice` only
+/// holds the `wgpu` device and queue, which are both thread-safe, behind an `Arc`. Cloning a `SharedDevice` is
+/// cheap and yields a handle to the same GPU device. All state of a compute job is created per call to
+/// [run_compute()](SharedDevice::run_compute), so concurrent jobs are independent of each other.
+///
+/// A `SharedDevice` can be created from an existing `Device` using [Device::into_shared()].
+#[derive(Clone)]
+pub struct SharedDevice {
+    pub(crate) wgpu_device: Arc<wgpu::Device>,
+    pub(crate) wgpu_queue: Arc<wgpu::Queue>,
+}
+
+impl SharedDevice {
+    /// Creates a new `SharedDevice` with the given `device_options`. See [Device::new()] for details
+    pub async fn new(device_options: DeviceOptions) -> Result<Self> {
+        let device = Device::new(device_options).await?;
+        Ok(device.into_shared())
+    }
+
+    /// Returns a handle to `wgpu`'s [Device](wgpu::Device). Can be used to create buffers, bind groups, etc.
+    pub fn wgpu_device(&self) -> &wgpu::Device {
+        &self.wgpu_device
+    }
+
+    /// Returns a handle to `wgpu`'s [Queue](wgpu::Queue). Can be used to write to buffers, submit work, etc.
+    pub fn wgpu_queue(&self) -> &wgpu::Queue {
+        &self.wgpu_queue
+    }
+
+    /// Runs a complete compute pass on the points in `buffer`. This behaves like [Device::run_compute()], but only
+    /// requires shared access to the associated `SharedDevice`, so it can be called from multiple threads at once.
+    /// In contrast to `Device`, the GPU buffers are not pooled, but allocated anew for every call. Returns the number
+    /// of values that were clamped when writing the results back into `buffer`.
+    ///
+    /// # Errors
+    /// If the points can't be uploaded onto the GPU or if the shader fails to compile, an error is returned.
+    pub async fn run_compute<'b, B: PerAttributePointBufferMut<'b>>(
+        &self,
+        buffer: &mut B,
+        buffer_infos: &[BufferInfoPerAttribute<'_>],
+        shader_src: &str,
+        dispatch: (u32, u32, u32),
+    ) -> Result<usize> {
+        run_compute_on(
+            &self.wgpu_device,
+            &self.wgpu_queue,
+            None,
+            buffer,
+            buffer_infos,
+            shader_src,
+            dispatch,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::{PerAttributeVecPointStorage, PointBufferExt};
+    use crate::layout::{attributes, PointLayout};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_shared_device_is_send_and_sync() {
+        assert_send_sync::<SharedDevice>();
+    }
+
+    #[test]
+    fn test_concurrent_kernels_on_shared_device() {
+        // Skip this test on systems without a suitable GPU
+        let device = match futures::executor::block_on(SharedDevice::new(Default::default())) {
+            Ok(device) => device,
+            Err(_) => return,
+        };
+
+        let run_kernel = |device: SharedDevice, factor: u32| {
+            std::thread::spawn(move || {
+                let buffer_infos = [BufferInfoPerAttribute {
+                    attribute: &attributes::INTENSITY,
+                    binding: 0,
+                    extra_usage: wgpu::BufferUsages::empty(),
+                }];
+                let mut points = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+                    attributes::INTENSITY,
+                ]));
+                let mut pusher = points.begin_push_attributes();
+                pusher
+                    .push_attribute_range(&attributes::INTENSITY, &(1..=64).collect::<Vec<u16>>());
+                pusher.done();
+
+                let shader_src = format!(
+                    "
+                    #version 450
+                    layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+                    layout(std430, set = 0, binding = 0) buffer Intensities {{
+                        uint intensities[];
+                    }};
+                    void main() {{
+                        uint idx = gl_GlobalInvocationID.x;
+                        intensities[idx] *= {};
+                    }}",
+                    factor
+                );
+                futures::executor::block_on(device.run_compute(
+                    &mut points,
+                    &buffer_infos,
+                    &shader_src,
+                    (64, 1, 1),
+                ))
+                .expect("run_compute failed");
+
+                points
+                    .iter_attribute::<u16>(&attributes::INTENSITY)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let first = run_kernel(device.clone(), 2);
+        let second = run_kernel(device, 3);
+
+        let expected_first = (1..=64).map(|v| v * 2).collect::<Vec<u16>>();
+        let expected_second = (1..=64).map(|v| v * 3).collect::<Vec<u16>>();
+        assert_eq!(expected_first, first.join().unwrap());
+        assert_eq!(expected_second, second.join().unwrap());
+    }
+}
@@ -0,0 +1,15 @@
+//! Bits of the [CLASSIFICATION_FLAGS](crate::layout::attributes::CLASSIFICATION_FLAGS) attribute, as defined by the
+//! LAS 1.4 specification. Other formats and algorithms use the same bit order, so the flags can be tested independent
+//! of where the points came from.
+
+/// Bit of the `CLASSIFICATION_FLAGS` attribute that marks a point as synthetic, i.e. created by a technique other than
+/// LIDAR collection
+pub const CLASSIFICATION_FLAG_SYNTHETIC: u8 = 0b0001;
+/// Bit of the `CLASSIFICATION_FLAGS` attribute that marks a point as a model key-point
+pub const CLASSIFICATION_FLAG_KEY_POINT: u8 = 0b0010;
+/// Bit of the `CLASSIFICATION_FLAGS` attribute that marks a point as withheld, i.e. it should not be included in
+/// processing
+pub const CLASSIFICATION_FLAG_WITHHELD: u8 = 0b0100;
+/// Bit of the `CLASSIFICATION_FLAGS` attribute that marks a point as being within the overlap region of two or more
+/// swaths. Only the extended LAS point formats (6 to 10) can store this flag
+pub const CLASSIFICATION_FLAG_OVERLAP: u8 = 0b1000;
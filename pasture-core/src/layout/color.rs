@@ -0,0 +1,118 @@
+//! Helper functions for converting colors between the vector types that pasture uses for color attributes (e.g.
+//! `Vector3<u8>`) and packed representations that some file formats and the GPU use (e.g. RGB565 or packed RGBA8).
+
+use nalgebra::{Vector3, Vector4};
+
+/// Packs the given 8-bit RGB color into a 16-bit RGB565 value. Red is stored in the 5 most significant bits, followed
+/// by 6 bits of green and 5 bits of blue. The lower bits of each channel are discarded.
+///
+/// ```
+/// # use pasture_core::layout::color::*;
+/// # use pasture_core::nalgebra::Vector3;
+/// assert_eq!(0xF800, pack_rgb565(Vector3::new(255, 0, 0)));
+/// ```
+pub fn pack_rgb565(color: Vector3<u8>) -> u16 {
+    let r = (color.x >> 3) as u16;
+    let g = (color.y >> 2) as u16;
+    let b = (color.z >> 3) as u16;
+    (r << 11) | (g << 5) | b
+}
+
+/// Unpacks the given 16-bit RGB565 value into an 8-bit RGB color. The channels are expanded to 8 bits by replicating
+/// their most significant bits into the lower bits, so that e.g. `0xFFFF` unpacks to pure white `(255, 255, 255)`.
+///
+/// ```
+/// # use pasture_core::layout::color::*;
+/// # use pasture_core::nalgebra::Vector3;
+/// assert_eq!(Vector3::new(255, 0, 0), unpack_rgb565(0xF800));
+/// ```
+pub fn unpack_rgb565(packed: u16) -> Vector3<u8> {
+    let r = ((packed >> 11) & 0x1F) as u8;
+    let g = ((packed >> 5) & 0x3F) as u8;
+    let b = (packed & 0x1F) as u8;
+    Vector3::new(
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    )
+}
+
+/// Packs the given 8-bit RGBA color into a single `u32`. Red is stored in the least significant byte, followed by
+/// green, blue and alpha, so that the in-memory representation of the packed value on a little-endian machine is
+/// identical to that of the `Vector4<u8>`.
+///
+/// ```
+/// # use pasture_core::layout::color::*;
+/// # use pasture_core::nalgebra::Vector4;
+/// assert_eq!(0xFF0000FF, pack_rgba8(Vector4::new(255, 0, 0, 255)));
+/// ```
+pub fn pack_rgba8(color: Vector4<u8>) -> u32 {
+    (color.x as u32) | ((color.y as u32) << 8) | ((color.z as u32) << 16) | ((color.w as u32) << 24)
+}
+
+/// Unpacks the given `u32` into an 8-bit RGBA color. This is the inverse of [pack_rgba8]
+///
+/// ```
+/// # use pasture_core::layout::color::*;
+/// # use pasture_core::nalgebra::Vector4;
+/// assert_eq!(Vector4::new(255, 0, 0, 255), unpack_rgba8(0xFF0000FF));
+/// ```
+pub fn unpack_rgba8(packed: u32) -> Vector4<u8> {
+    Vector4::new(
+        (packed & 0xFF) as u8,
+        ((packed >> 8) & 0xFF) as u8,
+        ((packed >> 16) & 0xFF) as u8,
+        ((packed >> 24) & 0xFF) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_rgb565() {
+        assert_eq!(0xFFFF, pack_rgb565(Vector3::new(255, 255, 255)));
+        assert_eq!(0x0000, pack_rgb565(Vector3::new(0, 0, 0)));
+        assert_eq!(0xF800, pack_rgb565(Vector3::new(255, 0, 0)));
+        assert_eq!(0x07E0, pack_rgb565(Vector3::new(0, 255, 0)));
+        assert_eq!(0x001F, pack_rgb565(Vector3::new(0, 0, 255)));
+        // Lower bits are discarded
+        assert_eq!(0xF800, pack_rgb565(Vector3::new(0xFF, 0x03, 0x07)));
+    }
+
+    #[test]
+    fn test_unpack_rgb565() {
+        assert_eq!(Vector3::new(255, 255, 255), unpack_rgb565(0xFFFF));
+        assert_eq!(Vector3::new(0, 0, 0), unpack_rgb565(0x0000));
+        assert_eq!(Vector3::new(255, 0, 0), unpack_rgb565(0xF800));
+        assert_eq!(Vector3::new(0, 255, 0), unpack_rgb565(0x07E0));
+        assert_eq!(Vector3::new(0, 0, 255), unpack_rgb565(0x001F));
+    }
+
+    #[test]
+    fn test_rgb565_roundtrip() {
+        for packed in 0..=u16::MAX {
+            assert_eq!(packed, pack_rgb565(unpack_rgb565(packed)));
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_rgba8() {
+        assert_eq!(0xFFFFFFFF, pack_rgba8(Vector4::new(255, 255, 255, 255)));
+        assert_eq!(0x00000000, pack_rgba8(Vector4::new(0, 0, 0, 0)));
+        assert_eq!(0x000000FF, pack_rgba8(Vector4::new(255, 0, 0, 0)));
+        assert_eq!(0x04030201, pack_rgba8(Vector4::new(1, 2, 3, 4)));
+
+        assert_eq!(Vector4::new(255, 255, 255, 255), unpack_rgba8(0xFFFFFFFF));
+        assert_eq!(Vector4::new(0, 0, 0, 0), unpack_rgba8(0x00000000));
+        assert_eq!(Vector4::new(255, 0, 0, 0), unpack_rgba8(0x000000FF));
+        assert_eq!(Vector4::new(1, 2, 3, 4), unpack_rgba8(0x04030201));
+    }
+
+    #[test]
+    fn test_rgba8_matches_memory_layout() {
+        let color = Vector4::new(10, 20, 30, 40);
+        assert_eq!(u32::from_le_bytes([10, 20, 30, 40]), pack_rgba8(color));
+    }
+}
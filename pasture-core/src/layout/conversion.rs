@@ -13,8 +13,9 @@
 //! The conversion then operates on these two buffers. As this is a *highly* unsafe operation where all sorts of things
 //! could go wrong, any conversion is only valid together with the *exact* `PointLayout` of both `A` and `B`!
 
+use anyhow::{bail, Result};
 use lazy_static::lazy_static;
-use nalgebra::{Scalar, Vector3};
+use nalgebra::{Scalar, Vector3, Vector4};
 use std::{collections::HashMap, ops::Range};
 
 use crate::layout::{PointAttributeDataType, PointAttributeDefinition, PointLayout};
@@ -51,7 +52,7 @@ impl RawAttributeConverter {
     /// Performs the conversion
     unsafe fn convert(&self, source_point: &[u8], target_point: &mut [u8]) {
         let source_slice = &source_point[self.source_range.start..self.source_range.end];
-        let target_slice = &mut target_point[self.target_range.start..self.source_range.end];
+        let target_slice = &mut target_point[self.target_range.start..self.target_range.end];
 
         (self.conversion_fn)(source_slice, target_slice);
     }
@@ -106,9 +107,15 @@ impl RawPointConverter {
 pub type AttributeConversionFn = unsafe fn(&[u8], &mut [u8]) -> ();
 
 /// Returns a conversion function for converting from `from_attribute` into `to_attribute`. Both attributes must have the
-/// same name but can have different datatypes. Conversion functions operate on raw byte buffers, where the first argument
-/// is a buffer that represents a single value of `from_attribute` and the second buffer is a single mutable value of
-/// `to_attribute`. If both attributes are equal, `None` is returned.
+/// same name but can have different datatypes. The only exception are the [COLOR_RGB](crate::layout::attributes::COLOR_RGB)
+/// and [COLOR_RGBA](crate::layout::attributes::COLOR_RGBA) attributes, which can be converted into each other. Conversion
+/// functions operate on raw byte buffers, where the first argument is a buffer that represents a single value of
+/// `from_attribute` and the second buffer is a single mutable value of `to_attribute`. If both attributes are equal, `None`
+/// is returned.
+///
+/// If the integer side of a conversion between an integer and a floating-point datatype is
+/// [normalized](PointAttributeDefinition::as_normalized), the values are scaled instead of being cast, so a normalized `U8`
+/// value of 255 becomes 1.0 and vice versa.
 ///
 /// # Panics
 ///
@@ -117,20 +124,150 @@ pub fn get_converter_for_attributes(
     from_attribute: &PointAttributeDefinition,
     to_attribute: &PointAttributeDefinition,
 ) -> Option<AttributeConversionFn> {
+    let from_type = from_attribute.datatype();
+    let to_type = to_attribute.datatype();
+    match (from_attribute.name(), to_attribute.name()) {
+        ("ColorRGB", "ColorRGBA") | ("ColorRGBA", "ColorRGB") => {
+            return Some(
+                get_color_rgb_rgba_converter(from_type, to_type).unwrap_or_else(|| {
+                    panic!("Invalid color conversion {} -> {}", from_type, to_type)
+                }),
+            )
+        }
+        _ => (),
+    }
     if from_attribute.name() != to_attribute.name() {
         panic!("get_converter_for_attributes: from and to attributes must have the same name!");
     }
-    if from_attribute.datatype() == to_attribute.datatype() {
+    if from_type == to_type {
         return None;
     }
+    if let Some(converter) = get_normalized_converter(from_attribute, to_attribute) {
+        return Some(converter);
+    }
 
     match from_attribute.name() {
-        "Position3D" => get_position_converter(from_attribute.datatype(), to_attribute.datatype()),
-        "ColorRGB" => get_color_rgb_converter(from_attribute.datatype(), to_attribute.datatype()),
-        _ => get_generic_converter(from_attribute.datatype(), to_attribute.datatype()),
+        "Position3D" => get_position_converter(from_type, to_type),
+        "ColorRGB" => get_color_rgb_converter(from_type, to_type),
+        _ => Some(
+            get_generic_converter(from_type, to_type)
+                .unwrap_or_else(|| panic!("Invalid conversion {} -> {}", from_type, to_type)),
+        ),
+    }
+}
+
+/// Like [get_converter_for_attributes], but returns an error instead of panicking if no conversion from `from_attribute`
+/// into `to_attribute` is possible. Returns `Ok(None)` if both attributes are equal, so that no conversion is necessary
+///
+/// ```
+/// # use pasture_core::layout::*;
+/// # use pasture_core::layout::conversion::*;
+/// let intensity_u32 = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::U32);
+/// assert!(try_get_converter_for_attributes(&intensity_u32, &attributes::INTENSITY).unwrap().is_some());
+/// assert!(try_get_converter_for_attributes(&attributes::INTENSITY, &attributes::INTENSITY).unwrap().is_none());
+///
+/// let intensity_bool = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::Bool);
+/// assert!(try_get_converter_for_attributes(&attributes::INTENSITY, &intensity_bool).is_err());
+/// ```
+///
+/// # Errors
+///
+/// If no conversion from `from_attribute` into `to_attribute` is possible, e.g. because they have different names or
+/// there is no conversion between their datatypes
+pub fn try_get_converter_for_attributes(
+    from_attribute: &PointAttributeDefinition,
+    to_attribute: &PointAttributeDefinition,
+) -> Result<Option<AttributeConversionFn>> {
+    let from_type = from_attribute.datatype();
+    let to_type = to_attribute.datatype();
+    let converter = match (from_attribute.name(), to_attribute.name()) {
+        ("ColorRGB", "ColorRGBA") | ("ColorRGBA", "ColorRGB") => {
+            get_color_rgb_rgba_converter(from_type, to_type)
+        }
+        (from_name, to_name) if from_name != to_name => bail!(
+            "Can't convert between attributes with different names ({} and {})",
+            from_name,
+            to_name
+        ),
+        _ if from_type == to_type => return Ok(None),
+        (name, _) => {
+            get_normalized_converter(from_attribute, to_attribute).or_else(|| match name {
+                "Position3D" => get_position_converter(from_type, to_type),
+                "ColorRGB" => get_color_rgb_converter(from_type, to_type),
+                _ => get_generic_converter(from_type, to_type),
+            })
+        }
+    };
+    match converter {
+        Some(converter) => Ok(Some(converter)),
+        None => bail!(
+            "No conversion from {} to {} exists for attribute {}",
+            from_type,
+            to_type,
+            from_attribute.name()
+        ),
+    }
+}
+
+/// Converter that reads a scalar value, applies a linear transform of the form `value * scale + offset` and writes the
+/// result as an `f64` value. This is how file formats such as LAS store physical quantities in integer attributes. In
+/// contrast to the `AttributeConversionFn`s returned by `get_converter_for_attributes`, which only cast values, this
+/// converter carries the `scale` and `offset` with it, so it is a struct instead of a function pointer
+#[derive(Copy, Clone)]
+pub struct ScaledAttributeConverter {
+    read_fn: unsafe fn(&[u8]) -> f64,
+    scale: f64,
+    offset: f64,
+}
+
+impl ScaledAttributeConverter {
+    /// Converts the single scalar value in `from` into the scaled `f64` value in `to`. Just as for an `AttributeConversionFn`,
+    /// `from` must be a single value of the source datatype of this converter, and `to` must be 8 bytes long
+    pub unsafe fn convert(&self, from: &[u8], to: &mut [u8]) {
+        let value = (self.read_fn)(from) * self.scale + self.offset;
+        (to.as_mut_ptr() as *mut f64).write_unaligned(value);
+    }
+
+    /// Returns the scale of this converter
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the offset of this converter
+    pub fn offset(&self) -> f64 {
+        self.offset
     }
 }
 
+/// Returns a converter that reads scalar values of the datatype `source_datatype` and converts them into `f64` values by
+/// applying `value * scale + offset`. This can be used to present integer attributes that are stored with a scale and
+/// offset, such as scaled LAS extra bytes, as their physical values. Returns `None` if `source_datatype` is not a
+/// scalar datatype
+pub fn scaled_converter(
+    source_datatype: PointAttributeDataType,
+    scale: f64,
+    offset: f64,
+) -> Option<ScaledAttributeConverter> {
+    let read_fn: unsafe fn(&[u8]) -> f64 = match source_datatype {
+        PointAttributeDataType::U8 => read_u8_as_f64,
+        PointAttributeDataType::I8 => read_i8_as_f64,
+        PointAttributeDataType::U16 => read_u16_as_f64,
+        PointAttributeDataType::I16 => read_i16_as_f64,
+        PointAttributeDataType::U32 => read_u32_as_f64,
+        PointAttributeDataType::I32 => read_i32_as_f64,
+        PointAttributeDataType::U64 => read_u64_as_f64,
+        PointAttributeDataType::I64 => read_i64_as_f64,
+        PointAttributeDataType::F32 => read_f32_as_f64,
+        PointAttributeDataType::F64 => read_f64_as_f64,
+        _ => return None,
+    };
+    Some(ScaledAttributeConverter {
+        read_fn,
+        scale,
+        offset,
+    })
+}
+
 fn get_position_converter(
     from_type: PointAttributeDataType,
     to_type: PointAttributeDataType,
@@ -194,6 +331,83 @@ fn get_color_rgb_converter(
     let key = (from_type, to_type);
     COLOR_RGB_CONVERTERS.get(&key).map(|&fptr| fptr)
 }
+macro_rules! insert_normalized_converters {
+    ($prim_int:ident, $prim_float:ident, $type_int:ident, $type_float:ident, $map:expr) => {
+        ($map).insert(
+            (
+                PointAttributeDataType::$type_int,
+                PointAttributeDataType::$type_float,
+            ),
+            convert_normalized_to_float::<$prim_int, $prim_float>,
+        );
+        ($map).insert(
+            (
+                PointAttributeDataType::$type_float,
+                PointAttributeDataType::$type_int,
+            ),
+            convert_float_to_normalized::<$prim_float, $prim_int>,
+        );
+    };
+}
+
+/// Returns a converter between an integer and a floating-point attribute that respects normalization, if the integer
+/// attribute is normalized. Returns `None` if the attributes are not a normalized integer and a floating-point attribute
+/// with the same number of components
+fn get_normalized_converter(
+    from_attribute: &PointAttributeDefinition,
+    to_attribute: &PointAttributeDefinition,
+) -> Option<AttributeConversionFn> {
+    lazy_static! {
+        static ref NORMALIZED_CONVERTERS: HashMap<(PointAttributeDataType, PointAttributeDataType), AttributeConversionFn> = {
+            let mut converters = HashMap::<
+                (PointAttributeDataType, PointAttributeDataType),
+                AttributeConversionFn,
+            >::new();
+            insert_normalized_converters!(u8, f32, U8, F32, converters);
+            insert_normalized_converters!(u8, f64, U8, F64, converters);
+            insert_normalized_converters!(i8, f32, I8, F32, converters);
+            insert_normalized_converters!(i8, f64, I8, F64, converters);
+            insert_normalized_converters!(u16, f32, U16, F32, converters);
+            insert_normalized_converters!(u16, f64, U16, F64, converters);
+            insert_normalized_converters!(i16, f32, I16, F32, converters);
+            insert_normalized_converters!(i16, f64, I16, F64, converters);
+            insert_normalized_converters!(u32, f32, U32, F32, converters);
+            insert_normalized_converters!(u32, f64, U32, F64, converters);
+            insert_normalized_converters!(i32, f32, I32, F32, converters);
+            insert_normalized_converters!(i32, f64, I32, F64, converters);
+
+            insert_normalized_converters!(u8, f32, Vec3u8, Vec3f32, converters);
+            insert_normalized_converters!(u8, f64, Vec3u8, Vec3f64, converters);
+            insert_normalized_converters!(u16, f32, Vec3u16, Vec3f32, converters);
+            insert_normalized_converters!(u16, f64, Vec3u16, Vec3f64, converters);
+            insert_normalized_converters!(u8, f32, Vec4u8, Vec4f32, converters);
+            insert_normalized_converters!(u8, f64, Vec4u8, Vec4f64, converters);
+            insert_normalized_converters!(u16, f32, Vec4u16, Vec4f32, converters);
+            insert_normalized_converters!(u16, f64, Vec4u16, Vec4f64, converters);
+
+            converters
+        };
+    }
+
+    let is_floating_point = |datatype: PointAttributeDataType| {
+        matches!(
+            datatype.component_type(),
+            PointAttributeDataType::F32 | PointAttributeDataType::F64
+        )
+    };
+    // Only the integer side of the conversion determines whether the values are normalized
+    let is_normalized = if is_floating_point(from_attribute.datatype()) {
+        to_attribute.is_normalized()
+    } else {
+        from_attribute.is_normalized()
+    };
+    if !is_normalized {
+        return None;
+    }
+
+    let key = (from_attribute.datatype(), to_attribute.datatype());
+    NORMALIZED_CONVERTERS.get(&key).copied()
+}
 
 macro_rules! insert_converter_using_into {
     ($prim_from:ident, $prim_to:ident, $type_from:ident, $type_to:ident, $map:expr) => {
@@ -207,6 +421,18 @@ macro_rules! insert_converter_using_into {
     };
 }
 
+macro_rules! insert_vector_converter_using_into {
+    ($prim_from:ident, $prim_to:ident, $type_from:ident, $type_to:ident, $map:expr) => {
+        ($map).insert(
+            (
+                PointAttributeDataType::$type_from,
+                PointAttributeDataType::$type_to,
+            ),
+            convert_components_using_into::<$prim_from, $prim_to>,
+        )
+    };
+}
+
 macro_rules! insert_converter_using_as {
     ($type_from:ident, $type_to:ident, $convert_fn:ident, $map:expr) => {
         ($map).insert(
@@ -219,6 +445,50 @@ macro_rules! insert_converter_using_as {
     };
 }
 
+/// Returns a converter between an RGB color and an RGBA color (in either direction). Alpha is dropped when going from
+/// RGBA to RGB and is set to 255 when going from RGB to RGBA
+fn get_color_rgb_rgba_converter(
+    from_type: PointAttributeDataType,
+    to_type: PointAttributeDataType,
+) -> Option<AttributeConversionFn> {
+    lazy_static! {
+        static ref COLOR_RGB_RGBA_CONVERTERS: HashMap<(PointAttributeDataType, PointAttributeDataType), AttributeConversionFn> = {
+            let mut converters = HashMap::<
+                (PointAttributeDataType, PointAttributeDataType),
+                AttributeConversionFn,
+            >::new();
+            insert_converter_using_as!(
+                Vec3u8,
+                Vec4u8,
+                convert_color_rgb_from_vec3u8_to_rgba_vec4u8,
+                converters
+            );
+            insert_converter_using_as!(
+                Vec3u16,
+                Vec4u8,
+                convert_color_rgb_from_vec3u16_to_rgba_vec4u8,
+                converters
+            );
+            insert_converter_using_as!(
+                Vec4u8,
+                Vec3u8,
+                convert_color_rgba_from_vec4u8_to_rgb_vec3u8,
+                converters
+            );
+            insert_converter_using_as!(
+                Vec4u8,
+                Vec3u16,
+                convert_color_rgba_from_vec4u8_to_rgb_vec3u16,
+                converters
+            );
+            converters
+        };
+    }
+
+    let key = (from_type, to_type);
+    COLOR_RGB_RGBA_CONVERTERS.get(&key).copied()
+}
+
 /// Returns a generic converter that can convert between primitive types. Going from smaller to larger types is realized
 /// through `.into()` calls, while going from larger to smaller types is done through coercions (using `as`) where possible
 fn get_generic_converter(
@@ -245,6 +515,8 @@ fn get_generic_converter(
             insert_converter_using_into!(i16, i64, I16, I64, converters);
             insert_converter_using_into!(i32, i64, I32, I64, converters);
 
+            insert_converter_using_into!(f32, f64, F32, F64, converters);
+
             insert_converter_using_as!(U16, U8, convert_u16_to_u8, converters);
             insert_converter_using_as!(U32, U8, convert_u32_to_u8, converters);
             insert_converter_using_as!(U64, U8, convert_u64_to_u8, converters);
@@ -261,15 +533,17 @@ fn get_generic_converter(
 
             insert_converter_using_as!(F64, F32, convert_f64_to_f32, converters);
 
+            insert_vector_converter_using_into!(u8, u16, Vec4u8, Vec4u16, converters);
+            insert_vector_converter_using_into!(f32, f64, Vec4f32, Vec4f64, converters);
+            insert_converter_using_as!(Vec4u16, Vec4u8, convert_vec4u16_to_vec4u8, converters);
+            insert_converter_using_as!(Vec4f64, Vec4f32, convert_vec4f64_to_vec4f32, converters);
+
             converters
         };
     }
 
     let key = (from_type, to_type);
-    let f = GENERIC_CONVERTERS
-        .get(&key)
-        .unwrap_or_else(|| panic!("Invalid conversion {} -> {}", from_type, to_type));
-    Some(*f)
+    GENERIC_CONVERTERS.get(&key).copied()
 }
 
 /// Unit conversion function (when from and to represent the same datatype)
@@ -399,6 +673,54 @@ unsafe fn convert_color_rgb_from_vec3u8_to_vec3u16(from: &[u8], to: &mut [u8]) {
     to_vec.z = ((from_vec.z as u16) << 8) as u16;
 }
 
+/// Unsafe conversion of a `Vector3<u8>` RGB color to a `Vector4<u8>` RGBA color using their binary representations. The
+/// alpha channel is set to 255
+unsafe fn convert_color_rgb_from_vec3u8_to_rgba_vec4u8(from: &[u8], to: &mut [u8]) {
+    let from_vec = &*(from.as_ptr() as *const Vector3<u8>);
+    let to_vec = &mut *(to.as_mut_ptr() as *mut Vector4<u8>);
+
+    to_vec.x = from_vec.x;
+    to_vec.y = from_vec.y;
+    to_vec.z = from_vec.z;
+    to_vec.w = u8::MAX;
+}
+
+/// Unsafe conversion of a `Vector3<u16>` RGB color to a `Vector4<u8>` RGBA color using their binary representations. Like
+/// `convert_color_rgb_from_vec3u16_to_vec3u8`, this performs a bit shift to reduce the dynamic range of the color. The alpha
+/// channel is set to 255
+unsafe fn convert_color_rgb_from_vec3u16_to_rgba_vec4u8(from: &[u8], to: &mut [u8]) {
+    let from_vec = &*(from.as_ptr() as *const Vector3<u16>);
+    let to_vec = &mut *(to.as_mut_ptr() as *mut Vector4<u8>);
+
+    to_vec.x = (from_vec.x >> 8) as u8;
+    to_vec.y = (from_vec.y >> 8) as u8;
+    to_vec.z = (from_vec.z >> 8) as u8;
+    to_vec.w = u8::MAX;
+}
+
+/// Unsafe conversion of a `Vector4<u8>` RGBA color to a `Vector3<u8>` RGB color using their binary representations. The
+/// alpha channel is dropped
+unsafe fn convert_color_rgba_from_vec4u8_to_rgb_vec3u8(from: &[u8], to: &mut [u8]) {
+    let from_vec = &*(from.as_ptr() as *const Vector4<u8>);
+    let to_vec = &mut *(to.as_mut_ptr() as *mut Vector3<u8>);
+
+    to_vec.x = from_vec.x;
+    to_vec.y = from_vec.y;
+    to_vec.z = from_vec.z;
+}
+
+/// Unsafe conversion of a `Vector4<u8>` RGBA color to a `Vector3<u16>` RGB color using their binary representations. Like
+/// `convert_color_rgb_from_vec3u8_to_vec3u16`, this performs a bit shift to increase the dynamic range of the color. The
+/// alpha channel is dropped
+unsafe fn convert_color_rgba_from_vec4u8_to_rgb_vec3u16(from: &[u8], to: &mut [u8]) {
+    let from_vec = &*(from.as_ptr() as *const Vector4<u8>);
+    let to_vec = &mut *(to.as_mut_ptr() as *mut Vector3<u16>);
+
+    to_vec.x = (from_vec.x as u16) << 8;
+    to_vec.y = (from_vec.y as u16) << 8;
+    to_vec.z = (from_vec.z as u16) << 8;
+}
+
 unsafe fn _convert_generic_vec3<F, T>(from: &[u8], to: &mut [u8])
 where
     F: Into<T> + Copy + Scalar,
@@ -421,6 +743,108 @@ where
     (to.as_mut_ptr() as *mut T).write_unaligned(from_typed.into());
 }
 
+/// Converts a scalar or vector component-wise using `.into()`. The number of components is given by the length of `from`
+unsafe fn convert_components_using_into<F, T>(from: &[u8], to: &mut [u8])
+where
+    F: Into<T> + Copy,
+    T: Copy,
+{
+    let num_components = from.len() / std::mem::size_of::<F>();
+    let from_ptr = from.as_ptr() as *const F;
+    let to_ptr = to.as_mut_ptr() as *mut T;
+    for component in 0..num_components {
+        let value = from_ptr.add(component).read_unaligned();
+        to_ptr.add(component).write_unaligned(value.into());
+    }
+}
+
+/// Integer types that can store normalized values. Unsigned values are mapped to `[0;1]`, signed values to `[-1;1]`
+trait NormalizedInteger: Copy {
+    fn to_normalized(self) -> f64;
+    fn from_normalized(value: f64) -> Self;
+}
+
+macro_rules! impl_normalized_integer {
+    ($type:ident) => {
+        impl NormalizedInteger for $type {
+            fn to_normalized(self) -> f64 {
+                // For signed types, MIN is one smaller than -MAX, so we clamp to get a symmetric range
+                (self as f64 / $type::MAX as f64).max(-1.0)
+            }
+
+            fn from_normalized(value: f64) -> Self {
+                // `as` saturates, so negative values become 0 for unsigned types
+                (value.max(-1.0).min(1.0) * $type::MAX as f64).round() as $type
+            }
+        }
+    };
+}
+
+impl_normalized_integer!(u8);
+impl_normalized_integer!(i8);
+impl_normalized_integer!(u16);
+impl_normalized_integer!(i16);
+impl_normalized_integer!(u32);
+impl_normalized_integer!(i32);
+
+/// Floating-point types that are the target (or source) of conversions of normalized values
+trait FloatingPoint: Copy {
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl FloatingPoint for f32 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl FloatingPoint for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+/// Converts a normalized integer scalar or vector into a floating-point scalar or vector with the same number of
+/// components. The number of components is given by the length of `from`
+unsafe fn convert_normalized_to_float<I: NormalizedInteger, F: FloatingPoint>(
+    from: &[u8],
+    to: &mut [u8],
+) {
+    let num_components = from.len() / std::mem::size_of::<I>();
+    let from_ptr = from.as_ptr() as *const I;
+    let to_ptr = to.as_mut_ptr() as *mut F;
+    for component in 0..num_components {
+        let value = from_ptr.add(component).read_unaligned().to_normalized();
+        to_ptr.add(component).write_unaligned(F::from_f64(value));
+    }
+}
+
+/// Converts a floating-point scalar or vector into a normalized integer scalar or vector with the same number of
+/// components. Values outside of the range of the normalized integer type are clamped
+unsafe fn convert_float_to_normalized<F: FloatingPoint, I: NormalizedInteger>(
+    from: &[u8],
+    to: &mut [u8],
+) {
+    let num_components = from.len() / std::mem::size_of::<F>();
+    let from_ptr = from.as_ptr() as *const F;
+    let to_ptr = to.as_mut_ptr() as *mut I;
+    for component in 0..num_components {
+        let value = from_ptr.add(component).read_unaligned().to_f64();
+        to_ptr
+            .add(component)
+            .write_unaligned(I::from_normalized(value));
+    }
+}
+
 macro_rules! convert_using_as {
     ($type_from:ident, $type_to:ident, $name:ident) => {
         unsafe fn $name(from: &[u8], to: &mut [u8]) {
@@ -445,3 +869,241 @@ convert_using_as!(i64, i16, convert_i64_to_i16);
 convert_using_as!(i64, i32, convert_i64_to_i32);
 
 convert_using_as!(f64, f32, convert_f64_to_f32);
+
+macro_rules! convert_components_using_as {
+    ($type_from:ident, $type_to:ident, $name:ident) => {
+        unsafe fn $name(from: &[u8], to: &mut [u8]) {
+            let num_components = from.len() / std::mem::size_of::<$type_from>();
+            let from_ptr = from.as_ptr() as *const $type_from;
+            let to_ptr = to.as_mut_ptr() as *mut $type_to;
+            for component in 0..num_components {
+                let value = from_ptr.add(component).read_unaligned();
+                to_ptr.add(component).write_unaligned(value as $type_to);
+            }
+        }
+    };
+}
+
+convert_components_using_as!(u16, u8, convert_vec4u16_to_vec4u8);
+convert_components_using_as!(f64, f32, convert_vec4f64_to_vec4f32);
+
+macro_rules! read_as_f64 {
+    ($type_from:ident, $name:ident) => {
+        unsafe fn $name(from: &[u8]) -> f64 {
+            (from.as_ptr() as *const $type_from).read_unaligned() as f64
+        }
+    };
+}
+
+read_as_f64!(u8, read_u8_as_f64);
+read_as_f64!(i8, read_i8_as_f64);
+read_as_f64!(u16, read_u16_as_f64);
+read_as_f64!(i16, read_i16_as_f64);
+read_as_f64!(u32, read_u32_as_f64);
+read_as_f64!(i32, read_i32_as_f64);
+read_as_f64!(u64, read_u64_as_f64);
+read_as_f64!(i64, read_i64_as_f64);
+read_as_f64!(f32, read_f32_as_f64);
+read_as_f64!(f64, read_f64_as_f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::attributes::{COLOR_RGB, COLOR_RGBA};
+    use crate::util::{view_raw_bytes, view_raw_bytes_mut};
+
+    #[test]
+    fn test_convert_color_rgb_to_rgba() {
+        let converter = get_converter_for_attributes(
+            &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+            &COLOR_RGBA,
+        )
+        .expect("Converter from RGB to RGBA must exist");
+
+        let source = Vector3::<u8>::new(10, 20, 30);
+        let mut target = Vector4::<u8>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector4::new(10, 20, 30, 255), target);
+
+        let converter = get_converter_for_attributes(&COLOR_RGB, &COLOR_RGBA)
+            .expect("Converter from RGB to RGBA must exist");
+        let source = Vector3::<u16>::new(0xFFFF, 0x8000, 0x0100);
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector4::new(0xFF, 0x80, 0x01, 255), target);
+    }
+
+    #[test]
+    fn test_convert_color_rgba_to_rgb() {
+        let converter = get_converter_for_attributes(
+            &COLOR_RGBA,
+            &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+        )
+        .expect("Converter from RGBA to RGB must exist");
+
+        let source = Vector4::<u8>::new(10, 20, 30, 40);
+        let mut target = Vector3::<u8>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector3::new(10, 20, 30), target);
+
+        let converter = get_converter_for_attributes(&COLOR_RGBA, &COLOR_RGB)
+            .expect("Converter from RGBA to RGB must exist");
+        let mut target = Vector3::<u16>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector3::new(10 << 8, 20 << 8, 30 << 8), target);
+    }
+
+    #[test]
+    fn test_convert_normalized_attributes() {
+        let normalized_u8 =
+            PointAttributeDefinition::custom("Custom", PointAttributeDataType::U8).as_normalized();
+        let f32_attribute = normalized_u8.with_custom_datatype(PointAttributeDataType::F32);
+
+        let converter = get_converter_for_attributes(&normalized_u8, &f32_attribute)
+            .expect("Converter from normalized U8 to F32 must exist");
+        let mut target = 0.0_f32;
+        for (source, expected) in [(255_u8, 1.0_f32), (0, 0.0), (51, 0.2)].iter() {
+            unsafe {
+                converter(view_raw_bytes(source), view_raw_bytes_mut(&mut target));
+            }
+            assert_eq!(*expected, target);
+        }
+
+        let converter = get_converter_for_attributes(&f32_attribute, &normalized_u8)
+            .expect("Converter from F32 to normalized U8 must exist");
+        let mut target = 0_u8;
+        for (source, expected) in [(1.0_f32, 255_u8), (0.2, 51), (-0.5, 0), (2.0, 255)].iter() {
+            unsafe {
+                converter(view_raw_bytes(source), view_raw_bytes_mut(&mut target));
+            }
+            assert_eq!(*expected, target);
+        }
+
+        let normalized_i16 =
+            PointAttributeDefinition::custom("Custom", PointAttributeDataType::I16).as_normalized();
+        let converter = get_converter_for_attributes(
+            &normalized_i16,
+            &normalized_i16.with_custom_datatype(PointAttributeDataType::F64),
+        )
+        .expect("Converter from normalized I16 to F64 must exist");
+        let mut target = 0.0_f64;
+        for (source, expected) in [(i16::MAX, 1.0_f64), (i16::MIN, -1.0), (0, 0.0)].iter() {
+            unsafe {
+                converter(view_raw_bytes(source), view_raw_bytes_mut(&mut target));
+            }
+            assert_eq!(*expected, target);
+        }
+    }
+
+    #[test]
+    fn test_scaled_converter() {
+        let converter = scaled_converter(PointAttributeDataType::I16, 0.01, 0.0)
+            .expect("Scaled converter for I16 must exist");
+        let mut target = 0.0_f64;
+        for (source, expected) in [(1234_i16, 12.34_f64), (-250, -2.5), (0, 0.0)].iter() {
+            unsafe {
+                converter.convert(view_raw_bytes(source), view_raw_bytes_mut(&mut target));
+            }
+            assert!(
+                (expected - target).abs() < 1e-9,
+                "Expected {} but got {}",
+                expected,
+                target
+            );
+        }
+
+        let converter = scaled_converter(PointAttributeDataType::U8, 0.5, 100.0)
+            .expect("Scaled converter for U8 must exist");
+        unsafe {
+            converter.convert(view_raw_bytes(&255_u8), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(227.5, target);
+
+        assert!(scaled_converter(PointAttributeDataType::Vec3u16, 0.01, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_convert_normalized_color() {
+        let normalized_color = COLOR_RGB
+            .with_custom_datatype(PointAttributeDataType::Vec3u8)
+            .as_normalized();
+        let converter = get_converter_for_attributes(
+            &normalized_color,
+            &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3f32),
+        )
+        .expect("Converter from normalized Vec3u8 to Vec3f32 must exist");
+
+        let source = Vector3::<u8>::new(255, 0, 51);
+        let mut target = Vector3::<f32>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector3::new(1.0, 0.0, 0.2), target);
+    }
+
+    #[test]
+    fn test_convert_normalized_color_rgba() {
+        let normalized_color = COLOR_RGBA.as_normalized();
+        let converter = get_converter_for_attributes(
+            &normalized_color,
+            &COLOR_RGBA.with_custom_datatype(PointAttributeDataType::Vec4f32),
+        )
+        .expect("Converter from normalized Vec4u8 to Vec4f32 must exist");
+        let source = Vector4::<u8>::new(255, 0, 51, 255);
+        let mut target = Vector4::<f32>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector4::new(1.0, 0.0, 0.2, 1.0), target);
+
+        let converter = get_converter_for_attributes(
+            &COLOR_RGBA.with_custom_datatype(PointAttributeDataType::Vec4f64),
+            &normalized_color,
+        )
+        .expect("Converter from Vec4f64 to normalized Vec4u8 must exist");
+        let source = Vector4::<f64>::new(1.0, 0.2, -0.5, 2.0);
+        let mut target = Vector4::<u8>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector4::new(255, 51, 0, 255), target);
+    }
+
+    #[test]
+    fn test_convert_vec4() {
+        let orientation =
+            PointAttributeDefinition::custom("Orientation", PointAttributeDataType::Vec4f64);
+        let converter = get_converter_for_attributes(
+            &orientation,
+            &orientation.with_custom_datatype(PointAttributeDataType::Vec4f32),
+        )
+        .expect("Converter from Vec4f64 to Vec4f32 must exist");
+        let source = Vector4::<f64>::new(0.5, -0.5, 0.25, 1.0);
+        let mut target = Vector4::<f32>::zeros();
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector4::new(0.5, -0.5, 0.25, 1.0), target);
+
+        let normalized_color = COLOR_RGBA
+            .with_custom_datatype(PointAttributeDataType::Vec4u16)
+            .as_normalized();
+        let converter = get_converter_for_attributes(
+            &normalized_color,
+            &COLOR_RGBA.with_custom_datatype(PointAttributeDataType::Vec4f32),
+        )
+        .expect("Converter from normalized Vec4u16 to Vec4f32 must exist");
+        let source = Vector4::<u16>::new(u16::MAX, 0, 0, u16::MAX);
+        unsafe {
+            converter(view_raw_bytes(&source), view_raw_bytes_mut(&mut target));
+        }
+        assert_eq!(Vector4::new(1.0, 0.0, 0.0, 1.0), target);
+    }
+}
@@ -4,5 +4,9 @@ pub use self::point_layout::*;
 mod point_type;
 pub use self::point_type::*;
 
+pub mod classification_flags;
+
+pub mod color;
+
 pub mod conversion;
 //pub use self::conversion;
@@ -1,5 +1,12 @@
-use std::{alloc::Layout, fmt::Display};
-
+use std::{
+    alloc::Layout,
+    collections::BTreeSet,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use anyhow::{bail, Result};
 use itertools::Itertools;
 use nalgebra::{Vector3, Vector4};
 use static_assertions::const_assert;
@@ -27,6 +34,13 @@ mod private {
     impl Sealed for Vector3<f32> {}
     impl Sealed for Vector3<f64> {}
     impl Sealed for Vector4<u8> {}
+    impl Sealed for Vector4<u16> {}
+    impl Sealed for Vector4<f32> {}
+    impl Sealed for Vector4<f64> {}
+    impl Sealed for [f32; 4] {}
+    impl Sealed for [f32; 8] {}
+    impl Sealed for [i32; 4] {}
+    impl Sealed for [i32; 8] {}
 }
 
 /// Possible data types for individual point attributes
@@ -64,6 +78,20 @@ pub enum PointAttributeDataType {
     Vec3f64,
     /// A 4-component vector storing unsigned 8-bit integer values. Corresponding to the `Vector4<u8>` type of the [nalgebra crate](https://crates.io/crates/nalgebra)
     Vec4u8,
+    /// A 4-component vector storing unsigned 16-bit integer values, e.g. an RGBA color. Corresponding to the `Vector4<u16>` type of the [nalgebra crate](https://crates.io/crates/nalgebra)
+    Vec4u16,
+    /// A 4-component vector storing single-precision floating point values, e.g. an orientation quaternion. Corresponding to the `Vector4<f32>` type of the [nalgebra crate](https://crates.io/crates/nalgebra)
+    Vec4f32,
+    /// A 4-component vector storing double-precision floating point values. Corresponding to the `Vector4<f64>` type of the [nalgebra crate](https://crates.io/crates/nalgebra)
+    Vec4f64,
+    /// A fixed-size array of 4 single-precision floating point values, corresponding to Rusts `[f32; 4]` type
+    Array4f32,
+    /// A fixed-size array of 8 single-precision floating point values, corresponding to Rusts `[f32; 8]` type
+    Array8f32,
+    /// A fixed-size array of 4 signed 32-bit integer values, corresponding to Rusts `[i32; 4]` type
+    Array4i32,
+    /// A fixed-size array of 8 signed 32-bit integer values, corresponding to Rusts `[i32; 8]` type
+    Array8i32,
     //TODO REFACTOR Vector types should probably be Point3 instead, or at least use nalgebra::Point3 as their underlying type!
     //TODO Instead of representing each VecN<T> type as a separate literal, might it be possible to do: Vec3(PointAttributeDataType)?
     //Not in that way of course, because of recursive datastructures, but something like that?
@@ -89,6 +117,13 @@ impl PointAttributeDataType {
             PointAttributeDataType::Vec3f32 => 12,
             PointAttributeDataType::Vec3f64 => 24,
             PointAttributeDataType::Vec4u8 => 4,
+            PointAttributeDataType::Vec4u16 => 8,
+            PointAttributeDataType::Vec4f32 => 16,
+            PointAttributeDataType::Vec4f64 => 32,
+            PointAttributeDataType::Array4f32 => 16,
+            PointAttributeDataType::Array8f32 => 32,
+            PointAttributeDataType::Array4i32 => 16,
+            PointAttributeDataType::Array8i32 => 32,
         }
     }
 
@@ -111,9 +146,168 @@ impl PointAttributeDataType {
             PointAttributeDataType::Vec3f32 => std::mem::align_of::<Vector3<f32>>(),
             PointAttributeDataType::Vec3f64 => std::mem::align_of::<Vector3<f64>>(),
             PointAttributeDataType::Vec4u8 => std::mem::align_of::<Vector4<u8>>(),
+            PointAttributeDataType::Vec4u16 => std::mem::align_of::<Vector4<u16>>(),
+            PointAttributeDataType::Vec4f32 => std::mem::align_of::<Vector4<f32>>(),
+            PointAttributeDataType::Vec4f64 => std::mem::align_of::<Vector4<f64>>(),
+            PointAttributeDataType::Array4f32 => std::mem::align_of::<[f32; 4]>(),
+            PointAttributeDataType::Array8f32 => std::mem::align_of::<[f32; 8]>(),
+            PointAttributeDataType::Array4i32 => std::mem::align_of::<[i32; 4]>(),
+            PointAttributeDataType::Array8i32 => std::mem::align_of::<[i32; 8]>(),
         };
         align as u64
     }
+
+    /// Alignment of the associated `PointAttributeDataType` following the `std430` layout rules of GLSL. Scalars are aligned
+    /// to their size, three- and four-component vectors are aligned to four times the size of their components, so a
+    /// `Vec3f32` is aligned to a 16-byte boundary, just like a `vec3` in a GLSL shader storage buffer. Arrays are aligned
+    /// like their elements, just like a `float[4]` in a GLSL shader storage buffer
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// assert_eq!(4, PointAttributeDataType::F32.std430_alignment());
+    /// assert_eq!(16, PointAttributeDataType::Vec3f32.std430_alignment());
+    /// assert_eq!(32, PointAttributeDataType::Vec3f64.std430_alignment());
+    /// assert_eq!(4, PointAttributeDataType::Array8f32.std430_alignment());
+    /// ```
+    pub fn std430_alignment(&self) -> u64 {
+        let component_alignment = self.component_type().min_alignment();
+        if self.is_array() {
+            return component_alignment;
+        }
+        match self.component_count() {
+            1 => component_alignment,
+            2 => 2 * component_alignment,
+            _ => 4 * component_alignment,
+        }
+    }
+
+    /// Number of components of the associated `PointAttributeDataType`. This is 1 for all scalar types, the
+    /// number of vector components for vector types (e.g. 3 for `Vec3f64`) and the number of elements for array types
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// assert_eq!(1, PointAttributeDataType::U16.component_count());
+    /// assert_eq!(3, PointAttributeDataType::Vec3f64.component_count());
+    /// assert_eq!(8, PointAttributeDataType::Array8f32.component_count());
+    /// ```
+    pub fn component_count(&self) -> usize {
+        match self {
+            PointAttributeDataType::Vec3u8
+            | PointAttributeDataType::Vec3u16
+            | PointAttributeDataType::Vec3f32
+            | PointAttributeDataType::Vec3f64 => 3,
+            PointAttributeDataType::Vec4u8
+            | PointAttributeDataType::Vec4u16
+            | PointAttributeDataType::Vec4f32
+            | PointAttributeDataType::Vec4f64
+            | PointAttributeDataType::Array4f32
+            | PointAttributeDataType::Array4i32 => 4,
+            PointAttributeDataType::Array8f32 | PointAttributeDataType::Array8i32 => 8,
+            _ => 1,
+        }
+    }
+
+    /// The scalar type of a single component of the associated `PointAttributeDataType`. For scalar types, this is
+    /// the type itself, for vector and array types it is the type of the components (e.g. `F64` for `Vec3f64`)
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// assert_eq!(PointAttributeDataType::U16, PointAttributeDataType::U16.component_type());
+    /// assert_eq!(PointAttributeDataType::F64, PointAttributeDataType::Vec3f64.component_type());
+    /// ```
+    pub fn component_type(&self) -> PointAttributeDataType {
+        match self {
+            PointAttributeDataType::Vec3u8 => PointAttributeDataType::U8,
+            PointAttributeDataType::Vec3u16 => PointAttributeDataType::U16,
+            PointAttributeDataType::Vec3f32 => PointAttributeDataType::F32,
+            PointAttributeDataType::Vec3f64 => PointAttributeDataType::F64,
+            PointAttributeDataType::Vec4u8 => PointAttributeDataType::U8,
+            PointAttributeDataType::Vec4u16 => PointAttributeDataType::U16,
+            PointAttributeDataType::Vec4f32 => PointAttributeDataType::F32,
+            PointAttributeDataType::Vec4f64 => PointAttributeDataType::F64,
+            PointAttributeDataType::Array4f32 | PointAttributeDataType::Array8f32 => {
+                PointAttributeDataType::F32
+            }
+            PointAttributeDataType::Array4i32 | PointAttributeDataType::Array8i32 => {
+                PointAttributeDataType::I32
+            }
+            other => *other,
+        }
+    }
+
+    /// Is the associated `PointAttributeDataType` a vector type?
+    pub fn is_vector(&self) -> bool {
+        self.component_count() > 1 && !self.is_array()
+    }
+
+    /// Is the associated `PointAttributeDataType` a fixed-size array type?
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// assert!(PointAttributeDataType::Array4f32.is_array());
+    /// assert!(!PointAttributeDataType::Vec3f32.is_array());
+    /// ```
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            PointAttributeDataType::Array4f32
+                | PointAttributeDataType::Array8f32
+                | PointAttributeDataType::Array4i32
+                | PointAttributeDataType::Array8i32
+        )
+    }
+
+    /// Converts the values of the associated `PointAttributeDataType` in `data` from the byte order `from` to the
+    /// byte order `to`, in-place. `data` may contain any number of consecutive values. The bytes of each component of
+    /// a vector type are swapped individually, so a `Vec3f32` in big-endian order becomes three little-endian `f32`
+    /// values and not a reversed vector. Use this together with [Endianness::NATIVE] to read values from file formats
+    /// that store data in a foreign byte order
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// let mut data = [0x12, 0x34, 0x56, 0x78];
+    /// PointAttributeDataType::U16.convert_endianness(&mut data, Endianness::Big, Endianness::Little);
+    /// assert_eq!([0x34, 0x12, 0x78, 0x56], data);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the length of `data` is not a multiple of the size of the associated `PointAttributeDataType`
+    pub fn convert_endianness(&self, data: &mut [u8], from: Endianness, to: Endianness) {
+        if data.len() % self.size() as usize != 0 {
+            panic!(
+                "Length of data ({}) is not a multiple of the size of datatype {}",
+                data.len(),
+                self
+            );
+        }
+        let component_size = self.component_type().size() as usize;
+        if from == to || component_size == 1 {
+            return;
+        }
+        for component in data.chunks_exact_mut(component_size) {
+            component.reverse();
+        }
+    }
+}
+
+/// Byte order of multi-byte values, e.g. the components of a point attribute
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+impl Endianness {
+    /// The byte order of the platform that pasture is compiled for. All point buffers store their attributes in this
+    /// byte order
+    pub const NATIVE: Endianness = if cfg!(target_endian = "big") {
+        Endianness::Big
+    } else {
+        Endianness::Little
+    };
 }
 
 impl Display for PointAttributeDataType {
@@ -135,6 +329,13 @@ impl Display for PointAttributeDataType {
             PointAttributeDataType::Vec3f32 => write!(f, "Vec3<f32>"),
             PointAttributeDataType::Vec3f64 => write!(f, "Vec3<f64>"),
             &PointAttributeDataType::Vec4u8 => write!(f, "Vec4<u8>"),
+            PointAttributeDataType::Vec4u16 => write!(f, "Vec4<u16>"),
+            PointAttributeDataType::Vec4f32 => write!(f, "Vec4<f32>"),
+            PointAttributeDataType::Vec4f64 => write!(f, "Vec4<f64>"),
+            PointAttributeDataType::Array4f32 => write!(f, "[f32; 4]"),
+            PointAttributeDataType::Array8f32 => write!(f, "[f32; 8]"),
+            PointAttributeDataType::Array4i32 => write!(f, "[i32; 4]"),
+            PointAttributeDataType::Array8i32 => write!(f, "[i32; 8]"),
         }
     }
 }
@@ -227,6 +428,41 @@ impl PrimitiveType for Vector4<u8> {
         PointAttributeDataType::Vec4u8
     }
 }
+impl PrimitiveType for Vector4<u16> {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Vec4u16
+    }
+}
+impl PrimitiveType for Vector4<f32> {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Vec4f32
+    }
+}
+impl PrimitiveType for Vector4<f64> {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Vec4f64
+    }
+}
+impl PrimitiveType for [f32; 4] {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Array4f32
+    }
+}
+impl PrimitiveType for [f32; 8] {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Array8f32
+    }
+}
+impl PrimitiveType for [i32; 4] {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Array4i32
+    }
+}
+impl PrimitiveType for [i32; 8] {
+    fn data_type() -> PointAttributeDataType {
+        PointAttributeDataType::Array8i32
+    }
+}
 
 // Assert sizes of vector types are as we expect. Primitive types always are the same size, but we don't know
 // what nalgebra does with the Vector3 types on the target machine...
@@ -235,15 +471,24 @@ const_assert!(std::mem::size_of::<Vector3<u16>>() == 6);
 const_assert!(std::mem::size_of::<Vector3<f32>>() == 12);
 const_assert!(std::mem::size_of::<Vector3<f64>>() == 24);
 const_assert!(std::mem::size_of::<Vector4<u8>>() == 4);
+const_assert!(std::mem::size_of::<Vector4<u16>>() == 8);
+const_assert!(std::mem::size_of::<Vector4<f32>>() == 16);
+const_assert!(std::mem::size_of::<Vector4<f64>>() == 32);
 
 /// A definition for a single point attribute of a point cloud. Point attributes are things like the position,
 /// GPS time, intensity etc. In Pasture, attributes are identified by a unique name together with the data type
 /// that a single record of the attribute is stored in. Attributes can be grouped into two categories: Built-in
 /// attributes (e.g. POSITION_3D, INTENSITY, GPS_TIME etc.) and custom attributes.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Attributes with an integer datatype can additionally be marked as [normalized](Self::as_normalized), in which case
+/// their values are interpreted as fixed-point numbers in the range `[0;1]` (or `[-1;1]` for signed types) when they are
+/// converted into floating-point datatypes. Normalization does not take part in the identity of an attribute, so it is
+/// ignored when comparing attributes.
+#[derive(Debug, Clone)]
 pub struct PointAttributeDefinition {
     name: &'static str,
     datatype: PointAttributeDataType,
+    normalized: bool,
 }
 
 impl PointAttributeDefinition {
@@ -255,7 +500,35 @@ impl PointAttributeDefinition {
     /// # assert_eq!(custom_attribute.datatype(), PointAttributeDataType::F32);
     /// ```
     pub const fn custom(name: &'static str, datatype: PointAttributeDataType) -> Self {
-        Self { name, datatype }
+        Self {
+            name,
+            datatype,
+            normalized: false,
+        }
+    }
+
+    /// Creates a new custom PointAttributeDefinition with a name that is only known at runtime, e.g. the name of a
+    /// custom attribute that is read from a file. Since attribute names are `&'static str`, the name is interned:
+    /// Each distinct name is allocated once and lives until the end of the program
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// let name = String::from("Temperature");
+    /// let custom_attribute = PointAttributeDefinition::custom_dynamic(&name, PointAttributeDataType::F32);
+    /// # assert_eq!(custom_attribute.name(), "Temperature");
+    /// # assert_eq!(custom_attribute.datatype(), PointAttributeDataType::F32);
+    /// ```
+    pub fn custom_dynamic(name: &str, datatype: PointAttributeDataType) -> Self {
+        static INTERNED_NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+        let mut interned_names = INTERNED_NAMES.lock().unwrap();
+        let interned_name = match interned_names.get(name) {
+            Some(interned_name) => *interned_name,
+            None => {
+                let interned_name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+                interned_names.insert(interned_name);
+                interned_name
+            }
+        };
+        Self::custom(interned_name, datatype)
     }
 
     /// Returns the name of this PointAttributeDefinition
@@ -285,6 +558,37 @@ impl PointAttributeDefinition {
         self.datatype.size()
     }
 
+    /// Returns true if the values of this PointAttributeDefinition are normalized
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// assert!(!attributes::COLOR_RGB.is_normalized());
+    /// assert!(attributes::COLOR_RGB.as_normalized().is_normalized());
+    /// ```
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
+    /// Returns a new PointAttributeDefinition based on this PointAttributeDefinition whose values are normalized, i.e.
+    /// interpreted as values in the range `[0;1]` (or `[-1;1]` for signed datatypes) when they are converted into a
+    /// floating-point datatype. A `Vector3<u8>` color of `(255, 0, 51)` thus becomes `(1.0, 0.0, 0.2)`, and converting
+    /// floating-point values back into a normalized attribute clamps and scales them to the full range of the integer type.
+    /// Normalization has no effect for attributes with floating-point datatypes
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// let normalized_color = attributes::COLOR_RGB
+    ///     .with_custom_datatype(PointAttributeDataType::Vec3u8)
+    ///     .as_normalized();
+    /// # assert!(normalized_color.is_normalized());
+    /// # assert_eq!(normalized_color.datatype(), PointAttributeDataType::Vec3u8);
+    /// ```
+    pub fn as_normalized(&self) -> Self {
+        Self {
+            name: self.name,
+            datatype: self.datatype,
+            normalized: true,
+        }
+    }
+
     /// Returns a new PointAttributeDefinition based on this PointAttributeDefinition, but with a different datatype
     /// ```
     /// # use pasture_core::layout::*;
@@ -296,6 +600,7 @@ impl PointAttributeDefinition {
         Self {
             name: self.name,
             datatype: new_datatype,
+            normalized: self.normalized,
         }
     }
 
@@ -314,10 +619,26 @@ impl PointAttributeDefinition {
             datatype: self.datatype,
             name: self.name,
             offset,
+            normalized: self.normalized,
         }
     }
 }
 
+impl PartialEq for PointAttributeDefinition {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.datatype == other.datatype
+    }
+}
+
+impl Eq for PointAttributeDefinition {}
+
+impl Hash for PointAttributeDefinition {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.datatype.hash(state);
+    }
+}
+
 impl Display for PointAttributeDefinition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[{};{}]", self.name, self.datatype)
@@ -329,6 +650,7 @@ impl From<PointAttributeMember> for PointAttributeDefinition {
         Self {
             datatype: attribute.datatype,
             name: attribute.name,
+            normalized: attribute.normalized,
         }
     }
 }
@@ -338,6 +660,7 @@ impl From<&PointAttributeMember> for PointAttributeDefinition {
         Self {
             datatype: attribute.datatype,
             name: attribute.name,
+            normalized: attribute.normalized,
         }
     }
 }
@@ -349,6 +672,7 @@ pub struct PointAttributeMember {
     name: &'static str,
     datatype: PointAttributeDataType,
     offset: u64,
+    normalized: bool,
 }
 
 impl PointAttributeMember {
@@ -365,6 +689,7 @@ impl PointAttributeMember {
             name,
             datatype,
             offset,
+            normalized: false,
         }
     }
 
@@ -401,6 +726,17 @@ impl PointAttributeMember {
         self.offset
     }
 
+    /// Returns true if the values of the associated `PointAttributeMember` are normalized. See
+    /// [PointAttributeDefinition::as_normalized] for details
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// let custom_attribute = PointAttributeDefinition::custom("Custom", PointAttributeDataType::U8).as_normalized();
+    /// assert!(custom_attribute.at_offset_in_type(0).is_normalized());
+    /// ```
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
     /// Returns the size in bytes of the associated `PointAttributeMember`
     pub fn size(&self) -> u64 {
         match self.datatype {
@@ -420,6 +756,13 @@ impl PointAttributeMember {
             PointAttributeDataType::Vec3u16 => 3 * 2,
             PointAttributeDataType::Vec3u8 => 3,
             PointAttributeDataType::Vec4u8 => 4,
+            PointAttributeDataType::Vec4u16 => 8,
+            PointAttributeDataType::Vec4f32 => 16,
+            PointAttributeDataType::Vec4f64 => 32,
+            PointAttributeDataType::Array4f32 => 16,
+            PointAttributeDataType::Array8f32 => 32,
+            PointAttributeDataType::Array4i32 => 16,
+            PointAttributeDataType::Array8i32 => 32,
         }
     }
 }
@@ -450,90 +793,113 @@ pub mod attributes {
     pub const POSITION_3D: PointAttributeDefinition = PointAttributeDefinition {
         name: "Position3D",
         datatype: PointAttributeDataType::Vec3f64,
+        normalized: false,
     };
 
     /// Attribute definition for an intensity value. Default datatype is U16
     pub const INTENSITY: PointAttributeDefinition = PointAttributeDefinition {
         name: "Intensity",
         datatype: PointAttributeDataType::U16,
+        normalized: false,
     };
 
     /// Attribute definition for a return number. Default datatype is U8
     pub const RETURN_NUMBER: PointAttributeDefinition = PointAttributeDefinition {
         name: "ReturnNumber",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for the number of returns. Default datatype is U8
     pub const NUMBER_OF_RETURNS: PointAttributeDefinition = PointAttributeDefinition {
         name: "NumberOfReturns",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for the classification flags. Default datatype is U8
     pub const CLASSIFICATION_FLAGS: PointAttributeDefinition = PointAttributeDefinition {
         name: "ClassificationFlags",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for the scanner channel. Default datatype is U8
     pub const SCANNER_CHANNEL: PointAttributeDefinition = PointAttributeDefinition {
         name: "ScannerChannel",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for a scan direction flag. Default datatype is Bool
     pub const SCAN_DIRECTION_FLAG: PointAttributeDefinition = PointAttributeDefinition {
         name: "ScanDirectionFlag",
         datatype: PointAttributeDataType::Bool,
+        normalized: false,
     };
 
     /// Attribute definition for an edge of flight line flag. Default datatype is Bool
     pub const EDGE_OF_FLIGHT_LINE: PointAttributeDefinition = PointAttributeDefinition {
         name: "EdgeOfFlightLine",
         datatype: PointAttributeDataType::Bool,
+        normalized: false,
     };
 
     /// Attribute definition for a classification. Default datatype is U8
     pub const CLASSIFICATION: PointAttributeDefinition = PointAttributeDefinition {
         name: "Classification",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for a scan angle rank. Default datatype is I8
     pub const SCAN_ANGLE_RANK: PointAttributeDefinition = PointAttributeDefinition {
         name: "ScanAngleRank",
         datatype: PointAttributeDataType::I8,
+        normalized: false,
     };
 
     /// Attribute definition for a scan angle with extended precision (like in LAS format 1.4). Default datatype is I16
     pub const SCAN_ANGLE: PointAttributeDefinition = PointAttributeDefinition {
         name: "ScanAngle",
         datatype: PointAttributeDataType::I16,
+        normalized: false,
     };
 
     /// Attribute definition for a user data field. Default datatype is U8
     pub const USER_DATA: PointAttributeDefinition = PointAttributeDefinition {
         name: "UserData",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for a point source ID. Default datatype is U16
     pub const POINT_SOURCE_ID: PointAttributeDefinition = PointAttributeDefinition {
         name: "PointSourceID",
         datatype: PointAttributeDataType::U16,
+        normalized: false,
     };
 
     /// Attribute definition for an RGB color. Default datatype is Vec3u16
     pub const COLOR_RGB: PointAttributeDefinition = PointAttributeDefinition {
         name: "ColorRGB",
         datatype: PointAttributeDataType::Vec3u16,
+        normalized: false,
+    };
+
+    /// Attribute definition for an RGBA color with an alpha channel. Default datatype is Vec4u8. Can be converted
+    /// from and to [COLOR_RGB], in which case the alpha channel is defaulted to 255 or dropped, respectively
+    pub const COLOR_RGBA: PointAttributeDefinition = PointAttributeDefinition {
+        name: "ColorRGBA",
+        datatype: PointAttributeDataType::Vec4u8,
+        normalized: false,
     };
 
     /// Attribute definition for a GPS timestamp. Default datatype is F64
     pub const GPS_TIME: PointAttributeDefinition = PointAttributeDefinition {
         name: "GpsTime",
         datatype: PointAttributeDataType::F64,
+        normalized: false,
     };
 
     /// Attribute definition for near-infrared records (NIR). Default datatype is U16
@@ -542,48 +908,56 @@ pub mod attributes {
     pub const NIR: PointAttributeDefinition = PointAttributeDefinition {
         name: "NIR",
         datatype: PointAttributeDataType::U16,
+        normalized: false,
     };
 
     /// Attribute definition for the wave packet descriptor index in the LAS format. Default datatype is U8
     pub const WAVE_PACKET_DESCRIPTOR_INDEX: PointAttributeDefinition = PointAttributeDefinition {
         name: "WavePacketDescriptorIndex",
         datatype: PointAttributeDataType::U8,
+        normalized: false,
     };
 
     /// Attribute definition for the offset to the waveform data in the LAS format. Default datatype is U64
     pub const WAVEFORM_DATA_OFFSET: PointAttributeDefinition = PointAttributeDefinition {
         name: "WaveformDataOffset",
         datatype: PointAttributeDataType::U64,
+        normalized: false,
     };
 
     /// Attribute definition for the size of a waveform data packet in the LAS format. Default datatype is U32
     pub const WAVEFORM_PACKET_SIZE: PointAttributeDefinition = PointAttributeDefinition {
         name: "WaveformPacketSize",
         datatype: PointAttributeDataType::U32,
+        normalized: false,
     };
 
     /// Attribute definition for the return point waveform location in the LAS format. Default datatype is F32
     pub const RETURN_POINT_WAVEFORM_LOCATION: PointAttributeDefinition = PointAttributeDefinition {
         name: "ReturnPointWaveformLocation",
         datatype: PointAttributeDataType::F32,
+        normalized: false,
     };
 
     /// Attribute definition for the waveform parameters in the LAS format. Default datatype is Vector3<f32>
     pub const WAVEFORM_PARAMETERS: PointAttributeDefinition = PointAttributeDefinition {
         name: "WaveformParameters",
         datatype: PointAttributeDataType::Vec3f32,
+        normalized: false,
     };
 
     /// Attribute definition for a point ID. Default datatype is U64
     pub const POINT_ID: PointAttributeDefinition = PointAttributeDefinition {
         name: "PointID",
         datatype: PointAttributeDataType::U64,
+        normalized: false,
     };
 
     /// Attribute definition for a 3D point normal. Default datatype is Vec3f32
     pub const NORMAL: PointAttributeDefinition = PointAttributeDefinition {
         name: "Normal",
         datatype: PointAttributeDataType::Vec3f32,
+        normalized: false,
     };
 }
 
@@ -593,6 +967,10 @@ pub enum FieldAlignment {
     Default,
     /// Use alignment as if the type is [`#[repr(packed(N))]`](https://doc.rust-lang.org/reference/type-layout.html#the-alignment-modifiers)
     Packed(u64),
+    /// Use alignment as if the type is a struct inside a GLSL shader storage buffer with `std430` layout.
+    /// This aligns three-component vectors like four-component vectors (see [PointAttributeDataType::std430_alignment]), so
+    /// the attribute offsets of the `PointLayout` match the offsets of the corresponding struct on the shader side
+    Std430,
 }
 
 /// Describes the data layout of a single point in a point cloud
@@ -693,6 +1071,34 @@ impl PointLayout {
         layout
     }
 
+    /// Creates a new PointLayout from the given sequence of attributes. The attributes will be aligned using the `std430`
+    /// layout rules of GLSL (see [FieldAlignment::Std430]), which makes the resulting `PointLayout` suitable for structs
+    /// inside shader storage buffers
+    ///
+    /// #Panics
+    ///
+    /// If any two attributes within the sequence share the same attribute name.
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// // struct { float intensity; vec3 normal; float gps_time; } in std430 layout
+    /// let layout = PointLayout::from_attributes_std430(&[
+    ///     attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::F32),
+    ///     attributes::NORMAL,
+    ///     attributes::GPS_TIME.with_custom_datatype(PointAttributeDataType::F32),
+    /// ]);
+    /// assert_eq!(16, layout.at(1).offset());
+    /// assert_eq!(28, layout.at(2).offset());
+    /// assert_eq!(32, layout.size_of_point_entry());
+    /// ```
+    pub fn from_attributes_std430(attributes: &[PointAttributeDefinition]) -> Self {
+        let mut layout = Self::default();
+        for attribute in attributes {
+            layout.add_attribute(attribute.clone(), FieldAlignment::Std430);
+        }
+        layout
+    }
+
     /// Creates a new PointLayout from the given `PointAttributeMember` sequence as well as the given `type_alignment`.
     ///
     /// #Panics
@@ -784,6 +1190,7 @@ impl PointLayout {
             FieldAlignment::Packed(max_alignment) => {
                 std::cmp::min(max_alignment, point_attribute.datatype().min_alignment())
             }
+            FieldAlignment::Std430 => point_attribute.datatype().std430_alignment(),
         };
         let offset = self
             .packed_offset_of_next_field()
@@ -798,6 +1205,10 @@ impl PointLayout {
             FieldAlignment::Packed(max_alignment) => {
                 std::cmp::min(max_alignment, current_max_alignment)
             }
+            FieldAlignment::Std430 => std::cmp::max(
+                current_max_alignment,
+                point_attribute.datatype().std430_alignment(),
+            ),
         };
 
         self.attributes
@@ -813,6 +1224,42 @@ impl PointLayout {
         .expect("Could not create memory layout for PointLayout");
     }
 
+    /// Renames the attribute with the name `old_name` in the associated `PointLayout` to `new_name`. The datatype and offset of
+    /// the attribute are not changed. This is useful to map attributes of foreign file formats onto the builtin attributes of
+    /// pasture, e.g. a `scalar_Classification` attribute onto [CLASSIFICATION](attributes::CLASSIFICATION).
+    ///
+    /// ```
+    /// # use pasture_core::layout::*;
+    /// let mut layout = PointLayout::from_attributes(&[PointAttributeDefinition::custom("scalar_Classification", PointAttributeDataType::U8)]);
+    /// layout.rename_attribute("scalar_Classification", attributes::CLASSIFICATION.name()).unwrap();
+    /// assert!(layout.has_attribute(&attributes::CLASSIFICATION));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If there is no attribute named `old_name` in the associated `PointLayout`, or if an attribute named `new_name` already
+    /// exists in the associated `PointLayout`
+    pub fn rename_attribute(&mut self, old_name: &str, new_name: &'static str) -> Result<()> {
+        if self.has_attribute_with_name(new_name) {
+            bail!(
+                "Can't rename attribute {} to {} because an attribute with this name is already present in this PointLayout",
+                old_name,
+                new_name
+            );
+        }
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.name() == old_name)
+        {
+            Some(attribute) => {
+                attribute.name = new_name;
+                Ok(())
+            }
+            None => bail!("Attribute {} is not part of this PointLayout", old_name),
+        }
+    }
+
     /// Returns true if an attribute with the given name is part of this PointLayout.
     /// ```
     /// # use pasture_core::layout::*;
@@ -1017,9 +1464,14 @@ impl Default for PointLayout {
 #[cfg(test)]
 mod tests {
     use crate::layout::{
-        attributes::{COLOR_RGB, INTENSITY, POSITION_3D},
+        attributes::{
+            CLASSIFICATION, COLOR_RGB, GPS_TIME, INTENSITY, NORMAL, POSITION_3D, SCAN_ANGLE,
+        },
+        conversion::RawPointConverter,
         PointType,
     };
+    use crate::util::view_raw_bytes;
+    use std::convert::TryInto;
 
     use super::*;
     use pasture_derive::PointType;
@@ -1048,4 +1500,233 @@ mod tests {
 
         assert_eq!(expected_layout_1, TestPoint1::layout());
     }
+
+    #[derive(Debug, PointType, Copy, Clone, PartialEq)]
+    #[repr(C, packed)]
+    struct TestPointNormalized {
+        #[pasture(BUILTIN_COLOR_RGB, normalized)]
+        color: Vector3<u8>,
+        #[pasture(attribute = "Quality", normalized)]
+        quality: u16,
+        #[pasture(BUILTIN_INTENSITY)]
+        intensity: u16,
+    }
+
+    // The derive macro does not know about align(N), so the derived layout is too small for this type
+    #[derive(Debug, PointType, Copy, Clone, PartialEq)]
+    #[repr(C, align(16))]
+    struct TestPointOveraligned {
+        #[pasture(BUILTIN_INTENSITY)]
+        intensity: u16,
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match the size of TestPointOveraligned in memory")]
+    fn test_derive_point_type_with_mismatched_repr_is_rejected() {
+        TestPointOveraligned::layout();
+    }
+
+    #[test]
+    fn test_derive_normalized_point_type() {
+        let layout = TestPointNormalized::layout();
+        assert!(layout
+            .get_attribute_by_name(COLOR_RGB.name())
+            .unwrap()
+            .is_normalized());
+        assert!(layout
+            .get_attribute_by_name("Quality")
+            .unwrap()
+            .is_normalized());
+        assert!(!layout
+            .get_attribute_by_name(INTENSITY.name())
+            .unwrap()
+            .is_normalized());
+        // Normalization does not change the identity of the attribute
+        assert!(
+            layout.has_attribute(&COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8))
+        );
+
+        let target_layout = PointLayout::from_attributes_packed(
+            &[
+                COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3f32),
+                PointAttributeDefinition::custom("Quality", PointAttributeDataType::F64),
+            ],
+            1,
+        );
+        let converter = RawPointConverter::from_to(&layout, &target_layout);
+        let point = TestPointNormalized {
+            color: Vector3::new(255, 0, 51),
+            quality: u16::MAX,
+            intensity: 42,
+        };
+        let mut target_point = vec![0; target_layout.size_of_point_entry() as usize];
+        unsafe {
+            converter.convert(view_raw_bytes(&point), &mut target_point);
+        }
+        let color = Vector3::<f32>::new(
+            f32::from_ne_bytes(target_point[0..4].try_into().unwrap()),
+            f32::from_ne_bytes(target_point[4..8].try_into().unwrap()),
+            f32::from_ne_bytes(target_point[8..12].try_into().unwrap()),
+        );
+        assert_eq!(Vector3::new(1.0, 0.0, 0.2), color);
+        let quality = f64::from_ne_bytes(target_point[12..20].try_into().unwrap());
+        assert_eq!(1.0, quality);
+    }
+
+    #[test]
+    fn test_std430_layout() {
+        // Matches the following struct in a GLSL shader storage buffer with std430 layout:
+        //
+        // struct Point {
+        //     uint intensity;   // offset 0
+        //     dvec3 position;   // offset 32 (dvec3 is aligned like dvec4)
+        //     vec3 normal;      // offset 64 (vec3 is aligned like vec4)
+        //     float gps_time;   // offset 76 (fills the gap after vec3)
+        //     double scan_angle; // offset 80
+        // };                    // size 96 (rounded up to the struct alignment of 32)
+        let layout = PointLayout::from_attributes_std430(&[
+            INTENSITY.with_custom_datatype(PointAttributeDataType::U32),
+            POSITION_3D,
+            NORMAL,
+            GPS_TIME.with_custom_datatype(PointAttributeDataType::F32),
+            SCAN_ANGLE.with_custom_datatype(PointAttributeDataType::F64),
+        ]);
+
+        let offsets = layout.attributes().map(|a| a.offset()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 32, 64, 76, 80], offsets);
+        assert_eq!(96, layout.size_of_point_entry());
+    }
+
+    #[test]
+    fn test_rename_attribute() {
+        let mut layout = PointLayout::from_attributes(&[
+            POSITION_3D,
+            PointAttributeDefinition::custom("scalar_Classification", PointAttributeDataType::U8),
+        ]);
+        let old_member = layout.at(1).clone();
+
+        layout
+            .rename_attribute("scalar_Classification", CLASSIFICATION.name())
+            .expect("Renaming failed");
+
+        assert!(layout.has_attribute(&CLASSIFICATION));
+        assert!(!layout.has_attribute_with_name("scalar_Classification"));
+        let new_member = layout.get_attribute(&CLASSIFICATION).unwrap();
+        assert_eq!(old_member.offset(), new_member.offset());
+        assert_eq!(old_member.datatype(), new_member.datatype());
+    }
+
+    #[test]
+    fn test_rename_attribute_to_existing_name_fails() {
+        let mut layout = PointLayout::from_attributes(&[POSITION_3D, INTENSITY]);
+        assert!(layout
+            .rename_attribute(INTENSITY.name(), POSITION_3D.name())
+            .is_err());
+        assert!(layout.rename_attribute("Unknown", "Other").is_err());
+        assert_eq!(
+            PointLayout::from_attributes(&[POSITION_3D, INTENSITY]),
+            layout
+        );
+    }
+
+    #[test]
+    fn test_datatype_components() {
+        use PointAttributeDataType::*;
+
+        let expected = [
+            (U8, 1, U8),
+            (I8, 1, I8),
+            (U16, 1, U16),
+            (I16, 1, I16),
+            (U32, 1, U32),
+            (I32, 1, I32),
+            (U64, 1, U64),
+            (I64, 1, I64),
+            (F32, 1, F32),
+            (F64, 1, F64),
+            (Bool, 1, Bool),
+            (Vec3u8, 3, U8),
+            (Vec3u16, 3, U16),
+            (Vec3f32, 3, F32),
+            (Vec3f64, 3, F64),
+            (Vec4u8, 4, U8),
+            (Vec4u16, 4, U16),
+            (Vec4f32, 4, F32),
+            (Vec4f64, 4, F64),
+            (Array4f32, 4, F32),
+            (Array8f32, 8, F32),
+            (Array4i32, 4, I32),
+            (Array8i32, 8, I32),
+        ];
+
+        for (datatype, component_count, component_type) in expected.iter() {
+            assert_eq!(*component_count, datatype.component_count(), "{}", datatype);
+            assert_eq!(*component_type, datatype.component_type(), "{}", datatype);
+            assert_eq!(
+                *component_count > 1 && !datatype.is_array(),
+                datatype.is_vector(),
+                "{}",
+                datatype
+            );
+            assert_eq!(
+                datatype.size(),
+                component_type.size() * *component_count as u64,
+                "{}",
+                datatype
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_dynamic_interns_names() {
+        let first = PointAttributeDefinition::custom_dynamic(
+            &String::from("DynamicName"),
+            PointAttributeDataType::U8,
+        );
+        let second = PointAttributeDefinition::custom_dynamic(
+            &String::from("DynamicName"),
+            PointAttributeDataType::F64,
+        );
+        assert_eq!("DynamicName", first.name());
+        assert!(std::ptr::eq(first.name(), second.name()));
+        assert_eq!(PointAttributeDataType::F64, second.datatype());
+    }
+
+    #[test]
+    fn test_convert_endianness_respects_components() {
+        let expected = Vector3::new(1.5_f32, -2.25, 1024.0);
+        let mut data = expected
+            .iter()
+            .flat_map(|v| v.to_be_bytes().to_vec())
+            .collect::<Vec<_>>();
+        PointAttributeDataType::Vec3f32.convert_endianness(
+            &mut data,
+            Endianness::Big,
+            Endianness::NATIVE,
+        );
+        let actual = data
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect::<Vec<_>>();
+        assert_eq!(expected.iter().copied().collect::<Vec<_>>(), actual);
+
+        let mut bytes = [1, 2, 3, 4];
+        PointAttributeDataType::Vec4u8.convert_endianness(
+            &mut bytes,
+            Endianness::Big,
+            Endianness::Little,
+        );
+        assert_eq!([1, 2, 3, 4], bytes);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_convert_endianness_with_partial_value() {
+        let mut data = [0; 5];
+        PointAttributeDataType::U32.convert_endianness(
+            &mut data,
+            Endianness::Big,
+            Endianness::Little,
+        );
+    }
 }
@@ -7,6 +7,8 @@
 //! For understanding Pasture, it is best to look at the [PointLayout](crate::layout::PointLayout) type and the [containers](crate::containers) module.
 
 pub extern crate nalgebra;
+#[cfg(feature = "gpu")]
+pub extern crate wgpu;
 extern crate self as pasture_core;
 
 pub mod containers;
@@ -20,3 +22,6 @@ pub mod meta;
 pub mod util;
 #[cfg(feature = "gpu")]
 pub mod gpu;
+/// Utilities for generating point data for tests and benchmarks
+#[cfg(feature = "testutil")]
+pub mod testutil;
@@ -12,3 +12,6 @@ pub use self::arithmetic::*;
 
 mod minmax;
 pub use self::minmax::*;
+
+mod raster;
+pub use self::raster::*;
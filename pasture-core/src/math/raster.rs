@@ -0,0 +1,101 @@
+use nalgebra::Vector2;
+
+/// Value that marks cells of a `Raster<f32>` that contain no data
+pub const RASTER_NODATA: f32 = -9999.0;
+
+/// A regular 2D grid of values in the XY plane, e.g. a digital elevation model. The raster is axis-aligned and all cells
+/// are squares of size `cell_size`. Following the convention of GIS applications, the `origin` is the upper left corner
+/// of the raster (i.e. the minimum x and maximum y coordinate) and rows go from north to south (decreasing y), so the
+/// cell at `(column, row)` covers `[origin.x + column * cell_size; origin.x + (column + 1) * cell_size)` in x and
+/// `(origin.y - (row + 1) * cell_size; origin.y - row * cell_size]` in y.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster<T> {
+    /// Upper left corner of the raster
+    pub origin: Vector2<f64>,
+    /// Size of a single (square) cell
+    pub cell_size: f64,
+    /// Number of columns
+    pub width: usize,
+    /// Number of rows
+    pub height: usize,
+    /// The values of all cells in row-major order, starting with the upper left cell
+    pub values: Vec<T>,
+    /// Value that marks cells that contain no data
+    pub nodata: T,
+}
+
+impl<T: Clone> Raster<T> {
+    /// Creates a new `Raster` with the given dimensions where all cells are set to `nodata`
+    /// ```
+    /// # use pasture_core::math::Raster;
+    /// # use nalgebra::Vector2;
+    /// let raster = Raster::new(Vector2::new(0.0, 10.0), 1.0, 4, 2, -1.0);
+    /// assert_eq!(8, raster.values.len());
+    /// assert!(raster.values.iter().all(|v| *v == -1.0));
+    /// ```
+    pub fn new(
+        origin: Vector2<f64>,
+        cell_size: f64,
+        width: usize,
+        height: usize,
+        nodata: T,
+    ) -> Self {
+        Self {
+            origin,
+            cell_size,
+            width,
+            height,
+            values: vec![nodata.clone(); width * height],
+            nodata,
+        }
+    }
+}
+
+impl<T> Raster<T> {
+    /// Returns the value of the cell at `column` and `row`, or `None` if the cell is outside of this raster
+    pub fn get(&self, column: usize, row: usize) -> Option<&T> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        self.values.get(row * self.width + column)
+    }
+
+    /// Returns a mutable reference to the value of the cell at `column` and `row`, or `None` if the cell is outside of
+    /// this raster
+    pub fn get_mut(&mut self, column: usize, row: usize) -> Option<&mut T> {
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        self.values.get_mut(row * self.width + column)
+    }
+
+    /// Returns the column and row of the cell that contains the given XY `position`, or `None` if the position is
+    /// outside of this raster
+    /// ```
+    /// # use pasture_core::math::Raster;
+    /// # use nalgebra::Vector2;
+    /// let raster = Raster::new(Vector2::new(0.0, 10.0), 2.0, 4, 5, 0.0);
+    /// assert_eq!(Some((1, 0)), raster.cell_at(&Vector2::new(3.0, 9.0)));
+    /// assert_eq!(None, raster.cell_at(&Vector2::new(-1.0, 9.0)));
+    /// ```
+    pub fn cell_at(&self, position: &Vector2<f64>) -> Option<(usize, usize)> {
+        let column = ((position.x - self.origin.x) / self.cell_size).floor();
+        let row = ((self.origin.y - position.y) / self.cell_size).floor();
+        if column < 0.0 || row < 0.0 {
+            return None;
+        }
+        let (column, row) = (column as usize, row as usize);
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        Some((column, row))
+    }
+
+    /// Returns the XY position of the center of the cell at `column` and `row`
+    pub fn cell_center(&self, column: usize, row: usize) -> Vector2<f64> {
+        Vector2::new(
+            self.origin.x + (column as f64 + 0.5) * self.cell_size,
+            self.origin.y - (row as f64 + 0.5) * self.cell_size,
+        )
+    }
+}
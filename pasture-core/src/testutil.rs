@@ -0,0 +1,189 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    containers::{PerAttributePointBufferMut, PerAttributeVecPointStorage, PointBufferWriteable},
+    layout::{attributes, PointAttributeDataType, PointAttributeDefinition, PointLayout},
+};
+
+/// Extent of the axis-aligned box in which [random_point_buffer] generates positions. The box starts at the origin
+pub const RANDOM_POSITIONS_EXTENT: f64 = 1000.0;
+
+/// Range of the random values for a single point attribute
+#[derive(Copy, Clone, Debug)]
+enum ValueRange {
+    /// Integer values in the inclusive range `[min; max]`
+    Integer(i64, i64),
+    /// Floating-point values in the half-open range `[min; max)`
+    Float(f64, f64),
+    /// Values that cover the whole range of the datatype of the attribute
+    Full,
+}
+
+/// Returns a sensible range of values for the given attribute, based on what values this attribute usually has in real
+/// point cloud data
+fn value_range_for(attribute: &PointAttributeDefinition) -> ValueRange {
+    match attribute.name() {
+        name if name == attributes::POSITION_3D.name() => {
+            ValueRange::Float(0.0, RANDOM_POSITIONS_EXTENT)
+        }
+        name if name == attributes::CLASSIFICATION.name() => ValueRange::Integer(0, 31),
+        name if name == attributes::RETURN_NUMBER.name()
+            || name == attributes::NUMBER_OF_RETURNS.name() =>
+        {
+            ValueRange::Integer(1, 5)
+        }
+        name if name == attributes::CLASSIFICATION_FLAGS.name() => ValueRange::Integer(0, 15),
+        name if name == attributes::SCANNER_CHANNEL.name() => ValueRange::Integer(0, 3),
+        name if name == attributes::SCAN_ANGLE_RANK.name() => ValueRange::Integer(-90, 90),
+        name if name == attributes::GPS_TIME.name() => ValueRange::Float(0.0, 1_000_000.0),
+        name if name == attributes::NORMAL.name() => ValueRange::Float(-1.0, 1.0),
+        _ => ValueRange::Full,
+    }
+}
+
+/// Writes a single random value of type `component_type` within `range` into `bytes`
+fn write_random_component<R: Rng>(
+    component_type: PointAttributeDataType,
+    range: ValueRange,
+    bytes: &mut [u8],
+    rng: &mut R,
+) {
+    let value = match range {
+        ValueRange::Integer(min, max) => rng.gen_range(min..=max) as f64,
+        ValueRange::Float(min, max) => rng.gen_range(min..max),
+        ValueRange::Full => match component_type {
+            PointAttributeDataType::F32 | PointAttributeDataType::F64 => rng.gen::<f64>(),
+            PointAttributeDataType::Bool => rng.gen_range(0..=1) as f64,
+            _ => {
+                rng.fill(bytes);
+                return;
+            }
+        },
+    };
+
+    match component_type {
+        PointAttributeDataType::U8 => bytes.copy_from_slice(&(value as u8).to_ne_bytes()),
+        PointAttributeDataType::I8 => bytes.copy_from_slice(&(value as i8).to_ne_bytes()),
+        PointAttributeDataType::U16 => bytes.copy_from_slice(&(value as u16).to_ne_bytes()),
+        PointAttributeDataType::I16 => bytes.copy_from_slice(&(value as i16).to_ne_bytes()),
+        PointAttributeDataType::U32 => bytes.copy_from_slice(&(value as u32).to_ne_bytes()),
+        PointAttributeDataType::I32 => bytes.copy_from_slice(&(value as i32).to_ne_bytes()),
+        PointAttributeDataType::U64 => bytes.copy_from_slice(&(value as u64).to_ne_bytes()),
+        PointAttributeDataType::I64 => bytes.copy_from_slice(&(value as i64).to_ne_bytes()),
+        PointAttributeDataType::F32 => bytes.copy_from_slice(&(value as f32).to_ne_bytes()),
+        PointAttributeDataType::F64 => bytes.copy_from_slice(&value.to_ne_bytes()),
+        PointAttributeDataType::Bool => bytes[0] = (value != 0.0) as u8,
+        other => panic!("Invalid component type {}", other),
+    }
+}
+
+/// Creates a new `PerAttributeVecPointStorage` with the given `PointLayout` that contains `count` points with
+/// pseudo-random attribute values. The values are generated from the given `seed`, so calling this function twice
+/// with the same arguments yields the same points, which makes it suitable for reproducible tests and benchmarks.
+///
+/// Known attributes are filled with values in sensible ranges: Positions lie within the box `[0; RANDOM_POSITIONS_EXTENT]^3`,
+/// classifications are in `[0; 31]`, return numbers in `[1; 5]` etc. All other integer attributes (e.g. intensities) cover
+/// the full range of their datatype, all other floating-point attributes are in `[0; 1)`.
+///
+/// ```
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::*;
+/// # use pasture_core::testutil::random_point_buffer;
+/// let layout = PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::CLASSIFICATION]);
+/// let points = random_point_buffer(layout.clone(), 100, 42);
+/// assert_eq!(100, points.len());
+/// assert!(points.iter_attribute::<u8>(&attributes::CLASSIFICATION).all(|class| class < 32));
+///
+/// let same_points = random_point_buffer(layout, 100, 42);
+/// assert!(buffers_equal(&points, &same_points));
+/// ```
+///
+/// The pseudo-random values are generated using `rand`'s `StdRng`, so the generated values might differ between versions
+/// of `rand`.
+pub fn random_point_buffer(
+    layout: PointLayout,
+    count: usize,
+    seed: u64,
+) -> PerAttributeVecPointStorage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let attribute_definitions = layout
+        .attributes()
+        .map(|member| member.into())
+        .collect::<Vec<PointAttributeDefinition>>();
+
+    let mut buffer = PerAttributeVecPointStorage::with_capacity(count, layout);
+    buffer.resize(count);
+
+    for attribute in attribute_definitions {
+        let component_type = attribute.datatype().component_type();
+        let component_size = component_type.size() as usize;
+        let range = value_range_for(&attribute);
+        let attribute_data = buffer.get_raw_attribute_range_mut(0..count, &attribute);
+        for component in attribute_data.chunks_exact_mut(component_size) {
+            write_random_component(component_type, range, component, &mut rng);
+        }
+    }
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::{buffers_equal, PointBuffer, PointBufferExt};
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_random_point_buffer_ranges() {
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            attributes::INTENSITY,
+            attributes::CLASSIFICATION,
+            attributes::RETURN_NUMBER,
+            attributes::EDGE_OF_FLIGHT_LINE,
+            attributes::GPS_TIME,
+        ]);
+        let points = random_point_buffer(layout, 1000, 0);
+        assert_eq!(1000, points.len());
+
+        assert!(points
+            .iter_attribute::<Vector3<f64>>(&attributes::POSITION_3D)
+            .all(|position| position
+                .iter()
+                .all(|v| *v >= 0.0 && *v < RANDOM_POSITIONS_EXTENT)));
+        assert!(points
+            .iter_attribute::<u8>(&attributes::CLASSIFICATION)
+            .all(|class| class <= 31));
+        assert!(points
+            .iter_attribute::<u8>(&attributes::RETURN_NUMBER)
+            .all(|return_number| (1..=5).contains(&return_number)));
+        // bool values must be valid, i.e. either 0 or 1
+        let mut edge_of_flight_line = vec![0; 1000];
+        points.get_raw_attribute_range(
+            0..1000,
+            &attributes::EDGE_OF_FLIGHT_LINE,
+            &mut edge_of_flight_line,
+        );
+        assert!(edge_of_flight_line.iter().all(|v| *v <= 1));
+
+        // Intensities cover the full u16 range, so with 1000 points we expect at least some large values
+        assert!(points
+            .iter_attribute::<u16>(&attributes::INTENSITY)
+            .any(|intensity| intensity > u8::MAX as u16));
+    }
+
+    #[test]
+    fn test_random_point_buffer_is_deterministic() {
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            attributes::COLOR_RGB,
+            attributes::NORMAL,
+        ]);
+        let first = random_point_buffer(layout.clone(), 64, 1234);
+        let second = random_point_buffer(layout.clone(), 64, 1234);
+        let different_seed = random_point_buffer(layout, 64, 4321);
+
+        assert!(buffers_equal(&first, &second));
+        assert!(!buffers_equal(&first, &different_seed));
+    }
+}
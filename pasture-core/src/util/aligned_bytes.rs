@@ -0,0 +1,168 @@
+use std::{
+    alloc::{self, Layout},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+/// A growable byte buffer similar to `Vec<u8>` whose memory always starts at a multiple of a fixed alignment. This
+/// guarantee holds across all reallocations, so a pointer to the start of the buffer can always be reinterpreted as a
+/// pointer to any type whose alignment does not exceed the alignment of the buffer
+pub(crate) struct AlignedByteVec {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+    alignment: usize,
+}
+
+impl AlignedByteVec {
+    /// Creates a new empty `AlignedByteVec` with the given `alignment`. No memory is allocated until bytes are pushed
+    ///
+    /// # Panics
+    ///
+    /// If `alignment` is not a power of two
+    pub fn new(alignment: usize) -> Self {
+        assert!(
+            alignment.is_power_of_two(),
+            "Alignment {} is not a power of two",
+            alignment
+        );
+        Self {
+            // A dangling pointer that is still aligned correctly, like the one that `Vec` uses
+            ptr: NonNull::new(alignment as *mut u8).unwrap(),
+            len: 0,
+            capacity: 0,
+            alignment,
+        }
+    }
+
+    /// Creates a new empty `AlignedByteVec` with the given `alignment` that can hold at least `capacity` bytes without
+    /// reallocating
+    ///
+    /// # Panics
+    ///
+    /// If `alignment` is not a power of two
+    pub fn with_capacity(capacity: usize, alignment: usize) -> Self {
+        let mut vec = Self::new(alignment);
+        vec.reserve(capacity);
+        vec
+    }
+
+    /// Returns the number of bytes that this `AlignedByteVec` can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Reserves capacity for at least `additional` more bytes
+    pub fn reserve(&mut self, additional: usize) {
+        let required_capacity = self.len.checked_add(additional).expect("Capacity overflow");
+        if required_capacity <= self.capacity {
+            return;
+        }
+        let new_capacity = std::cmp::max(required_capacity, self.capacity * 2);
+        let new_layout =
+            Layout::from_size_align(new_capacity, self.alignment).expect("Capacity overflow");
+        let new_ptr = unsafe {
+            if self.capacity == 0 {
+                alloc::alloc(new_layout)
+            } else {
+                // `realloc` keeps the alignment of the old layout, which is the alignment of this buffer
+                alloc::realloc(self.ptr.as_ptr(), self.current_layout(), new_capacity)
+            }
+        };
+        self.ptr = NonNull::new(new_ptr).unwrap_or_else(|| alloc::handle_alloc_error(new_layout));
+        self.capacity = new_capacity;
+    }
+
+    /// Appends all bytes in `bytes` to the end of this `AlignedByteVec`
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.reserve(bytes.len());
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                self.ptr.as_ptr().add(self.len),
+                bytes.len(),
+            );
+        }
+        self.len += bytes.len();
+    }
+
+    /// Resizes this `AlignedByteVec` to `new_len` bytes. New bytes are set to `value`
+    pub fn resize(&mut self, new_len: usize, value: u8) {
+        if new_len > self.len {
+            self.reserve(new_len - self.len);
+            unsafe {
+                std::ptr::write_bytes(self.ptr.as_ptr().add(self.len), value, new_len - self.len);
+            }
+        }
+        self.len = new_len;
+    }
+
+    /// Shortens this `AlignedByteVec` to `len` bytes. Has no effect if `len` is not smaller than the current length
+    pub fn truncate(&mut self, len: usize) {
+        self.len = std::cmp::min(self.len, len);
+    }
+
+    /// Removes all bytes from this `AlignedByteVec` without affecting its capacity
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn current_layout(&self) -> Layout {
+        Layout::from_size_align(self.capacity, self.alignment).unwrap()
+    }
+}
+
+impl Deref for AlignedByteVec {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedByteVec {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for AlignedByteVec {
+    fn drop(&mut self) {
+        if self.capacity != 0 {
+            unsafe {
+                alloc::dealloc(self.ptr.as_ptr(), self.current_layout());
+            }
+        }
+    }
+}
+
+// The memory of an `AlignedByteVec` is exclusively owned, just like the memory of a `Vec<u8>`
+unsafe impl Send for AlignedByteVec {}
+unsafe impl Sync for AlignedByteVec {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aligned_byte_vec_stays_aligned() {
+        for &alignment in [1, 16, 32, 64].iter() {
+            let mut vec = AlignedByteVec::new(alignment);
+            assert!(vec.is_empty());
+            for idx in 0..1000_u32 {
+                vec.extend_from_slice(&idx.to_ne_bytes());
+                assert_eq!(0, vec.as_ptr() as usize % alignment);
+            }
+            assert_eq!(4000, vec.len());
+            assert_eq!(999_u32.to_ne_bytes(), vec[3996..4000]);
+
+            vec.resize(4004, 0xff);
+            assert_eq!([0xff; 4], vec[4000..4004]);
+            vec.truncate(4);
+            assert_eq!(0_u32.to_ne_bytes(), vec[..]);
+            vec.clear();
+            assert!(vec.is_empty());
+            assert!(vec.capacity() >= 4004);
+        }
+    }
+}
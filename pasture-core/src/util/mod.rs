@@ -1,2 +1,8 @@
+mod aligned_bytes;
+pub(crate) use self::aligned_bytes::*;
+
 mod memory;
 pub use self::memory::*;
+
+mod parallel;
+pub use self::parallel::*;
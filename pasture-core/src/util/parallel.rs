@@ -0,0 +1,80 @@
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use rayon::ThreadPool;
+
+lazy_static! {
+    static ref THREAD_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+}
+
+/// Sets the [`ThreadPool`](rayon::ThreadPool) that pasture uses for all parallel operations, such as
+/// [`par_sort_by_attribute`](crate::containers::PerAttributeVecPointStorage::par_sort_by_attribute). By default,
+/// pasture uses the global `rayon` thread pool, which has one thread per CPU core. Applications that share their
+/// process with other work can use this function to limit pasture to a fixed number of threads:
+///
+/// ```
+/// # use pasture_core::util::*;
+/// let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+/// set_thread_pool(pool);
+/// assert_eq!(2, run_parallel(|| rayon::current_num_threads()));
+/// # reset_thread_pool();
+/// ```
+pub fn set_thread_pool(pool: ThreadPool) {
+    *THREAD_POOL.write().unwrap() = Some(Arc::new(pool));
+}
+
+/// Resets the thread pool that pasture uses for parallel operations to the global `rayon` thread pool
+pub fn reset_thread_pool() {
+    *THREAD_POOL.write().unwrap() = None;
+}
+
+/// Runs `op` within the thread pool configured through [`set_thread_pool`], or within the global `rayon` thread pool
+/// if no thread pool was configured. All parallel iterators that `op` uses run on this thread pool. Every parallel
+/// operation in pasture is executed through this function.
+pub fn run_parallel<R: Send, F: FnOnce() -> R + Send>(op: F) -> R {
+    // Clone the pool so that the lock is not held while `op` is running
+    let pool = THREAD_POOL.read().unwrap().clone();
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[test]
+    fn test_run_parallel_respects_thread_pool() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        set_thread_pool(pool);
+
+        let active_tasks = AtomicUsize::new(0);
+        let max_active_tasks = AtomicUsize::new(0);
+        run_parallel(|| {
+            (0..32).into_par_iter().for_each(|_| {
+                let active = active_tasks.fetch_add(1, Ordering::SeqCst) + 1;
+                max_active_tasks.fetch_max(active, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                active_tasks.fetch_sub(1, Ordering::SeqCst);
+            });
+        });
+        reset_thread_pool();
+
+        let max_active_tasks = max_active_tasks.load(Ordering::SeqCst);
+        assert!(max_active_tasks >= 1);
+        assert!(
+            max_active_tasks <= 2,
+            "Expected at most 2 parallel tasks, but got {}",
+            max_active_tasks
+        );
+    }
+}
@@ -7,8 +7,8 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::DeriveInput;
 use syn::{
-    parse_macro_input, Attribute, Data, Error, Field, Fields, GenericArgument, Ident, Lit,
-    NestedMeta, PathArguments, Result, Type, TypePath,
+    parse_macro_input, Attribute, Data, Error, Expr, ExprLit, Field, Fields, GenericArgument,
+    Ident, Lit, Member, NestedMeta, PathArguments, Result, Type, TypeArray, TypePath,
 };
 
 mod layout;
@@ -30,6 +30,13 @@ enum PasturePrimitiveType {
     Vec3f32,
     Vec3f64,
     Vec4u8,
+    Vec4u16,
+    Vec4f32,
+    Vec4f64,
+    Array4f32,
+    Array8f32,
+    Array4i32,
+    Array8i32,
 }
 
 impl PasturePrimitiveType {
@@ -51,6 +58,13 @@ impl PasturePrimitiveType {
             PasturePrimitiveType::Vec3f32 => 4,
             PasturePrimitiveType::Vec3f64 => 8,
             &PasturePrimitiveType::Vec4u8 => 1,
+            PasturePrimitiveType::Vec4u16 => 2,
+            PasturePrimitiveType::Vec4f32 => 4,
+            PasturePrimitiveType::Vec4f64 => 8,
+            PasturePrimitiveType::Array4f32 => 4,
+            PasturePrimitiveType::Array8f32 => 4,
+            PasturePrimitiveType::Array4i32 => 4,
+            PasturePrimitiveType::Array8i32 => 4,
         }
     }
 
@@ -72,6 +86,13 @@ impl PasturePrimitiveType {
             PasturePrimitiveType::Vec3f32 => 12,
             PasturePrimitiveType::Vec3f64 => 24,
             &PasturePrimitiveType::Vec4u8 => 4,
+            PasturePrimitiveType::Vec4u16 => 8,
+            PasturePrimitiveType::Vec4f32 => 16,
+            PasturePrimitiveType::Vec4f64 => 32,
+            PasturePrimitiveType::Array4f32 => 16,
+            PasturePrimitiveType::Array8f32 => 32,
+            PasturePrimitiveType::Array4i32 => 16,
+            PasturePrimitiveType::Array8i32 => 32,
         }
     }
 
@@ -105,6 +126,27 @@ impl PasturePrimitiveType {
             PasturePrimitiveType::Vec4u8 => {
                 quote! {pasture_core::layout::PointAttributeDataType::Vec4u8}
             }
+            PasturePrimitiveType::Vec4u16 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Vec4u16}
+            }
+            PasturePrimitiveType::Vec4f32 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Vec4f32}
+            }
+            PasturePrimitiveType::Vec4f64 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Vec4f64}
+            }
+            PasturePrimitiveType::Array4f32 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Array4f32}
+            }
+            PasturePrimitiveType::Array8f32 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Array8f32}
+            }
+            PasturePrimitiveType::Array4i32 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Array4i32}
+            }
+            PasturePrimitiveType::Array8i32 => {
+                quote! {pasture_core::layout::PointAttributeDataType::Array8i32}
+            }
         }
     }
 }
@@ -180,9 +222,12 @@ fn get_primitive_type_for_non_ident_type(type_path: &TypePath) -> Result<Pasture
                 },
                 "Vector4" => match type_name.as_str() {
                     "u8" => Ok(PasturePrimitiveType::Vec4u8),
+                    "u16" => Ok(PasturePrimitiveType::Vec4u16),
+                    "f32" => Ok(PasturePrimitiveType::Vec4f32),
+                    "f64" => Ok(PasturePrimitiveType::Vec4f64),
                     _ => Err(Error::new_spanned(
                         ident,
-                        format!("Vector4<{}> is no valid Pasture primitive type. Vector4 is supported, but only for generic argument(s) u8", type_name),
+                        format!("Vector4<{}> is no valid Pasture primitive type. Vector4 is supported, but only for generic argument(s) u8, u16, f32 or f64", type_name),
                     ))
                 },
                 _ => Err(Error::new_spanned(ident, format!("Invalid type"))),
@@ -212,7 +257,49 @@ fn type_path_to_primitive_type(type_path: &TypePath) -> Result<PasturePrimitiveT
     // Ok(gen)
 }
 
-fn get_attribute_name_from_field(field: &Field) -> Result<String> {
+fn type_array_to_primitive_type(type_array: &TypeArray) -> Result<PasturePrimitiveType> {
+    let invalid_array_error_msg = "Array is no valid Pasture primitive type. Arrays are supported, but only for element types f32 and i32 with lengths 4 or 8";
+    let element_type = match &*type_array.elem {
+        Type::Path(type_path) => type_path
+            .path
+            .get_ident()
+            .map(|ident| ident.to_string())
+            .ok_or_else(|| Error::new_spanned(type_path, invalid_array_error_msg))?,
+        bad => return Err(Error::new_spanned(bad, invalid_array_error_msg)),
+    };
+    let length = match &type_array.len {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(length),
+            ..
+        }) => length.base10_parse::<usize>()?,
+        bad => return Err(Error::new_spanned(bad, invalid_array_error_msg)),
+    };
+
+    match (element_type.as_str(), length) {
+        ("f32", 4) => Ok(PasturePrimitiveType::Array4f32),
+        ("f32", 8) => Ok(PasturePrimitiveType::Array8f32),
+        ("i32", 4) => Ok(PasturePrimitiveType::Array4i32),
+        ("i32", 8) => Ok(PasturePrimitiveType::Array8i32),
+        _ => Err(Error::new_spanned(type_array, invalid_array_error_msg)),
+    }
+}
+
+/// Returns true if the `#[pasture]` attribute list contains the `normalized` flag after the attribute name, as in
+/// `#[pasture(BUILTIN_COLOR_RGB, normalized)]`
+fn get_normalized_flag(list: &syn::MetaList, malformed_field_error_msg: &str) -> Result<bool> {
+    let mut normalized = false;
+    for flag in list.nested.iter().skip(1) {
+        match flag {
+            NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("normalized") => {
+                normalized = true
+            }
+            bad => return Err(Error::new_spanned(bad, malformed_field_error_msg)),
+        }
+    }
+    Ok(normalized)
+}
+
+fn get_attribute_name_from_field(field: &Field) -> Result<(String, bool)> {
     if field.attrs.len() != 1 {
         return Err(Error::new_spanned(
             field,
@@ -222,9 +309,9 @@ fn get_attribute_name_from_field(field: &Field) -> Result<String> {
     let pasture_attribute = &field.attrs[0];
     let meta = pasture_attribute.parse_meta()?;
     // TODO Better explanation of the builtin Pasture attributes in this error message!
-    let malformed_field_error_msg = "#[pasture] attribute is malformed. Correct syntax is #[pasture(attribute = \"NAME\")] or #[pasture(BUILTIN_XXX)], where XXX matches any of the builtin attributes in Pasture.";
+    let malformed_field_error_msg = "#[pasture] attribute is malformed. Correct syntax is #[pasture(attribute = \"NAME\")] or #[pasture(BUILTIN_XXX)], where XXX matches any of the builtin attributes in Pasture, optionally followed by the normalized flag, as in #[pasture(BUILTIN_XXX, normalized)].";
 
-    // For now, we expect that 'meta' is a Meta::List containing a single entry
+    // For now, we expect that 'meta' is a Meta::List containing the attribute name as its first entry, optionally followed by flags
     // The entry should be a NameValue, corresponding to 'attribute = "NAME"', or a Path, corresponding to 'builtin_XXX', where XXX matches any of the basic
     // builtin attributes in Pasture (such as INTENSITY, POSITION_3D etc.)
     match &meta {
@@ -237,8 +324,9 @@ fn get_attribute_name_from_field(field: &Field) -> Result<String> {
                 NestedMeta::Meta(nested_meta) => nested_meta,
                 _ => return Err(Error::new_spanned(list, malformed_field_error_msg)),
             };
+            let normalized = get_normalized_flag(list, malformed_field_error_msg)?;
 
-            match nested_meta {
+            let attribute_name: String = match nested_meta {
                 syn::Meta::Path(path) => {
                     let ident = path
                         .get_ident()
@@ -259,6 +347,7 @@ fn get_attribute_name_from_field(field: &Field) -> Result<String> {
                         "BUILTIN_USER_DATA" => Ok("UserData".into()),
                         "BUILTIN_POINT_SOURCE_ID" => Ok("PointSourceID".into()),
                         "BUILTIN_COLOR_RGB" => Ok("ColorRGB".into()),
+                        "BUILTIN_COLOR_RGBA" => Ok("ColorRGBA".into()),
                         "BUILTIN_GPS_TIME" => Ok("GpsTime".into()),
                         "BUILTIN_NIR" => Ok("NIR".into()),
                         "BUILTIN_WAVE_PACKET_DESCRIPTOR_INDEX" => {
@@ -297,7 +386,8 @@ fn get_attribute_name_from_field(field: &Field) -> Result<String> {
                     })
                     .ok_or_else(|| Error::new_spanned(name_value, malformed_field_error_msg)),
                 bad => Err(Error::new_spanned(bad, malformed_field_error_msg)),
-            }
+            }?;
+            Ok((attribute_name, normalized))
         }
         bad => Err(Error::new_spanned(bad, malformed_field_error_msg)),
     }
@@ -306,27 +396,39 @@ fn get_attribute_name_from_field(field: &Field) -> Result<String> {
 /// Describes a single field within a `PointType` struct. Contains the name of the field, the point attribute
 /// that the field maps to, as well as the primitive type of the field
 struct FieldLayoutDescription {
+    pub member: Member,
     pub attribute_name: String,
     pub primitive_type: PasturePrimitiveType,
+    pub normalized: bool,
 }
 
 fn get_field_layout_descriptions(fields: &Fields) -> Result<Vec<FieldLayoutDescription>> {
     fields
         .iter()
-        .map(|field| match field.ty {
-            Type::Path(ref type_path) => {
-                let primitive_type = type_path_to_primitive_type(type_path)?;
-                let attribute_name = get_attribute_name_from_field(field)?;
-
-                Ok(FieldLayoutDescription {
-                    attribute_name,
-                    primitive_type,
-                })
-            }
-            ref bad => Err(Error::new_spanned(
-                bad,
-                format!("Invalid type in PointType struct"),
-            )),
+        .enumerate()
+        .map(|(index, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(index.into()),
+            };
+            let primitive_type = match field.ty {
+                Type::Path(ref type_path) => type_path_to_primitive_type(type_path)?,
+                Type::Array(ref type_array) => type_array_to_primitive_type(type_array)?,
+                ref bad => {
+                    return Err(Error::new_spanned(
+                        bad,
+                        format!("Invalid type in PointType struct"),
+                    ))
+                }
+            };
+            let (attribute_name, normalized) = get_attribute_name_from_field(field)?;
+
+            Ok(FieldLayoutDescription {
+                member,
+                attribute_name,
+                primitive_type,
+                normalized,
+            })
         })
         .collect::<Result<Vec<FieldLayoutDescription>>>()
 }
@@ -389,7 +491,8 @@ fn calculate_offsets_and_alignment(
 ///
 /// Any that that wants to implement `PointType` using this `derive` macro must fulfill the following requirements:
 /// - It must be at least one of `#[repr(C)]` and `#[repr(packed)]`
-/// - All its members may only be [Pasture primitive types](pasture_core::layout::PointAttributeDataType)
+/// - All its members may only be [Pasture primitive types](pasture_core::layout::PointAttributeDataType), which includes the
+///   fixed-size arrays `[f32; 4]`, `[f32; 8]`, `[i32; 4]` and `[i32; 8]`
 /// - Each member must contain an attribute `#[pasture(X)]`, where `X` is either one of the builtin attributes explained below, or `attribute = "name"` for a custom attribute named `name`
 /// - No two members may share the same attribute name
 ///
@@ -411,6 +514,7 @@ fn calculate_offsets_and_alignment(
 /// - `BUILTIN_USER_DATA` corresponding to the [USER_DATA](pasture_core::layout::attributes::USER_DATA) attribute
 /// - `BUILTIN_POINT_SOURCE_ID` corresponding to the [POINT_SOURCE_ID](pasture_core::layout::attributes::POINT_SOURCE_ID) attribute
 /// - `BUILTIN_COLOR_RGB` corresponding to the [COLOR_RGB](pasture_core::layout::attributes::COLOR_RGB) attribute
+/// - `BUILTIN_COLOR_RGBA` corresponding to the [COLOR_RGBA](pasture_core::layout::attributes::COLOR_RGBA) attribute
 /// - `BUILTIN_GPS_TIME` corresponding to the [GPS_TIME](pasture_core::layout::attributes::GPS_TIME) attribute
 /// - `BUILTIN_NIR` corresponding to the [NIR](pasture_core::layout::attributes::NIR) attribute
 /// - `BUILTIN_WAVE_PACKET_DESCRIPTOR_INDEX` corresponding to the [WAVE_PACKET_DESCRIPTOR_INDEX](pasture_core::layout::attributes::WAVE_PACKET_DESCRIPTOR_INDEX) attribute
@@ -424,6 +528,12 @@ fn calculate_offsets_and_alignment(
 /// # Custom attributes
 ///
 /// To associate a member of a custom `PointType` with a point attribute with custom `name`, use the `#[pasture(attribute = "name")]` attribute
+///
+/// # Normalized attributes
+///
+/// Members with an integer type can be marked as [normalized](pasture_core::layout::PointAttributeDefinition::as_normalized) by adding
+/// the `normalized` flag after the attribute name, e.g. `#[pasture(BUILTIN_COLOR_RGB, normalized)]`. The values of normalized attributes
+/// are scaled into the range `[0;1]` (or `[-1;1]` for signed types) when they are converted into floating-point datatypes
 #[proc_macro_derive(PointType, attributes(pasture))]
 pub fn derive_point_type(item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as DeriveInput);
@@ -465,17 +575,57 @@ pub fn derive_point_type(item: TokenStream) -> TokenStream {
     let attribute_descriptions = fields.iter().zip(offsets.iter()).map(|(field, offset)| {
         let attribute_name = &field.attribute_name;
         let primitive_type = &field.primitive_type.as_token_stream();
-        quote! {
-            pasture_core::layout::PointAttributeDefinition::custom(#attribute_name, #primitive_type).at_offset_in_type(#offset)
+        if field.normalized {
+            quote! {
+                pasture_core::layout::PointAttributeDefinition::custom(#attribute_name, #primitive_type).as_normalized().at_offset_in_type(#offset)
+            }
+        } else {
+            quote! {
+                pasture_core::layout::PointAttributeDefinition::custom(#attribute_name, #primitive_type).at_offset_in_type(#offset)
+            }
         }
     });
 
+    // The offsets are derived from the #[repr] attribute of the struct. If we got them wrong (e.g. because of an unsupported
+    // #[repr] like align(N)), all accesses through the PointLayout would silently read garbage, so the generated code checks
+    // the offsets and the size against the actual memory layout of the struct
+    let members = fields.iter().map(|field| &field.member);
+    let member_names = fields.iter().map(|field| {
+        let member = &field.member;
+        quote!(#member).to_string()
+    });
+    let name_str = name.to_string();
+
     let gen = quote! {
         impl pasture_core::layout::PointType for #name {
             fn layout() -> pasture_core::layout::PointLayout {
-                pasture_core::layout::PointLayout::from_members_and_alignment(&[
+                let layout = pasture_core::layout::PointLayout::from_members_and_alignment(&[
                     #(#attribute_descriptions ,)*
-                ], #type_alignment)
+                ], #type_alignment);
+
+                let point = std::mem::MaybeUninit::<#name>::uninit();
+                let base_address = point.as_ptr() as usize;
+                #(
+                    let actual_offset = unsafe { std::ptr::addr_of!((*point.as_ptr()).#members) } as usize - base_address;
+                    assert_eq!(
+                        #offsets as usize,
+                        actual_offset,
+                        "derive(PointType): Offset of member {} of {} does not match its offset in memory. Is the #[repr] attribute of {} supported?",
+                        #member_names,
+                        #name_str,
+                        #name_str
+                    );
+                )*
+                assert_eq!(
+                    layout.size_of_point_entry() as usize,
+                    std::mem::size_of::<#name>(),
+                    "derive(PointType): Size of the PointLayout of {} does not match the size of {} in memory. Is the #[repr] attribute of {} supported?",
+                    #name_str,
+                    #name_str,
+                    #name_str
+                );
+
+                layout
             }
         }
     };
@@ -10,7 +10,7 @@ use pasture_core::{
 use pasture_derive::PointType;
 use pasture_io::{
     base::{PointReader, PointWriter},
-    las::{LASReader, LASWriter, LasPointFormat0},
+    las::{LASReader, LASWriter, LasPointFormat0, LazReaderOptions},
 };
 use rand::{distributions::Uniform, thread_rng, Rng};
 use scopeguard::defer;
@@ -106,6 +106,12 @@ fn read_performance(path: &str) {
     reader.read(count).unwrap();
 }
 
+fn read_performance_laz_options(laz_options: LazReaderOptions) {
+    let mut reader = LASReader::from_path_with_options(LAZ_PATH, laz_options).unwrap();
+    let count = reader.remaining_points();
+    reader.read(count).unwrap();
+}
+
 fn read_performance_custom_format(buffer: &mut dyn PointBufferWriteable, path: &str) {
     buffer.clear();
     let mut reader = LASReader::from_path(path).unwrap();
@@ -128,6 +134,27 @@ fn bench(c: &mut Criterion) {
     c.bench_function("las_read", |b| b.iter(|| read_performance(LAS_PATH)));
     c.bench_function("laz_read", |b| b.iter(|| read_performance(LAZ_PATH)));
 
+    for prefetch_chunks in [1, 4, 16].iter() {
+        let serial = LazReaderOptions {
+            decompress_parallel: false,
+            prefetch_chunks: *prefetch_chunks,
+        };
+        c.bench_function(
+            &format!("laz_read_serial_prefetch_{}", prefetch_chunks),
+            |b| b.iter(|| read_performance_laz_options(serial)),
+        );
+        if cfg!(feature = "parallel") {
+            let parallel = LazReaderOptions {
+                decompress_parallel: true,
+                ..serial
+            };
+            c.bench_function(
+                &format!("laz_read_parallel_prefetch_{}", prefetch_chunks),
+                |b| b.iter(|| read_performance_laz_options(parallel)),
+            );
+        }
+    }
+
     {
         let mut read_buffer =
             InterleavedVecPointStorage::with_capacity(1_000_000, CustomPointType::layout());
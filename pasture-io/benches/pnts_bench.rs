@@ -0,0 +1,131 @@
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use pasture_core::{
+    containers::{
+        InterleavedVecPointStorage, PerAttributeVecPointStorage, PointBuffer, PointBufferExt,
+    },
+    layout::{
+        attributes::{COLOR_RGB, POSITION_3D},
+        PointAttributeDataType, PointType,
+    },
+    nalgebra::Vector3,
+};
+use pasture_derive::PointType;
+use pasture_io::{base::PointWriter, tiles3d::PntsWriter};
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+const NUM_POINTS: usize = 1_000_000;
+
+/// Point type whose attributes all have to be converted to the datatypes of the .pnts format
+#[derive(PointType, Clone, Copy)]
+#[repr(C)]
+struct CustomPointType {
+    #[pasture(BUILTIN_POSITION_3D)]
+    pub position: Vector3<f64>,
+    #[pasture(BUILTIN_COLOR_RGB)]
+    pub color: Vector3<u16>,
+}
+
+fn random_custom_point<R: Rng + ?Sized>(rng: &mut R) -> CustomPointType {
+    CustomPointType {
+        position: Vector3::new(
+            rng.sample(Uniform::new(-100.0, 100.0)),
+            rng.sample(Uniform::new(-100.0, 100.0)),
+            rng.sample(Uniform::new(-100.0, 100.0)),
+        ),
+        color: Vector3::new(rng.gen(), rng.gen(), rng.gen()),
+    }
+}
+
+fn get_dummy_points() -> Vec<CustomPointType> {
+    let mut rng = thread_rng();
+    (0..NUM_POINTS)
+        .map(|_| random_custom_point(&mut rng))
+        .collect()
+}
+
+fn write_performance(points: &dyn PointBuffer) {
+    let mut writer =
+        PntsWriter::from_write_and_layout(Cursor::new(Vec::new()), CustomPointType::layout());
+    writer.write(points).unwrap();
+    writer.finalize().unwrap();
+}
+
+fn write_all_points_performance(points: &dyn PointBuffer) {
+    let mut writer =
+        PntsWriter::from_write_and_layout(Cursor::new(Vec::new()), CustomPointType::layout());
+    writer.write_all_points(points).unwrap();
+    writer.finalize().unwrap();
+}
+
+/// Converts the given points into the default `PointLayout` of the `PntsWriter`, so that they can be written without
+/// any conversion
+fn get_layout_compatible_points(points: &[CustomPointType]) -> PerAttributeVecPointStorage {
+    let writer =
+        PntsWriter::from_write_and_layout(Cursor::new(Vec::new()), CustomPointType::layout());
+    let source: PerAttributeVecPointStorage = points.into();
+    let positions = source
+        .iter_attribute_as::<Vector3<f32>>(&POSITION_3D)
+        .collect::<Vec<_>>();
+    let colors = source
+        .iter_attribute::<Vector3<u16>>(&COLOR_RGB)
+        .map(|color| Vector3::new(color.x as u8, color.y as u8, color.z as u8))
+        .collect::<Vec<_>>();
+
+    let mut compatible_points =
+        PerAttributeVecPointStorage::new(writer.get_default_point_layout().clone());
+    let mut pusher = compatible_points.begin_push_attributes();
+    pusher.push_attribute_range(
+        &POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+        positions.as_slice(),
+    );
+    pusher.push_attribute_range(
+        &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+        colors.as_slice(),
+    );
+    pusher.done();
+    compatible_points
+}
+
+fn write_layout_compatible_performance(points: &dyn PointBuffer) {
+    let mut writer =
+        PntsWriter::from_write_and_layout(Cursor::new(Vec::new()), CustomPointType::layout());
+    writer.write_layout_compatible(points).unwrap();
+    writer.finalize().unwrap();
+}
+
+fn bench(c: &mut Criterion) {
+    let points = get_dummy_points();
+
+    {
+        let interleaved: InterleavedVecPointStorage = points.as_slice().into();
+        c.bench_function("pnts_write_with_conversion_interleaved", |b| {
+            b.iter(|| write_performance(&interleaved))
+        });
+        c.bench_function("pnts_write_all_points_with_conversion_interleaved", |b| {
+            b.iter(|| write_all_points_performance(&interleaved))
+        });
+    }
+
+    {
+        let per_attribute: PerAttributeVecPointStorage = points.as_slice().into();
+        c.bench_function("pnts_write_with_conversion_per_attribute", |b| {
+            b.iter(|| write_performance(&per_attribute))
+        });
+    }
+
+    {
+        let compatible = get_layout_compatible_points(points.as_slice());
+        c.bench_function("pnts_write_layout_compatible_per_attribute", |b| {
+            b.iter(|| write_layout_compatible_performance(&compatible))
+        });
+    }
+}
+
+criterion_group! {
+    name = pnts;
+    config = Criterion::default().sample_size(20);
+    targets = bench
+}
+criterion_main!(pnts);
@@ -0,0 +1,102 @@
+//! Measures the peak memory of writing .pnts files. This is a separate bench target from `pnts_bench`, because it
+//! replaces the global allocator, which would distort the timings of the other benchmarks
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::Cursor,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use pasture_core::{
+    containers::{InterleavedVecPointStorage, PointBuffer},
+    layout::PointType,
+    nalgebra::Vector3,
+};
+use pasture_derive::PointType;
+use pasture_io::{base::PointWriter, tiles3d::PntsWriter};
+use rand::{distributions::Uniform, thread_rng, Rng};
+
+const NUM_POINTS: usize = 1_000_000;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocator that keeps track of the peak number of allocated bytes, so that the peak memory of the different ways of
+/// writing a .pnts file can be compared
+struct PeakTrackingAllocator;
+
+unsafe impl GlobalAlloc for PeakTrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated_bytes =
+                ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK_ALLOCATED_BYTES.fetch_max(allocated_bytes, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakTrackingAllocator = PeakTrackingAllocator;
+
+/// Returns the peak number of bytes that `f` allocates on top of the memory that is already allocated
+fn peak_memory_of<F: FnOnce()>(f: F) -> usize {
+    let allocated_bytes_before = ALLOCATED_BYTES.load(Ordering::SeqCst);
+    PEAK_ALLOCATED_BYTES.store(allocated_bytes_before, Ordering::SeqCst);
+    f();
+    PEAK_ALLOCATED_BYTES.load(Ordering::SeqCst) - allocated_bytes_before
+}
+
+/// Point type whose attributes all have to be converted to the datatypes of the .pnts format
+#[derive(PointType, Clone, Copy)]
+#[repr(C)]
+struct CustomPointType {
+    #[pasture(BUILTIN_POSITION_3D)]
+    pub position: Vector3<f64>,
+    #[pasture(BUILTIN_COLOR_RGB)]
+    pub color: Vector3<u16>,
+}
+
+fn get_dummy_points() -> Vec<CustomPointType> {
+    let mut rng = thread_rng();
+    (0..NUM_POINTS)
+        .map(|_| CustomPointType {
+            position: Vector3::new(
+                rng.sample(Uniform::new(-100.0, 100.0)),
+                rng.sample(Uniform::new(-100.0, 100.0)),
+                rng.sample(Uniform::new(-100.0, 100.0)),
+            ),
+            color: Vector3::new(rng.gen(), rng.gen(), rng.gen()),
+        })
+        .collect()
+}
+
+fn write(points: &dyn PointBuffer) {
+    let mut writer =
+        PntsWriter::from_write_and_layout(Cursor::new(Vec::new()), CustomPointType::layout());
+    writer.write(points).unwrap();
+    writer.finalize().unwrap();
+}
+
+fn write_all_points(points: &dyn PointBuffer) {
+    let mut writer =
+        PntsWriter::from_write_and_layout(Cursor::new(Vec::new()), CustomPointType::layout());
+    writer.write_all_points(points).unwrap();
+    writer.finalize().unwrap();
+}
+
+fn main() {
+    let points: InterleavedVecPointStorage = get_dummy_points().as_slice().into();
+    println!(
+        "Peak memory of writing {} interleaved points: {} bytes using write, {} bytes using write_all_points",
+        NUM_POINTS,
+        peak_memory_of(|| write(&points)),
+        peak_memory_of(|| write_all_points(&points))
+    );
+}
@@ -79,6 +79,7 @@ mod ex {
             device_backend: gpu::DeviceBackend::Vulkan,
             use_adapter_features: true,
             use_adapter_limits: true,
+            ..Default::default()
         })
         .await;
 
@@ -95,6 +96,7 @@ mod ex {
             // Same order as in shader
             attributes: &[attributes::POSITION_3D, attributes::INTENSITY],
             binding: 0,
+            extra_usage: pasture_core::wgpu::BufferUsages::empty(),
         };
 
         let point_count = point_buffer.len();
@@ -120,14 +122,16 @@ mod ex {
 
         // Allocate memory for point buffer and queue it for upload onto the GPU
         let mut gpu_point_buffer = GpuPointBufferInterleaved::new();
-        gpu_point_buffer.malloc(point_count as u64, &buffer_info, &mut device.wgpu_device);
-        gpu_point_buffer.upload(
-            &mut point_buffer,
-            0..point_count,
-            &buffer_info,
-            &mut device.wgpu_device,
-            &device.wgpu_queue,
-        );
+        gpu_point_buffer.malloc(point_count as u64, &buffer_info, &device.wgpu_device);
+        gpu_point_buffer
+            .upload(
+                &mut point_buffer,
+                0..point_count,
+                &buffer_info,
+                &device.wgpu_device,
+                &device.wgpu_queue,
+            )
+            .expect("Failed to upload points to the GPU");
 
         // Here: GpuPointBuffer -> "set=0",
         //       PointUniform   -> "set=1"
@@ -149,7 +153,8 @@ mod ex {
                 &buffer_info,
                 &device.wgpu_device,
             )
-            .await;
+            .await
+            .expect("Failed to download points from the GPU");
 
         let duration_end = chrono::Utc::now().timestamp_millis();
         let elapsed_time = (duration_end - duration_start) as f32 / 1000.0;
@@ -58,6 +58,7 @@ mod ex {
                 device_backend: gpu::DeviceBackend::Vulkan,
                 use_adapter_features: true,
                 use_adapter_limits: true,
+                ..Default::default()
             }
         ).await;
 
@@ -74,10 +75,12 @@ mod ex {
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::POSITION_3D,
                 binding: 0,
+                extra_usage: pasture_core::wgpu::BufferUsages::empty(),
             },
             gpu::BufferInfoPerAttribute {
                 attribute: &attributes::INTENSITY,
                 binding: 1,
+                extra_usage: pasture_core::wgpu::BufferUsages::empty(),
             },
         ];
 
@@ -102,8 +105,9 @@ mod ex {
 
         // Allocate memory for point buffer and queue it for upload onto the GPU
         let mut gpu_point_buffer = GpuPointBufferPerAttribute::new();
-        gpu_point_buffer.malloc(point_count as u64, &buffer_infos, &mut device.wgpu_device);
-        gpu_point_buffer.upload(&mut point_buffer, 0..point_count, &buffer_infos, &mut device.wgpu_device, &device.wgpu_queue);
+        gpu_point_buffer.malloc(point_count as u64, &buffer_infos, &device.wgpu_device);
+        gpu_point_buffer.upload(&mut point_buffer, 0..point_count, &buffer_infos, &device.wgpu_device, &device.wgpu_queue)
+            .expect("Failed to upload points to the GPU");
 
         // Here: GpuPointBuffer -> "set=0",
         //       PointUniform   -> "set=1"
@@ -113,7 +117,10 @@ mod ex {
         device.set_compute_shader_glsl(include_str!("shaders/io_per_attribute.comp"));
         device.compute(((point_count / 128) + 1) as u32, 1, 1);
 
-        gpu_point_buffer.download_into_per_attribute(&mut point_buffer, 0..point_count, &buffer_infos, &device.wgpu_device).await;
+        gpu_point_buffer
+            .download_into_per_attribute(&mut point_buffer, 0..point_count, &buffer_infos, &device.wgpu_device)
+            .await
+            .expect("Failed to download points from the GPU");
 
         let duration_end = chrono::Utc::now().timestamp_millis();
         let elapsed_time = (duration_end - duration_start) as f32 / 1000.0;
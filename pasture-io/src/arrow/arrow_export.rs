@@ -0,0 +1,261 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_rs::{
+    array::{make_array, ArrayData, ArrayRef, BooleanArray},
+    buffer::{Buffer, MutableBuffer},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatch,
+};
+use pasture_core::{
+    containers::PointBuffer,
+    layout::{PointAttributeDataType, PointAttributeDefinition},
+};
+
+/// Number of components of the vector datatypes that can be converted to Arrow
+pub(super) const VECTOR_COMPONENTS: i32 = 3;
+
+/// Returns the Arrow `DataType` of the column that stores an attribute with the given `datatype`, or `None` if attributes
+/// with this datatype can't be converted to Arrow. Scalar datatypes map to the corresponding primitive Arrow type, `Vec3`
+/// datatypes map to a `FixedSizeList` of three values of the component type
+pub fn arrow_datatype(datatype: PointAttributeDataType) -> Option<DataType> {
+    let arrow_datatype = match datatype {
+        PointAttributeDataType::U8 => DataType::UInt8,
+        PointAttributeDataType::I8 => DataType::Int8,
+        PointAttributeDataType::U16 => DataType::UInt16,
+        PointAttributeDataType::I16 => DataType::Int16,
+        PointAttributeDataType::U32 => DataType::UInt32,
+        PointAttributeDataType::I32 => DataType::Int32,
+        PointAttributeDataType::U64 => DataType::UInt64,
+        PointAttributeDataType::I64 => DataType::Int64,
+        PointAttributeDataType::F32 => DataType::Float32,
+        PointAttributeDataType::F64 => DataType::Float64,
+        PointAttributeDataType::Bool => DataType::Boolean,
+        PointAttributeDataType::Vec3u8
+        | PointAttributeDataType::Vec3u16
+        | PointAttributeDataType::Vec3f32
+        | PointAttributeDataType::Vec3f64 => DataType::FixedSizeList(
+            Box::new(Field::new(
+                "item",
+                arrow_datatype(datatype.component_type())?,
+                false,
+            )),
+            VECTOR_COMPONENTS,
+        ),
+        _ => return None,
+    };
+    Some(arrow_datatype)
+}
+
+/// Converts all points in `buffer` into an Arrow `RecordBatch` with one column per attribute. Each column has the name of
+/// its attribute and the Arrow datatype given by [arrow_datatype]. All columns are non-nullable.
+///
+/// Arrow stores each column contiguously, just like a per-attribute point buffer. If `buffer` is in per-attribute memory
+/// layout, the data of each attribute is therefore copied into its column as a single block without any conversion.
+/// For all other buffers, the values of each attribute are gathered from the individual points. In both cases, the
+/// values are written directly into the memory of the column, so each value is copied exactly once.
+///
+/// # Errors
+///
+/// If `buffer` contains an attribute whose datatype can't be converted to Arrow, see [arrow_datatype]
+///
+/// # Examples
+///
+/// ```
+/// # use pasture_core::{containers::{PerAttributeVecPointStorage, PointBufferWriteable}, layout::{attributes::INTENSITY, PointLayout}};
+/// # use pasture_io::arrow::to_record_batch;
+/// let mut points = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[INTENSITY]));
+/// points.resize(4);
+/// let batch = to_record_batch(&points)?;
+/// assert_eq!(4, batch.num_rows());
+/// assert_eq!("Intensity", batch.schema().field(0).name());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn to_record_batch(buffer: &dyn PointBuffer) -> Result<RecordBatch> {
+    let num_points = buffer.len();
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for attribute in buffer.point_layout().attributes() {
+        let attribute_def: PointAttributeDefinition = attribute.into();
+        // Arrow arrays own their memory, so the data is written into an Arrow buffer that becomes the column without
+        // any further copies
+        let attribute_data = match buffer.as_per_attribute() {
+            Some(per_attribute_buffer) => {
+                let data =
+                    per_attribute_buffer.get_raw_attribute_range_ref(0..num_points, &attribute_def);
+                let mut attribute_data = MutableBuffer::new(data.len());
+                attribute_data.extend_from_slice(data);
+                attribute_data
+            }
+            None => {
+                let mut attribute_data =
+                    MutableBuffer::from_len_zeroed(num_points * attribute.size() as usize);
+                buffer.get_raw_attribute_range(
+                    0..num_points,
+                    &attribute_def,
+                    attribute_data.as_slice_mut(),
+                );
+                attribute_data
+            }
+        };
+
+        let column = arrow_column(attribute.datatype(), attribute_data.into(), num_points)?;
+        fields.push(Field::new(
+            attribute.name(),
+            column.data_type().clone(),
+            false,
+        ));
+        columns.push(column);
+    }
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+/// Creates an Arrow array from the raw `data` of `num_points` values of the given `datatype`. The array takes ownership
+/// of `data` without copying it, except for booleans
+fn arrow_column(
+    datatype: PointAttributeDataType,
+    data: Buffer,
+    num_points: usize,
+) -> Result<ArrayRef> {
+    let column_datatype = arrow_datatype(datatype).ok_or_else(|| {
+        anyhow!(
+            "Attributes with datatype {} can't be converted to Arrow",
+            datatype
+        )
+    })?;
+    let column_data = match &column_datatype {
+        // Arrow stores booleans as a bitmap, so they can't be copied
+        DataType::Boolean => {
+            let values = data
+                .as_slice()
+                .iter()
+                .map(|value| *value != 0)
+                .collect::<Vec<_>>();
+            return Ok(Arc::new(BooleanArray::from(values)));
+        }
+        DataType::FixedSizeList(component_field, num_components) => {
+            let component_data = ArrayData::builder(component_field.data_type().clone())
+                .len(num_points * *num_components as usize)
+                .add_buffer(data)
+                .build();
+            ArrayData::builder(column_datatype.clone())
+                .len(num_points)
+                .add_child_data(component_data)
+                .build()
+        }
+        _ => ArrayData::builder(column_datatype.clone())
+            .len(num_points)
+            .add_buffer(data)
+            .build(),
+    };
+    Ok(make_array(column_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_rs::array::{FixedSizeListArray, Float64Array, UInt16Array};
+    use pasture_core::{
+        containers::{InterleavedVecPointStorage, PerAttributeVecPointStorage},
+        layout::{attributes::POSITION_3D, PointLayout, PointType},
+        nalgebra::Vector3,
+    };
+    use pasture_derive::PointType;
+
+    use super::*;
+
+    #[derive(PointType, Copy, Clone, Debug, PartialEq)]
+    #[repr(C, packed)]
+    struct TestPoint {
+        #[pasture(BUILTIN_POSITION_3D)]
+        position: Vector3<f64>,
+        #[pasture(BUILTIN_INTENSITY)]
+        intensity: u16,
+        #[pasture(BUILTIN_EDGE_OF_FLIGHT_LINE)]
+        edge_of_flight_line: bool,
+    }
+
+    fn test_points() -> Vec<TestPoint> {
+        vec![
+            TestPoint {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                intensity: 42,
+                edge_of_flight_line: false,
+            },
+            TestPoint {
+                position: Vector3::new(-4.5, 5.25, 1024.0),
+                intensity: 65535,
+                edge_of_flight_line: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_to_record_batch() -> Result<()> {
+        let points = test_points();
+        let per_attribute_points: PerAttributeVecPointStorage = points.as_slice().into();
+        let interleaved_points: InterleavedVecPointStorage = points.as_slice().into();
+
+        for buffer in [
+            &per_attribute_points as &dyn PointBuffer,
+            &interleaved_points as &dyn PointBuffer,
+        ]
+        .iter()
+        {
+            let batch = to_record_batch(*buffer)?;
+            assert_eq!(2, batch.num_rows());
+            assert_eq!(3, batch.num_columns());
+
+            let schema = batch.schema();
+            assert_eq!(POSITION_3D.name(), schema.field(0).name());
+            assert_eq!(
+                &arrow_datatype(PointAttributeDataType::Vec3f64).unwrap(),
+                schema.field(0).data_type()
+            );
+            assert_eq!(&DataType::UInt16, schema.field(1).data_type());
+            assert_eq!(&DataType::Boolean, schema.field(2).data_type());
+
+            let positions = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<FixedSizeListArray>()
+                .unwrap();
+            assert_eq!(3, positions.value_length());
+            let position_components = positions.values();
+            let position_components = position_components
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .unwrap();
+            assert_eq!(
+                &[1.0, 2.0, 3.0, -4.5, 5.25, 1024.0],
+                position_components.values()
+            );
+
+            let intensities = batch
+                .column(1)
+                .as_any()
+                .downcast_ref::<UInt16Array>()
+                .unwrap();
+            assert_eq!(&[42, 65535], intensities.values());
+
+            let edge_of_flight_lines = batch
+                .column(2)
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .unwrap();
+            assert!(!edge_of_flight_lines.value(0));
+            assert!(edge_of_flight_lines.value(1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_record_batch_unsupported_datatype() {
+        let buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            PointAttributeDefinition::custom("Waveform", PointAttributeDataType::Array8f32),
+        ]));
+        assert!(to_record_batch(&buffer).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, bail, Result};
+use arrow_rs::{
+    array::{Array, ArrayData, BooleanArray},
+    datatypes::DataType,
+    record_batch::RecordBatch,
+};
+use pasture_core::{
+    containers::{PerAttributePointBufferMut, PerAttributeVecPointStorage, PointBufferWriteable},
+    layout::{
+        conversion::try_get_converter_for_attributes, PointAttributeDataType,
+        PointAttributeDefinition, PointLayout,
+    },
+};
+
+use super::arrow_export::VECTOR_COMPONENTS;
+
+/// Returns the datatype of the point attribute that stores the values of an Arrow column with the given `arrow_datatype`,
+/// or `None` if there is no such datatype. This is the inverse of [arrow_datatype](super::arrow_datatype), so primitive
+/// Arrow types map to scalar datatypes and `FixedSizeList` types with three values map to `Vec3` datatypes
+pub fn point_attribute_datatype(arrow_datatype: &DataType) -> Option<PointAttributeDataType> {
+    match arrow_datatype {
+        DataType::UInt8 => Some(PointAttributeDataType::U8),
+        DataType::Int8 => Some(PointAttributeDataType::I8),
+        DataType::UInt16 => Some(PointAttributeDataType::U16),
+        DataType::Int16 => Some(PointAttributeDataType::I16),
+        DataType::UInt32 => Some(PointAttributeDataType::U32),
+        DataType::Int32 => Some(PointAttributeDataType::I32),
+        DataType::UInt64 => Some(PointAttributeDataType::U64),
+        DataType::Int64 => Some(PointAttributeDataType::I64),
+        DataType::Float32 => Some(PointAttributeDataType::F32),
+        DataType::Float64 => Some(PointAttributeDataType::F64),
+        DataType::Boolean => Some(PointAttributeDataType::Bool),
+        DataType::FixedSizeList(component_field, VECTOR_COMPONENTS) => {
+            match point_attribute_datatype(component_field.data_type())? {
+                PointAttributeDataType::U8 => Some(PointAttributeDataType::Vec3u8),
+                PointAttributeDataType::U16 => Some(PointAttributeDataType::Vec3u16),
+                PointAttributeDataType::F32 => Some(PointAttributeDataType::Vec3f32),
+                PointAttributeDataType::F64 => Some(PointAttributeDataType::Vec3f64),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Converts the given Arrow `batch` into a `PerAttributeVecPointStorage`, mapping the columns of `batch` to point attributes
+/// by their name. The datatype of each column is mapped to a point attribute datatype using [point_attribute_datatype].
+///
+/// If `layout_hint` is `None`, the resulting buffer has one attribute for each column of `batch`, with the name of the
+/// column and the corresponding datatype. If `layout_hint` is given, the resulting buffer has this `PointLayout`
+/// instead. Each of its attributes is read from the column with the same name, converting the values to the datatype
+/// of the attribute if necessary, and all other columns are ignored. This makes it possible to e.g. read positions that
+/// were written as `Float32` values into `Vec3f64` positions.
+///
+/// # Errors
+///
+/// If a column that has to be read has a datatype that is not supported, if it contains null values, or if its values
+/// can't be converted to the datatype of the attribute. If `layout_hint` contains an attribute for which `batch` has no
+/// column, an error is returned as well
+pub fn from_record_batch(
+    batch: &RecordBatch,
+    layout_hint: Option<&PointLayout>,
+) -> Result<PerAttributeVecPointStorage> {
+    let schema = batch.schema();
+    let mut column_attributes = vec![];
+    for (column_index, field) in schema.fields().iter().enumerate() {
+        match point_attribute_datatype(field.data_type()) {
+            Some(datatype) => column_attributes.push((
+                column_index,
+                PointAttributeDefinition::custom_dynamic(field.name(), datatype),
+            )),
+            // Columns that are not part of the layout hint don't have to be supported
+            None if layout_hint.is_some() => (),
+            None => bail!(
+                "Column {} has the Arrow datatype {:?}, which is not supported for point attributes",
+                field.name(),
+                field.data_type()
+            ),
+        }
+    }
+
+    let layout = match layout_hint {
+        Some(layout) => layout.clone(),
+        None => PointLayout::from_attributes(
+            &column_attributes
+                .iter()
+                .map(|(_, attribute)| attribute.clone())
+                .collect::<Vec<_>>(),
+        ),
+    };
+
+    let num_points = batch.num_rows();
+    let mut points = PerAttributeVecPointStorage::new(layout.clone());
+    points.resize(num_points);
+    for attribute in layout.attributes() {
+        let (column_index, column_attribute) = column_attributes
+            .iter()
+            .find(|(_, column_attribute)| column_attribute.name() == attribute.name())
+            .ok_or_else(|| {
+                anyhow!(
+                    "RecordBatch has no supported column for attribute {}",
+                    attribute.name()
+                )
+            })?;
+        let column = batch.column(*column_index);
+        if column.null_count() > 0 {
+            bail!(
+                "Column {} contains null values, which are not supported for point attributes",
+                attribute.name()
+            );
+        }
+
+        let attribute_def: PointAttributeDefinition = attribute.into();
+        let converter = try_get_converter_for_attributes(column_attribute, &attribute_def)?;
+        let column_data = column_data(column.as_ref(), column_attribute.datatype());
+        let attribute_data = points.get_raw_attribute_range_mut(0..num_points, &attribute_def);
+        match converter {
+            None => attribute_data.copy_from_slice(&column_data),
+            Some(conversion_fn) => {
+                for (src, dst) in column_data
+                    .chunks_exact(column_attribute.size() as usize)
+                    .zip(attribute_data.chunks_exact_mut(attribute_def.size() as usize))
+                {
+                    unsafe {
+                        conversion_fn(src, dst);
+                    }
+                }
+            }
+        }
+    }
+    Ok(points)
+}
+
+/// Returns the values of the given `column` in the memory representation of the point attribute `datatype`. Since
+/// Arrow stores all fixed-width values contiguously, only boolean values have to be copied
+fn column_data(column: &dyn Array, datatype: PointAttributeDataType) -> Cow<'_, [u8]> {
+    let num_values = column.len();
+    match datatype {
+        PointAttributeDataType::Bool => {
+            let values = column
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .expect("Column is no BooleanArray");
+            Cow::Owned(
+                (0..num_values)
+                    .map(|index| values.value(index) as u8)
+                    .collect(),
+            )
+        }
+        PointAttributeDataType::Vec3u8
+        | PointAttributeDataType::Vec3u16
+        | PointAttributeDataType::Vec3f32
+        | PointAttributeDataType::Vec3f64 => {
+            let num_components = VECTOR_COMPONENTS as usize;
+            Cow::Borrowed(raw_values(
+                &column.data().child_data()[0],
+                datatype.component_type().size() as usize,
+                column.offset() * num_components,
+                num_values * num_components,
+            ))
+        }
+        _ => Cow::Borrowed(raw_values(
+            column.data(),
+            datatype.size() as usize,
+            0,
+            num_values,
+        )),
+    }
+}
+
+/// Returns the raw memory of `num_values` values of `value_size` bytes each, starting at the value with index
+/// `first_value`, from the values buffer of the given fixed-width `array_data`
+fn raw_values(
+    array_data: &ArrayData,
+    value_size: usize,
+    first_value: usize,
+    num_values: usize,
+) -> &[u8] {
+    let start = (array_data.offset() + first_value) * value_size;
+    &array_data.buffers()[0].as_slice()[start..start + num_values * value_size]
+}
+
+#[cfg(test)]
+mod tests {
+    use pasture_core::{
+        containers::{PerAttributePointBuffer, PointBuffer, PointBufferExt},
+        layout::{
+            attributes::{COLOR_RGB, EDGE_OF_FLIGHT_LINE, INTENSITY, POSITION_3D},
+            PointType,
+        },
+        nalgebra::Vector3,
+    };
+    use pasture_derive::PointType;
+
+    use crate::arrow::to_record_batch;
+
+    use super::*;
+
+    #[derive(PointType, Copy, Clone, Debug, PartialEq)]
+    #[repr(C, packed)]
+    struct TestPoint {
+        #[pasture(BUILTIN_POSITION_3D)]
+        position: Vector3<f64>,
+        #[pasture(BUILTIN_COLOR_RGB)]
+        color: Vector3<u16>,
+        #[pasture(BUILTIN_INTENSITY)]
+        intensity: u16,
+        #[pasture(BUILTIN_EDGE_OF_FLIGHT_LINE)]
+        edge_of_flight_line: bool,
+    }
+
+    fn test_buffer() -> PerAttributeVecPointStorage {
+        let points = vec![
+            TestPoint {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                color: Vector3::new(0, 128, 65535),
+                intensity: 42,
+                edge_of_flight_line: false,
+            },
+            TestPoint {
+                position: Vector3::new(-4.5, 5.25, 1024.0),
+                color: Vector3::new(1, 2, 3),
+                intensity: 65535,
+                edge_of_flight_line: true,
+            },
+            TestPoint {
+                position: Vector3::new(0.125, -8.0, 16.5),
+                color: Vector3::new(300, 200, 100),
+                intensity: 0,
+                edge_of_flight_line: true,
+            },
+        ];
+        points.as_slice().into()
+    }
+
+    #[test]
+    fn test_record_batch_roundtrip() -> Result<()> {
+        let buffer = test_buffer();
+        let batch = to_record_batch(&buffer)?;
+        let points = from_record_batch(&batch, None)?;
+
+        assert_eq!(buffer.len(), points.len());
+        for attribute in buffer.point_layout().attributes() {
+            assert!(points.point_layout().has_attribute(&attribute.into()));
+            let attribute_def: PointAttributeDefinition = attribute.into();
+            assert_eq!(
+                buffer.get_raw_attribute_range_ref(0..buffer.len(), &attribute_def),
+                points.get_raw_attribute_range_ref(0..points.len(), &attribute_def),
+                "{}",
+                attribute.name()
+            );
+        }
+
+        // Slices of a RecordBatch start at an offset into the column data
+        let sliced_batch = RecordBatch::try_new(
+            batch.schema(),
+            batch
+                .columns()
+                .iter()
+                .map(|column| column.slice(1, 2))
+                .collect(),
+        )?;
+        let sliced_points = from_record_batch(&sliced_batch, None)?;
+        assert_eq!(2, sliced_points.len());
+        assert_eq!(
+            buffer
+                .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+                .skip(1)
+                .collect::<Vec<_>>(),
+            sliced_points
+                .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            buffer
+                .iter_attribute::<Vector3<u16>>(&COLOR_RGB)
+                .skip(1)
+                .collect::<Vec<_>>(),
+            sliced_points
+                .iter_attribute::<Vector3<u16>>(&COLOR_RGB)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![true, true],
+            sliced_points
+                .iter_attribute::<bool>(&EDGE_OF_FLIGHT_LINE)
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_record_batch_with_layout_hint() -> Result<()> {
+        let buffer = test_buffer();
+        let batch = to_record_batch(&buffer)?;
+
+        let positions_f32 = POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32);
+        let layout = PointLayout::from_attributes(&[positions_f32.clone(), INTENSITY]);
+        let points = from_record_batch(&batch, Some(&layout))?;
+        assert_eq!(&layout, points.point_layout());
+        assert_eq!(
+            Vector3::new(-4.5f32, 5.25, 1024.0),
+            points.get_attribute::<Vector3<f32>>(&positions_f32, 1)
+        );
+        assert_eq!(
+            vec![42, 65535, 0],
+            points.iter_attribute::<u16>(&INTENSITY).collect::<Vec<_>>()
+        );
+
+        let missing_attribute = PointLayout::from_attributes(&[
+            POSITION_3D,
+            PointAttributeDefinition::custom("Missing", PointAttributeDataType::F32),
+        ]);
+        assert!(from_record_batch(&batch, Some(&missing_attribute)).is_err());
+        Ok(())
+    }
+}
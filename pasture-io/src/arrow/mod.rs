@@ -0,0 +1,5 @@
+mod arrow_export;
+pub use self::arrow_export::*;
+
+mod arrow_import;
+pub use self::arrow_import::*;
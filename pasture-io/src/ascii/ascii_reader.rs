@@ -11,7 +11,7 @@ use crate::base::PointReader;
 /// `PointReader` implementation for ascii files
 
 pub struct AsciiReader<'a> {
-    raw_reader: Box<dyn PointReader + 'a>,
+    raw_reader: RawAsciiReader<Box<dyn BufRead + Send + 'a>>,
 }
 impl<'a> AsciiReader<'a> {
     /// Creates a new `AsciiReader` by opening the file at the given `path`.
@@ -93,13 +93,33 @@ impl<'a> AsciiReader<'a> {
         format: &str,
         delimiter: &str,
     ) -> Result<Self> {
-        let raw_reader: Box<dyn PointReader> =
-            Box::new(RawAsciiReader::from_read(read, format, delimiter)?);
+        let read: Box<dyn BufRead + Send + 'a> = Box::new(read);
+        let raw_reader = RawAsciiReader::from_read(read, format, delimiter)?;
         Ok(Self {
             raw_reader: raw_reader,
         })
     }
 
+    /// Sets whether points whose position contains NaN or infinite coordinates are skipped while reading. Such points
+    /// are usually the result of broken sensors and break everything that works with the bounds of the points. By
+    /// default, these points are not skipped. If they are skipped, the `read` functions return fewer points than
+    /// requested
+    pub fn set_skip_invalid_positions(&mut self, skip_invalid_positions: bool) {
+        self.raw_reader
+            .set_skip_invalid_positions(skip_invalid_positions);
+    }
+
+    /// Returns whether points with NaN or infinite coordinates are skipped while reading
+    pub fn skip_invalid_positions(&self) -> bool {
+        self.raw_reader.skip_invalid_positions()
+    }
+
+    /// Returns the number of points with NaN or infinite coordinates that were encountered so far. If
+    /// [`AsciiReader::set_skip_invalid_positions`] is enabled, this is the number of points that were skipped
+    pub fn count_invalid(&self) -> usize {
+        self.raw_reader.count_invalid()
+    }
+
     pub fn print_format_literals() {
         println!(
             "The following literals can be interpreted from this AsciiReader:
@@ -3,6 +3,7 @@ use byteorder::{LittleEndian, WriteBytesExt};
 use itertools::{EitherOrBoth::*, Itertools};
 use pasture_core::layout::attributes;
 use pasture_core::meta::Metadata;
+use pasture_core::nalgebra::Vector3;
 use pasture_core::{containers::PointBufferWriteable, layout::PointLayout};
 use pasture_core::{
     containers::{InterleavedVecPointStorage, PointBuffer},
@@ -24,6 +25,8 @@ pub(crate) struct RawAsciiReader<T: Read + BufRead> {
     delimiter: String,
     point_layout: PointLayout,
     parse_layout: Vec<PointDataType>,
+    skip_invalid_positions: bool,
+    invalid_positions_count: usize,
 }
 impl<T: Read + BufRead> RawAsciiReader<T> {
     pub fn from_read(read: T, format: &str, delimiter: &str) -> Result<Self> {
@@ -37,9 +40,33 @@ impl<T: Read + BufRead> RawAsciiReader<T> {
             delimiter: delimiter.to_string(),
             point_layout: layout,
             parse_layout: parse_layout,
+            skip_invalid_positions: false,
+            invalid_positions_count: 0,
         })
     }
 
+    pub fn set_skip_invalid_positions(&mut self, skip_invalid_positions: bool) {
+        self.skip_invalid_positions = skip_invalid_positions;
+    }
+
+    pub fn skip_invalid_positions(&self) -> bool {
+        self.skip_invalid_positions
+    }
+
+    pub fn count_invalid(&self) -> usize {
+        self.invalid_positions_count
+    }
+
+    /// Returns `true` if the `POSITION_3D` attribute of `point` contains NaN or infinite values. Points whose layout
+    /// does not contain positions are always valid
+    fn has_invalid_position(point: &UntypedPointBuffer) -> Result<bool> {
+        if !point.get_layout().has_attribute(&attributes::POSITION_3D) {
+            return Ok(false);
+        }
+        let position = point.get_attribute::<Vector3<f64>>(&attributes::POSITION_3D)?;
+        Ok(!position.iter().all(|component| component.is_finite()))
+    }
+
     fn get_point_layout_from_parse_layout(parse_layout: &[PointDataType]) -> PointLayout {
         let hashset = parse_layout
             .iter()
@@ -326,16 +353,24 @@ impl<T: Read + BufRead> PointReader for RawAsciiReader<T> {
     ) -> Result<usize> {
         let layout = point_buffer.point_layout().clone();
         let mut temp_point = UntypedPointBuffer::new(&layout);
+        let mut points_read = 0;
         //read line by line
         for (index, line) in (&mut self.reader).lines().take(count).enumerate() {
             let line = line?;
             //parse the line in an untypedpoint
             Self::parse_point(&mut temp_point, &line, &self.delimiter, &self.parse_layout)
                 .with_context(|| format!("ReadError in line {}.", index))?;
+            if Self::has_invalid_position(&temp_point)? {
+                self.invalid_positions_count += 1;
+                if self.skip_invalid_positions {
+                    continue;
+                }
+            }
             //put it in the buffer
             point_buffer.push(&temp_point.get_interleaved_point_view());
+            points_read += 1;
         }
-        Ok(count)
+        Ok(points_read)
     }
     fn get_default_point_layout(&self) -> &PointLayout {
         &self.point_layout
@@ -369,7 +404,6 @@ mod tests {
     use anyhow::Result;
     use pasture_core::containers::PointBufferExt;
     use pasture_core::layout::{attributes, PointType};
-    use pasture_core::nalgebra::Vector3;
     use pasture_derive::PointType;
     use std::{fs::File, io::BufReader};
 
@@ -628,4 +662,40 @@ mod tests {
         let ascii_reader = RawAsciiReader::from_read(reader, "x", ", ");
         ascii_reader.unwrap().read(10).unwrap();
     }
+
+    const DATA_WITH_INVALID_POSITIONS: &str =
+        "0.0, 0.0, 0.0, 1\nNaN, 1.0, 2.0, 2\n1.0, inf, 1.0, 3\n2.0, 2.0, -inf, 4\n2.0, 4.0, 2.0, 5";
+
+    #[test]
+    fn test_read_skip_invalid_positions() -> Result<()> {
+        let mut ascii_reader =
+            RawAsciiReader::from_read(DATA_WITH_INVALID_POSITIONS.as_bytes(), "xyzi", ", ")?;
+        ascii_reader.set_skip_invalid_positions(true);
+        let buffer = ascii_reader.read(5)?;
+        assert_eq!(2, buffer.len());
+        assert_eq!(3, ascii_reader.count_invalid());
+
+        let positions = buffer
+            .iter_attribute::<Vector3<f64>>(&attributes::POSITION_3D)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 4.0, 2.0)],
+            positions
+        );
+        let intensities = buffer
+            .iter_attribute::<u16>(&attributes::INTENSITY)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 5], intensities);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_keeps_invalid_positions_by_default() -> Result<()> {
+        let mut ascii_reader =
+            RawAsciiReader::from_read(DATA_WITH_INVALID_POSITIONS.as_bytes(), "xyzi", ", ")?;
+        let buffer = ascii_reader.read(5)?;
+        assert_eq!(5, buffer.len());
+        assert_eq!(3, ascii_reader.count_invalid());
+        Ok(())
+    }
 }
@@ -0,0 +1,118 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+use pasture_core::math::Raster;
+
+/// Writes the given `raster` as an Esri ASCII Grid (`.asc`) file to `path`. The file starts with the `ncols`, `nrows`,
+/// `xllcorner`, `yllcorner`, `cellsize` and `NODATA_value` header, followed by one line of space-separated values per
+/// row of the raster, starting with the top row. Since the origin of a `Raster` is its upper-left corner, the lower-left
+/// corner written to the header is computed from the origin and the height of the raster.
+///
+/// The ASCII Grid format can't store a coordinate reference system, GIS applications expect it in a separate `.prj` file
+///
+/// ```no_run
+/// # use pasture_core::math::{Raster, RASTER_NODATA};
+/// # use pasture_core::nalgebra::Vector2;
+/// # use pasture_io::ascii_grid::*;
+/// let raster = Raster::new(Vector2::new(350000.0, 5600000.0), 1.0, 100, 100, RASTER_NODATA);
+/// write_ascii_grid(&raster, "dem.asc").unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If the file at `path` can't be created or if the raster is empty
+pub fn write_ascii_grid<P: AsRef<Path>>(raster: &Raster<f32>, path: P) -> Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    write_ascii_grid_to(raster, writer)
+}
+
+/// Like [write_ascii_grid], but writes the ASCII Grid data to the given `writer`
+pub fn write_ascii_grid_to<W: Write>(raster: &Raster<f32>, mut writer: W) -> Result<()> {
+    if raster.width == 0 || raster.height == 0 {
+        bail!("Can't write an empty raster to an ASCII Grid file");
+    }
+    if raster.values.len() != raster.width * raster.height {
+        bail!(
+            "Raster has {} values but its dimensions are {}x{}",
+            raster.values.len(),
+            raster.width,
+            raster.height
+        );
+    }
+
+    let yllcorner = raster.origin.y - raster.height as f64 * raster.cell_size;
+    writeln!(writer, "ncols {}", raster.width)?;
+    writeln!(writer, "nrows {}", raster.height)?;
+    writeln!(writer, "xllcorner {}", raster.origin.x)?;
+    writeln!(writer, "yllcorner {}", yllcorner)?;
+    writeln!(writer, "cellsize {}", raster.cell_size)?;
+    writeln!(writer, "NODATA_value {}", raster.nodata)?;
+
+    for row in raster.values.chunks_exact(raster.width) {
+        let mut values = row.iter();
+        // `chunks_exact` never yields empty rows, and the width is not zero
+        write!(writer, "{}", values.next().unwrap())?;
+        for value in values {
+            write!(writer, " {}", value)?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use pasture_core::{math::RASTER_NODATA, nalgebra::Vector2};
+    use scopeguard::defer;
+
+    #[test]
+    fn test_write_ascii_grid() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_write_ascii_grid.asc");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut raster = Raster::new(Vector2::new(350000.5, 5600000.25), 2.0, 3, 2, RASTER_NODATA);
+        raster.values = vec![1.0, 2.0, RASTER_NODATA, 4.5, -5.0, 6.0];
+        write_ascii_grid(&raster, &test_file_path)?;
+
+        let contents = std::fs::read_to_string(&test_file_path)?;
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "ncols 3",
+                "nrows 2",
+                "xllcorner 350000.5",
+                "yllcorner 5599996.25",
+                "cellsize 2",
+                "NODATA_value -9999",
+                "1 2 -9999",
+                "4.5 -5 6",
+            ],
+            lines
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_invalid_ascii_grid_fails() {
+        let raster = Raster::new(Vector2::new(0.0, 0.0), 1.0, 0, 0, RASTER_NODATA);
+        assert!(write_ascii_grid_to(&raster, vec![]).is_err());
+
+        let mut raster = Raster::new(Vector2::new(0.0, 0.0), 1.0, 2, 2, RASTER_NODATA);
+        raster.values.pop();
+        assert!(write_ascii_grid_to(&raster, vec![]).is_err());
+    }
+}
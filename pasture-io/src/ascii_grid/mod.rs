@@ -0,0 +1,2 @@
+mod ascii_grid_writer;
+pub use self::ascii_grid_writer::*;
@@ -0,0 +1,283 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::SeekFrom,
+};
+
+use anyhow::{bail, Result};
+use pasture_core::{
+    containers::{
+        InterleavedPointBufferMut, InterleavedVecPointStorage, PointBuffer, PointBufferWriteable,
+    },
+    layout::PointLayout,
+};
+
+use super::{PointReader, SeekToPoint};
+
+/// Wrapper around a `PointReader` that reads points in chunks of a fixed size and keeps the decoded chunks in memory, so
+/// that repeated reads of the same or overlapping point ranges don't have to decode the underlying data again. This is
+/// useful for random access into compressed files such as LAZ, where seeking and decoding is expensive.
+///
+/// Chunks are evicted in least-recently-used order once the cached chunks exceed the capacity of the `CachedReader`.
+/// The memory used by the cache is therefore bounded by the capacity, plus the memory of at most one chunk that is
+/// decoded during a `read_range` call. If a single chunk is larger than the capacity, nothing is cached at all.
+pub struct CachedReader<R: PointReader + SeekToPoint> {
+    reader: R,
+    point_count: usize,
+    chunk_size: usize,
+    capacity_bytes: usize,
+    cached_chunks: HashMap<usize, Box<dyn PointBuffer>>,
+    /// Indices of all cached chunks, from least recently used to most recently used
+    lru_order: VecDeque<usize>,
+    cached_bytes: usize,
+}
+
+impl<R: PointReader + SeekToPoint> CachedReader<R> {
+    /// Creates a new `CachedReader` that reads from `reader` in chunks of `chunk_size` points and caches at most
+    /// `capacity_bytes` bytes of decoded points
+    ///
+    /// # Errors
+    ///
+    /// If the number of points in `reader` can't be determined
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero
+    pub fn new(mut reader: R, chunk_size: usize, capacity_bytes: usize) -> Result<Self> {
+        assert!(chunk_size > 0, "chunk_size must not be zero");
+        let point_count = reader.point_count()?;
+        Ok(Self {
+            reader,
+            point_count,
+            chunk_size,
+            capacity_bytes,
+            cached_chunks: HashMap::new(),
+            lru_order: VecDeque::new(),
+            cached_bytes: 0,
+        })
+    }
+
+    /// Reads the `count` points starting at point index `start`. All chunks that are overlapped by this range and are not
+    /// cached yet are decoded from the underlying reader and added to the cache. The points are returned in the default
+    /// `PointLayout` of the underlying reader
+    ///
+    /// # Errors
+    ///
+    /// If the range exceeds the number of points in the underlying reader, or if reading from the underlying reader fails
+    pub fn read_range(&mut self, start: usize, count: usize) -> Result<Box<dyn PointBuffer>> {
+        let end = start + count;
+        if end > self.point_count {
+            bail!(
+                "Point range {}..{} is out of bounds, the reader only contains {} points",
+                start,
+                end,
+                self.point_count
+            );
+        }
+
+        let mut points =
+            InterleavedVecPointStorage::with_capacity(count, self.point_layout().clone());
+        points.resize(count);
+        if count == 0 {
+            return Ok(Box::new(points));
+        }
+
+        let first_chunk = start / self.chunk_size;
+        let last_chunk = (end - 1) / self.chunk_size;
+        for chunk_index in first_chunk..=last_chunk {
+            let chunk_start = chunk_index * self.chunk_size;
+            let chunk = self.get_chunk(chunk_index)?;
+            let range_in_chunk = (start.max(chunk_start) - chunk_start)
+                ..(end.min(chunk_start + chunk.len()) - chunk_start);
+            let range_in_points = (chunk_start + range_in_chunk.start - start)
+                ..(chunk_start + range_in_chunk.end - start);
+            chunk.get_raw_points(range_in_chunk, points.get_raw_points_mut(range_in_points));
+            self.evict_chunks();
+        }
+
+        Ok(Box::new(points))
+    }
+
+    /// Returns the total number of points in the underlying reader
+    pub fn point_count(&self) -> usize {
+        self.point_count
+    }
+
+    /// Returns the number of bytes of decoded points that are currently cached
+    pub fn cached_bytes(&self) -> usize {
+        self.cached_bytes
+    }
+
+    /// Returns the default `PointLayout` of the underlying reader, which is the `PointLayout` of all point buffers that
+    /// `read_range` returns
+    pub fn point_layout(&self) -> &PointLayout {
+        self.reader.get_default_point_layout()
+    }
+
+    /// Returns a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Drops all cached chunks and returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns the chunk with the given index, decoding it from the underlying reader if it is not cached yet. The chunk
+    /// becomes the most recently used chunk
+    fn get_chunk(&mut self, chunk_index: usize) -> Result<&dyn PointBuffer> {
+        if self.cached_chunks.contains_key(&chunk_index) {
+            self.lru_order.retain(|&index| index != chunk_index);
+        } else {
+            let chunk_start = chunk_index * self.chunk_size;
+            let chunk_len = self.chunk_size.min(self.point_count - chunk_start);
+            self.reader
+                .seek_point(SeekFrom::Start(chunk_start as u64))?;
+            let chunk = self.reader.read(chunk_len)?;
+            self.cached_bytes += chunk.memory_size_bytes();
+            self.cached_chunks.insert(chunk_index, chunk);
+        }
+        self.lru_order.push_back(chunk_index);
+        Ok(self.cached_chunks[&chunk_index].as_ref())
+    }
+
+    /// Evicts the least recently used chunks until the cached chunks fit into the capacity of this `CachedReader`
+    fn evict_chunks(&mut self) {
+        while self.cached_bytes > self.capacity_bytes {
+            let chunk_index = match self.lru_order.pop_front() {
+                Some(index) => index,
+                None => break,
+            };
+            if let Some(chunk) = self.cached_chunks.remove(&chunk_index) {
+                self.cached_bytes -= chunk.memory_size_bytes();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasture_core::{
+        containers::PointBufferExt, layout::attributes::POSITION_3D, meta::Metadata,
+        nalgebra::Vector3,
+    };
+
+    use crate::las::{get_test_laz_path, test_data_positions, LASReader};
+
+    use super::*;
+
+    /// `PointReader` that counts how often points are read from the wrapped reader
+    struct CountingReader<R: PointReader + SeekToPoint> {
+        reader: R,
+        read_calls: usize,
+    }
+
+    impl<R: PointReader + SeekToPoint> PointReader for CountingReader<R> {
+        fn read(&mut self, count: usize) -> Result<Box<dyn PointBuffer>> {
+            self.read_calls += 1;
+            self.reader.read(count)
+        }
+
+        fn read_into(
+            &mut self,
+            point_buffer: &mut dyn PointBufferWriteable,
+            count: usize,
+        ) -> Result<usize> {
+            self.read_calls += 1;
+            self.reader.read_into(point_buffer, count)
+        }
+
+        fn get_metadata(&self) -> &dyn Metadata {
+            self.reader.get_metadata()
+        }
+
+        fn get_default_point_layout(&self) -> &PointLayout {
+            self.reader.get_default_point_layout()
+        }
+    }
+
+    impl<R: PointReader + SeekToPoint> SeekToPoint for CountingReader<R> {
+        fn seek_point(&mut self, position: SeekFrom) -> Result<usize> {
+            self.reader.seek_point(position)
+        }
+    }
+
+    fn read_positions(
+        reader: &mut CachedReader<impl PointReader + SeekToPoint>,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<Vector3<f64>>> {
+        let points = reader.read_range(start, count)?;
+        Ok(points
+            .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+            .collect())
+    }
+
+    #[test]
+    fn test_cached_reader_decodes_chunks_once() -> Result<()> {
+        let counting_reader = CountingReader {
+            reader: LASReader::from_path(get_test_laz_path(0))?,
+            read_calls: 0,
+        };
+        let mut reader = CachedReader::new(counting_reader, 4, usize::MAX)?;
+        let expected_positions = test_data_positions();
+
+        assert_eq!(
+            expected_positions[2..7],
+            read_positions(&mut reader, 2, 5)?[..]
+        );
+        assert_eq!(2, reader.get_ref().read_calls);
+
+        assert_eq!(
+            expected_positions[2..7],
+            read_positions(&mut reader, 2, 5)?[..]
+        );
+        assert_eq!(
+            expected_positions[4..6],
+            read_positions(&mut reader, 4, 2)?[..]
+        );
+        assert_eq!(2, reader.get_ref().read_calls);
+
+        assert!(reader.read_range(2, reader.point_count()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_reader_evicts_least_recently_used_chunks() -> Result<()> {
+        let counting_reader = CountingReader {
+            reader: LASReader::from_path(get_test_laz_path(0))?,
+            read_calls: 0,
+        };
+        let chunk_bytes = 4 * counting_reader
+            .get_default_point_layout()
+            .size_of_point_entry() as usize;
+        let mut reader = CachedReader::new(counting_reader, 4, chunk_bytes)?;
+        let expected_positions = test_data_positions();
+
+        // Only one chunk fits into the cache, so reading the first chunk again after the second one decodes it again
+        assert_eq!(
+            expected_positions[0..4],
+            read_positions(&mut reader, 0, 4)?[..]
+        );
+        assert_eq!(
+            expected_positions[4..8],
+            read_positions(&mut reader, 4, 4)?[..]
+        );
+        assert_eq!(chunk_bytes, reader.cached_bytes());
+        assert_eq!(2, reader.get_ref().read_calls);
+
+        assert_eq!(
+            expected_positions[4..8],
+            read_positions(&mut reader, 4, 4)?[..]
+        );
+        assert_eq!(2, reader.get_ref().read_calls);
+        assert_eq!(
+            expected_positions[0..4],
+            read_positions(&mut reader, 0, 4)?[..]
+        );
+        assert_eq!(3, reader.get_ref().read_calls);
+
+        Ok(())
+    }
+}
@@ -1,9 +1,19 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use las_rs::Builder;
+use pasture_core::layout::PointLayout;
 
-use crate::las::{LASReader, LASWriter};
+use crate::{
+    las::{las_point_format_from_point_layout, LASReader, LASWriter},
+    ply::PlyReader,
+    tiles3d::{PntsReader, PntsWriter},
+};
 
 use super::{PointReader, PointWriter, SeekToPoint};
 
@@ -154,12 +164,113 @@ impl Default for IOFactory {
             Ok(Box::new(writer))
         });
 
+        factory.register_reader_for_extension("pnts", |path| {
+            let reader = PntsReader::from_read(BufReader::new(File::open(path)?))?;
+            Ok(Box::new(reader))
+        });
+
+        factory.register_reader_for_extension("ply", |path| {
+            let reader = PlyReader::from_read(BufReader::new(File::open(path)?))?;
+            Ok(Box::new(reader))
+        });
+
         factory
     }
 }
 
+/// File extensions of all formats that `open_reader` and `open_writer` support
+const SUPPORTED_EXTENSIONS: &[&str] = &["las", "laz", "pnts"];
+
+/// Returns the lower-case file extension of the given `path`
+fn lowercase_extension(path: &Path) -> Result<String> {
+    let extension = path.extension().ok_or_else(|| {
+        anyhow!(
+            "File extension could not be determined from path {}",
+            path.display()
+        )
+    })?;
+    let extension_str = extension.to_str().ok_or_else(|| {
+        anyhow!(
+            "File extension of path {} is no valid Unicode string",
+            path.display()
+        )
+    })?;
+    Ok(extension_str.to_lowercase())
+}
+
+fn unsupported_extension_error(extension: &str) -> anyhow::Error {
+    anyhow!(
+        "Point cloud files with extension {} are not supported. Supported extensions are: {}",
+        extension,
+        SUPPORTED_EXTENSIONS.join(", ")
+    )
+}
+
+/// Opens a `PointReader` for the given `path`. The concrete type of the reader is determined from the file extension of
+/// `path`, see `SUPPORTED_EXTENSIONS` for all supported extensions.
+///
+/// # Errors
+///
+/// If the file extension of `path` is not supported, or if the reader can't be created, e.g. because of I/O errors
+pub fn open_reader(path: &Path) -> Result<Box<dyn PointReader>> {
+    let extension = lowercase_extension(path)?;
+    match extension.as_str() {
+        "las" | "laz" => Ok(Box::new(LASReader::from_path(path)?)),
+        "pnts" => Ok(Box::new(PntsReader::from_read(BufReader::new(
+            File::open(path)?,
+        ))?)),
+        "ply" => Ok(Box::new(PlyReader::from_read(BufReader::new(File::open(
+            path,
+        )?))?)),
+        "pcd" => bail!(
+            "Reading {} files is not supported yet. Supported extensions are: {}",
+            extension,
+            SUPPORTED_EXTENSIONS.join(", ")
+        ),
+        _ => Err(unsupported_extension_error(&extension)),
+    }
+}
+
+/// Opens a `PointWriter` for writing points in the given `point_layout` to `path`. The concrete type of the writer is
+/// determined from the file extension of `path`, see `SUPPORTED_EXTENSIONS` for all supported extensions. For LAS and
+/// LAZ files, the point record format is the smallest format that can store all attributes in `point_layout`.
+///
+/// # Errors
+///
+/// If the file extension of `path` is not supported, or if the writer can't be created, e.g. because of I/O errors
+pub fn open_writer(path: &Path, point_layout: &PointLayout) -> Result<Box<dyn PointWriter>> {
+    let extension = lowercase_extension(path)?;
+    match extension.as_str() {
+        "las" | "laz" => {
+            let mut header_builder = Builder::from((1, 4));
+            header_builder.point_format = las_point_format_from_point_layout(point_layout);
+            Ok(Box::new(LASWriter::from_path_and_header(
+                path,
+                header_builder.into_header()?,
+            )?))
+        }
+        "pnts" => Ok(Box::new(PntsWriter::from_write_and_layout(
+            BufWriter::new(File::create(path)?),
+            point_layout.clone(),
+        ))),
+        "ply" | "pcd" => bail!(
+            "Writing {} files is not supported yet. Supported extensions are: {}",
+            extension,
+            SUPPORTED_EXTENSIONS.join(", ")
+        ),
+        _ => Err(unsupported_extension_error(&extension)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
+    use pasture_core::layout::attributes::{COLOR_RGB, INTENSITY, POSITION_3D};
+    use scopeguard::defer;
+
+    use crate::las::{get_test_las_path, point_layout_from_las_point_format};
+
     use super::*;
 
     #[test]
@@ -179,4 +290,63 @@ mod tests {
         assert!(factory.supports_writing_to("foo"));
         assert!(factory.supports_writing_to("FOO"));
     }
+
+    #[test]
+    fn test_open_reader_dispatches_on_extension() -> Result<()> {
+        let las_reader = open_reader(&get_test_las_path(1))?;
+        assert_eq!(
+            point_layout_from_las_point_format(&las_rs::point::Format::new(1)?)?,
+            *las_reader.get_default_point_layout()
+        );
+
+        let mut pnts_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        pnts_path.push("resources/test/points.pnts");
+        let pnts_reader = open_reader(&pnts_path)?;
+        let expected_pnts_reader = PntsReader::from_read(BufReader::new(File::open(&pnts_path)?))?;
+        assert_eq!(
+            expected_pnts_reader.get_default_point_layout(),
+            pnts_reader.get_default_point_layout()
+        );
+
+        let error = open_reader(Path::new("points.xyz")).err().unwrap();
+        assert!(error.to_string().contains("las, laz, pnts"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_writer_dispatches_on_extension() -> Result<()> {
+        let layout = PointLayout::from_attributes(&[POSITION_3D, INTENSITY, COLOR_RGB]);
+
+        let las_path = "./test_open_writer.las";
+        defer! {
+            std::fs::remove_file(las_path).expect("Could not remove test file");
+        }
+        {
+            let las_writer = open_writer(Path::new(las_path), &layout)?;
+            assert_eq!(
+                point_layout_from_las_point_format(&las_rs::point::Format::new(2)?)?,
+                *las_writer.get_default_point_layout()
+            );
+        }
+
+        let pnts_path = "./test_open_writer.pnts";
+        defer! {
+            std::fs::remove_file(pnts_path).expect("Could not remove test file");
+        }
+        {
+            let pnts_writer = open_writer(Path::new(pnts_path), &layout)?;
+            // Intensities are not supported by the pnts format
+            assert!(!pnts_writer
+                .get_default_point_layout()
+                .has_attribute_with_name(INTENSITY.name()));
+            assert!(pnts_writer
+                .get_default_point_layout()
+                .has_attribute_with_name(COLOR_RGB.name()));
+        }
+
+        assert!(open_writer(Path::new("points.xyz"), &layout).is_err());
+
+        Ok(())
+    }
 }
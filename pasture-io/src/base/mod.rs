@@ -9,3 +9,12 @@ pub use self::seek::*;
 
 mod io_factory;
 pub use self::io_factory::*;
+
+mod pipeline;
+pub use self::pipeline::*;
+
+mod read_from_memory;
+pub use self::read_from_memory::*;
+
+mod cached_reader;
+pub use self::cached_reader::*;
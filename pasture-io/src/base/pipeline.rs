@@ -0,0 +1,202 @@
+use anyhow::Result;
+use pasture_core::containers::PointBuffer;
+
+use super::{PointReader, PointWriter};
+
+/// A single stage of a `PointPipeline`. Each stage receives a chunk of points and produces a new chunk of points,
+/// which is passed on to the next stage of the pipeline. Stages may change the number of points (e.g. filtering or
+/// downsampling) as well as the `PointLayout` of the points (e.g. adding or converting attributes). Since stages take
+/// `&mut self`, they are free to keep state between chunks.
+///
+/// Closures of the form `FnMut(&dyn PointBuffer) -> Result<Box<dyn PointBuffer>>` implement `PipelineStage`, so simple
+/// stages don't require a dedicated type.
+pub trait PipelineStage {
+    /// Processes the given `chunk` of points and returns the resulting points
+    fn process(&mut self, chunk: &dyn PointBuffer) -> Result<Box<dyn PointBuffer>>;
+}
+
+impl<F: FnMut(&dyn PointBuffer) -> Result<Box<dyn PointBuffer>>> PipelineStage for F {
+    fn process(&mut self, chunk: &dyn PointBuffer) -> Result<Box<dyn PointBuffer>> {
+        self(chunk)
+    }
+}
+
+/// A streaming point transformation pipeline. A `PointPipeline` reads points in chunks from a `PointReader`, runs each
+/// chunk through all of its `PipelineStage`s in the order in which they were added and writes the resulting points to a
+/// `PointWriter`. At no point are more than a single chunk (plus the intermediate results of the stages for this chunk)
+/// held in memory, so pipelines can process files of arbitrary size.
+///
+/// ```no_run
+/// # use anyhow::Result;
+/// # use pasture_io::base::*;
+/// # use pasture_io::las::*;
+/// # use pasture_core::containers::*;
+/// # use pasture_core::layout::attributes;
+/// # fn main() -> Result<()> {
+/// let mut reader = LASReader::from_path("in.las")?;
+/// let mut writer = LASWriter::from_path_and_header("out.las", reader.header().clone())?;
+/// reader
+///     .pipeline()
+///     .with_chunk_size(1_000_000)
+///     .then(|chunk: &dyn PointBuffer| -> Result<Box<dyn PointBuffer>> {
+///         // Remove all points that are classified as noise
+///         let mut points = PerAttributeVecPointStorage::new(chunk.point_layout().clone());
+///         points.push(chunk);
+///         points.retain(|index| chunk.get_attribute::<u8>(&attributes::CLASSIFICATION, index) != 7);
+///         Ok(Box::new(points))
+///     })
+///     .write_to(&mut writer)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PointPipeline<'a> {
+    source: &'a mut dyn PointReader,
+    stages: Vec<Box<dyn PipelineStage + 'a>>,
+    chunk_size: usize,
+}
+
+impl<'a> PointPipeline<'a> {
+    /// The default number of points that a `PointPipeline` reads per chunk
+    pub const DEFAULT_CHUNK_SIZE: usize = 50_000;
+
+    /// Creates a new `PointPipeline` without any stages that reads its points from the given `source`
+    pub fn new(source: &'a mut dyn PointReader) -> Self {
+        Self {
+            source,
+            stages: vec![],
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets the number of points that are read from the source per chunk
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        if chunk_size == 0 {
+            panic!("PointPipeline::with_chunk_size: chunk_size must not be zero");
+        }
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Appends the given `stage` to the end of this `PointPipeline`
+    pub fn then<S: PipelineStage + 'a>(mut self, stage: S) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Runs this `PointPipeline` until its source is exhausted, writing all resulting points to the given `sink`. The
+    /// `sink` is flushed after the last chunk has been written. Returns the number of points that were written to the
+    /// `sink`.
+    pub fn write_to(mut self, sink: &mut dyn PointWriter) -> Result<usize> {
+        let mut points_written = 0;
+        loop {
+            let chunk = self.source.read(self.chunk_size)?;
+            if chunk.len() == 0 {
+                break;
+            }
+
+            let processed_chunk = self
+                .stages
+                .iter_mut()
+                .try_fold(chunk, |current_chunk, stage| {
+                    stage.process(current_chunk.as_ref())
+                })?;
+
+            if processed_chunk.len() > 0 {
+                sink.write(processed_chunk.as_ref())?;
+                points_written += processed_chunk.len();
+            }
+        }
+        sink.flush()?;
+        Ok(points_written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::las::{get_test_las_path, test_data_positions, LASReader};
+    use pasture_core::{
+        containers::{
+            InterleavedPointBufferMut, InterleavedVecPointStorage, PointBufferExt,
+            PointBufferWriteable,
+        },
+        layout::{attributes, PointLayout},
+        nalgebra::Vector3,
+    };
+
+    /// `PointWriter` that keeps all written points in memory
+    struct CollectingWriter {
+        points: InterleavedVecPointStorage,
+        was_flushed: bool,
+    }
+
+    impl PointWriter for CollectingWriter {
+        fn write(&mut self, points: &dyn PointBuffer) -> Result<()> {
+            self.points.push(points);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            self.was_flushed = true;
+            Ok(())
+        }
+
+        fn get_default_point_layout(&self) -> &PointLayout {
+            self.points.point_layout()
+        }
+    }
+
+    /// Keeps every second point of each chunk
+    fn decimate(chunk: &dyn PointBuffer) -> Result<Box<dyn PointBuffer>> {
+        let point_size = chunk.point_layout().size_of_point_entry() as usize;
+        let mut point = vec![0; point_size];
+        let mut decimated = InterleavedVecPointStorage::new(chunk.point_layout().clone());
+        decimated.resize((chunk.len() + 1) / 2);
+        for (target_index, source_index) in (0..chunk.len()).step_by(2).enumerate() {
+            chunk.get_raw_point(source_index, &mut point);
+            decimated
+                .get_raw_point_mut(target_index)
+                .copy_from_slice(&point);
+        }
+        Ok(Box::new(decimated))
+    }
+
+    #[test]
+    fn test_point_pipeline() -> Result<()> {
+        let mut reader = LASReader::from_path(get_test_las_path(0))?;
+        let mut writer = CollectingWriter {
+            points: InterleavedVecPointStorage::new(reader.get_default_point_layout().clone()),
+            was_flushed: false,
+        };
+
+        let mut chunk_sizes = vec![];
+        let points_written = reader
+            .pipeline()
+            .with_chunk_size(3)
+            .then(|chunk: &dyn PointBuffer| -> Result<Box<dyn PointBuffer>> {
+                chunk_sizes.push(chunk.len());
+                decimate(chunk)
+            })
+            .write_to(&mut writer)?;
+
+        assert_eq!(vec![3, 3, 3, 1], chunk_sizes);
+        assert_eq!(7, points_written);
+        assert!(writer.was_flushed);
+
+        let expected_positions = [0, 2, 3, 5, 6, 8, 9]
+            .iter()
+            .map(|idx| test_data_positions()[*idx])
+            .collect::<Vec<_>>();
+        let actual_positions = writer
+            .points
+            .iter_attribute::<Vector3<f64>>(&attributes::POSITION_3D)
+            .collect::<Vec<_>>();
+        assert_eq!(expected_positions, actual_positions);
+
+        Ok(())
+    }
+}
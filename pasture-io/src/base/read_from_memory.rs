@@ -0,0 +1,132 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, bail, Result};
+use pasture_core::containers::PointBuffer;
+
+use crate::{las::LASReader, ply::PlyReader, tiles3d::PntsReader};
+
+use super::PointReader;
+
+/// Offset of the point data record format within the LAS header
+const LAS_POINT_FORMAT_OFFSET: usize = 104;
+
+/// Point cloud file formats that `read_from_memory` can detect from their magic bytes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum DetectedFormat {
+    Las,
+    Laz,
+    Pnts,
+    Ply,
+    Pcd,
+}
+
+fn detect_format(bytes: &[u8]) -> Option<DetectedFormat> {
+    if bytes.starts_with(b"LASF") {
+        // LAZ files use the same magic bytes as LAS files, but set one of the two upper bits of the point data record
+        // format to indicate compression
+        let point_format = *bytes.get(LAS_POINT_FORMAT_OFFSET)?;
+        if point_format & 0b1100_0000 != 0 {
+            Some(DetectedFormat::Laz)
+        } else {
+            Some(DetectedFormat::Las)
+        }
+    } else if bytes.starts_with(b"pnts") {
+        Some(DetectedFormat::Pnts)
+    } else if bytes.starts_with(b"ply\n") || bytes.starts_with(b"ply\r\n") {
+        Some(DetectedFormat::Ply)
+    } else if bytes.starts_with(b"# .PCD") {
+        Some(DetectedFormat::Pcd)
+    } else {
+        None
+    }
+}
+
+/// Reads all remaining points from the given `reader` in its default `PointLayout`
+fn read_all_points(reader: &mut dyn PointReader) -> Result<Box<dyn PointBuffer>> {
+    let num_points = reader
+        .get_metadata()
+        .number_of_points()
+        .ok_or_else(|| anyhow!("Number of points could not be determined"))?;
+    reader.read(num_points)
+}
+
+/// Reads all points from the given in-memory `bytes` of a point cloud file. The file format is detected from the magic
+/// bytes at the start of `bytes`, so this works for data that does not come from a file, e.g. data received over the
+/// network. The points are returned in the default `PointLayout` of the detected format.
+///
+/// # Errors
+///
+/// If the format of `bytes` can't be detected from its magic bytes, if the format is detected but not supported by
+/// pasture, or if reading the points fails.
+pub fn read_from_memory(bytes: &[u8]) -> Result<Box<dyn PointBuffer>> {
+    let format = detect_format(bytes)
+        .ok_or_else(|| anyhow!("Could not detect the point cloud format from the magic bytes"))?;
+    match format {
+        DetectedFormat::Las | DetectedFormat::Laz => {
+            let mut reader =
+                LASReader::from_read(Cursor::new(bytes), format == DetectedFormat::Laz)?;
+            read_all_points(&mut reader)
+        }
+        DetectedFormat::Pnts => {
+            let mut reader = PntsReader::from_read(Cursor::new(bytes))?;
+            read_all_points(&mut reader)
+        }
+        DetectedFormat::Ply => {
+            let mut reader = PlyReader::from_read(Cursor::new(bytes))?;
+            read_all_points(&mut reader)
+        }
+        DetectedFormat::Pcd => {
+            bail!("Reading {:?} files is not supported", format)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::BufReader, path::PathBuf};
+
+    use pasture_core::{
+        containers::PointBufferExt, layout::attributes::POSITION_3D, nalgebra::Vector3,
+    };
+
+    use crate::las::{get_test_las_path, get_test_laz_path, test_data_positions};
+
+    use super::*;
+
+    #[test]
+    fn test_read_las_from_memory() -> Result<()> {
+        for path in [get_test_las_path(0), get_test_laz_path(0)].iter() {
+            let bytes = std::fs::read(path)?;
+            let points = read_from_memory(bytes.as_slice())?;
+            let positions = points
+                .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+                .collect::<Vec<_>>();
+            assert_eq!(test_data_positions(), positions);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_pnts_from_memory() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("resources/test/points.pnts");
+
+        let expected_points = {
+            let mut reader = PntsReader::from_read(BufReader::new(File::open(&test_file_path)?))?;
+            read_all_points(&mut reader)?
+        };
+
+        let bytes = std::fs::read(&test_file_path)?;
+        let points = read_from_memory(bytes.as_slice())?;
+        assert_eq!(expected_points.point_layout(), points.point_layout());
+        assert_eq!(expected_points.len(), points.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_from_memory_unknown_format() {
+        assert!(read_from_memory(b"FOOBAR").is_err());
+        assert!(read_from_memory(&[]).is_err());
+        assert!(read_from_memory(b"ply\nformat ascii 1.0\n").is_err());
+    }
+}
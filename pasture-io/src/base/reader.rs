@@ -1,8 +1,36 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use pasture_core::containers::{PointBuffer, PointBufferWriteable};
 use pasture_core::layout::PointLayout;
 use pasture_core::meta::Metadata;
 
+use super::PointPipeline;
+
+/// Default maximum number of points that a single `read` call of a reader may read, see e.g.
+/// [LASReader::set_max_points_per_read](crate::las::LASReader::set_max_points_per_read)
+pub const DEFAULT_MAX_POINTS_PER_READ: usize = 100_000_000;
+
+/// Clamps the number of points `count` that should be read to the `remaining_points` of a reader and checks that the
+/// result does not exceed `max_points_per_read`. Returns the number of points to read
+///
+/// # Errors
+///
+/// If more than `max_points_per_read` points would have to be read
+pub(crate) fn clamp_points_per_read(
+    count: usize,
+    remaining_points: usize,
+    max_points_per_read: usize,
+) -> Result<usize> {
+    let count = usize::min(count, remaining_points);
+    if count > max_points_per_read {
+        bail!(
+            "Reading {} points at once exceeds the maximum of {} points per read",
+            count,
+            max_points_per_read
+        );
+    }
+    Ok(count)
+}
+
 /// Base trait for all types that support reading point data
 pub trait PointReader {
     /// Read `count` points from this `PointReader`. Returns an opaque `PointBuffer` type filled with
@@ -22,4 +50,13 @@ pub trait PointReader {
     fn get_metadata(&self) -> &dyn Metadata;
     /// Returns the default `PointLayout` of the associated `PointReader`
     fn get_default_point_layout(&self) -> &PointLayout;
+
+    /// Creates a new `PointPipeline` that streams the points of this `PointReader` in chunks through a sequence of
+    /// processing stages
+    fn pipeline(&mut self) -> PointPipeline<'_>
+    where
+        Self: Sized,
+    {
+        PointPipeline::new(self)
+    }
 }
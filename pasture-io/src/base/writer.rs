@@ -1,10 +1,28 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use pasture_core::{containers::PointBuffer, layout::PointLayout};
 
 /// Base trait for all types that support writing point data
 pub trait PointWriter {
     /// Write the points in the given `PointBuffer` to the associated `PointWriter`.
     fn write(&mut self, points: &dyn PointBuffer) -> Result<()>;
+    /// Write the points in the given `PointBuffer`, whose `PointLayout` must exactly match the default `PointLayout` of
+    /// the associated `PointWriter` (see `get_default_point_layout`). Since no attributes have to be converted, writers
+    /// can implement this by copying the raw attribute data without any per-point conversion. The default
+    /// implementation checks the layout and then calls `write`.
+    ///
+    /// # Errors
+    ///
+    /// If the `PointLayout` of `points` does not match the default `PointLayout`, or if writing fails
+    fn write_layout_compatible(&mut self, points: &dyn PointBuffer) -> Result<()> {
+        if points.point_layout() != self.get_default_point_layout() {
+            bail!(
+                "PointLayout of buffer ({}) does not match the default PointLayout of the writer ({})",
+                points.point_layout(),
+                self.get_default_point_layout()
+            );
+        }
+        self.write(points)
+    }
     /// Flush this `PointWriter`, ensuring that all points are written to their destination and that all required
     /// metadata is written as well
     fn flush(&mut self) -> Result<()>;
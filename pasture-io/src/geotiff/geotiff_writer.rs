@@ -0,0 +1,373 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+use byteorder::{LittleEndian, WriteBytesExt};
+use pasture_core::math::Raster;
+
+/// Size of the TIFF file header in bytes
+const TIFF_HEADER_SIZE: usize = 8;
+/// Size of a single entry in a TIFF image file directory (IFD) in bytes
+const IFD_ENTRY_SIZE: usize = 12;
+
+// TIFF field types
+const TIFF_TYPE_ASCII: u16 = 2;
+const TIFF_TYPE_SHORT: u16 = 3;
+const TIFF_TYPE_LONG: u16 = 4;
+const TIFF_TYPE_DOUBLE: u16 = 12;
+
+// TIFF and GeoTIFF tags
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC_INTERPRETATION: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_ROWS_PER_STRIP: u16 = 278;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_PLANAR_CONFIGURATION: u16 = 284;
+const TAG_SAMPLE_FORMAT: u16 = 339;
+const TAG_MODEL_PIXEL_SCALE: u16 = 33550;
+const TAG_MODEL_TIEPOINT: u16 = 33922;
+const TAG_GEO_KEY_DIRECTORY: u16 = 34735;
+/// Non-standard tag that GDAL (and thus QGIS) uses for the nodata value
+const TAG_GDAL_NODATA: u16 = 42113;
+
+// GeoTIFF keys and their values
+const GEO_KEY_MODEL_TYPE: u16 = 1024;
+const GEO_KEY_RASTER_TYPE: u16 = 1025;
+const GEO_KEY_GEOGRAPHIC_TYPE: u16 = 2048;
+const GEO_KEY_PROJECTED_CS_TYPE: u16 = 3072;
+const MODEL_TYPE_PROJECTED: u16 = 1;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_AREA: u16 = 1;
+
+/// Coordinate reference system of a GeoTIFF file, identified by its EPSG code
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Crs {
+    /// A projected coordinate system, e.g. `Projected(25832)` for ETRS89 / UTM zone 32N
+    Projected(u16),
+    /// A geographic coordinate system, e.g. `Geographic(4326)` for WGS 84
+    Geographic(u16),
+}
+
+/// A single entry of a TIFF image file directory
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    /// The encoded values of this entry
+    data: Vec<u8>,
+}
+
+impl IfdEntry {
+    fn shorts(tag: u16, values: &[u16]) -> Self {
+        let mut data = vec![];
+        for value in values {
+            data.write_u16::<LittleEndian>(*value).unwrap();
+        }
+        Self {
+            tag,
+            field_type: TIFF_TYPE_SHORT,
+            count: values.len() as u32,
+            data,
+        }
+    }
+
+    fn long(tag: u16, value: u32) -> Self {
+        Self {
+            tag,
+            field_type: TIFF_TYPE_LONG,
+            count: 1,
+            data: value.to_le_bytes().to_vec(),
+        }
+    }
+
+    fn doubles(tag: u16, values: &[f64]) -> Self {
+        let mut data = vec![];
+        for value in values {
+            data.write_f64::<LittleEndian>(*value).unwrap();
+        }
+        Self {
+            tag,
+            field_type: TIFF_TYPE_DOUBLE,
+            count: values.len() as u32,
+            data,
+        }
+    }
+
+    fn ascii(tag: u16, value: &str) -> Self {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        Self {
+            tag,
+            field_type: TIFF_TYPE_ASCII,
+            count: data.len() as u32,
+            data,
+        }
+    }
+}
+
+/// Writes the given `raster` as a single-band GeoTIFF file with 32-bit floating point samples to `path`. The georeferencing
+/// of the raster (its origin and cell size) is stored in the `ModelTiepointTag` and `ModelPixelScaleTag`, and the optional
+/// `crs` is stored in the GeoKey directory. Cells with the nodata value of the raster are marked as nodata using the
+/// `GDAL_NODATA` tag, which is understood by GDAL-based applications like QGIS.
+///
+/// ```no_run
+/// # use pasture_core::math::{Raster, RASTER_NODATA};
+/// # use pasture_core::nalgebra::Vector2;
+/// # use pasture_io::geotiff::*;
+/// let raster = Raster::new(Vector2::new(350000.0, 5600000.0), 1.0, 100, 100, RASTER_NODATA);
+/// write_geotiff(&raster, "dem.tif", Some(Crs::Projected(25832))).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// If the file at `path` can't be created, if the raster is empty, or if the raster is too large for a (non-BigTIFF) TIFF
+/// file, i.e. larger than 4GiB
+pub fn write_geotiff<P: AsRef<Path>>(
+    raster: &Raster<f32>,
+    path: P,
+    crs: Option<Crs>,
+) -> Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    write_geotiff_to(raster, writer, crs)
+}
+
+/// Like [write_geotiff], but writes the GeoTIFF data to the given `writer`
+pub fn write_geotiff_to<W: Write>(
+    raster: &Raster<f32>,
+    mut writer: W,
+    crs: Option<Crs>,
+) -> Result<()> {
+    if raster.width == 0 || raster.height == 0 {
+        bail!("Can't write an empty raster to a GeoTIFF file");
+    }
+    if raster.values.len() != raster.width * raster.height {
+        bail!(
+            "Raster has {} values but its dimensions are {}x{}",
+            raster.values.len(),
+            raster.width,
+            raster.height
+        );
+    }
+
+    let image_size = raster.values.len() * std::mem::size_of::<f32>();
+    // Leave some room for the header and the IFD, which are much smaller than this
+    if image_size > u32::MAX as usize - 4096 {
+        bail!("Raster is too large for a TIFF file");
+    }
+    if raster.width > u32::MAX as usize || raster.height > u32::MAX as usize {
+        bail!("Raster dimensions are too large for a TIFF file");
+    }
+
+    let mut geo_keys = vec![(GEO_KEY_RASTER_TYPE, RASTER_PIXEL_IS_AREA)];
+    match crs {
+        Some(Crs::Projected(epsg)) => {
+            geo_keys.push((GEO_KEY_MODEL_TYPE, MODEL_TYPE_PROJECTED));
+            geo_keys.push((GEO_KEY_PROJECTED_CS_TYPE, epsg));
+        }
+        Some(Crs::Geographic(epsg)) => {
+            geo_keys.push((GEO_KEY_MODEL_TYPE, MODEL_TYPE_GEOGRAPHIC));
+            geo_keys.push((GEO_KEY_GEOGRAPHIC_TYPE, epsg));
+        }
+        None => (),
+    }
+    geo_keys.sort_unstable();
+    // Header of the GeoKey directory: Version 1.1.0, followed by the number of keys and the keys themselves
+    let mut geo_key_directory = vec![1, 1, 0, geo_keys.len() as u16];
+    for (key, value) in geo_keys {
+        geo_key_directory.extend_from_slice(&[key, 0, 1, value]);
+    }
+
+    let image_offset = TIFF_HEADER_SIZE as u32;
+    // Entries must be sorted by tag
+    let entries = vec![
+        IfdEntry::long(TAG_IMAGE_WIDTH, raster.width as u32),
+        IfdEntry::long(TAG_IMAGE_LENGTH, raster.height as u32),
+        IfdEntry::shorts(TAG_BITS_PER_SAMPLE, &[32]),
+        // No compression
+        IfdEntry::shorts(TAG_COMPRESSION, &[1]),
+        // BlackIsZero
+        IfdEntry::shorts(TAG_PHOTOMETRIC_INTERPRETATION, &[1]),
+        IfdEntry::long(TAG_STRIP_OFFSETS, image_offset),
+        IfdEntry::shorts(TAG_SAMPLES_PER_PIXEL, &[1]),
+        IfdEntry::long(TAG_ROWS_PER_STRIP, raster.height as u32),
+        IfdEntry::long(TAG_STRIP_BYTE_COUNTS, image_size as u32),
+        // Chunky
+        IfdEntry::shorts(TAG_PLANAR_CONFIGURATION, &[1]),
+        // IEEE floating point
+        IfdEntry::shorts(TAG_SAMPLE_FORMAT, &[3]),
+        IfdEntry::doubles(
+            TAG_MODEL_PIXEL_SCALE,
+            &[raster.cell_size, raster.cell_size, 0.0],
+        ),
+        // Maps the upper left corner of the raster to the origin
+        IfdEntry::doubles(
+            TAG_MODEL_TIEPOINT,
+            &[0.0, 0.0, 0.0, raster.origin.x, raster.origin.y, 0.0],
+        ),
+        IfdEntry::shorts(TAG_GEO_KEY_DIRECTORY, &geo_key_directory),
+        IfdEntry::ascii(TAG_GDAL_NODATA, &raster.nodata.to_string()),
+    ];
+
+    let ifd_offset = TIFF_HEADER_SIZE + image_size;
+    let ifd_size = 2 + entries.len() * IFD_ENTRY_SIZE + 4;
+    let mut data_offset = ifd_offset + ifd_size;
+
+    let mut bytes = Vec::with_capacity(data_offset);
+    bytes.extend_from_slice(b"II");
+    bytes.write_u16::<LittleEndian>(42)?;
+    bytes.write_u32::<LittleEndian>(ifd_offset as u32)?;
+    for value in raster.values.iter() {
+        bytes.write_f32::<LittleEndian>(*value)?;
+    }
+
+    // Values that don't fit into the 4 bytes of an IFD entry are stored after the IFD at word boundaries
+    let mut entry_data = vec![];
+    bytes.write_u16::<LittleEndian>(entries.len() as u16)?;
+    for entry in entries.iter() {
+        bytes.write_u16::<LittleEndian>(entry.tag)?;
+        bytes.write_u16::<LittleEndian>(entry.field_type)?;
+        bytes.write_u32::<LittleEndian>(entry.count)?;
+        if entry.data.len() <= 4 {
+            let mut value = [0; 4];
+            value[..entry.data.len()].copy_from_slice(&entry.data);
+            bytes.extend_from_slice(&value);
+        } else {
+            bytes.write_u32::<LittleEndian>(data_offset as u32)?;
+            entry_data.extend_from_slice(&entry.data);
+            if entry.data.len() % 2 != 0 {
+                entry_data.push(0);
+            }
+            data_offset = ifd_offset + ifd_size + entry_data.len();
+        }
+    }
+    // There is only one IFD
+    bytes.write_u32::<LittleEndian>(0)?;
+    bytes.extend_from_slice(&entry_data);
+
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, convert::TryInto, path::PathBuf};
+
+    use super::*;
+    use byteorder::ReadBytesExt;
+    use pasture_core::{math::RASTER_NODATA, nalgebra::Vector2};
+    use scopeguard::defer;
+
+    /// Parses the first IFD of the given little-endian TIFF file and returns the type, count and raw data of each entry
+    fn read_ifd(bytes: &[u8]) -> HashMap<u16, (u16, u32, Vec<u8>)> {
+        assert_eq!(b"II", &bytes[0..2]);
+        assert_eq!(42, (&bytes[2..4]).read_u16::<LittleEndian>().unwrap());
+        let ifd_offset = (&bytes[4..8]).read_u32::<LittleEndian>().unwrap() as usize;
+        let num_entries = (&bytes[ifd_offset..]).read_u16::<LittleEndian>().unwrap() as usize;
+
+        let mut entries = HashMap::new();
+        for entry_index in 0..num_entries {
+            let mut entry = &bytes[ifd_offset + 2 + entry_index * IFD_ENTRY_SIZE..];
+            let tag = entry.read_u16::<LittleEndian>().unwrap();
+            let field_type = entry.read_u16::<LittleEndian>().unwrap();
+            let count = entry.read_u32::<LittleEndian>().unwrap();
+            let type_size = match field_type {
+                TIFF_TYPE_ASCII => 1,
+                TIFF_TYPE_SHORT => 2,
+                TIFF_TYPE_LONG => 4,
+                TIFF_TYPE_DOUBLE => 8,
+                other => panic!("Unexpected TIFF field type {}", other),
+            };
+            let size = type_size * count as usize;
+            let data = if size <= 4 {
+                entry[..size].to_vec()
+            } else {
+                let offset = entry.read_u32::<LittleEndian>().unwrap() as usize;
+                bytes[offset..offset + size].to_vec()
+            };
+            entries.insert(tag, (field_type, count, data));
+        }
+        entries
+    }
+
+    fn u32_value(entries: &HashMap<u16, (u16, u32, Vec<u8>)>, tag: u16) -> u32 {
+        u32::from_le_bytes(entries[&tag].2[..4].try_into().unwrap())
+    }
+
+    fn doubles(entries: &HashMap<u16, (u16, u32, Vec<u8>)>, tag: u16) -> Vec<f64> {
+        entries[&tag]
+            .2
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    fn shorts(entries: &HashMap<u16, (u16, u32, Vec<u8>)>, tag: u16) -> Vec<u16> {
+        entries[&tag]
+            .2
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_write_geotiff() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_write_geotiff.tif");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut raster = Raster::new(Vector2::new(350000.5, 5600000.25), 2.0, 3, 2, RASTER_NODATA);
+        raster.values = vec![1.0, 2.0, RASTER_NODATA, 4.5, -5.0, 6.0];
+        write_geotiff(&raster, &test_file_path, Some(Crs::Projected(25832)))?;
+
+        let bytes = std::fs::read(&test_file_path)?;
+        let entries = read_ifd(&bytes);
+
+        assert_eq!(3, u32_value(&entries, TAG_IMAGE_WIDTH));
+        assert_eq!(2, u32_value(&entries, TAG_IMAGE_LENGTH));
+        assert_eq!(vec![32], shorts(&entries, TAG_BITS_PER_SAMPLE));
+        assert_eq!(vec![3], shorts(&entries, TAG_SAMPLE_FORMAT));
+        assert_eq!(
+            vec![2.0, 2.0, 0.0],
+            doubles(&entries, TAG_MODEL_PIXEL_SCALE)
+        );
+        assert_eq!(
+            vec![0.0, 0.0, 0.0, 350000.5, 5600000.25, 0.0],
+            doubles(&entries, TAG_MODEL_TIEPOINT)
+        );
+        assert_eq!(b"-9999\0".to_vec(), entries[&TAG_GDAL_NODATA].2);
+
+        let geo_keys = shorts(&entries, TAG_GEO_KEY_DIRECTORY);
+        assert_eq!(3, geo_keys[3]);
+        assert!(geo_keys[4..]
+            .chunks_exact(4)
+            .any(|key| key == [GEO_KEY_PROJECTED_CS_TYPE, 0, 1, 25832]));
+
+        let image_offset = u32_value(&entries, TAG_STRIP_OFFSETS) as usize;
+        let image_size = u32_value(&entries, TAG_STRIP_BYTE_COUNTS) as usize;
+        assert_eq!(raster.values.len() * 4, image_size);
+        let values = bytes[image_offset..image_offset + image_size]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(raster.values, values);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_empty_geotiff_fails() {
+        let raster = Raster::new(Vector2::new(0.0, 0.0), 1.0, 0, 0, RASTER_NODATA);
+        assert!(write_geotiff_to(&raster, vec![], None).is_err());
+    }
+}
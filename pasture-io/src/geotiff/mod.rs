@@ -0,0 +1,2 @@
+mod geotiff_writer;
+pub use self::geotiff_writer::*;
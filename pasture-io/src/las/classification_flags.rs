@@ -0,0 +1,50 @@
+pub use pasture_core::layout::classification_flags::*;
+
+/// Number of bits of the classification byte that store the classification in the regular LAS point formats (0 to 5)
+const LEGACY_CLASSIFICATION_BITS: u8 = 5;
+const LEGACY_CLASSIFICATION_MASK: u8 = (1 << LEGACY_CLASSIFICATION_BITS) - 1;
+
+/// Splits the classification byte of the regular LAS point formats (0 to 5) into the classification value (lower 5 bits)
+/// and the classification flags (upper 3 bits). The flags are returned in the same bit order as the `CLASSIFICATION_FLAGS`
+/// attribute of the extended LAS point formats
+pub(crate) fn split_legacy_classification(classification_byte: u8) -> (u8, u8) {
+    (
+        classification_byte & LEGACY_CLASSIFICATION_MASK,
+        classification_byte >> LEGACY_CLASSIFICATION_BITS,
+    )
+}
+
+/// Packs the given `classification` and `classification_flags` into the classification byte of the regular LAS point formats
+/// (0 to 5). Since the regular formats only have room for 32 classes and three flags, higher classes and the overlap flag are
+/// discarded
+pub(crate) fn merge_legacy_classification(classification: u8, classification_flags: u8) -> u8 {
+    (classification & LEGACY_CLASSIFICATION_MASK)
+        | ((classification_flags & 0b111) << LEGACY_CLASSIFICATION_BITS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_classification_roundtrip() {
+        let classification_byte = 0b1000_0010;
+        let (classification, flags) = split_legacy_classification(classification_byte);
+        assert_eq!(2, classification);
+        assert_eq!(CLASSIFICATION_FLAG_WITHHELD, flags);
+        assert_eq!(
+            classification_byte,
+            merge_legacy_classification(classification, flags)
+        );
+
+        assert_eq!(
+            0b0110_0001,
+            merge_legacy_classification(
+                1,
+                CLASSIFICATION_FLAG_SYNTHETIC
+                    | CLASSIFICATION_FLAG_KEY_POINT
+                    | CLASSIFICATION_FLAG_OVERLAP
+            )
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    io::{Cursor, Read, Write},
+};
+
+use anyhow::{anyhow, bail, Result};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use las::{point::Format, Builder, Header, Vlr};
+use pasture_core::layout::{
+    conversion::scaled_converter, Endianness, FieldAlignment, PointAttributeDataType,
+    PointAttributeDefinition, PointLayout,
+};
+
+use super::{fixed_str_to_string, point_layout_from_las_point_format, string_to_fixed};
+
+/// User ID of the VLR that contains the extra bytes records of a LAS file
+pub const EXTRA_BYTES_USER_ID: &str = "LASF_Spec";
+/// Record ID of the VLR that contains the extra bytes records of a LAS file
+pub const EXTRA_BYTES_RECORD_ID: u16 = 4;
+/// Size of a single extra bytes record within the extra bytes VLR, in bytes
+pub const EXTRA_BYTES_RECORD_SIZE: usize = 192;
+
+const OPTION_SCALE_BIT: u8 = 1 << 3;
+const OPTION_OFFSET_BIT: u8 = 1 << 4;
+
+/// An extra bytes record as defined by the LAS 1.4 specification. Each record describes one custom attribute that is
+/// stored in the extra bytes at the end of the LAS point records. The records of a file are stored in order of their
+/// attributes within the extra bytes in the VLR with user ID [EXTRA_BYTES_USER_ID] and record ID
+/// [EXTRA_BYTES_RECORD_ID].
+///
+/// The fields mirror the binary layout of the record. `no_data`, `min` and `max` store a value of the datatype of the
+/// record in little-endian byte order, and the reserved and deprecated fields of the binary record are omitted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtraBytesRecord {
+    /// The LAS datatype of the attribute. 0 means that the attribute consists of `options` undocumented bytes, 1 to 10
+    /// are the scalar types `u8`, `i8`, `u16`, `i16`, `u32`, `i32`, `u64`, `i64`, `f32` and `f64`, and 11 to 30 are
+    /// the deprecated arrays of two and three of these scalar types
+    pub data_type: u8,
+    /// Bit field that defines which of `no_data`, `min`, `max`, `scale` and `offset` are valid
+    pub options: u8,
+    /// The name of the attribute, see [name](ExtraBytesRecord::name)
+    pub name: [i8; 32],
+    pub no_data: [u8; 8],
+    pub min: [u8; 8],
+    pub max: [u8; 8],
+    pub scale: f64,
+    pub offset: f64,
+    /// The description of the attribute, see [description](ExtraBytesRecord::description)
+    pub description: [i8; 32],
+}
+
+impl ExtraBytesRecord {
+    /// Creates a new `ExtraBytesRecord` for an attribute with the given `name` and `datatype`. The record has no
+    /// scale, offset or description, and none of its optional values are set. Names longer than 32 bytes are
+    /// truncated
+    ///
+    /// # Errors
+    ///
+    /// If `datatype` is not a scalar datatype, as only these can be stored in the extra bytes of a LAS point record
+    pub fn new(name: &str, datatype: PointAttributeDataType) -> Result<Self> {
+        let data_type = (1..=10)
+            .find(|&data_type| scalar_datatype(data_type) == Some(datatype))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Datatype {} of attribute {} can't be stored in LAS extra bytes",
+                    datatype,
+                    name
+                )
+            })?;
+        Ok(Self {
+            data_type,
+            options: 0,
+            name: string_to_fixed(name),
+            no_data: [0; 8],
+            min: [0; 8],
+            max: [0; 8],
+            scale: 0.0,
+            offset: 0.0,
+            description: [0; 32],
+        })
+    }
+
+    /// Parses a single `ExtraBytesRecord` from the first [EXTRA_BYTES_RECORD_SIZE] bytes of `data`
+    ///
+    /// # Errors
+    ///
+    /// If `data` is shorter than [EXTRA_BYTES_RECORD_SIZE]
+    pub fn from_raw(data: &[u8]) -> Result<Self> {
+        if data.len() < EXTRA_BYTES_RECORD_SIZE {
+            bail!(
+                "An extra bytes record requires {} bytes, but only {} bytes were given",
+                EXTRA_BYTES_RECORD_SIZE,
+                data.len()
+            );
+        }
+
+        fn skip(reader: &mut Cursor<&[u8]>, count: u64) {
+            reader.set_position(reader.position() + count);
+        }
+
+        fn read_bytes(reader: &mut Cursor<&[u8]>) -> Result<[u8; 8]> {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            Ok(bytes)
+        }
+
+        let mut reader = Cursor::new(data);
+        // Reserved
+        skip(&mut reader, 2);
+        let data_type = reader.read_u8()?;
+        let options = reader.read_u8()?;
+        let mut name = [0; 32];
+        reader.read_i8_into(&mut name)?;
+        // Unused
+        skip(&mut reader, 4);
+        let no_data = read_bytes(&mut reader)?;
+        // Each value is followed by the deprecated values for the second and third array component
+        skip(&mut reader, 16);
+        let min = read_bytes(&mut reader)?;
+        skip(&mut reader, 16);
+        let max = read_bytes(&mut reader)?;
+        skip(&mut reader, 16);
+        let scale = reader.read_f64::<LittleEndian>()?;
+        skip(&mut reader, 16);
+        let offset = reader.read_f64::<LittleEndian>()?;
+        skip(&mut reader, 16);
+        let mut description = [0; 32];
+        reader.read_i8_into(&mut description)?;
+
+        Ok(Self {
+            data_type,
+            options,
+            name,
+            no_data,
+            min,
+            max,
+            scale,
+            offset,
+            description,
+        })
+    }
+
+    /// Serializes this record into its binary representation of [EXTRA_BYTES_RECORD_SIZE] bytes, as it is stored in
+    /// the extra bytes VLR. This is the inverse of [from_raw](ExtraBytesRecord::from_raw)
+    pub fn to_raw(&self) -> Vec<u8> {
+        fn write_value(writer: &mut Vec<u8>, value: &[u8]) {
+            writer.write_all(value).unwrap();
+            // The deprecated values for the second and third array component are always zero
+            writer.write_all(&[0; 16]).unwrap();
+        }
+
+        let mut data = Vec::with_capacity(EXTRA_BYTES_RECORD_SIZE);
+        // Reserved
+        data.write_all(&[0; 2]).unwrap();
+        data.write_u8(self.data_type).unwrap();
+        data.write_u8(self.options).unwrap();
+        for &c in self.name.iter() {
+            data.write_i8(c).unwrap();
+        }
+        // Unused
+        data.write_all(&[0; 4]).unwrap();
+        write_value(&mut data, &self.no_data);
+        write_value(&mut data, &self.min);
+        write_value(&mut data, &self.max);
+        write_value(&mut data, &self.scale.to_le_bytes());
+        write_value(&mut data, &self.offset.to_le_bytes());
+        for &c in self.description.iter() {
+            data.write_i8(c).unwrap();
+        }
+        data
+    }
+
+    /// Returns the name of the attribute described by this record
+    pub fn name(&self) -> String {
+        fixed_str_to_string(&self.name)
+    }
+
+    /// Returns the description of the attribute described by this record
+    pub fn description(&self) -> String {
+        fixed_str_to_string(&self.description)
+    }
+
+    /// Returns `true` if the raw values of the attribute have to be multiplied with `scale`
+    pub fn has_scale(&self) -> bool {
+        self.options & OPTION_SCALE_BIT != 0
+    }
+
+    /// Returns `true` if `offset` has to be added to the raw values of the attribute
+    pub fn has_offset(&self) -> bool {
+        self.options & OPTION_OFFSET_BIT != 0
+    }
+
+    /// Returns the size of the attribute within the extra bytes of a point record, in bytes
+    ///
+    /// # Errors
+    ///
+    /// If `data_type` is no valid LAS extra bytes datatype
+    pub fn size(&self) -> Result<usize> {
+        match self.data_type {
+            0 => Ok(self.options as usize),
+            1..=30 => {
+                let scalar_type = scalar_datatype((self.data_type - 1) % 10 + 1).unwrap();
+                let components = (self.data_type as usize - 1) / 10 + 1;
+                Ok(scalar_type.size() as usize * components)
+            }
+            other => bail!("Invalid extra bytes datatype {}", other),
+        }
+    }
+
+    /// Returns the `PointAttributeDefinition` that the attribute described by this record is read into. The attribute
+    /// has the name of this record and the matching primitive datatype. If the record defines a scale or an offset,
+    /// the values are read as scaled `f64` values instead. Returns `None` for undocumented extra bytes and for the
+    /// deprecated array datatypes, which are not supported
+    pub fn as_point_attribute(&self) -> Option<PointAttributeDefinition> {
+        let datatype = scalar_datatype(self.data_type)?;
+        let name = self.name();
+        if name.is_empty() {
+            return None;
+        }
+        if self.has_scale() || self.has_offset() {
+            Some(PointAttributeDefinition::custom_dynamic(
+                &name,
+                PointAttributeDataType::F64,
+            ))
+        } else {
+            Some(PointAttributeDefinition::custom_dynamic(&name, datatype))
+        }
+    }
+
+    /// Decodes the raw little-endian value of the attribute in `raw_value` into the datatype of
+    /// [as_point_attribute](ExtraBytesRecord::as_point_attribute) in native byte order, applying scale and offset
+    fn decode(&self, raw_value: &[u8]) -> Vec<u8> {
+        let datatype = scalar_datatype(self.data_type).unwrap();
+        let mut value = raw_value.to_vec();
+        datatype.convert_endianness(&mut value, Endianness::Little, Endianness::NATIVE);
+        if !self.has_scale() && !self.has_offset() {
+            return value;
+        }
+
+        let scale = if self.has_scale() { self.scale } else { 1.0 };
+        let offset = if self.has_offset() { self.offset } else { 0.0 };
+        let converter = scaled_converter(datatype, scale, offset).unwrap();
+        let mut scaled_value = vec![0; std::mem::size_of::<f64>()];
+        unsafe {
+            converter.convert(&value, &mut scaled_value);
+        }
+        scaled_value
+    }
+
+    /// Encodes a `value` in the datatype of [as_point_attribute](ExtraBytesRecord::as_point_attribute) and in native
+    /// byte order into the raw little-endian value of the attribute. This is the inverse of `decode`
+    fn encode(&self, value: &[u8]) -> Vec<u8> {
+        let datatype = scalar_datatype(self.data_type).unwrap();
+        let mut raw_value = if !self.has_scale() && !self.has_offset() {
+            value.to_vec()
+        } else {
+            let scale = if self.has_scale() { self.scale } else { 1.0 };
+            let offset = if self.has_offset() { self.offset } else { 0.0 };
+            let scaled_value = f64::from_ne_bytes(value.try_into().unwrap());
+            f64_as_scalar((scaled_value - offset) / scale, datatype)
+        };
+        datatype.convert_endianness(&mut raw_value, Endianness::NATIVE, Endianness::Little);
+        raw_value
+    }
+}
+
+/// Returns the scalar datatype for the LAS extra bytes datatype `data_type`, or `None` if `data_type` is no scalar type
+fn scalar_datatype(data_type: u8) -> Option<PointAttributeDataType> {
+    match data_type {
+        1 => Some(PointAttributeDataType::U8),
+        2 => Some(PointAttributeDataType::I8),
+        3 => Some(PointAttributeDataType::U16),
+        4 => Some(PointAttributeDataType::I16),
+        5 => Some(PointAttributeDataType::U32),
+        6 => Some(PointAttributeDataType::I32),
+        7 => Some(PointAttributeDataType::U64),
+        8 => Some(PointAttributeDataType::I64),
+        9 => Some(PointAttributeDataType::F32),
+        10 => Some(PointAttributeDataType::F64),
+        _ => None,
+    }
+}
+
+/// Converts `value` into a scalar value of the given `datatype` and returns its native-endian bytes. Integer values are
+/// rounded to the nearest integer
+fn f64_as_scalar(value: f64, datatype: PointAttributeDataType) -> Vec<u8> {
+    let rounded = value.round();
+    match datatype {
+        PointAttributeDataType::U8 => vec![rounded as u8],
+        PointAttributeDataType::I8 => vec![rounded as i8 as u8],
+        PointAttributeDataType::U16 => (rounded as u16).to_ne_bytes().to_vec(),
+        PointAttributeDataType::I16 => (rounded as i16).to_ne_bytes().to_vec(),
+        PointAttributeDataType::U32 => (rounded as u32).to_ne_bytes().to_vec(),
+        PointAttributeDataType::I32 => (rounded as i32).to_ne_bytes().to_vec(),
+        PointAttributeDataType::U64 => (rounded as u64).to_ne_bytes().to_vec(),
+        PointAttributeDataType::I64 => (rounded as i64).to_ne_bytes().to_vec(),
+        PointAttributeDataType::F32 => (value as f32).to_ne_bytes().to_vec(),
+        PointAttributeDataType::F64 => value.to_ne_bytes().to_vec(),
+        other => panic!("Datatype {} is no extra bytes datatype", other),
+    }
+}
+
+/// Returns all extra bytes records in the VLRs and EVLRs of the given LAS `header`, in the order in which their
+/// attributes are stored within the extra bytes of the point records
+///
+/// # Errors
+///
+/// If the size of an extra bytes VLR is not a multiple of [EXTRA_BYTES_RECORD_SIZE]
+pub fn extra_bytes_records(header: &Header) -> Result<Vec<ExtraBytesRecord>> {
+    let mut records = vec![];
+    for vlr in header.vlrs().iter().chain(header.evlrs().iter()) {
+        if vlr.user_id != EXTRA_BYTES_USER_ID || vlr.record_id != EXTRA_BYTES_RECORD_ID {
+            continue;
+        }
+        if vlr.data.len() % EXTRA_BYTES_RECORD_SIZE != 0 {
+            bail!(
+                "Size of the extra bytes VLR ({} bytes) is not a multiple of the size of an extra bytes record",
+                vlr.data.len()
+            );
+        }
+        for record_data in vlr.data.chunks_exact(EXTRA_BYTES_RECORD_SIZE) {
+            records.push(ExtraBytesRecord::from_raw(record_data)?);
+        }
+    }
+    Ok(records)
+}
+
+/// A single attribute within the extra bytes of a LAS point record
+pub(crate) struct ExtraBytesField {
+    record: ExtraBytesRecord,
+    /// The attribute that this field is read into, or `None` if the field is skipped
+    pub attribute: Option<PointAttributeDefinition>,
+    /// Offset of this field within the extra bytes of a point record
+    offset: usize,
+    size: usize,
+}
+
+impl ExtraBytesField {
+    /// Reads the value of this field from the given `extra_bytes` of a point record, in the datatype of `attribute`
+    /// and in native byte order
+    pub fn read_value(&self, extra_bytes: &[u8]) -> Vec<u8> {
+        self.record
+            .decode(&extra_bytes[self.offset..self.offset + self.size])
+    }
+
+    /// Writes the given `value` of this field into the given `extra_bytes` of a point record. `value` has to be in the
+    /// datatype of `attribute` and in native byte order
+    pub fn write_value(&self, value: &[u8], extra_bytes: &mut [u8]) {
+        let raw_value = self.record.encode(value);
+        extra_bytes[self.offset..self.offset + self.size].copy_from_slice(&raw_value);
+    }
+}
+
+/// Returns the fields within the extra bytes of the point records of the LAS file with the given `header`. Fields that
+/// are not supported by pasture, as well as fields whose name clashes with a standard LAS attribute or a previous field,
+/// are skipped during reading and thus have no attribute
+///
+/// # Errors
+///
+/// If the extra bytes records can't be parsed or describe more bytes than there are extra bytes in the point records
+pub(crate) fn extra_bytes_fields(header: &Header) -> Result<Vec<ExtraBytesField>> {
+    let format = header.point_format();
+    let standard_layout = point_layout_from_las_point_format(format)?;
+
+    let mut fields = vec![];
+    let mut used_names = HashSet::new();
+    let mut offset = 0;
+    for record in extra_bytes_records(header)? {
+        let size = record.size()?;
+        let attribute = record.as_point_attribute().filter(|attribute| {
+            !standard_layout.has_attribute_with_name(attribute.name())
+                && used_names.insert(attribute.name())
+        });
+        fields.push(ExtraBytesField {
+            record,
+            attribute,
+            offset,
+            size,
+        });
+        offset += size;
+    }
+
+    if offset > format.extra_bytes as usize {
+        bail!(
+            "The extra bytes records describe {} bytes, but the point records only contain {} extra bytes",
+            offset,
+            format.extra_bytes
+        );
+    }
+    Ok(fields)
+}
+
+/// Returns the default `PointLayout` for points of the LAS file with the given `header`. This is the layout for the
+/// point format of the file (see [point_layout_from_las_point_format]), followed by the attributes that are described
+/// by the extra bytes records of the file (see [ExtraBytesRecord::as_point_attribute]). Extra bytes that pasture does
+/// not support are not part of the layout.
+///
+/// # Errors
+///
+/// If the point format of `header` is invalid, or if the extra bytes records of `header` are invalid
+pub fn point_layout_from_las_header(header: &Header) -> Result<PointLayout> {
+    let mut layout = point_layout_from_las_point_format(header.point_format())?;
+    for field in extra_bytes_fields(header)? {
+        if let Some(attribute) = field.attribute {
+            layout.add_attribute(attribute, FieldAlignment::Packed(1));
+        }
+    }
+    Ok(layout)
+}
+
+/// Returns a copy of the given LAS `header` that stores all custom attributes of the given `layout` in the extra bytes
+/// of its point records. Custom attributes are all attributes that are not part of any of the LAS point formats.
+/// They are stored in the order in which they appear in `layout`, tightly packed and in little-endian byte order. An
+/// extra bytes VLR that describes these attributes is added to the header, and the size of the extra bytes in the point
+/// format of the header is set accordingly. If `layout` contains no custom attributes, the header is returned unchanged
+///
+/// # Errors
+///
+/// If `header` already defines extra bytes, or if a custom attribute has a datatype that can't be stored in the extra
+/// bytes (see [ExtraBytesRecord::new])
+pub fn las_header_with_extra_bytes(header: &Header, layout: &PointLayout) -> Result<Header> {
+    let mut standard_attribute_names = HashSet::new();
+    for format in 0..=10 {
+        let standard_layout = point_layout_from_las_point_format(&Format::new(format)?)?;
+        standard_attribute_names.extend(
+            standard_layout
+                .attributes()
+                .map(|attribute| attribute.name()),
+        );
+    }
+
+    let records = layout
+        .attributes()
+        .filter(|attribute| !standard_attribute_names.contains(attribute.name()))
+        .map(|attribute| ExtraBytesRecord::new(attribute.name(), attribute.datatype()))
+        .collect::<Result<Vec<_>>>()?;
+    if records.is_empty() {
+        return Ok(header.clone());
+    }
+    if header.point_format().extra_bytes != 0 || !extra_bytes_records(header)?.is_empty() {
+        bail!("Can't add extra bytes to a LAS header that already defines extra bytes");
+    }
+
+    let mut extra_bytes_size = 0;
+    let mut vlr_data = Vec::with_capacity(records.len() * EXTRA_BYTES_RECORD_SIZE);
+    for record in records.iter() {
+        extra_bytes_size += record.size()?;
+        vlr_data.extend(record.to_raw());
+    }
+
+    let mut builder = Builder::from(header.clone());
+    builder.point_format.extra_bytes = extra_bytes_size as u16;
+    builder.vlrs.push(Vlr {
+        user_id: EXTRA_BYTES_USER_ID.to_owned(),
+        record_id: EXTRA_BYTES_RECORD_ID,
+        description: "Extra bytes".to_owned(),
+        data: vlr_data,
+    });
+    Ok(builder.into_header()?)
+}
@@ -0,0 +1,95 @@
+/// Converts a fixed-size, NUL-padded string field of a LAS record (e.g. the `name` or `description` of an extra bytes
+/// record) into a `String`. The string ends at the first NUL byte, or at the end of `bytes` if there is none, which is
+/// the case for strings that use up the whole field. Bytes that are no valid UTF-8 are replaced with the Unicode
+/// replacement character.
+///
+/// ```
+/// # use pasture_io::las::*;
+/// let mut name = [0_i8; 32];
+/// name[..5].copy_from_slice(&[b'h' as i8, b'e' as i8, b'l' as i8, b'l' as i8, b'o' as i8]);
+/// assert_eq!("hello", fixed_str_to_string(&name));
+/// ```
+pub fn fixed_str_to_string(bytes: &[i8]) -> String {
+    let bytes = bytes
+        .iter()
+        .take_while(|byte| **byte != 0)
+        .map(|byte| *byte as u8)
+        .collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Converts `s` into a fixed-size, NUL-padded string field of `N` bytes as it is stored in LAS records. This is the
+/// inverse of [fixed_str_to_string]. Strings that are longer than `N` bytes are truncated to the last character that
+/// fits completely into the field. A string of exactly `N` bytes fills the whole field without a terminating NUL.
+///
+/// ```
+/// # use pasture_io::las::*;
+/// let name: [i8; 32] = string_to_fixed("hello");
+/// assert_eq!("hello", fixed_str_to_string(&name));
+/// ```
+pub fn string_to_fixed<const N: usize>(s: &str) -> [i8; N] {
+    let mut length = s.len().min(N);
+    while !s.is_char_boundary(length) {
+        length -= 1;
+    }
+
+    let mut fixed = [0; N];
+    for (target, source) in fixed.iter_mut().zip(s[..length].bytes()) {
+        *target = source as i8;
+    }
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_i8(bytes: &[u8]) -> Vec<i8> {
+        bytes.iter().map(|byte| *byte as i8).collect()
+    }
+
+    #[test]
+    fn test_fixed_str_to_string_stops_at_first_nul() {
+        let mut name = to_i8(b"intensity\0gain\0");
+        name.resize(32, 0);
+        assert_eq!("intensity", fixed_str_to_string(&name));
+
+        let mut trailing_nuls = to_i8(b"amplitude");
+        trailing_nuls.resize(32, 0);
+        assert_eq!("amplitude", fixed_str_to_string(&trailing_nuls));
+
+        assert_eq!("", fixed_str_to_string(&[0; 32]));
+        assert_eq!("", fixed_str_to_string(&to_i8(b"\0name")));
+    }
+
+    #[test]
+    fn test_fixed_str_to_string_without_nul() {
+        let full_name = "abcdefghijklmnopqrstuvwxyz012345";
+        assert_eq!(32, full_name.len());
+        assert_eq!(full_name, fixed_str_to_string(&to_i8(full_name.as_bytes())));
+    }
+
+    #[test]
+    fn test_fixed_str_to_string_invalid_utf8() {
+        let name = to_i8(b"bad\xFFname\0");
+        assert_eq!("bad\u{FFFD}name", fixed_str_to_string(&name));
+    }
+
+    #[test]
+    fn test_string_to_fixed() {
+        let name: [i8; 32] = string_to_fixed("reflectance");
+        assert_eq!(to_i8(b"reflectance").as_slice(), &name[..11]);
+        assert!(name[11..].iter().all(|byte| *byte == 0));
+
+        let full_name = "abcdefghijklmnopqrstuvwxyz012345";
+        let fixed: [i8; 32] = string_to_fixed(full_name);
+        assert_eq!(full_name, fixed_str_to_string(&fixed));
+
+        let truncated: [i8; 32] = string_to_fixed("abcdefghijklmnopqrstuvwxyz0123456789");
+        assert_eq!(full_name, fixed_str_to_string(&truncated));
+
+        // 'ä' takes two bytes, which must not be split when truncating
+        let multi_byte: [i8; 4] = string_to_fixed("abcä");
+        assert_eq!("abc", fixed_str_to_string(&multi_byte));
+    }
+}
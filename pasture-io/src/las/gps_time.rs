@@ -0,0 +1,62 @@
+use pasture_core::{
+    containers::{PointBufferWriteable, PointBufferWriteableExt},
+    layout::attributes::GPS_TIME,
+};
+
+/// Number of seconds in a GPS week
+pub const SECONDS_PER_GPS_WEEK: f64 = 604_800.0;
+/// LAS files with standard GPS time store the adjusted standard GPS time, which is the number of seconds since the GPS
+/// epoch minus this offset
+pub const ADJUSTED_STANDARD_GPS_TIME_OFFSET: f64 = 1e9;
+
+/// Converts the given adjusted standard GPS time, as stored in LAS files with [GpsTimeType::Standard](las::GpsTimeType),
+/// into the GPS week time, i.e. the number of seconds since the start of the GPS week
+pub fn standard_gps_time_to_week_time(standard_time: f64) -> f64 {
+    (standard_time + ADJUSTED_STANDARD_GPS_TIME_OFFSET).rem_euclid(SECONDS_PER_GPS_WEEK)
+}
+
+/// Returns the GPS week of the given adjusted standard GPS time, i.e. the number of weeks since the GPS epoch
+pub fn gps_week_of_standard_gps_time(standard_time: f64) -> u32 {
+    ((standard_time + ADJUSTED_STANDARD_GPS_TIME_OFFSET) / SECONDS_PER_GPS_WEEK).floor() as u32
+}
+
+/// Converts the given GPS `week_time` within the GPS week `gps_week` into the adjusted standard GPS time. Since GPS week
+/// time does not store the week itself, the week has to be known from another source, e.g. the acquisition date
+pub fn week_time_to_standard_gps_time(week_time: f64, gps_week: u32) -> f64 {
+    gps_week as f64 * SECONDS_PER_GPS_WEEK + week_time - ADJUSTED_STANDARD_GPS_TIME_OFFSET
+}
+
+/// Converts the `GPS_TIME` attribute of all points in `buffer` from adjusted standard GPS time to GPS week time, see
+/// [standard_gps_time_to_week_time]. Does nothing if `buffer` has no `GPS_TIME` attribute
+pub fn convert_gps_times_to_week_time<B: PointBufferWriteable + ?Sized>(buffer: &mut B) {
+    buffer.transform_attribute(GPS_TIME.name(), |_, gps_time: &mut f64| {
+        *gps_time = standard_gps_time_to_week_time(*gps_time);
+    });
+}
+
+/// Converts the `GPS_TIME` attribute of all points in `buffer` from GPS week time within the GPS week `gps_week` to
+/// adjusted standard GPS time, see [week_time_to_standard_gps_time]. Does nothing if `buffer` has no `GPS_TIME` attribute
+pub fn convert_gps_times_to_standard_time<B: PointBufferWriteable + ?Sized>(
+    buffer: &mut B,
+    gps_week: u32,
+) {
+    buffer.transform_attribute(GPS_TIME.name(), |_, gps_time: &mut f64| {
+        *gps_time = week_time_to_standard_gps_time(*gps_time, gps_week);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gps_time_conversions() {
+        assert_eq!(76_800.0, standard_gps_time_to_week_time(2.0e8));
+        assert_eq!(1984, gps_week_of_standard_gps_time(2.0e8));
+        assert_eq!(2.0e8, week_time_to_standard_gps_time(76_800.0, 1984));
+
+        assert_eq!(290_567.5, standard_gps_time_to_week_time(1_234_567.5));
+        assert_eq!(1655, gps_week_of_standard_gps_time(1_234_567.5));
+        assert_eq!(1_234_567.5, week_time_to_standard_gps_time(290_567.5, 1655));
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use las::point::Format;
 use pasture_core::{
     layout::attributes,
-    layout::{PointLayout, PointType},
+    layout::{PointAttributeDataType, PointAttributeDefinition, PointLayout, PointType},
 };
 
 use super::{
@@ -81,3 +81,396 @@ pub fn las_point_format_from_point_layout(point_layout: &PointLayout) -> Format
 
     format
 }
+
+/// The highest LAS point format number defined by the LAS 1.4 specification
+const MAX_LAS_POINT_FORMAT: u8 = 10;
+
+/// Returns `true` if the LAS point format with the given number can store the given `attribute`. Attributes are matched
+/// by name, so any attribute whose datatype can be converted to the datatype of the corresponding LAS attribute is
+/// supported. Returns `false` if `format` is not a valid LAS point format.
+///
+/// ```
+/// # use pasture_io::las::*;
+/// # use pasture_core::layout::*;
+/// assert!(format_supports(3, &attributes::COLOR_RGB));
+/// assert!(!format_supports(1, &attributes::COLOR_RGB));
+/// // The extended formats store SCAN_ANGLE instead of SCAN_ANGLE_RANK
+/// assert!(!format_supports(6, &attributes::SCAN_ANGLE_RANK));
+/// ```
+pub fn format_supports(format: u8, attribute: &PointAttributeDefinition) -> bool {
+    let las_format = match Format::new(format) {
+        Ok(las_format) => las_format,
+        Err(_) => return false,
+    };
+    point_layout_from_las_point_format(&las_format)
+        .map(|layout| layout.has_attribute_with_name(attribute.name()))
+        .unwrap_or(false)
+}
+
+/// Returns the LAS point format with the lowest number that can store all attributes of the given `PointLayout`, or
+/// `None` if there is no LAS point format that can store all attributes. Unlike [las_point_format_from_point_layout],
+/// which ignores attributes that LAS does not support, this can be used to make sure that no data is lost when writing
+/// points in the given `PointLayout` to a LAS file.
+///
+/// ```
+/// # use pasture_io::las::*;
+/// # use pasture_core::layout::*;
+/// let layout = PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::COLOR_RGB]);
+/// assert_eq!(Some(2), minimal_format_for(&layout));
+///
+/// let layout = PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::POINT_ID]);
+/// assert_eq!(None, minimal_format_for(&layout));
+/// ```
+pub fn minimal_format_for(layout: &PointLayout) -> Option<u8> {
+    (0..=MAX_LAS_POINT_FORMAT).find(|format| {
+        layout
+            .attributes()
+            .all(|attribute| format_supports(*format, &attribute.into()))
+    })
+}
+
+/// How the value of a field of a LAS point record is obtained from its source attribute, see [`FieldMapping`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldConversion {
+    /// The source attribute has the datatype of the LAS field and is written as is
+    None,
+    /// The source attribute is converted from its datatype `from` into the datatype `to` of the LAS field
+    Datatype {
+        from: PointAttributeDataType,
+        to: PointAttributeDataType,
+    },
+    /// The source attribute is the scan angle representation of the other group of point formats, i.e. an extended
+    /// `SCAN_ANGLE` that is converted into a `SCAN_ANGLE_RANK` for formats 0 to 5, or the other way around for formats
+    /// 6 to 10
+    ScanAngle,
+    /// The `CLASSIFICATION_FLAGS` of the source layout are packed into the upper bits of the classification field of
+    /// formats 0 to 5. The classification itself is taken from the source attribute and converted to `u8` if
+    /// necessary, or is zero if the source attribute is `CLASSIFICATION_FLAGS`
+    PackClassificationFlags,
+}
+
+/// Describes how a single field of a LAS point record is filled from the attributes of a `PointLayout`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldMapping {
+    /// The field of the LAS point record, as it appears in the default `PointLayout` of the LAS point format
+    pub field: PointAttributeDefinition,
+    /// The attribute of the source `PointLayout` that supplies the value of the field, or `None` if there is no such
+    /// attribute. In this case, the field is written with its default value (i.e. zero)
+    pub source: Option<PointAttributeDefinition>,
+    /// How the value of `source` is converted into the value of the field
+    pub conversion: FieldConversion,
+}
+
+impl FieldMapping {
+    /// Is this field written with its default value because there is no source attribute for it?
+    pub fn is_defaulted(&self) -> bool {
+        self.source.is_none()
+    }
+}
+
+/// Describes how the attributes of a `PointLayout` map to the fields of the point records of a LAS point format when
+/// points are written to a LAS file. See [`layout_to_record_mapping`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordMapping {
+    format: u8,
+    fields: Vec<FieldMapping>,
+    ignored_attributes: Vec<PointAttributeDefinition>,
+}
+
+impl RecordMapping {
+    /// The LAS point format of the records
+    pub fn format(&self) -> u8 {
+        self.format
+    }
+
+    /// The mappings of all fields of the point records, in the order of the default `PointLayout` of the point format
+    pub fn fields(&self) -> &[FieldMapping] {
+        &self.fields
+    }
+
+    /// The mapping of the field with the given name, or `None` if the point format has no such field
+    pub fn field(&self, name: &str) -> Option<&FieldMapping> {
+        self.fields.iter().find(|field| field.field.name() == name)
+    }
+
+    /// All fields that are written with their default value because there is no source attribute for them
+    pub fn defaulted_fields(&self) -> impl Iterator<Item = &FieldMapping> {
+        self.fields.iter().filter(|field| field.is_defaulted())
+    }
+
+    /// All attributes of the source `PointLayout` that are not written to any field of the point records
+    pub fn ignored_attributes(&self) -> &[PointAttributeDefinition] {
+        &self.ignored_attributes
+    }
+}
+
+/// Returns how the attributes of `layout` map to the fields of the LAS point records when writing points in `layout`
+/// to a LAS file with the best matching point format, as determined by [las_point_format_from_point_layout]. Use
+/// this to find out which fields are defaulted or which attributes are dropped when writing, e.g. to debug missing
+/// fields in a LAS file.
+///
+/// ```
+/// # use pasture_io::las::*;
+/// # use pasture_core::layout::*;
+/// let layout = PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::POINT_ID]);
+/// let mapping = layout_to_record_mapping(&layout);
+/// assert_eq!(0, mapping.format());
+/// assert!(mapping.field(attributes::INTENSITY.name()).unwrap().is_defaulted());
+/// assert_eq!(&[attributes::POINT_ID], mapping.ignored_attributes());
+/// ```
+pub fn layout_to_record_mapping(layout: &PointLayout) -> RecordMapping {
+    let format = las_point_format_from_point_layout(layout).to_u8().unwrap();
+    layout_to_record_mapping_for_format(layout, format).unwrap()
+}
+
+/// Like [layout_to_record_mapping], but for the given LAS point `format`
+///
+/// # Errors
+///
+/// If `format` is not a valid LAS point format
+pub fn layout_to_record_mapping_for_format(
+    layout: &PointLayout,
+    format: u8,
+) -> Result<RecordMapping> {
+    let las_format = Format::new(format)?;
+    let las_layout = point_layout_from_las_point_format(&las_format)?;
+    let source_attribute = |attribute: &PointAttributeDefinition| {
+        layout
+            .get_attribute_by_name(attribute.name())
+            .map(PointAttributeDefinition::from)
+    };
+
+    let fields = las_layout
+        .attributes()
+        .map(|las_attribute| {
+            let field: PointAttributeDefinition = las_attribute.into();
+            let source = source_attribute(&field);
+            let conversion = match &source {
+                Some(source) if source.datatype() != field.datatype() => {
+                    FieldConversion::Datatype {
+                        from: source.datatype(),
+                        to: field.datatype(),
+                    }
+                }
+                _ => FieldConversion::None,
+            };
+            let mut mapping = FieldMapping {
+                field,
+                source,
+                conversion,
+            };
+
+            // These rules mirror the conversions of the LAS writers
+            let scan_angle_fallback = if las_format.is_extended {
+                &attributes::SCAN_ANGLE_RANK
+            } else {
+                &attributes::SCAN_ANGLE
+            };
+            let is_scan_angle = mapping.field.name() == attributes::SCAN_ANGLE.name()
+                || mapping.field.name() == attributes::SCAN_ANGLE_RANK.name();
+            if is_scan_angle && mapping.source.is_none() {
+                if let Some(fallback) = source_attribute(scan_angle_fallback) {
+                    mapping.source = Some(fallback);
+                    mapping.conversion = FieldConversion::ScanAngle;
+                }
+            }
+
+            if !las_format.is_extended && mapping.field.name() == attributes::CLASSIFICATION.name()
+            {
+                if let Some(flags) = source_attribute(&attributes::CLASSIFICATION_FLAGS) {
+                    mapping.source = mapping.source.or(Some(flags));
+                    mapping.conversion = FieldConversion::PackClassificationFlags;
+                }
+            }
+
+            mapping
+        })
+        .collect::<Vec<_>>();
+
+    let ignored_attributes = layout
+        .attributes()
+        .map(PointAttributeDefinition::from)
+        .filter(|attribute| {
+            let is_classification_flags =
+                attribute.name() == attributes::CLASSIFICATION_FLAGS.name();
+            !fields.iter().any(|field| {
+                field.source.as_ref().map(|source| source.name()) == Some(attribute.name())
+                    || (is_classification_flags
+                        && field.conversion == FieldConversion::PackClassificationFlags)
+            })
+        })
+        .collect();
+
+    Ok(RecordMapping {
+        format,
+        fields,
+        ignored_attributes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_supports() {
+        for format in 0..=MAX_LAS_POINT_FORMAT {
+            assert!(format_supports(format, &attributes::POSITION_3D));
+            assert!(format_supports(format, &attributes::CLASSIFICATION));
+        }
+
+        assert!(!format_supports(0, &attributes::GPS_TIME));
+        assert!(format_supports(1, &attributes::GPS_TIME));
+        assert!(!format_supports(3, &attributes::NIR));
+        assert!(format_supports(8, &attributes::NIR));
+        assert!(!format_supports(5, &attributes::CLASSIFICATION_FLAGS));
+        assert!(format_supports(6, &attributes::CLASSIFICATION_FLAGS));
+
+        assert!(!format_supports(11, &attributes::POSITION_3D));
+    }
+
+    #[test]
+    fn test_minimal_format_for() {
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            attributes::GPS_TIME,
+            attributes::COLOR_RGB,
+        ]);
+        assert_eq!(Some(3), minimal_format_for(&layout));
+
+        let layout =
+            PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::INTENSITY]);
+        assert_eq!(Some(0), minimal_format_for(&layout));
+
+        let layout = PointLayout::from_attributes(&[attributes::POSITION_3D, attributes::GPS_TIME]);
+        assert_eq!(Some(1), minimal_format_for(&layout));
+
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            attributes::CLASSIFICATION_FLAGS,
+            attributes::COLOR_RGB,
+            attributes::NIR,
+        ]);
+        assert_eq!(Some(8), minimal_format_for(&layout));
+
+        // Legacy scan angle ranks and extended classification flags can't be stored in the same format
+        let layout = PointLayout::from_attributes(&[
+            attributes::SCAN_ANGLE_RANK,
+            attributes::CLASSIFICATION_FLAGS,
+        ]);
+        assert_eq!(None, minimal_format_for(&layout));
+    }
+
+    #[test]
+    fn test_layout_to_record_mapping() {
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            attributes::INTENSITY,
+            attributes::CLASSIFICATION,
+        ]);
+        let mapping = layout_to_record_mapping(&layout);
+        assert_eq!(0, mapping.format());
+        assert_eq!(
+            LasPointFormat0::layout()
+                .attributes()
+                .map(PointAttributeDefinition::from)
+                .collect::<Vec<_>>(),
+            mapping
+                .fields()
+                .iter()
+                .map(|field| field.field.clone())
+                .collect::<Vec<_>>()
+        );
+
+        for attribute in &[
+            attributes::POSITION_3D,
+            attributes::INTENSITY,
+            attributes::CLASSIFICATION,
+        ] {
+            let field = mapping.field(attribute.name()).unwrap();
+            assert_eq!(Some(attribute), field.source.as_ref());
+            assert_eq!(FieldConversion::None, field.conversion);
+        }
+        let defaulted_fields = mapping
+            .defaulted_fields()
+            .map(|field| field.field.name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                attributes::RETURN_NUMBER.name(),
+                attributes::NUMBER_OF_RETURNS.name(),
+                attributes::SCAN_DIRECTION_FLAG.name(),
+                attributes::EDGE_OF_FLIGHT_LINE.name(),
+                attributes::SCAN_ANGLE_RANK.name(),
+                attributes::USER_DATA.name(),
+                attributes::POINT_SOURCE_ID.name(),
+            ],
+            defaulted_fields
+        );
+        assert!(mapping.ignored_attributes().is_empty());
+        assert!(mapping.field(attributes::GPS_TIME.name()).is_none());
+    }
+
+    #[test]
+    fn test_layout_to_record_mapping_conversions() -> Result<()> {
+        let intensity_u8 = attributes::INTENSITY.with_custom_datatype(PointAttributeDataType::U8);
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            intensity_u8,
+            attributes::SCAN_ANGLE,
+            attributes::CLASSIFICATION_FLAGS,
+        ]);
+
+        let mapping = layout_to_record_mapping_for_format(&layout, 1)?;
+        assert_eq!(
+            FieldConversion::Datatype {
+                from: PointAttributeDataType::U8,
+                to: PointAttributeDataType::U16
+            },
+            mapping
+                .field(attributes::INTENSITY.name())
+                .unwrap()
+                .conversion
+        );
+        let scan_angle_rank = mapping.field(attributes::SCAN_ANGLE_RANK.name()).unwrap();
+        assert_eq!(
+            Some(&attributes::SCAN_ANGLE),
+            scan_angle_rank.source.as_ref()
+        );
+        assert_eq!(FieldConversion::ScanAngle, scan_angle_rank.conversion);
+        let classification = mapping.field(attributes::CLASSIFICATION.name()).unwrap();
+        assert_eq!(
+            Some(&attributes::CLASSIFICATION_FLAGS),
+            classification.source.as_ref()
+        );
+        assert_eq!(
+            FieldConversion::PackClassificationFlags,
+            classification.conversion
+        );
+        assert!(mapping.ignored_attributes().is_empty());
+
+        // The extended formats store the classification flags in a separate field
+        let mapping = layout_to_record_mapping_for_format(&layout, 6)?;
+        assert!(mapping
+            .field(attributes::CLASSIFICATION.name())
+            .unwrap()
+            .is_defaulted());
+        assert_eq!(
+            FieldConversion::None,
+            mapping
+                .field(attributes::CLASSIFICATION_FLAGS.name())
+                .unwrap()
+                .conversion
+        );
+        assert_eq!(
+            FieldConversion::None,
+            mapping
+                .field(attributes::SCAN_ANGLE.name())
+                .unwrap()
+                .conversion
+        );
+
+        assert!(layout_to_record_mapping_for_format(&layout, 11).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use las::{point::Format, Builder, Header, Transform, Vector, Version, Vlr};
+use laz::LazVlr;
+use pasture_core::layout::{FieldAlignment, PointLayout};
+
+use crate::base::{PointReader, PointWriter};
+
+use super::{
+    las_header_with_extra_bytes, path_is_compressed_las_file, point_layout_from_las_header,
+    LASReader, LASWriter, EXTRA_BYTES_RECORD_ID, EXTRA_BYTES_USER_ID,
+};
+
+/// Options for [merge_las]
+#[derive(Clone, Debug)]
+pub struct MergeLasOptions {
+    /// If the input files have different point formats, write the merged file in the smallest point format that can store
+    /// the attributes of all input formats. If this is `false`, merging files with different point formats fails
+    pub promote_point_formats: bool,
+    /// Number of points that are read from an input file and written to the merged file at once. Values smaller than 1
+    /// are treated as 1
+    pub points_per_chunk: usize,
+}
+
+impl Default for MergeLasOptions {
+    fn default() -> Self {
+        Self {
+            promote_point_formats: true,
+            points_per_chunk: 50_000,
+        }
+    }
+}
+
+/// Merges the LAS/LAZ files at `inputs` into a single LAS/LAZ file at `output`. The points of the input files are streamed
+/// into the output file in the order of `inputs`, so only `options.points_per_chunk` points are held in memory at once.
+///
+/// The header of the merged file is derived deterministically from the headers of the input files:
+/// - The scale of each axis is the smallest scale of this axis in all input files, so no precision is lost. The offset is
+///   the minimum of the bounds of all input files. The positions of all points are re-quantized with this scale and
+///   offset
+/// - The point format is the common point format of the input files, see [MergeLasOptions::promote_point_formats]
+/// - The LAS version is the largest version of all input files, or 1.4 if this version does not support the point format
+/// - The VLRs and EVLRs are the union of the (E)VLRs of all input files, in order of their first occurrence. Identical
+///   (E)VLRs are only written once. The LASzip VLR and the extra bytes VLR are generated for the merged file instead,
+///   with the attributes of the extra bytes of all input files (see [las_header_with_extra_bytes])
+/// - Bounds and point counts are accumulated from the merged points
+///
+/// # Errors
+///
+/// If `inputs` is empty, if any of the files can't be read or written, if the input files have different point formats
+/// and `options.promote_point_formats` is `false`, if they use different types of GPS time, or if the positions of the
+/// merged file can't be represented with the common scale and offset
+pub fn merge_las(inputs: &[&Path], output: &Path, options: MergeLasOptions) -> Result<()> {
+    if inputs.is_empty() {
+        bail!("At least one input file is required for merging LAS files");
+    }
+
+    let headers = inputs
+        .iter()
+        .map(|path| -> Result<Header> {
+            let reader = LASReader::from_path(path)
+                .with_context(|| format!("Could not open input file {}", path.display()))?;
+            Ok(reader.header().clone())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let merged_header = merged_las_header(&headers, &options)?;
+    let mut writer = LASWriter::from_path_and_header(output, merged_header)?;
+
+    let points_per_chunk = std::cmp::max(1, options.points_per_chunk);
+    for path in inputs {
+        let mut reader = LASReader::from_path(path)?;
+        while reader.remaining_points() > 0 {
+            let points = reader.read(points_per_chunk)?;
+            writer.write(points.as_ref())?;
+        }
+    }
+    // LAZ writers don't support flushing, they write the header once they are dropped
+    if !path_is_compressed_las_file(output)? {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Creates the header for the file that results from merging files with the given `headers`, see [merge_las]
+fn merged_las_header(headers: &[Header], options: &MergeLasOptions) -> Result<Header> {
+    let first_header = &headers[0];
+
+    let point_format = common_point_format(headers, options.promote_point_formats)?;
+    let max_version = headers.iter().map(|header| header.version()).max().unwrap();
+    let version = if max_version.supports_point_format(point_format) {
+        max_version
+    } else {
+        Version::new(1, 4)
+    };
+
+    if headers
+        .iter()
+        .any(|header| header.gps_time_type() != first_header.gps_time_type())
+    {
+        bail!("Can't merge LAS files that use different types of GPS time");
+    }
+
+    let mut builder = Builder::from(version);
+    builder.point_format = point_format;
+    builder.gps_time_type = first_header.gps_time_type();
+    builder.transforms = common_transforms(headers)?;
+    builder.date = headers.iter().filter_map(|header| header.date()).max();
+    builder.system_identifier = first_header.system_identifier().to_owned();
+    if headers
+        .iter()
+        .all(|header| header.file_source_id() == first_header.file_source_id())
+    {
+        builder.file_source_id = first_header.file_source_id();
+    }
+
+    let is_generated_vlr = |vlr: &Vlr| {
+        vlr.user_id == LazVlr::USER_ID
+            || (vlr.user_id == EXTRA_BYTES_USER_ID && vlr.record_id == EXTRA_BYTES_RECORD_ID)
+    };
+    for header in headers {
+        for vlr in header.vlrs() {
+            if !is_generated_vlr(vlr) && !builder.vlrs.contains(vlr) {
+                builder.vlrs.push(vlr.clone());
+            }
+        }
+        for evlr in header.evlrs() {
+            if !is_generated_vlr(evlr) && !builder.evlrs.contains(evlr) {
+                builder.evlrs.push(evlr.clone());
+            }
+        }
+    }
+
+    // All attributes of all input files, of which `las_header_with_extra_bytes` picks the custom attributes. If the
+    // same attribute has different datatypes in different files, the datatype of the first file is used
+    let mut merged_layout = PointLayout::default();
+    for header in headers {
+        for attribute in point_layout_from_las_header(header)?.attributes() {
+            if !merged_layout.has_attribute_with_name(attribute.name()) {
+                merged_layout.add_attribute(attribute.into(), FieldAlignment::Packed(1));
+            }
+        }
+    }
+
+    let header = builder.into_header()?;
+    las_header_with_extra_bytes(&header, &merged_layout)
+}
+
+/// Returns the point format that all files with the given `headers` are merged into. This is the smallest LAS point
+/// format that stores all attributes of the point formats of all `headers`
+fn common_point_format(headers: &[Header], promote_point_formats: bool) -> Result<Format> {
+    let formats = headers
+        .iter()
+        .map(|header| {
+            let mut format = *header.point_format();
+            // Extra bytes are handled separately
+            format.extra_bytes = 0;
+            format
+        })
+        .collect::<Vec<_>>();
+    if formats.iter().all(|format| *format == formats[0]) {
+        return Ok(formats[0]);
+    }
+    if !promote_point_formats {
+        bail!(
+            "Can't merge LAS files with different point formats ({}) without promoting them to a common point format",
+            formats
+                .iter()
+                .map(|format| format.to_u8().map(|id| id.to_string()))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(", ")
+        );
+    }
+
+    (0..=10)
+        .map(|id| Format::new(id).unwrap())
+        .find(|candidate| {
+            formats.iter().all(|format| {
+                (!format.is_extended || candidate.is_extended)
+                    && (!format.has_gps_time || candidate.has_gps_time)
+                    && (!format.has_color || candidate.has_color)
+                    && (!format.has_nir || candidate.has_nir)
+                    && (!format.has_waveform || candidate.has_waveform)
+            })
+        })
+        .ok_or_else(|| {
+            anyhow!("There is no LAS point format that can store the points of all input files")
+        })
+}
+
+/// Returns the scales and offsets for the merged file from the input files with the given `headers`. The scale of each
+/// axis is the smallest scale of all files, the offset is the minimum of the bounds of all files
+fn common_transforms(headers: &[Header]) -> Result<Vector<Transform>> {
+    let non_empty_headers = headers
+        .iter()
+        .filter(|header| header.number_of_points() > 0)
+        .collect::<Vec<_>>();
+
+    let common_transform = |get_transform: fn(&Vector<Transform>) -> Transform,
+                            get_bounds: fn(&Vector<f64>) -> f64|
+     -> Result<Transform> {
+        let scale = headers
+            .iter()
+            .map(|header| get_transform(header.transforms()).scale)
+            .fold(std::f64::INFINITY, f64::min);
+        if non_empty_headers.is_empty() {
+            return Ok(Transform { scale, offset: 0.0 });
+        }
+        let min = non_empty_headers
+            .iter()
+            .map(|header| get_bounds(&header.bounds().min))
+            .fold(std::f64::INFINITY, f64::min);
+        let max = non_empty_headers
+            .iter()
+            .map(|header| get_bounds(&header.bounds().max))
+            .fold(std::f64::NEG_INFINITY, f64::max);
+        if ((max - min) / scale).round() > std::i32::MAX as f64 {
+            bail!(
+                "The extent {} of the merged files is too large to be stored with a scale of {}",
+                max - min,
+                scale
+            );
+        }
+        Ok(Transform { scale, offset: min })
+    };
+
+    Ok(Vector {
+        x: common_transform(|transforms| transforms.x, |vector| vector.x)?,
+        y: common_transform(|transforms| transforms.y, |vector| vector.y)?,
+        z: common_transform(|transforms| transforms.z, |vector| vector.z)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pasture_core::{
+        containers::{InterleavedVecPointStorage, PointBufferExt},
+        layout::{attributes, PointType},
+        nalgebra::{Point3, Vector3},
+    };
+    use pasture_derive::PointType;
+    use scopeguard::defer;
+
+    use crate::las::epsilon_compare_point3f64;
+
+    use super::*;
+
+    #[repr(C, packed)]
+    #[derive(Debug, Clone, Copy, PointType)]
+    struct PointWithIntensity {
+        #[pasture(BUILTIN_POSITION_3D)]
+        pub position: Vector3<f64>,
+        #[pasture(BUILTIN_INTENSITY)]
+        pub intensity: u16,
+    }
+
+    #[repr(C, packed)]
+    #[derive(Debug, Clone, Copy, PointType)]
+    struct PointWithGpsTime {
+        #[pasture(BUILTIN_POSITION_3D)]
+        pub position: Vector3<f64>,
+        #[pasture(BUILTIN_GPS_TIME)]
+        pub gps_time: f64,
+    }
+
+    fn write_test_file<T: PointType + Copy>(
+        path: &Path,
+        format: u8,
+        scale: f64,
+        offset: f64,
+        points: &[T],
+    ) -> Result<()> {
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(format)?;
+        let transform = Transform { scale, offset };
+        builder.transforms = Vector {
+            x: transform,
+            y: transform,
+            z: transform,
+        };
+        let mut writer = LASWriter::from_path_and_header(path, builder.into_header()?)?;
+        let mut buffer = InterleavedVecPointStorage::new(T::layout());
+        buffer.push_points(points);
+        writer.write(&buffer)?;
+        writer.flush()
+    }
+
+    fn test_file_path(name: &str) -> PathBuf {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push(name);
+        path
+    }
+
+    #[test]
+    fn test_merge_las() -> Result<()> {
+        let first_path = test_file_path("test_merge_las_first.las");
+        let second_path = test_file_path("test_merge_las_second.las");
+        let merged_path = test_file_path("test_merge_las_merged.las");
+        defer! {
+            for path in [&first_path, &second_path, &merged_path].iter() {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+
+        write_test_file(
+            &first_path,
+            0,
+            0.01,
+            0.0,
+            &[
+                PointWithIntensity {
+                    position: Vector3::new(1.0, 2.0, 3.0),
+                    intensity: 10,
+                },
+                PointWithIntensity {
+                    position: Vector3::new(4.5, 5.5, 6.5),
+                    intensity: 20,
+                },
+            ],
+        )?;
+        write_test_file(
+            &second_path,
+            1,
+            0.001,
+            100.0,
+            &[
+                PointWithGpsTime {
+                    position: Vector3::new(-1.125, 10.0, 0.5),
+                    gps_time: 42.0,
+                },
+                PointWithGpsTime {
+                    position: Vector3::new(2.0, -3.0, 7.25),
+                    gps_time: 43.0,
+                },
+                PointWithGpsTime {
+                    position: Vector3::new(0.0, 0.0, 0.0),
+                    gps_time: 44.0,
+                },
+            ],
+        )?;
+
+        merge_las(
+            &[&first_path, &second_path],
+            &merged_path,
+            Default::default(),
+        )?;
+
+        let mut reader = LASReader::from_path(&merged_path)?;
+        assert_eq!(5, reader.remaining_points());
+        // Format 0 and 1 are promoted to format 1, which can store the GPS times
+        assert_eq!(Format::new(1)?, *reader.header().point_format());
+        assert_eq!(0.001, reader.header().transforms().x.scale);
+        assert_eq!(-1.125, reader.header().transforms().x.offset);
+
+        let bounds = reader
+            .get_metadata()
+            .bounds()
+            .expect("LAS file must have bounds");
+        assert!(epsilon_compare_point3f64(
+            &Point3::new(-1.125, -3.0, 0.0),
+            bounds.min()
+        ));
+        assert!(epsilon_compare_point3f64(
+            &Point3::new(4.5, 10.0, 7.25),
+            bounds.max()
+        ));
+
+        let points = reader.read(5)?;
+        let positions = points
+            .iter_attribute::<Vector3<f64>>(&attributes::POSITION_3D)
+            .collect::<Vec<_>>();
+        let expected_positions = vec![
+            Vector3::new(1.0, 2.0, 3.0),
+            Vector3::new(4.5, 5.5, 6.5),
+            Vector3::new(-1.125, 10.0, 0.5),
+            Vector3::new(2.0, -3.0, 7.25),
+            Vector3::new(0.0, 0.0, 0.0),
+        ];
+        for (expected, actual) in expected_positions.iter().zip(positions.iter()) {
+            assert!((expected - actual).norm() < 1e-6);
+        }
+        let intensities = points
+            .iter_attribute::<u16>(&attributes::INTENSITY)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![10, 20, 0, 0, 0], intensities);
+        let gps_times = points
+            .iter_attribute::<f64>(&attributes::GPS_TIME)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0.0, 0.0, 42.0, 43.0, 44.0], gps_times);
+
+        // Without promotion, the different point formats can't be merged
+        assert!(merge_las(
+            &[&first_path, &second_path],
+            &merged_path,
+            MergeLasOptions {
+                promote_point_formats: false,
+                ..Default::default()
+            },
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
@@ -5,18 +5,44 @@ use std::{
 use std::{io::SeekFrom, path::Path};
 
 use anyhow::Result;
-use las_rs::Header;
+use las_rs::{raw, Builder, Header, Vlr};
 
 use crate::base::{PointReader, SeekToPoint};
 use pasture_core::{containers::PointBufferWriteable, layout::PointLayout, meta::Metadata};
 
-use super::{path_is_compressed_las_file, LASReaderBase, RawLASReader, RawLAZReader};
+use super::{path_is_compressed_las_file, LASMetadata, LASReaderBase, RawLASReader, RawLAZReader};
+
+/// Options that control how a `LASReader` decompresses LAZ files. LAZ files are compressed in independent chunks of
+/// points (typically 50000 points per chunk), which are decoded as a whole
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LazReaderOptions {
+    /// Decompress chunks on a thread pool instead of on the calling thread. This can substantially speed up
+    /// sequential reads of large files on multicore machines, but only has an effect if `prefetch_chunks` is larger
+    /// than 1, since each chunk is decoded by a single thread. Requires the `parallel` feature, without it creating
+    /// the reader fails
+    pub decompress_parallel: bool,
+    /// Number of chunks that are decompressed at once. Larger values give the thread pool more work to distribute,
+    /// but the reader has to hold all decompressed points of these chunks in memory, twice: once in the LAZ point
+    /// record format and once converted into the target `PointLayout`. Values smaller than 1 are treated as 1
+    pub prefetch_chunks: usize,
+}
+
+impl Default for LazReaderOptions {
+    fn default() -> Self {
+        Self {
+            decompress_parallel: false,
+            prefetch_chunks: 1,
+        }
+    }
+}
 
 trait AnyLASReader: PointReader + SeekToPoint + LASReaderBase {}
 
 impl<T: PointReader + SeekToPoint + LASReaderBase> AnyLASReader for T {}
 
-/// `PointReader` implementation for LAS/LAZ files
+/// `PointReader` implementation for LAS/LAZ files. Extra bytes in the point records that are described by an extra bytes
+/// record (see [ExtraBytesRecord](super::ExtraBytesRecord)) are read as custom attributes, with scale and offset applied,
+/// so the default `PointLayout` of a file is given by [point_layout_from_las_header](super::point_layout_from_las_header)
 pub struct LASReader<'a> {
     raw_reader: Box<dyn AnyLASReader + 'a>,
 }
@@ -48,8 +74,37 @@ impl<'a> LASReader<'a> {
     ///
     /// If the given `Read` does not represent a valid LAS/LAZ file, an error is returned.
     pub fn from_read<R: Read + Seek + Send + 'a>(read: R, is_compressed: bool) -> Result<Self> {
+        Self::from_read_with_options(read, is_compressed, Default::default())
+    }
+
+    /// Like [`from_path`](LASReader::from_path), but uses the given `LazReaderOptions` if the file is a LAZ file
+    ///
+    /// # Errors
+    ///
+    /// If `path` does not exist, cannot be opened or does not point to a valid LAS/LAZ file, an error is returned.
+    /// If `laz_options` requests parallel decompression without the `parallel` feature, an error is returned.
+    pub fn from_path_with_options<P: AsRef<Path>>(
+        path: P,
+        laz_options: LazReaderOptions,
+    ) -> Result<Self> {
+        let is_compressed = path_is_compressed_las_file(path.as_ref())?;
+        let file = BufReader::new(File::open(path)?);
+        Self::from_read_with_options(file, is_compressed, laz_options)
+    }
+
+    /// Like [`from_read`](LASReader::from_read), but uses the given `LazReaderOptions` if `is_compressed` is `true`
+    ///
+    /// # Errors
+    ///
+    /// If the given `Read` does not represent a valid LAS/LAZ file, an error is returned. If `laz_options` requests
+    /// parallel decompression without the `parallel` feature, an error is returned.
+    pub fn from_read_with_options<R: Read + Seek + Send + 'a>(
+        read: R,
+        is_compressed: bool,
+        laz_options: LazReaderOptions,
+    ) -> Result<Self> {
         let raw_reader: Box<dyn AnyLASReader> = if is_compressed {
-            Box::new(RawLAZReader::from_read(read)?)
+            Box::new(RawLAZReader::from_read_with_options(read, laz_options)?)
         } else {
             Box::new(RawLASReader::from_read(read)?)
         };
@@ -58,14 +113,63 @@ impl<'a> LASReader<'a> {
         })
     }
 
+    /// Reads only the header and the VLRs of the LAS/LAZ file in the given `read` and returns them as `LASMetadata`
+    /// (point count, bounds, point format as well as the full LAS header with scales, offsets and VLRs). No point
+    /// data is read and no LAZ decompressor is created, which makes this much faster than creating a `LASReader`,
+    /// e.g. to catalog many files. This works for both LAS and LAZ files.
+    ///
+    /// # Errors
+    ///
+    /// If the given `Read` does not start with a valid LAS header, an error is returned.
+    pub fn metadata<R: Read>(mut read: R) -> Result<LASMetadata> {
+        let raw_header = raw::Header::read_from(&mut read)?;
+        let number_of_vlrs = raw_header.number_of_variable_length_records;
+        let mut header_builder = Builder::new(raw_header)?;
+        for _ in 0..number_of_vlrs {
+            let vlr = raw::Vlr::read_from(&mut read, false).map(Vlr::new)?;
+            header_builder.vlrs.push(vlr);
+        }
+        Ok(header_builder.into_header()?.into())
+    }
+
+    /// Like [`metadata`](LASReader::metadata), but reads the header of the file at the given `path`
+    ///
+    /// # Errors
+    ///
+    /// If `path` does not exist, cannot be opened or does not point to a valid LAS/LAZ file, an error is returned.
+    pub fn metadata_from_path<P: AsRef<Path>>(path: P) -> Result<LASMetadata> {
+        Self::metadata(BufReader::new(File::open(path)?))
+    }
+
     pub fn remaining_points(&mut self) -> usize {
         self.raw_reader.remaining_points()
     }
 
+    /// Returns the maximum number of points that a single call to `read` or `read_into` may read
+    pub fn max_points_per_read(&self) -> usize {
+        self.raw_reader.max_points_per_read()
+    }
+
+    /// Sets the maximum number of points that a single call to `read` or `read_into` may read. Requests for more points
+    /// than remain in the file are clamped to the remaining points first. If the clamped request still exceeds this
+    /// limit, reading fails instead of allocating memory for all requested points. This protects against corrupt or
+    /// malicious headers that claim an absurd number of points. Defaults to
+    /// [DEFAULT_MAX_POINTS_PER_READ](crate::base::DEFAULT_MAX_POINTS_PER_READ)
+    pub fn set_max_points_per_read(&mut self, max_points_per_read: usize) {
+        self.raw_reader.set_max_points_per_read(max_points_per_read);
+    }
+
     /// Returns the LAS header for the associated `LASReader`
     pub fn header(&self) -> &Header {
         self.raw_reader.header()
     }
+
+    /// Returns the type of the GPS times in the associated `LASReader`, as stored in the global encoding of the LAS header.
+    /// GPS times are read as they are stored in the file, see [convert_gps_times_to_week_time](super::convert_gps_times_to_week_time)
+    /// and [convert_gps_times_to_standard_time](super::convert_gps_times_to_standard_time) for converting them
+    pub fn gps_time_type(&self) -> las::GpsTimeType {
+        self.header().gps_time_type()
+    }
 }
 
 impl<'a> PointReader for LASReader<'a> {
@@ -95,3 +199,234 @@ impl<'a> SeekToPoint for LASReader<'a> {
         self.raw_reader.seek_point(position)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::DEFAULT_MAX_POINTS_PER_READ;
+    use crate::las::{
+        get_test_las_path, get_test_laz_path, test_data_bounds, test_data_point_count,
+        EXTRA_BYTES_RECORD_ID, EXTRA_BYTES_RECORD_SIZE, EXTRA_BYTES_USER_ID,
+    };
+    use las_rs::{point::Format, Write};
+    use pasture_core::{
+        containers::{InterleavedVecPointStorage, PointBufferExt},
+        layout::{attributes, PointAttributeDataType, PointAttributeDefinition},
+        nalgebra::Vector3,
+    };
+    use std::io::Cursor;
+
+    #[test]
+    fn test_las_metadata_matches_full_read() -> Result<()> {
+        let las_paths = (0..=10).map(get_test_las_path);
+        // LAZ files with wave packets (formats 4 and 5) are not supported
+        let laz_paths = (0..=3).map(get_test_laz_path);
+        for path in las_paths.chain(laz_paths) {
+            let metadata = LASReader::metadata_from_path(&path)?;
+
+            let mut reader = LASReader::from_path(&path)?;
+            let points = reader.read(test_data_point_count())?;
+            assert_eq!(points.len(), metadata.point_count(), "{}", path.display());
+            assert_eq!(test_data_point_count(), metadata.point_count());
+            assert_eq!(Some(test_data_bounds()), metadata.bounds());
+            assert_eq!(
+                reader.get_metadata().bounds(),
+                metadata.bounds(),
+                "{}",
+                path.display()
+            );
+            assert_eq!(
+                reader.header().point_format().to_u8()?,
+                metadata.point_format()
+            );
+
+            let raw_header = metadata.raw_las_header().unwrap();
+            assert_eq!(reader.header().transforms(), raw_header.transforms());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_las_metadata_invalid_file() {
+        assert!(LASReader::metadata(&b"NOT A LAS FILE"[..]).is_err());
+    }
+
+    #[test]
+    fn test_laz_reader_options_read_same_points() -> Result<()> {
+        let options = LazReaderOptions {
+            decompress_parallel: cfg!(feature = "parallel"),
+            prefetch_chunks: 3,
+        };
+        // LAZ files with wave packets (formats 4 and 5) are not supported
+        for path in (0..=3).map(get_test_laz_path) {
+            let expected = LASReader::from_path(&path)?.read(test_data_point_count())?;
+
+            let mut reader = LASReader::from_path_with_options(&path, options)?;
+            let actual = reader.read(test_data_point_count())?;
+            assert_eq!(expected.len(), actual.len());
+
+            let point_size = expected.point_layout().size_of_point_entry() as usize;
+            let mut expected_point = vec![0; point_size];
+            let mut actual_point = vec![0; point_size];
+            for index in 0..expected.len() {
+                expected.get_raw_point(index, &mut expected_point);
+                actual.get_raw_point(index, &mut actual_point);
+                assert_eq!(expected_point, actual_point, "{}", path.display());
+            }
+
+            reader.seek_point(SeekFrom::Start(5))?;
+            let after_seek = reader.read(1)?;
+            expected.get_raw_point(5, &mut expected_point);
+            after_seek.get_raw_point(0, &mut actual_point);
+            assert_eq!(expected_point, actual_point, "{}", path.display());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_las_reader_max_points_per_read() -> Result<()> {
+        for path in [get_test_las_path(0), get_test_laz_path(0)].iter() {
+            let mut reader = LASReader::from_path(path)?;
+            assert_eq!(DEFAULT_MAX_POINTS_PER_READ, reader.max_points_per_read());
+            let points = reader.read(test_data_point_count() + 100)?;
+            assert_eq!(test_data_point_count(), points.len());
+
+            // Requests beyond the point count are clamped before they are checked against the maximum
+            let mut reader = LASReader::from_path(path)?;
+            reader.set_max_points_per_read(test_data_point_count());
+            let points = reader.read(usize::MAX)?;
+            assert_eq!(test_data_point_count(), points.len());
+
+            let mut reader = LASReader::from_path(path)?;
+            reader.set_max_points_per_read(5);
+            assert!(reader.read(6).is_err());
+            let mut buffer =
+                InterleavedVecPointStorage::new(reader.get_default_point_layout().clone());
+            assert!(reader.read_into(&mut buffer, 6).is_err());
+            assert_eq!(5, reader.read(5)?.len());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    #[test]
+    fn test_laz_parallel_decompression_requires_feature() {
+        let options = LazReaderOptions {
+            decompress_parallel: true,
+            ..Default::default()
+        };
+        assert!(LASReader::from_path_with_options(get_test_laz_path(0), options).is_err());
+    }
+
+    /// Creates a raw extra bytes record with the given name, LAS datatype, and optional scale and offset
+    fn raw_extra_bytes_record(
+        name: &str,
+        data_type: u8,
+        scale: Option<f64>,
+        offset: Option<f64>,
+    ) -> Vec<u8> {
+        let mut record = vec![0; EXTRA_BYTES_RECORD_SIZE];
+        record[2] = data_type;
+        record[4..4 + name.len()].copy_from_slice(name.as_bytes());
+        if let Some(scale) = scale {
+            record[3] |= 1 << 3;
+            record[112..120].copy_from_slice(&scale.to_le_bytes());
+        }
+        if let Some(offset) = offset {
+            record[3] |= 1 << 4;
+            record[136..144].copy_from_slice(&offset.to_le_bytes());
+        }
+        record
+    }
+
+    const TEST_AMPLITUDES: [u16; 3] = [0, 10, 255];
+    const TEST_DEVIATIONS: [f32; 3] = [0.5, -1.25, 1024.0];
+
+    /// Creates a LAS or LAZ file in memory with two extra bytes attributes: A scaled `u16` amplitude and an `f32`
+    /// deviation
+    fn las_file_with_extra_bytes(compressed: bool) -> Result<Vec<u8>> {
+        let mut header_builder = Builder::from((1, 4));
+        header_builder.point_format = Format::new(1)?;
+        header_builder.point_format.extra_bytes = 6;
+        header_builder.point_format.is_compressed = compressed;
+        let mut extra_bytes_vlr = Vlr::default();
+        extra_bytes_vlr.user_id = EXTRA_BYTES_USER_ID.to_owned();
+        extra_bytes_vlr.record_id = EXTRA_BYTES_RECORD_ID;
+        extra_bytes_vlr.data = raw_extra_bytes_record("amplitude", 3, Some(0.1), Some(5.0));
+        extra_bytes_vlr
+            .data
+            .extend(raw_extra_bytes_record("deviation", 9, None, None));
+        header_builder.vlrs.push(extra_bytes_vlr);
+
+        let mut writer =
+            las_rs::Writer::new(Cursor::new(Vec::new()), header_builder.into_header()?)?;
+        for (index, (amplitude, deviation)) in TEST_AMPLITUDES
+            .iter()
+            .zip(TEST_DEVIATIONS.iter())
+            .enumerate()
+        {
+            let mut extra_bytes = amplitude.to_le_bytes().to_vec();
+            extra_bytes.extend_from_slice(&deviation.to_le_bytes());
+            writer.write(las_rs::Point {
+                x: index as f64,
+                y: 1.0,
+                z: 2.0,
+                gps_time: Some(index as f64),
+                extra_bytes,
+                ..Default::default()
+            })?;
+        }
+        Ok(writer.into_inner()?.into_inner())
+    }
+
+    #[test]
+    fn test_read_extra_bytes_attributes() -> Result<()> {
+        let amplitude = PointAttributeDefinition::custom("amplitude", PointAttributeDataType::F64);
+        let deviation = PointAttributeDefinition::custom("deviation", PointAttributeDataType::F32);
+        let expected_amplitudes = TEST_AMPLITUDES
+            .iter()
+            .map(|amplitude| *amplitude as f64 * 0.1 + 5.0)
+            .collect::<Vec<_>>();
+
+        for compressed in [false, true].iter() {
+            let data = las_file_with_extra_bytes(*compressed)?;
+            let mut reader = LASReader::from_read(Cursor::new(data.clone()), *compressed)?;
+            let layout = reader.get_default_point_layout();
+            assert!(layout.has_attribute(&amplitude));
+            assert!(layout.has_attribute(&deviation));
+
+            let points = reader.read(TEST_AMPLITUDES.len())?;
+            let amplitudes = points.iter_attribute::<f64>(&amplitude).collect::<Vec<_>>();
+            assert_eq!(expected_amplitudes, amplitudes);
+            let deviations = points.iter_attribute::<f32>(&deviation).collect::<Vec<_>>();
+            assert_eq!(TEST_DEVIATIONS.to_vec(), deviations);
+            let gps_times = points
+                .iter_attribute::<f64>(&attributes::GPS_TIME)
+                .collect::<Vec<_>>();
+            assert_eq!(vec![0.0, 1.0, 2.0], gps_times);
+
+            // Extra bytes attributes are converted when reading into a custom layout
+            let deviation_f64 = deviation.with_custom_datatype(PointAttributeDataType::F64);
+            let mut reader = LASReader::from_read(Cursor::new(data), *compressed)?;
+            let mut custom_points =
+                InterleavedVecPointStorage::new(PointLayout::from_attributes(&[
+                    attributes::POSITION_3D,
+                    deviation_f64.clone(),
+                ]));
+            reader.read_into(&mut custom_points, TEST_AMPLITUDES.len())?;
+            let deviations = custom_points
+                .iter_attribute::<f64>(&deviation_f64)
+                .collect::<Vec<_>>();
+            let expected_deviations = TEST_DEVIATIONS
+                .iter()
+                .map(|deviation| *deviation as f64)
+                .collect::<Vec<_>>();
+            assert_eq!(expected_deviations, deviations);
+            let positions = custom_points
+                .iter_attribute::<Vector3<f64>>(&attributes::POSITION_3D)
+                .collect::<Vec<_>>();
+            assert_eq!(Vector3::new(2.0, 1.0, 2.0), positions[2]);
+        }
+        Ok(())
+    }
+}
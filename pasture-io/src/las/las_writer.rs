@@ -1,15 +1,40 @@
-use std::{fs::File, io::BufWriter, io::Seek, io::Write, path::Path};
-
-use anyhow::Result;
+use std::{
+    fs::{File, OpenOptions},
+    io::BufWriter,
+    io::Read,
+    io::Seek,
+    io::Write,
+    path::Path,
+};
+
+use anyhow::{bail, Result};
 use pasture_core::{containers::PointBuffer, layout::PointLayout};
 
 use crate::base::PointWriter;
 
-use super::{path_is_compressed_las_file, RawLASWriter, RawLAZWriter};
+use super::{
+    las_header_with_extra_bytes, path_is_compressed_las_file, read_las_header_for_append,
+    LASWriterBase, RawLASWriter, RawLAZWriter,
+};
+
+trait AnyLASWriter: PointWriter + LASWriterBase {}
+
+impl<T: PointWriter + LASWriterBase> AnyLASWriter for T {}
 
 /// `PointWriter` implementation for LAS/LAZ files
+///
+/// The bounds and point counts in the LAS header are computed from the points while they are written, so any bounds
+/// in the header passed to the `LASWriter` are ignored. The final header is written once the `LASWriter` is flushed
+/// or dropped. Since the header is located at the start of the file, this requires seeking back, which is why the
+/// underlying writer has to implement `Seek`. To write to a non-seekable sink, write into an in-memory buffer such as
+/// a `Cursor<Vec<u8>>` first
+///
+/// Custom attributes that are not part of any LAS point format can be written into the extra bytes of the point records
+/// by creating the `LASWriter` with [from_path_header_and_layout](LASWriter::from_path_header_and_layout) or
+/// [from_writer_header_and_layout](LASWriter::from_writer_header_and_layout). Extra bytes are only supported for
+/// uncompressed LAS files
 pub struct LASWriter {
-    writer: Box<dyn PointWriter>,
+    writer: Box<dyn AnyLASWriter>,
 }
 
 impl LASWriter {
@@ -26,13 +51,92 @@ impl LASWriter {
         header: las::Header,
         is_compressed: bool,
     ) -> Result<Self> {
-        let raw_writer: Box<dyn PointWriter> = if is_compressed {
+        let raw_writer: Box<dyn AnyLASWriter> = if is_compressed {
             Box::new(RawLAZWriter::from_write_and_header(writer, header)?)
         } else {
             Box::new(RawLASWriter::from_write_and_header(writer, header)?)
         };
         Ok(Self { writer: raw_writer })
     }
+
+    /// Creates a new `LASWriter` from the given path and LAS header that also writes all custom attributes of the given
+    /// `layout` into the extra bytes of the point records. See [las_header_with_extra_bytes] for details on how these
+    /// attributes are stored
+    ///
+    /// # Errors
+    ///
+    /// If the extra bytes can't be added to `header`, or if `layout` contains custom attributes and `path` refers to a
+    /// compressed LAZ file
+    pub fn from_path_header_and_layout<P: AsRef<Path>>(
+        path: P,
+        header: las::Header,
+        layout: &PointLayout,
+    ) -> Result<Self> {
+        let is_compressed = path_is_compressed_las_file(path.as_ref())?;
+        let writer = BufWriter::new(File::create(path)?);
+        Self::from_writer_header_and_layout(writer, header, layout, is_compressed)
+    }
+
+    /// Creates a new `LASWriter` from the given writer and LAS header that also writes all custom attributes of the given
+    /// `layout` into the extra bytes of the point records. See [LASWriter::from_path_header_and_layout] for details
+    pub fn from_writer_header_and_layout<T: Write + Seek + Send + 'static>(
+        writer: T,
+        header: las::Header,
+        layout: &PointLayout,
+        is_compressed: bool,
+    ) -> Result<Self> {
+        let header = las_header_with_extra_bytes(&header, layout)?;
+        Self::from_writer_and_header(writer, header, is_compressed)
+    }
+
+    /// Creates a new `LASWriter` that appends points to the existing LAS file at `path`. The points are written after the
+    /// existing point records using the point format of the file, and the point counts and bounds in the header are
+    /// updated once the `LASWriter` is flushed or dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be opened or is not a valid LAS file. Appending to compressed LAZ files and to
+    /// files with extra bytes in their point records is not supported. Writing points that contain attributes which the
+    /// point format of the file can't store will also fail.
+    pub fn append<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if path_is_compressed_las_file(path.as_ref())? {
+            bail!("Appending to compressed LAZ files is not supported");
+        }
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let (raw_header, evlrs) = read_las_header_for_append(&mut file)?;
+        let raw_writer = RawLASWriter::append_to(BufWriter::new(file), raw_header, evlrs)?;
+        Ok(Self {
+            writer: Box::new(raw_writer),
+        })
+    }
+
+    /// Creates a new `LASWriter` that appends points to the existing LAS data in `writer`. See [LASWriter::append] for details
+    pub fn append_to_writer<T: Read + Write + Seek + Send + 'static>(
+        mut writer: T,
+    ) -> Result<Self> {
+        let (raw_header, evlrs) = read_las_header_for_append(&mut writer)?;
+        let raw_writer = RawLASWriter::append_to(writer, raw_header, evlrs)?;
+        Ok(Self {
+            writer: Box::new(raw_writer),
+        })
+    }
+
+    /// Returns the type of the GPS times that this `LASWriter` writes, as stored in the global encoding of the LAS header
+    pub fn gps_time_type(&self) -> las::GpsTimeType {
+        self.writer.gps_time_type()
+    }
+
+    /// Sets the type of the GPS times that this `LASWriter` writes. This only changes the global encoding in the LAS
+    /// header, the GPS times themselves are written unaltered. To convert GPS times between the two types, use
+    /// [convert_gps_times_to_week_time](super::convert_gps_times_to_week_time) and
+    /// [convert_gps_times_to_standard_time](super::convert_gps_times_to_standard_time)
+    ///
+    /// # Errors
+    ///
+    /// If `gps_time_type` is standard GPS time, but the LAS version of the header is earlier than 1.2
+    pub fn set_gps_time_type(&mut self, gps_time_type: las::GpsTimeType) -> Result<()> {
+        self.writer.set_gps_time_type(gps_time_type)
+    }
 }
 
 impl PointWriter for LASWriter {
@@ -53,18 +157,21 @@ impl PointWriter for LASWriter {
 mod tests {
     use std::path::PathBuf;
 
-    use las::{point::Format, Builder};
+    use las::{point::Format, Builder, GpsTimeType};
     use pasture_core::{
-        containers::InterleavedVecPointStorage, containers::PointBufferExt, layout::PointType,
-        nalgebra::Vector3,
+        containers::InterleavedVecPointStorage,
+        containers::{PerAttributeVecPointStorage, PointBufferExt},
+        layout::{attributes, PointAttributeDataType, PointAttributeDefinition, PointType},
+        nalgebra::{Point3, Vector3},
     };
     use scopeguard::defer;
 
     use crate::{
         base::PointReader,
         las::{
-            LASReader, LasPointFormat0, LasPointFormat1, LasPointFormat2, LasPointFormat3,
-            LasPointFormat4, LasPointFormat5,
+            convert_gps_times_to_week_time, epsilon_compare_point3f64, extra_bytes_records,
+            point_layout_from_las_point_format, LASReader, LasPointFormat0, LasPointFormat1,
+            LasPointFormat2, LasPointFormat3, LasPointFormat4, LasPointFormat5,
         },
     };
     use pasture_derive::PointType;
@@ -313,6 +420,126 @@ mod tests {
         source_point_buffer
     }
 
+    fn test_bounds_in_header(extension: &str) -> Result<()> {
+        let first_batch = prepare_point_buffer(&get_test_points_custom_format());
+        let second_batch = prepare_point_buffer(&[TestPoint {
+            position: Vector3::new(-1.0, 10.0, 4.5),
+            color: Default::default(),
+        }]);
+
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push(format!("test_write_bounds.{}", extension));
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(2)?;
+
+        {
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            // Bounds have to be accumulated over multiple writes
+            writer.write(&first_batch)?;
+            writer.write(&second_batch)?;
+        }
+
+        {
+            let reader = LASReader::from_path(&test_file_path)?;
+            let bounds = reader
+                .get_metadata()
+                .bounds()
+                .expect("LAS file must have bounds");
+            assert!(epsilon_compare_point3f64(
+                &Point3::new(-1.0, 2.0, 3.0),
+                bounds.min()
+            ));
+            assert!(epsilon_compare_point3f64(
+                &Point3::new(4.0, 10.0, 6.0),
+                bounds.max()
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_las_bounds() -> Result<()> {
+        test_bounds_in_header("las")
+    }
+
+    #[test]
+    fn test_write_laz_bounds() -> Result<()> {
+        test_bounds_in_header("laz")
+    }
+
+    #[test]
+    fn test_write_empty_las_bounds() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_write_empty_las_bounds.las");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(0)?;
+
+        {
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            writer.flush()?;
+        }
+
+        let reader = LASReader::from_path(&test_file_path)?;
+        let bounds = reader
+            .get_metadata()
+            .bounds()
+            .expect("LAS file must have bounds");
+        assert_eq!(Point3::new(0.0, 0.0, 0.0), *bounds.min());
+        assert_eq!(Point3::new(0.0, 0.0, 0.0), *bounds.max());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_empty_las_buffer_roundtrip() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_write_empty_las_buffer_roundtrip.las");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(2)?;
+
+        {
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            writer.write(&InterleavedVecPointStorage::new(LasPointFormat2::layout()))?;
+            writer.flush()?;
+        }
+
+        let mut reader = LASReader::from_path(&test_file_path)?;
+        assert_eq!(Some(0), reader.get_metadata().number_of_points());
+        let read_points = reader.read(0)?;
+        assert_eq!(0, read_points.len());
+        assert_eq!(
+            &point_layout_from_las_point_format(&Format::new(2)?)?,
+            read_points.point_layout()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_write_las_format_0() -> Result<()> {
         let source_points = get_test_points_las_format_0();
@@ -1068,4 +1295,321 @@ mod tests {
 
         Ok(())
     }
+
+    fn test_point_source_id_and_user_data_roundtrip(format: u8, extension: &str) -> Result<()> {
+        // Two flightlines with five points each, every flightline has its own point source ID
+        let layout = PointLayout::from_attributes(&[
+            attributes::POSITION_3D,
+            attributes::POINT_SOURCE_ID,
+            attributes::USER_DATA,
+        ]);
+        let mut source_point_buffer = PerAttributeVecPointStorage::new(layout);
+        let positions = (0..10)
+            .map(|idx| Vector3::new(idx as f64, 0.0, 0.0))
+            .collect::<Vec<_>>();
+        let point_source_ids = (0..10)
+            .map(|idx| if idx < 5 { 17_u16 } else { 4242 })
+            .collect::<Vec<_>>();
+        let user_data = (0..10).map(|idx| idx as u8 * 3).collect::<Vec<_>>();
+        {
+            let mut pusher = source_point_buffer.begin_push_attributes();
+            pusher.push_attribute_range(&attributes::POSITION_3D, &positions);
+            pusher.push_attribute_range(&attributes::POINT_SOURCE_ID, &point_source_ids);
+            pusher.push_attribute_range(&attributes::USER_DATA, &user_data);
+            pusher.done();
+        }
+
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push(format!(
+            "test_write_point_source_id_format_{}.{}",
+            format, extension
+        ));
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(format)?;
+
+        {
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            writer.write(&source_point_buffer)?;
+        }
+
+        {
+            let mut reader = LASReader::from_path(&test_file_path)?;
+            let read_points_buffer = reader.read(10)?;
+            let read_point_source_ids = read_points_buffer
+                .iter_attribute::<u16>(&attributes::POINT_SOURCE_ID)
+                .collect::<Vec<_>>();
+            assert_eq!(point_source_ids, read_point_source_ids);
+            let read_user_data = read_points_buffer
+                .iter_attribute::<u8>(&attributes::USER_DATA)
+                .collect::<Vec<_>>();
+            assert_eq!(user_data, read_user_data);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_las_point_source_id_and_user_data() -> Result<()> {
+        test_point_source_id_and_user_data_roundtrip(0, "las")?;
+        test_point_source_id_and_user_data_roundtrip(6, "las")
+    }
+
+    #[test]
+    fn test_write_laz_point_source_id_and_user_data() -> Result<()> {
+        // Format 6 is only covered as LAS, because the LAZ reader does not support the extended formats
+        test_point_source_id_and_user_data_roundtrip(0, "laz")?;
+        test_point_source_id_and_user_data_roundtrip(3, "laz")
+    }
+
+    #[test]
+    fn test_append_to_las_file() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_append_to_las_file.las");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let source_points = get_test_points_las_format_1();
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(1)?;
+
+        {
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            writer.write(&prepare_point_buffer(&source_points[..1]))?;
+        }
+
+        {
+            let mut writer = LASWriter::append(&test_file_path)?;
+            writer.write(&prepare_point_buffer(&source_points[1..]))?;
+        }
+
+        let mut reader = LASReader::from_path(&test_file_path)?;
+        assert_eq!(source_points.len(), reader.remaining_points());
+        let bounds = reader
+            .get_metadata()
+            .bounds()
+            .expect("LAS file must have bounds");
+        assert!(epsilon_compare_point3f64(
+            &Point3::new(1.0, 1.0, 1.0),
+            bounds.min()
+        ));
+        assert!(epsilon_compare_point3f64(
+            &Point3::new(2.0, 2.0, 2.0),
+            bounds.max()
+        ));
+
+        let read_points_buffer = reader.read(source_points.len())?;
+        let read_points: Vec<LasPointFormat1> = read_points_buffer.iter_point().collect();
+        assert_eq!(read_points, source_points);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_to_las_file_with_unsupported_attributes() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_append_to_las_file_with_unsupported_attributes.las");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(0)?;
+
+        {
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            writer.write(&prepare_point_buffer(&get_test_points_las_format_0()))?;
+        }
+
+        {
+            let mut writer = LASWriter::append_to_writer(
+                std::fs::OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(&test_file_path)?,
+            )?;
+            // Format 0 can't store GPS times, so appending points with GPS times must fail
+            assert!(writer
+                .write(&prepare_point_buffer(&get_test_points_las_format_1()))
+                .is_err());
+            writer.write(&prepare_point_buffer(&get_test_points_las_format_0()))?;
+        }
+
+        let mut reader = LASReader::from_path(&test_file_path)?;
+        assert_eq!(4, reader.remaining_points());
+
+        Ok(())
+    }
+
+    #[repr(C, packed)]
+    #[derive(Debug, Clone, Copy, PointType)]
+    struct PointWithExtraBytes {
+        #[pasture(BUILTIN_POSITION_3D)]
+        pub position: Vector3<f64>,
+        #[pasture(attribute = "Deviation")]
+        pub deviation: f64,
+        #[pasture(BUILTIN_INTENSITY)]
+        pub intensity: u16,
+        #[pasture(attribute = "Amplitude")]
+        pub amplitude: u16,
+    }
+
+    #[test]
+    fn test_write_extra_bytes_attributes() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_write_extra_bytes_attributes.las");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let source_points = vec![
+            PointWithExtraBytes {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                deviation: 0.125,
+                intensity: 1,
+                amplitude: 300,
+            },
+            PointWithExtraBytes {
+                position: Vector3::new(4.0, 5.0, 6.0),
+                deviation: -1234.5,
+                intensity: 2,
+                amplitude: 65535,
+            },
+        ];
+
+        {
+            let mut las_header_builder = Builder::from((1, 4));
+            las_header_builder.point_format = Format::new(0)?;
+            let mut writer = LASWriter::from_path_header_and_layout(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+                &PointWithExtraBytes::layout(),
+            )?;
+            writer.write(&prepare_point_buffer(&source_points))?;
+        }
+
+        let deviation = PointAttributeDefinition::custom("Deviation", PointAttributeDataType::F64);
+        let amplitude = PointAttributeDefinition::custom("Amplitude", PointAttributeDataType::U16);
+
+        let mut reader = LASReader::from_path(&test_file_path)?;
+        // The custom attributes are stored in layout order and tightly packed after the point format 0 attributes
+        assert_eq!(10, reader.header().point_format().extra_bytes);
+        let records = extra_bytes_records(reader.header())?;
+        assert_eq!(2, records.len());
+        assert_eq!("Deviation", records[0].name());
+        assert_eq!(10, records[0].data_type);
+        assert_eq!("Amplitude", records[1].name());
+        assert_eq!(3, records[1].data_type);
+        assert!(reader.get_default_point_layout().has_attribute(&deviation));
+        assert!(reader.get_default_point_layout().has_attribute(&amplitude));
+
+        let read_points = reader.read(source_points.len())?;
+        let deviations = read_points
+            .iter_attribute::<f64>(&deviation)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0.125, -1234.5], deviations);
+        let amplitudes = read_points
+            .iter_attribute::<u16>(&amplitude)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![300, 65535], amplitudes);
+        let intensities = read_points
+            .iter_attribute::<u16>(&attributes::INTENSITY)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2], intensities);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_extra_bytes_attributes_to_laz_fails() {
+        let mut las_header_builder = Builder::from((1, 4));
+        las_header_builder.point_format = Format::new(0).unwrap();
+        assert!(LASWriter::from_writer_header_and_layout(
+            std::io::Cursor::new(vec![]),
+            las_header_builder.into_header().unwrap(),
+            &PointWithExtraBytes::layout(),
+            true,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_gps_time_type() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("test_gps_time_type.las");
+
+        defer! {
+            std::fs::remove_file(&test_file_path).expect("Removing test file failed!");
+        }
+
+        let standard_times = vec![2.0e8, 1_234_567.5];
+        let mut source_points = get_test_points_las_format_1();
+        for (point, gps_time) in source_points.iter_mut().zip(standard_times.iter()) {
+            point.gps_time = *gps_time;
+        }
+
+        {
+            let mut las_header_builder = Builder::from((1, 2));
+            las_header_builder.point_format = Format::new(1)?;
+            let mut writer = LASWriter::from_path_and_header(
+                &test_file_path,
+                las_header_builder.into_header().unwrap(),
+            )?;
+            assert_eq!(GpsTimeType::Week, writer.gps_time_type());
+            writer.set_gps_time_type(GpsTimeType::Standard)?;
+            assert_eq!(GpsTimeType::Standard, writer.gps_time_type());
+            writer.write(&prepare_point_buffer(&source_points))?;
+        }
+
+        let mut reader = LASReader::from_path(&test_file_path)?;
+        assert_eq!(GpsTimeType::Standard, reader.gps_time_type());
+
+        let mut points = InterleavedVecPointStorage::new(reader.get_default_point_layout().clone());
+        reader.read_into(&mut points, source_points.len())?;
+        assert_eq!(
+            standard_times,
+            points
+                .iter_attribute::<f64>(&attributes::GPS_TIME)
+                .collect::<Vec<_>>()
+        );
+
+        convert_gps_times_to_week_time(&mut points);
+        assert_eq!(
+            vec![76_800.0, 290_567.5],
+            points
+                .iter_attribute::<f64>(&attributes::GPS_TIME)
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_standard_gps_time_requires_las_1_2() {
+        let las_header_builder = Builder::from((1, 1));
+        let mut writer = LASWriter::from_writer_and_header(
+            std::io::Cursor::new(vec![]),
+            las_header_builder.into_header().unwrap(),
+            false,
+        )
+        .unwrap();
+        assert!(writer.set_gps_time_type(GpsTimeType::Standard).is_err());
+    }
 }
@@ -4,6 +4,9 @@ pub use self::las_reader::*;
 mod las_writer;
 pub use self::las_writer::*;
 
+mod las_merge;
+pub use self::las_merge::*;
+
 mod las_layout;
 pub use self::las_layout::*;
 
@@ -13,6 +16,21 @@ pub use self::las_types::*;
 mod las_metadata;
 pub use self::las_metadata::*;
 
+mod scan_angle;
+pub use self::scan_angle::*;
+
+mod classification_flags;
+pub use self::classification_flags::*;
+
+mod fixed_strings;
+pub use self::fixed_strings::*;
+
+mod extra_bytes;
+pub use self::extra_bytes::*;
+
+mod gps_time;
+pub use self::gps_time::*;
+
 mod raw_readers;
 pub(crate) use self::raw_readers::*;
 
@@ -1,4 +1,4 @@
-use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 use anyhow::{anyhow, Result};
 use byteorder::{LittleEndian, NativeEndian, ReadBytesExt, WriteBytesExt};
@@ -21,10 +21,12 @@ use pasture_core::{
 };
 
 use super::{
-    map_laz_err, point_layout_from_las_point_format, BitAttributes, BitAttributesExtended,
-    BitAttributesRegular, LASMetadata,
+    extended_scan_angle_to_scan_angle_rank, extra_bytes_fields, map_laz_err,
+    point_layout_from_las_header, scan_angle_rank_to_extended_scan_angle,
+    split_legacy_classification, BitAttributes, BitAttributesExtended, BitAttributesRegular,
+    ExtraBytesField, LASMetadata, LazReaderOptions,
 };
-use crate::base::{PointReader, SeekToPoint};
+use crate::base::{clamp_points_per_read, PointReader, SeekToPoint, DEFAULT_MAX_POINTS_PER_READ};
 
 /// Is the given VLR the LASzip VLR? Function taken from the `las` crate because it is not exported there
 fn is_laszip_vlr(vlr: &Vlr) -> bool {
@@ -35,10 +37,71 @@ fn is_laszip_vlr(vlr: &Vlr) -> bool {
     }
 }
 
+/// Reads a single extra bytes attribute into a target layout: The field within the extra bytes, the offset and size of the
+/// attribute within the target layout, and a converter if the target attribute has a different datatype
+type ExtraBytesParser<'a> = (
+    &'a ExtraBytesField,
+    usize,
+    usize,
+    Option<AttributeConversionFn>,
+);
+
+/// Returns parsers for all of the given extra bytes `fields` that are contained in the `target_layout`
+fn extra_bytes_parsers<'a>(
+    fields: &'a [ExtraBytesField],
+    target_layout: &PointLayout,
+) -> Vec<ExtraBytesParser<'a>> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let source_attribute = field.attribute.as_ref()?;
+            let target_attribute = target_layout.get_attribute_by_name(source_attribute.name())?;
+            let converter =
+                get_converter_for_attributes(source_attribute, &target_attribute.into());
+            Some((
+                field,
+                target_attribute.offset() as usize,
+                target_attribute.size() as usize,
+                converter,
+            ))
+        })
+        .collect()
+}
+
+/// Reads the extra bytes attributes of a single point from its `extra_bytes` into the point that starts at
+/// `start_of_target_point_in_chunk` within `chunk_buffer`
+fn run_extra_bytes_parsers(
+    parsers: &[ExtraBytesParser<'_>],
+    extra_bytes: &[u8],
+    start_of_target_point_in_chunk: usize,
+    chunk_buffer: &mut [u8],
+) {
+    for (field, offset, size, maybe_converter) in parsers {
+        let source_data = field.read_value(extra_bytes);
+
+        let pos_start = start_of_target_point_in_chunk + offset;
+        let pos_end = pos_start + size;
+        let target_slice = &mut chunk_buffer[pos_start..pos_end];
+
+        if let Some(converter) = maybe_converter {
+            unsafe {
+                converter(&source_data, target_slice);
+            }
+        } else {
+            target_slice.copy_from_slice(&source_data);
+        }
+    }
+}
+
 pub(crate) trait LASReaderBase {
     /// Returns the remaining number of points in the underyling `LASReaderBase`
     fn remaining_points(&self) -> usize;
     fn header(&self) -> &Header;
+    /// Returns the maximum number of points that a single call to `read` or `read_into` may read
+    fn max_points_per_read(&self) -> usize;
+    /// Sets the maximum number of points that a single call to `read` or `read_into` may read, see
+    /// [clamp_points_per_read]
+    fn set_max_points_per_read(&mut self, max_points_per_read: usize);
 }
 
 pub(crate) struct RawLASReader<T: Read + Seek> {
@@ -50,6 +113,8 @@ pub(crate) struct RawLASReader<T: Read + Seek> {
     point_scales: Vector3<f64>,
     offset_to_first_point_in_file: u64,
     size_of_point_in_file: u64,
+    extra_bytes_fields: Vec<ExtraBytesField>,
+    max_points_per_read: usize,
     //TODO Add an option to not convert the position fields into world space
 }
 
@@ -69,9 +134,19 @@ impl<T: Read + Seek> RawLASReader<T> {
             raw_header.z_scale_factor,
         );
 
-        let header = Header::from_raw(raw_header)?;
+        let number_of_vlrs = raw_header.number_of_variable_length_records;
+
+        // The VLRs are required for the extra bytes records
+        let mut header_builder = Builder::new(raw_header)?;
+        for _ in 0..number_of_vlrs {
+            let vlr = raw::Vlr::read_from(&mut read, false).map(Vlr::new)?;
+            header_builder.vlrs.push(vlr);
+        }
+
+        let header = header_builder.into_header()?;
         let metadata: LASMetadata = header.clone().into();
-        let point_layout = point_layout_from_las_point_format(header.point_format())?;
+        let point_layout = point_layout_from_las_header(&header)?;
+        let extra_bytes_fields = extra_bytes_fields(&header)?;
 
         read.seek(SeekFrom::Start(offset_to_first_point_in_file as u64))?;
 
@@ -84,6 +159,8 @@ impl<T: Read + Seek> RawLASReader<T> {
             point_scales,
             offset_to_first_point_in_file,
             size_of_point_in_file,
+            extra_bytes_fields,
+            max_points_per_read: DEFAULT_MAX_POINTS_PER_READ,
         })
     }
 
@@ -94,13 +171,14 @@ impl<T: Read + Seek> RawLASReader<T> {
     ) -> Result<()> {
         let mut buffer_cursor = Cursor::new(chunk_buffer);
 
-        let format = Format::new(self.metadata.point_format())?;
+        let format = self.header().point_format().clone();
 
         let offset_to_first_point_in_file = self.reader.seek(SeekFrom::Current(0))?;
+        let mut extra_bytes = vec![0; format.extra_bytes as usize];
 
         for point_index in 0..num_points_in_chunk {
-            // Point size might be larger than what the format indicates due to extra bytes. Extra bytes are not
-            // supported by pasture at the moment, so we skip over them
+            // Point size might be larger than what the format indicates due to extra bytes that are not described by
+            // an extra bytes record, so we seek to the start of each point explicitly
             let start_of_source_point =
                 offset_to_first_point_in_file + point_index as u64 * self.size_of_point_in_file;
             self.reader.seek(SeekFrom::Start(start_of_source_point))?;
@@ -192,6 +270,16 @@ impl<T: Read + Seek> RawLASReader<T> {
                 buffer_cursor.write_f32::<NativeEndian>(self.reader.read_f32::<LittleEndian>()?)?;
                 buffer_cursor.write_f32::<NativeEndian>(self.reader.read_f32::<LittleEndian>()?)?;
             }
+
+            // Extra bytes attributes
+            if !extra_bytes.is_empty() {
+                self.reader.read_exact(&mut extra_bytes)?;
+                for field in &self.extra_bytes_fields {
+                    if field.attribute.is_some() {
+                        buffer_cursor.write_all(&field.read_value(&extra_bytes))?;
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -206,7 +294,7 @@ impl<T: Read + Seek> RawLASReader<T> {
     ) -> Result<()> {
         //let mut buffer_cursor = Cursor::new(chunk_buffer);
 
-        let source_format = Format::new(self.metadata.point_format())?;
+        let source_format = self.header().point_format().clone();
 
         // This probably works best by introducing a type that stores all information needed for reading and writing a single
         // attribute:
@@ -277,6 +365,8 @@ impl<T: Read + Seek> RawLASReader<T> {
             get_attribute_parser(&attributes::CLASSIFICATION, &self.layout, target_layout);
         let target_scan_angle_rank_parser =
             get_attribute_parser(&attributes::SCAN_ANGLE_RANK, &self.layout, target_layout);
+        let target_scan_angle_parser =
+            get_attribute_parser(&attributes::SCAN_ANGLE, &self.layout, target_layout);
         let target_user_data_parser =
             get_attribute_parser(&attributes::USER_DATA, &self.layout, target_layout);
         let target_point_source_id_parser =
@@ -312,6 +402,10 @@ impl<T: Read + Seek> RawLASReader<T> {
             target_layout,
         );
 
+        let target_extra_bytes_parsers =
+            extra_bytes_parsers(&self.extra_bytes_fields, target_layout);
+        let start_of_extra_bytes = (source_format.len() - source_format.extra_bytes) as usize;
+
         let target_point_size = target_layout.size_of_point_entry() as usize;
 
         fn run_parser<T: Read + Seek, U>(
@@ -350,8 +444,8 @@ impl<T: Read + Seek> RawLASReader<T> {
         let mut source_reader = Cursor::new(source_data);
 
         for point_index in 0..num_points_in_chunk {
-            // Point size might be larger than what the format indicates due to extra bytes. Extra bytes are not
-            // supported by pasture at the moment, so we skip over them
+            // Point size might be larger than what the format indicates due to extra bytes. They are read at the end
+            // of the point, so we can skip over them here
             let start_of_source_point = point_index as u64 * self.size_of_point_in_file;
             source_reader.seek(SeekFrom::Start(start_of_source_point))?;
 
@@ -395,14 +489,6 @@ impl<T: Read + Seek> RawLASReader<T> {
                 &mut source_reader,
                 chunk_buffer,
             )?;
-            run_parser(
-                |_| Ok(bit_attributes.classification_flags_or_default()),
-                target_classification_flags_parser,
-                start_of_target_point_in_chunk,
-                None,
-                &mut source_reader,
-                chunk_buffer,
-            )?;
             run_parser(
                 |_| Ok(bit_attributes.scanner_channel_or_default()),
                 target_scanner_channel_parser,
@@ -428,11 +514,32 @@ impl<T: Read + Seek> RawLASReader<T> {
                 chunk_buffer,
             )?;
 
+            // The regular LAS formats store the classification flags in the upper bits of the classification. These
+            // are only split off if the target layout explicitly requests the CLASSIFICATION_FLAGS attribute, otherwise
+            // the classification is read unaltered
+            let classification_byte = source_reader.read_u8()?;
+            let (classification, classification_flags) =
+                if !source_format.is_extended && target_classification_flags_parser.is_some() {
+                    split_legacy_classification(classification_byte)
+                } else {
+                    (
+                        classification_byte,
+                        bit_attributes.classification_flags_or_default(),
+                    )
+                };
             run_parser(
-                |buf| Ok(buf.read_u8()?),
+                |_| Ok(classification_flags),
+                target_classification_flags_parser,
+                start_of_target_point_in_chunk,
+                None,
+                &mut source_reader,
+                chunk_buffer,
+            )?;
+            run_parser(
+                |_| Ok(classification),
                 target_classification_parser,
                 start_of_target_point_in_chunk,
-                Some(1),
+                None,
                 &mut source_reader,
                 chunk_buffer,
             )?;
@@ -448,21 +555,41 @@ impl<T: Read + Seek> RawLASReader<T> {
                     chunk_buffer,
                 )?;
 
+                // The scan angle is read into SCAN_ANGLE as-is, or converted into the legacy SCAN_ANGLE_RANK
+                let scan_angle = source_reader.read_i16::<LittleEndian>()?;
                 run_parser(
-                    |buf| Ok(buf.read_i16::<LittleEndian>()?),
+                    |_| Ok(scan_angle),
+                    target_scan_angle_parser,
+                    start_of_target_point_in_chunk,
+                    None,
+                    &mut source_reader,
+                    chunk_buffer,
+                )?;
+                run_parser(
+                    |_| Ok(extended_scan_angle_to_scan_angle_rank(scan_angle)),
                     target_scan_angle_rank_parser,
                     start_of_target_point_in_chunk,
-                    Some(2),
+                    None,
                     &mut source_reader,
                     chunk_buffer,
                 )?;
             } else {
-                // Regular formats have scan angle rank before user data
+                // Regular formats have scan angle rank before user data. The scan angle rank is read into
+                // SCAN_ANGLE_RANK as-is, or converted into the extended SCAN_ANGLE
+                let scan_angle_rank = source_reader.read_i8()?;
                 run_parser(
-                    |buf| Ok(buf.read_i8()?),
+                    |_| Ok(scan_angle_rank),
                     target_scan_angle_rank_parser,
                     start_of_target_point_in_chunk,
-                    Some(1),
+                    None,
+                    &mut source_reader,
+                    chunk_buffer,
+                )?;
+                run_parser(
+                    |_| Ok(scan_angle_rank_to_extended_scan_angle(scan_angle_rank)),
+                    target_scan_angle_parser,
+                    start_of_target_point_in_chunk,
+                    None,
                     &mut source_reader,
                     chunk_buffer,
                 )?;
@@ -590,6 +717,15 @@ impl<T: Read + Seek> RawLASReader<T> {
                 &mut source_reader,
                 chunk_buffer,
             )?;
+
+            let extra_bytes_start = start_of_source_point as usize + start_of_extra_bytes;
+            run_extra_bytes_parsers(
+                &target_extra_bytes_parsers,
+                &source_reader.get_ref()
+                    [extra_bytes_start..extra_bytes_start + source_format.extra_bytes as usize],
+                start_of_target_point_in_chunk,
+                chunk_buffer,
+            );
         }
 
         Ok(())
@@ -758,11 +894,20 @@ impl<T: Read + Seek> LASReaderBase for RawLASReader<T> {
     fn header(&self) -> &Header {
         self.metadata.raw_las_header().unwrap()
     }
+
+    fn max_points_per_read(&self) -> usize {
+        self.max_points_per_read
+    }
+
+    fn set_max_points_per_read(&mut self, max_points_per_read: usize) {
+        self.max_points_per_read = max_points_per_read;
+    }
 }
 
 impl<T: Read + Seek> PointReader for RawLASReader<T> {
     fn read(&mut self, count: usize) -> Result<Box<dyn pasture_core::containers::PointBuffer>> {
-        let num_points_to_read = usize::min(count, self.remaining_points());
+        let num_points_to_read =
+            clamp_points_per_read(count, self.remaining_points(), self.max_points_per_read)?;
         let mut buffer =
             InterleavedVecPointStorage::with_capacity(num_points_to_read, self.layout.clone());
 
@@ -776,6 +921,8 @@ impl<T: Read + Seek> PointReader for RawLASReader<T> {
         point_buffer: &mut dyn PointBufferWriteable,
         count: usize,
     ) -> Result<usize> {
+        let count =
+            clamp_points_per_read(count, self.remaining_points(), self.max_points_per_read)?;
         if *point_buffer.point_layout() != self.layout {
             self.read_into_custom_layout(point_buffer, count)
         } else {
@@ -816,18 +963,79 @@ impl<T: Read + Seek> SeekToPoint for RawLASReader<T> {
     }
 }
 
+/// Number of points per LAZ chunk that is assumed if the file uses variable-sized chunks
+const DEFAULT_LAZ_CHUNK_SIZE: usize = 50_000;
+
+/// The decompressor that a `RawLAZReader` uses, depending on its `LazReaderOptions`
+enum AnyLazDecompressor<'a, T: Read + Seek + Send + 'a> {
+    Sequential(LasZipDecompressor<'a, T>),
+    #[cfg(feature = "parallel")]
+    Parallel(laz::ParLasZipDecompressor<T>),
+}
+
+impl<'a, T: Read + Seek + Send + 'a> AnyLazDecompressor<'a, T> {
+    fn new(read: T, laszip_vlr: laz::LazVlr, options: &LazReaderOptions) -> Result<Self> {
+        if options.decompress_parallel {
+            #[cfg(feature = "parallel")]
+            {
+                return Ok(Self::Parallel(
+                    laz::ParLasZipDecompressor::new(read, laszip_vlr).map_err(map_laz_err)?,
+                ));
+            }
+            #[cfg(not(feature = "parallel"))]
+            {
+                return Err(anyhow!(
+                    "Parallel LAZ decompression requires the 'parallel' feature of pasture-io"
+                ));
+            }
+        }
+        Ok(Self::Sequential(
+            LasZipDecompressor::new(read, laszip_vlr).map_err(map_laz_err)?,
+        ))
+    }
+
+    fn decompress_many(&mut self, out: &mut [u8]) -> Result<()> {
+        match self {
+            Self::Sequential(decompressor) => decompressor.decompress_many(out)?,
+            #[cfg(feature = "parallel")]
+            Self::Parallel(decompressor) => {
+                // Honor a thread pool configured through `set_thread_pool`
+                pasture_core::util::run_parallel(|| decompressor.decompress_many(out))?
+            }
+        }
+        Ok(())
+    }
+
+    fn seek(&mut self, point_index: u64) -> Result<()> {
+        match self {
+            Self::Sequential(decompressor) => decompressor.seek(point_index)?,
+            #[cfg(feature = "parallel")]
+            Self::Parallel(decompressor) => decompressor.seek(point_index)?,
+        }
+        Ok(())
+    }
+}
+
 pub(crate) struct RawLAZReader<'a, T: Read + Seek + Send + 'a> {
-    reader: LasZipDecompressor<'a, T>,
+    reader: AnyLazDecompressor<'a, T>,
     metadata: LASMetadata,
     layout: PointLayout,
     current_point_index: usize,
     point_offsets: Vector3<f64>,
     point_scales: Vector3<f64>,
     size_of_point_in_file: u64,
+    extra_bytes_fields: Vec<ExtraBytesField>,
+    /// Number of points that are decompressed at once while reading
+    points_per_batch: usize,
+    max_points_per_read: usize,
 }
 
 impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
-    pub fn from_read(mut read: T) -> Result<Self> {
+    pub fn from_read(read: T) -> Result<Self> {
+        Self::from_read_with_options(read, LazReaderOptions::default())
+    }
+
+    pub fn from_read_with_options(mut read: T, options: LazReaderOptions) -> Result<Self> {
         let raw_header = raw::Header::read_from(&mut read)?;
         let offset_to_first_point_in_file = raw_header.offset_to_point_data as u64;
         let size_of_point_in_file = raw_header.point_data_record_length as u64;
@@ -864,7 +1072,8 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
         }
 
         let metadata: LASMetadata = header.clone().into();
-        let point_layout = point_layout_from_las_point_format(header.point_format())?;
+        let point_layout = point_layout_from_las_header(&header)?;
+        let extra_bytes_fields = extra_bytes_fields(&header)?;
 
         read.seek(SeekFrom::Start(offset_to_first_point_in_file as u64))?;
 
@@ -878,7 +1087,13 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
                 Ok(laz_record)
             }
         }?;
-        let reader = LasZipDecompressor::new(read, laszip_vlr).map_err(map_laz_err)?;
+        let laz_chunk_size = if laszip_vlr.uses_variable_size_chunks() {
+            DEFAULT_LAZ_CHUNK_SIZE
+        } else {
+            laszip_vlr.chunk_size() as usize
+        };
+        let points_per_batch = laz_chunk_size * usize::max(1, options.prefetch_chunks);
+        let reader = AnyLazDecompressor::new(read, laszip_vlr, &options)?;
 
         Ok(Self {
             reader,
@@ -888,6 +1103,9 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
             point_offsets,
             point_scales,
             size_of_point_in_file,
+            extra_bytes_fields,
+            points_per_batch,
+            max_points_per_read: DEFAULT_MAX_POINTS_PER_READ,
         })
     }
 
@@ -898,7 +1116,8 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
         num_points_in_chunk: usize,
     ) -> Result<()> {
         let bytes_in_chunk = num_points_in_chunk * self.size_of_point_in_file as usize;
-        let las_format = Format::new(self.metadata.point_format())?;
+        let las_format = self.header().point_format().clone();
+        let mut extra_bytes = vec![0; las_format.extra_bytes as usize];
 
         self.reader
             .decompress_many(&mut decompression_buffer[0..bytes_in_chunk])?;
@@ -907,8 +1126,8 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
 
         // Convert the decompressed points - which have XYZ as u32 - into the target layout
         for point_index in 0..num_points_in_chunk {
-            // Point size might be larger than what the format indicates due to extra bytes. Extra bytes are not
-            // supported by pasture at the moment, so we skip over them
+            // Point size might be larger than what the format indicates due to extra bytes that are not described by
+            // an extra bytes record, so we seek to the start of each point explicitly
             let start_of_point_in_decompressed_data =
                 point_index as u64 * self.size_of_point_in_file;
             decompression_chunk_cursor
@@ -1028,6 +1247,16 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
                     decompression_chunk_cursor.read_f32::<LittleEndian>()?,
                 )?;
             }
+
+            // Extra bytes attributes
+            if !extra_bytes.is_empty() {
+                decompression_chunk_cursor.read_exact(&mut extra_bytes)?;
+                for field in &self.extra_bytes_fields {
+                    if field.attribute.is_some() {
+                        target_chunk_cursor.write_all(&field.read_value(&extra_bytes))?;
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -1042,7 +1271,7 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
     ) -> Result<()> {
         // HACK Not happy with how large this function is... But there are so many special
         // cases, I don't know how to clean it up at the moment. Maybe revise in future?
-        let source_format = Format::new(self.metadata.point_format())?;
+        let source_format = self.header().point_format().clone();
 
         fn get_attribute_parser(
             default_attribute: &PointAttributeDefinition,
@@ -1104,6 +1333,8 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
             get_attribute_parser(&attributes::CLASSIFICATION, &self.layout, target_layout);
         let target_scan_angle_rank_parser =
             get_attribute_parser(&attributes::SCAN_ANGLE_RANK, &self.layout, target_layout);
+        let target_scan_angle_parser =
+            get_attribute_parser(&attributes::SCAN_ANGLE, &self.layout, target_layout);
         let target_user_data_parser =
             get_attribute_parser(&attributes::USER_DATA, &self.layout, target_layout);
         let target_point_source_id_parser =
@@ -1147,6 +1378,10 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
         )?;
         let mut decompressed_data = Cursor::new(decompression_buffer);
 
+        let target_extra_bytes_parsers =
+            extra_bytes_parsers(&self.extra_bytes_fields, target_layout);
+        let start_of_extra_bytes = (source_format.len() - source_format.extra_bytes) as usize;
+
         fn run_parser<T>(
             decoder_fn: impl Fn(&mut Cursor<&mut [u8]>) -> Result<T>,
             maybe_parser: Option<(usize, usize, Option<AttributeConversionFn>)>,
@@ -1178,8 +1413,8 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
         }
 
         for point_index in 0..num_points_in_chunk {
-            // Point size might be larger than what the format indicates due to extra bytes. Extra bytes are not
-            // supported by pasture at the moment, so we skip over them
+            // Point size might be larger than what the format indicates due to extra bytes. They are read at the end
+            // of the point, so we can skip over them here
             let start_of_point_in_decompressed_data =
                 point_index as u64 * self.size_of_point_in_file;
             decompressed_data.seek(SeekFrom::Start(start_of_point_in_decompressed_data))?;
@@ -1222,14 +1457,6 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
                 &mut decompressed_data,
                 chunk_buffer,
             )?;
-            run_parser(
-                |_| Ok(bit_attributes.classification_flags_or_default()),
-                target_classification_flags_parser,
-                start_of_target_point_in_chunk,
-                None,
-                &mut decompressed_data,
-                chunk_buffer,
-            )?;
             run_parser(
                 |_| Ok(bit_attributes.scanner_channel_or_default()),
                 target_scanner_channel_parser,
@@ -1255,11 +1482,32 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
                 chunk_buffer,
             )?;
 
+            // The regular LAS formats store the classification flags in the upper bits of the classification. These
+            // are only split off if the target layout explicitly requests the CLASSIFICATION_FLAGS attribute, otherwise
+            // the classification is read unaltered
+            let classification_byte = decompressed_data.read_u8()?;
+            let (classification, classification_flags) =
+                if !source_format.is_extended && target_classification_flags_parser.is_some() {
+                    split_legacy_classification(classification_byte)
+                } else {
+                    (
+                        classification_byte,
+                        bit_attributes.classification_flags_or_default(),
+                    )
+                };
             run_parser(
-                |buf| Ok(buf.read_u8()?),
+                |_| Ok(classification_flags),
+                target_classification_flags_parser,
+                start_of_target_point_in_chunk,
+                None,
+                &mut decompressed_data,
+                chunk_buffer,
+            )?;
+            run_parser(
+                |_| Ok(classification),
                 target_classification_parser,
                 start_of_target_point_in_chunk,
-                Some(1),
+                None,
                 &mut decompressed_data,
                 chunk_buffer,
             )?;
@@ -1275,21 +1523,41 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
                     chunk_buffer,
                 )?;
 
+                // The scan angle is read into SCAN_ANGLE as-is, or converted into the legacy SCAN_ANGLE_RANK
+                let scan_angle = decompressed_data.read_i16::<LittleEndian>()?;
+                run_parser(
+                    |_| Ok(scan_angle),
+                    target_scan_angle_parser,
+                    start_of_target_point_in_chunk,
+                    None,
+                    &mut decompressed_data,
+                    chunk_buffer,
+                )?;
                 run_parser(
-                    |buf| Ok(buf.read_i16::<LittleEndian>()?),
+                    |_| Ok(extended_scan_angle_to_scan_angle_rank(scan_angle)),
                     target_scan_angle_rank_parser,
                     start_of_target_point_in_chunk,
-                    Some(2),
+                    None,
                     &mut decompressed_data,
                     chunk_buffer,
                 )?;
             } else {
-                // Regular formats have scan angle rank before user data
+                // Regular formats have scan angle rank before user data. The scan angle rank is read into
+                // SCAN_ANGLE_RANK as-is, or converted into the extended SCAN_ANGLE
+                let scan_angle_rank = decompressed_data.read_i8()?;
                 run_parser(
-                    |buf| Ok(buf.read_i8()?),
+                    |_| Ok(scan_angle_rank),
                     target_scan_angle_rank_parser,
                     start_of_target_point_in_chunk,
-                    Some(1),
+                    None,
+                    &mut decompressed_data,
+                    chunk_buffer,
+                )?;
+                run_parser(
+                    |_| Ok(scan_angle_rank_to_extended_scan_angle(scan_angle_rank)),
+                    target_scan_angle_parser,
+                    start_of_target_point_in_chunk,
+                    None,
                     &mut decompressed_data,
                     chunk_buffer,
                 )?;
@@ -1417,6 +1685,16 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
                 &mut decompressed_data,
                 chunk_buffer,
             )?;
+
+            let extra_bytes_start =
+                start_of_point_in_decompressed_data as usize + start_of_extra_bytes;
+            run_extra_bytes_parsers(
+                &target_extra_bytes_parsers,
+                &decompressed_data.get_ref()
+                    [extra_bytes_start..extra_bytes_start + source_format.extra_bytes as usize],
+                start_of_target_point_in_chunk,
+                chunk_buffer,
+            );
         }
 
         Ok(())
@@ -1434,7 +1712,7 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
 
         // Read into chunks of a fixed size. Within each chunk, read all data into an untyped buffer
         // then push the untyped data into 'buffer'
-        let chunk_size = 50_000;
+        let chunk_size = self.points_per_batch;
         let point_size = self.layout.size_of_point_entry() as usize;
         let chunk_bytes = point_size as usize * chunk_size;
         let num_chunks = (num_points_to_read + chunk_size - 1) / chunk_size;
@@ -1477,7 +1755,7 @@ impl<'a, T: Read + Seek + Send + 'a> RawLAZReader<'a, T> {
 
         // Read in interleaved chunks, even if the `point_buffer` is not interleaved. `push_points_interleaved` will
         // handle the memory transpose in this case
-        let chunk_size = 50_000;
+        let chunk_size = self.points_per_batch;
         let point_size = point_buffer.point_layout().size_of_point_entry() as usize;
         let chunk_bytes = point_size * chunk_size;
         let num_chunks = (num_points_to_read + chunk_size - 1) / chunk_size;
@@ -1585,11 +1863,20 @@ impl<'a, T: Read + Seek + Send + 'a> LASReaderBase for RawLAZReader<'a, T> {
     fn header(&self) -> &Header {
         self.metadata.raw_las_header().unwrap()
     }
+
+    fn max_points_per_read(&self) -> usize {
+        self.max_points_per_read
+    }
+
+    fn set_max_points_per_read(&mut self, max_points_per_read: usize) {
+        self.max_points_per_read = max_points_per_read;
+    }
 }
 
 impl<'a, T: Read + Seek + Send + 'a> PointReader for RawLAZReader<'a, T> {
     fn read(&mut self, count: usize) -> Result<Box<dyn PointBuffer>> {
-        let num_points_to_read = usize::min(count, self.remaining_points());
+        let num_points_to_read =
+            clamp_points_per_read(count, self.remaining_points(), self.max_points_per_read)?;
         let mut buffer =
             InterleavedVecPointStorage::with_capacity(num_points_to_read, self.layout.clone());
 
@@ -1603,6 +1890,8 @@ impl<'a, T: Read + Seek + Send + 'a> PointReader for RawLAZReader<'a, T> {
         point_buffer: &mut dyn PointBufferWriteable,
         count: usize,
     ) -> Result<usize> {
+        let count =
+            clamp_points_per_read(count, self.remaining_points(), self.max_points_per_read)?;
         if *point_buffer.point_layout() != self.layout {
             self.read_into_custom_layout(point_buffer, count)
         } else {
@@ -1651,9 +1940,9 @@ mod tests {
 
     use crate::las::{
         compare_to_reference_data, compare_to_reference_data_range, get_test_las_path,
-        get_test_laz_path, test_data_bounds, test_data_classifications, test_data_colors,
-        test_data_point_count, test_data_point_source_ids, test_data_positions,
-        test_data_wavepacket_parameters,
+        get_test_laz_path, point_layout_from_las_point_format, test_data_bounds,
+        test_data_classifications, test_data_colors, test_data_point_count,
+        test_data_point_source_ids, test_data_positions, test_data_wavepacket_parameters,
     };
 
     use super::*;
@@ -1881,6 +2170,28 @@ mod tests {
 
                     Ok(())
                 }
+
+                #[test]
+                fn test_raw_las_reader_max_points_per_read() -> Result<()> {
+                    let read = BufReader::new(File::open(get_test_file_path())?);
+                    let mut reader = $reader::from_read(read)?;
+                    assert_eq!(DEFAULT_MAX_POINTS_PER_READ, reader.max_points_per_read());
+
+                    // Requests beyond the point count are clamped before they are checked against the maximum
+                    reader.set_max_points_per_read(test_data_point_count());
+                    let points = reader.read(usize::MAX)?;
+                    assert_eq!(test_data_point_count(), points.len());
+
+                    reader.seek_point(SeekFrom::Start(0))?;
+                    reader.set_max_points_per_read(5);
+                    assert!(reader.read(6).is_err());
+                    let mut buffer =
+                        InterleavedVecPointStorage::new(reader.get_default_point_layout().clone());
+                    assert!(reader.read_into(&mut buffer, 6).is_err());
+                    assert_eq!(5, reader.read(5)?.len());
+
+                    Ok(())
+                }
             }
         };
     }
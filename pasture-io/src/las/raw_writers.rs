@@ -1,31 +1,113 @@
 use std::{
     collections::HashMap,
-    io::{Cursor, SeekFrom},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
 };
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use byteorder::{LittleEndian, NativeEndian, ReadBytesExt, WriteBytesExt};
-use las_rs::{point::Format, Builder, Vlr};
+use las_rs::{point::Format, Builder, GpsTimeType, Version, Vlr};
 use laz::{
     las::laszip::LASZIP_DESCRIPTION, las::laszip::LASZIP_RECORD_ID, las::laszip::LASZIP_USER_ID,
     LasZipCompressor, LazItemRecordBuilder, LazVlr,
 };
-use pasture_core::{containers::PointBuffer, layout::PointLayout, nalgebra::Vector3};
+use pasture_core::{
+    containers::PointBuffer,
+    layout::{
+        attributes,
+        conversion::{get_converter_for_attributes, AttributeConversionFn},
+        PointLayout,
+    },
+    nalgebra::Vector3,
+};
 
 use crate::base::PointWriter;
 
 use super::{
-    get_classification_flags_reader, get_classification_reader, get_color_reader,
-    get_edge_of_flight_line_reader, get_extended_scan_angle_rank_reader, get_gps_time_reader,
-    get_intensity_reader, get_nir_reader, get_number_of_returns_reader, get_point_source_id_reader,
-    get_position_reader, get_return_number_reader, get_return_point_waveform_location_reader,
-    get_scan_angle_rank_reader, get_scan_direction_flag_reader, get_scanner_channel_reader,
-    get_user_data_reader, get_wave_packet_descriptor_index_reader, get_waveform_data_offset_reader,
+    extra_bytes_fields, get_classification_flags_reader, get_classification_reader,
+    get_color_reader, get_edge_of_flight_line_reader, get_extended_scan_angle_reader_with_fallback,
+    get_gps_time_reader, get_intensity_reader, get_nir_reader, get_number_of_returns_reader,
+    get_point_source_id_reader, get_position_reader, get_return_number_reader,
+    get_return_point_waveform_location_reader, get_scan_angle_rank_reader_with_fallback,
+    get_scan_direction_flag_reader, get_scanner_channel_reader, get_user_data_reader,
+    get_wave_packet_descriptor_index_reader, get_waveform_data_offset_reader,
     get_waveform_packet_size_reader, get_waveform_parameters_reader, map_laz_err,
-    point_layout_from_las_point_format, write_las_bit_attributes, write_position_as_las_position,
-    BitAttributes, BitAttributesExtended, BitAttributesRegular,
+    merge_legacy_classification, point_layout_from_las_header, point_layout_from_las_point_format,
+    write_las_bit_attributes, write_position_as_las_position, BitAttributes, BitAttributesExtended,
+    BitAttributesRegular, ExtraBytesField,
 };
 
+/// Writes a single attribute of a source layout into the extra bytes of a LAS point record: The field within the extra
+/// bytes, the offset and size of the attribute within the source layout, and an optional conversion function into the
+/// datatype of the field
+type ExtraBytesWriter<'a> = (
+    &'a ExtraBytesField,
+    usize,
+    usize,
+    Option<AttributeConversionFn>,
+);
+
+/// Returns writers for all of the given extra bytes `fields` whose attribute is contained in the `source_layout`
+fn extra_bytes_writers<'a>(
+    fields: &'a [ExtraBytesField],
+    source_layout: &PointLayout,
+) -> Vec<ExtraBytesWriter<'a>> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let target_attribute = field.attribute.as_ref()?;
+            let source_attribute = source_layout.get_attribute_by_name(target_attribute.name())?;
+            let converter =
+                get_converter_for_attributes(&source_attribute.into(), target_attribute);
+            Some((
+                field,
+                source_attribute.offset() as usize,
+                source_attribute.size() as usize,
+                converter,
+            ))
+        })
+        .collect()
+}
+
+/// Writes the attributes of the given `source_point` into the `extra_bytes` of its LAS point record
+fn run_extra_bytes_writers(
+    writers: &[ExtraBytesWriter<'_>],
+    source_point: &[u8],
+    extra_bytes: &mut [u8],
+) {
+    for (field, offset, size, maybe_converter) in writers {
+        let source_data = &source_point[*offset..*offset + *size];
+        match maybe_converter {
+            Some(converter) => {
+                let mut value = vec![0; field.attribute.as_ref().unwrap().size() as usize];
+                unsafe {
+                    converter(source_data, &mut value);
+                }
+                field.write_value(&value, extra_bytes);
+            }
+            None => field.write_value(source_data, extra_bytes),
+        }
+    }
+}
+
+/// Sets the GPS time type in the global encoding of the given `las_header`
+///
+/// # Errors
+///
+/// If `gps_time_type` is standard GPS time, which requires LAS version 1.2 or later, but `las_header` has an earlier version
+fn set_gps_time_type_in_las_header(
+    gps_time_type: GpsTimeType,
+    las_header: &mut las::raw::Header,
+) -> Result<()> {
+    if gps_time_type.is_standard() && las_header.version < Version::new(1, 2) {
+        bail!(
+            "Standard GPS time requires LAS version 1.2 or later, but the LAS version is {}",
+            las_header.version
+        );
+    }
+    las_header.global_encoding = (las_header.global_encoding & !1) | u16::from(gps_time_type);
+    Ok(())
+}
+
 /// Update the bounds in the given `las_header` by including the given `new_position`
 fn update_bounds_in_las_header(new_position: &Vector3<f64>, las_header: &mut las::raw::Header) {
     if new_position.x < las_header.min_x {
@@ -76,6 +158,16 @@ fn finalize_las_header(las_header: &mut las::raw::Header) {
         .large_file
         .as_ref()
         .expect("LAS header must contain large_file field!");
+    // The bounds are accumulated while writing points, so if no points were written they are still in their initial
+    // (invalid) state
+    if large_file.number_of_point_records == 0 {
+        las_header.min_x = 0.0;
+        las_header.min_y = 0.0;
+        las_header.min_z = 0.0;
+        las_header.max_x = 0.0;
+        las_header.max_y = 0.0;
+        las_header.max_z = 0.0;
+    }
     if large_file.number_of_point_records > u32::MAX as u64 {
         return;
     }
@@ -93,18 +185,59 @@ fn finalize_las_header(las_header: &mut las::raw::Header) {
     }
 }
 
+/// Reads the header and the extended VLRs of the existing LAS file in `read`, so that points can be appended to this file
+/// using [RawLASWriter::append_to]
+pub(crate) fn read_las_header_for_append<R: Read + Seek>(
+    read: &mut R,
+) -> Result<(las::raw::Header, Vec<las::raw::Vlr>)> {
+    read.seek(SeekFrom::Start(0))?;
+    let raw_header = las::raw::Header::read_from(&mut *read)?;
+    let format = Format::new(raw_header.point_data_record_format)?;
+    if format.is_compressed {
+        bail!("Appending to compressed LAZ files is not supported");
+    }
+    if raw_header.point_data_record_length != format.len() {
+        bail!("Appending to LAS files with extra bytes in the point records is not supported");
+    }
+
+    let evlrs = match &raw_header.evlr {
+        Some(evlr) if evlr.number_of_evlrs > 0 => {
+            read.seek(SeekFrom::Start(evlr.start_of_first_evlr))?;
+            (0..evlr.number_of_evlrs)
+                .map(|_| las::raw::Vlr::read_from(&mut *read, true))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        _ => vec![],
+    };
+
+    Ok((raw_header, evlrs))
+}
+
+pub(crate) trait LASWriterBase {
+    /// Returns the GPS time type in the header of the underlying `LASWriterBase`
+    fn gps_time_type(&self) -> GpsTimeType;
+    /// Sets the GPS time type in the header of the underlying `LASWriterBase`. The header is written when the writer is
+    /// flushed or dropped
+    fn set_gps_time_type(&mut self, gps_time_type: GpsTimeType) -> Result<()>;
+}
+
 pub(crate) struct RawLASWriter<T: std::io::Write + std::io::Seek> {
     writer: T,
     default_layout: PointLayout,
+    extra_bytes_fields: Vec<ExtraBytesField>,
     current_header: las::raw::Header,
     evlrs: Vec<las::raw::Vlr>,
     _point_start_index: u64,
     requires_flush: bool,
+    /// When appending to an existing file, points with attributes that the point format of the file can't store are
+    /// rejected instead of silently dropping these attributes
+    reject_unsupported_attributes: bool,
 }
 
 impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
     pub fn from_write_and_header(mut write: T, header: las::Header) -> Result<Self> {
-        let default_layout = point_layout_from_las_point_format(header.point_format())?;
+        let default_layout = point_layout_from_las_header(&header)?;
+        let extra_bytes_fields = extra_bytes_fields(&header)?;
 
         // Sanitize header, i.e. clear point counts and bounds
         // TODO Add flag to prevent recalculating bounds
@@ -143,6 +276,7 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
         Ok(Self {
             writer: write,
             default_layout,
+            extra_bytes_fields,
             current_header: raw_header,
             evlrs: header
                 .evlrs()
@@ -151,6 +285,67 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
                 .collect::<Result<Vec<_>, _>>()?,
             _point_start_index: point_start_index,
             requires_flush: true,
+            reject_unsupported_attributes: false,
+        })
+    }
+
+    /// Creates a `RawLASWriter` that appends points to the existing LAS file in `write`. `raw_header` and `evlrs` are the
+    /// header and extended VLRs of this file, as returned by [read_las_header_for_append]. New points are written after
+    /// the existing point records, the point counts and bounds in the header are updated accordingly
+    pub fn append_to(
+        mut write: T,
+        mut raw_header: las::raw::Header,
+        evlrs: Vec<las::raw::Vlr>,
+    ) -> Result<Self> {
+        let format = Format::new(raw_header.point_data_record_format)?;
+        let default_layout = point_layout_from_las_point_format(&format)?;
+
+        // Pasture always uses the 'large_file' field for keeping track of the number of points, so we have to initialize
+        // it from the legacy point counts for files with a LAS version below 1.4
+        if raw_header.large_file.is_none() {
+            let mut large_file = las::raw::header::LargeFile {
+                number_of_point_records: raw_header.number_of_point_records as u64,
+                ..Default::default()
+            };
+            for (count, legacy_count) in large_file
+                .number_of_points_by_return
+                .iter_mut()
+                .zip(raw_header.number_of_points_by_return.iter())
+            {
+                *count = *legacy_count as u64;
+            }
+            raw_header.large_file = Some(large_file);
+        }
+
+        let existing_point_count = raw_header
+            .large_file
+            .as_ref()
+            .unwrap()
+            .number_of_point_records;
+        // The bounds of an empty file are zero, so they must not be included in the bounds of the new points
+        if existing_point_count == 0 {
+            raw_header.min_x = std::f64::MAX;
+            raw_header.min_y = std::f64::MAX;
+            raw_header.min_z = std::f64::MAX;
+            raw_header.max_x = std::f64::MIN;
+            raw_header.max_y = std::f64::MIN;
+            raw_header.max_z = std::f64::MIN;
+        }
+
+        let point_start_index = raw_header.offset_to_point_data as u64;
+        let end_of_point_records =
+            point_start_index + existing_point_count * raw_header.point_data_record_length as u64;
+        write.seek(SeekFrom::Start(end_of_point_records))?;
+
+        Ok(Self {
+            writer: write,
+            default_layout,
+            extra_bytes_fields: vec![],
+            current_header: raw_header,
+            evlrs,
+            _point_start_index: point_start_index,
+            requires_flush: true,
+            reject_unsupported_attributes: true,
         })
     }
 
@@ -188,6 +383,13 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
         let mut chunk_buffer: Vec<u8> = vec![0; num_points_in_chunk * size_of_single_point];
 
         let source_format = Format::new(self.current_header.point_data_record_format)?;
+        let mut extra_bytes = vec![
+            0;
+            self.current_header.point_data_record_length as usize
+                - source_format.len() as usize
+        ];
+        let extra_bytes_writers =
+            extra_bytes_writers(&self.extra_bytes_fields, &self.default_layout);
 
         let mut points_by_return: HashMap<u8, u64> = HashMap::new();
         let max_return_number = if self.current_header.large_file.is_some() {
@@ -212,7 +414,7 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
             let mut point_read = Cursor::new(chunk_buffer);
 
             // Read all the attributes from the raw memory inside `points` and transform them into the format that LAS expects
-            for _ in 0..points_in_cur_chunk {
+            for point_index in 0..points_in_cur_chunk {
                 let pos_x = point_read.read_f64::<NativeEndian>()?;
                 let pos_y = point_read.read_f64::<NativeEndian>()?;
                 let pos_z = point_read.read_f64::<NativeEndian>()?;
@@ -318,6 +520,19 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
                     self.writer.write_f32::<LittleEndian>(py)?;
                     self.writer.write_f32::<LittleEndian>(pz)?;
                 }
+
+                // The extra bytes attributes are located at the end of each point in the default layout
+                if !extra_bytes.is_empty() {
+                    let start_of_point = point_index * size_of_single_point;
+                    let end_of_point = start_of_point + size_of_single_point;
+                    run_extra_bytes_writers(
+                        &extra_bytes_writers,
+                        &point_read.get_ref()[start_of_point..end_of_point],
+                        &mut extra_bytes,
+                    );
+                    point_read.set_position(end_of_point as u64);
+                    self.writer.write_all(&extra_bytes)?;
+                }
             }
 
             chunk_buffer = point_read.into_inner();
@@ -344,6 +559,13 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
         let mut chunk_buffer: Vec<u8> = vec![0; num_points_in_chunk * size_of_single_point];
 
         let target_format = Format::new(self.current_header.point_data_record_format)?;
+        let mut extra_bytes = vec![
+            0;
+            self.current_header.point_data_record_length as usize
+                - target_format.len() as usize
+        ];
+        let extra_bytes_writers =
+            extra_bytes_writers(&self.extra_bytes_fields, points.point_layout());
 
         let mut points_by_return: HashMap<u8, u64> = HashMap::new();
         let max_return_number = if self.current_header.large_file.is_some() {
@@ -367,6 +589,17 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
         } else {
             None
         };
+        // The regular LAS formats pack the classification flags into the upper bits of the classification, but only if the
+        // source layout has them as a separate attribute. Otherwise the classification is written unaltered
+        let legacy_classification_flags_reader = if !target_format.is_extended
+            && points
+                .point_layout()
+                .has_attribute_with_name(attributes::CLASSIFICATION_FLAGS.name())
+        {
+            Some(get_classification_flags_reader(points.point_layout()))
+        } else {
+            None
+        };
         let scanner_channel_reader = if target_format.is_extended {
             Some(get_scanner_channel_reader(points.point_layout()))
         } else {
@@ -379,10 +612,14 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
         let scan_angle_reader = if target_format.is_extended {
             None
         } else {
-            Some(get_scan_angle_rank_reader(points.point_layout()))
+            Some(get_scan_angle_rank_reader_with_fallback(
+                points.point_layout(),
+            ))
         };
         let extended_scan_angle_reader = if target_format.is_extended {
-            Some(get_extended_scan_angle_rank_reader(points.point_layout()))
+            Some(get_extended_scan_angle_reader_with_fallback(
+                points.point_layout(),
+            ))
         } else {
             None
         };
@@ -506,8 +743,14 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
                 };
                 write_las_bit_attributes(bit_attributes, &mut self.writer)?;
 
-                self.writer
-                    .write_u8(classification_reader(point_index, &mut point_read)?)?;
+                let classification = classification_reader(point_index, &mut point_read)?;
+                match &legacy_classification_flags_reader {
+                    Some(flags_reader) => self.writer.write_u8(merge_legacy_classification(
+                        classification,
+                        flags_reader(point_index, &mut point_read)?,
+                    ))?,
+                    None => self.writer.write_u8(classification)?,
+                }
 
                 if target_format.is_extended {
                     self.writer
@@ -571,6 +814,17 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
                     self.writer.write_f32::<LittleEndian>(params.y)?;
                     self.writer.write_f32::<LittleEndian>(params.z)?;
                 }
+
+                if !extra_bytes.is_empty() {
+                    let start_of_point = point_index * size_of_single_point;
+                    run_extra_bytes_writers(
+                        &extra_bytes_writers,
+                        &point_read.get_ref()
+                            [start_of_point..start_of_point + size_of_single_point],
+                        &mut extra_bytes,
+                    );
+                    self.writer.write_all(&extra_bytes)?;
+                }
             }
 
             chunk_buffer = point_read.into_inner();
@@ -589,6 +843,21 @@ impl<T: std::io::Write + std::io::Seek> RawLASWriter<T> {
 
 impl<T: std::io::Write + std::io::Seek> PointWriter for RawLASWriter<T> {
     fn write(&mut self, points: &dyn PointBuffer) -> Result<()> {
+        if self.reject_unsupported_attributes {
+            if let Some(unsupported_attribute) =
+                points.point_layout().attributes().find(|attribute| {
+                    !self
+                        .default_layout
+                        .has_attribute_with_name(attribute.name())
+                })
+            {
+                bail!(
+                    "Attribute {} can't be stored in the point format of the LAS file that is appended to",
+                    unsupported_attribute
+                );
+            }
+        }
+
         if *points.point_layout() == self.default_layout {
             self.write_points_default_layout(points)
         } else {
@@ -602,6 +871,12 @@ impl<T: std::io::Write + std::io::Seek> PointWriter for RawLASWriter<T> {
         }
 
         let current_index = self.writer.seek(SeekFrom::Current(0))?;
+        // The EVLRs are located directly after the point records
+        if !self.evlrs.is_empty() {
+            if let Some(evlr) = self.current_header.evlr.as_mut() {
+                evlr.start_of_first_evlr = current_index;
+            }
+        }
         self.write_header()?;
         self.write_evlrs()?;
         self.writer.seek(SeekFrom::Start(current_index))?;
@@ -616,6 +891,18 @@ impl<T: std::io::Write + std::io::Seek> PointWriter for RawLASWriter<T> {
     }
 }
 
+impl<T: std::io::Write + std::io::Seek> LASWriterBase for RawLASWriter<T> {
+    fn gps_time_type(&self) -> GpsTimeType {
+        self.current_header.global_encoding.into()
+    }
+
+    fn set_gps_time_type(&mut self, gps_time_type: GpsTimeType) -> Result<()> {
+        set_gps_time_type_in_las_header(gps_time_type, &mut self.current_header)?;
+        self.requires_flush = true;
+        Ok(())
+    }
+}
+
 impl<T: std::io::Write + std::io::Seek> Drop for RawLASWriter<T> {
     fn drop(&mut self) {
         self.flush()
@@ -636,7 +923,7 @@ impl<T: std::io::Write + std::io::Seek + Send + 'static> RawLAZWriter<T> {
         let default_layout = point_layout_from_las_point_format(header.point_format())?;
 
         if header.point_format().extra_bytes != 0 {
-            panic!("Extra bytes in LAZ point records are currently unsupported!");
+            bail!("Extra bytes in LAZ point records are currently unsupported!");
         }
 
         let mut raw_header = header.clone().into_raw()?;
@@ -907,6 +1194,17 @@ impl<T: std::io::Write + std::io::Seek + Send + 'static> RawLAZWriter<T> {
         } else {
             None
         };
+        // The regular LAS formats pack the classification flags into the upper bits of the classification, but only if the
+        // source layout has them as a separate attribute. Otherwise the classification is written unaltered
+        let legacy_classification_flags_reader = if !target_format.is_extended
+            && points
+                .point_layout()
+                .has_attribute_with_name(attributes::CLASSIFICATION_FLAGS.name())
+        {
+            Some(get_classification_flags_reader(points.point_layout()))
+        } else {
+            None
+        };
         let scanner_channel_reader = if target_format.is_extended {
             Some(get_scanner_channel_reader(points.point_layout()))
         } else {
@@ -919,10 +1217,14 @@ impl<T: std::io::Write + std::io::Seek + Send + 'static> RawLAZWriter<T> {
         let scan_angle_reader = if target_format.is_extended {
             None
         } else {
-            Some(get_scan_angle_rank_reader(points.point_layout()))
+            Some(get_scan_angle_rank_reader_with_fallback(
+                points.point_layout(),
+            ))
         };
         let extended_scan_angle_reader = if target_format.is_extended {
-            Some(get_extended_scan_angle_rank_reader(points.point_layout()))
+            Some(get_extended_scan_angle_reader_with_fallback(
+                points.point_layout(),
+            ))
         } else {
             None
         };
@@ -1051,7 +1353,14 @@ impl<T: std::io::Write + std::io::Seek + Send + 'static> RawLAZWriter<T> {
                 };
                 write_las_bit_attributes(bit_attributes, &mut las_point_write)?;
 
-                las_point_write.write_u8(classification_reader(point_index, &mut point_read)?)?;
+                let classification = classification_reader(point_index, &mut point_read)?;
+                match &legacy_classification_flags_reader {
+                    Some(flags_reader) => las_point_write.write_u8(merge_legacy_classification(
+                        classification,
+                        flags_reader(point_index, &mut point_read)?,
+                    ))?,
+                    None => las_point_write.write_u8(classification)?,
+                }
 
                 if target_format.is_extended {
                     las_point_write.write_u8(user_data_reader(point_index, &mut point_read)?)?;
@@ -1181,6 +1490,16 @@ impl<T: std::io::Write + std::io::Seek + Send + 'static> PointWriter for RawLAZW
     }
 }
 
+impl<T: std::io::Write + std::io::Seek + Send + 'static> LASWriterBase for RawLAZWriter<T> {
+    fn gps_time_type(&self) -> GpsTimeType {
+        self.current_header.global_encoding.into()
+    }
+
+    fn set_gps_time_type(&mut self, gps_time_type: GpsTimeType) -> Result<()> {
+        set_gps_time_type_in_las_header(gps_time_type, &mut self.current_header)
+    }
+}
+
 impl<T: std::io::Write + std::io::Seek + Send + 'static> Drop for RawLAZWriter<T> {
     fn drop(&mut self) {
         self.do_flush()
@@ -1203,7 +1522,8 @@ mod tests {
             epsilon_compare_point3f64, epsilon_compare_vec3f64, get_test_points_in_las_format,
             test_data_bounds, LASReader, LasPointFormat0, LasPointFormat1, LasPointFormat10,
             LasPointFormat2, LasPointFormat3, LasPointFormat4, LasPointFormat5, LasPointFormat6,
-            LasPointFormat7, LasPointFormat8, LasPointFormat9,
+            LasPointFormat7, LasPointFormat8, LasPointFormat9, CLASSIFICATION_FLAG_KEY_POINT,
+            CLASSIFICATION_FLAG_SYNTHETIC, CLASSIFICATION_FLAG_WITHHELD,
         },
     };
     use pasture_derive::PointType;
@@ -1582,6 +1902,114 @@ mod tests {
     laz_write_tests!(laz_write_2, 2, LasPointFormat2);
     laz_write_tests!(laz_write_3, 3, LasPointFormat3);
 
+    #[derive(PointType, Default, Debug, Clone, Copy, PartialEq)]
+    #[repr(C, packed)]
+    struct PointWithClassificationFlags {
+        #[pasture(BUILTIN_POSITION_3D)]
+        pub position: Vector3<f64>,
+        #[pasture(BUILTIN_CLASSIFICATION)]
+        pub classification: u8,
+        #[pasture(BUILTIN_CLASSIFICATION_FLAGS)]
+        pub classification_flags: u8,
+    }
+
+    fn test_classification_flags_roundtrip(format: u8) -> Result<()> {
+        let test_data = vec![
+            PointWithClassificationFlags {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                classification: 2,
+                classification_flags: CLASSIFICATION_FLAG_WITHHELD,
+            },
+            PointWithClassificationFlags {
+                position: Vector3::new(4.0, 5.0, 6.0),
+                classification: 6,
+                classification_flags: CLASSIFICATION_FLAG_SYNTHETIC | CLASSIFICATION_FLAG_KEY_POINT,
+            },
+            PointWithClassificationFlags {
+                position: Vector3::new(7.0, 8.0, 9.0),
+                classification: 9,
+                classification_flags: 0,
+            },
+        ];
+
+        let mut points = InterleavedVecPointStorage::new(PointWithClassificationFlags::layout());
+        points.push_points(test_data.as_slice());
+
+        let mut header_builder = Builder::from((1, 4));
+        header_builder.point_format = Format::new(format)?;
+
+        let out_path = format!("./test_classification_flags_format_{}.las", format);
+        defer! {
+            std::fs::remove_file(&out_path).expect("Could not remove test file");
+        }
+
+        {
+            let mut writer = RawLASWriter::from_write_and_header(
+                BufWriter::new(File::create(&out_path)?),
+                header_builder.into_header()?,
+            )?;
+            writer.write(&points)?;
+        }
+
+        let mut reader = LASReader::from_path(&out_path)?;
+        let mut read_points = InterleavedVecPointStorage::with_capacity(
+            test_data.len(),
+            PointWithClassificationFlags::layout(),
+        );
+        reader.read_into(&mut read_points, test_data.len())?;
+
+        let actual_points = read_points
+            .iter_point::<PointWithClassificationFlags>()
+            .collect::<Vec<_>>();
+        assert_eq!(test_data, actual_points);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_classification_flags_roundtrip_legacy_format() -> Result<()> {
+        test_classification_flags_roundtrip(1)
+    }
+
+    #[test]
+    fn test_classification_flags_roundtrip_extended_format() -> Result<()> {
+        test_classification_flags_roundtrip(6)
+    }
+
+    #[test]
+    fn test_classification_flags_are_packed_into_legacy_classification() -> Result<()> {
+        let mut points = InterleavedVecPointStorage::new(PointWithClassificationFlags::layout());
+        points.push_point(PointWithClassificationFlags {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            classification: 2,
+            classification_flags: CLASSIFICATION_FLAG_WITHHELD,
+        });
+
+        let mut header_builder = Builder::from((1, 4));
+        header_builder.point_format = Format::new(0)?;
+
+        let out_path = "./test_classification_flags_packed.las";
+        defer! {
+            std::fs::remove_file(out_path).expect("Could not remove test file");
+        }
+
+        {
+            let mut writer = RawLASWriter::from_write_and_header(
+                BufWriter::new(File::create(out_path)?),
+                header_builder.into_header()?,
+            )?;
+            writer.write(&points)?;
+        }
+
+        // Reading in the default layout yields the raw classification byte with the withheld bit set
+        let mut reader = LASReader::from_path(out_path)?;
+        let read_points = reader.read(1)?;
+        let point = read_points.iter_point::<LasPointFormat0>().next().unwrap();
+        assert_eq!(0b1000_0010, point.classification);
+
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn test_raw_laz_writer_flush() {
@@ -10,6 +10,8 @@ use pasture_core::{
     util::view_raw_bytes_mut,
 };
 
+use super::{extended_scan_angle_to_scan_angle_rank, scan_angle_rank_to_extended_scan_angle};
+
 /// ReaderFn is a helper function that allows reading a single value of a specific point attribute from an arbitrary
 /// buffer, applying all necessary conversions or falling back to default values if required. This abstraction is
 /// necessary to deal with the general case of an arbitrary source point layout in the LASWriter that has to be
@@ -488,3 +490,39 @@ make_get_reader_fn!(
     WAVEFORM_PARAMETERS,
     read_waveform_parameters_in_default_layout
 );
+
+/// Returns a `ReaderFn` for the scan angle rank of the regular LAS point formats. If the `source_layout` has no
+/// `SCAN_ANGLE_RANK` attribute but an extended `SCAN_ANGLE` attribute, the extended scan angle is converted
+pub(crate) fn get_scan_angle_rank_reader_with_fallback(
+    source_layout: &PointLayout,
+) -> ReaderFn<i8> {
+    if !source_layout.has_attribute_with_name(attributes::SCAN_ANGLE_RANK.name())
+        && source_layout.has_attribute_with_name(attributes::SCAN_ANGLE.name())
+    {
+        let extended_reader = get_extended_scan_angle_rank_reader(source_layout);
+        Box::new(move |current_point_index, point_read| {
+            let scan_angle = extended_reader(current_point_index, point_read)?;
+            Ok(extended_scan_angle_to_scan_angle_rank(scan_angle))
+        })
+    } else {
+        get_scan_angle_rank_reader(source_layout)
+    }
+}
+
+/// Returns a `ReaderFn` for the scan angle of the extended LAS point formats. If the `source_layout` has no
+/// `SCAN_ANGLE` attribute but a regular `SCAN_ANGLE_RANK` attribute, the scan angle rank is converted
+pub(crate) fn get_extended_scan_angle_reader_with_fallback(
+    source_layout: &PointLayout,
+) -> ReaderFn<i16> {
+    if !source_layout.has_attribute_with_name(attributes::SCAN_ANGLE.name())
+        && source_layout.has_attribute_with_name(attributes::SCAN_ANGLE_RANK.name())
+    {
+        let regular_reader = get_scan_angle_rank_reader(source_layout);
+        Box::new(move |current_point_index, point_read| {
+            let scan_angle_rank = regular_reader(current_point_index, point_read)?;
+            Ok(scan_angle_rank_to_extended_scan_angle(scan_angle_rank))
+        })
+    } else {
+        get_extended_scan_angle_rank_reader(source_layout)
+    }
+}
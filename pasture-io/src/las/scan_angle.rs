@@ -0,0 +1,91 @@
+use las::point::Format;
+
+/// Size of a single unit of the scan angle in the extended LAS point formats (6 to 10), in degrees
+const EXTENDED_SCAN_ANGLE_UNIT_IN_DEGREES: f64 = 0.006;
+
+/// Converts the given scan angle from the scan angle encoding of the LAS point format `from_format` into the scan angle
+/// encoding of the LAS point format `to_format`. LAS point formats 0 to 5 store the scan angle as the `SCAN_ANGLE_RANK`
+/// attribute, which is an `i8` value in whole degrees. The extended formats 6 to 10 store the scan angle as the `SCAN_ANGLE`
+/// attribute, which is an `i16` value in increments of 0.006 degrees. If both formats use the same encoding, `value` is
+/// returned unchanged. Conversions into the regular encoding are clamped to the range of `i8`.
+///
+/// ```
+/// # use pasture_io::las::*;
+/// let format_1 = las::point::Format::new(1).unwrap();
+/// let format_6 = las::point::Format::new(6).unwrap();
+///
+/// assert_eq!(15000, convert_scan_angle(90, &format_1, &format_6));
+/// assert_eq!(-90, convert_scan_angle(-15000, &format_6, &format_1));
+/// assert_eq!(42, convert_scan_angle(42, &format_1, &format_1));
+/// ```
+pub fn convert_scan_angle(value: i16, from_format: &Format, to_format: &Format) -> i16 {
+    match (from_format.is_extended, to_format.is_extended) {
+        (false, true) => {
+            let clamped_value = value.max(i8::MIN as i16).min(i8::MAX as i16) as i8;
+            scan_angle_rank_to_extended_scan_angle(clamped_value)
+        }
+        (true, false) => extended_scan_angle_to_scan_angle_rank(value) as i16,
+        _ => value,
+    }
+}
+
+/// Converts a scan angle rank in whole degrees (regular LAS point formats) into a scan angle in increments of 0.006
+/// degrees (extended LAS point formats)
+pub(crate) fn scan_angle_rank_to_extended_scan_angle(scan_angle_rank: i8) -> i16 {
+    (scan_angle_rank as f64 / EXTENDED_SCAN_ANGLE_UNIT_IN_DEGREES).round() as i16
+}
+
+/// Converts a scan angle in increments of 0.006 degrees (extended LAS point formats) into a scan angle rank in whole
+/// degrees (regular LAS point formats)
+pub(crate) fn extended_scan_angle_to_scan_angle_rank(scan_angle: i16) -> i8 {
+    let degrees = (scan_angle as f64 * EXTENDED_SCAN_ANGLE_UNIT_IN_DEGREES).round();
+    degrees.max(i8::MIN as f64).min(i8::MAX as f64) as i8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_scan_angle_boundaries() {
+        let regular_format = Format::new(1).unwrap();
+        let extended_format = Format::new(6).unwrap();
+
+        for degrees in &[-90_i16, -45, 0, 1, 45, 90] {
+            let extended = convert_scan_angle(*degrees, &regular_format, &extended_format);
+            let regular_again = convert_scan_angle(extended, &extended_format, &regular_format);
+            assert_eq!(*degrees, regular_again);
+        }
+
+        assert_eq!(
+            15000,
+            convert_scan_angle(90, &regular_format, &extended_format)
+        );
+        assert_eq!(
+            -15000,
+            convert_scan_angle(-90, &regular_format, &extended_format)
+        );
+
+        for extended in &[-15000_i16, -14999, -7500, 0, 7500, 14999, 15000] {
+            let regular = convert_scan_angle(*extended, &extended_format, &regular_format);
+            let extended_again = convert_scan_angle(regular, &regular_format, &extended_format);
+            // One degree in the regular format equals ~167 units in the extended format
+            assert!((*extended - extended_again).abs() <= 84);
+        }
+    }
+
+    #[test]
+    fn test_convert_scan_angle_clamps() {
+        let regular_format = Format::new(0).unwrap();
+        let extended_format = Format::new(7).unwrap();
+
+        assert_eq!(
+            i8::MAX as i16,
+            convert_scan_angle(i16::MAX, &extended_format, &regular_format)
+        );
+        assert_eq!(
+            i8::MIN as i16,
+            convert_scan_angle(i16::MIN, &extended_format, &regular_format)
+        );
+    }
+}
@@ -1,8 +1,16 @@
 #![warn(clippy::all)]
 
+#[cfg(feature = "arrow")]
+pub extern crate arrow as arrow_rs;
 pub extern crate las as las_rs;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod ascii;
+pub mod ascii_grid;
 pub mod base;
+#[cfg(feature = "geotiff")]
+pub mod geotiff;
 pub mod las;
+pub mod ply;
 pub mod tiles3d;
@@ -0,0 +1,236 @@
+use std::io::BufRead;
+
+use anyhow::{anyhow, bail, Context, Result};
+use pasture_core::layout::{Endianness, PointAttributeDataType};
+
+/// Encoding of the element data in a PLY file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlyFormat {
+    /// Elements are stored as whitespace-separated text
+    Ascii,
+    /// Elements are stored as binary data in little-endian byte order
+    BinaryLittleEndian,
+    /// Elements are stored as binary data in big-endian byte order
+    BinaryBigEndian,
+}
+
+impl PlyFormat {
+    /// Returns the byte order of the binary element data, or `None` for the `Ascii` format
+    pub fn endianness(&self) -> Option<Endianness> {
+        match self {
+            PlyFormat::Ascii => None,
+            PlyFormat::BinaryLittleEndian => Some(Endianness::Little),
+            PlyFormat::BinaryBigEndian => Some(Endianness::Big),
+        }
+    }
+}
+
+/// The type of a single property of a PLY element
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlyPropertyType {
+    /// A single scalar value
+    Scalar(PointAttributeDataType),
+    /// A variable-length list of scalar values, preceded by the number of values in the list
+    List {
+        count_type: PointAttributeDataType,
+        value_type: PointAttributeDataType,
+    },
+}
+
+/// A named property of a PLY element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlyProperty {
+    pub name: String,
+    pub property_type: PlyPropertyType,
+}
+
+/// An element (e.g. `vertex` or `face`) declared in the header of a PLY file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlyElement {
+    pub name: String,
+    /// Number of instances of this element in the file
+    pub count: usize,
+    pub properties: Vec<PlyProperty>,
+}
+
+impl PlyElement {
+    /// Size in bytes of a single instance of this element in a binary PLY file, or `None` if the element has list
+    /// properties and thus no fixed size
+    pub fn binary_size(&self) -> Option<usize> {
+        self.properties
+            .iter()
+            .map(|property| match property.property_type {
+                PlyPropertyType::Scalar(datatype) => Some(datatype.size() as usize),
+                PlyPropertyType::List { .. } => None,
+            })
+            .sum()
+    }
+}
+
+/// The header of a PLY file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlyHeader {
+    pub format: PlyFormat,
+    /// All elements in the order in which their data is stored in the file
+    pub elements: Vec<PlyElement>,
+    pub comments: Vec<String>,
+}
+
+impl PlyHeader {
+    /// Reads a `PlyHeader` from the given `reader`. After this call, `reader` is positioned at the start of the element
+    /// data, i.e. right after the `end_header` line
+    ///
+    /// # Errors
+    ///
+    /// If the data in `reader` does not start with a valid PLY header, or if an I/O error occurs
+    pub fn read_from<R: BufRead>(mut reader: R) -> Result<Self> {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim_end() != "ply" {
+            bail!("Data does not start with the PLY magic number");
+        }
+
+        let mut format = None;
+        let mut elements: Vec<PlyElement> = vec![];
+        let mut comments = vec![];
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                bail!("Unexpected end of data in PLY header, end_header is missing");
+            }
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("format") => {
+                    format = Some(match (tokens.next(), tokens.next()) {
+                        (Some("ascii"), Some("1.0")) => PlyFormat::Ascii,
+                        (Some("binary_little_endian"), Some("1.0")) => {
+                            PlyFormat::BinaryLittleEndian
+                        }
+                        (Some("binary_big_endian"), Some("1.0")) => PlyFormat::BinaryBigEndian,
+                        _ => bail!("Invalid PLY format line: {}", line.trim_end()),
+                    });
+                }
+                Some("comment") | Some("obj_info") => {
+                    comments.push(tokens.collect::<Vec<_>>().join(" "));
+                }
+                Some("element") => {
+                    let (name, count) = match (tokens.next(), tokens.next()) {
+                        (Some(name), Some(count)) => (name, count),
+                        _ => bail!("Invalid PLY element line: {}", line.trim_end()),
+                    };
+                    elements.push(PlyElement {
+                        name: name.to_owned(),
+                        count: count
+                            .parse()
+                            .with_context(|| format!("Invalid count for PLY element {}", name))?,
+                        properties: vec![],
+                    });
+                }
+                Some("property") => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| anyhow!("PLY property declared before the first element"))?;
+                    element.properties.push(parse_property(tokens.collect())?);
+                }
+                Some("end_header") => break,
+                None => (),
+                Some(other) => bail!("Unknown keyword {} in PLY header", other),
+            }
+        }
+
+        Ok(Self {
+            format: format.ok_or_else(|| anyhow!("PLY header does not specify a format"))?,
+            elements,
+            comments,
+        })
+    }
+
+    /// Returns the element with the given `name`, if it exists
+    pub fn element(&self, name: &str) -> Option<&PlyElement> {
+        self.elements.iter().find(|element| element.name == name)
+    }
+}
+
+/// Parses the tokens of a `property` line in a PLY header (without the `property` keyword)
+fn parse_property(tokens: Vec<&str>) -> Result<PlyProperty> {
+    match tokens.as_slice() {
+        [datatype, name] => Ok(PlyProperty {
+            name: (*name).to_owned(),
+            property_type: PlyPropertyType::Scalar(ply_datatype(datatype)?),
+        }),
+        ["list", count_type, value_type, name] => Ok(PlyProperty {
+            name: (*name).to_owned(),
+            property_type: PlyPropertyType::List {
+                count_type: ply_datatype(count_type)?,
+                value_type: ply_datatype(value_type)?,
+            },
+        }),
+        _ => bail!("Invalid PLY property: {}", tokens.join(" ")),
+    }
+}
+
+/// Returns the `PointAttributeDataType` for the given PLY type name. Both the original names (e.g. `uchar`) and the
+/// sized names (e.g. `uint8`) are supported
+fn ply_datatype(name: &str) -> Result<PointAttributeDataType> {
+    match name {
+        "char" | "int8" => Ok(PointAttributeDataType::I8),
+        "uchar" | "uint8" => Ok(PointAttributeDataType::U8),
+        "short" | "int16" => Ok(PointAttributeDataType::I16),
+        "ushort" | "uint16" => Ok(PointAttributeDataType::U16),
+        "int" | "int32" => Ok(PointAttributeDataType::I32),
+        "uint" | "uint32" => Ok(PointAttributeDataType::U32),
+        "float" | "float32" => Ok(PointAttributeDataType::F32),
+        "double" | "float64" => Ok(PointAttributeDataType::F64),
+        _ => bail!("Unknown PLY datatype {}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::*;
+
+    #[test]
+    fn test_read_ply_header() -> Result<()> {
+        let data = b"ply\nformat binary_big_endian 1.0\ncomment made by hand\nelement vertex 2\nproperty float x\nproperty uint8 red\nelement face 1\nproperty list uchar int vertex_indices\nend_header\nBODY";
+        let mut cursor = Cursor::new(&data[..]);
+        let header = PlyHeader::read_from(&mut cursor)?;
+
+        assert_eq!(PlyFormat::BinaryBigEndian, header.format);
+        assert_eq!(vec!["made by hand".to_owned()], header.comments);
+        let vertex = header.element("vertex").unwrap();
+        assert_eq!(2, vertex.count);
+        assert_eq!(
+            vec![
+                PlyProperty {
+                    name: "x".into(),
+                    property_type: PlyPropertyType::Scalar(PointAttributeDataType::F32),
+                },
+                PlyProperty {
+                    name: "red".into(),
+                    property_type: PlyPropertyType::Scalar(PointAttributeDataType::U8),
+                },
+            ],
+            vertex.properties
+        );
+        assert_eq!(Some(5), vertex.binary_size());
+        assert_eq!(None, header.element("face").unwrap().binary_size());
+
+        let mut body = String::new();
+        cursor.read_to_string(&mut body)?;
+        assert_eq!("BODY", body);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_invalid_ply_header() {
+        assert!(PlyHeader::read_from(&b"plx\nformat ascii 1.0\nend_header\n"[..]).is_err());
+        assert!(PlyHeader::read_from(&b"ply\nelement vertex 1\nend_header\n"[..]).is_err());
+        assert!(PlyHeader::read_from(&b"ply\nformat ascii 1.0\nelement vertex 1\n"[..]).is_err());
+        assert!(PlyHeader::read_from(
+            &b"ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n"[..]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt::Display;
+
+use pasture_core::{math::AABB, meta::Metadata};
+
+use super::{PlyFormat, PlyHeader};
+
+/// `Metadata` implementation for PLY files
+#[derive(Debug, Clone)]
+pub struct PlyMetadata {
+    header: PlyHeader,
+    number_of_points: usize,
+}
+
+impl PlyMetadata {
+    /// Creates a new `PlyMetadata` from the given `header`. The number of points is the number of `vertex` elements
+    pub fn new(header: PlyHeader) -> Self {
+        let number_of_points = header
+            .element("vertex")
+            .map(|element| element.count)
+            .unwrap_or(0);
+        Self {
+            header,
+            number_of_points,
+        }
+    }
+
+    /// The header of the PLY file
+    pub fn header(&self) -> &PlyHeader {
+        &self.header
+    }
+
+    /// The encoding of the element data in the PLY file
+    pub fn format(&self) -> PlyFormat {
+        self.header.format
+    }
+}
+
+impl Metadata for PlyMetadata {
+    fn bounds(&self) -> Option<AABB<f64>> {
+        None
+    }
+
+    fn number_of_points(&self) -> Option<usize> {
+        Some(self.number_of_points)
+    }
+
+    fn get_named_field(&self, field_name: &str) -> Option<Box<dyn std::any::Any>> {
+        match field_name {
+            "format" => Some(Box::new(self.header.format)),
+            "comments" => Some(Box::new(self.header.comments.clone())),
+            _ => None,
+        }
+    }
+
+    fn clone_into_box(&self) -> Box<dyn Metadata> {
+        Box::new(self.clone())
+    }
+}
+
+impl Display for PlyMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "PlyMetadata {{")?;
+        writeln!(f, "\t\"format\": {:?}", self.header.format)?;
+        for element in &self.header.elements {
+            writeln!(f, "\t\"{}\": {}", element.name, element.count)?;
+        }
+        for comment in &self.header.comments {
+            writeln!(f, "\t\"comment\": {}", comment)?;
+        }
+        writeln!(f, "}}")
+    }
+}
@@ -0,0 +1,807 @@
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Result};
+use pasture_core::{
+    containers::{PerAttributeVecPointStorage, PointBuffer, PointBufferWriteable},
+    layout::{
+        attributes::{COLOR_RGB, INTENSITY, NORMAL, POSITION_3D},
+        conversion::get_converter_for_attributes,
+        Endianness, FieldAlignment, PointAttributeDataType, PointAttributeDefinition, PointLayout,
+    },
+    meta::Metadata,
+    nalgebra::clamp,
+};
+
+use crate::base::{clamp_points_per_read, PointReader, SeekToPoint, DEFAULT_MAX_POINTS_PER_READ};
+
+use super::{PlyElement, PlyHeader, PlyMetadata, PlyPropertyType};
+
+/// Groups of three scalar PLY vertex properties that are combined into a single vector attribute
+const VECTOR_PROPERTIES: [([&str; 3], PointAttributeDefinition); 3] = [
+    (["x", "y", "z"], POSITION_3D),
+    (["nx", "ny", "nz"], NORMAL),
+    (["red", "green", "blue"], COLOR_RGB),
+];
+
+/// Describes how the values of a point attribute are assembled from the scalar properties of a PLY vertex
+struct PlyAttributeMapping {
+    attribute: PointAttributeDefinition,
+    /// Byte offset within a vertex and datatype of the PLY property for each component of `attribute`
+    components: Vec<(usize, PointAttributeDataType)>,
+}
+
+/// A reader for points in the binary PLY format. The points are read from the `vertex` element of the PLY file. The
+/// vertex properties `x`, `y` and `z`, `nx`, `ny` and `nz` as well as `red`, `green` and `blue` are combined into the
+/// `POSITION_3D`, `NORMAL` and `COLOR_RGB` attributes, and the `intensity` property is read as the `INTENSITY`
+/// attribute. All other properties are read as custom attributes with the name and datatype of the property.
+///
+/// Both little-endian and big-endian PLY files are supported, values are converted to the native byte order during
+/// reading. The data of all other elements (e.g. `face`) is skipped, as are list properties of the `vertex` element.
+/// If the `vertex` element has list properties, vertices have no fixed size, so seeking to a point requires reading all
+/// vertices before it. ASCII PLY files are not supported.
+///
+/// Vertices whose position contains NaN or infinite coordinates can be skipped during reading, see
+/// [`PlyReader::set_skip_invalid_positions`].
+pub struct PlyReader<R: BufRead + Seek> {
+    reader: R,
+    metadata: PlyMetadata,
+    layout: PointLayout,
+    attribute_mappings: Vec<PlyAttributeMapping>,
+    vertex_element: PlyElement,
+    endianness: Endianness,
+    vertex_data_offset: u64,
+    /// Size of a single vertex in bytes, or `None` if the vertex element has list properties
+    vertex_size: Option<usize>,
+    /// Size of the scalar properties of a single vertex in bytes, which is the size of a vertex in the data returned by
+    /// `read_vertex_data`
+    scalar_vertex_size: usize,
+    current_point_index: usize,
+    /// Byte offset of the vertex at `current_point_index`
+    current_vertex_offset: u64,
+    max_points_per_read: usize,
+    skip_invalid_positions: bool,
+    invalid_positions_count: usize,
+}
+
+impl<R: BufRead + Seek> PlyReader<R> {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<PlyReader<BufReader<File>>> {
+        let reader = BufReader::new(File::open(path)?);
+        PlyReader::<BufReader<File>>::from_read(reader)
+    }
+
+    pub fn from_read(mut read: R) -> Result<PlyReader<R>> {
+        let header = PlyHeader::read_from(&mut read)?;
+        let endianness = header
+            .format
+            .endianness()
+            .ok_or_else(|| anyhow!("Reading ASCII PLY files is not supported"))?;
+        let vertex_element_index = header
+            .elements
+            .iter()
+            .position(|element| element.name == "vertex")
+            .ok_or_else(|| anyhow!("PLY file contains no vertex element"))?;
+        for element in &header.elements[..vertex_element_index] {
+            skip_element(&mut read, element, endianness)?;
+        }
+        let vertex_data_offset = read.seek(SeekFrom::Current(0))?;
+
+        let vertex_element = header.elements[vertex_element_index].clone();
+        let scalar_vertex_size = vertex_element
+            .properties
+            .iter()
+            .map(|property| match property.property_type {
+                PlyPropertyType::Scalar(datatype) => datatype.size() as usize,
+                PlyPropertyType::List { .. } => 0,
+            })
+            .sum();
+        let attribute_mappings = Self::attribute_mappings(&vertex_element);
+
+        let mut layout = PointLayout::default();
+        for mapping in &attribute_mappings {
+            // Duplicate property names, or properties named like a builtin attribute that the reader already maps to
+            // (e.g. `Intensity` alongside `intensity`), would yield two attributes with the same name
+            if layout.has_attribute_with_name(mapping.attribute.name()) {
+                bail!(
+                    "Multiple properties of the PLY vertex element map to the point attribute {}",
+                    mapping.attribute.name()
+                );
+            }
+            layout.add_attribute(mapping.attribute.clone(), FieldAlignment::Packed(1));
+        }
+
+        Ok(Self {
+            reader: read,
+            metadata: PlyMetadata::new(header),
+            layout,
+            attribute_mappings,
+            endianness,
+            vertex_data_offset,
+            vertex_size: vertex_element.binary_size(),
+            scalar_vertex_size,
+            vertex_element,
+            current_point_index: 0,
+            current_vertex_offset: vertex_data_offset,
+            max_points_per_read: DEFAULT_MAX_POINTS_PER_READ,
+            skip_invalid_positions: false,
+            invalid_positions_count: 0,
+        })
+    }
+
+    /// Returns the maximum number of points that a single call to `read` or `read_into` may read
+    pub fn max_points_per_read(&self) -> usize {
+        self.max_points_per_read
+    }
+
+    /// Sets the maximum number of points that a single call to `read` or `read_into` may read. Requests for more points
+    /// than remain in the file are clamped to the remaining points first. If the clamped request still exceeds this
+    /// limit, reading fails instead of allocating memory for all requested points. Defaults to
+    /// [DEFAULT_MAX_POINTS_PER_READ]
+    pub fn set_max_points_per_read(&mut self, max_points_per_read: usize) {
+        self.max_points_per_read = max_points_per_read;
+    }
+
+    /// Sets whether points whose position contains NaN or infinite coordinates are skipped while reading. Such points
+    /// are usually the result of broken sensors and break everything that works with the bounds of the points. By
+    /// default, these points are not skipped. If they are skipped, the `read` functions return fewer points than
+    /// requested
+    pub fn set_skip_invalid_positions(&mut self, skip_invalid_positions: bool) {
+        self.skip_invalid_positions = skip_invalid_positions;
+    }
+
+    /// Returns whether points with NaN or infinite coordinates are skipped while reading
+    pub fn skip_invalid_positions(&self) -> bool {
+        self.skip_invalid_positions
+    }
+
+    /// Returns the number of points with NaN or infinite coordinates that were encountered so far. If
+    /// [`PlyReader::set_skip_invalid_positions`] is enabled, this is the number of points that were skipped
+    pub fn count_invalid(&self) -> usize {
+        self.invalid_positions_count
+    }
+
+    fn attribute_mappings(vertex_element: &PlyElement) -> Vec<PlyAttributeMapping> {
+        let mut offset = 0;
+        let mut scalar_properties = vec![];
+        for property in &vertex_element.properties {
+            if let PlyPropertyType::Scalar(datatype) = property.property_type {
+                scalar_properties.push((property.name.as_str(), offset, datatype));
+                offset += datatype.size() as usize;
+            }
+        }
+        let find_property = |name: &str| {
+            scalar_properties
+                .iter()
+                .find(|(property_name, _, _)| *property_name == name)
+                .map(|(_, offset, datatype)| (*offset, *datatype))
+        };
+
+        let mut mappings = vec![];
+        let mut mapped_properties = HashSet::new();
+        for (names, attribute) in VECTOR_PROPERTIES.iter() {
+            let components = names
+                .iter()
+                .map(|name| find_property(name))
+                .collect::<Option<Vec<_>>>();
+            if let Some(components) = components {
+                let datatype = vector_datatype(&components);
+                mappings.push(PlyAttributeMapping {
+                    attribute: attribute.with_custom_datatype(datatype),
+                    components,
+                });
+                mapped_properties.extend(names.iter().copied());
+            }
+        }
+
+        for (name, offset, datatype) in &scalar_properties {
+            if mapped_properties.contains(name) {
+                continue;
+            }
+            let attribute = if *name == "intensity" {
+                INTENSITY.with_custom_datatype(*datatype)
+            } else {
+                PointAttributeDefinition::custom_dynamic(name, *datatype)
+            };
+            mappings.push(PlyAttributeMapping {
+                attribute,
+                components: vec![(*offset, *datatype)],
+            });
+        }
+        mappings
+    }
+
+    /// Reads the raw binary data of the scalar properties of the next `count` vertices, skipping all list properties
+    fn read_vertex_data(&mut self, count: usize) -> Result<Vec<u8>> {
+        self.reader
+            .seek(SeekFrom::Start(self.current_vertex_offset))?;
+        if self.vertex_size.is_some() {
+            let mut vertex_data = vec![0; count * self.scalar_vertex_size];
+            self.reader.read_exact(vertex_data.as_mut_slice())?;
+            self.current_vertex_offset += vertex_data.len() as u64;
+            return Ok(vertex_data);
+        }
+
+        let mut vertex_data = Vec::with_capacity(count * self.scalar_vertex_size);
+        for _ in 0..count {
+            self.current_vertex_offset += read_element_instance(
+                &mut self.reader,
+                &self.vertex_element,
+                self.endianness,
+                Some(&mut vertex_data),
+            )?;
+        }
+        Ok(vertex_data)
+    }
+
+    /// Counts the vertices in the raw `vertex_data` whose position contains NaN or infinite coordinates and removes
+    /// them from `vertex_data` if invalid positions are skipped. Returns the number of removed vertices
+    fn filter_invalid_positions(&mut self, vertex_data: &mut Vec<u8>) -> usize {
+        let position_mapping = match self
+            .attribute_mappings
+            .iter()
+            .find(|mapping| mapping.attribute.name() == POSITION_3D.name())
+        {
+            Some(mapping) => mapping,
+            None => return 0,
+        };
+
+        let mut valid_vertex_data = Vec::with_capacity(vertex_data.len());
+        let mut num_invalid = 0;
+        for vertex in vertex_data.chunks_exact(self.scalar_vertex_size) {
+            if has_invalid_position(position_mapping, vertex, self.endianness) {
+                num_invalid += 1;
+                if self.skip_invalid_positions {
+                    continue;
+                }
+            }
+            valid_vertex_data.extend_from_slice(vertex);
+        }
+        self.invalid_positions_count += num_invalid;
+
+        if !self.skip_invalid_positions {
+            return 0;
+        }
+        *vertex_data = valid_vertex_data;
+        num_invalid
+    }
+
+    /// Moves the reader to the vertex at `index`. If the vertices have no fixed size, all vertices between the current
+    /// vertex (or the first vertex, if `index` lies before the current vertex) and `index` are read to find its offset
+    fn seek_to_vertex(&mut self, index: usize) -> Result<()> {
+        if let Some(vertex_size) = self.vertex_size {
+            self.current_vertex_offset = self.vertex_data_offset + (index * vertex_size) as u64;
+            self.current_point_index = index;
+            return Ok(());
+        }
+
+        if index < self.current_point_index {
+            self.current_point_index = 0;
+            self.current_vertex_offset = self.vertex_data_offset;
+        }
+        self.reader
+            .seek(SeekFrom::Start(self.current_vertex_offset))?;
+        while self.current_point_index < index {
+            self.current_vertex_offset += read_element_instance(
+                &mut self.reader,
+                &self.vertex_element,
+                self.endianness,
+                None,
+            )?;
+            self.current_point_index += 1;
+        }
+        Ok(())
+    }
+
+    /// Extracts the values for the attribute of `mapping` from the given raw `vertex_data`, in the native byte order
+    fn attribute_data(&self, mapping: &PlyAttributeMapping, vertex_data: &[u8]) -> Vec<u8> {
+        let target_component_type = mapping.attribute.datatype().component_type();
+        let num_vertices = vertex_data.len() / self.scalar_vertex_size;
+        let mut data = Vec::with_capacity(num_vertices * mapping.attribute.size() as usize);
+        // Scalar PLY properties are at most 8 bytes large, so all components are converted in the same buffer
+        let mut component_buffer = [0; 8];
+        for vertex in vertex_data.chunks_exact(self.scalar_vertex_size) {
+            for (offset, datatype) in &mapping.components {
+                let component = &mut component_buffer[..datatype.size() as usize];
+                component.copy_from_slice(&vertex[*offset..*offset + component.len()]);
+                datatype.convert_endianness(component, self.endianness, Endianness::NATIVE);
+                if *datatype == target_component_type {
+                    data.extend_from_slice(component);
+                } else {
+                    // Vector components with mixed datatypes are always stored as F64
+                    data.extend_from_slice(&scalar_as_f64(component, *datatype).to_ne_bytes());
+                }
+            }
+        }
+        data
+    }
+}
+
+/// Skips the binary data of all instances of the given `element` in `reader`
+fn skip_element<R: BufRead + Seek>(
+    reader: &mut R,
+    element: &PlyElement,
+    endianness: Endianness,
+) -> Result<()> {
+    match element.binary_size() {
+        Some(size) => {
+            reader.seek(SeekFrom::Current((element.count * size) as i64))?;
+        }
+        None => {
+            for _ in 0..element.count {
+                read_element_instance(reader, element, endianness, None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads a single instance of the given `element` from the binary data in `reader` and returns the number of bytes that
+/// were read. The raw values of all scalar properties are appended to `scalar_data`, if it is given. For list properties,
+/// only the number of values is read and the values themselves are skipped
+fn read_element_instance<R: Read>(
+    reader: &mut R,
+    element: &PlyElement,
+    endianness: Endianness,
+    mut scalar_data: Option<&mut Vec<u8>>,
+) -> Result<u64> {
+    let mut bytes_read = 0;
+    let mut buffer = [0; 8];
+    for property in &element.properties {
+        match property.property_type {
+            PlyPropertyType::Scalar(datatype) => {
+                let value = &mut buffer[..datatype.size() as usize];
+                reader.read_exact(value)?;
+                if let Some(scalar_data) = scalar_data.as_mut() {
+                    scalar_data.extend_from_slice(value);
+                }
+                bytes_read += value.len() as u64;
+            }
+            PlyPropertyType::List {
+                count_type,
+                value_type,
+            } => {
+                let count = &mut buffer[..count_type.size() as usize];
+                reader.read_exact(count)?;
+                count_type.convert_endianness(count, endianness, Endianness::NATIVE);
+                let count = scalar_as_f64(count, count_type);
+                if count < 0.0 {
+                    bail!("Negative length of PLY list property {}", property.name);
+                }
+                let list_size = count as u64 * value_type.size();
+                if io::copy(&mut reader.by_ref().take(list_size), &mut io::sink())? != list_size {
+                    bail!(
+                        "Unexpected end of data in PLY list property {}",
+                        property.name
+                    );
+                }
+                bytes_read += count_type.size() + list_size;
+            }
+        }
+    }
+    Ok(bytes_read)
+}
+
+/// Returns `true` if the position that `position_mapping` assembles from the raw `vertex` contains NaN or infinite
+/// coordinates
+fn has_invalid_position(
+    position_mapping: &PlyAttributeMapping,
+    vertex: &[u8],
+    endianness: Endianness,
+) -> bool {
+    let mut component_buffer = [0; 8];
+    position_mapping
+        .components
+        .iter()
+        .any(|(offset, datatype)| {
+            let component = &mut component_buffer[..datatype.size() as usize];
+            component.copy_from_slice(&vertex[*offset..*offset + component.len()]);
+            datatype.convert_endianness(component, endianness, Endianness::NATIVE);
+            !scalar_as_f64(component, *datatype).is_finite()
+        })
+}
+
+/// Returns the vector datatype for the given components, which is the vector type matching the datatype of the
+/// components, or `Vec3f64` if the components have different datatypes or there is no matching vector type
+fn vector_datatype(components: &[(usize, PointAttributeDataType)]) -> PointAttributeDataType {
+    let component_type = components[0].1;
+    if components
+        .iter()
+        .any(|(_, datatype)| *datatype != component_type)
+    {
+        return PointAttributeDataType::Vec3f64;
+    }
+    match component_type {
+        PointAttributeDataType::U8 => PointAttributeDataType::Vec3u8,
+        PointAttributeDataType::U16 => PointAttributeDataType::Vec3u16,
+        PointAttributeDataType::F32 => PointAttributeDataType::Vec3f32,
+        _ => PointAttributeDataType::Vec3f64,
+    }
+}
+
+/// Interprets the given native-endian `bytes` as a scalar value of the given `datatype` and converts it to `f64`
+fn scalar_as_f64(bytes: &[u8], datatype: PointAttributeDataType) -> f64 {
+    match datatype {
+        PointAttributeDataType::U8 => bytes[0] as f64,
+        PointAttributeDataType::I8 => bytes[0] as i8 as f64,
+        PointAttributeDataType::U16 => u16::from_ne_bytes(bytes.try_into().unwrap()) as f64,
+        PointAttributeDataType::I16 => i16::from_ne_bytes(bytes.try_into().unwrap()) as f64,
+        PointAttributeDataType::U32 => u32::from_ne_bytes(bytes.try_into().unwrap()) as f64,
+        PointAttributeDataType::I32 => i32::from_ne_bytes(bytes.try_into().unwrap()) as f64,
+        PointAttributeDataType::F32 => f32::from_ne_bytes(bytes.try_into().unwrap()) as f64,
+        PointAttributeDataType::F64 => f64::from_ne_bytes(bytes.try_into().unwrap()),
+        other => panic!("Datatype {} is not a PLY datatype", other),
+    }
+}
+
+impl<R: BufRead + Seek> PointReader for PlyReader<R> {
+    fn read(&mut self, count: usize) -> Result<Box<dyn PointBuffer>> {
+        let mut buffer = PerAttributeVecPointStorage::new(self.layout.clone());
+        self.read_into(&mut buffer, count)?;
+        Ok(Box::new(buffer))
+    }
+
+    fn read_into(
+        &mut self,
+        point_buffer: &mut dyn PointBufferWriteable,
+        count: usize,
+    ) -> Result<usize> {
+        let remaining_points = self.metadata.number_of_points().unwrap() - self.current_point_index;
+        let num_to_read = clamp_points_per_read(count, remaining_points, self.max_points_per_read)?;
+        if num_to_read == 0 {
+            bail!("No points remaining in PLY file")
+        }
+
+        let mut vertex_data = self.read_vertex_data(num_to_read)?;
+        let num_points = num_to_read - self.filter_invalid_positions(&mut vertex_data);
+        let target_layout = point_buffer.point_layout().clone();
+        point_buffer.resize(num_points);
+        for mapping in &self.attribute_mappings {
+            // Try to read this attribute only if it exists in the target buffer's PointLayout
+            if let Some(target_attribute) =
+                target_layout.get_attribute_by_name(mapping.attribute.name())
+            {
+                let data = self.attribute_data(mapping, &vertex_data);
+                let target_attribute_def: PointAttributeDefinition = target_attribute.into();
+
+                // Maybe we have to convert the datatype?
+                let converter =
+                    get_converter_for_attributes(&mapping.attribute, &target_attribute_def);
+                let mut dst_buf: Vec<u8> = vec![0; target_attribute.size() as usize];
+                for (point_index, src_buf) in data
+                    .chunks_exact(mapping.attribute.size() as usize)
+                    .enumerate()
+                {
+                    if let Some(conversion_fn) = converter {
+                        unsafe {
+                            conversion_fn(src_buf, dst_buf.as_mut_slice());
+                        }
+                        point_buffer.set_raw_attribute(
+                            point_index,
+                            &target_attribute_def,
+                            dst_buf.as_slice(),
+                        );
+                    } else {
+                        point_buffer.set_raw_attribute(point_index, &target_attribute_def, src_buf);
+                    }
+                }
+            }
+        }
+
+        self.current_point_index += num_to_read;
+        Ok(num_points)
+    }
+
+    fn get_metadata(&self) -> &dyn Metadata {
+        &self.metadata
+    }
+
+    fn get_default_point_layout(&self) -> &PointLayout {
+        &self.layout
+    }
+}
+
+impl<R: BufRead + Seek> SeekToPoint for PlyReader<R> {
+    fn seek_point(&mut self, position: SeekFrom) -> Result<usize> {
+        let number_of_points = self.metadata.number_of_points().unwrap();
+        let new_point_idx: u64 = match position {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::End(offset) => {
+                let max_point_as_i64: i64 = number_of_points.try_into()?;
+                (max_point_as_i64 + offset).try_into()?
+            }
+            SeekFrom::Current(offset) => {
+                let cur_point_asi64: i64 = self.current_point_index.try_into()?;
+                (cur_point_asi64 + offset).try_into()?
+            }
+        };
+        let new_point_idx_clamped = clamp(new_point_idx, 0, number_of_points as u64);
+        self.seek_to_vertex(new_point_idx_clamped.try_into()?)?;
+        Ok(self.current_point_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use pasture_core::{containers::PointBufferExt, nalgebra::Vector3};
+
+    use super::*;
+
+    const TEST_POSITIONS: [[f32; 3]; 2] = [[1.5, -2.25, 1024.0], [0.125, 3.0, -7.5]];
+    const TEST_COLORS: [[u8; 3]; 2] = [[255, 0, 16], [1, 2, 3]];
+    const TEST_INTENSITIES: [u16; 2] = [0x1234, 65535];
+    const TEST_CONFIDENCES: [f64; 2] = [0.25, -1.0e10];
+
+    /// Creates a binary PLY file in memory with the test data in the given byte order
+    fn make_test_ply(endianness: Endianness) -> Vec<u8> {
+        let format = match endianness {
+            Endianness::Little => "binary_little_endian",
+            Endianness::Big => "binary_big_endian",
+        };
+        let mut ply = format!(
+            "ply\nformat {} 1.0\ncomment test data\nelement vertex 2\n\
+             property float x\nproperty float y\nproperty float z\n\
+             property uchar red\nproperty uchar green\nproperty uchar blue\n\
+             property ushort intensity\nproperty double confidence\nend_header\n",
+            format
+        )
+        .into_bytes();
+
+        for index in 0..2 {
+            let mut vertex = vec![];
+            for coordinate in &TEST_POSITIONS[index] {
+                vertex.extend_from_slice(&coordinate.to_ne_bytes());
+            }
+            vertex.extend_from_slice(&TEST_COLORS[index]);
+            vertex.extend_from_slice(&TEST_INTENSITIES[index].to_ne_bytes());
+            vertex.extend_from_slice(&TEST_CONFIDENCES[index].to_ne_bytes());
+            // Convert the components individually, the vertex itself is not a single value
+            let mut offset = 0;
+            for datatype in [
+                PointAttributeDataType::Vec3f32,
+                PointAttributeDataType::Vec3u8,
+                PointAttributeDataType::U16,
+                PointAttributeDataType::F64,
+            ]
+            .iter()
+            {
+                let size = datatype.size() as usize;
+                datatype.convert_endianness(
+                    &mut vertex[offset..offset + size],
+                    Endianness::NATIVE,
+                    endianness,
+                );
+                offset += size;
+            }
+            ply.extend_from_slice(&vertex);
+        }
+        ply
+    }
+
+    #[test]
+    fn test_ply_reader_default_layout() -> Result<()> {
+        let reader = PlyReader::from_read(Cursor::new(make_test_ply(Endianness::Big)))?;
+        let expected_layout = PointLayout::from_attributes_packed(
+            &[
+                POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+                COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+                INTENSITY,
+                PointAttributeDefinition::custom_dynamic("confidence", PointAttributeDataType::F64),
+            ],
+            1,
+        );
+        assert_eq!(&expected_layout, reader.get_default_point_layout());
+        assert_eq!(Some(2), reader.get_metadata().number_of_points());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ply_reader_big_and_little_endian() -> Result<()> {
+        for endianness in [Endianness::Big, Endianness::Little].iter() {
+            let mut reader = PlyReader::from_read(Cursor::new(make_test_ply(*endianness)))?;
+            let points = reader.read(2)?;
+
+            let positions = points
+                .iter_attribute::<Vector3<f32>>(
+                    &POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+                )
+                .collect::<Vec<_>>();
+            let expected_positions = TEST_POSITIONS
+                .iter()
+                .map(|p| Vector3::new(p[0], p[1], p[2]))
+                .collect::<Vec<_>>();
+            assert_eq!(expected_positions, positions, "{:?}", endianness);
+
+            let colors = points
+                .iter_attribute::<Vector3<u8>>(
+                    &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+                )
+                .collect::<Vec<_>>();
+            let expected_colors = TEST_COLORS
+                .iter()
+                .map(|c| Vector3::new(c[0], c[1], c[2]))
+                .collect::<Vec<_>>();
+            assert_eq!(expected_colors, colors, "{:?}", endianness);
+
+            let intensities = points.iter_attribute::<u16>(&INTENSITY).collect::<Vec<_>>();
+            assert_eq!(TEST_INTENSITIES.to_vec(), intensities, "{:?}", endianness);
+
+            let confidences = points
+                .iter_attribute::<f64>(&PointAttributeDefinition::custom_dynamic(
+                    "confidence",
+                    PointAttributeDataType::F64,
+                ))
+                .collect::<Vec<_>>();
+            assert_eq!(TEST_CONFIDENCES.to_vec(), confidences, "{:?}", endianness);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ply_reader_read_into_and_seek() -> Result<()> {
+        let mut reader = PlyReader::from_read(Cursor::new(make_test_ply(Endianness::Big)))?;
+        reader.seek_point(SeekFrom::Start(1))?;
+
+        let mut buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            POSITION_3D,
+            COLOR_RGB,
+        ]));
+        assert_eq!(1, reader.read_into(&mut buffer, 2)?);
+        let position = buffer.get_attribute::<Vector3<f64>>(&POSITION_3D, 0);
+        assert_eq!(Vector3::new(0.125, 3.0, -7.5), position);
+        // The file stores 8-bit colors, which are scaled up to the 16-bit COLOR_RGB range
+        let color = buffer.get_attribute::<Vector3<u16>>(&COLOR_RGB, 0);
+        assert_eq!(Vector3::new(256, 512, 768), color);
+
+        assert!(reader.read(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ply_reader_max_points_per_read() -> Result<()> {
+        let mut reader = PlyReader::from_read(Cursor::new(make_test_ply(Endianness::Little)))?;
+        reader.set_max_points_per_read(1);
+        assert!(reader.read(2).is_err());
+        assert_eq!(1, reader.read(1)?.len());
+        // Only one point remains, so the request is clamped and does not exceed the maximum
+        assert_eq!(1, reader.read(100)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ply_reader_unsupported_files() {
+        let ascii = b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nend_header\n1.0\n";
+        assert!(PlyReader::from_read(Cursor::new(&ascii[..])).is_err());
+
+        let no_vertices = b"ply\nformat binary_little_endian 1.0\nelement face 0\nproperty list uchar int vertex_indices\nend_header\n";
+        assert!(PlyReader::from_read(Cursor::new(&no_vertices[..])).is_err());
+    }
+
+    #[test]
+    fn test_ply_reader_duplicate_attributes() {
+        let duplicate_property = b"ply\nformat binary_little_endian 1.0\nelement vertex 0\n\
+            property float confidence\nproperty double confidence\nend_header\n";
+        assert!(PlyReader::from_read(Cursor::new(&duplicate_property[..])).is_err());
+
+        // `intensity` is read as the builtin `INTENSITY` attribute, whose name is `Intensity`
+        let builtin_name = b"ply\nformat binary_little_endian 1.0\nelement vertex 0\n\
+            property ushort intensity\nproperty ushort Intensity\nend_header\n";
+        assert!(PlyReader::from_read(Cursor::new(&builtin_name[..])).is_err());
+    }
+
+    #[test]
+    fn test_ply_reader_skips_list_properties() -> Result<()> {
+        let mut ply = b"ply\nformat binary_big_endian 1.0\nelement face 2\n\
+            property list uchar int vertex_indices\nproperty uchar flags\nelement vertex 3\n\
+            property float x\nproperty list ushort uchar labels\nproperty float y\nproperty float z\n\
+            element edge 1\nproperty int vertex1\nproperty int vertex2\nend_header\n"
+            .to_vec();
+        // Faces with 3 and 4 vertex indices
+        for face in [vec![0_i32, 1, 2], vec![2, 1, 0, 1]].iter() {
+            ply.push(face.len() as u8);
+            for index in face {
+                ply.extend_from_slice(&index.to_be_bytes());
+            }
+            ply.push(0xff);
+        }
+        let positions = [[1.0_f32, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]];
+        for (index, position) in positions.iter().enumerate() {
+            ply.extend_from_slice(&position[0].to_be_bytes());
+            // Vertices with 0, 1 and 2 labels
+            ply.extend_from_slice(&(index as u16).to_be_bytes());
+            ply.extend(std::iter::repeat(0xee).take(index));
+            ply.extend_from_slice(&position[1].to_be_bytes());
+            ply.extend_from_slice(&position[2].to_be_bytes());
+        }
+        ply.extend_from_slice(&[0; 8]);
+
+        let mut reader = PlyReader::from_read(Cursor::new(ply))?;
+        let expected_layout = PointLayout::from_attributes_packed(
+            &[POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32)],
+            1,
+        );
+        assert_eq!(&expected_layout, reader.get_default_point_layout());
+
+        let read_positions = |points: Box<dyn PointBuffer>| {
+            points
+                .iter_attribute::<Vector3<f32>>(
+                    &POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+                )
+                .collect::<Vec<_>>()
+        };
+        let expected_positions = positions
+            .iter()
+            .map(|p| Vector3::new(p[0], p[1], p[2]))
+            .collect::<Vec<_>>();
+        assert_eq!(expected_positions, read_positions(reader.read(3)?));
+
+        reader.seek_point(SeekFrom::Start(2))?;
+        assert_eq!(expected_positions[2..], read_positions(reader.read(1)?)[..]);
+        reader.seek_point(SeekFrom::Start(1))?;
+        assert_eq!(expected_positions[1..], read_positions(reader.read(2)?)[..]);
+        Ok(())
+    }
+
+    /// Creates a little-endian PLY file in memory that contains only the given positions
+    fn make_positions_ply(positions: &[[f32; 3]]) -> Vec<u8> {
+        let mut ply = format!(
+            "ply\nformat binary_little_endian 1.0\nelement vertex {}\n\
+             property float x\nproperty float y\nproperty float z\nend_header\n",
+            positions.len()
+        )
+        .into_bytes();
+        for coordinate in positions.iter().flatten() {
+            ply.extend_from_slice(&coordinate.to_le_bytes());
+        }
+        ply
+    }
+
+    const POSITIONS_WITH_INVALID_VALUES: [[f32; 3]; 5] = [
+        [0.0, 0.0, 0.0],
+        [f32::NAN, 1.0, 2.0],
+        [1.0, f32::INFINITY, 1.0],
+        [2.0, 2.0, f32::NEG_INFINITY],
+        [2.0, 4.0, 2.0],
+    ];
+
+    #[test]
+    fn test_ply_reader_skip_invalid_positions() -> Result<()> {
+        let mut reader = PlyReader::from_read(Cursor::new(make_positions_ply(
+            &POSITIONS_WITH_INVALID_VALUES,
+        )))?;
+        reader.set_skip_invalid_positions(true);
+        let mut buffer =
+            PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[POSITION_3D]));
+        assert_eq!(1, reader.read_into(&mut buffer, 2)?);
+        assert_eq!(1, reader.count_invalid());
+        let points = reader.read(3)?;
+        assert_eq!(3, reader.count_invalid());
+
+        let positions = points
+            .iter_attribute_as::<Vector3<f64>>(&POSITION_3D)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Vector3::new(2.0, 4.0, 2.0)], positions);
+        assert_eq!(
+            Vector3::new(0.0, 0.0, 0.0),
+            buffer.get_attribute::<Vector3<f64>>(&POSITION_3D, 0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ply_reader_keeps_invalid_positions_by_default() -> Result<()> {
+        let mut reader = PlyReader::from_read(Cursor::new(make_positions_ply(
+            &POSITIONS_WITH_INVALID_VALUES,
+        )))?;
+        assert!(!reader.skip_invalid_positions());
+        assert_eq!(5, reader.read(5)?.len());
+        assert_eq!(3, reader.count_invalid());
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use pasture_core::math::Alignable;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
     convert::TryInto,
     io::{BufRead, Seek, SeekFrom, Write},
@@ -126,9 +126,13 @@ pub fn ser_batch_table_header<W: Write + Seek>(
     batch_table_header: &BatchTableHeader,
     position_in_file: usize,
 ) -> Result<()> {
+    // Sort the keys so that the JSON does not depend on the HashMap order, since serde_json might preserve the
+    // insertion order
     let header_as_map = batch_table_header
         .iter()
         .map(|(k, v)| -> (String, Value) { (k.clone(), v.into()) })
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
         .collect::<Map<_, _>>();
     let header_json_obj = Value::Object(header_as_map);
 
@@ -53,6 +53,22 @@ pub fn write_json_header<W: Write>(
     Ok(())
 }
 
+/// Converts an array of JSON Values into a Vector3<f64>
+pub fn json_arr_to_vec3f64(json_arr: &[Value]) -> Result<Vector3<f64>> {
+    if json_arr.len() != 3 {
+        bail!(
+            "JSON array must have length 3 to convert to Vector3<f64> (but has length {})",
+            json_arr.len()
+        )
+    }
+    let vals = json_arr
+        .iter()
+        .map(|v| v.as_f64().ok_or(anyhow!("Can't convert JSON value to f64")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Vector3::new(vals[0], vals[1], vals[2]))
+}
+
 /// Converts an array of JSON Values into a Vector3<f32>
 pub fn json_arr_to_vec3f32(json_arr: &[Value]) -> Result<Vector3<f32>> {
     if json_arr.len() != 3 {
@@ -1,9 +1,9 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use pasture_core::math::Alignable;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
     convert::TryInto,
     io::{BufRead, Seek, SeekFrom, Write},
@@ -20,6 +20,22 @@ pub struct FeatureTableDataReference {
     pub component_type: Option<String>,
 }
 
+/// Returns the size in bytes of a single component with the given `component_type`, as defined in the
+/// [3D Tiles specification](https://github.com/CesiumGS/3d-tiles/blob/master/specification/TileFormats/FeatureTable/README.md#binary-body)
+///
+/// # Errors
+///
+/// If `component_type` is not one of the component types defined by 3D Tiles
+pub fn component_type_size(component_type: &str) -> Result<usize> {
+    match component_type {
+        "BYTE" | "UNSIGNED_BYTE" => Ok(1),
+        "SHORT" | "UNSIGNED_SHORT" => Ok(2),
+        "INT" | "UNSIGNED_INT" | "FLOAT" => Ok(4),
+        "DOUBLE" => Ok(8),
+        _ => bail!("Unknown componentType {}", component_type),
+    }
+}
+
 /// Different possible values for an entry in a 3D Tiles FeatureTable
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum FeatureTableValue {
@@ -146,9 +162,13 @@ pub fn ser_feature_table_header<W: Write>(
     feature_table_header: &FeatureTableHeader,
     header_start_position_in_file: usize,
 ) -> Result<()> {
+    // Sort the keys so that the JSON does not depend on the HashMap order, since serde_json might preserve the
+    // insertion order
     let header_as_map = feature_table_header
         .iter()
         .map(|(k, v)| -> (String, Value) { (k.clone(), v.into()) })
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
         .collect::<Map<_, _>>();
     let header_json_obj = Value::Object(header_as_map);
 
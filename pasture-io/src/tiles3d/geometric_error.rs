@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use pasture_core::{
+    containers::{PointBuffer, PointBufferExt},
+    layout::attributes::POSITION_3D,
+    math::AABB,
+    nalgebra::{Point3, Vector3},
+};
+
+use super::octree_builder::bounds_of_positions;
+
+/// Method for estimating the spacing of a set of points, see [estimate_point_spacing]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointSpacingEstimate {
+    /// Mean distance of each point to its nearest neighbor. This is accurate for all distributions of points, but
+    /// requires a nearest neighbor search for every point
+    MeanNearestNeighborDistance,
+    /// Length of the diagonal of the bounding box of the points, divided by the cube root of the number of points. This
+    /// is cheap to compute and proportional to the spacing of points that fill their bounding box uniformly, but it
+    /// overestimates the spacing of points that lie on a surface, as is the case for most LiDAR data
+    BoundsDiagonal,
+}
+
+/// Returns the geometric error of a tile with the given `node_bounds` whose points have the given `point_spacing`.
+///
+/// The geometric error is the error (in the units of the positions) that is introduced if a tile is rendered without its
+/// children, which Cesium uses to decide when to refine a tile. A tile that is rendered on its own shows gaps of about
+/// `point_spacing` between its points, so the point spacing is used as the geometric error. Since a tile can't be off by
+/// more than its own size, the error is clamped to the length of the diagonal of `node_bounds`.
+///
+/// With this heuristic, the geometric error of a tile grows linearly with the spacing of its points. If the points of a
+/// tile are estimated with [estimate_point_spacing], a tile that covers twice the extent with the same number of points
+/// gets twice the error, while a denser tile gets a proportionally smaller error and is refined later.
+pub fn geometric_error(node_bounds: AABB<f64>, point_spacing: f64) -> f64 {
+    let diagonal = node_bounds.extent().norm();
+    point_spacing.max(0.0).min(diagonal)
+}
+
+/// Estimates the spacing of the points in `buffer` using the given `method`. If `buffer` contains less than two points,
+/// the spacing is zero
+///
+/// # Errors
+///
+/// If `buffer` has no `POSITION_3D` attribute
+pub fn estimate_point_spacing(
+    buffer: &dyn PointBuffer,
+    method: PointSpacingEstimate,
+) -> Result<f64> {
+    if !buffer
+        .point_layout()
+        .has_attribute_with_name(POSITION_3D.name())
+    {
+        bail!(
+            "Can't estimate the point spacing of a PointBuffer without the POSITION_3D attribute"
+        );
+    }
+    let positions = buffer
+        .iter_attribute_as::<Vector3<f64>>(&POSITION_3D)
+        .map(Point3::from)
+        .collect::<Vec<_>>();
+    Ok(estimate_point_spacing_of_positions(&positions, method))
+}
+
+/// Estimates the spacing of the given `positions` using the given `method`
+pub(crate) fn estimate_point_spacing_of_positions(
+    positions: &[Point3<f64>],
+    method: PointSpacingEstimate,
+) -> f64 {
+    if positions.len() < 2 {
+        return 0.0;
+    }
+    let bounds = bounds_of_positions(positions);
+    match method {
+        PointSpacingEstimate::BoundsDiagonal => bounds_diagonal_spacing(&bounds, positions.len()),
+        PointSpacingEstimate::MeanNearestNeighborDistance => {
+            mean_nearest_neighbor_distance(positions, &bounds)
+        }
+    }
+}
+
+fn bounds_diagonal_spacing(bounds: &AABB<f64>, num_points: usize) -> f64 {
+    bounds.extent().norm() / (num_points as f64).cbrt()
+}
+
+/// Maximum number of times that the cells of the grid for the nearest neighbor search are subdivided
+const MAX_GRID_SUBDIVISIONS: usize = 16;
+/// Average number of points per occupied cell that the grid for the nearest neighbor search aims for
+const TARGET_POINTS_PER_CELL: usize = 8;
+/// Maximum number of rings of cells that are searched around a position before falling back to a brute-force search
+const MAX_SEARCH_RINGS: i64 = 4;
+/// Maximum number of positions that the brute-force search compares a position against
+const MAX_BRUTE_FORCE_SAMPLES: usize = 4096;
+
+/// Computes the mean distance of each of the given `positions` to its nearest neighbor. The positions are sorted into a
+/// uniform grid, and the neighbors of each position are searched in rings of cells of growing size around its cell.
+///
+/// The cells are sized for the densest regions, so positions far away from all others (e.g. outliers) would require a
+/// huge number of rings. If no neighbor is found within `MAX_SEARCH_RINGS` rings, the position is instead compared
+/// against an evenly strided sample of at most `MAX_BRUTE_FORCE_SAMPLES` positions. For these positions, the distance
+/// is therefore an upper bound of the distance to the nearest neighbor
+fn mean_nearest_neighbor_distance(positions: &[Point3<f64>], bounds: &AABB<f64>) -> f64 {
+    let mut cell_size = bounds_diagonal_spacing(bounds, positions.len());
+    if cell_size == 0.0 {
+        // All positions are equal
+        return 0.0;
+    }
+
+    let cell_of = |position: &Point3<f64>, cell_size: f64| {
+        let cell = (position - bounds.min()) / cell_size;
+        [
+            cell.x.floor() as i64,
+            cell.y.floor() as i64,
+            cell.z.floor() as i64,
+        ]
+    };
+    let build_grid = |cell_size: f64| {
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        for (index, position) in positions.iter().enumerate() {
+            grid.entry(cell_of(position, cell_size))
+                .or_default()
+                .push(index);
+        }
+        grid
+    };
+
+    // Points on a surface are much denser than the bounding box suggests, so the cells are subdivided until they
+    // contain only a few points each
+    let mut grid = build_grid(cell_size);
+    for _ in 0..MAX_GRID_SUBDIVISIONS {
+        if grid.len() * TARGET_POINTS_PER_CELL >= positions.len() {
+            break;
+        }
+        cell_size /= 2.0;
+        grid = build_grid(cell_size);
+    }
+
+    let max_ring = ((bounds.extent().max() / cell_size).ceil() as i64 + 1).min(MAX_SEARCH_RINGS);
+    let sample_stride = (positions.len() + MAX_BRUTE_FORCE_SAMPLES - 1) / MAX_BRUTE_FORCE_SAMPLES;
+    let mut sum_of_distances = 0.0;
+    for (index, position) in positions.iter().enumerate() {
+        let center_cell = cell_of(position, cell_size);
+        let mut min_distance = f64::INFINITY;
+        let mut found_nearest_neighbor = false;
+        for ring in 0..=max_ring {
+            for_each_cell_in_ring(center_cell, ring, |cell| {
+                for neighbor_index in grid.get(&cell).into_iter().flatten() {
+                    if *neighbor_index != index {
+                        let distance = (positions[*neighbor_index] - position).norm();
+                        min_distance = min_distance.min(distance);
+                    }
+                }
+            });
+            // All cells outside of the current ring are at least `ring` cells away from the position
+            if min_distance <= ring as f64 * cell_size {
+                found_nearest_neighbor = true;
+                break;
+            }
+        }
+        if !found_nearest_neighbor {
+            for (neighbor_index, neighbor) in positions.iter().enumerate().step_by(sample_stride) {
+                if neighbor_index != index {
+                    min_distance = min_distance.min((neighbor - position).norm());
+                }
+            }
+        }
+        sum_of_distances += min_distance;
+    }
+    sum_of_distances / positions.len() as f64
+}
+
+/// Calls `f` for each grid cell whose Chebyshev distance to `center` equals `ring`, i.e. for the cells on the shell of
+/// the cube of cells with radius `ring` around `center`
+fn for_each_cell_in_ring<F: FnMut([i64; 3])>(center: [i64; 3], ring: i64, mut f: F) {
+    if ring == 0 {
+        f(center);
+        return;
+    }
+    for dz in -ring..=ring {
+        for dy in -ring..=ring {
+            if dz.abs() == ring || dy.abs() == ring {
+                for dx in -ring..=ring {
+                    f([center[0] + dx, center[1] + dy, center[2] + dz]);
+                }
+            } else {
+                // Only the two cells on the faces of the cube in x-direction are part of the shell
+                f([center[0] - ring, center[1] + dy, center[2] + dz]);
+                f([center[0] + ring, center[1] + dy, center[2] + dz]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasture_core::{
+        containers::PerAttributeVecPointStorage,
+        layout::{attributes::INTENSITY, PointLayout},
+    };
+
+    use super::*;
+
+    /// Returns `points_per_axis`^3 positions on a regular grid with the given `spacing`, starting at the origin
+    fn grid_positions(points_per_axis: usize, spacing: f64) -> Vec<Point3<f64>> {
+        let mut positions = vec![];
+        for z in 0..points_per_axis {
+            for y in 0..points_per_axis {
+                for x in 0..points_per_axis {
+                    positions.push(Point3::new(
+                        x as f64 * spacing,
+                        y as f64 * spacing,
+                        z as f64 * spacing,
+                    ));
+                }
+            }
+        }
+        positions
+    }
+
+    fn geometric_error_of_positions(
+        positions: &[Point3<f64>],
+        method: PointSpacingEstimate,
+    ) -> f64 {
+        geometric_error(
+            bounds_of_positions(positions),
+            estimate_point_spacing_of_positions(positions, method),
+        )
+    }
+
+    #[test]
+    fn test_mean_nearest_neighbor_distance() {
+        let positions = grid_positions(10, 1.5);
+        let spacing = estimate_point_spacing_of_positions(
+            &positions,
+            PointSpacingEstimate::MeanNearestNeighborDistance,
+        );
+        assert!(
+            (spacing - 1.5).abs() < 1e-9,
+            "Unexpected spacing {}",
+            spacing
+        );
+
+        // Points on a plane
+        let planar_positions = (0..10_000)
+            .map(|index| Point3::new((index % 100) as f64, (index / 100) as f64, 0.0))
+            .collect::<Vec<_>>();
+        let spacing = estimate_point_spacing_of_positions(
+            &planar_positions,
+            PointSpacingEstimate::MeanNearestNeighborDistance,
+        );
+        assert!(
+            (spacing - 1.0).abs() < 1e-9,
+            "Unexpected spacing {}",
+            spacing
+        );
+
+        assert_eq!(
+            0.0,
+            estimate_point_spacing_of_positions(
+                &[Point3::new(1.0, 2.0, 3.0)],
+                PointSpacingEstimate::MeanNearestNeighborDistance
+            )
+        );
+    }
+
+    #[test]
+    fn test_mean_nearest_neighbor_distance_with_outlier() {
+        // A single outlier far away from a dense plane must neither hang the search nor distort the spacing of the
+        // other points
+        let mut positions = (0..10_000)
+            .map(|index| Point3::new((index % 100) as f64, (index / 100) as f64, 0.0))
+            .collect::<Vec<_>>();
+        positions.push(Point3::new(10_000.0, 0.0, 0.0));
+        let spacing = estimate_point_spacing_of_positions(
+            &positions,
+            PointSpacingEstimate::MeanNearestNeighborDistance,
+        );
+        // The nearest neighbor of the outlier is (99, 0, 0)
+        let expected_spacing = (10_000.0 + 9_901.0) / 10_001.0;
+        assert!(
+            (spacing - expected_spacing).abs() < 1e-2,
+            "Unexpected spacing {}",
+            spacing
+        );
+    }
+
+    #[test]
+    fn test_for_each_cell_in_ring_visits_shell() {
+        for ring in 0..4 {
+            let mut cells = vec![];
+            for_each_cell_in_ring([1, 2, 3], ring, |cell| cells.push(cell));
+            let expected_count = if ring == 0 {
+                1
+            } else {
+                (2 * ring + 1).pow(3) - (2 * ring - 1).pow(3)
+            };
+            assert_eq!(expected_count as usize, cells.len());
+            assert!(cells.iter().all(|cell| {
+                let distance = (cell[0] - 1)
+                    .abs()
+                    .max((cell[1] - 2).abs())
+                    .max((cell[2] - 3).abs());
+                distance == ring
+            }));
+            cells.sort_unstable();
+            cells.dedup();
+            assert_eq!(expected_count as usize, cells.len());
+        }
+    }
+
+    #[test]
+    fn test_geometric_error_scales_with_node_size_and_density() {
+        for method in [
+            PointSpacingEstimate::MeanNearestNeighborDistance,
+            PointSpacingEstimate::BoundsDiagonal,
+        ]
+        .iter()
+        {
+            let node_error = geometric_error_of_positions(&grid_positions(10, 1.0), *method);
+            assert!(node_error > 0.0);
+
+            // The same number of points in a node with twice the extent
+            let larger_node_error = geometric_error_of_positions(&grid_positions(10, 2.0), *method);
+            assert!(
+                (larger_node_error - 2.0 * node_error).abs() < 1e-9,
+                "{:?}: {} is not twice {}",
+                method,
+                larger_node_error,
+                node_error
+            );
+
+            // Eight times the number of points with the same extent
+            let denser_node_error =
+                geometric_error_of_positions(&grid_positions(20, 9.0 / 19.0), *method);
+            let expected_ratio = match method {
+                PointSpacingEstimate::MeanNearestNeighborDistance => 9.0 / 19.0,
+                PointSpacingEstimate::BoundsDiagonal => 0.5,
+            };
+            assert!(
+                (denser_node_error - expected_ratio * node_error).abs() < 1e-9,
+                "{:?}: {} does not match {} * {}",
+                method,
+                denser_node_error,
+                expected_ratio,
+                node_error
+            );
+        }
+    }
+
+    #[test]
+    fn test_geometric_error_is_clamped_to_node_size() {
+        let bounds = AABB::from_min_max(Point3::new(0.0, 0.0, 0.0), Point3::new(3.0, 4.0, 0.0));
+        assert_eq!(0.5, geometric_error(bounds, 0.5));
+        assert_eq!(5.0, geometric_error(bounds, 100.0));
+        assert_eq!(0.0, geometric_error(bounds, -1.0));
+    }
+
+    #[test]
+    fn test_estimate_point_spacing_requires_positions() {
+        let buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[INTENSITY]));
+        assert!(estimate_point_spacing(&buffer, PointSpacingEstimate::BoundsDiagonal).is_err());
+    }
+}
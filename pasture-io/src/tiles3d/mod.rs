@@ -21,3 +21,9 @@ pub use self::common::*;
 
 mod tileset;
 pub use self::tileset::*;
+
+mod octree_builder;
+pub use self::octree_builder::*;
+
+mod geometric_error;
+pub use self::geometric_error::*;
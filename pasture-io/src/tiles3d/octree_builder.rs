@@ -0,0 +1,437 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use pasture_core::{
+    containers::{
+        PerAttributeVecPointStorage, PointBuffer, PointBufferExt, PointBufferWriteable,
+        PointBufferWriteableExt,
+    },
+    layout::attributes::POSITION_3D,
+    math::AABB,
+    nalgebra::{Point3, Vector3},
+};
+
+use crate::base::PointWriter;
+
+use super::{
+    estimate_point_spacing_of_positions, geometric_error, BoundingVolume, PntsWriter,
+    PointSpacingEstimate, Refinement, RootTileset, Tileset, TilesetBuilder,
+};
+
+/// Options for building a 3D Tiles tileset using `build_tileset_from_buffer`
+#[derive(Clone, Debug)]
+pub struct OctreeTilesetOptions {
+    /// Maximum number of points that a single node of the octree (and thus a single .pnts file) may contain. Nodes
+    /// with more points are split into eight child nodes
+    pub max_points_per_node: usize,
+    /// Maximum depth of the octree. Nodes at this depth are never split, regardless of their number of points
+    pub max_depth: usize,
+    /// Geometric error of the root node. If this is `None`, the length of the diagonal of the bounding box of the
+    /// points is used. The geometric error halves with each level of the octree, leaf nodes have a geometric error
+    /// of zero
+    pub root_geometric_error: Option<f64>,
+    /// If this is set, the geometric error of each inner node is computed with [geometric_error] from the spacing of
+    /// the points of the node, which is estimated with the given method. This adapts the levels of detail to the
+    /// density of the points instead of halving the geometric error with each level. `root_geometric_error` is then only
+    /// used for the tileset itself
+    pub point_spacing_estimate: Option<PointSpacingEstimate>,
+    /// Name of the tileset JSON file within the output directory
+    pub tileset_file_name: String,
+}
+
+impl Default for OctreeTilesetOptions {
+    fn default() -> Self {
+        Self {
+            max_points_per_node: 50_000,
+            max_depth: 16,
+            root_geometric_error: None,
+            point_spacing_estimate: None,
+            tileset_file_name: "tileset.json".into(),
+        }
+    }
+}
+
+/// A node of the octree during construction
+struct OctreeNode {
+    name: String,
+    bounds: AABB<f64>,
+    depth: usize,
+    point_indices: Vec<usize>,
+}
+
+/// Builds an octree from the points in the given `buffer` and writes it as a 3D Tiles tileset into `output_dir`. Each
+/// node of the octree is written as a separate .pnts file and a tileset JSON file that references all nodes is written
+/// alongside them. Inner nodes store a uniform subsample of their points and refine additively (`Refinement::Add`), so
+/// a viewer such as Cesium can display coarse levels of detail first and progressively load the finer nodes.
+///
+/// Positions of each node are stored relative to the center of the node (using the `RTC_CENTER` semantic), so the
+/// precision of large (e.g. georeferenced) coordinates is preserved even though .pnts files store positions as `Vec3f32`.
+///
+/// On success, the `RootTileset` that was written to the tileset JSON file is returned.
+///
+/// # Errors
+///
+/// Returns an error if `buffer` has no `POSITION_3D` attribute, if `options.max_points_per_node` is zero, or if any
+/// of the files could not be written
+///
+/// # Example
+/// ```no_run
+/// # use anyhow::Result;
+/// # use pasture_io::{base::PointReader, las::LASReader, tiles3d::{build_tileset_from_buffer, OctreeTilesetOptions}};
+/// # fn main() -> Result<()> {
+/// let mut reader = LASReader::from_path("points.las")?;
+/// let point_count = reader.remaining_points();
+/// let points = reader.read(point_count)?;
+/// build_tileset_from_buffer(points.as_ref(), "tileset", &OctreeTilesetOptions::default())?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn build_tileset_from_buffer<P: AsRef<Path>>(
+    buffer: &dyn PointBuffer,
+    output_dir: P,
+    options: &OctreeTilesetOptions,
+) -> Result<RootTileset> {
+    if !buffer
+        .point_layout()
+        .has_attribute_with_name(POSITION_3D.name())
+    {
+        bail!("Can't build a tileset from a PointBuffer without the POSITION_3D attribute");
+    }
+    if options.max_points_per_node == 0 {
+        bail!("The maximum number of points per octree node must be larger than zero");
+    }
+
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Could not create output directory {}", output_dir.display()))?;
+
+    let positions = buffer
+        .iter_attribute_as::<Vector3<f64>>(&POSITION_3D)
+        .map(Point3::from)
+        .collect::<Vec<_>>();
+    let bounds = bounds_of_positions(&positions).as_cubic();
+    let root_geometric_error = options
+        .root_geometric_error
+        .unwrap_or_else(|| bounds.extent().norm());
+
+    let root_node = OctreeNode {
+        name: "r".into(),
+        bounds,
+        depth: 0,
+        point_indices: (0..buffer.len()).collect(),
+    };
+    let root = build_node(
+        root_node,
+        buffer,
+        &positions,
+        output_dir,
+        root_geometric_error,
+        options,
+    )?;
+
+    let root_tileset = RootTileset {
+        geometric_error: root_geometric_error,
+        root,
+        ..Default::default()
+    };
+
+    let tileset_path = output_dir.join(&options.tileset_file_name);
+    let tileset_writer = BufWriter::new(
+        File::create(&tileset_path)
+            .with_context(|| format!("Could not create file {}", tileset_path.display()))?,
+    );
+    serde_json::to_writer(tileset_writer, &root_tileset)
+        .context("Error while writing tileset JSON")?;
+
+    Ok(root_tileset)
+}
+
+/// Returns the bounding box of the given positions. If `positions` is empty, an empty bounding box at the origin is returned
+pub(crate) fn bounds_of_positions(positions: &[Point3<f64>]) -> AABB<f64> {
+    match positions.first() {
+        None => AABB::from_min_max(Point3::origin(), Point3::origin()),
+        Some(first) => positions
+            .iter()
+            .skip(1)
+            .fold(AABB::from_min_max(*first, *first), |bounds, position| {
+                AABB::extend_with_point(&bounds, position)
+            }),
+    }
+}
+
+/// Returns the index of the child octant of `bounds` that contains `position`
+fn octant_of(position: &Point3<f64>, bounds: &AABB<f64>) -> usize {
+    let center = bounds.center();
+    let mut octant = 0;
+    if position.x >= center.x {
+        octant |= 1;
+    }
+    if position.y >= center.y {
+        octant |= 2;
+    }
+    if position.z >= center.z {
+        octant |= 4;
+    }
+    octant
+}
+
+/// Returns the bounds of the given child octant of `bounds`
+fn child_bounds(bounds: &AABB<f64>, octant: usize) -> AABB<f64> {
+    let center = bounds.center();
+    let min = bounds.min();
+    let max = bounds.max();
+    let child_min = Point3::new(
+        if octant & 1 == 0 { min.x } else { center.x },
+        if octant & 2 == 0 { min.y } else { center.y },
+        if octant & 4 == 0 { min.z } else { center.z },
+    );
+    let child_max = Point3::new(
+        if octant & 1 == 0 { center.x } else { max.x },
+        if octant & 2 == 0 { center.y } else { max.y },
+        if octant & 4 == 0 { center.z } else { max.z },
+    );
+    AABB::from_min_max_unchecked(child_min, child_max)
+}
+
+/// Recursively builds the given `node` and all its children, writes the .pnts files and returns the corresponding `Tileset`
+fn build_node(
+    mut node: OctreeNode,
+    buffer: &dyn PointBuffer,
+    positions: &[Point3<f64>],
+    output_dir: &Path,
+    root_geometric_error: f64,
+    options: &OctreeTilesetOptions,
+) -> Result<Tileset> {
+    let is_leaf =
+        node.point_indices.len() <= options.max_points_per_node || node.depth >= options.max_depth;
+
+    // Inner nodes keep a uniform subsample of their points, all remaining points are distributed to the children
+    let (node_points, remaining_points) = if is_leaf {
+        (std::mem::take(&mut node.point_indices), vec![])
+    } else {
+        let stride = (node.point_indices.len() + options.max_points_per_node - 1)
+            / options.max_points_per_node;
+        let (sampled, remaining): (Vec<_>, Vec<_>) = node
+            .point_indices
+            .drain(..)
+            .enumerate()
+            .partition(|(idx, _)| idx % stride == 0);
+        (
+            sampled
+                .into_iter()
+                .map(|(_, point_index)| point_index)
+                .collect(),
+            remaining
+                .into_iter()
+                .map(|(_, point_index)| point_index)
+                .collect(),
+        )
+    };
+
+    let mut children = vec![];
+    if !is_leaf {
+        let mut child_points: Vec<Vec<usize>> = vec![vec![]; 8];
+        for point_index in remaining_points {
+            child_points[octant_of(&positions[point_index], &node.bounds)].push(point_index);
+        }
+        for (octant, point_indices) in child_points.into_iter().enumerate() {
+            if point_indices.is_empty() {
+                continue;
+            }
+            let child_node = OctreeNode {
+                name: format!("{}{}", node.name, octant),
+                bounds: child_bounds(&node.bounds, octant),
+                depth: node.depth + 1,
+                point_indices,
+            };
+            children.push(build_node(
+                child_node,
+                buffer,
+                positions,
+                output_dir,
+                root_geometric_error,
+                options,
+            )?);
+        }
+    }
+
+    let content_uri = format!("{}.pnts", node.name);
+    write_node_points(
+        buffer,
+        &node_points,
+        &node.bounds,
+        output_dir.join(&content_uri),
+    )?;
+
+    let node_geometric_error = if children.is_empty() {
+        0.0
+    } else if let Some(method) = options.point_spacing_estimate {
+        let node_positions = node_points
+            .iter()
+            .map(|point_index| positions[*point_index])
+            .collect::<Vec<_>>();
+        geometric_error(
+            node.bounds,
+            estimate_point_spacing_of_positions(&node_positions, method),
+        )
+    } else {
+        root_geometric_error / (1 << node.depth) as f64
+    };
+
+    let mut tileset_builder = TilesetBuilder::new()
+        .bounding_volume(BoundingVolume::Box(node.bounds.into()))
+        .geometric_error(node_geometric_error)
+        .content(content_uri, None)
+        .add_children(children);
+    if node.depth == 0 {
+        tileset_builder = tileset_builder.refinement(Refinement::Add);
+    }
+    Ok(tileset_builder.into())
+}
+
+/// Writes the points with the given indices from `buffer` into a .pnts file at `path`. Positions are written relative
+/// to the center of `bounds`
+fn write_node_points(
+    buffer: &dyn PointBuffer,
+    point_indices: &[usize],
+    bounds: &AABB<f64>,
+    path: PathBuf,
+) -> Result<()> {
+    let layout = buffer.point_layout().clone();
+    let mut node_buffer =
+        PerAttributeVecPointStorage::with_capacity(point_indices.len(), layout.clone());
+    node_buffer.resize(point_indices.len());
+    let mut point_data = vec![0; layout.size_of_point_entry() as usize];
+    for (target_index, source_index) in point_indices.iter().enumerate() {
+        buffer.get_raw_point(*source_index, point_data.as_mut_slice());
+        node_buffer.set_raw_point(target_index, point_data.as_slice());
+    }
+
+    let center = bounds.center().coords;
+    node_buffer.transform_attribute(POSITION_3D.name(), |_, position: &mut Vector3<f64>| {
+        *position -= center;
+    });
+
+    let mut writer = PntsWriter::from_write_and_layout(
+        BufWriter::new(
+            File::create(&path)
+                .with_context(|| format!("Could not create file {}", path.display()))?,
+        ),
+        layout,
+    );
+    writer.set_rtc_center(center);
+    writer.reserve(node_buffer.len());
+    writer.write(&node_buffer)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{base::PointReader, tiles3d::PntsReader};
+    use pasture_core::layout::PointType;
+    use pasture_derive::PointType;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[repr(C, packed)]
+    #[derive(Copy, Clone, PartialEq, PointType, Debug)]
+    struct TestPoint(#[pasture(BUILTIN_POSITION_3D)] Vector3<f64>);
+
+    /// Returns a new output directory that no other test (or concurrent run of the same test) writes to
+    fn unique_output_dir(test_name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!(
+            "pasture_{}_{}_{}",
+            test_name,
+            std::process::id(),
+            nanos
+        ))
+    }
+
+    fn count_points_in_tileset(tileset: &Tileset, output_dir: &Path) -> Result<usize> {
+        let uri = &tileset.content.as_ref().unwrap().uri;
+        let reader = PntsReader::<std::io::BufReader<File>>::from_path(output_dir.join(uri))?;
+        let mut count = reader.get_metadata().number_of_points().unwrap();
+        for child in &tileset.children {
+            count += count_points_in_tileset(child, output_dir)?;
+        }
+        Ok(count)
+    }
+
+    #[test]
+    fn test_build_tileset_from_buffer() -> Result<()> {
+        let points_per_axis = 20;
+        let mut buffer = PerAttributeVecPointStorage::new(TestPoint::layout());
+        for z in 0..points_per_axis {
+            for y in 0..points_per_axis {
+                for x in 0..points_per_axis {
+                    buffer.push_point(TestPoint(Vector3::new(
+                        400_000.0 + x as f64,
+                        5_000_000.0 + y as f64,
+                        z as f64,
+                    )));
+                }
+            }
+        }
+
+        let output_dir = unique_output_dir("test_build_tileset_from_buffer");
+        let options = OctreeTilesetOptions {
+            max_points_per_node: 1000,
+            ..Default::default()
+        };
+        let tileset = build_tileset_from_buffer(&buffer, &output_dir, &options)?;
+
+        assert!(output_dir.join("tileset.json").exists());
+        assert!(!tileset.root.children.is_empty());
+        assert!(tileset.root.geometric_error > 0.0);
+        assert_eq!(Some(Refinement::Add), tileset.root.refinement);
+        assert_eq!(
+            buffer.len(),
+            count_points_in_tileset(&tileset.root, &output_dir)?
+        );
+
+        std::fs::remove_dir_all(&output_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_tileset_with_estimated_point_spacing() -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut buffer = PerAttributeVecPointStorage::new(TestPoint::layout());
+        for _ in 0..8000 {
+            buffer.push_point(TestPoint(Vector3::new(
+                rng.gen_range(0.0..20.0),
+                rng.gen_range(0.0..20.0),
+                rng.gen_range(0.0..20.0),
+            )));
+        }
+
+        let output_dir = unique_output_dir("test_build_tileset_with_estimated_point_spacing");
+        let options = OctreeTilesetOptions {
+            max_points_per_node: 100,
+            point_spacing_estimate: Some(PointSpacingEstimate::MeanNearestNeighborDistance),
+            ..Default::default()
+        };
+        let tileset = build_tileset_from_buffer(&buffer, &output_dir, &options)?;
+
+        // The root node contains a sparse subsample of all points, so its error is larger than the error of its
+        // children, which contain denser subsamples of smaller regions
+        let root_error = tileset.root.geometric_error;
+        assert!(root_error > 0.0);
+        assert!(root_error < tileset.geometric_error);
+        assert!(!tileset.root.children.is_empty());
+        for child in &tileset.root.children {
+            assert!(child.geometric_error < root_error);
+        }
+
+        std::fs::remove_dir_all(&output_dir)?;
+        Ok(())
+    }
+}
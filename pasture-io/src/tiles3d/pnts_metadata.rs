@@ -6,13 +6,15 @@ use pasture_core::{
     nalgebra::{Vector3, Vector4},
 };
 
+use super::semantics;
+
 /// Metadata for .pnts files. Contains the PNTS global semantics
 #[derive(Clone, Debug)]
 pub struct PntsMetadata {
     points_length: usize,
     rtc_center: Option<Vector3<f32>>,
-    quantized_volume_offset: Option<Vector3<f32>>,
-    quantized_volume_scale: Option<Vector3<f32>>,
+    quantized_volume_offset: Option<Vector3<f64>>,
+    quantized_volume_scale: Option<Vector3<f64>>,
     constant_rgba: Option<Vector4<u8>>,
     batch_length: Option<usize>,
 }
@@ -21,8 +23,8 @@ impl PntsMetadata {
     pub fn new(
         points_length: usize,
         rtc_center: Option<Vector3<f32>>,
-        quantized_volume_offset: Option<Vector3<f32>>,
-        quantized_volume_scale: Option<Vector3<f32>>,
+        quantized_volume_offset: Option<Vector3<f64>>,
+        quantized_volume_scale: Option<Vector3<f64>>,
         constant_rgba: Option<Vector4<u8>>,
         batch_length: Option<usize>,
     ) -> Self {
@@ -43,6 +45,22 @@ impl PntsMetadata {
     pub fn rtc_center(&self) -> Option<Vector3<f32>> {
         self.rtc_center
     }
+
+    pub fn quantized_volume_offset(&self) -> Option<Vector3<f64>> {
+        self.quantized_volume_offset
+    }
+
+    pub fn quantized_volume_scale(&self) -> Option<Vector3<f64>> {
+        self.quantized_volume_scale
+    }
+
+    pub fn constant_rgba(&self) -> Option<Vector4<u8>> {
+        self.constant_rgba
+    }
+
+    pub fn batch_length(&self) -> Option<usize> {
+        self.batch_length
+    }
 }
 
 impl Metadata for PntsMetadata {
@@ -56,19 +74,19 @@ impl Metadata for PntsMetadata {
 
     fn get_named_field(&self, field_name: &str) -> Option<Box<dyn std::any::Any>> {
         match field_name {
-            "RTC_CENTER" => self
+            semantics::RTC_CENTER => self
                 .rtc_center
                 .map(|v| -> Box<dyn Any> { Box::new(v.clone()) }),
-            "QUANTIZED_VOLUME_OFFSET" => self
+            semantics::QUANTIZED_VOLUME_OFFSET => self
                 .quantized_volume_offset
                 .map(|v| -> Box<dyn Any> { Box::new(v.clone()) }),
-            "QUANTIZED_VOLUME_SCALE" => self
+            semantics::QUANTIZED_VOLUME_SCALE => self
                 .quantized_volume_scale
                 .map(|v| -> Box<dyn Any> { Box::new(v.clone()) }),
-            "CONSTANT_RGBA" => self
+            semantics::CONSTANT_RGBA => self
                 .constant_rgba
                 .map(|v| -> Box<dyn Any> { Box::new(v.clone()) }),
-            "BATCH_LENGTH" => Some(Box::new(self.batch_length)),
+            semantics::BATCH_LENGTH => Some(Box::new(self.batch_length)),
             _ => None,
         }
     }
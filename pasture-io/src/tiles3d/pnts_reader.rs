@@ -15,20 +15,70 @@ use pasture_core::{
     layout::{
         attributes::{COLOR_RGB, NORMAL, POSITION_3D},
         conversion::get_converter_for_attributes,
-        FieldAlignment, PointAttributeDataType, PointAttributeDefinition, PointLayout,
+        FieldAlignment, PointAttributeDataType, PointAttributeDefinition, PointAttributeMember,
+        PointLayout,
     },
     meta::Metadata,
     nalgebra::{clamp, Vector3},
 };
 
-use crate::tiles3d::{deser_feature_table_header, FeatureTableValue, PntsHeader};
+use crate::tiles3d::{
+    component_type_size, deser_batch_table_header, deser_feature_table_header,
+    BatchTableDataReference, BatchTableEntry, FeatureTableValue, PntsHeader,
+};
 use crate::{
     base::{PointReader, SeekToPoint},
-    tiles3d::{attributes::COLOR_RGBA, json_arr_to_vec3f32, json_arr_to_vec4u8},
+    tiles3d::{
+        attributes::COLOR_RGBA, json_arr_to_vec3f32, json_arr_to_vec3f64, json_arr_to_vec4u8,
+        semantics,
+    },
 };
 
 use super::PntsMetadata;
 
+/// Expands a color in the `RGB565` format of 3D Tiles (5 bits red in the most significant bits, 6 bits green, 5 bits blue)
+/// to an 8-bit RGB color
+fn rgb565_to_rgb(packed_color: u16) -> [u8; 3] {
+    let red = ((packed_color >> 11) & 0x1F) as u8;
+    let green = ((packed_color >> 5) & 0x3F) as u8;
+    let blue = (packed_color & 0x1F) as u8;
+    // Replicate the most significant bits into the lower bits, so that the maximum value maps to 255
+    [
+        (red << 3) | (red >> 2),
+        (green << 2) | (green >> 4),
+        (blue << 3) | (blue >> 2),
+    ]
+}
+
+/// Returns the `PointAttributeDataType` for the `componentType` and `type` of the given BatchTable property, or `None` if
+/// there is no matching datatype
+fn batch_table_property_datatype(
+    reference: &BatchTableDataReference,
+) -> Option<PointAttributeDataType> {
+    match (
+        reference.scalar_or_vector_type.as_str(),
+        reference.component_type.as_str(),
+    ) {
+        ("SCALAR", "BYTE") => Some(PointAttributeDataType::I8),
+        ("SCALAR", "UNSIGNED_BYTE") => Some(PointAttributeDataType::U8),
+        ("SCALAR", "SHORT") => Some(PointAttributeDataType::I16),
+        ("SCALAR", "UNSIGNED_SHORT") => Some(PointAttributeDataType::U16),
+        ("SCALAR", "INT") => Some(PointAttributeDataType::I32),
+        ("SCALAR", "UNSIGNED_INT") => Some(PointAttributeDataType::U32),
+        ("SCALAR", "FLOAT") => Some(PointAttributeDataType::F32),
+        ("SCALAR", "DOUBLE") => Some(PointAttributeDataType::F64),
+        ("VEC3", "UNSIGNED_BYTE") => Some(PointAttributeDataType::Vec3u8),
+        ("VEC3", "UNSIGNED_SHORT") => Some(PointAttributeDataType::Vec3u16),
+        ("VEC3", "FLOAT") => Some(PointAttributeDataType::Vec3f32),
+        ("VEC3", "DOUBLE") => Some(PointAttributeDataType::Vec3f64),
+        ("VEC4", "UNSIGNED_BYTE") => Some(PointAttributeDataType::Vec4u8),
+        ("VEC4", "UNSIGNED_SHORT") => Some(PointAttributeDataType::Vec4u16),
+        ("VEC4", "FLOAT") => Some(PointAttributeDataType::Vec4f32),
+        ("VEC4", "DOUBLE") => Some(PointAttributeDataType::Vec4f64),
+        _ => None,
+    }
+}
+
 /// Defines how the `PntsReader` reads positions if the `RTC_CENTER` point semantic is present
 #[derive(Copy, Clone, Debug)]
 pub enum PntsReadPositionsMode {
@@ -41,13 +91,59 @@ pub enum PntsReadPositionsMode {
 }
 
 /// A reader for points in the 3D Tiles PNTS format
+///
+/// Per-point properties in the binary body of the BatchTable are read as custom attributes with the name of the property
+/// and a datatype matching its `componentType` and `type`. BatchTable properties stored as JSON arrays, as well as
+/// per-batch properties (if the FeatureTable defines `BATCH_LENGTH`), are not supported and are ignored. The names of
+/// ignored per-point properties are available through
+/// [skipped_batch_table_properties](PntsReader::skipped_batch_table_properties). If the BatchTable is invalid, all of its
+/// properties are ignored, see [batch_table_error](PntsReader::batch_table_error)
 pub struct PntsReader<R: BufRead + Seek> {
     reader: R,
     metadata: PntsMetadata,
     layout: PointLayout,
     current_point_index: usize,
     attribute_offsets: HashMap<String, u64>,
+    attribute_strides: HashMap<String, u64>,
     read_positions_mode: PntsReadPositionsMode,
+    positions_quantized: bool,
+    colors_rgb565: bool,
+    skipped_batch_table_properties: Vec<String>,
+    batch_table_error: Option<anyhow::Error>,
+}
+
+/// Describes where the point attributes are located within the FeatureTable binary body of a .pnts file
+#[derive(Default)]
+struct FeatureTableBinaryLayout {
+    layout: PointLayout,
+    /// Byte offset of the first value of each attribute within the FeatureTable binary body
+    attribute_offsets: HashMap<String, u64>,
+    /// Size in bytes of a single value of each attribute within the FeatureTable binary body
+    attribute_strides: HashMap<String, u64>,
+    /// Are the positions stored quantized (`POSITION_QUANTIZED`)?
+    positions_quantized: bool,
+    /// Are the colors stored as `RGB565`?
+    colors_rgb565: bool,
+}
+
+/// Describes the per-point properties within the BatchTable binary body of a .pnts file
+#[derive(Default)]
+struct BatchTableBinaryLayout {
+    /// Attribute, byte offset of its first value within the file and stride of each per-point property
+    attributes: Vec<(PointAttributeDefinition, u64, u64)>,
+    /// Names of the properties that can't be read as point attributes
+    skipped_properties: Vec<String>,
+}
+
+impl FeatureTableBinaryLayout {
+    fn add_attribute(&mut self, attribute: PointAttributeDefinition, offset: u64, stride: u64) {
+        self.attribute_offsets
+            .insert(attribute.name().to_owned(), offset);
+        self.attribute_strides
+            .insert(attribute.name().to_owned(), stride);
+        self.layout
+            .add_attribute(attribute, FieldAlignment::Packed(1));
+    }
 }
 
 impl<R: BufRead + Seek> PntsReader<R> {
@@ -76,14 +172,32 @@ impl<R: BufRead + Seek> PntsReader<R> {
             header.feature_table_json_byte_length as usize,
             position_after_header,
         )?;
-        // TODO BatchTable support
 
         // The following functions mutate the feature table header HashMap and remove the entries that
         // are relevant. This is done because both point semantics and global semantics are stored in the
         // same header, so this makes parsing easier
-        let (layout, mut attribute_offsets) =
-            Self::layout_from_feature_table_header(&mut feature_table_header)?;
+        let FeatureTableBinaryLayout {
+            mut layout,
+            mut attribute_offsets,
+            mut attribute_strides,
+            positions_quantized,
+            colors_rgb565,
+        } = Self::layout_from_feature_table_header(&mut feature_table_header)?;
         let metadata = Self::metadata_from_feature_table_header(&mut feature_table_header)?;
+        // Per-point colors take precedence over CONSTANT_RGBA. If there are none, the constant color is expanded to
+        // a per-point color attribute during reading
+        if metadata.constant_rgba().is_some()
+            && !layout.has_attribute_with_name(COLOR_RGB.name())
+            && !layout.has_attribute_with_name(COLOR_RGBA.name())
+        {
+            layout.add_attribute(COLOR_RGBA, FieldAlignment::Packed(1));
+        }
+        if positions_quantized
+            && (metadata.quantized_volume_offset().is_none()
+                || metadata.quantized_volume_scale().is_none())
+        {
+            bail!("Found PNTS attribute POSITION_QUANTIZED but QUANTIZED_VOLUME_OFFSET or QUANTIZED_VOLUME_SCALE is missing in the feature table header!");
+        }
 
         // TODO Log all parameters that could not be parsed. This requires logging support for pasture
 
@@ -93,16 +207,140 @@ impl<R: BufRead + Seek> PntsReader<R> {
             *offset += feature_table_binary_offset;
         }
 
+        let mut skipped_batch_table_properties = vec![];
+        let mut batch_table_error = None;
+        // Properties in the BatchTable are per-point only if there is no BATCH_LENGTH. Otherwise they are per-batch and
+        // have to be looked up through the BATCH_ID of each point, which is not supported yet
+        if header.batch_table_json_byte_length > 0 && metadata.batch_length().is_none() {
+            // The BatchTable is optional, so if it can't be parsed, the points are read without its properties
+            match Self::batch_table_layout_from_read(&mut read, &header, &metadata, &layout) {
+                Ok(batch_table_layout) => {
+                    for (attribute, offset, stride) in batch_table_layout.attributes {
+                        attribute_offsets.insert(attribute.name().to_owned(), offset);
+                        attribute_strides.insert(attribute.name().to_owned(), stride);
+                        layout.add_attribute(attribute, FieldAlignment::Packed(1));
+                    }
+                    skipped_batch_table_properties = batch_table_layout.skipped_properties;
+                }
+                Err(e) => batch_table_error = Some(e),
+            }
+        }
+
         Ok(Self {
             reader: read,
             metadata,
             layout,
             current_point_index: 0,
             attribute_offsets,
+            attribute_strides,
             read_positions_mode: PntsReadPositionsMode::Absolute,
+            positions_quantized,
+            colors_rgb565,
+            skipped_batch_table_properties,
+            batch_table_error,
         })
     }
 
+    /// Parses the BatchTable JSON header and determines the per-point properties within the BatchTable binary body. The
+    /// BatchTable starts right after the FeatureTable, whose length is given by the byte lengths in `header`
+    fn batch_table_layout_from_read(
+        read: &mut R,
+        header: &PntsHeader,
+        metadata: &PntsMetadata,
+        feature_table_layout: &PointLayout,
+    ) -> Result<BatchTableBinaryLayout> {
+        let batch_table_offset = PntsHeader::BYTE_LENGTH as u64
+            + header.feature_table_json_byte_length as u64
+            + header.feature_table_binary_byte_length as u64;
+        read.seek(SeekFrom::Start(batch_table_offset))?;
+        let batch_table_header = deser_batch_table_header(
+            &mut *read,
+            header.batch_table_json_byte_length as usize,
+            batch_table_offset as usize,
+        )?;
+        let batch_table_binary_offset =
+            batch_table_offset + header.batch_table_json_byte_length as u64;
+
+        let mut batch_table_layout = BatchTableBinaryLayout::default();
+        let mut property_names = batch_table_header.keys().collect::<Vec<_>>();
+        property_names.sort();
+        for property_name in property_names {
+            let reference = match &batch_table_header[property_name] {
+                BatchTableEntry::DataReference(reference) => reference,
+                // Properties stored as JSON arrays are not supported
+                BatchTableEntry::ArrayData(_) => {
+                    batch_table_layout
+                        .skipped_properties
+                        .push(property_name.clone());
+                    continue;
+                }
+            };
+            let datatype = match batch_table_property_datatype(reference) {
+                Some(datatype) => datatype,
+                None => {
+                    batch_table_layout
+                        .skipped_properties
+                        .push(property_name.clone());
+                    continue;
+                }
+            };
+            if feature_table_layout.has_attribute_with_name(property_name) {
+                bail!(
+                    "BatchTable property {} has the same name as a point semantic in the FeatureTable",
+                    property_name
+                );
+            }
+
+            let stride = datatype.size();
+            let end_of_property =
+                reference.byte_offset as u64 + stride * metadata.points_length() as u64;
+            if end_of_property > header.batch_table_binary_byte_length as u64 {
+                bail!(
+                    "BatchTable property {} exceeds the BatchTable binary body",
+                    property_name
+                );
+            }
+
+            batch_table_layout.attributes.push((
+                PointAttributeDefinition::custom_dynamic(property_name, datatype),
+                batch_table_binary_offset + reference.byte_offset as u64,
+                stride,
+            ));
+        }
+        Ok(batch_table_layout)
+    }
+
+    /// Returns the names of all per-point BatchTable properties that this `PntsReader` can't read, in alphabetical
+    /// order. These are properties stored as JSON arrays, and properties with a combination of `componentType` and
+    /// `type` that has no matching `PointAttributeDataType`. They are not part of the default `PointLayout`
+    pub fn skipped_batch_table_properties(&self) -> &[String] {
+        &self.skipped_batch_table_properties
+    }
+
+    /// Returns the reason why the BatchTable could not be read, if it is present but invalid. In this case, none of its
+    /// properties are part of the default `PointLayout`, but the points can still be read
+    pub fn batch_table_error(&self) -> Option<&anyhow::Error> {
+        self.batch_table_error.as_ref()
+    }
+
+    /// Reads only the `PntsHeader` from the given `read`, without parsing the FeatureTable or BatchTable. This is much
+    /// cheaper than creating a `PntsReader` if only the sizes stored in the header are of interest.
+    ///
+    /// # Errors
+    ///
+    /// If the header can't be read from `read`, or if the magic bytes or the version in the header are invalid
+    pub fn read_header_only(mut read: R) -> Result<PntsHeader> {
+        let mut header_bytes = [0; PntsHeader::BYTE_LENGTH];
+        read.read_exact(&mut header_bytes)
+            .context("Could not read PNTS header from reader")?;
+        // PNTS is little-endian, this is the default of bincode
+        let header: PntsHeader =
+            bincode::deserialize(&header_bytes).context("Could not deserialize PNTS header")?;
+        header.verify_magic()?;
+        header.verify_version()?;
+        Ok(header)
+    }
+
     /// Sets the `PntsReadPositionsMode` for this `PntsReader`
     pub fn set_read_positions_mode(&mut self, read_mode: PntsReadPositionsMode) {
         self.read_positions_mode = read_mode;
@@ -120,74 +358,127 @@ impl<R: BufRead + Seek> PntsReader<R> {
     /// that they are defined in [here](https://github.com/CesiumGS/3d-tiles/blob/master/specification/TileFormats/PointCloud/README.md#semantics).
     fn layout_from_feature_table_header(
         header: &mut HashMap<String, FeatureTableValue>,
-    ) -> Result<(PointLayout, HashMap<String, u64>)> {
+    ) -> Result<FeatureTableBinaryLayout> {
         // 3D Tiles .pnts has very few supported point attributes, so we can just enumerate them by hand
-        let mut layout: PointLayout = Default::default();
-        let mut attribute_offsets = HashMap::new();
-        if header.contains_key("POSITION") {
-            let pos_attribute = &header["POSITION"];
-            match pos_attribute {
-                FeatureTableValue::DataReference(reference) => {
-                    attribute_offsets.insert(POSITION_3D.name().to_owned(), reference.byte_offset as u64);
-                    layout.add_attribute(POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32), FieldAlignment::Packed(1));
-                },
-                _ => bail!("Found PNTS attribute POSITION ({:?}) but it was not a reference to the feature table binary!", pos_attribute),
-            }
-            header.remove("POSITION");
+        let mut binary_layout = FeatureTableBinaryLayout::default();
+        if let Some((offset, stride)) =
+            Self::take_binary_reference(header, semantics::POSITION, "FLOAT", 3)?
+        {
+            binary_layout.add_attribute(
+                POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+                offset,
+                stride,
+            );
         }
 
-        // TODO Quantized positions, which probably require an option to de-quantize during reading (similar to LAS/LAZ)
-
-        if header.contains_key("RGBA") {
-            let color_attribute = &header["RGBA"];
-            match color_attribute {
-                FeatureTableValue::DataReference(reference) => {
-                    attribute_offsets.insert(COLOR_RGBA.name().to_owned(), reference.byte_offset as u64);
-                    layout.add_attribute(COLOR_RGBA, FieldAlignment::Packed(1));
-                },
-                _ => bail!("Found PNTS attribute RGBA ({:?}) but it was not a reference to the feature table binary!", color_attribute),
+        // Quantized positions are de-quantized during reading, so they show up as regular positions. As the quantized volume
+        // can be large (e.g. in ECEF coordinates), we use Vec3f64 here instead of Vec3f32 as for regular PNTS positions.
+        // If both POSITION and POSITION_QUANTIZED are present, POSITION takes precedence as per the 3D Tiles spec
+        if let Some((offset, stride)) =
+            Self::take_binary_reference(header, semantics::POSITION_QUANTIZED, "UNSIGNED_SHORT", 3)?
+        {
+            if !binary_layout
+                .layout
+                .has_attribute_with_name(POSITION_3D.name())
+            {
+                binary_layout.add_attribute(POSITION_3D, offset, stride);
+                binary_layout.positions_quantized = true;
             }
-            header.remove("RGBA");
         }
 
-        if header.contains_key("RGB") {
-            let color_attribute = &header["RGB"];
-            match color_attribute {
-                FeatureTableValue::DataReference(reference) => {
-                    attribute_offsets.insert(COLOR_RGB.name().to_owned(), reference.byte_offset as u64);
-                    layout.add_attribute(COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8), FieldAlignment::Packed(1));
-                },
-                _ => bail!("Found PNTS attribute RGB ({:?}) but it was not a reference to the feature table binary!", color_attribute),
-            }
-            header.remove("RGB");
+        if let Some((offset, stride)) =
+            Self::take_binary_reference(header, semantics::RGBA, "UNSIGNED_BYTE", 4)?
+        {
+            binary_layout.add_attribute(COLOR_RGBA, offset, stride);
         }
 
-        // TOOD RGB565
+        if let Some((offset, stride)) =
+            Self::take_binary_reference(header, semantics::RGB, "UNSIGNED_BYTE", 3)?
+        {
+            binary_layout.add_attribute(
+                COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+                offset,
+                stride,
+            );
+        }
 
-        if header.contains_key("NORMAL") {
-            let normal_attribute = &header["NORMAL"];
-            match normal_attribute {
-                FeatureTableValue::DataReference(reference) => {
-                    attribute_offsets.insert(NORMAL.name().to_owned(), reference.byte_offset as u64);
-                    layout.add_attribute(NORMAL ,FieldAlignment::Packed(1));
-                },
-                _ => bail!("Found PNTS attribute NORMAL ({:?}) but it was not a reference to the feature table binary!", normal_attribute),
+        // RGB565 colors are expanded to regular RGB colors during reading. RGBA and RGB take precedence over RGB565 as per
+        // the 3D Tiles spec
+        if let Some((offset, stride)) =
+            Self::take_binary_reference(header, semantics::RGB565, "UNSIGNED_SHORT", 1)?
+        {
+            if !binary_layout
+                .layout
+                .has_attribute_with_name(COLOR_RGB.name())
+                && !binary_layout
+                    .layout
+                    .has_attribute_with_name(COLOR_RGBA.name())
+            {
+                binary_layout.add_attribute(
+                    COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+                    offset,
+                    stride,
+                );
+                binary_layout.colors_rgb565 = true;
             }
-            header.remove("NORMAL");
+        }
+
+        if let Some((offset, stride)) =
+            Self::take_binary_reference(header, semantics::NORMAL, "FLOAT", 3)?
+        {
+            binary_layout.add_attribute(NORMAL, offset, stride);
         }
 
         // Normal oct16p
 
         // Batch ID
 
-        Ok((layout, attribute_offsets))
+        Ok(binary_layout)
+    }
+
+    /// Removes the point semantic `semantic` from the FeatureTable `header` and returns its byte offset within the FeatureTable
+    /// binary body together with the size in bytes of a single value of the semantic. The size is determined from the
+    /// `componentType` of the semantic, or from the `default_component_type` of the semantic as defined by the 3D Tiles spec if
+    /// the `componentType` is missing. Returns `None` if `semantic` is not part of `header`.
+    fn take_binary_reference(
+        header: &mut HashMap<String, FeatureTableValue>,
+        semantic: &str,
+        default_component_type: &str,
+        num_components: usize,
+    ) -> Result<Option<(u64, u64)>> {
+        let reference = match header.remove(semantic) {
+            None => return Ok(None),
+            Some(FeatureTableValue::DataReference(reference)) => reference,
+            Some(other) => bail!(
+                "Found PNTS attribute {} ({:?}) but it was not a reference to the feature table binary!",
+                semantic,
+                other
+            ),
+        };
+
+        let component_type = reference
+            .component_type
+            .as_deref()
+            .unwrap_or(default_component_type);
+        // The values of all point semantics are decoded using their default component type, so a different component type
+        // would lead to garbage values
+        if component_type != default_component_type {
+            bail!(
+                "Unsupported componentType {} of PNTS attribute {} (expected {})",
+                component_type,
+                semantic,
+                default_component_type
+            );
+        }
+        let stride = component_type_size(component_type)? * num_components;
+        Ok(Some((reference.byte_offset as u64, stride as u64)))
     }
 
     fn metadata_from_feature_table_header(
         header: &mut HashMap<String, FeatureTableValue>,
     ) -> Result<PntsMetadata> {
         let num_points = header
-            .get("POINTS_LENGTH")
+            .get(semantics::POINTS_LENGTH)
             .map(|entry| match entry {
                 FeatureTableValue::SingleValue(v) => v
                     .as_u64()
@@ -200,7 +491,7 @@ impl<R: BufRead + Seek> PntsReader<R> {
             ))??;
 
         let rtc_center = header
-            .get("RTC_CENTER")
+            .get(semantics::RTC_CENTER)
             .map(|entry| match entry {
                 FeatureTableValue::Array(array) => json_arr_to_vec3f32(&array),
                 _ => Err(anyhow!("RTC_CENTER value was no array entry")),
@@ -208,23 +499,23 @@ impl<R: BufRead + Seek> PntsReader<R> {
             .transpose()?;
 
         let quantized_volume_offset = header
-            .get("QUANTIZED_VOLUME_OFFSET")
+            .get(semantics::QUANTIZED_VOLUME_OFFSET)
             .map(|entry| match entry {
-                FeatureTableValue::Array(array) => json_arr_to_vec3f32(&array),
+                FeatureTableValue::Array(array) => json_arr_to_vec3f64(&array),
                 _ => Err(anyhow!("QUANTIZED_VOLUME_OFFSET value was no array entry")),
             })
             .transpose()?;
 
         let quantized_volume_scale = header
-            .get("QUANTIZED_VOLUME_SCALE")
+            .get(semantics::QUANTIZED_VOLUME_SCALE)
             .map(|entry| match entry {
-                FeatureTableValue::Array(array) => json_arr_to_vec3f32(&array),
+                FeatureTableValue::Array(array) => json_arr_to_vec3f64(&array),
                 _ => Err(anyhow!("QUANTIZED_VOLUME_SCALE value was no array entry")),
             })
             .transpose()?;
 
         let constant_rgba = header
-            .get("CONSTANT_RGBA")
+            .get(semantics::CONSTANT_RGBA)
             .map(|entry| match entry {
                 FeatureTableValue::Array(array) => json_arr_to_vec4u8(&array),
                 _ => Err(anyhow!("CONSTANT_RGBA value was no array entry")),
@@ -232,7 +523,7 @@ impl<R: BufRead + Seek> PntsReader<R> {
             .transpose()?;
 
         let batch_length = header
-            .get("BATCH_LENGTH")
+            .get(semantics::BATCH_LENGTH)
             .map(|entry| match entry {
                 FeatureTableValue::SingleValue(v) => v
                     .as_u64()
@@ -252,6 +543,61 @@ impl<R: BufRead + Seek> PntsReader<R> {
         ))
     }
 
+    /// Reads the data of `count` consecutive values of `attribute`, starting at the current point. The data is returned in
+    /// the datatype of `attribute`, quantized positions are de-quantized here
+    fn read_attribute_data(
+        &mut self,
+        attribute: &PointAttributeMember,
+        count: usize,
+    ) -> Result<Vec<u8>> {
+        if attribute.name() == COLOR_RGBA.name()
+            && !self.attribute_offsets.contains_key(attribute.name())
+        {
+            // The color is stored as CONSTANT_RGBA for all points
+            let constant_rgba = self.metadata.constant_rgba().unwrap();
+            return Ok(constant_rgba.as_slice().repeat(count));
+        }
+
+        let offset_to_first_point_of_attribute =
+            *self.attribute_offsets.get(attribute.name()).unwrap();
+        let stride = *self.attribute_strides.get(attribute.name()).unwrap();
+        self.reader.seek(SeekFrom::Start(
+            offset_to_first_point_of_attribute + (self.current_point_index as u64 * stride),
+        ))?;
+        let mut raw_data = vec![0; count * stride as usize];
+        self.reader.read_exact(raw_data.as_mut_slice())?;
+
+        if self.positions_quantized && attribute.name() == POSITION_3D.name() {
+            // POSITION_QUANTIZED is stored as three u16 values per point
+            let volume_offset = self.metadata.quantized_volume_offset().unwrap();
+            let volume_scale = self.metadata.quantized_volume_scale().unwrap();
+            let mut data = Vec::with_capacity(count * attribute.size() as usize);
+            for quantized_position in raw_data.chunks_exact(stride as usize) {
+                for component in 0..3 {
+                    let quantized_value = u16::from_le_bytes([
+                        quantized_position[2 * component],
+                        quantized_position[2 * component + 1],
+                    ]);
+                    let value = volume_offset[component]
+                        + (quantized_value as f64 * volume_scale[component] / 65535.0);
+                    data.extend_from_slice(&value.to_ne_bytes());
+                }
+            }
+            return Ok(data);
+        }
+
+        if self.colors_rgb565 && attribute.name() == COLOR_RGB.name() {
+            return Ok(raw_data
+                .chunks_exact(stride as usize)
+                .flat_map(|packed_color| {
+                    rgb565_to_rgb(u16::from_le_bytes([packed_color[0], packed_color[1]]))
+                })
+                .collect());
+        }
+
+        Ok(raw_data)
+    }
+
     fn apply_rtc_center_offset(&self, point_buffer: &mut dyn PointBufferWriteable) {
         let maybe_position = point_buffer
             .point_layout()
@@ -290,24 +636,19 @@ impl<R: BufRead + Seek> PointReader for PntsReader<R> {
     fn read(&mut self, count: usize) -> Result<Box<dyn PointBuffer>> {
         let remaining_points = self.metadata.points_length() - self.current_point_index;
         let num_to_read = usize::min(remaining_points, count);
-        if num_to_read == 0 {
+        // Reading zero points is valid (e.g. for empty files), it is only an error if points were requested
+        if num_to_read == 0 && count > 0 {
             bail!("No points remaining in PNTS file")
         }
 
-        let mut buffer = PerAttributeVecPointStorage::new(self.layout.clone());
+        let layout = self.layout.clone();
+        let mut buffer = PerAttributeVecPointStorage::new(layout.clone());
         buffer.resize(num_to_read);
-        for attribute in self.layout.attributes() {
-            let attribute_stride = attribute.size();
-            let offset_to_first_point_of_attribute =
-                *self.attribute_offsets.get(attribute.name()).unwrap();
-            let offset_to_current_point_of_attribute = offset_to_first_point_of_attribute
-                + (self.current_point_index as u64 * attribute_stride);
-
-            self.reader
-                .seek(SeekFrom::Start(offset_to_current_point_of_attribute))?;
-            let target_buffer =
-                buffer.get_raw_attribute_range_mut(0..num_to_read, &attribute.into());
-            self.reader.read_exact(target_buffer)?;
+        for attribute in layout.attributes() {
+            let data = self.read_attribute_data(attribute, num_to_read)?;
+            buffer
+                .get_raw_attribute_range_mut(0..num_to_read, &attribute.into())
+                .copy_from_slice(data.as_slice());
         }
 
         self.current_point_index += num_to_read;
@@ -326,52 +667,38 @@ impl<R: BufRead + Seek> PointReader for PntsReader<R> {
     ) -> Result<usize> {
         let remaining_points = self.metadata.points_length() - self.current_point_index;
         let num_to_read = usize::min(remaining_points, count);
-        if num_to_read == 0 {
+        // Reading zero points is valid (e.g. for empty files), it is only an error if points were requested
+        if num_to_read == 0 && count > 0 {
             bail!("No points remaining in PNTS file")
         }
 
         let target_layout = point_buffer.point_layout().clone();
         point_buffer.resize(num_to_read);
-        for attribute in self.layout.attributes() {
+        let layout = self.layout.clone();
+        for attribute in layout.attributes() {
             // Try to read this attribute only if it exists in the target buffer's PointLayout
             if let Some(target_attribute) = target_layout.get_attribute_by_name(attribute.name()) {
-                let attribute_stride = attribute.size();
-                let offset_to_first_point_of_attribute =
-                    *self.attribute_offsets.get(attribute.name()).unwrap();
-                let offset_to_current_point_of_attribute = offset_to_first_point_of_attribute
-                    + (self.current_point_index as u64 * attribute_stride);
-
-                self.reader
-                    .seek(SeekFrom::Start(offset_to_current_point_of_attribute))?;
+                let data = self.read_attribute_data(attribute, num_to_read)?;
+                let target_attribute_def: PointAttributeDefinition = target_attribute.into();
 
                 // Maybe we have to convert the datatype?
                 let converter =
-                    get_converter_for_attributes(&attribute.into(), &target_attribute.into());
-                if let Some(conversion_fn) = converter {
-                    let mut src_buf: Vec<u8> = vec![0; attribute.size() as usize];
-                    let mut dst_buf: Vec<u8> = vec![0; attribute.size() as usize];
-                    let target_attribute_def: PointAttributeDefinition = target_attribute.into();
-                    for point_index in 0..num_to_read {
-                        self.reader.read_exact(src_buf.as_mut_slice())?;
+                    get_converter_for_attributes(&attribute.into(), &target_attribute_def);
+                let mut dst_buf: Vec<u8> = vec![0; target_attribute.size() as usize];
+                for (point_index, src_buf) in
+                    data.chunks_exact(attribute.size() as usize).enumerate()
+                {
+                    if let Some(conversion_fn) = converter {
                         unsafe {
-                            conversion_fn(src_buf.as_slice(), dst_buf.as_mut_slice());
+                            conversion_fn(src_buf, dst_buf.as_mut_slice());
                         }
                         point_buffer.set_raw_attribute(
                             point_index,
                             &target_attribute_def,
                             dst_buf.as_slice(),
                         );
-                    }
-                } else {
-                    let mut buf: Vec<u8> = vec![0; attribute.size() as usize];
-                    let target_attribute_def: PointAttributeDefinition = target_attribute.into();
-                    for point_index in 0..num_to_read {
-                        self.reader.read_exact(buf.as_mut_slice())?;
-                        point_buffer.set_raw_attribute(
-                            point_index,
-                            &target_attribute_def,
-                            buf.as_slice(),
-                        );
+                    } else {
+                        point_buffer.set_raw_attribute(point_index, &target_attribute_def, src_buf);
                     }
                 }
             }
@@ -416,18 +743,83 @@ impl<R: BufRead + Seek> SeekToPoint for PntsReader<R> {
 
 #[cfg(test)]
 mod tests {
-    use std::io::Cursor;
+    use std::{io::Cursor, path::PathBuf};
 
-    use crate::{base::PointWriter, tiles3d::PntsWriter};
+    use crate::{
+        base::PointWriter,
+        tiles3d::{
+            ser_batch_table_header, ser_feature_table_header, BatchTableHeader,
+            FeatureTableDataReference, FeatureTableHeader, PntsWriter,
+        },
+    };
+    use serde_json::json;
 
     use super::*;
-    use pasture_core::{containers::PointBufferExt, layout::PointType};
+    use pasture_core::{containers::PointBufferExt, layout::PointType, nalgebra::Vector4};
     use pasture_derive::PointType;
 
     #[repr(C, packed)]
     #[derive(Copy, Clone, PartialEq, PointType, Debug)]
     struct TestPoint(#[pasture(BUILTIN_POSITION_3D)] Vector3<f32>);
 
+    #[test]
+    fn test_pnts_reader_read_header_only() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("resources/test/points.pnts");
+        let file = BufReader::new(File::open(test_file_path)?);
+        let header = PntsReader::read_header_only(file)?;
+        assert_eq!(PntsHeader::new(1, 264332, 80, 120000, 224, 144000), header);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_batch_table_from_file() -> Result<()> {
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("resources/test/points.pnts");
+        let mut reader = PntsReader::<BufReader<File>>::from_path(test_file_path)?;
+        assert!(reader.batch_table_error().is_none());
+
+        let layout = reader.get_default_point_layout().clone();
+        assert!(
+            layout.has_attribute(&PointAttributeDefinition::custom_dynamic(
+                "temperature",
+                PointAttributeDataType::F32
+            ))
+        );
+        assert!(
+            layout.has_attribute(&PointAttributeDefinition::custom_dynamic(
+                "secondaryColor",
+                PointAttributeDataType::Vec3f32
+            ))
+        );
+        assert!(
+            layout.has_attribute(&PointAttributeDefinition::custom_dynamic(
+                "id",
+                PointAttributeDataType::U16
+            ))
+        );
+        assert_eq!(8000, reader.read(8000)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_read_header_only_invalid() {
+        let valid_header = PntsHeader::new(1, 28, 0, 0, 0, 0);
+
+        let mut wrong_magic = valid_header.clone();
+        wrong_magic.magic = [b'b', b'3', b'd', b'm'];
+        let bytes = bincode::serialize(&wrong_magic).unwrap();
+        assert!(PntsReader::read_header_only(Cursor::new(bytes.as_slice())).is_err());
+
+        let mut wrong_version = valid_header.clone();
+        wrong_version.version = 2;
+        let bytes = bincode::serialize(&wrong_version).unwrap();
+        assert!(PntsReader::read_header_only(Cursor::new(bytes.as_slice())).is_err());
+
+        let bytes = bincode::serialize(&valid_header).unwrap();
+        assert!(PntsReader::read_header_only(Cursor::new(&bytes[..10])).is_err());
+    }
+
     #[test]
     fn test_pnts_reader_read_modes() {
         let test_points = vec![
@@ -476,4 +868,496 @@ mod tests {
             assert_eq!(test_points, actual_points);
         }
     }
+
+    /// Creates a .pnts file in memory from the given FeatureTable header and binary body
+    fn make_pnts(
+        feature_table_header: &FeatureTableHeader,
+        feature_table_body: Vec<u8>,
+    ) -> Vec<u8> {
+        make_pnts_with_batch_table(
+            feature_table_header,
+            feature_table_body,
+            &BatchTableHeader::new(),
+            vec![],
+        )
+    }
+
+    /// Creates a .pnts file in memory from the given FeatureTable and BatchTable headers and binary bodies. The
+    /// BatchTable is omitted if `batch_table_header` is empty
+    fn make_pnts_with_batch_table(
+        feature_table_header: &FeatureTableHeader,
+        mut feature_table_body: Vec<u8>,
+        batch_table_header: &BatchTableHeader,
+        batch_table_body: Vec<u8>,
+    ) -> Vec<u8> {
+        let mut feature_table_json = vec![];
+        ser_feature_table_header(
+            Cursor::new(&mut feature_table_json),
+            feature_table_header,
+            PntsHeader::BYTE_LENGTH,
+        )
+        .unwrap();
+
+        while (PntsHeader::BYTE_LENGTH + feature_table_json.len() + feature_table_body.len()) % 8
+            != 0
+        {
+            feature_table_body.push(0);
+        }
+
+        let batch_table_start =
+            PntsHeader::BYTE_LENGTH + feature_table_json.len() + feature_table_body.len();
+        let mut batch_table_json = vec![];
+        if !batch_table_header.is_empty() {
+            ser_batch_table_header(
+                Cursor::new(&mut batch_table_json),
+                batch_table_header,
+                batch_table_start,
+            )
+            .unwrap();
+        }
+
+        let header = PntsHeader::new(
+            1,
+            (batch_table_start + batch_table_json.len() + batch_table_body.len()) as u32,
+            feature_table_json.len() as u32,
+            feature_table_body.len() as u32,
+            batch_table_json.len() as u32,
+            batch_table_body.len() as u32,
+        );
+
+        let mut pnts = bincode::serialize(&header).unwrap();
+        pnts.extend_from_slice(&feature_table_json);
+        pnts.extend_from_slice(&feature_table_body);
+        pnts.extend_from_slice(&batch_table_json);
+        pnts.extend_from_slice(&batch_table_body);
+        pnts
+    }
+
+    /// Creates a .pnts file in memory that stores the given `quantized_positions` in the `POSITION_QUANTIZED` semantic,
+    /// similar to what the Cesium tiler produces
+    fn make_quantized_pnts(
+        quantized_positions: &[[u16; 3]],
+        volume_offset: [f64; 3],
+        volume_scale: [f64; 3],
+        component_type: Option<&str>,
+    ) -> Vec<u8> {
+        let mut feature_table_header = FeatureTableHeader::new();
+        feature_table_header.insert(
+            semantics::POINTS_LENGTH.into(),
+            FeatureTableValue::SingleValue(json!(quantized_positions.len())),
+        );
+        feature_table_header.insert(
+            semantics::POSITION_QUANTIZED.into(),
+            FeatureTableValue::DataReference(FeatureTableDataReference {
+                byte_offset: 0,
+                component_type: component_type.map(|c| c.to_owned()),
+            }),
+        );
+        feature_table_header.insert(
+            semantics::QUANTIZED_VOLUME_OFFSET.into(),
+            FeatureTableValue::Array(volume_offset.iter().map(|v| json!(v)).collect()),
+        );
+        feature_table_header.insert(
+            semantics::QUANTIZED_VOLUME_SCALE.into(),
+            FeatureTableValue::Array(volume_scale.iter().map(|v| json!(v)).collect()),
+        );
+
+        let feature_table_body = quantized_positions
+            .iter()
+            .flat_map(|position| position.iter().flat_map(|v| v.to_le_bytes()))
+            .collect::<Vec<_>>();
+        make_pnts(&feature_table_header, feature_table_body)
+    }
+
+    #[test]
+    fn test_pnts_reader_quantized_positions() -> Result<()> {
+        let pnts = make_quantized_pnts(
+            &[[0, 0, 0], [65535, 65535, 65535], [0, 32768, 65535]],
+            [1000.0, 2000.0, -3000.0],
+            [100.0, 200.0, 300.0],
+            None,
+        );
+        let expected_positions = vec![
+            Vector3::new(1000.0, 2000.0, -3000.0),
+            Vector3::new(1100.0, 2200.0, -2700.0),
+            Vector3::new(1000.0, 2000.0 + (32768.0 * 200.0 / 65535.0), -2700.0),
+        ];
+
+        let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+        assert_eq!(
+            PointAttributeDataType::Vec3f64,
+            reader
+                .get_default_point_layout()
+                .get_attribute_by_name(POSITION_3D.name())
+                .unwrap()
+                .datatype()
+        );
+
+        let points = reader.read(3)?;
+        let positions = points
+            .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+            .collect::<Vec<_>>();
+        for (expected, actual) in expected_positions.iter().zip(positions.iter()) {
+            assert!((expected - actual).norm() < 1e-9);
+        }
+
+        // Reading into a Vec3f32 position attribute converts the de-quantized positions
+        reader.seek_point(SeekFrom::Start(1))?;
+        let mut layout = PointLayout::default();
+        layout.add_attribute(
+            POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+            FieldAlignment::Default,
+        );
+        let mut buffer = PerAttributeVecPointStorage::new(layout);
+        assert_eq!(2, reader.read_into(&mut buffer, 2)?);
+        let positions_f32 = buffer
+            .iter_attribute::<Vector3<f32>>(
+                &POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(Vector3::new(1100.0_f32, 2200.0, -2700.0), positions_f32[0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_quantized_positions_without_volume() {
+        let mut pnts = make_quantized_pnts(&[[0, 0, 0]], [0.0; 3], [1.0; 3], None);
+        // Rename QUANTIZED_VOLUME_SCALE so that it is missing from the feature table
+        let needle = semantics::QUANTIZED_VOLUME_SCALE.as_bytes();
+        let position = pnts
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .unwrap();
+        pnts[position] = b'X';
+
+        assert!(PntsReader::from_read(Cursor::new(pnts.as_slice())).is_err());
+    }
+
+    #[test]
+    fn test_pnts_reader_quantized_positions_with_component_type() -> Result<()> {
+        let pnts = make_quantized_pnts(
+            &[[0, 0, 0], [65535, 32768, 0]],
+            [0.0, 0.0, 0.0],
+            [65535.0, 65535.0, 65535.0],
+            Some("UNSIGNED_SHORT"),
+        );
+        let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+        let points = reader.read(2)?;
+        let positions = points
+            .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(65535.0, 32768.0, 0.0)
+            ],
+            positions
+        );
+
+        let pnts = make_quantized_pnts(&[[0, 0, 0]], [0.0; 3], [1.0; 3], Some("FLOAT"));
+        assert!(PntsReader::from_read(Cursor::new(pnts.as_slice())).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_quantized_positions_in_ecef() -> Result<()> {
+        // The file is not written by pasture, but laid out like the quantized tiles of the Cesium tiler: The FeatureTable
+        // JSON is padded with spaces, the RGB colors follow the quantized positions in the binary body, and the quantized
+        // volume is given in ECEF coordinates, which f32 values can't represent accurately
+        let mut test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file_path.push("resources/test/quantized_ecef.pnts");
+        let mut reader = PntsReader::from_read(BufReader::new(File::open(test_file_path)?))?;
+        assert_eq!(Some(8), reader.get_metadata().number_of_points());
+
+        let volume_offset = Vector3::new(1215012.8828876738, -4736313.051199594, 4081605.22126042);
+        let volume_scale = Vector3::new(24.5, 17.25, 31.0);
+        let points = reader.read(8)?;
+        let positions = points
+            .iter_attribute::<Vector3<f64>>(&POSITION_3D)
+            .collect::<Vec<_>>();
+        let expected_quantized_positions = [
+            (0, [0_u16, 0, 0]),
+            (1, [65535, 65535, 65535]),
+            (2, [32768, 16384, 49152]),
+            (7, [7, 65000, 8]),
+        ];
+        for (index, quantized_position) in expected_quantized_positions.iter() {
+            let expected_position = Vector3::new(
+                volume_offset.x + quantized_position[0] as f64 * volume_scale.x / 65535.0,
+                volume_offset.y + quantized_position[1] as f64 * volume_scale.y / 65535.0,
+                volume_offset.z + quantized_position[2] as f64 * volume_scale.z / 65535.0,
+            );
+            assert!(
+                (expected_position - positions[*index]).norm() < 1e-6,
+                "Position {} is {}, expected {}",
+                index,
+                positions[*index],
+                expected_position
+            );
+        }
+
+        let colors = points
+            .iter_attribute::<Vector3<u8>>(
+                &COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(Vector3::new(0, 0, 255), colors[2]);
+        assert_eq!(Vector3::new(130, 140, 150), colors[7]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_rgb565_colors() -> Result<()> {
+        let mut feature_table_header = FeatureTableHeader::new();
+        feature_table_header.insert(
+            semantics::POINTS_LENGTH.into(),
+            FeatureTableValue::SingleValue(json!(3)),
+        );
+        feature_table_header.insert(
+            semantics::POSITION.into(),
+            FeatureTableValue::DataReference(FeatureTableDataReference {
+                byte_offset: 0,
+                component_type: None,
+            }),
+        );
+        feature_table_header.insert(
+            semantics::RGB565.into(),
+            FeatureTableValue::DataReference(FeatureTableDataReference {
+                byte_offset: 36,
+                component_type: Some("UNSIGNED_SHORT".into()),
+            }),
+        );
+
+        let mut feature_table_body = vec![0; 36];
+        for packed_color in &[0xF800_u16, 0x07E0, 0x001F] {
+            feature_table_body.extend_from_slice(&packed_color.to_le_bytes());
+        }
+        let pnts = make_pnts(&feature_table_header, feature_table_body);
+
+        let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+        let color_attribute = COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8);
+        assert!(reader
+            .get_default_point_layout()
+            .has_attribute(&color_attribute));
+
+        let points = reader.read(3)?;
+        let colors = points
+            .iter_attribute::<Vector3<u8>>(&color_attribute)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Vector3::new(255, 0, 0),
+                Vector3::new(0, 255, 0),
+                Vector3::new(0, 0, 255)
+            ],
+            colors
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_batch_table_properties() -> Result<()> {
+        let mut feature_table_header = FeatureTableHeader::new();
+        feature_table_header.insert(
+            semantics::POINTS_LENGTH.into(),
+            FeatureTableValue::SingleValue(json!(3)),
+        );
+        feature_table_header.insert(
+            semantics::POSITION.into(),
+            FeatureTableValue::DataReference(FeatureTableDataReference {
+                byte_offset: 0,
+                component_type: None,
+            }),
+        );
+        let feature_table_body = vec![0; 36];
+
+        let mut batch_table_header = BatchTableHeader::new();
+        batch_table_header.insert(
+            "Temperature".into(),
+            BatchTableEntry::DataReference(BatchTableDataReference {
+                byte_offset: 0,
+                component_type: "FLOAT".into(),
+                scalar_or_vector_type: "SCALAR".into(),
+            }),
+        );
+        batch_table_header.insert(
+            "Names".into(),
+            BatchTableEntry::ArrayData(vec![json!("a"), json!("b"), json!("c")]),
+        );
+        // There is no PointAttributeDataType for VEC2 properties
+        batch_table_header.insert(
+            "UV".into(),
+            BatchTableEntry::DataReference(BatchTableDataReference {
+                byte_offset: 12,
+                component_type: "FLOAT".into(),
+                scalar_or_vector_type: "VEC2".into(),
+            }),
+        );
+        let temperatures = [21.5_f32, -3.25, 100.0];
+        let batch_table_body = temperatures
+            .iter()
+            .flat_map(|t| t.to_le_bytes().to_vec())
+            .collect::<Vec<_>>();
+
+        let pnts = make_pnts_with_batch_table(
+            &feature_table_header,
+            feature_table_body,
+            &batch_table_header,
+            batch_table_body,
+        );
+
+        let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+        let temperature_attribute =
+            PointAttributeDefinition::custom_dynamic("Temperature", PointAttributeDataType::F32);
+        let layout = reader.get_default_point_layout().clone();
+        assert!(layout.has_attribute(&temperature_attribute));
+        assert!(!layout.has_attribute_with_name("Names"));
+        assert!(!layout.has_attribute_with_name("UV"));
+        assert_eq!(
+            &["Names".to_owned(), "UV".to_owned()],
+            reader.skipped_batch_table_properties()
+        );
+
+        let points = reader.read(3)?;
+        let actual_temperatures = points
+            .iter_attribute::<f32>(&temperature_attribute)
+            .collect::<Vec<_>>();
+        assert_eq!(temperatures.to_vec(), actual_temperatures);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_invalid_batch_table() -> Result<()> {
+        let mut feature_table_header = FeatureTableHeader::new();
+        feature_table_header.insert(
+            semantics::POINTS_LENGTH.into(),
+            FeatureTableValue::SingleValue(json!(3)),
+        );
+        feature_table_header.insert(
+            semantics::POSITION.into(),
+            FeatureTableValue::DataReference(FeatureTableDataReference {
+                byte_offset: 0,
+                component_type: None,
+            }),
+        );
+        let mut batch_table_header = BatchTableHeader::new();
+        batch_table_header.insert(
+            "Temperature".into(),
+            BatchTableEntry::DataReference(BatchTableDataReference {
+                byte_offset: 0,
+                component_type: "DOUBLE".into(),
+                scalar_or_vector_type: "SCALAR".into(),
+            }),
+        );
+        // The body only holds 3 FLOAT values, but the property claims to store 3 DOUBLE values
+        let pnts = make_pnts_with_batch_table(
+            &feature_table_header,
+            vec![0; 36],
+            &batch_table_header,
+            vec![0; 12],
+        );
+
+        // The BatchTable is optional, so the points can still be read without it
+        let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+        assert!(reader.batch_table_error().is_some());
+        assert!(!reader
+            .get_default_point_layout()
+            .has_attribute_with_name("Temperature"));
+        assert_eq!(3, reader.read(3)?.len());
+
+        Ok(())
+    }
+
+    /// Creates a .pnts file with three points at the origin. If `rgba_colors` are given, they are stored with the
+    /// `RGBA` semantic. If `constant_rgba` is given, it is stored as the `CONSTANT_RGBA` global semantic
+    fn make_rgba_pnts(
+        rgba_colors: Option<&[[u8; 4]; 3]>,
+        constant_rgba: Option<[u8; 4]>,
+    ) -> Vec<u8> {
+        let mut feature_table_header = FeatureTableHeader::new();
+        feature_table_header.insert(
+            semantics::POINTS_LENGTH.into(),
+            FeatureTableValue::SingleValue(json!(3)),
+        );
+        feature_table_header.insert(
+            semantics::POSITION.into(),
+            FeatureTableValue::DataReference(FeatureTableDataReference {
+                byte_offset: 0,
+                component_type: None,
+            }),
+        );
+        let mut feature_table_body = vec![0; 36];
+        if let Some(colors) = rgba_colors {
+            feature_table_header.insert(
+                semantics::RGBA.into(),
+                FeatureTableValue::DataReference(FeatureTableDataReference {
+                    byte_offset: 36,
+                    component_type: Some("UNSIGNED_BYTE".into()),
+                }),
+            );
+            for color in colors.iter() {
+                feature_table_body.extend_from_slice(color);
+            }
+        }
+        if let Some(color) = constant_rgba {
+            feature_table_header.insert(
+                semantics::CONSTANT_RGBA.into(),
+                FeatureTableValue::Array(color.iter().map(|component| json!(component)).collect()),
+            );
+        }
+        make_pnts(&feature_table_header, feature_table_body)
+    }
+
+    #[test]
+    fn test_pnts_reader_rgba_colors() -> Result<()> {
+        let colors = [[255, 0, 0, 255], [0, 255, 0, 128], [0, 0, 255, 0]];
+        let expected_colors = colors
+            .iter()
+            .map(|c| Vector4::new(c[0], c[1], c[2], c[3]))
+            .collect::<Vec<_>>();
+
+        // Per-point RGBA colors take precedence over CONSTANT_RGBA
+        for constant_rgba in [None, Some([1, 2, 3, 4])].iter() {
+            let pnts = make_rgba_pnts(Some(&colors), *constant_rgba);
+            let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+            assert!(reader.get_default_point_layout().has_attribute(&COLOR_RGBA));
+            assert!(!reader
+                .get_default_point_layout()
+                .has_attribute_with_name(COLOR_RGB.name()));
+
+            let points = reader.read(3)?;
+            let actual_colors = points
+                .iter_attribute::<Vector4<u8>>(&COLOR_RGBA)
+                .collect::<Vec<_>>();
+            assert_eq!(expected_colors, actual_colors);
+
+            reader.seek_point(SeekFrom::Start(2))?;
+            let last_point = reader.read(1)?;
+            assert_eq!(
+                expected_colors[2],
+                last_point.get_attribute::<Vector4<u8>>(&COLOR_RGBA, 0)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pnts_reader_constant_rgba() -> Result<()> {
+        let pnts = make_rgba_pnts(None, Some([10, 20, 30, 40]));
+        let mut reader = PntsReader::from_read(Cursor::new(pnts.as_slice()))?;
+        assert!(reader.get_default_point_layout().has_attribute(&COLOR_RGBA));
+
+        let points = reader.read(3)?;
+        assert!(points
+            .iter_attribute::<Vector4<u8>>(&COLOR_RGBA)
+            .all(|color| color == Vector4::new(10, 20, 30, 40)));
+
+        Ok(())
+    }
 }
@@ -3,16 +3,52 @@ use serde::{Deserialize, Serialize};
 use static_assertions::const_assert;
 
 pub mod attributes {
-    use pasture_core::layout::{PointAttributeDataType, PointAttributeDefinition};
+    /// Attribute definition for an RGBA color in the 3D Tiles format. This is the builtin `COLOR_RGBA` attribute of
+    /// pasture, which is re-exported here for backwards compatibility
+    pub use pasture_core::layout::attributes::COLOR_RGBA;
+}
+
+/// Names of the semantics of the feature table of .pnts files, as defined in the
+/// [3D Tiles specification](https://github.com/CesiumGS/3d-tiles/blob/master/specification/TileFormats/PointCloud/README.md#semantics)
+pub mod semantics {
+    // Per-point semantics
+
+    /// Cartesian position of a point as three `f32` values
+    pub const POSITION: &str = "POSITION";
+    /// Quantized position of a point as three `u16` values
+    pub const POSITION_QUANTIZED: &str = "POSITION_QUANTIZED";
+    /// RGBA color of a point as four `u8` values
+    pub const RGBA: &str = "RGBA";
+    /// RGB color of a point as three `u8` values
+    pub const RGB: &str = "RGB";
+    /// Lossy compressed RGB color of a point, packed into a single `u16` value
+    pub const RGB565: &str = "RGB565";
+    /// Unit normal vector of a point as three `f32` values
+    pub const NORMAL: &str = "NORMAL";
+    /// Oct-encoded unit normal vector of a point as two `u8` values
+    pub const NORMAL_OCT16P: &str = "NORMAL_OCT16P";
+    /// Batch ID of a point, used to look up properties in the batch table
+    pub const BATCH_ID: &str = "BATCH_ID";
+
+    // Global semantics
 
-    /// Attribute definition for an RGBA color in the 3D Tiles format
-    pub const COLOR_RGBA: PointAttributeDefinition =
-        PointAttributeDefinition::custom("ColorRGBA", PointAttributeDataType::Vec4u8);
+    /// Number of points in the file
+    pub const POINTS_LENGTH: &str = "POINTS_LENGTH";
+    /// Center that all positions are relative to
+    pub const RTC_CENTER: &str = "RTC_CENTER";
+    /// Offset of the quantized volume
+    pub const QUANTIZED_VOLUME_OFFSET: &str = "QUANTIZED_VOLUME_OFFSET";
+    /// Scale of the quantized volume
+    pub const QUANTIZED_VOLUME_SCALE: &str = "QUANTIZED_VOLUME_SCALE";
+    /// RGBA color that is used for all points
+    pub const CONSTANT_RGBA: &str = "CONSTANT_RGBA";
+    /// Number of unique batch IDs
+    pub const BATCH_LENGTH: &str = "BATCH_LENGTH";
 }
 
 /// Header of .pnts files
 #[repr(packed)]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PntsHeader {
     pub magic: [u8; 4],
     pub version: u32,
@@ -26,6 +62,8 @@ pub struct PntsHeader {
 impl PntsHeader {
     /// Length of a .pnts header in bytes
     pub const BYTE_LENGTH: usize = 28;
+    /// The version of the .pnts format that is supported by pasture
+    pub const VERSION: u32 = 1;
 
     pub fn new(
         version: u32,
@@ -53,6 +91,20 @@ impl PntsHeader {
         }
         Ok(())
     }
+
+    /// Returns an Err if the version in this header is not supported
+    pub fn verify_version(&self) -> Result<()> {
+        // Copy the field, because references to fields of packed structs may be unaligned
+        let version = self.version;
+        if version != Self::VERSION {
+            bail!(
+                "Unsupported PNTS version {}, only version {} is supported",
+                version,
+                Self::VERSION
+            );
+        }
+        Ok(())
+    }
 }
 
 const_assert!(PntsHeader::BYTE_LENGTH == std::mem::size_of::<PntsHeader>());
@@ -1,28 +1,31 @@
 use std::{
+    borrow::Cow,
     collections::HashMap,
     convert::TryInto,
     io::{Cursor, Seek, SeekFrom, Write},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use pasture_core::{
     containers::{
-        PerAttributePointBuffer, PerAttributeVecPointStorage, PointBuffer, PointBufferWriteable,
+        PerAttributePointBufferMut, PerAttributeVecPointStorage, PointBuffer, PointBufferWriteable,
     },
     layout::{
         attributes::{COLOR_RGB, NORMAL, POSITION_3D},
         conversion::{get_converter_for_attributes, AttributeConversionFn},
-        FieldAlignment, PointAttributeDataType, PointAttributeDefinition, PointLayout,
+        FieldAlignment, PointAttributeDataType, PointAttributeDefinition, PointAttributeMember,
+        PointLayout,
     },
     math::Alignable,
-    nalgebra::Vector3,
+    nalgebra::{Vector3, Vector4},
 };
 use serde_json::json;
 
 use crate::{
     base::PointWriter,
     tiles3d::{
-        attributes::COLOR_RGBA, ser_batch_table_header, ser_feature_table_header, PntsHeader,
+        attributes::COLOR_RGBA, semantics, ser_batch_table_header, ser_feature_table_header,
+        PntsHeader,
     },
 };
 
@@ -38,18 +41,144 @@ fn pnts_semantics_name_from_point_attribute(
     attribute: &PointAttributeDefinition,
 ) -> Option<String> {
     if attribute.name() == POSITION_3D.name() {
-        Some("POSITION".into())
+        Some(semantics::POSITION.into())
     } else if attribute.name() == COLOR_RGB.name() {
-        Some("RGB".into())
+        Some(semantics::RGB.into())
     } else if attribute.name() == COLOR_RGBA.name() {
-        Some("RGBA".into())
+        Some(semantics::RGBA.into())
     } else if attribute.name() == NORMAL.name() {
-        Some("NORMAL".into())
+        Some(semantics::NORMAL.into())
     } else {
         None
     }
 }
 
+/// Returns the datatype with which the attribute with the given `attribute_name` is stored in a .pnts file, or `None` if the
+/// attribute is not supported by the `PntsWriter`
+fn pnts_datatype_for_attribute(attribute_name: &str) -> Option<PointAttributeDataType> {
+    if attribute_name == POSITION_3D.name() {
+        Some(PointAttributeDataType::Vec3f32)
+    } else if attribute_name == COLOR_RGB.name() {
+        Some(PointAttributeDataType::Vec3u8)
+    } else if attribute_name == COLOR_RGBA.name() {
+        Some(PointAttributeDataType::Vec4u8)
+    } else if attribute_name == NORMAL.name() {
+        Some(PointAttributeDataType::Vec3f32)
+    } else {
+        None
+    }
+}
+
+/// Defines whether a `PntsWriter` writes the colors of all points as a single `CONSTANT_RGBA` global semantic instead of
+/// writing per-point colors. A constant color is much smaller for monochrome tiles
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PntsConstantColorMode {
+    /// Always write per-point colors. This is the default
+    Disabled,
+    /// Write `CONSTANT_RGBA` if all points have the same color, otherwise write per-point colors. RGB colors are
+    /// written with an alpha value of 255
+    Detect,
+    /// Write the given color as `CONSTANT_RGBA` and discard all per-point colors
+    Force(Vector4<u8>),
+}
+
+impl Default for PntsConstantColorMode {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
+/// Defines how a `PntsWriter` aligns the attributes in the binary body of the FeatureTable
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PntsAttributeAlignment {
+    /// Align all attributes to 8 bytes, the largest alignment that any point semantic requires. This is the default
+    MaxAlignment,
+    /// Align each attribute to the size of its `componentType` as required by the 3D Tiles specification, e.g. 4 bytes
+    /// for `POSITION` (`FLOAT`) and 1 byte for `RGB` (`UNSIGNED_BYTE`). This avoids padding between small attributes
+    ComponentType,
+}
+
+impl Default for PntsAttributeAlignment {
+    fn default() -> Self {
+        Self::MaxAlignment
+    }
+}
+
+/// Returns `true` if the given `attribute` stores a per-point color
+fn is_color_attribute(attribute: &PointAttributeMember) -> bool {
+    attribute.name() == COLOR_RGB.name() || attribute.name() == COLOR_RGBA.name()
+}
+
+/// Returns the values of the given `attribute` for all `points`, converted to the datatype of `attribute` using the given
+/// `attribute_converters` if necessary. If `points` already stores the attribute contiguously and with the same datatype,
+/// its memory is borrowed instead of copied
+fn attribute_data<'p>(
+    points: &'p dyn PointBuffer,
+    attribute: &PointAttributeDefinition,
+    attribute_converters: &HashMap<&'static str, Option<AttributeConversionFn>>,
+) -> Cow<'p, [u8]> {
+    let num_points = points.len();
+    if let Some(per_attribute_points) = points.as_per_attribute() {
+        if points.point_layout().has_attribute(attribute) {
+            return Cow::Borrowed(
+                per_attribute_points.get_raw_attribute_range_ref(0..num_points, attribute),
+            );
+        }
+    }
+    let mut data = vec![0; num_points * attribute.size() as usize];
+    copy_attribute_data(points, attribute, attribute_converters, data.as_mut_slice());
+    Cow::Owned(data)
+}
+
+/// Copies the values of the given `attribute` for all `points` into `dst_bytes`, converting them to the datatype of
+/// `attribute` using the given `attribute_converters` if `points` stores the attribute with a different datatype
+fn copy_attribute_data(
+    points: &dyn PointBuffer,
+    attribute: &PointAttributeDefinition,
+    attribute_converters: &HashMap<&'static str, Option<AttributeConversionFn>>,
+    dst_bytes: &mut [u8],
+) {
+    let num_points = points.len();
+    let src_attribute: PointAttributeDefinition = points
+        .point_layout()
+        .get_attribute_by_name(attribute.name())
+        .expect("Attribute not found in PointBuffer")
+        .into();
+    if src_attribute.datatype() == attribute.datatype() {
+        points.get_raw_attribute_range(0..num_points, attribute, dst_bytes);
+        return;
+    }
+
+    let conversion_fn = attribute_converters[attribute.name()]
+        .expect("No conversion function for attribute with different datatype");
+    let src_attribute_size = src_attribute.size() as usize;
+    // Per-attribute buffers already store the attribute contiguously, so it can be converted directly from their
+    // memory. All other buffers have to copy it first
+    let mut src_bytes_copy = vec![];
+    let src_bytes = match points.as_per_attribute() {
+        Some(per_attribute_points) => {
+            per_attribute_points.get_raw_attribute_range_ref(0..num_points, &src_attribute)
+        }
+        None => {
+            src_bytes_copy.resize(num_points * src_attribute_size, 0);
+            points.get_raw_attribute_range(
+                0..num_points,
+                &src_attribute,
+                src_bytes_copy.as_mut_slice(),
+            );
+            src_bytes_copy.as_slice()
+        }
+    };
+    for (src, dst) in src_bytes
+        .chunks_exact(src_attribute_size)
+        .zip(dst_bytes.chunks_exact_mut(attribute.size() as usize))
+    {
+        unsafe {
+            conversion_fn(src, dst);
+        }
+    }
+}
+
 /// Writer for .pnts files, the point cloud file format in the 3D Tiles standard.
 ///
 /// 3D Tiles .pnts files store their data in per-attribute memory layout. Append to data
@@ -61,14 +190,19 @@ fn pnts_semantics_name_from_point_attribute(
 /// 2) Cache all data locally in a `PerAttributePointBuffer`, and only write the data during
 ///    the `flush` call
 ///
-/// This `PntsWriter` implementation uses the second approach
+/// This `PntsWriter` implementation uses the second approach. If all points are available at once, `write_all_points`
+/// writes them directly without caching them first. If no points are written at all, the `PntsWriter` still produces a
+/// valid .pnts file with a `POINTS_LENGTH` of zero and an empty FeatureTable body
 pub struct PntsWriter<W: Write + Seek> {
-    writer: W,
+    /// The underlying writer. This is only `None` after the `PntsWriter` has been finalized
+    writer: Option<W>,
     expected_layout: PointLayout,
     default_layout: PointLayout,
     cached_points: PerAttributeVecPointStorage,
     attribute_converters: HashMap<&'static str, Option<AttributeConversionFn>>,
     rtc_center: Option<Vector3<f64>>,
+    constant_color_mode: PntsConstantColorMode,
+    attribute_alignment: PntsAttributeAlignment,
     requires_flush: bool,
 }
 
@@ -82,12 +216,14 @@ impl<W: Write + Seek> PntsWriter<W> {
         let (cache_layout, attribute_converters) = Self::make_compatible_layout(&point_layout);
         let cache = PerAttributeVecPointStorage::new(cache_layout.clone());
         Self {
-            writer,
+            writer: Some(writer),
             expected_layout: point_layout,
             default_layout: cache_layout,
             cached_points: cache,
             attribute_converters,
             rtc_center: None,
+            constant_color_mode: Default::default(),
+            attribute_alignment: Default::default(),
             requires_flush: true,
         }
     }
@@ -99,6 +235,107 @@ impl<W: Write + Seek> PntsWriter<W> {
         self.rtc_center = Some(rtc_center);
     }
 
+    /// Sets the `PntsConstantColorMode` of this `PntsWriter`, which determines whether colors are written per point or as
+    /// a single `CONSTANT_RGBA` value. See `PntsConstantColorMode` for details
+    pub fn set_constant_color_mode(&mut self, constant_color_mode: PntsConstantColorMode) {
+        self.constant_color_mode = constant_color_mode;
+    }
+
+    /// Sets the `PntsAttributeAlignment` of this `PntsWriter`, which determines how the attributes in the FeatureTable
+    /// binary body are aligned. See `PntsAttributeAlignment` for details
+    pub fn set_attribute_alignment(&mut self, attribute_alignment: PntsAttributeAlignment) {
+        self.attribute_alignment = attribute_alignment;
+    }
+
+    /// Reserves memory for at least `additional_points` more points in the internal point cache of this `PntsWriter`. Since
+    /// all points are cached until the writer is flushed, calling this before a sequence of `write` calls avoids repeated
+    /// reallocations of the cache if the total number of points is known up front.
+    pub fn reserve(&mut self, additional_points: usize) {
+        self.cached_points.reserve(additional_points);
+    }
+
+    /// Returns the number of points that this `PntsWriter` has cached so far. These points are written to the underlying
+    /// writer once the `PntsWriter` is flushed
+    pub fn cached_point_count(&self) -> usize {
+        self.cached_points.len()
+    }
+
+    /// Returns `true` if the cached points of this `PntsWriter` have already been written to the underlying writer, either
+    /// through `flush` or `finalize`. Until then, they are written when the `PntsWriter` is dropped
+    pub fn is_flushed(&self) -> bool {
+        !self.requires_flush
+    }
+
+    /// Returns which attributes of the given `point_layout` a `PntsWriter` created with this layout would write, without
+    /// actually writing anything. The first `Vec` contains all attributes that are kept, with the datatype that they are
+    /// stored with in the .pnts file. The second `Vec` contains all attributes that are not supported and are discarded
+    /// during writing.
+    ///
+    /// Since this function does not depend on the type of the underlying writer, a concrete writer type has to be named
+    /// explicitly when calling it, e.g. `PntsWriter::<File>::supported_attributes(&layout)`
+    pub fn supported_attributes(
+        point_layout: &PointLayout,
+    ) -> (Vec<PointAttributeDefinition>, Vec<PointAttributeDefinition>) {
+        let mut kept_attributes = vec![];
+        let mut dropped_attributes = vec![];
+        for attribute in point_layout.attributes() {
+            match pnts_datatype_for_attribute(attribute.name()) {
+                Some(datatype) => kept_attributes
+                    .push(PointAttributeDefinition::custom(attribute.name(), datatype)),
+                None => dropped_attributes.push(attribute.into()),
+            }
+        }
+        (kept_attributes, dropped_attributes)
+    }
+
+    /// Writes all cached points and returns the underlying writer. In contrast to relying on `Drop` to write the points,
+    /// this surfaces any error that occurs during writing.
+    ///
+    /// # Errors
+    ///
+    /// If writing the cached points to the underlying writer fails
+    pub fn finalize(mut self) -> Result<W> {
+        let flush_result = self.flush();
+        // Whether or not the flush succeeded, `Drop` must not try to write the points again
+        self.requires_flush = false;
+        flush_result?;
+        Ok(self
+            .writer
+            .take()
+            .expect("PntsWriter has already been finalized"))
+    }
+
+    /// Writes the given `points` as the only points of the .pnts file, without caching them first. Each attribute is
+    /// converted and written directly into the FeatureTable binary body, which avoids holding a full copy of `points`
+    /// in the cache. This is the preferred way of writing a .pnts file if all points fit into a single buffer, in
+    /// particular if `points` is not in per-attribute memory layout. `points` can have either the `PointLayout` that
+    /// this `PntsWriter` was created with, or its default `PointLayout`. Afterwards, the `PntsWriter` is flushed.
+    ///
+    /// # Errors
+    ///
+    /// If points have already been written to this `PntsWriter`, if `points` has an unsupported `PointLayout`, or if
+    /// writing to the underlying writer fails
+    pub fn write_all_points(&mut self, points: &dyn PointBuffer) -> Result<()> {
+        if self.cached_points.len() > 0 || !self.requires_flush {
+            bail!("write_all_points can only be used if no points have been written to the PntsWriter before");
+        }
+        if points.point_layout() != &self.expected_layout
+            && points.point_layout() != &self.default_layout
+        {
+            bail!(
+                "PointLayout of buffer ({}) matches neither the PointLayout that this PntsWriter was created with nor its default PointLayout",
+                points.point_layout()
+            );
+        }
+        self.write_points(points)
+    }
+
+    fn writer_mut(&mut self) -> &mut W {
+        self.writer
+            .as_mut()
+            .expect("PntsWriter has already been finalized")
+    }
+
     /// Makes the given `PointLayout` compatible with the supported point semantics of the 3D Tiles .pnts format. Doing
     /// so is done by iterating through the attributes in the `point_layout` and checking each attribute if it is one of
     /// the supported point semantics. If not, it is discarded. Supported semantics are then converted to the default data
@@ -118,19 +355,11 @@ impl<W: Write + Seek> PntsWriter<W> {
         // * Normal oct encoded
         // * Batch ID (and batch table with custom attributes)
 
-        let supported_attributes: HashMap<&'static str, PointAttributeDataType> = vec![
-            (POSITION_3D.name(), PointAttributeDataType::Vec3f32),
-            (COLOR_RGB.name(), PointAttributeDataType::Vec3u8),
-            (COLOR_RGBA.name(), PointAttributeDataType::Vec4u8),
-            (NORMAL.name(), PointAttributeDataType::Vec3f32),
-        ]
-        .drain(..)
-        .collect();
-
         for src_attribute in point_layout.attributes() {
-            if let Some(dst_attribute_datatype) = supported_attributes.get(&src_attribute.name()) {
+            if let Some(dst_attribute_datatype) = pnts_datatype_for_attribute(src_attribute.name())
+            {
                 compatible_layout.add_attribute(
-                    PointAttributeDefinition::custom(src_attribute.name(), *dst_attribute_datatype),
+                    PointAttributeDefinition::custom(src_attribute.name(), dst_attribute_datatype),
                     FieldAlignment::Default,
                 );
                 let dst_attribute = compatible_layout
@@ -150,8 +379,82 @@ impl<W: Write + Seek> PntsWriter<W> {
         (compatible_layout, conversion_fns)
     }
 
+    /// Returns the color that is written as `CONSTANT_RGBA` for the given `points`, or `None` if colors are written per
+    /// point
+    fn constant_color(&self, points: &dyn PointBuffer) -> Option<Vector4<u8>> {
+        match self.constant_color_mode {
+            PntsConstantColorMode::Disabled => None,
+            PntsConstantColorMode::Force(color) => Some(color),
+            PntsConstantColorMode::Detect => self.detect_constant_color(points),
+        }
+    }
+
+    /// Returns the color of all `points`, if all of them have the same color
+    fn detect_constant_color(&self, points: &dyn PointBuffer) -> Option<Vector4<u8>> {
+        let num_points = points.len();
+        if num_points == 0 {
+            return None;
+        }
+
+        let mut constant_color = None;
+        for attribute in self.default_layout.attributes() {
+            if !is_color_attribute(attribute) {
+                continue;
+            }
+
+            let colors = attribute_data(points, &attribute.into(), &self.attribute_converters);
+            let mut color_iter = colors.chunks_exact(attribute.size() as usize);
+            let first_color = color_iter.next().unwrap();
+            if !color_iter.all(|color| color == first_color) {
+                return None;
+            }
+            let color = if first_color.len() == 4 {
+                Vector4::new(
+                    first_color[0],
+                    first_color[1],
+                    first_color[2],
+                    first_color[3],
+                )
+            } else {
+                Vector4::new(first_color[0], first_color[1], first_color[2], 255)
+            };
+
+            match constant_color {
+                Some(previous_color) if previous_color != color => return None,
+                _ => constant_color = Some(color),
+            }
+        }
+        constant_color
+    }
+
+    /// Returns all attributes that are written to the FeatureTable binary body
+    fn feature_table_body_attributes(&self, has_constant_color: bool) -> Vec<PointAttributeMember> {
+        self.default_layout
+            .attributes()
+            .filter(|attribute| !(has_constant_color && is_color_attribute(attribute)))
+            .cloned()
+            .collect()
+    }
+
     fn write_cached_points(&mut self) -> Result<()> {
-        let feature_table_header = self.create_feature_table();
+        // The cache is moved out while writing, so that it can be borrowed alongside the underlying writer
+        let cached_points = std::mem::replace(
+            &mut self.cached_points,
+            PerAttributeVecPointStorage::new(self.default_layout.clone()),
+        );
+        let result = self.write_points(&cached_points);
+        self.cached_points = cached_points;
+        result
+    }
+
+    /// Writes the given `points` as a complete .pnts file to the underlying writer. `points` must have either the
+    /// `PointLayout` that this `PntsWriter` was created with, or its default `PointLayout`
+    fn write_points(&mut self, points: &dyn PointBuffer) -> Result<()> {
+        let num_points = points.len();
+        let constant_color = self.constant_color(points);
+        let body_attributes = self.feature_table_body_attributes(constant_color.is_some());
+        let feature_table_header =
+            self.create_feature_table(&body_attributes, constant_color, num_points);
         let batch_table_header = self.create_batch_table();
 
         let mut feature_table_blob = vec![];
@@ -165,7 +468,8 @@ impl<W: Write + Seek> PntsWriter<W> {
         .context("Error serializing FeatureTable header")?;
 
         let feature_table_byte_size = feature_table_blob.len();
-        let feature_table_body_byte_size = self.calc_feature_table_body_length();
+        let feature_table_body_byte_size =
+            self.calc_feature_table_body_length(&body_attributes, num_points);
         let feature_table_body_byte_size_aligned =
             (PntsHeader::BYTE_LENGTH + feature_table_byte_size + feature_table_body_byte_size)
                 .align_to(8)
@@ -206,13 +510,13 @@ impl<W: Write + Seek> PntsWriter<W> {
                 .expect("Size of BatchTable binary body exceeds maximum size of 4GiB!"),
         );
 
-        bincode::serialize_into(&mut self.writer, &pnts_header)
+        bincode::serialize_into(self.writer_mut(), &pnts_header)
             .context("Error while serializing .pnts header")?;
-        self.writer
+        self.writer_mut()
             .write(feature_table_blob.as_slice())
             .context("Error while writing FeatureTable header")?;
-        self.write_feature_table_body()?;
-        self.writer
+        self.write_feature_table_body(&body_attributes, points)?;
+        self.writer_mut()
             .write(batch_table_blob.as_slice())
             .context("Error while writing BatchTable header")?;
         // TODO Write BatchTable binary body. For now, it doesn't exist, so we don't have to write anything
@@ -222,22 +526,17 @@ impl<W: Write + Seek> PntsWriter<W> {
         Ok(())
     }
 
-    fn create_feature_table(&self) -> FeatureTableHeader {
-        let num_points = self.cached_points.len();
-        let cumulative_attribute_offsets = self
-            .default_layout
-            .attributes()
-            .scan(0, |state, attribute| {
-                let ret = *state;
-                *state +=
-                    (attribute.size() as usize * num_points).align_to(PNTS_SEMANTICS_MAX_ALIGNMENT);
-                Some(ret)
-            })
-            .collect::<Vec<_>>();
-
-        let mut point_semantics = self
-            .default_layout
-            .attributes()
+    fn create_feature_table(
+        &self,
+        body_attributes: &[PointAttributeMember],
+        constant_color: Option<Vector4<u8>>,
+        num_points: usize,
+    ) -> FeatureTableHeader {
+        let cumulative_attribute_offsets =
+            self.feature_table_body_offsets(body_attributes, num_points);
+
+        let mut point_semantics = body_attributes
+            .iter()
             .enumerate()
             .map(|(idx, attribute)| -> (String, FeatureTableValue) {
                 let semantic_name = pnts_semantics_name_from_point_attribute(&attribute.into())
@@ -254,13 +553,13 @@ impl<W: Write + Seek> PntsWriter<W> {
 
         // Create global semantics. Only POINTS_LENGTH is mandatory
         point_semantics.insert(
-            "POINTS_LENGTH".into(),
+            semantics::POINTS_LENGTH.into(),
             FeatureTableValue::SingleValue(json!(num_points)),
         );
 
         if let Some(ref rtc_center) = self.rtc_center {
             point_semantics.insert(
-                "RTC_CENTER".into(),
+                semantics::RTC_CENTER.into(),
                 FeatureTableValue::Array(vec![
                     json!(rtc_center.x),
                     json!(rtc_center.y),
@@ -269,6 +568,13 @@ impl<W: Write + Seek> PntsWriter<W> {
             );
         }
 
+        if let Some(color) = constant_color {
+            point_semantics.insert(
+                semantics::CONSTANT_RGBA.into(),
+                FeatureTableValue::Array(color.iter().map(|component| json!(component)).collect()),
+            );
+        }
+
         point_semantics
     }
 
@@ -276,49 +582,89 @@ impl<W: Write + Seek> PntsWriter<W> {
         Default::default()
     }
 
-    /// Calculate the length in bytes of the FeatureTable binary body. This is based on the default PointLayout
-    /// and the number of cached points. For simplicities sake, we store all attributes with the same memory
-    /// alignment (PNTS_SEMANTICS_MAX_ALIGNMENT), which makes the calculation of total size easier. The whole FeatureTable
-    /// body has to end at an 8-byte boundary, however THIS IS NOT TAKEN INTO ACCOUNT BY THIS METHOD! The padding bytes are
-    /// written in `write_feature_table_body` instead!
-    fn calc_feature_table_body_length(&self) -> usize {
-        let num_points = self.cached_points.len();
-        self.default_layout
-            .attributes()
-            .map(|attribute| {
-                (num_points * attribute.size() as usize).align_to(PNTS_SEMANTICS_MAX_ALIGNMENT)
-            })
-            .sum()
+    /// Returns the alignment of the given `attribute` within the FeatureTable binary body, based on the
+    /// `PntsAttributeAlignment` of this `PntsWriter`
+    fn alignment_of_attribute(&self, attribute: &PointAttributeMember) -> usize {
+        match self.attribute_alignment {
+            PntsAttributeAlignment::MaxAlignment => PNTS_SEMANTICS_MAX_ALIGNMENT,
+            PntsAttributeAlignment::ComponentType => {
+                attribute.datatype().component_type().size() as usize
+            }
+        }
     }
 
-    fn write_feature_table_body(&mut self) -> Result<()> {
-        let num_points = self.cached_points.len();
+    /// Calculates the byte offsets of the given `body_attributes` for `num_points` points within the FeatureTable binary
+    /// body. The binary body
+    /// itself starts at an 8-byte boundary, so aligning the offsets relative to the start of the body is sufficient. This
+    /// is the single source of truth for the layout of the body, both the FeatureTable header and the actual writing of
+    /// the body are based on these offsets
+    fn feature_table_body_offsets(
+        &self,
+        body_attributes: &[PointAttributeMember],
+        num_points: usize,
+    ) -> Vec<usize> {
+        body_attributes
+            .iter()
+            .scan(0, |end_of_previous_attribute, attribute| {
+                let offset =
+                    end_of_previous_attribute.align_to(self.alignment_of_attribute(attribute));
+                *end_of_previous_attribute = offset + attribute.size() as usize * num_points;
+                Some(offset)
+            })
+            .collect()
+    }
 
-        for attribute in self.default_layout.attributes() {
-            let attribute_data = self
-                .cached_points
-                .get_raw_attribute_range_ref(0..num_points, &attribute.into());
-            self.writer
-                .write_all(attribute_data)
-                .context("Error while writing attribute data")?;
+    /// Calculate the length in bytes of the FeatureTable binary body. This is based on the default PointLayout
+    /// and the number of points. The whole FeatureTable body has to end at an 8-byte boundary, however THIS IS
+    /// NOT TAKEN INTO ACCOUNT BY THIS METHOD! The padding bytes are written in `write_feature_table_body` instead!
+    fn calc_feature_table_body_length(
+        &self,
+        body_attributes: &[PointAttributeMember],
+        num_points: usize,
+    ) -> usize {
+        let offsets = self.feature_table_body_offsets(body_attributes, num_points);
+        match (offsets.last(), body_attributes.last()) {
+            (Some(offset), Some(attribute)) => offset + attribute.size() as usize * num_points,
+            _ => 0,
+        }
+    }
 
-            let blob_byte_size = attribute.size() as usize * self.cached_points.len();
-            let num_padding_bytes =
-                blob_byte_size.align_to(PNTS_SEMANTICS_MAX_ALIGNMENT) - blob_byte_size;
+    fn write_feature_table_body(
+        &mut self,
+        body_attributes: &[PointAttributeMember],
+        points: &dyn PointBuffer,
+    ) -> Result<()> {
+        let offsets = self.feature_table_body_offsets(body_attributes, points.len());
+        let writer = self
+            .writer
+            .as_mut()
+            .expect("PntsWriter has already been finalized");
+
+        let mut position_in_body = 0;
+        for (attribute, offset) in body_attributes.iter().zip(offsets) {
+            let num_padding_bytes = offset - position_in_body;
             if num_padding_bytes != 0 {
                 let padding_bytes = vec![0; num_padding_bytes];
-                self.writer
+                writer
                     .write_all(padding_bytes.as_slice())
                     .context("Error while writing padding bytes")?;
             }
+
+            // Only one attribute is converted at a time, so at most one attribute of `points` is copied at once
+            let attribute_bytes =
+                attribute_data(points, &attribute.into(), &self.attribute_converters);
+            writer
+                .write_all(&attribute_bytes)
+                .context("Error while writing attribute data")?;
+            position_in_body = offset + attribute_bytes.len();
         }
 
         // Write padding bytes to ensure we are at an 8-byte boundary!
-        let current_write_position = self.writer.seek(SeekFrom::Current(0))?;
+        let current_write_position = writer.seek(SeekFrom::Current(0))?;
         let next_8_byte_boundary = current_write_position.align_to(8);
         let num_padding_bytes = next_8_byte_boundary - current_write_position;
         if num_padding_bytes > 0 {
-            self.writer.write(&vec![0; num_padding_bytes as usize])?;
+            writer.write(&vec![0; num_padding_bytes as usize])?;
         }
 
         Ok(())
@@ -334,51 +680,52 @@ impl<W: Write + Seek> PointWriter for PntsWriter<W> {
         if points.point_layout() == self.cached_points.point_layout() {
             self.cached_points.push(points);
         } else {
-            // Have to convert data
-            // TODO Depending on the memory layout of `points`, there might be faster ways to push the data than
-            // using the generic functions from the `PointBuffer` trait. Revise this method once we have a good API
-            // for pushing into a buffer with a different PointLayout!
+            // Have to convert data. Each attribute is copied in one call for the whole range of points and written in
+            // one block into the cache, so the conversion loop only has to run over contiguous memory
+            let num_new_points = points.len();
             let base_point_index = self.cached_points.len();
-            self.cached_points
-                .resize(self.cached_points.len() + points.len());
-            for (attribute_name, maybe_converter) in self.attribute_converters.iter() {
-                if let Some(attr) = points.point_layout().get_attribute_by_name(attribute_name) {
-                    let attribute_def: PointAttributeDefinition = attr.into();
-                    let mut buf = vec![0; attribute_def.size() as usize];
-                    let dst_attribute = self
-                        .cached_points
-                        .point_layout()
-                        .get_attribute_by_name(attribute_name)
-                        .unwrap()
-                        .clone();
-                    let dst_attribute_size = dst_attribute.size() as usize;
-                    let dst_attribute_def: PointAttributeDefinition = dst_attribute.into();
-                    let mut converted_buf = vec![0; dst_attribute_size];
-                    for point_index in 0..points.len() {
-                        points.get_raw_attribute(point_index, &attribute_def, buf.as_mut_slice());
-                        if let Some(conversion_fn) = maybe_converter {
-                            unsafe {
-                                conversion_fn(buf.as_slice(), converted_buf.as_mut_slice());
-                            }
-                            self.cached_points.set_raw_attribute(
-                                base_point_index + point_index,
-                                &dst_attribute_def,
-                                converted_buf.as_slice(),
-                            );
-                        } else {
-                            self.cached_points.set_raw_attribute(
-                                base_point_index + point_index,
-                                &dst_attribute_def,
-                                buf.as_slice(),
-                            )
-                        }
-                    }
-                }
+            self.cached_points.resize(base_point_index + num_new_points);
+            for attribute in self.default_layout.attributes() {
+                let attribute_def: PointAttributeDefinition = attribute.into();
+                let dst_bytes = self.cached_points.get_raw_attribute_range_mut(
+                    base_point_index..(base_point_index + num_new_points),
+                    &attribute_def,
+                );
+                copy_attribute_data(
+                    points,
+                    &attribute_def,
+                    &self.attribute_converters,
+                    dst_bytes,
+                );
             }
         }
         Ok(())
     }
 
+    fn write_layout_compatible(&mut self, points: &dyn PointBuffer) -> Result<()> {
+        if points.point_layout() != self.cached_points.point_layout() {
+            bail!(
+                "PointLayout of buffer ({}) does not match the default PointLayout of the PntsWriter ({})",
+                points.point_layout(),
+                self.cached_points.point_layout()
+            );
+        }
+
+        // The cache stores each attribute contiguously, so each attribute is copied in one block without any conversion
+        let num_new_points = points.len();
+        let base_point_index = self.cached_points.len();
+        self.cached_points.resize(base_point_index + num_new_points);
+        for attribute in self.default_layout.attributes() {
+            let attribute_def: PointAttributeDefinition = attribute.into();
+            let dst_bytes = self.cached_points.get_raw_attribute_range_mut(
+                base_point_index..(base_point_index + num_new_points),
+                &attribute_def,
+            );
+            points.get_raw_attribute_range(0..num_new_points, &attribute_def, dst_bytes);
+        }
+        Ok(())
+    }
+
     fn flush(&mut self) -> Result<()> {
         if !self.requires_flush {
             return Ok(());
@@ -393,7 +740,14 @@ impl<W: Write + Seek> PointWriter for PntsWriter<W> {
 
 impl<W: Write + Seek> Drop for PntsWriter<W> {
     fn drop(&mut self) {
-        self.flush().expect("Error while flushing PntsWriter")
+        // Panicking while the thread is already unwinding would abort the process, so errors are ignored here. Use
+        // `PntsWriter::finalize` to handle errors during writing
+        if !self.requires_flush {
+            return;
+        }
+        // Clear the flag first, so that a failed write is never attempted a second time
+        self.requires_flush = false;
+        let _ = self.write_cached_points();
     }
 }
 
@@ -405,8 +759,11 @@ mod tests {
 
     use super::*;
     use pasture_core::{
-        containers::PointBufferExt,
-        layout::PointType,
+        containers::{InterleavedVecPointStorage, PointBufferExt},
+        layout::{
+            attributes::{CLASSIFICATION, INTENSITY},
+            PointType,
+        },
         nalgebra::{Vector3, Vector4},
     };
     use pasture_derive::PointType;
@@ -435,6 +792,131 @@ mod tests {
         intensity: u16,
     }
 
+    /// Writer that fails on every write, to test error handling
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "FailingWriter can't write",
+            ))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for FailingWriter {
+        fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn test_finalize_returns_writer() -> Result<()> {
+        let mut test_point_buffer = PerAttributeVecPointStorage::new(PntsDefaultPoint::layout());
+        test_point_buffer.push_point(PntsDefaultPoint {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            color: Vector3::new(10, 20, 30),
+            color_rgba: Vector4::new(11, 21, 31, 41),
+            normal: Vector3::new(0.1, 0.2, 0.3),
+        });
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsDefaultPoint::layout(),
+        );
+        writer.write(&test_point_buffer)?;
+        let mut cursor = writer.finalize()?;
+
+        cursor.seek(SeekFrom::Start(0))?;
+        let mut reader = PntsReader::from_read(&mut cursor)?;
+        let read_points = reader.read(1)?;
+        assert_eq!(
+            test_point_buffer.get_point::<PntsDefaultPoint>(0),
+            read_points.get_point::<PntsDefaultPoint>(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_finalize_surfaces_write_error() {
+        let mut test_point_buffer = PerAttributeVecPointStorage::new(PntsDefaultPoint::layout());
+        test_point_buffer.push_point(PntsDefaultPoint {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            color: Vector3::new(10, 20, 30),
+            color_rgba: Vector4::new(11, 21, 31, 41),
+            normal: Vector3::new(0.1, 0.2, 0.3),
+        });
+
+        let mut writer =
+            PntsWriter::from_write_and_layout(FailingWriter, PntsDefaultPoint::layout());
+        writer
+            .write(&test_point_buffer)
+            .expect("Writing only caches the points and should not fail");
+        assert!(writer.finalize().is_err());
+    }
+
+    #[test]
+    fn test_drop_does_not_panic_on_write_error() {
+        let writer = PntsWriter::from_write_and_layout(FailingWriter, PntsDefaultPoint::layout());
+        drop(writer);
+    }
+
+    #[test]
+    fn test_cached_point_count_and_flush_state() -> Result<()> {
+        let mut test_point_buffer = PerAttributeVecPointStorage::new(PntsDefaultPoint::layout());
+        test_point_buffer.push_point(PntsDefaultPoint {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            color: Vector3::new(10, 20, 30),
+            color_rgba: Vector4::new(11, 21, 31, 41),
+            normal: Vector3::new(0.1, 0.2, 0.3),
+        });
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsDefaultPoint::layout(),
+        );
+        assert_eq!(0, writer.cached_point_count());
+        assert!(!writer.is_flushed());
+
+        writer.write(&test_point_buffer)?;
+        assert_eq!(1, writer.cached_point_count());
+        writer.write(&test_point_buffer)?;
+        assert_eq!(2, writer.cached_point_count());
+        assert!(!writer.is_flushed());
+
+        writer.flush()?;
+        assert!(writer.is_flushed());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_supported_attributes() {
+        let layout = PointLayout::from_attributes(&[
+            POSITION_3D,
+            INTENSITY,
+            COLOR_RGB,
+            CLASSIFICATION,
+            NORMAL,
+        ]);
+
+        let (kept, dropped) = PntsWriter::<Cursor<Vec<u8>>>::supported_attributes(&layout);
+        assert_eq!(
+            vec![
+                POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+                COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8),
+                NORMAL,
+            ],
+            kept
+        );
+        assert_eq!(vec![INTENSITY, CLASSIFICATION], dropped);
+    }
+
     #[test]
     fn test_write_pnts_default_layout() -> Result<()> {
         let mut cursor = Cursor::new(Vec::<u8>::new());
@@ -481,13 +963,109 @@ mod tests {
             assert_eq!(read_points.point_layout(), test_point_buffer.point_layout());
             assert_eq!(read_points.len(), test_point_buffer.len());
 
-            for point_idx in 0..test_point_buffer.len() {
-                let expected_point = test_data[point_idx];
-                let actual_point = read_points.get_point::<PntsDefaultPoint>(point_idx);
-                assert_eq!(expected_point, actual_point);
-            }
+            assert_eq!(
+                test_data,
+                read_points
+                    .iter_point::<PntsDefaultPoint>()
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_pnts_attribute_alignment() -> Result<()> {
+        // Three points, so that the RGB colors end at an offset that is not a multiple of 4 or 8
+        let test_data = (1..=3)
+            .map(|idx| PntsDefaultPoint {
+                position: Vector3::new(idx as f32, 2.0 * idx as f32, 3.0 * idx as f32),
+                color: Vector3::new(10 * idx, 20 * idx, 30 * idx),
+                color_rgba: Vector4::new(11 * idx, 21 * idx, 31 * idx, 41 * idx),
+                normal: Vector3::new(0.1 * idx as f32, 0.2 * idx as f32, 0.3 * idx as f32),
+            })
+            .collect::<Vec<_>>();
+        let mut test_point_buffer = PerAttributeVecPointStorage::new(PntsDefaultPoint::layout());
+        test_point_buffer.push_points(test_data.as_slice());
+
+        let mut file_sizes = vec![];
+        for &alignment in [
+            PntsAttributeAlignment::MaxAlignment,
+            PntsAttributeAlignment::ComponentType,
+        ]
+        .iter()
+        {
+            let mut writer = PntsWriter::from_write_and_layout(
+                Cursor::new(Vec::<u8>::new()),
+                PntsDefaultPoint::layout(),
+            );
+            writer.set_attribute_alignment(alignment);
+            writer.write(&test_point_buffer)?;
+            let mut cursor = writer.finalize()?;
+            file_sizes.push(cursor.get_ref().len());
+
+            cursor.seek(SeekFrom::Start(0))?;
+            let mut reader = PntsReader::from_read(&mut cursor)?;
+            let read_points = reader.read(test_point_buffer.len())?;
+            assert_eq!(read_points.point_layout(), test_point_buffer.point_layout());
+            assert_eq!(test_data.len(), read_points.len());
+            assert_eq!(
+                test_data,
+                read_points
+                    .iter_point::<PntsDefaultPoint>()
+                    .collect::<Vec<_>>()
+            );
         }
 
+        assert!(file_sizes[1] < file_sizes[0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_layout_compatible() -> Result<()> {
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsCustomLayout::layout(),
+        );
+
+        // Only buffers in the default layout of the writer can be written without conversion
+        let mut custom_points = PerAttributeVecPointStorage::new(PntsCustomLayout::layout());
+        custom_points.push_point(PntsCustomLayout {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            color: Vector3::new(1, 2, 3),
+            intensity: 4,
+        });
+        assert!(writer.write_layout_compatible(&custom_points).is_err());
+
+        let position_attribute = POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32);
+        let color_attribute = COLOR_RGB.with_custom_datatype(PointAttributeDataType::Vec3u8);
+        let positions = vec![Vector3::new(1.0_f32, 2.0, 3.0), Vector3::new(4.0, 5.0, 6.0)];
+        let colors = vec![Vector3::new(10_u8, 20, 30), Vector3::new(40, 50, 60)];
+        let mut points =
+            PerAttributeVecPointStorage::new(writer.get_default_point_layout().clone());
+        let mut pusher = points.begin_push_attributes();
+        pusher.push_attribute_range(&position_attribute, positions.as_slice());
+        pusher.push_attribute_range(&color_attribute, colors.as_slice());
+        pusher.done();
+
+        writer.write_layout_compatible(&points)?;
+        writer.write_layout_compatible(&points)?;
+        let mut cursor = writer.finalize()?;
+
+        cursor.seek(SeekFrom::Start(0))?;
+        let mut reader = PntsReader::from_read(&mut cursor)?;
+        let read_points = reader.read(4)?;
+        assert_eq!(4, read_points.len());
+        let read_positions = read_points
+            .iter_attribute::<Vector3<f32>>(&position_attribute)
+            .collect::<Vec<_>>();
+        assert_eq!(positions.repeat(2), read_positions);
+        let read_colors = read_points
+            .iter_attribute::<Vector3<u8>>(&color_attribute)
+            .collect::<Vec<_>>();
+        assert_eq!(colors.repeat(2), read_colors);
+
         Ok(())
     }
 
@@ -579,4 +1157,202 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_write_pnts_custom_layout_interleaved_matches_per_attribute() -> Result<()> {
+        let test_data = vec![
+            PntsCustomLayout {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                color: Vector3::new(1 << 8, 2 << 8, 3 << 8),
+                intensity: 10_000,
+            },
+            PntsCustomLayout {
+                position: Vector3::new(2.0, 4.0, 6.0),
+                color: Vector3::new(2 << 8, 4 << 8, 6 << 8),
+                intensity: 20_000,
+            },
+        ];
+        let mut per_attribute_points = PerAttributeVecPointStorage::new(PntsCustomLayout::layout());
+        per_attribute_points.push_points(test_data.as_slice());
+        let interleaved_points = InterleavedVecPointStorage::from(test_data.as_slice());
+
+        let write_points = |points: &dyn PointBuffer| -> Result<Vec<u8>> {
+            let mut writer = PntsWriter::from_write_and_layout(
+                Cursor::new(Vec::<u8>::new()),
+                PntsCustomLayout::layout(),
+            );
+            writer.write(points)?;
+            Ok(writer.finalize()?.into_inner())
+        };
+        assert_eq!(
+            write_points(&per_attribute_points)?,
+            write_points(&interleaved_points)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_all_points_matches_cached_write() -> Result<()> {
+        let test_data = vec![
+            PntsCustomLayout {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                color: Vector3::new(1 << 8, 2 << 8, 3 << 8),
+                intensity: 10_000,
+            },
+            PntsCustomLayout {
+                position: Vector3::new(2.0, 4.0, 6.0),
+                color: Vector3::new(2 << 8, 4 << 8, 6 << 8),
+                intensity: 20_000,
+            },
+        ];
+        let interleaved_points = InterleavedVecPointStorage::from(test_data.as_slice());
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsCustomLayout::layout(),
+        );
+        writer.write(&interleaved_points)?;
+        let cached_bytes = writer.finalize()?.into_inner();
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsCustomLayout::layout(),
+        );
+        writer.write_all_points(&interleaved_points)?;
+        assert!(writer.is_flushed());
+        assert_eq!(0, writer.cached_point_count());
+        assert_eq!(cached_bytes, writer.finalize()?.into_inner());
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsCustomLayout::layout(),
+        );
+        writer.write(&interleaved_points)?;
+        assert!(writer.write_all_points(&interleaved_points).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_empty_pnts() -> Result<()> {
+        let empty_buffer = PerAttributeVecPointStorage::new(PntsDefaultPoint::layout());
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsDefaultPoint::layout(),
+        );
+        writer.write(&empty_buffer)?;
+        let cached_bytes = writer.finalize()?.into_inner();
+
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            PntsDefaultPoint::layout(),
+        );
+        writer.write_all_points(&empty_buffer)?;
+        assert_eq!(cached_bytes, writer.finalize()?.into_inner());
+
+        let header = PntsReader::read_header_only(Cursor::new(&cached_bytes))?;
+        let byte_length = header.byte_length;
+        let feature_table_binary_byte_length = header.feature_table_binary_byte_length;
+        assert_eq!(cached_bytes.len(), byte_length as usize);
+        assert_eq!(0, feature_table_binary_byte_length);
+
+        let mut reader = PntsReader::from_read(Cursor::new(cached_bytes))?;
+        assert_eq!(Some(0), reader.get_metadata().number_of_points());
+        let read_points = reader.read(0)?;
+        assert_eq!(0, read_points.len());
+        assert_eq!(empty_buffer.point_layout(), read_points.point_layout());
+
+        Ok(())
+    }
+
+    fn write_with_constant_color_mode(
+        points: &PerAttributeVecPointStorage,
+        constant_color_mode: PntsConstantColorMode,
+    ) -> Result<Vec<u8>> {
+        let mut writer = PntsWriter::from_write_and_layout(
+            Cursor::new(Vec::<u8>::new()),
+            points.point_layout().clone(),
+        );
+        writer.set_constant_color_mode(constant_color_mode);
+        writer.write(points)?;
+        Ok(writer.finalize()?.into_inner())
+    }
+
+    #[test]
+    fn test_write_pnts_constant_color() -> Result<()> {
+        let color = Vector4::new(10, 20, 30, 40);
+        let test_data = vec![
+            PntsDefaultPoint {
+                position: Vector3::new(1.0, 2.0, 3.0),
+                color: Vector3::new(10, 20, 30),
+                color_rgba: color,
+                normal: Vector3::new(0.1, 0.2, 0.3),
+            },
+            PntsDefaultPoint {
+                position: Vector3::new(2.0, 4.0, 6.0),
+                color: Vector3::new(10, 20, 30),
+                color_rgba: color,
+                normal: Vector3::new(0.2, 0.4, 0.6),
+            },
+        ];
+        let mut test_point_buffer = PerAttributeVecPointStorage::new(PntsDefaultPoint::layout());
+        test_point_buffer.push_points(test_data.as_slice());
+
+        // RGB and RGBA colors disagree in their alpha value, so no constant color is detected
+        let per_point_bytes =
+            write_with_constant_color_mode(&test_point_buffer, PntsConstantColorMode::Detect)?;
+        let per_point_header = PntsReader::read_header_only(Cursor::new(&per_point_bytes))?;
+
+        let mut uniform_buffer = PerAttributeVecPointStorage::new(PointLayout::from_attributes(&[
+            POSITION_3D.with_custom_datatype(PointAttributeDataType::Vec3f32),
+            COLOR_RGBA,
+        ]));
+        uniform_buffer.resize(2);
+        for point_index in 0..2 {
+            uniform_buffer.set_raw_attribute(point_index, &COLOR_RGBA, color.as_slice());
+        }
+        let constant_bytes =
+            write_with_constant_color_mode(&uniform_buffer, PntsConstantColorMode::Detect)?;
+        let constant_header = PntsReader::read_header_only(Cursor::new(&constant_bytes))?;
+
+        // Only the positions (2 * 12 bytes, padded to 8 bytes) are in the FeatureTable body
+        let constant_binary_byte_length = constant_header.feature_table_binary_byte_length;
+        let per_point_binary_byte_length = per_point_header.feature_table_binary_byte_length;
+        assert_eq!(24, constant_binary_byte_length);
+        assert!(per_point_binary_byte_length > constant_binary_byte_length);
+
+        let mut reader = PntsReader::from_read(Cursor::new(constant_bytes))?;
+        let constant_rgba = reader
+            .get_metadata()
+            .get_named_field(semantics::CONSTANT_RGBA)
+            .and_then(|field| field.downcast_ref::<Vector4<u8>>().copied());
+        assert_eq!(Some(color), constant_rgba);
+        assert!(reader.get_default_point_layout().has_attribute(&COLOR_RGBA));
+        let read_points = reader.read(2)?;
+        for point_index in 0..2 {
+            assert_eq!(
+                color,
+                read_points.get_attribute::<Vector4<u8>>(&COLOR_RGBA, point_index)
+            );
+        }
+
+        // Forcing a constant color discards per-point colors
+        let forced_color = Vector4::new(1, 2, 3, 4);
+        let forced_bytes = write_with_constant_color_mode(
+            &test_point_buffer,
+            PntsConstantColorMode::Force(forced_color),
+        )?;
+        let mut reader = PntsReader::from_read(Cursor::new(forced_bytes))?;
+        assert!(!reader
+            .get_default_point_layout()
+            .has_attribute_with_name(COLOR_RGB.name()));
+        let read_points = reader.read(2)?;
+        assert_eq!(
+            forced_color,
+            read_points.get_attribute::<Vector4<u8>>(&COLOR_RGBA, 1)
+        );
+
+        Ok(())
+    }
 }